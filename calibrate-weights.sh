@@ -0,0 +1,9 @@
+#!/bin/bash
+
+set -ex
+
+# Measures WASM instructions, fee table entries and native functions on this machine and writes
+# a report together with proposed `weights.rs`, `fee_table.rs` and `native_function_base_costs.csv`
+# diffs to `target/weights-calibration` (or `$WEIGHTS_CALIBRATION_OUTPUT_DIR`).
+# Should be run on the reference machine, see `fee_table.rs`.
+cargo bench --package radix-engine-tests --bench weights_calibration --features calibration
@@ -23,7 +23,7 @@ use rand_chacha::rand_core::SeedableRng;
 use rand_chacha::ChaCha8Rng;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
-use transaction::model::PreAllocatedAddress;
+use transaction::model::{PreAllocatedAddress, SubintentParams};
 
 struct TestCallFrameData;
 
@@ -60,6 +60,7 @@ impl KernelCallbackObject for TestCallbackObject {
         _: &Vec<PreAllocatedAddress>,
         _: &IndexSet<Reference>,
         _: &IndexMap<Hash, Vec<u8>>,
+        _: &SubintentParams,
     ) -> Result<Vec<u8>, RuntimeError>
     where
         Y: KernelApi<Self>,
@@ -205,6 +205,12 @@ pub const CONSENSUS_MANAGER: ComponentAddress = ComponentAddress::new_or_panic([
     181, 74, 106, 134, 49, 140, 99, 24, 198,
 ]);
 
+/// The scheduler native component - in charge of deferred method calls executed by the protocol.
+pub const SCHEDULER: ComponentAddress = ComponentAddress::new_or_panic([
+    192, 86, 99, 24, 198, 49, 140, 104, 98, 237, 248, 204, 99, 24, 198, 49, 140, 247, 214, 145, 85,
+    31, 168, 188, 230, 49, 140, 99, 24, 198,
+]);
+
 //=========================================================================
 // SYSTEM SINGLETON COMPONENTS - SCRYPTO
 //=========================================================================
@@ -392,6 +398,11 @@ mod tests {
             EntityType::GlobalTransactionTracker,
             "transactiontracker_rdx1stxxxxxxxxxxtxtrakxxxxxxxxx006844685494xxxxxxxxxtxtrak",
         );
+        check_address(
+            SCHEDULER.as_ref(),
+            EntityType::GlobalGenericComponent,
+            "component_rdx1cptxxxxxxxxxschdlrxxxxxxxxx004532506308xxxxxxxxxschdlr",
+        );
     }
 
     fn check_address(address_bytes: &[u8], entity_type: EntityType, address_string: &str) {
@@ -58,6 +58,9 @@ pub const MAX_NUMBER_OF_EVENTS: usize = 256;
 /// The max number of logs
 pub const MAX_NUMBER_OF_LOGS: usize = 256;
 
+/// The max number of entries which can be scanned from a key value store in a single call
+pub const MAX_KEY_VALUE_STORE_SCAN_LIMIT: u32 = 256;
+
 /// The max SBOR size of metadata key
 pub const MAX_METADATA_KEY_STRING_LEN: usize = 100;
 
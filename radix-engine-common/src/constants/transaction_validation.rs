@@ -4,6 +4,12 @@ pub const MAX_NUMBER_OF_INTENT_SIGNATURES: usize = 16;
 
 pub const MAX_NUMBER_OF_BLOBS: usize = 64;
 
+/// The max number of subintents in a transaction, excluding the root intent
+pub const MAX_NUMBER_OF_SUBINTENTS: usize = 32;
+
+/// The max depth of the intent tree, where the root intent has depth 0
+pub const MAX_SUBINTENT_DEPTH: usize = 4;
+
 /// The minimum value of tip percentage
 ///
 /// 100 means 100%
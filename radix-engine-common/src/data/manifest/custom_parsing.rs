@@ -0,0 +1,96 @@
+use super::converter::*;
+use super::model::*;
+use crate::internal_prelude::*;
+
+/// The context needed to parse the custom values output by the display and serde representations.
+///
+/// Named buckets, proofs, address reservations and addresses can only be parsed from their ids,
+/// ie if they were output without names.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ManifestValueParseContext<'a> {
+    /// If set, addresses are checked to be on the decoder's network.
+    pub address_bech32_decoder: Option<&'a AddressBech32Decoder>,
+}
+
+impl<'a> ManifestValueParseContext<'a> {
+    pub fn no_context() -> Self {
+        Self {
+            address_bech32_decoder: None,
+        }
+    }
+
+    pub fn with_optional_bech32(address_bech32_decoder: Option<&'a AddressBech32Decoder>) -> Self {
+        Self {
+            address_bech32_decoder,
+        }
+    }
+}
+
+impl ParseableCustomExtension for ManifestCustomExtension {
+    type CustomValue = ManifestCustomValue;
+    type CustomParseContext<'a> = ManifestValueParseContext<'a>;
+
+    fn parse_custom_value_content(
+        context: &Self::CustomParseContext<'_>,
+        custom_value_kind: Self::CustomValueKind,
+        value: &str,
+    ) -> Result<Self::CustomValue, String> {
+        let parse_id = |value: &str| {
+            u32::from_str(value).map_err(|_| format!("Expected a numeric id: {}", value))
+        };
+        let custom_value = match custom_value_kind {
+            ManifestCustomValueKind::Address => {
+                // Static addresses are output as hex or Bech32m, and named addresses as ids
+                let address = if let Ok(address_id) = u32::from_str(value) {
+                    ManifestAddress::Named(address_id)
+                } else if let Ok(node_id) = node_id_from_hex(value) {
+                    ManifestAddress::Static(node_id)
+                } else {
+                    ManifestAddress::Static(node_id_from_bech32(
+                        context.address_bech32_decoder,
+                        value,
+                    )?)
+                };
+                ManifestCustomValue::Address(address)
+            }
+            ManifestCustomValueKind::Bucket => {
+                ManifestCustomValue::Bucket(ManifestBucket(parse_id(value)?))
+            }
+            ManifestCustomValueKind::Proof => {
+                ManifestCustomValue::Proof(ManifestProof(parse_id(value)?))
+            }
+            ManifestCustomValueKind::AddressReservation => ManifestCustomValue::AddressReservation(
+                ManifestAddressReservation(parse_id(value)?),
+            ),
+            ManifestCustomValueKind::Expression => {
+                let expression = match value {
+                    "ENTIRE_WORKTOP" => ManifestExpression::EntireWorktop,
+                    "ENTIRE_AUTH_ZONE" => ManifestExpression::EntireAuthZone,
+                    _ => return Err(format!("Unknown expression: {}", value)),
+                };
+                ManifestCustomValue::Expression(expression)
+            }
+            ManifestCustomValueKind::Blob => {
+                let hash = hex::decode(value)
+                    .ok()
+                    .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                    .ok_or_else(|| format!("Invalid blob hash: {}", value))?;
+                ManifestCustomValue::Blob(ManifestBlobRef(hash))
+            }
+            ManifestCustomValueKind::Decimal => ManifestCustomValue::Decimal(from_decimal(
+                &Decimal::from_str(value).map_err(|error| format!("{:?}", error))?,
+            )),
+            ManifestCustomValueKind::PreciseDecimal => {
+                ManifestCustomValue::PreciseDecimal(from_precise_decimal(
+                    &PreciseDecimal::from_str(value).map_err(|error| format!("{:?}", error))?,
+                ))
+            }
+            ManifestCustomValueKind::NonFungibleLocalId => {
+                ManifestCustomValue::NonFungibleLocalId(from_non_fungible_local_id(
+                    NonFungibleLocalId::from_str(value).map_err(|error| format!("{:?}", error))?,
+                ))
+            }
+        };
+        Ok(custom_value)
+    }
+}
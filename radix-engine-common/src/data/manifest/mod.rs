@@ -5,6 +5,7 @@ pub mod model;
 // Modules which should appear part of `manifest`
 mod custom_extension;
 mod custom_formatting;
+mod custom_parsing;
 mod custom_payload_wrappers;
 #[cfg(feature = "serde")]
 mod custom_serde;
@@ -17,6 +18,7 @@ mod display_context;
 
 pub use custom_extension::*;
 pub use custom_formatting::*;
+pub use custom_parsing::*;
 pub use custom_payload_wrappers::*;
 #[cfg(feature = "serde")]
 pub use custom_serde::*;
@@ -36,6 +38,7 @@ pub mod prelude {
     // Private modules to include in prelude
     pub use super::custom_extension::*;
     pub use super::custom_formatting::*;
+    pub use super::custom_parsing::*;
     pub use super::custom_payload_wrappers::*;
     #[cfg(feature = "serde")]
     pub use super::custom_serde::*;
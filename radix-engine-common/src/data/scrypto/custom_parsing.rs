@@ -0,0 +1,305 @@
+use crate::internal_prelude::*;
+
+/// The context needed to parse the custom values output by the display and serde representations.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ScryptoValueParseContext<'a> {
+    /// If set, addresses are checked to be on the decoder's network.
+    pub address_bech32_decoder: Option<&'a AddressBech32Decoder>,
+}
+
+impl<'a> ScryptoValueParseContext<'a> {
+    pub fn no_context() -> Self {
+        Self {
+            address_bech32_decoder: None,
+        }
+    }
+
+    pub fn with_optional_bech32(address_bech32_decoder: Option<&'a AddressBech32Decoder>) -> Self {
+        Self {
+            address_bech32_decoder,
+        }
+    }
+}
+
+impl ParseableCustomExtension for ScryptoCustomExtension {
+    type CustomValue = ScryptoCustomValue;
+    type CustomParseContext<'a> = ScryptoValueParseContext<'a>;
+
+    fn parse_custom_value_content(
+        context: &Self::CustomParseContext<'_>,
+        custom_value_kind: Self::CustomValueKind,
+        value: &str,
+    ) -> Result<Self::CustomValue, String> {
+        let custom_value = match custom_value_kind {
+            ScryptoCustomValueKind::Reference => ScryptoCustomValue::Reference(Reference(
+                parse_node_id(context.address_bech32_decoder, value)?,
+            )),
+            ScryptoCustomValueKind::Own => {
+                ScryptoCustomValue::Own(Own(parse_node_id(context.address_bech32_decoder, value)?))
+            }
+            ScryptoCustomValueKind::Decimal => ScryptoCustomValue::Decimal(
+                Decimal::from_str(value).map_err(|error| format!("{:?}", error))?,
+            ),
+            ScryptoCustomValueKind::PreciseDecimal => ScryptoCustomValue::PreciseDecimal(
+                PreciseDecimal::from_str(value).map_err(|error| format!("{:?}", error))?,
+            ),
+            ScryptoCustomValueKind::NonFungibleLocalId => ScryptoCustomValue::NonFungibleLocalId(
+                NonFungibleLocalId::from_str(value).map_err(|error| format!("{:?}", error))?,
+            ),
+        };
+        Ok(custom_value)
+    }
+}
+
+/// Parses a node id, as output by the display and serde representations: either a Bech32m address, or `NodeId(<hex>)`
+/// for node ids without an address.
+fn parse_node_id(
+    address_bech32_decoder: Option<&AddressBech32Decoder>,
+    value: &str,
+) -> Result<NodeId, String> {
+    match value
+        .strip_prefix("NodeId(")
+        .and_then(|value| value.strip_suffix(")"))
+    {
+        Some(hex) => node_id_from_hex(hex),
+        None => node_id_from_bech32(address_bech32_decoder, value),
+    }
+}
+
+pub(crate) fn node_id_from_hex(hex: &str) -> Result<NodeId, String> {
+    let bytes = hex::decode(hex).map_err(|_| format!("Invalid hex: {}", hex))?;
+    node_id_from_bytes(bytes)
+}
+
+/// Without a decoder, an address from any network is accepted.
+pub(crate) fn node_id_from_bech32(
+    address_bech32_decoder: Option<&AddressBech32Decoder>,
+    address: &str,
+) -> Result<NodeId, String> {
+    let (_, bytes) = match address_bech32_decoder {
+        Some(decoder) => decoder.validate_and_decode(address),
+        None => AddressBech32Decoder::validate_and_decode_ignore_hrp(address)
+            .map(|(_, entity_type, bytes)| (entity_type, bytes)),
+    }
+    .map_err(|error| format!("{:?}", error))?;
+    node_id_from_bytes(bytes)
+}
+
+fn node_id_from_bytes(bytes: Vec<u8>) -> Result<NodeId, String> {
+    <[u8; NodeId::LENGTH]>::try_from(bytes.as_slice())
+        .map(NodeId)
+        .map_err(|_| format!("Invalid node id length: {}", bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::test_addresses::*;
+    use crate::address::{AddressBech32Decoder, AddressBech32Encoder};
+    use crate::constants::*;
+    use crate::data::scrypto::model::*;
+    use crate::math::*;
+    use crate::network::NetworkDefinition;
+    use crate::types::*;
+
+    #[derive(ScryptoSbor)]
+    pub struct Sample {
+        pub resource: ResourceAddress,
+        pub vault: Own,
+        pub amounts: IndexMap<String, Decimal>,
+        pub precise_amount: PreciseDecimal,
+        pub ids: Vec<NonFungibleLocalId>,
+        pub limit: Option<u64>,
+    }
+
+    #[test]
+    fn test_rustlike_round_trip_with_schema() {
+        let encoder = AddressBech32Encoder::for_simulator();
+        let decoder = AddressBech32Decoder::for_simulator();
+        let (type_id, schema) =
+            generate_full_schema_from_single_type::<Sample, ScryptoCustomSchema>();
+        let value = Sample {
+            resource: FUNGIBLE_RESOURCE,
+            vault: Own(FUNGIBLE_RESOURCE_NODE_ID),
+            amounts: indexmap! {
+                "b".to_string() => Decimal::ONE,
+                "a".to_string() => Decimal::ONE.checked_div(100).unwrap(),
+            },
+            precise_amount: PreciseDecimal::ZERO,
+            ids: vec![
+                NonFungibleLocalId::integer(1),
+                NonFungibleLocalId::string("hello").unwrap(),
+            ],
+            limit: Some(u64::MAX),
+        };
+        let payload = scrypto_encode(&value).unwrap();
+
+        let text = ScryptoRawPayload::new_from_valid_slice(&payload).to_string(
+            ValueDisplayParameters::Annotated {
+                display_mode: DisplayMode::RustLike,
+                print_mode: PrintMode::MultiLine {
+                    indent_size: 4,
+                    base_indent: 8,
+                    first_line_indent: 0,
+                },
+                custom_context: ScryptoValueDisplayContext::with_optional_bech32(Some(&encoder)),
+                schema: schema.v1(),
+                type_id,
+                depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+            },
+        );
+        assert!(text.contains(&format!(
+            "resource: ResourceAddress(Reference(\"{FUNGIBLE_RESOURCE_SIM_ADDRESS}\")),"
+        )));
+
+        let parsed = parse_rustlike_payload(
+            &text,
+            &RustLikeParseParameters::<ScryptoCustomExtension>::Annotated {
+                custom_context: ScryptoValueParseContext::with_optional_bech32(Some(&decoder)),
+                schema: schema.v1(),
+                type_id,
+                depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+            },
+        )
+        .unwrap();
+        assert_eq!(parsed, payload, "Mismatching round trip of {}", text);
+    }
+
+    /// A placeholder for the engine's `Bucket`, which isn't available in this crate.
+    pub struct Bucket;
+
+    impl Describe<ScryptoCustomTypeKind> for Bucket {
+        const TYPE_ID: RustTypeId =
+            RustTypeId::WellKnown(well_known_scrypto_custom_types::OWN_BUCKET_TYPE);
+
+        fn type_data() -> TypeData<ScryptoCustomTypeKind, RustTypeId> {
+            well_known_scrypto_custom_types::own_bucket_type_data()
+        }
+    }
+
+    #[test]
+    fn test_rustlike_round_trip_of_named_custom_values() {
+        let encoder = AddressBech32Encoder::for_simulator();
+        let decoder = AddressBech32Decoder::for_simulator();
+        let (type_id, schema) = generate_full_schema_from_single_type::<
+            (
+                ComponentAddress,
+                PackageAddress,
+                GlobalAddress,
+                InternalAddress,
+                Bucket,
+            ),
+            ScryptoCustomSchema,
+        >();
+        let mut internal_node_id = [1u8; NodeId::LENGTH];
+        internal_node_id[0] = EntityType::InternalFungibleVault as u8;
+        let internal = InternalAddress::new_or_panic(internal_node_id);
+        let payload = scrypto_encode(&(
+            FAUCET,
+            ACCOUNT_PACKAGE,
+            GlobalAddress::try_from(XRD.into_node_id()).unwrap(),
+            internal,
+            Own(internal.into_node_id()),
+        ))
+        .unwrap();
+        let address = |node_id: &NodeId| encoder.encode(node_id.as_bytes()).unwrap();
+
+        for print_mode in [
+            PrintMode::SingleLine,
+            PrintMode::MultiLine {
+                indent_size: 4,
+                base_indent: 0,
+                first_line_indent: 0,
+            },
+        ] {
+            let text = ScryptoRawPayload::new_from_valid_slice(&payload).to_string(
+                ValueDisplayParameters::Annotated {
+                    display_mode: DisplayMode::RustLike,
+                    print_mode,
+                    custom_context: ScryptoValueDisplayContext::with_optional_bech32(Some(
+                        &encoder,
+                    )),
+                    schema: schema.v1(),
+                    type_id,
+                    depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+                },
+            );
+            for expected in [
+                format!(
+                    "ComponentAddress(Reference(\"{}\"))",
+                    address(FAUCET.as_node_id())
+                ),
+                format!(
+                    "PackageAddress(Reference(\"{}\"))",
+                    address(ACCOUNT_PACKAGE.as_node_id())
+                ),
+                format!(
+                    "GlobalAddress(Reference(\"{}\"))",
+                    address(XRD.as_node_id())
+                ),
+                format!(
+                    "InternalAddress(Reference(\"{}\"))",
+                    address(internal.as_node_id())
+                ),
+                format!("Bucket(Own(\"{}\"))", address(internal.as_node_id())),
+            ] {
+                assert!(text.contains(&expected), "{} not in {}", expected, text);
+            }
+            assert_eq!(
+                text.matches('(').count(),
+                text.matches(')').count(),
+                "Unbalanced parentheses in {}",
+                text
+            );
+
+            let parsed = parse_rustlike_payload(
+                &text,
+                &RustLikeParseParameters::<ScryptoCustomExtension>::Annotated {
+                    custom_context: ScryptoValueParseContext::with_optional_bech32(Some(&decoder)),
+                    schema: schema.v1(),
+                    type_id,
+                    depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+                },
+            )
+            .unwrap();
+            assert_eq!(parsed, payload, "Mismatching round trip of {}", text);
+        }
+    }
+
+    #[test]
+    fn test_rustlike_parse_without_schema() {
+        let text = format!("Tuple(Reference(\"{FUNGIBLE_RESOURCE_SIM_ADDRESS}\"), Own(\"NodeId({})\"), Decimal(\"0.01\"), NonFungibleLocalId(\"#123#\"), [1u32, 2u32])", hex::encode(FUNGIBLE_RESOURCE_NODE_ID.as_bytes()));
+        let value = parse_rustlike_value(
+            &text,
+            &RustLikeParseParameters::<ScryptoCustomExtension>::Schemaless {
+                custom_context: ScryptoValueParseContext::no_context(),
+                depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            scrypto_encode(&value).unwrap(),
+            scrypto_encode(&(
+                Reference(FUNGIBLE_RESOURCE_NODE_ID),
+                Own(FUNGIBLE_RESOURCE_NODE_ID),
+                Decimal::ONE.checked_div(100).unwrap(),
+                NonFungibleLocalId::integer(123),
+                vec![1u32, 2u32],
+            ))
+            .unwrap()
+        );
+
+        let decoder = AddressBech32Decoder::new(&NetworkDefinition::mainnet());
+        let result = parse_rustlike_value(
+            &text,
+            &RustLikeParseParameters::<ScryptoCustomExtension>::Schemaless {
+                custom_context: ScryptoValueParseContext::with_optional_bech32(Some(&decoder)),
+                depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+            },
+        );
+        assert!(matches!(
+            result,
+            Err(RustLikeParseError::InvalidCustomValue { .. })
+        ));
+    }
+}
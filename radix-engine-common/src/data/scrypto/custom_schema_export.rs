@@ -0,0 +1,161 @@
+use crate::internal_prelude::*;
+
+impl ExportableCustomExtension for ScryptoCustomExtension {
+    fn custom_string_format(
+        schema: &Schema<Self::CustomSchema>,
+        type_id: LocalTypeId,
+    ) -> Option<CustomStringFormat> {
+        let TypeKind::Custom(custom_type_kind) = schema.resolve_type_kind(type_id)? else {
+            return None;
+        };
+        let validation = match schema.resolve_type_validation(type_id) {
+            Some(TypeValidation::Custom(validation)) => Some(validation),
+            _ => None,
+        };
+        let (format, pattern) = match (custom_type_kind, validation) {
+            (ScryptoCustomTypeKind::Decimal, _) => ("decimal", decimal_pattern(Decimal::SCALE)),
+            (ScryptoCustomTypeKind::PreciseDecimal, _) => {
+                ("precise_decimal", decimal_pattern(PreciseDecimal::SCALE))
+            }
+            (ScryptoCustomTypeKind::NonFungibleLocalId, _) => {
+                ("non_fungible_local_id", non_fungible_local_id_pattern())
+            }
+            (
+                ScryptoCustomTypeKind::Reference,
+                Some(ScryptoCustomTypeValidation::Reference(validation)),
+            ) => match validation {
+                ReferenceValidation::IsGlobal | ReferenceValidation::IsGlobalTyped(..) => {
+                    ("global_address", address_pattern(EntityType::is_global))
+                }
+                ReferenceValidation::IsGlobalPackage => (
+                    "package_address",
+                    address_pattern(EntityType::is_global_package),
+                ),
+                ReferenceValidation::IsGlobalComponent => (
+                    "component_address",
+                    address_pattern(EntityType::is_global_component),
+                ),
+                ReferenceValidation::IsGlobalResourceManager => (
+                    "resource_address",
+                    address_pattern(EntityType::is_global_resource_manager),
+                ),
+                ReferenceValidation::IsInternal | ReferenceValidation::IsInternalTyped(..) => {
+                    ("internal_address", address_pattern(EntityType::is_internal))
+                }
+            },
+            (ScryptoCustomTypeKind::Reference, _) => ("reference", address_pattern(|_| true)),
+            (ScryptoCustomTypeKind::Own, Some(ScryptoCustomTypeValidation::Own(validation))) => {
+                match validation {
+                    OwnValidation::IsBucket => ("bucket", address_pattern(EntityType::is_internal)),
+                    OwnValidation::IsProof => ("proof", address_pattern(EntityType::is_internal)),
+                    OwnValidation::IsVault => {
+                        ("vault", address_pattern(EntityType::is_internal_vault))
+                    }
+                    OwnValidation::IsKeyValueStore => (
+                        "key_value_store",
+                        address_pattern(EntityType::is_internal_kv_store),
+                    ),
+                    OwnValidation::IsGlobalAddressReservation => (
+                        "global_address_reservation",
+                        address_pattern(EntityType::is_internal),
+                    ),
+                    OwnValidation::IsTypedObject(..) => {
+                        ("own", address_pattern(EntityType::is_internal))
+                    }
+                }
+            }
+            (ScryptoCustomTypeKind::Own, _) => ("own", address_pattern(EntityType::is_internal)),
+            _ => return None,
+        };
+        Some(CustomStringFormat {
+            format: format.to_string(),
+            pattern,
+        })
+    }
+}
+
+/// Matches the `Display` of a decimal with the given number of decimal places.
+fn decimal_pattern(scale: u32) -> String {
+    format!("^-?[0-9]+(?:\\.[0-9]{{1,{}}})?$", scale)
+}
+
+/// Matches the `Display` of each kind of non-fungible local id.
+fn non_fungible_local_id_pattern() -> String {
+    format!(
+        "^(?:<[a-zA-Z0-9_]{{1,{max}}}>|#[0-9]+#|\\[(?:[0-9a-f]{{2}}){{1,{max}}}\\]|\\{{[0-9a-f]{{16}}-[0-9a-f]{{16}}-[0-9a-f]{{16}}-[0-9a-f]{{16}}\\}})$",
+        max = NON_FUNGIBLE_LOCAL_ID_MAX_LENGTH
+    )
+}
+
+/// Matches a bech32 address of any network for one of the matching entity types, or the
+/// `NodeId(<hex>)` form which is used when no network is known.
+fn address_pattern(matches_entity_type: impl Fn(&EntityType) -> bool) -> String {
+    // The HRPs of every network are the entity prefix followed by the network's suffix
+    let prefixes = HrpSet::from(&NetworkDefinition {
+        id: 0,
+        logical_name: String::new(),
+        hrp_suffix: String::new(),
+    });
+    let entity_prefixes: IndexSet<&str> = (0..=u8::MAX)
+        .filter_map(EntityType::from_repr)
+        .filter(|entity_type| matches_entity_type(entity_type))
+        .map(|entity_type| prefixes.get_entity_hrp(&entity_type))
+        .collect();
+    let entity_prefixes: Vec<&str> = entity_prefixes.into_iter().collect();
+    format!(
+        "^(?:(?:{})[a-z0-9_]+1[02-9ac-hj-np-z]+|NodeId\\([0-9a-f]{{{}}}\\))$",
+        entity_prefixes.join("|"),
+        2 * NodeId::LENGTH
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+    use serde_json::{from_str, json, Value as JsonValue};
+
+    #[derive(ScryptoSbor)]
+    pub struct Deposit {
+        pub amount: Decimal,
+        pub resource: ResourceAddress,
+    }
+
+    #[test]
+    fn test_custom_values_are_exported_with_their_string_format() {
+        let (type_id, schema) =
+            generate_full_schema_from_single_type::<Deposit, ScryptoCustomSchema>();
+        let roots = [("Root".to_string(), type_id)];
+
+        let document: JsonValue =
+            from_str(&export_json_schema::<ScryptoCustomExtension>(schema.v1(), &roots).unwrap())
+                .unwrap();
+        let fields = &document["$defs"]["Deposit"]["properties"]["fields"]["prefixItems"];
+        assert_eq!(
+            fields[0]["allOf"][0]["properties"]["value"],
+            json!({
+                "type": "string",
+                "format": "decimal",
+                "pattern": "^-?[0-9]+(?:\\.[0-9]{1,18})?$",
+            })
+        );
+        assert_eq!(
+            fields[1]["allOf"][0]["properties"]["type_name"],
+            json!({ "const": "ResourceAddress" })
+        );
+        assert_eq!(
+            fields[1]["allOf"][0]["properties"]["value"],
+            json!({
+                "type": "string",
+                "format": "resource_address",
+                "pattern": "^(?:(?:resource_)[a-z0-9_]+1[02-9ac-hj-np-z]+|NodeId\\([0-9a-f]{60}\\))$",
+            })
+        );
+
+        let typescript = export_typescript::<ScryptoCustomExtension>(schema.v1(), &roots).unwrap();
+        assert!(typescript.contains("value: string & { readonly __format: \"decimal\" };\n"));
+        assert!(
+            typescript.contains("value: string & { readonly __format: \"resource_address\" };\n")
+        );
+    }
+}
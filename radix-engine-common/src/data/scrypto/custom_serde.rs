@@ -45,10 +45,11 @@ impl SerializableCustomExtension for ScryptoCustomExtension {
 mod tests {
     use super::*;
     use crate::address::test_addresses::*;
-    use crate::address::AddressBech32Encoder;
+    use crate::address::{AddressBech32Decoder, AddressBech32Encoder};
     use crate::data::scrypto::model::*;
-    use crate::data::scrypto::{scrypto_encode, ScryptoValue};
+    use crate::data::scrypto::{scrypto_decode, scrypto_encode, ScryptoValue};
     use crate::math::*;
+    use crate::network::NetworkDefinition;
     use crate::types::*;
     use sbor::rust::vec;
     use serde::Serialize;
@@ -281,4 +282,312 @@ mod tests {
             expected,
         );
     }
+
+    /// A deterministic generator of arbitrary values, covering all value kinds.
+    struct ValueGenerator(u64);
+
+    impl ValueGenerator {
+        fn next(&mut self) -> u64 {
+            // xorshift64
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: u64) -> usize {
+            (self.next() % bound) as usize
+        }
+
+        fn bytes(&mut self, length: usize) -> Vec<u8> {
+            (0..length).map(|_| self.next() as u8).collect()
+        }
+
+        fn alphanumeric_string(&mut self, length: usize) -> String {
+            const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
+            (0..length)
+                .map(|_| CHARS[self.below(CHARS.len() as u64)] as char)
+                .collect()
+        }
+
+        fn node_id(&mut self) -> NodeId {
+            // Node ids with an invalid entity type are output as hex, even with an encoder
+            match self.below(3) {
+                0 => FUNGIBLE_RESOURCE_NODE_ID,
+                _ => NodeId(self.bytes(NodeId::LENGTH).try_into().unwrap()),
+            }
+        }
+
+        fn value_kind(&mut self, depth: usize) -> ScryptoValueKind {
+            let value_kinds: &[ScryptoValueKind] = &[
+                ValueKind::Bool,
+                ValueKind::I8,
+                ValueKind::I16,
+                ValueKind::I32,
+                ValueKind::I64,
+                ValueKind::I128,
+                ValueKind::U8,
+                ValueKind::U16,
+                ValueKind::U32,
+                ValueKind::U64,
+                ValueKind::U128,
+                ValueKind::String,
+                ValueKind::Custom(ScryptoCustomValueKind::Reference),
+                ValueKind::Custom(ScryptoCustomValueKind::Own),
+                ValueKind::Custom(ScryptoCustomValueKind::Decimal),
+                ValueKind::Custom(ScryptoCustomValueKind::PreciseDecimal),
+                ValueKind::Custom(ScryptoCustomValueKind::NonFungibleLocalId),
+                ValueKind::Tuple,
+                ValueKind::Enum,
+                ValueKind::Array,
+                ValueKind::Map,
+            ];
+            // Containers are only generated up to a few levels deep
+            let count = if depth < 3 {
+                value_kinds.len()
+            } else {
+                value_kinds.len() - 4
+            };
+            value_kinds[self.below(count as u64)]
+        }
+
+        fn value(&mut self, value_kind: ScryptoValueKind, depth: usize) -> ScryptoValue {
+            match value_kind {
+                ValueKind::Bool => Value::Bool {
+                    value: self.next() % 2 == 0,
+                },
+                ValueKind::I8 => Value::I8 {
+                    value: self.next() as i8,
+                },
+                ValueKind::I16 => Value::I16 {
+                    value: self.next() as i16,
+                },
+                ValueKind::I32 => Value::I32 {
+                    value: self.next() as i32,
+                },
+                ValueKind::I64 => Value::I64 {
+                    value: self.next() as i64,
+                },
+                ValueKind::I128 => Value::I128 {
+                    value: (((self.next() as u128) << 64) | self.next() as u128) as i128,
+                },
+                ValueKind::U8 => Value::U8 {
+                    value: self.next() as u8,
+                },
+                ValueKind::U16 => Value::U16 {
+                    value: self.next() as u16,
+                },
+                ValueKind::U32 => Value::U32 {
+                    value: self.next() as u32,
+                },
+                ValueKind::U64 => Value::U64 { value: self.next() },
+                ValueKind::U128 => Value::U128 {
+                    value: ((self.next() as u128) << 64) | self.next() as u128,
+                },
+                ValueKind::String => {
+                    let length = self.below(8);
+                    Value::String {
+                        value: self.alphanumeric_string(length),
+                    }
+                }
+                ValueKind::Tuple => Value::Tuple {
+                    fields: self.values(depth),
+                },
+                ValueKind::Enum => Value::Enum {
+                    discriminator: self.next() as u8,
+                    fields: self.values(depth),
+                },
+                ValueKind::Array => {
+                    let element_value_kind = self.value_kind(depth + 1);
+                    let length = self.below(4);
+                    Value::Array {
+                        element_value_kind,
+                        elements: (0..length)
+                            .map(|_| self.value(element_value_kind, depth + 1))
+                            .collect(),
+                    }
+                }
+                ValueKind::Map => {
+                    let key_value_kind = self.value_kind(depth + 1);
+                    let value_value_kind = self.value_kind(depth + 1);
+                    let length = self.below(4);
+                    Value::Map {
+                        key_value_kind,
+                        value_value_kind,
+                        entries: (0..length)
+                            .map(|_| {
+                                (
+                                    self.value(key_value_kind, depth + 1),
+                                    self.value(value_value_kind, depth + 1),
+                                )
+                            })
+                            .collect(),
+                    }
+                }
+                ValueKind::Custom(custom_value_kind) => {
+                    let value = match custom_value_kind {
+                        ScryptoCustomValueKind::Reference => {
+                            ScryptoCustomValue::Reference(Reference(self.node_id()))
+                        }
+                        ScryptoCustomValueKind::Own => ScryptoCustomValue::Own(Own(self.node_id())),
+                        ScryptoCustomValueKind::Decimal => ScryptoCustomValue::Decimal(
+                            Decimal::from(self.next() as i64).checked_div(1000).unwrap(),
+                        ),
+                        ScryptoCustomValueKind::PreciseDecimal => {
+                            ScryptoCustomValue::PreciseDecimal(PreciseDecimal::from(
+                                self.next() as i64
+                            ))
+                        }
+                        ScryptoCustomValueKind::NonFungibleLocalId => {
+                            let length = 1 + self.below(16);
+                            let id = match self.below(4) {
+                                0 => NonFungibleLocalId::integer(self.next()),
+                                1 => NonFungibleLocalId::string(self.alphanumeric_string(length))
+                                    .unwrap(),
+                                2 => NonFungibleLocalId::bytes(self.bytes(length)).unwrap(),
+                                _ => NonFungibleLocalId::ruid(self.bytes(32).try_into().unwrap()),
+                            };
+                            ScryptoCustomValue::NonFungibleLocalId(id)
+                        }
+                    };
+                    Value::Custom { value }
+                }
+            }
+        }
+
+        fn values(&mut self, depth: usize) -> Vec<ScryptoValue> {
+            let length = self.below(4);
+            (0..length)
+                .map(|_| {
+                    let value_kind = self.value_kind(depth + 1);
+                    self.value(value_kind, depth + 1)
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")] // Workaround for VS Code "Run Test" feature
+    fn test_programmatic_json_round_trip() {
+        let encoder = AddressBech32Encoder::for_simulator();
+        let decoder = AddressBech32Decoder::for_simulator();
+        let mut generator = ValueGenerator(0x2545f4914f6cdd1d);
+        for _ in 0..500 {
+            let value_kind = generator.value_kind(0);
+            let value = generator.value(value_kind, 0);
+            let payload = scrypto_encode(&value).unwrap();
+
+            for (encoder, decoder) in [(None, None), (Some(&encoder), Some(&decoder))] {
+                let json = to_string(
+                    &ScryptoRawPayload::new_from_valid_slice(&payload).serializable(
+                        SerializationParameters::Schemaless {
+                            mode: SerializationMode::Programmatic,
+                            custom_context: ScryptoValueDisplayContext::with_optional_bech32(
+                                encoder,
+                            ),
+                            depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+                        },
+                    ),
+                )
+                .unwrap();
+                let deserialized = deserialize_payload(
+                    &mut serde_json::Deserializer::from_str(&json),
+                    &DeserializationParameters::<ScryptoCustomExtension>::Schemaless {
+                        mode: SerializationMode::Programmatic,
+                        custom_context: ScryptoValueParseContext::with_optional_bech32(decoder),
+                        depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+                    },
+                )
+                .unwrap();
+                assert_eq!(deserialized, payload, "Mismatching round trip of {}", json);
+                assert_eq!(
+                    scrypto_decode::<ScryptoValue>(&deserialized).unwrap(),
+                    value,
+                    "Mismatching round trip of {}",
+                    json
+                );
+            }
+        }
+    }
+
+    #[derive(ScryptoSbor)]
+    pub struct NaturalSample {
+        pub resource: ResourceAddress,
+        pub vault: Own,
+        pub amounts: IndexMap<String, Decimal>,
+        pub precise_amount: PreciseDecimal,
+        pub ids: Vec<NonFungibleLocalId>,
+        pub limit: Option<u64>,
+    }
+
+    #[test]
+    #[cfg(feature = "serde")] // Workaround for VS Code "Run Test" feature
+    fn test_natural_json_round_trip() {
+        let encoder = AddressBech32Encoder::for_simulator();
+        let decoder = AddressBech32Decoder::for_simulator();
+        let (type_id, schema) =
+            generate_full_schema_from_single_type::<NaturalSample, ScryptoCustomSchema>();
+        let value = NaturalSample {
+            resource: FUNGIBLE_RESOURCE,
+            vault: Own(FUNGIBLE_RESOURCE_NODE_ID),
+            amounts: indexmap! {
+                "b".to_string() => Decimal::ONE,
+                "a".to_string() => Decimal::ONE.checked_div(100).unwrap(),
+            },
+            precise_amount: PreciseDecimal::ZERO,
+            ids: vec![
+                NonFungibleLocalId::integer(1),
+                NonFungibleLocalId::string("hello").unwrap(),
+            ],
+            limit: Some(u64::MAX),
+        };
+        let payload = scrypto_encode(&value).unwrap();
+
+        let json = to_string(
+            &ScryptoRawPayload::new_from_valid_slice(&payload).serializable(
+                SerializationParameters::WithSchema {
+                    mode: SerializationMode::Natural,
+                    custom_context: ScryptoValueDisplayContext::with_optional_bech32(Some(
+                        &encoder,
+                    )),
+                    schema: schema.v1(),
+                    type_id,
+                    depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+                },
+            ),
+        )
+        .unwrap();
+        let deserialized = deserialize_payload(
+            &mut serde_json::Deserializer::from_str(&json),
+            &DeserializationParameters::<ScryptoCustomExtension>::WithSchema {
+                mode: SerializationMode::Natural,
+                custom_context: ScryptoValueParseContext::with_optional_bech32(Some(&decoder)),
+                schema: schema.v1(),
+                type_id,
+                depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+            },
+        )
+        .unwrap();
+        assert_eq!(deserialized, payload, "Mismatching round trip of {}", json);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")] // Workaround for VS Code "Run Test" feature
+    fn test_address_from_another_network_is_rejected() {
+        let json = format!(
+            r#"{{ "kind": "Reference", "value": "{}" }}"#,
+            FUNGIBLE_RESOURCE_SIM_ADDRESS
+        );
+        let result = deserialize_value(
+            &mut serde_json::Deserializer::from_str(&json),
+            &DeserializationParameters::<ScryptoCustomExtension>::Schemaless {
+                mode: SerializationMode::Programmatic,
+                custom_context: ScryptoValueParseContext::with_optional_bech32(Some(
+                    &AddressBech32Decoder::new(&NetworkDefinition::mainnet()),
+                )),
+                depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+            },
+        );
+        assert!(result.is_err());
+    }
 }
@@ -6,6 +6,7 @@ pub use crate::constants::SCRYPTO_SBOR_V1_PAYLOAD_PREFIX;
 pub type ScryptoEncoder<'a> = VecEncoder<'a, ScryptoCustomValueKind>;
 pub type ScryptoDecoder<'a> = VecDecoder<'a, ScryptoCustomValueKind>;
 pub type ScryptoTraverser<'a> = VecTraverser<'a, ScryptoCustomTraversal>;
+pub type ScryptoLazyValue<'a> = LazyValue<'a, ScryptoCustomExtension>;
 pub type ScryptoValueKind = ValueKind<ScryptoCustomValueKind>;
 pub type ScryptoValue = Value<ScryptoCustomValueKind, ScryptoCustomValue>;
 pub type RawScryptoValue<'a> = RawValue<'a, ScryptoCustomExtension>;
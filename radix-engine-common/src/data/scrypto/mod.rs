@@ -6,9 +6,13 @@ pub mod model;
 /// Defines the full Scrypto extension.
 mod custom_extension;
 mod custom_formatting;
+/// Defines how custom values are parsed from their string content.
+mod custom_parsing;
 mod custom_payload_wrappers;
 /// Defines the custom Scrypto schema types.
 mod custom_schema;
+/// Defines the string formats of custom values in exported schemas.
+mod custom_schema_export;
 /// Defines custom serialization of the types.
 #[cfg(feature = "serde")]
 mod custom_serde;
@@ -26,6 +30,7 @@ mod definitions;
 
 pub use custom_extension::*;
 pub use custom_formatting::*;
+pub use custom_parsing::*;
 pub use custom_payload_wrappers::*;
 pub use custom_schema::*;
 #[cfg(feature = "serde")]
@@ -47,6 +52,7 @@ pub mod prelude {
     // Private modules to include in prelude
     pub use super::custom_extension::*;
     pub use super::custom_formatting::*;
+    pub use super::custom_parsing::*;
     pub use super::custom_payload_wrappers::*;
     pub use super::custom_schema::*;
     #[cfg(feature = "serde")]
@@ -1,5 +1,7 @@
 //! Definitions of safe integers and uints.
 
+use crate::math::fixed_point::checked_div_rounded;
+use crate::math::rounding_mode::RoundingMode;
 use crate::math::traits::*;
 #[cfg(feature = "radix_engine_fuzzing")]
 use arbitrary::Arbitrary;
@@ -384,6 +386,24 @@ macro_rules! op_impl {
                         opt.map(|v| Self(v))
                     }
                 }
+
+                impl $t {
+                    /// Calculates `self * multiplier / divisor` with a single rounding.
+                    ///
+                    /// The intermediate product is computed exactly, so it can't overflow, and
+                    /// the quotient is rounded according to the given mode.
+                    /// Returns `None` if the divisor is zero or the result overflows.
+                    pub fn checked_mul_div(
+                        self,
+                        multiplier: Self,
+                        divisor: Self,
+                        mode: RoundingMode,
+                    ) -> Option<Self> {
+                        let product = BigInt::from(self) * BigInt::from(multiplier);
+                        let quotient = checked_div_rounded(product, BigInt::from(divisor), mode)?;
+                        Self::try_from(quotient).ok()
+                    }
+                }
             )*
         }
     };
@@ -462,3 +462,44 @@ fn test_bnum_to_bnum_errors() {
     let err = U256::try_from(i384).unwrap_err();
     assert_eq!(err, ParseU256Error::Overflow);
 }
+
+#[test]
+fn test_checked_mul_div() {
+    use crate::math::RoundingMode;
+
+    let mul_div = |a: i32, b: i32, c: i32, mode| {
+        I192::from(a).checked_mul_div(I192::from(b), I192::from(c), mode)
+    };
+    assert_eq!(mul_div(7, 3, 2, RoundingMode::ToZero), Some(I192::from(10)));
+    assert_eq!(
+        mul_div(7, 3, 2, RoundingMode::AwayFromZero),
+        Some(I192::from(11))
+    );
+    assert_eq!(
+        mul_div(7, 3, 2, RoundingMode::ToNearestMidpointToEven),
+        Some(I192::from(10))
+    );
+    assert_eq!(
+        mul_div(-7, 3, 2, RoundingMode::ToNegativeInfinity),
+        Some(I192::from(-11))
+    );
+    assert_eq!(
+        mul_div(-7, 3, 2, RoundingMode::ToNearestMidpointTowardZero),
+        Some(I192::from(-10))
+    );
+    assert_eq!(mul_div(7, 3, 0, RoundingMode::ToZero), None);
+
+    // The intermediate product doesn't overflow
+    assert_eq!(
+        I512::MAX.checked_mul_div(I512::MAX, I512::MAX, RoundingMode::ToZero),
+        Some(I512::MAX)
+    );
+    assert_eq!(
+        U256::MAX.checked_mul_div(U256::from(3u8), U256::from(4u8), RoundingMode::ToZero),
+        Some(U256::MAX / U256::from(4u8) * U256::from(3u8) + U256::from(2u8))
+    );
+    assert_eq!(
+        U256::MAX.checked_mul_div(U256::from(2u8), U256::ONE, RoundingMode::ToZero),
+        None
+    );
+}
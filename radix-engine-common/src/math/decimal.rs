@@ -15,6 +15,7 @@ use serde::{Deserialize, Serialize};
 use crate::data::manifest::ManifestCustomValueKind;
 use crate::data::scrypto::*;
 use crate::math::bnum_integer::*;
+use crate::math::fixed_point;
 use crate::math::rounding_mode::*;
 use crate::math::traits::*;
 use crate::math::PreciseDecimal;
@@ -269,6 +270,71 @@ impl Decimal {
             Some(Decimal(nth_root))
         }
     }
+
+    /// Natural logarithm of a Decimal
+    ///
+    /// The result is rounded to the nearest Decimal, and is within 1 ulp (`10^-18`) of the exact
+    /// value. Returns `None` if the number is not positive.
+    pub fn checked_ln(&self) -> Option<Self> {
+        let ln = fixed_point::ln(fixed_point::from_subunits(self.0.into(), Self::SCALE))?;
+        Self::from_fixed_point(ln)
+    }
+
+    /// Base 2 logarithm of a Decimal
+    ///
+    /// The result is rounded to the nearest Decimal, and is within 1 ulp (`10^-18`) of the exact
+    /// value. Returns `None` if the number is not positive.
+    pub fn checked_log2(&self) -> Option<Self> {
+        let log2 = fixed_point::log2(fixed_point::from_subunits(self.0.into(), Self::SCALE))?;
+        Self::from_fixed_point(log2)
+    }
+
+    /// Exponential function (`e^self`) of a Decimal
+    ///
+    /// The result is rounded to the nearest Decimal, and is within 1 ulp (`10^-18`) of the exact
+    /// value. Returns `None` if the result overflows.
+    pub fn checked_exp(&self) -> Option<Self> {
+        let exp = fixed_point::exp(fixed_point::from_subunits(self.0.into(), Self::SCALE))?;
+        Self::from_fixed_point(exp)
+    }
+
+    /// Raises a Decimal to a Decimal power
+    ///
+    /// Integer exponents are evaluated by repeated squaring, any other as
+    /// `exp(exponent * ln(self))`. The result is rounded to the nearest Decimal, and is within
+    /// 1 ulp (`10^-18`) of the exact value.
+    ///
+    /// Returns `None` if the result overflows or is undefined, i.e. for a negative number raised to
+    /// a non-integer power, or zero raised to a negative power.
+    pub fn checked_pow(&self, exponent: Self) -> Option<Self> {
+        let pow = fixed_point::pow(
+            fixed_point::from_subunits(self.0.into(), Self::SCALE),
+            fixed_point::from_subunits(exponent.0.into(), Self::SCALE),
+        )?;
+        Self::from_fixed_point(pow)
+    }
+
+    /// Calculates `self * multiplier / divisor` with a single rounding
+    ///
+    /// The intermediate product is computed exactly (in double the width), so it can't overflow and
+    /// no precision is lost before the quotient is rounded according to the given mode. Returns
+    /// `None` if the divisor is zero or the result overflows.
+    pub fn checked_mul_div(
+        &self,
+        multiplier: Self,
+        divisor: Self,
+        mode: RoundingMode,
+    ) -> Option<Self> {
+        let product = I768::from(self.0) * I768::from(multiplier.0);
+        let quotient = fixed_point::checked_div_rounded(product, I768::from(divisor.0), mode)?;
+        I192::try_from(quotient).ok().map(Self)
+    }
+
+    fn from_fixed_point(value: I768) -> Option<Self> {
+        I192::try_from(fixed_point::to_subunits(value, Self::SCALE))
+            .ok()
+            .map(Self)
+    }
 }
 
 macro_rules! from_primitive_type {
@@ -1959,6 +2025,295 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_ln() {
+        assert_eq!(
+            test_dec!("2").checked_ln().unwrap(),
+            test_dec!("0.693147180559945309")
+        );
+        assert_eq!(
+            test_dec!("10").checked_ln().unwrap(),
+            test_dec!("2.302585092994045684")
+        );
+        assert_eq!(
+            test_dec!("0.5").checked_ln().unwrap(),
+            test_dec!("-0.693147180559945309")
+        );
+        assert_eq!(
+            test_dec!("3.14159").checked_ln().unwrap(),
+            test_dec!("1.144729041185178381")
+        );
+        assert_eq!(
+            test_dec!("123456789.123456789").checked_ln().unwrap(),
+            test_dec!("18.631401767168018033")
+        );
+        assert_eq!(
+            Decimal(I192::ONE).checked_ln().unwrap(),
+            test_dec!("-41.446531673892822312")
+        );
+        assert_eq!(test_dec!("1").checked_ln().unwrap(), Decimal::ZERO);
+        assert_eq!(test_dec!("0").checked_ln(), None);
+        assert_eq!(test_dec!("-1").checked_ln(), None);
+    }
+
+    #[test]
+    fn test_log2() {
+        assert_eq!(test_dec!("8").checked_log2().unwrap(), test_dec!("3"));
+        assert_eq!(test_dec!("0.25").checked_log2().unwrap(), test_dec!("-2"));
+        assert_eq!(
+            test_dec!("10").checked_log2().unwrap(),
+            test_dec!("3.321928094887362348")
+        );
+        assert_eq!(
+            test_dec!("3").checked_log2().unwrap(),
+            test_dec!("1.584962500721156181")
+        );
+        assert_eq!(test_dec!("0").checked_log2(), None);
+    }
+
+    #[test]
+    fn test_exp() {
+        assert_eq!(test_dec!("0").checked_exp().unwrap(), Decimal::ONE);
+        assert_eq!(
+            test_dec!("1").checked_exp().unwrap(),
+            test_dec!("2.718281828459045235")
+        );
+        assert_eq!(
+            test_dec!("-1").checked_exp().unwrap(),
+            test_dec!("0.367879441171442322")
+        );
+        assert_eq!(
+            test_dec!("2.5").checked_exp().unwrap(),
+            test_dec!("12.182493960703473438")
+        );
+        assert_eq!(
+            test_dec!("-20.75").checked_exp().unwrap(),
+            test_dec!("0.000000000973620031")
+        );
+        assert_eq!(
+            test_dec!("90").checked_exp().unwrap(),
+            test_dec!("1220403294317840802002710035136369753970.746421099767546244")
+        );
+        assert_eq!(
+            test_dec!("-41").checked_exp().unwrap(),
+            test_dec!("0.000000000000000002")
+        );
+        assert_eq!(test_dec!("-1000").checked_exp().unwrap(), Decimal::ZERO);
+        assert_eq!(test_dec!("100").checked_exp(), None);
+        assert_eq!(Decimal::MAX.checked_exp(), None);
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(
+            test_dec!("2").checked_pow(test_dec!("0.5")).unwrap(),
+            test_dec!("1.414213562373095049")
+        );
+        assert_eq!(
+            test_dec!("4").checked_pow(test_dec!("-0.5")).unwrap(),
+            test_dec!("0.5")
+        );
+        assert_eq!(
+            test_dec!("1.5").checked_pow(test_dec!("2.5")).unwrap(),
+            test_dec!("2.75567596063107536")
+        );
+        assert_eq!(
+            test_dec!("10").checked_pow(test_dec!("-3")).unwrap(),
+            test_dec!("0.001")
+        );
+        assert_eq!(
+            test_dec!("0.9").checked_pow(test_dec!("100.1")).unwrap(),
+            test_dec!("0.000026283015724165")
+        );
+        assert_eq!(
+            test_dec!("123.456").checked_pow(test_dec!("7.89")).unwrap(),
+            test_dec!("31771028258180977.309068659682205126")
+        );
+        assert_eq!(
+            test_dec!("2").checked_pow(test_dec!("10")).unwrap(),
+            test_dec!("1024")
+        );
+        assert_eq!(
+            test_dec!("-2").checked_pow(test_dec!("3")).unwrap(),
+            test_dec!("-8")
+        );
+        assert_eq!(
+            test_dec!("-2").checked_pow(test_dec!("-2")).unwrap(),
+            test_dec!("0.25")
+        );
+        assert_eq!(
+            test_dec!("0").checked_pow(test_dec!("0")).unwrap(),
+            Decimal::ONE
+        );
+        assert_eq!(
+            test_dec!("0").checked_pow(test_dec!("0.5")).unwrap(),
+            Decimal::ZERO
+        );
+        assert_eq!(test_dec!("0").checked_pow(test_dec!("-1")), None);
+        assert_eq!(test_dec!("-8").checked_pow(test_dec!("0.5")), None);
+        assert_eq!(test_dec!("10").checked_pow(test_dec!("100")), None);
+        assert_eq!(
+            test_dec!("10").checked_pow(test_dec!("-100")).unwrap(),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_mul_div() {
+        let third = |mode| {
+            test_dec!("1")
+                .checked_mul_div(test_dec!("1"), test_dec!("3"), mode)
+                .unwrap()
+        };
+        assert_eq!(
+            third(RoundingMode::ToZero),
+            test_dec!("0.333333333333333333")
+        );
+        assert_eq!(
+            third(RoundingMode::AwayFromZero),
+            test_dec!("0.333333333333333334")
+        );
+        assert_eq!(
+            third(RoundingMode::ToNearestMidpointAwayFromZero),
+            test_dec!("0.333333333333333333")
+        );
+        let minus_third = |mode| {
+            test_dec!("-1")
+                .checked_mul_div(test_dec!("1"), test_dec!("3"), mode)
+                .unwrap()
+        };
+        assert_eq!(
+            minus_third(RoundingMode::ToNegativeInfinity),
+            test_dec!("-0.333333333333333334")
+        );
+        assert_eq!(
+            minus_third(RoundingMode::ToPositiveInfinity),
+            test_dec!("-0.333333333333333333")
+        );
+
+        let half_ulp = |mode| {
+            Decimal(I192::ONE)
+                .checked_mul_div(test_dec!("1"), test_dec!("2"), mode)
+                .unwrap()
+        };
+        assert_eq!(
+            half_ulp(RoundingMode::ToNearestMidpointToEven),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            half_ulp(RoundingMode::ToNearestMidpointAwayFromZero),
+            Decimal(I192::ONE)
+        );
+
+        // The intermediate product doesn't overflow
+        assert_eq!(
+            Decimal::MAX
+                .checked_mul_div(test_dec!("2"), test_dec!("2"), RoundingMode::ToZero)
+                .unwrap(),
+            Decimal::MAX
+        );
+        assert_eq!(
+            Decimal::MAX.checked_mul_div(test_dec!("2"), test_dec!("1"), RoundingMode::ToZero),
+            None
+        );
+        assert_eq!(
+            test_dec!("1").checked_mul_div(test_dec!("1"), test_dec!("0"), RoundingMode::ToZero),
+            None
+        );
+    }
+
+    /// A deterministic sequence of positive numbers, spread over many orders of magnitude.
+    fn pseudo_random_numbers(count: usize) -> Vec<Decimal> {
+        let mut state = 0x2545f4914f6cdd1du64;
+        (0..count)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let mantissa = Decimal(I192::from(state >> 1));
+                let magnitude = test_dec!("10").checked_powi((state % 21) as i64).unwrap();
+                mantissa * magnitude
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transcendental_properties() {
+        let ulp = Decimal(I192::ONE);
+        for x in pseudo_random_numbers(200) {
+            // exp(ln(x)) = x, where the rounding of ln(x) is magnified by x
+            let error = (x.checked_ln().unwrap().checked_exp().unwrap() - x)
+                .checked_abs()
+                .unwrap();
+            assert!(
+                error <= x * test_dec!("0.000000000000000002") + ulp,
+                "exp(ln({}))",
+                x
+            );
+
+            // ln(x * y) = ln(x) + ln(y), for x, y >= 1 (so that the product is rounded by less
+            // than 1 ulp)
+            let y = x.checked_sqrt().unwrap() + test_dec!("1");
+            let x = x + test_dec!("1");
+            let error =
+                ((x * y).checked_ln().unwrap() - x.checked_ln().unwrap() - y.checked_ln().unwrap())
+                    .checked_abs()
+                    .unwrap();
+            assert!(error <= ulp * 3, "ln({} * {})", x, y);
+
+            // Powers agree with the correctly rounded results of `checked_mul_div`
+            let nearest = RoundingMode::ToNearestMidpointToEven;
+            assert_eq!(
+                y.checked_pow(test_dec!("2")).unwrap(),
+                y.checked_mul_div(y, Decimal::ONE, nearest).unwrap()
+            );
+            assert_eq!(
+                y.checked_pow(test_dec!("-1")).unwrap(),
+                Decimal::ONE
+                    .checked_mul_div(Decimal::ONE, y, nearest)
+                    .unwrap()
+            );
+            let error = (y.checked_pow(test_dec!("0.5")).unwrap() - y.checked_sqrt().unwrap())
+                .checked_abs()
+                .unwrap();
+            assert!(error <= ulp, "{}^0.5", y);
+        }
+
+        for n in 0..=131i64 {
+            let power_of_two = test_dec!("2").checked_powi(n).unwrap();
+            assert_eq!(power_of_two.checked_log2().unwrap(), Decimal::from(n));
+        }
+    }
+
+    #[test]
+    fn test_mul_div_properties() {
+        let numbers = pseudo_random_numbers(100);
+        for window in numbers.windows(3) {
+            // Keeps the results within range
+            let (x, multiplier, divisor) = (
+                window[0],
+                window[1].checked_sqrt().unwrap(),
+                window[2] + Decimal::ONE,
+            );
+            let down = x
+                .checked_mul_div(multiplier, divisor, RoundingMode::ToNegativeInfinity)
+                .unwrap();
+            let up = x
+                .checked_mul_div(multiplier, divisor, RoundingMode::ToPositiveInfinity)
+                .unwrap();
+            let nearest = x
+                .checked_mul_div(multiplier, divisor, RoundingMode::ToNearestMidpointToEven)
+                .unwrap();
+            assert!(up - down <= Decimal(I192::ONE));
+            assert!(down <= nearest && nearest <= up);
+            assert_eq!(
+                x.checked_mul_div(divisor, divisor, RoundingMode::ToZero)
+                    .unwrap(),
+                x
+            );
+        }
+    }
+
     #[test]
     fn test_neg_decimal() {
         let d = Decimal::ONE;
@@ -0,0 +1,294 @@
+//! Fixed-point arithmetic backing the transcendental functions of `Decimal` and `PreciseDecimal`.
+//!
+//! Values are `I768` integers scaled by `10^WORKING_SCALE`. The working scale is much finer than
+//! the `PreciseDecimal` scale, so the truncation errors of the intermediate steps stay far below
+//! the final rounding, while all intermediate products still fit into `I768` (max ~7.7 * 10^230).
+
+use crate::math::bnum_integer::*;
+use crate::math::rounding_mode::*;
+use crate::math::traits::*;
+use num_traits::{Pow, ToPrimitive};
+use sbor::rust::cmp::Ordering;
+use sbor::rust::ops::{Add, Div, Rem, Sub};
+use sbor::rust::str::FromStr;
+
+/// The number of decimal places of the working representation.
+pub(crate) const WORKING_SCALE: u32 = 90;
+
+/// `exp` is computed as `exp(r / 2^EXP_HALVINGS)^(2^EXP_HALVINGS)`, which keeps the Taylor
+/// series short.
+const EXP_HALVINGS: u32 = 8;
+
+lazy_static::lazy_static! {
+    static ref ONE: I768 = I768::TEN.pow(WORKING_SCALE);
+    static ref LN_2: I768 = I768::from_str(
+        "693147180559945309417232121458176568075500134360255254120680009493393621969694715605863327"
+    ).unwrap();
+    static ref SQRT_2: I768 = I768::from_str(
+        "1414213562373095048801688724209698078569671875376948073176679737990732478462107038850387534"
+    ).unwrap();
+    /// Any larger argument overflows both `Decimal` and `PreciseDecimal` (`ln(PreciseDecimal::MAX)`
+    /// is ~93.86).
+    static ref MAX_EXP_ARGUMENT: I768 = *ONE * I768::from(94);
+    /// Any smaller argument rounds to zero for both `Decimal` and `PreciseDecimal`.
+    static ref MIN_EXP_ARGUMENT: I768 = *ONE * I768::from(-100);
+    /// The largest magnitude an integer power is allowed to reach before it is reported as an
+    /// overflow, bounding the intermediate products.
+    static ref MAX_POWI_MAGNITUDE: I768 = *ONE * I768::TEN.pow(41);
+}
+
+/// Divides `numerator` by `denominator`, rounding the exact quotient according to `mode`.
+///
+/// Returns `None` if the `denominator` is zero.
+pub(crate) fn checked_div_rounded<T>(numerator: T, denominator: T, mode: RoundingMode) -> Option<T>
+where
+    T: Clone
+        + Ord
+        + From<i8>
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Div<Output = T>
+        + Rem<Output = T>,
+{
+    let zero = T::from(0);
+    if denominator == zero {
+        return None;
+    }
+
+    // Both are rounded toward zero
+    let quotient = numerator.clone() / denominator.clone();
+    let remainder = numerator.clone() % denominator.clone();
+    if remainder == zero {
+        return Some(quotient);
+    }
+
+    let is_positive = (numerator > zero) == (denominator > zero);
+    let abs = |value: T| {
+        if value < zero {
+            zero.clone() - value
+        } else {
+            value
+        }
+    };
+    let (floor, ceiling) = if is_positive {
+        (quotient.clone(), quotient + T::from(1))
+    } else {
+        (quotient.clone() - T::from(1), quotient)
+    };
+
+    let resolved_strategy = ResolvedRoundingStrategy::from_mode(mode, is_positive, || {
+        // Compares the distance to the floor with half of the denominator
+        let denominator = abs(denominator.clone());
+        let remainder = abs(remainder.clone());
+        let distance_to_floor = if is_positive {
+            remainder
+        } else {
+            denominator.clone() - remainder
+        };
+        (distance_to_floor.clone() + distance_to_floor).cmp(&denominator)
+    });
+
+    Some(match resolved_strategy {
+        ResolvedRoundingStrategy::RoundUp => ceiling,
+        ResolvedRoundingStrategy::RoundDown => floor,
+        ResolvedRoundingStrategy::RoundToEven => {
+            if floor.clone() % T::from(2) == zero {
+                floor
+            } else {
+                ceiling
+            }
+        }
+    })
+}
+
+/// Converts subunits of the given scale into the working representation. This is exact.
+pub(crate) fn from_subunits(subunits: I768, scale: u32) -> I768 {
+    subunits * I768::TEN.pow(WORKING_SCALE - scale)
+}
+
+/// Converts a working representation into subunits of the given scale, rounding to the nearest
+/// (with midpoints to even).
+pub(crate) fn to_subunits(value: I768, scale: u32) -> I768 {
+    checked_div_rounded(
+        value,
+        I768::TEN.pow(WORKING_SCALE - scale),
+        RoundingMode::ToNearestMidpointToEven,
+    )
+    .expect("Always safe")
+}
+
+/// Multiplies two working representations, truncating the result. Unlike `a * b / ONE`, the
+/// intermediate product does not grow beyond the magnitude of the result times `ONE`.
+fn checked_mul(a: I768, b: I768) -> Option<I768> {
+    let integral = a / *ONE;
+    let fractional = a % *ONE;
+    integral
+        .checked_mul(b)?
+        .checked_add(fractional.checked_mul(b)? / *ONE)
+}
+
+/// Computes the natural logarithm, with an absolute error below `10^-87`.
+///
+/// Returns `None` if the value is not positive.
+pub(crate) fn ln(value: I768) -> Option<I768> {
+    if value <= I768::ZERO {
+        return None;
+    }
+
+    // Reduce the value to `m * 2^k`, with `m` in `[sqrt(2) / 2, sqrt(2)]`
+    let mut k = ONE.leading_zeros() as i32 - value.leading_zeros() as i32;
+    let mut m = if k >= 0 {
+        value / I768::from(2).pow(k as u32)
+    } else {
+        value * I768::from(2).pow(-k as u32)
+    };
+    while m > *SQRT_2 {
+        m = m / I768::from(2);
+        k += 1;
+    }
+    while m * I768::from(2) < *SQRT_2 {
+        m = m * I768::from(2);
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3 / 3 + z^5 / 5 + ...), with z = (m - 1) / (m + 1)
+    // As |z| <= 0.172, every term is ~30 times smaller than the previous one.
+    let z = (m - *ONE) * *ONE / (m + *ONE);
+    let z_squared = z * z / *ONE;
+    let mut sum = I768::ZERO;
+    let mut power = z;
+    let mut n = 1;
+    loop {
+        let term = power / I768::from(n);
+        if term == I768::ZERO {
+            break;
+        }
+        sum += term;
+        power = power * z_squared / *ONE;
+        n += 2;
+    }
+
+    Some(*LN_2 * I768::from(k) + sum * I768::from(2))
+}
+
+/// Computes the base 2 logarithm, with an absolute error below `10^-87`.
+///
+/// Returns `None` if the value is not positive.
+pub(crate) fn log2(value: I768) -> Option<I768> {
+    Some(ln(value)? * *ONE / *LN_2)
+}
+
+/// Computes the exponential function, with a relative error below `10^-86`.
+///
+/// Returns `None` if the result is too large for any of the decimal types.
+pub(crate) fn exp(value: I768) -> Option<I768> {
+    if value > *MAX_EXP_ARGUMENT {
+        return None;
+    }
+    if value < *MIN_EXP_ARGUMENT {
+        return Some(I768::ZERO);
+    }
+
+    // exp(value) = 2^k * exp(r), with |r| <= ln(2) / 2
+    let k = checked_div_rounded(value, *LN_2, RoundingMode::ToNearestMidpointAwayFromZero)?;
+    let r = value - k * *LN_2;
+    let k = k.to_i32().expect("Always safe");
+
+    // exp(r) = exp(r / 2^EXP_HALVINGS)^(2^EXP_HALVINGS), where the Taylor series of
+    // exp(r / 2^EXP_HALVINGS) converges after ~20 terms
+    let r = r / I768::from(2).pow(EXP_HALVINGS);
+    let mut sum = *ONE;
+    let mut term = *ONE;
+    let mut n = 1;
+    loop {
+        term = term * r / (*ONE * I768::from(n));
+        if term == I768::ZERO {
+            break;
+        }
+        sum += term;
+        n += 1;
+    }
+    for _ in 0..EXP_HALVINGS {
+        sum = sum * sum / *ONE;
+    }
+
+    Some(if k >= 0 {
+        sum * I768::from(2).pow(k as u32)
+    } else {
+        sum / I768::from(2).pow(-k as u32)
+    })
+}
+
+/// Raises the value to an integer power by repeated squaring.
+///
+/// Returns `None` if the magnitude of the result is above `10^41` or, for a negative exponent,
+/// the value is zero.
+pub(crate) fn powi(value: I768, exponent: i64) -> Option<I768> {
+    // For negative exponents, the base is inverted first, as an inverse of a large, truncated
+    // power would lose most of its precision.
+    let mut base = if exponent < 0 {
+        if value == I768::ZERO {
+            return None;
+        }
+        *ONE * *ONE / value
+    } else {
+        value
+    };
+    let mut exponent = exponent.unsigned_abs();
+    let mut result = *ONE;
+
+    let check = |value: I768| {
+        if value.abs() > *MAX_POWI_MAGNITUDE {
+            None
+        } else {
+            Some(value)
+        }
+    };
+    loop {
+        if exponent & 1 == 1 {
+            result = check(checked_mul(result, base)?)?;
+        }
+        exponent >>= 1;
+        if exponent == 0 {
+            break;
+        }
+        base = check(checked_mul(base, base)?)?;
+    }
+
+    Some(result)
+}
+
+/// Raises the value to a power, via `exp(exponent * ln(value))` for non-integer exponents.
+///
+/// Returns `None` if the result is too large or undefined (a negative value raised to a
+/// non-integer power, or zero raised to a negative power).
+pub(crate) fn pow(value: I768, exponent: I768) -> Option<I768> {
+    if exponent % *ONE == I768::ZERO {
+        if let Some(exponent) = (exponent / *ONE).to_i64() {
+            return powi(value, exponent);
+        }
+    }
+
+    match value.cmp(&I768::ZERO) {
+        Ordering::Less => {
+            // Only an integer power of a negative value is defined, e.g. `(-1)^(2^70)`
+            if exponent % *ONE != I768::ZERO {
+                return None;
+            }
+            let result = exp(ln(-value)? * exponent / *ONE)?;
+            if exponent / *ONE % I768::from(2) == I768::ZERO {
+                Some(result)
+            } else {
+                Some(-result)
+            }
+        }
+        Ordering::Equal => {
+            if exponent > I768::ZERO {
+                Some(I768::ZERO)
+            } else {
+                None
+            }
+        }
+        Ordering::Greater => exp(ln(value)? * exponent / *ONE),
+    }
+}
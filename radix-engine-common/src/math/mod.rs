@@ -1,5 +1,6 @@
 pub mod bnum_integer;
 pub mod decimal;
+mod fixed_point;
 pub mod precise_decimal;
 pub mod rounding_mode;
 pub mod traits;
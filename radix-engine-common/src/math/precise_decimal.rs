@@ -17,6 +17,7 @@ use crate::data::manifest::ManifestCustomValueKind;
 use crate::data::scrypto::*;
 use crate::math::bnum_integer::*;
 use crate::math::decimal::*;
+use crate::math::fixed_point;
 use crate::math::rounding_mode::*;
 use crate::math::traits::*;
 use crate::well_known_scrypto_custom_type;
@@ -293,6 +294,72 @@ impl PreciseDecimal {
             Some(Self(nth_root))
         }
     }
+
+    /// Natural logarithm of a PreciseDecimal
+    ///
+    /// The result is rounded to the nearest PreciseDecimal, and is within 1 ulp (`10^-36`) of the
+    /// exact value. Returns `None` if the number is not positive.
+    pub fn checked_ln(&self) -> Option<Self> {
+        let ln = fixed_point::ln(fixed_point::from_subunits(self.0.into(), Self::SCALE))?;
+        Self::from_fixed_point(ln)
+    }
+
+    /// Base 2 logarithm of a PreciseDecimal
+    ///
+    /// The result is rounded to the nearest PreciseDecimal, and is within 1 ulp (`10^-36`) of the
+    /// exact value. Returns `None` if the number is not positive.
+    pub fn checked_log2(&self) -> Option<Self> {
+        let log2 = fixed_point::log2(fixed_point::from_subunits(self.0.into(), Self::SCALE))?;
+        Self::from_fixed_point(log2)
+    }
+
+    /// Exponential function (`e^self`) of a PreciseDecimal
+    ///
+    /// The result is rounded to the nearest PreciseDecimal, and is within 1 ulp (`10^-36`) of the
+    /// exact value. Returns `None` if the result overflows.
+    pub fn checked_exp(&self) -> Option<Self> {
+        let exp = fixed_point::exp(fixed_point::from_subunits(self.0.into(), Self::SCALE))?;
+        Self::from_fixed_point(exp)
+    }
+
+    /// Raises a PreciseDecimal to a PreciseDecimal power
+    ///
+    /// Integer exponents are evaluated by repeated squaring, any other as
+    /// `exp(exponent * ln(self))`. The result is rounded to the nearest PreciseDecimal, and is
+    /// within 1 ulp (`10^-36`) of the exact value for results below `10^15` in magnitude. Above
+    /// it, the relative error of non-integer powers is below `10^-51`.
+    ///
+    /// Returns `None` if the result overflows or is undefined, i.e. for a negative number raised to
+    /// a non-integer power, or zero raised to a negative power.
+    pub fn checked_pow(&self, exponent: Self) -> Option<Self> {
+        let pow = fixed_point::pow(
+            fixed_point::from_subunits(self.0.into(), Self::SCALE),
+            fixed_point::from_subunits(exponent.0.into(), Self::SCALE),
+        )?;
+        Self::from_fixed_point(pow)
+    }
+
+    /// Calculates `self * multiplier / divisor` with a single rounding
+    ///
+    /// The intermediate product is computed exactly (in double the width), so it can't overflow and
+    /// no precision is lost before the quotient is rounded according to the given mode. Returns
+    /// `None` if the divisor is zero or the result overflows.
+    pub fn checked_mul_div(
+        &self,
+        multiplier: Self,
+        divisor: Self,
+        mode: RoundingMode,
+    ) -> Option<Self> {
+        let product = I768::from(self.0) * I768::from(multiplier.0);
+        let quotient = fixed_point::checked_div_rounded(product, I768::from(divisor.0), mode)?;
+        I256::try_from(quotient).ok().map(Self)
+    }
+
+    fn from_fixed_point(value: I768) -> Option<Self> {
+        I256::try_from(fixed_point::to_subunits(value, Self::SCALE))
+            .ok()
+            .map(Self)
+    }
 }
 
 macro_rules! from_primitive_type {
@@ -2161,6 +2228,309 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn test_ln() {
+        assert_eq!(
+            test_pdec!("2").checked_ln().unwrap(),
+            test_pdec!("0.693147180559945309417232121458176568")
+        );
+        assert_eq!(
+            test_pdec!("10").checked_ln().unwrap(),
+            test_pdec!("2.302585092994045684017991454684364208")
+        );
+        assert_eq!(
+            test_pdec!("0.5").checked_ln().unwrap(),
+            test_pdec!("-0.693147180559945309417232121458176568")
+        );
+        assert_eq!(
+            test_pdec!("3.14159").checked_ln().unwrap(),
+            test_pdec!("1.144729041185178381216412580436159459")
+        );
+        assert_eq!(
+            test_pdec!("123456789.123456789").checked_ln().unwrap(),
+            test_pdec!("18.631401767168018032693933348296537543")
+        );
+        assert_eq!(
+            PreciseDecimal(I256::ONE).checked_ln().unwrap(),
+            test_pdec!("-82.893063347785644624647692368637111474")
+        );
+        assert_eq!(test_pdec!("1").checked_ln().unwrap(), PreciseDecimal::ZERO);
+        assert_eq!(test_pdec!("0").checked_ln(), None);
+        assert_eq!(test_pdec!("-1").checked_ln(), None);
+    }
+
+    #[test]
+    fn test_log2() {
+        assert_eq!(test_pdec!("8").checked_log2().unwrap(), test_pdec!("3"));
+        assert_eq!(test_pdec!("0.25").checked_log2().unwrap(), test_pdec!("-2"));
+        assert_eq!(
+            test_pdec!("10").checked_log2().unwrap(),
+            test_pdec!("3.321928094887362347870319429489390176")
+        );
+        assert_eq!(
+            test_pdec!("3").checked_log2().unwrap(),
+            test_pdec!("1.584962500721156181453738943947816509")
+        );
+        assert_eq!(test_pdec!("0").checked_log2(), None);
+    }
+
+    #[test]
+    fn test_exp() {
+        assert_eq!(test_pdec!("0").checked_exp().unwrap(), PreciseDecimal::ONE);
+        assert_eq!(
+            test_pdec!("1").checked_exp().unwrap(),
+            test_pdec!("2.718281828459045235360287471352662498")
+        );
+        assert_eq!(
+            test_pdec!("-1").checked_exp().unwrap(),
+            test_pdec!("0.367879441171442321595523770161460867")
+        );
+        assert_eq!(
+            test_pdec!("2.5").checked_exp().unwrap(),
+            test_pdec!("12.182493960703473438070175951167966183")
+        );
+        assert_eq!(
+            test_pdec!("-20.75").checked_exp().unwrap(),
+            test_pdec!("0.000000000973620031300956540946712134")
+        );
+        assert_eq!(
+            test_pdec!("90").checked_exp().unwrap(),
+            test_pdec!(
+                "1220403294317840802002710035136369753970.746421099767546244343829824312727359"
+            )
+        );
+        assert_eq!(
+            test_pdec!("-41").checked_exp().unwrap(),
+            test_pdec!("0.000000000000000001562882189334988768")
+        );
+        assert_eq!(
+            test_pdec!("-1000").checked_exp().unwrap(),
+            PreciseDecimal::ZERO
+        );
+        assert_eq!(test_pdec!("100").checked_exp(), None);
+        assert_eq!(PreciseDecimal::MAX.checked_exp(), None);
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(
+            test_pdec!("2").checked_pow(test_pdec!("0.5")).unwrap(),
+            test_pdec!("1.414213562373095048801688724209698079")
+        );
+        assert_eq!(
+            test_pdec!("4").checked_pow(test_pdec!("-0.5")).unwrap(),
+            test_pdec!("0.5")
+        );
+        assert_eq!(
+            test_pdec!("1.5").checked_pow(test_pdec!("2.5")).unwrap(),
+            test_pdec!("2.755675960631075360471944584044127816")
+        );
+        assert_eq!(
+            test_pdec!("10").checked_pow(test_pdec!("-3")).unwrap(),
+            test_pdec!("0.001")
+        );
+        assert_eq!(
+            test_pdec!("0.9").checked_pow(test_pdec!("100.1")).unwrap(),
+            test_pdec!("0.000026283015724164928325141309672958")
+        );
+        assert_eq!(
+            test_pdec!("123.456")
+                .checked_pow(test_pdec!("7.89"))
+                .unwrap(),
+            test_pdec!("31771028258180977.309068659682205126175882467291598657")
+        );
+        assert_eq!(
+            test_pdec!("2").checked_pow(test_pdec!("10")).unwrap(),
+            test_pdec!("1024")
+        );
+        assert_eq!(
+            test_pdec!("-2").checked_pow(test_pdec!("3")).unwrap(),
+            test_pdec!("-8")
+        );
+        assert_eq!(
+            test_pdec!("-2").checked_pow(test_pdec!("-2")).unwrap(),
+            test_pdec!("0.25")
+        );
+        assert_eq!(
+            test_pdec!("0").checked_pow(test_pdec!("0")).unwrap(),
+            PreciseDecimal::ONE
+        );
+        assert_eq!(
+            test_pdec!("0").checked_pow(test_pdec!("0.5")).unwrap(),
+            PreciseDecimal::ZERO
+        );
+        assert_eq!(test_pdec!("0").checked_pow(test_pdec!("-1")), None);
+        assert_eq!(test_pdec!("-8").checked_pow(test_pdec!("0.5")), None);
+        assert_eq!(test_pdec!("10").checked_pow(test_pdec!("100")), None);
+        assert_eq!(
+            test_pdec!("10").checked_pow(test_pdec!("-100")).unwrap(),
+            PreciseDecimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_mul_div() {
+        let third = |mode| {
+            test_pdec!("1")
+                .checked_mul_div(test_pdec!("1"), test_pdec!("3"), mode)
+                .unwrap()
+        };
+        assert_eq!(
+            third(RoundingMode::ToZero),
+            test_pdec!("0.333333333333333333333333333333333333")
+        );
+        assert_eq!(
+            third(RoundingMode::AwayFromZero),
+            test_pdec!("0.333333333333333333333333333333333334")
+        );
+        assert_eq!(
+            third(RoundingMode::ToNearestMidpointAwayFromZero),
+            test_pdec!("0.333333333333333333333333333333333333")
+        );
+        let minus_third = |mode| {
+            test_pdec!("-1")
+                .checked_mul_div(test_pdec!("1"), test_pdec!("3"), mode)
+                .unwrap()
+        };
+        assert_eq!(
+            minus_third(RoundingMode::ToNegativeInfinity),
+            test_pdec!("-0.333333333333333333333333333333333334")
+        );
+        assert_eq!(
+            minus_third(RoundingMode::ToPositiveInfinity),
+            test_pdec!("-0.333333333333333333333333333333333333")
+        );
+
+        let half_ulp = |mode| {
+            PreciseDecimal(I256::ONE)
+                .checked_mul_div(test_pdec!("1"), test_pdec!("2"), mode)
+                .unwrap()
+        };
+        assert_eq!(
+            half_ulp(RoundingMode::ToNearestMidpointToEven),
+            PreciseDecimal::ZERO
+        );
+        assert_eq!(
+            half_ulp(RoundingMode::ToNearestMidpointAwayFromZero),
+            PreciseDecimal(I256::ONE)
+        );
+
+        // The intermediate product doesn't overflow
+        assert_eq!(
+            PreciseDecimal::MAX
+                .checked_mul_div(test_pdec!("2"), test_pdec!("2"), RoundingMode::ToZero)
+                .unwrap(),
+            PreciseDecimal::MAX
+        );
+        assert_eq!(
+            PreciseDecimal::MAX.checked_mul_div(
+                test_pdec!("2"),
+                test_pdec!("1"),
+                RoundingMode::ToZero
+            ),
+            None
+        );
+        assert_eq!(
+            test_pdec!("1").checked_mul_div(test_pdec!("1"), test_pdec!("0"), RoundingMode::ToZero),
+            None
+        );
+    }
+
+    /// A deterministic sequence of positive numbers, spread over many orders of magnitude.
+    fn pseudo_random_numbers(count: usize) -> Vec<PreciseDecimal> {
+        let mut state = 0x2545f4914f6cdd1du64;
+        (0..count)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let mantissa = PreciseDecimal(I256::from(state >> 1));
+                let magnitude = test_pdec!("10").checked_powi((state % 41) as i64).unwrap();
+                mantissa * magnitude
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transcendental_properties() {
+        let ulp = PreciseDecimal(I256::ONE);
+        for x in pseudo_random_numbers(200) {
+            // exp(ln(x)) = x, where the rounding of ln(x) is magnified by x
+            let error = (x.checked_ln().unwrap().checked_exp().unwrap() - x)
+                .checked_abs()
+                .unwrap();
+            assert!(
+                error <= x * test_pdec!("0.000000000000000000000000000000000002") + ulp,
+                "exp(ln({}))",
+                x
+            );
+
+            // ln(x * y) = ln(x) + ln(y), for x, y >= 1 (so that the product is rounded by less
+            // than 1 ulp)
+            let y = x.checked_sqrt().unwrap() + test_pdec!("1");
+            let x = x + test_pdec!("1");
+            let error =
+                ((x * y).checked_ln().unwrap() - x.checked_ln().unwrap() - y.checked_ln().unwrap())
+                    .checked_abs()
+                    .unwrap();
+            assert!(error <= ulp * 3, "ln({} * {})", x, y);
+
+            // Powers agree with the correctly rounded results of `checked_mul_div`
+            let nearest = RoundingMode::ToNearestMidpointToEven;
+            assert_eq!(
+                y.checked_pow(test_pdec!("2")).unwrap(),
+                y.checked_mul_div(y, PreciseDecimal::ONE, nearest).unwrap()
+            );
+            assert_eq!(
+                y.checked_pow(test_pdec!("-1")).unwrap(),
+                PreciseDecimal::ONE
+                    .checked_mul_div(PreciseDecimal::ONE, y, nearest)
+                    .unwrap()
+            );
+            let error = (y.checked_pow(test_pdec!("0.5")).unwrap() - y.checked_sqrt().unwrap())
+                .checked_abs()
+                .unwrap();
+            assert!(error <= ulp, "{}^0.5", y);
+        }
+
+        for n in 0..=131i64 {
+            let power_of_two = test_pdec!("2").checked_powi(n).unwrap();
+            assert_eq!(
+                power_of_two.checked_log2().unwrap(),
+                PreciseDecimal::from(n)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mul_div_properties() {
+        let numbers = pseudo_random_numbers(100);
+        for window in numbers.windows(3) {
+            // Keeps the results within range
+            let (x, multiplier, divisor) = (
+                window[0],
+                window[1].checked_sqrt().unwrap(),
+                window[2] + PreciseDecimal::ONE,
+            );
+            let down = x
+                .checked_mul_div(multiplier, divisor, RoundingMode::ToNegativeInfinity)
+                .unwrap();
+            let up = x
+                .checked_mul_div(multiplier, divisor, RoundingMode::ToPositiveInfinity)
+                .unwrap();
+            let nearest = x
+                .checked_mul_div(multiplier, divisor, RoundingMode::ToNearestMidpointToEven)
+                .unwrap();
+            assert!(up - down <= PreciseDecimal(I256::ONE));
+            assert!(down <= nearest && nearest <= up);
+            assert_eq!(
+                x.checked_mul_div(divisor, divisor, RoundingMode::ToZero)
+                    .unwrap(),
+                x
+            );
+        }
+    }
+
     #[test]
     fn test_neg_precise_decimal() {
         let d = PreciseDecimal::ONE;
@@ -137,6 +137,15 @@ impl KeyValueStoreDataSchema {
     }
 }
 
+/// A page of entries returned by a key value store scan.
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub struct KeyValueStoreEntriesPage {
+    /// The SBOR-encoded keys and values of the entries in this page
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+    /// The cursor to pass in to continue the scan, or `None` if the scan is complete
+    pub next_cursor: Option<Vec<u8>>,
+}
+
 pub trait ClientKeyValueStoreApi<E> {
     /// Creates a new key value store with a given schema
     fn key_value_store_new(&mut self, data_schema: KeyValueStoreDataSchema) -> Result<NodeId, E>;
@@ -154,4 +163,17 @@ pub trait ClientKeyValueStoreApi<E> {
         node_id: &NodeId,
         key: &Vec<u8>,
     ) -> Result<Vec<u8>, E>;
+
+    /// Scans up to `limit` entries of a key value store, starting strictly after the
+    /// given cursor key (or from the start if `None`). The `limit` must not be zero.
+    ///
+    /// Entries are returned in a deterministic but unspecified order, which is stable
+    /// across calls (whether or not the store has been persisted in between), so that a
+    /// scan can be resumed with the returned cursor.
+    fn key_value_store_scan_entries(
+        &mut self,
+        node_id: &NodeId,
+        cursor: Option<Vec<u8>>,
+        limit: u32,
+    ) -> Result<KeyValueStoreEntriesPage, E>;
 }
@@ -6,7 +6,7 @@ use radix_engine_common::prelude::ManifestBucket;
 use radix_engine_common::prelude::CONSENSUS_MANAGER_PACKAGE;
 use radix_engine_common::time::{Instant, TimeComparisonOperator};
 use radix_engine_common::types::*;
-use radix_engine_interface::crypto::Secp256k1PublicKey;
+use radix_engine_interface::crypto::{Hash, Secp256k1PublicKey};
 use radix_engine_interface::math::{traits::*, Decimal};
 use sbor::rust::fmt::Debug;
 use sbor::rust::string::String;
@@ -14,6 +14,7 @@ use sbor::rust::vec::Vec;
 
 pub const CONSENSUS_MANAGER_BLUEPRINT: &str = "ConsensusManager";
 pub const VALIDATOR_BLUEPRINT: &str = "Validator";
+pub const SCHEDULER_BLUEPRINT: &str = "Scheduler";
 
 define_type_info_marker!(Some(CONSENSUS_MANAGER_PACKAGE), ConsensusManager);
 define_type_info_marker!(Some(CONSENSUS_MANAGER_PACKAGE), Validator);
@@ -228,6 +229,33 @@ pub struct ConsensusManagerCompareCurrentTimeInput {
 
 pub type ConsensusManagerCompareCurrentTimeOutput = bool;
 
+pub const CONSENSUS_MANAGER_COMMIT_RANDOM_SEED_IDENT: &str = "commit_random_seed";
+
+/// A commitment to the random seed of the first round following the one in which the commitment
+/// was made - i.e. to a value which was unknown to anyone at the time of committing.
+///
+/// Note that a commitment can be forged for any past round, so it is only meaningful when it is
+/// stored together with whatever it should randomize (e.g. a bet), at the time of committing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Sbor)]
+pub struct RandomSeedCommitment {
+    /// The sequence number of the round (counted since genesis) in which the commitment was made.
+    pub round_sequence: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Sbor)]
+pub struct ConsensusManagerCommitRandomSeedInput;
+
+pub type ConsensusManagerCommitRandomSeedOutput = RandomSeedCommitment;
+
+pub const CONSENSUS_MANAGER_GET_RANDOM_SEED_IDENT: &str = "get_random_seed";
+
+#[derive(Debug, Clone, Eq, PartialEq, Sbor)]
+pub struct ConsensusManagerGetRandomSeedInput {
+    pub commitment: RandomSeedCommitment,
+}
+
+pub type ConsensusManagerGetRandomSeedOutput = Hash;
+
 pub const CONSENSUS_MANAGER_NEXT_ROUND_IDENT: &str = "next_round";
 
 #[derive(Debug, Clone, Eq, PartialEq, Sbor)]
@@ -269,6 +297,19 @@ impl ConsensusManagerNextRoundInput {
             },
         }
     }
+
+    /// Adds the current leader's contribution to the randomness beacon.
+    pub fn with_randomness(
+        self,
+        randomness: RandomnessContribution,
+    ) -> ConsensusManagerNextRoundWithRandomnessInput {
+        ConsensusManagerNextRoundWithRandomnessInput {
+            round: self.round,
+            proposer_timestamp_ms: self.proposer_timestamp_ms,
+            leader_proposal_history: self.leader_proposal_history,
+            randomness,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Sbor)]
@@ -296,6 +337,36 @@ pub struct LeaderProposalHistory {
 
 pub type ConsensusManagerNextRoundOutput = ();
 
+pub const CONSENSUS_MANAGER_NEXT_ROUND_WITH_RANDOMNESS_IDENT: &str = "next_round_with_randomness";
+
+/// The same as [`ConsensusManagerNextRoundInput`], with the current leader's contribution to the
+/// randomness beacon. A round change without a contribution still advances the beacon, but only
+/// deterministically (and hence predictably).
+#[derive(Debug, Clone, Eq, PartialEq, Sbor)]
+pub struct ConsensusManagerNextRoundWithRandomnessInput {
+    pub round: Round,
+    pub proposer_timestamp_ms: i64,
+    pub leader_proposal_history: LeaderProposalHistory,
+    pub randomness: RandomnessContribution,
+}
+
+pub type ConsensusManagerNextRoundWithRandomnessOutput = ();
+
+/// A leader's contribution to the randomness beacon, under a commit-reveal scheme: the leader
+/// reveals the secret committed to in its previous contribution, and commits to the secret it will
+/// reveal in its next one.
+///
+/// Since every revealed secret is fixed before the seed it is mixed into is known, the leader
+/// cannot grind the seed; it can only withhold its proposal altogether.
+#[derive(Debug, Clone, Eq, PartialEq, Sbor)]
+pub struct RandomnessContribution {
+    /// The secret whose hash the leader committed to in its previous contribution, or `None` if
+    /// this is the leader's first contribution.
+    pub reveal: Option<Hash>,
+    /// The hash of the secret to be revealed in the leader's next contribution.
+    pub next_commitment: Hash,
+}
+
 pub const CONSENSUS_MANAGER_CREATE_VALIDATOR_IDENT: &str = "create_validator";
 
 #[derive(Debug, Eq, PartialEq, ScryptoSbor)]
@@ -515,3 +586,98 @@ pub const VALIDATOR_FINISH_UNLOCK_OWNER_STAKE_UNITS_IDENT: &str = "finish_unlock
 pub struct ValidatorFinishUnlockOwnerStakeUnitsInput {}
 
 pub type ValidatorFinishUnlockOwnerStakeUnitsOutput = Bucket;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ScryptoSbor, ManifestSbor)]
+#[sbor(transparent)]
+pub struct ScheduledCallId(pub u64);
+
+/// The condition upon which a scheduled call becomes due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ScryptoSbor, ManifestSbor)]
+pub enum ScheduledCallTrigger {
+    /// The call becomes due on the first round of the given epoch (or of any later epoch).
+    AtEpoch(Epoch),
+    /// The call becomes due on the first round whose proposer timestamp, rounded down to the
+    /// minute, is not earlier than the given instant.
+    AtTime(Instant),
+}
+
+pub const SCHEDULER_CREATE_IDENT: &str = "create";
+
+#[derive(Debug, Eq, PartialEq, ScryptoSbor)]
+pub struct SchedulerCreateInput {
+    pub address_reservation: GlobalAddressReservation,
+}
+
+#[derive(Debug, Eq, PartialEq, ManifestSbor)]
+pub struct SchedulerCreateManifestInput {
+    pub address_reservation: ManifestAddressReservation,
+}
+
+pub type SchedulerCreateOutput = ComponentAddress;
+
+pub const SCHEDULER_SCHEDULE_CALL_IDENT: &str = "schedule_call";
+
+#[derive(Debug, Eq, PartialEq, ScryptoSbor)]
+pub struct SchedulerScheduleCallInput {
+    pub target: ComponentAddress,
+    pub method_name: String,
+    /// The SBOR-encoded arguments of the call, which may not contain any owned nodes.
+    pub args: Vec<u8>,
+    pub trigger: ScheduledCallTrigger,
+    /// The XRD to lock the fee of the call's execution from. Once the call has been attempted,
+    /// whatever the execution did not cost goes to the validators. It is returned in full if the
+    /// call is cancelled, or if it expires without having been attempted.
+    pub fee: Bucket,
+    /// The rule which must be satisfied in order to cancel the call.
+    pub cancel_rule: AccessRule,
+}
+
+#[derive(Debug, Eq, PartialEq, ManifestSbor)]
+pub struct SchedulerScheduleCallManifestInput {
+    pub target: ComponentAddress,
+    pub method_name: String,
+    pub args: Vec<u8>,
+    pub trigger: ScheduledCallTrigger,
+    pub fee: ManifestBucket,
+    pub cancel_rule: AccessRule,
+}
+
+pub type SchedulerScheduleCallOutput = ScheduledCallId;
+
+pub const SCHEDULER_CANCEL_CALL_IDENT: &str = "cancel_call";
+
+#[derive(Debug, Clone, Eq, PartialEq, ScryptoSbor, ManifestSbor)]
+pub struct SchedulerCancelCallInput {
+    pub id: ScheduledCallId,
+}
+
+pub type SchedulerCancelCallOutput = Bucket;
+
+pub const SCHEDULER_PROCESS_DUE_CALLS_IDENT: &str = "process_due_calls";
+
+#[derive(Debug, Clone, Eq, PartialEq, ScryptoSbor, ManifestSbor)]
+pub struct SchedulerProcessDueCallsInput {
+    pub epoch: Epoch,
+    pub proposer_timestamp_ms: i64,
+}
+
+/// The fees of the calls settled since the previous round change, if any.
+pub type SchedulerProcessDueCallsOutput = Option<Bucket>;
+
+pub const SCHEDULER_EXECUTE_CALL_IDENT: &str = "execute_call";
+
+#[derive(Debug, Clone, Eq, PartialEq, ScryptoSbor, ManifestSbor)]
+pub struct SchedulerExecuteCallInput {
+    pub id: ScheduledCallId,
+}
+
+pub type SchedulerExecuteCallOutput = ();
+
+pub const SCHEDULER_RECORD_FAILED_CALL_IDENT: &str = "record_failed_call";
+
+#[derive(Debug, Clone, Eq, PartialEq, ScryptoSbor, ManifestSbor)]
+pub struct SchedulerRecordFailedCallInput {
+    pub id: ScheduledCallId,
+}
+
+pub type SchedulerRecordFailedCallOutput = ();
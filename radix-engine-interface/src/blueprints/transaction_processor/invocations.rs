@@ -5,6 +5,7 @@ use sbor::rust::prelude::*;
 pub const TRANSACTION_PROCESSOR_BLUEPRINT: &str = "TransactionProcessor";
 
 pub const TRANSACTION_PROCESSOR_RUN_IDENT: &str = "run";
+pub const TRANSACTION_PROCESSOR_RUN_V2_IDENT: &str = "run_v2";
 
 // TransactionProcessorInput in the engine
 
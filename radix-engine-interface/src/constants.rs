@@ -47,6 +47,7 @@ lazy_static! {
             // components
             CONSENSUS_MANAGER.into(),
             TRANSACTION_TRACKER.into(),
+            SCHEDULER.into(),
         )
     };
 }
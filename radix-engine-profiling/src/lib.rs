@@ -7,3 +7,6 @@ pub use qemu_plugin_interface::*;
 pub mod rocks_db_metrics;
 #[cfg(feature = "rocksdb")]
 pub use rocks_db_metrics::*;
+
+#[cfg(feature = "calibration")]
+pub mod weights_calibration;
@@ -108,6 +108,14 @@ impl<S: SubstateDatabase + CommittableSubstateDatabase> SubstateDatabase
     ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
         self.db.list_entries(partition_key)
     }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: &DbSortKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.db.list_entries_from(partition_key, from_sort_key)
+    }
 }
 
 impl<S: SubstateDatabase + CommittableSubstateDatabase> CommittableSubstateDatabase
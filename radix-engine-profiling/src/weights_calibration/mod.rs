@@ -0,0 +1,417 @@
+use linreg::linear_regression;
+use std::{collections::BTreeMap, fmt::Write};
+
+mod source_patch;
+pub use source_patch::*;
+
+#[cfg(test)]
+mod tests;
+
+/// Clock frequency (MHz) of the reference machine (EC2 c5.4xlarge) the fee table is calibrated for.
+/// Used to convert measured CPU instructions to picoseconds, which is the unit of `weights.rs`.
+pub const REFERENCE_CPU_FREQUENCY_MHZ: u64 = 3_400;
+
+/// Conversion ratio of CPU instructions to cost units, same as in `fee_table.rs`.
+pub const CPU_INSTRUCTIONS_TO_COST_UNIT: u64 = 34;
+
+/// Single measurement of a benchmark which repeats the measured operation `repetitions` times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalibrationSample {
+    pub repetitions: u64,
+    pub cpu_instructions: u64,
+}
+
+/// Linear approximation `base + per_repetition * repetitions` of measured CPU instructions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+    pub base: f64,
+    pub per_repetition: f64,
+    /// Standard error of `per_repetition`.
+    pub standard_error: f64,
+    /// Coefficient of determination, the closer to 1 the better the fit.
+    pub r_squared: f64,
+    /// Smallest measured value.
+    pub min_cpu_instructions: u64,
+    pub samples_count: usize,
+}
+
+impl LinearFit {
+    /// Fits a line to the samples. Samples with the same repetitions count are reduced to
+    /// their median first, to filter out measurements disturbed by the OS scheduler.
+    ///
+    /// Returns `None` if there are less than two distinct repetitions counts.
+    pub fn from_samples(samples: &[CalibrationSample]) -> Option<Self> {
+        let mut grouped: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        for sample in samples {
+            grouped
+                .entry(sample.repetitions)
+                .or_default()
+                .push(sample.cpu_instructions);
+        }
+        if grouped.len() < 2 {
+            return None;
+        }
+
+        let mut xs = Vec::with_capacity(grouped.len());
+        let mut ys = Vec::with_capacity(grouped.len());
+        for (repetitions, mut values) in grouped {
+            values.sort_unstable();
+            xs.push(repetitions as f64);
+            ys.push(values[values.len() / 2] as f64);
+        }
+
+        let (per_repetition, base): (f64, f64) = linear_regression(&xs, &ys).ok()?;
+
+        let n = xs.len() as f64;
+        let x_mean = xs.iter().sum::<f64>() / n;
+        let y_mean = ys.iter().sum::<f64>() / n;
+        let mut ss_residual = 0f64;
+        let mut ss_total = 0f64;
+        let mut ss_x = 0f64;
+        for (x, y) in xs.iter().zip(ys.iter()) {
+            let residual = y - (base + per_repetition * x);
+            ss_residual += residual * residual;
+            ss_total += (y - y_mean) * (y - y_mean);
+            ss_x += (x - x_mean) * (x - x_mean);
+        }
+        let r_squared = if ss_total > 0f64 {
+            1f64 - ss_residual / ss_total
+        } else {
+            1f64
+        };
+        let standard_error = if xs.len() > 2 && ss_x > 0f64 {
+            (ss_residual / (n - 2f64) / ss_x).sqrt()
+        } else {
+            0f64
+        };
+
+        Some(Self {
+            base,
+            per_repetition,
+            standard_error,
+            r_squared,
+            min_cpu_instructions: samples.iter().map(|s| s.cpu_instructions).min().unwrap(),
+            samples_count: samples.len(),
+        })
+    }
+}
+
+/// Converts CPU instructions to picoseconds on the reference machine.
+pub fn cpu_instructions_to_picoseconds(cpu_instructions: f64) -> u64 {
+    (cpu_instructions * 1_000_000f64 / REFERENCE_CPU_FREQUENCY_MHZ as f64)
+        .round()
+        .max(0f64) as u64
+}
+
+/// Converts CPU instructions to cost units.
+pub fn cpu_instructions_to_cost_units(cpu_instructions: f64) -> f64 {
+    (cpu_instructions / CPU_INSTRUCTIONS_TO_COST_UNIT as f64).max(0f64)
+}
+
+/// Collects benchmark measurements and fits costs of:
+/// - WASM instructions, identified by the `WeightInfo` function name in `weights.rs` (eg. `instr_i64add`),
+/// - `FeeTable` entries, identified by the `FeeTable` method name (eg. `instantiate_wasm_code_cost`),
+/// - native blueprint functions, identified by package address (hex) and export name as in
+///   `native_function_base_costs.csv`.
+#[derive(Debug, Clone, Default)]
+pub struct WeightsCalibration {
+    instructions: BTreeMap<String, Vec<CalibrationSample>>,
+    fee_table_entries: BTreeMap<String, FeeTableEntryMeasurements>,
+    native_functions: BTreeMap<(String, String), Vec<CalibrationSample>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FeeTableEntryMeasurements {
+    samples: Vec<CalibrationSample>,
+    current: Option<(f64, f64)>,
+}
+
+impl WeightsCalibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_instruction_sample(&mut self, weight_fn: &str, sample: CalibrationSample) {
+        self.instructions
+            .entry(weight_fn.to_string())
+            .or_default()
+            .push(sample);
+    }
+
+    /// Adds a sample of a fee table entry, where `repetitions` is the variable the entry
+    /// is costed by (eg. size in bytes).
+    pub fn add_fee_table_entry_sample(&mut self, entry: &str, sample: CalibrationSample) {
+        self.fee_table_entries
+            .entry(entry.to_string())
+            .or_default()
+            .samples
+            .push(sample);
+    }
+
+    /// Records the currently charged cost units of a fee table entry, for comparison.
+    pub fn set_fee_table_entry_current_cost(
+        &mut self,
+        entry: &str,
+        base_cost_units: f64,
+        per_repetition_cost_units: f64,
+    ) {
+        self.fee_table_entries
+            .entry(entry.to_string())
+            .or_default()
+            .current = Some((base_cost_units, per_repetition_cost_units));
+    }
+
+    /// Adds a sample of a native function, where `repetitions` is the number of calls.
+    pub fn add_native_function_sample(
+        &mut self,
+        package_address_hex: &str,
+        export_name: &str,
+        sample: CalibrationSample,
+    ) {
+        self.native_functions
+            .entry((package_address_hex.to_string(), export_name.to_string()))
+            .or_default()
+            .push(sample);
+    }
+
+    pub fn fit(&self) -> CalibrationReport {
+        CalibrationReport {
+            instruction_weights: self
+                .instructions
+                .iter()
+                .filter_map(|(weight_fn, samples)| {
+                    LinearFit::from_samples(samples).map(|fit| InstructionWeightFit {
+                        weight_fn: weight_fn.clone(),
+                        fit,
+                    })
+                })
+                .collect(),
+            fee_table_entries: self
+                .fee_table_entries
+                .iter()
+                .filter_map(|(entry, measurements)| {
+                    LinearFit::from_samples(&measurements.samples).map(|fit| FeeTableEntryFit {
+                        entry: entry.clone(),
+                        fit,
+                        current: measurements.current,
+                    })
+                })
+                .collect(),
+            native_functions: self
+                .native_functions
+                .iter()
+                .filter_map(|((package_address_hex, export_name), samples)| {
+                    LinearFit::from_samples(samples).map(|fit| NativeFunctionFit {
+                        package_address_hex: package_address_hex.clone(),
+                        export_name: export_name.clone(),
+                        fit,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstructionWeightFit {
+    pub weight_fn: String,
+    pub fit: LinearFit,
+}
+
+impl InstructionWeightFit {
+    /// Proposed `(base, per_repetition)` weights in picoseconds.
+    pub fn proposed_weights(&self) -> (u64, u64) {
+        (
+            cpu_instructions_to_picoseconds(self.fit.base),
+            cpu_instructions_to_picoseconds(self.fit.per_repetition),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeTableEntryFit {
+    pub entry: String,
+    pub fit: LinearFit,
+    /// Currently charged `(base, per_repetition)` cost units, if known.
+    pub current: Option<(f64, f64)>,
+}
+
+impl FeeTableEntryFit {
+    /// Proposed `(base, per_repetition)` cost units.
+    pub fn proposed_cost_units(&self) -> (f64, f64) {
+        (
+            cpu_instructions_to_cost_units(self.fit.base),
+            cpu_instructions_to_cost_units(self.fit.per_repetition),
+        )
+    }
+
+    /// Proposed constant of the same form as the given current one.
+    pub fn proposed_constant(&self, current: FeeTableConstant) -> FeeTableConstant {
+        current.with_cost_units_per_unit(self.proposed_cost_units().1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeFunctionFit {
+    pub package_address_hex: String,
+    pub export_name: String,
+    pub fit: LinearFit,
+}
+
+impl NativeFunctionFit {
+    /// Proposed CPU instructions per call, as stored in `native_function_base_costs.csv`.
+    ///
+    /// Note that measured calls also include the invocation overhead, which is costed
+    /// separately, so this is an upper bound of the native execution cost.
+    pub fn proposed_cpu_instructions(&self) -> u64 {
+        self.fit.per_repetition.round().max(0f64) as u64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationReport {
+    pub instruction_weights: Vec<InstructionWeightFit>,
+    pub fee_table_entries: Vec<FeeTableEntryFit>,
+    pub native_functions: Vec<NativeFunctionFit>,
+}
+
+impl CalibrationReport {
+    /// Renders the report in markdown, comparing proposed costs with the current ones found
+    /// in `weights.rs`, `fee_table.rs` and `native_function_base_costs.csv` sources.
+    pub fn to_markdown(
+        &self,
+        weights_rs: &str,
+        fee_table_rs: &str,
+        native_function_costs_csv: &str,
+    ) -> String {
+        let mut out = String::new();
+        writeln!(out, "# Weights calibration report").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "Reference CPU: {} MHz, {} CPU instructions per cost unit.",
+            REFERENCE_CPU_FREQUENCY_MHZ, CPU_INSTRUCTIONS_TO_COST_UNIT
+        )
+        .unwrap();
+
+        writeln!(out).unwrap();
+        writeln!(out, "## WASM instruction weights (picoseconds)").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "| Weight | Samples | R² | Current base | Proposed base | Current per repetition | Proposed per repetition | Change |"
+        )
+        .unwrap();
+        writeln!(out, "|---|---|---|---|---|---|---|---|").unwrap();
+        for item in &self.instruction_weights {
+            let (base, per_repetition) = item.proposed_weights();
+            let current = find_instruction_weight(weights_rs, &item.weight_fn);
+            writeln!(
+                out,
+                "| {} | {} | {:.4} | {} | {} | {} | {} | {} |",
+                item.weight_fn,
+                item.fit.samples_count,
+                item.fit.r_squared,
+                format_optional(current.map(|x| x.base)),
+                format_number(base),
+                format_optional(current.map(|x| x.per_repetition)),
+                format_number(per_repetition),
+                format_change(
+                    current.map(|x| x.per_repetition as f64),
+                    per_repetition as f64
+                ),
+            )
+            .unwrap();
+        }
+
+        writeln!(out).unwrap();
+        writeln!(out, "## FeeTable entries (cost units)").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "Entries with a constant in `fee_table.rs` are updated by `fee_table.rs.diff`, the other entries need to be updated manually."
+        )
+        .unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "| Entry | Samples | R² | Current base | Proposed base | Current per unit | Proposed per unit | Change | Current constant | Proposed constant |"
+        )
+        .unwrap();
+        writeln!(out, "|---|---|---|---|---|---|---|---|---|---|").unwrap();
+        for item in &self.fee_table_entries {
+            let (base, per_repetition) = item.proposed_cost_units();
+            let current_constant = find_fee_table_constant(fee_table_rs, &item.entry);
+            writeln!(
+                out,
+                "| {} | {} | {:.4} | {} | {:.3} | {} | {:.3} | {} | {} | {} |",
+                item.entry,
+                item.fit.samples_count,
+                item.fit.r_squared,
+                format_optional_cost_units(item.current.map(|x| x.0)),
+                base,
+                format_optional_cost_units(item.current.map(|x| x.1)),
+                per_repetition,
+                format_change(item.current.map(|x| x.1), per_repetition),
+                current_constant
+                    .map(|x| x.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                current_constant
+                    .map(|x| item.proposed_constant(x).to_string())
+                    .unwrap_or_else(|| "manual".to_string()),
+            )
+            .unwrap();
+        }
+
+        writeln!(out).unwrap();
+        writeln!(out, "## Native functions (CPU instructions per call)").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "| Package | Export | Samples | R² | Current | Proposed | Change |"
+        )
+        .unwrap();
+        writeln!(out, "|---|---|---|---|---|---|---|").unwrap();
+        for item in &self.native_functions {
+            let proposed = item.proposed_cpu_instructions();
+            let current = find_native_function_cost(
+                native_function_costs_csv,
+                &item.package_address_hex,
+                &item.export_name,
+            );
+            writeln!(
+                out,
+                "| {} | {} | {} | {:.4} | {} | {} | {} |",
+                item.package_address_hex,
+                item.export_name,
+                item.fit.samples_count,
+                item.fit.r_squared,
+                format_optional(current),
+                format_number(proposed),
+                format_change(current.map(|x| x as f64), proposed as f64),
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+fn format_optional(value: Option<u64>) -> String {
+    value.map(format_number).unwrap_or_else(|| "-".to_string())
+}
+
+fn format_optional_cost_units(value: Option<f64>) -> String {
+    value
+        .map(|x| format!("{:.3}", x))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_change(current: Option<f64>, proposed: f64) -> String {
+    match current {
+        Some(current) if current > 0f64 => {
+            format!("{:+.1}%", (proposed - current) / current * 100f64)
+        }
+        _ => "-".to_string(),
+    }
+}
@@ -0,0 +1,371 @@
+use super::*;
+
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Weights of a `WeightInfo` function in `weights.rs`, in picoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentInstructionWeight {
+    pub base: u64,
+    pub per_repetition: u64,
+}
+
+/// Finds the weights of given `WeightInfo` function (eg. `instr_i64add`) in `weights.rs` source.
+pub fn find_instruction_weight(
+    weights_rs: &str,
+    weight_fn: &str,
+) -> Option<CurrentInstructionWeight> {
+    let mut in_fn = false;
+    let mut base = None;
+    for line in weights_rs.lines() {
+        if is_weight_fn_start(line) {
+            in_fn = weight_fn_name(line) == Some(weight_fn);
+        } else if in_fn {
+            if line.trim_start().starts_with(WEIGHT_FROM_PARTS) {
+                base = number_after(line, WEIGHT_FROM_PARTS);
+            } else if line.contains(PER_REPETITION_FROM_PARTS) {
+                return Some(CurrentInstructionWeight {
+                    base: base?,
+                    per_repetition: number_after(line, PER_REPETITION_FROM_PARTS)?,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// The constant of a `FeeTable` function in `fee_table.rs`, which the cost scales with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeTableConstant {
+    /// A cost expression `mul(cast(x), N)`, ie. `N` cost units per unit of `x`.
+    Multiplier(u64),
+    /// A cost expression `x / N`, ie. one cost unit per `N` units of `x`.
+    Divisor(u64),
+}
+
+impl FeeTableConstant {
+    /// Returns the constant of the same form, which charges the given cost units per unit.
+    ///
+    /// The constant is at least 1, so that a cost is never dropped entirely.
+    pub fn with_cost_units_per_unit(&self, cost_units: f64) -> Self {
+        match self {
+            Self::Multiplier(_) => Self::Multiplier(cost_units.round().max(1f64) as u64),
+            Self::Divisor(_) => Self::Divisor((1f64 / cost_units).round().max(1f64) as u64),
+        }
+    }
+
+    fn with_value(&self, value: u64) -> Self {
+        match self {
+            Self::Multiplier(_) => Self::Multiplier(value),
+            Self::Divisor(_) => Self::Divisor(value),
+        }
+    }
+
+    fn value(&self) -> u64 {
+        match self {
+            Self::Multiplier(value) | Self::Divisor(value) => *value,
+        }
+    }
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            Self::Multiplier(_) => MULTIPLIER_PREFIX,
+            Self::Divisor(_) => DIVISOR_PREFIX,
+        }
+    }
+}
+
+impl std::fmt::Display for FeeTableConstant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Multiplier(value) => write!(f, "× {}", format_number(*value)),
+            Self::Divisor(value) => write!(f, "÷ {}", format_number(*value)),
+        }
+    }
+}
+
+/// Finds the constant of given `FeeTable` function (eg. `run_wasm_code_cost`) in `fee_table.rs`
+/// source.
+///
+/// Only functions with a `mul(cast(x), N)` or `x / N` cost expression have such a constant,
+/// the others (eg. ones which delegate to other functions) need to be updated manually.
+pub fn find_fee_table_constant(fee_table_rs: &str, cost_fn: &str) -> Option<FeeTableConstant> {
+    find_fee_table_constant_line(fee_table_rs, cost_fn).map(|(_, constant)| constant)
+}
+
+/// Finds the cost of a native function in `native_function_base_costs.csv` source.
+pub fn find_native_function_cost(
+    native_function_costs_csv: &str,
+    package_address_hex: &str,
+    export_name: &str,
+) -> Option<u64> {
+    native_function_costs_csv.lines().find_map(|line| {
+        let mut tokens = line.split(',').map(|x| x.trim());
+        if tokens.next() == Some(package_address_hex) && tokens.next() == Some(export_name) {
+            tokens.next().and_then(|x| x.parse().ok())
+        } else {
+            None
+        }
+    })
+}
+
+impl CalibrationReport {
+    /// Returns `weights.rs` source with fitted instruction weights applied.
+    ///
+    /// If any weight changes, `InstructionWeights::version` is bumped as well, so that
+    /// already instrumented code gets re-instrumented.
+    pub fn patch_weights_rs(&self, weights_rs: &str) -> String {
+        let mut lines: Vec<String> = weights_rs.split('\n').map(|x| x.to_string()).collect();
+
+        let mut current: Option<&InstructionWeightFit> = None;
+        let mut changed = false;
+        for line in lines.iter_mut() {
+            if is_weight_fn_start(line) {
+                current = weight_fn_name(line).and_then(|name| {
+                    self.instruction_weights
+                        .iter()
+                        .find(|item| item.weight_fn == name)
+                });
+                continue;
+            }
+            let item = match current {
+                Some(item) => item,
+                None => continue,
+            };
+
+            let (base, per_repetition) = item.proposed_weights();
+            let patched = if line.contains(MINIMUM_EXECUTION_TIME) {
+                replace_number_after(
+                    line,
+                    MINIMUM_EXECUTION_TIME,
+                    cpu_instructions_to_picoseconds(item.fit.min_cpu_instructions as f64),
+                )
+            } else if line.contains(STANDARD_ERROR) {
+                replace_number_after(
+                    line,
+                    STANDARD_ERROR,
+                    cpu_instructions_to_picoseconds(item.fit.standard_error),
+                )
+            } else if line.trim_start().starts_with(WEIGHT_FROM_PARTS) {
+                replace_number_after(line, WEIGHT_FROM_PARTS, base)
+            } else if line.contains(PER_REPETITION_FROM_PARTS) {
+                current = None;
+                replace_number_after(line, PER_REPETITION_FROM_PARTS, per_repetition)
+            } else {
+                continue;
+            };
+
+            if patched != *line {
+                *line = patched;
+                changed = true;
+            }
+        }
+
+        if changed {
+            for line in lines.iter_mut() {
+                if line.trim_start().starts_with(WEIGHTS_VERSION) && line.trim_end().ends_with(',')
+                {
+                    if let Some(version) = number_after(line, WEIGHTS_VERSION) {
+                        *line = replace_number_after(line, WEIGHTS_VERSION, version + 1);
+                    }
+                }
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// Returns `fee_table.rs` source with fitted constants of the fee table entries applied.
+    ///
+    /// Only entries with a constant (see [`find_fee_table_constant`]) are updated.
+    pub fn patch_fee_table_rs(&self, fee_table_rs: &str) -> String {
+        let mut lines: Vec<String> = fee_table_rs.split('\n').map(|x| x.to_string()).collect();
+        for item in &self.fee_table_entries {
+            if let Some((index, constant)) = find_fee_table_constant_line(fee_table_rs, &item.entry)
+            {
+                let proposed = item.proposed_constant(constant);
+                lines[index] =
+                    replace_number_after(&lines[index], proposed.prefix(), proposed.value());
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Returns `native_function_base_costs.csv` source with fitted native function costs applied.
+    ///
+    /// Only existing entries are updated.
+    pub fn patch_native_function_costs_csv(&self, native_function_costs_csv: &str) -> String {
+        native_function_costs_csv
+            .split('\n')
+            .map(|line| {
+                let tokens: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+                if tokens.len() != 3 {
+                    return line.to_string();
+                }
+                match self.native_functions.iter().find(|item| {
+                    item.package_address_hex == tokens[0] && item.export_name == tokens[1]
+                }) {
+                    Some(item) => format!(
+                        "{},{},{}",
+                        tokens[0],
+                        tokens[1],
+                        item.proposed_cpu_instructions()
+                    ),
+                    None => line.to_string(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Renders a unified diff of two versions of a source file.
+///
+/// Patches produced by this module only modify lines in place, so line numbers of both
+/// versions are the same and no alignment of inserted or deleted lines is needed.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    assert_eq!(
+        old_lines.len(),
+        new_lines.len(),
+        "Only in place modifications are supported"
+    );
+
+    let changed: Vec<usize> = (0..old_lines.len())
+        .filter(|i| old_lines[*i] != new_lines[*i])
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Group changed lines into hunks with overlapping context
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for i in changed {
+        let start = i.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (i + DIFF_CONTEXT_LINES + 1).min(old_lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(out, "--- a/{}", path).unwrap();
+    writeln!(out, "+++ b/{}", path).unwrap();
+    for (start, end) in hunks {
+        writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            start + 1,
+            end - start,
+            start + 1,
+            end - start
+        )
+        .unwrap();
+        let mut i = start;
+        while i < end {
+            if old_lines[i] == new_lines[i] {
+                writeln!(out, " {}", old_lines[i]).unwrap();
+                i += 1;
+                continue;
+            }
+            // Emit a run of changed lines as removals followed by additions
+            let run_end = (i..end)
+                .find(|j| old_lines[*j] == new_lines[*j])
+                .unwrap_or(end);
+            for line in &old_lines[i..run_end] {
+                writeln!(out, "-{}", line).unwrap();
+            }
+            for line in &new_lines[i..run_end] {
+                writeln!(out, "+{}", line).unwrap();
+            }
+            i = run_end;
+        }
+    }
+    out
+}
+
+/// Formats a number the way `weights.rs` does, eg. `1_583_300`.
+pub fn format_number(value: u64) -> String {
+    let digits = value.to_string();
+    let mut groups: Vec<&str> = digits
+        .as_bytes()
+        .rchunks(3)
+        .map(|x| std::str::from_utf8(x).unwrap())
+        .collect();
+    groups.reverse();
+    groups.join("_")
+}
+
+const WEIGHT_FROM_PARTS: &str = "Weight::from_parts(";
+const PER_REPETITION_FROM_PARTS: &str = ".saturating_add(Weight::from_parts(";
+const MINIMUM_EXECUTION_TIME: &str = "// Minimum execution time: ";
+const STANDARD_ERROR: &str = "// Standard Error: ";
+const WEIGHTS_VERSION: &str = "version: ";
+const MULTIPLIER_PREFIX: &str = "), ";
+const DIVISOR_PREFIX: &str = " / ";
+
+fn is_weight_fn_start(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("fn instr_") && line.ends_with('{')
+}
+
+fn weight_fn_name(line: &str) -> Option<&str> {
+    line.trim()
+        .strip_prefix("fn ")
+        .and_then(|x| x.split('(').next())
+}
+
+/// Finds the line of the cost expression of a `FeeTable` function, if it has a constant.
+fn find_fee_table_constant_line(
+    fee_table_rs: &str,
+    cost_fn: &str,
+) -> Option<(usize, FeeTableConstant)> {
+    let mut in_fn = false;
+    for (index, line) in fee_table_rs.split('\n').enumerate() {
+        let line = line.trim();
+        let signature = line.strip_prefix("pub ").unwrap_or(line);
+        if signature.starts_with("fn ") {
+            in_fn = weight_fn_name(signature) == Some(cost_fn);
+        } else if in_fn && !line.starts_with("//") {
+            let constant = if line.starts_with("mul(cast(") {
+                FeeTableConstant::Multiplier(0)
+            } else {
+                FeeTableConstant::Divisor(0)
+            };
+            // The constant must end the expression, eg. not `x / 4 + 100`
+            match number_span(line, constant.prefix()) {
+                Some((_, end)) if line[end..].trim_end_matches(')').is_empty() => {
+                    let value = number_after(line, constant.prefix())?;
+                    return Some((index, constant.with_value(value)));
+                }
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+fn number_span(line: &str, prefix: &str) -> Option<(usize, usize)> {
+    let start = line.find(prefix)? + prefix.len();
+    let len = line[start..]
+        .find(|c: char| !c.is_ascii_digit() && c != '_')
+        .unwrap_or(line.len() - start);
+    if len == 0 {
+        None
+    } else {
+        Some((start, start + len))
+    }
+}
+
+fn number_after(line: &str, prefix: &str) -> Option<u64> {
+    let (start, end) = number_span(line, prefix)?;
+    line[start..end].replace('_', "").parse().ok()
+}
+
+fn replace_number_after(line: &str, prefix: &str, value: u64) -> String {
+    match number_span(line, prefix) {
+        Some((start, end)) => format!("{}{}{}", &line[..start], format_number(value), &line[end..]),
+        None => line.to_string(),
+    }
+}
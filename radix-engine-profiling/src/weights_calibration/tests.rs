@@ -0,0 +1,247 @@
+use super::*;
+
+const WEIGHTS_RS: &str = r#"impl Default for InstructionWeights {
+    fn default() -> Self {
+        Self {
+            version: 4,
+            fallback: 0,
+        }
+    }
+}
+
+pub trait WeightInfo {
+    fn instr_i64const(r: u32) -> Weight;
+    fn instr_i64add(r: u32) -> Weight;
+}
+
+impl WeightInfo for InstructionWeights {
+    /// The range of component `r` is `[0, 5000]`.
+    fn instr_i64const(r: u32) -> Weight {
+        // Proof Size summary in bytes:
+        //  Measured:  `0`
+        //  Estimated: `0`
+        // Minimum execution time: 1_405_000 picoseconds.
+        Weight::from_parts(1_583_300, 0)
+            // Standard Error: 1
+            .saturating_add(Weight::from_parts(2_743, 0).saturating_mul(r.into()))
+    }
+    /// The range of component `r` is `[0, 5000]`.
+    fn instr_i64add(r: u32) -> Weight {
+        // Proof Size summary in bytes:
+        //  Measured:  `0`
+        //  Estimated: `0`
+        // Minimum execution time: 1_442_000 picoseconds.
+        Weight::from_parts(1_653_166, 0)
+            // Standard Error: 6
+            .saturating_add(Weight::from_parts(5_534, 0).saturating_mul(r.into()))
+    }
+}
+"#;
+
+const NATIVE_FUNCTION_COSTS_CSV: &str = "0d906318c6318c61e603c64c6318c6318cf7be913d63aafbc6318c6318c6,AuthZone_create_proof_of_all,1497344
+0d906318c6318c61e603c64c6318c6318cf7be913d63aafbc6318c6318c6,AuthZone_create_proof_of_amount,1448421
+";
+
+const FEE_TABLE_RS: &str = r#"impl FeeTable {
+    #[inline]
+    pub fn verify_tx_signatures_cost(&self, n: usize) -> u32 {
+        // The cost for validating a single signature is: 67.522 µs * 100 units/µs = 7,000 cost units
+        mul(cast(n), 7_000)
+    }
+
+    #[inline]
+    pub fn run_wasm_code_cost(
+        &self,
+        _package_address: &PackageAddress,
+        _export_name: &str,
+        wasm_execution_units: u32,
+    ) -> u32 {
+        // Therefore, cost for single unit: 5.5391 *  1000 / 1918122691 * 100 = 0.00028877714
+
+        wasm_execution_units / 3000
+    }
+
+    #[inline]
+    pub fn after_invoke_cost(&self, input_size: usize) -> u32 {
+        Self::data_processing_cost(input_size)
+    }
+}
+"#;
+
+fn sample(repetitions: u64, cpu_instructions: u64) -> CalibrationSample {
+    CalibrationSample {
+        repetitions,
+        cpu_instructions,
+    }
+}
+
+#[test]
+fn test_format_number() {
+    assert_eq!(format_number(0), "0");
+    assert_eq!(format_number(29), "29");
+    assert_eq!(format_number(1_651), "1_651");
+    assert_eq!(format_number(1_583_300), "1_583_300");
+}
+
+#[test]
+fn test_linear_fit() {
+    let samples: Vec<CalibrationSample> = (0..10)
+        .flat_map(|i| {
+            let x = i * 100;
+            // Outliers are filtered out by taking median of samples
+            vec![
+                sample(x, 5_000 + 17 * x),
+                sample(x, 5_000 + 17 * x),
+                sample(x, 1_000_000),
+            ]
+        })
+        .collect();
+
+    let fit = LinearFit::from_samples(&samples).unwrap();
+    assert!((fit.base - 5_000f64).abs() < 1e-6);
+    assert!((fit.per_repetition - 17f64).abs() < 1e-6);
+    assert!((fit.r_squared - 1f64).abs() < 1e-9);
+    assert_eq!(fit.min_cpu_instructions, 5_000);
+    assert_eq!(fit.samples_count, 30);
+
+    assert_eq!(
+        LinearFit::from_samples(&[sample(1, 100), sample(1, 200)]),
+        None
+    );
+}
+
+#[test]
+fn test_find_current_costs() {
+    assert_eq!(
+        find_instruction_weight(WEIGHTS_RS, "instr_i64add"),
+        Some(CurrentInstructionWeight {
+            base: 1_653_166,
+            per_repetition: 5_534
+        })
+    );
+    assert_eq!(find_instruction_weight(WEIGHTS_RS, "instr_i64mul"), None);
+    assert_eq!(
+        find_fee_table_constant(FEE_TABLE_RS, "verify_tx_signatures_cost"),
+        Some(FeeTableConstant::Multiplier(7_000))
+    );
+    assert_eq!(
+        find_fee_table_constant(FEE_TABLE_RS, "run_wasm_code_cost"),
+        Some(FeeTableConstant::Divisor(3_000))
+    );
+    assert_eq!(
+        find_fee_table_constant(FEE_TABLE_RS, "after_invoke_cost"),
+        None
+    );
+    assert_eq!(
+        find_native_function_cost(
+            NATIVE_FUNCTION_COSTS_CSV,
+            "0d906318c6318c61e603c64c6318c6318cf7be913d63aafbc6318c6318c6",
+            "AuthZone_create_proof_of_amount"
+        ),
+        Some(1448421)
+    );
+}
+
+#[test]
+fn test_patch_weights_rs() {
+    let mut calibration = WeightsCalibration::new();
+    for x in [0, 1_000, 2_000, 3_000] {
+        // 3.4 CPU instructions = 1 ns
+        calibration.add_instruction_sample("instr_i64add", sample(x, 3_400 + 34 * x));
+    }
+    let report = calibration.fit();
+    let patched = report.patch_weights_rs(WEIGHTS_RS);
+
+    assert_eq!(
+        find_instruction_weight(&patched, "instr_i64add"),
+        Some(CurrentInstructionWeight {
+            base: 1_000_000,
+            per_repetition: 10_000
+        })
+    );
+    // Other weights are untouched
+    assert_eq!(
+        find_instruction_weight(&patched, "instr_i64const"),
+        find_instruction_weight(WEIGHTS_RS, "instr_i64const")
+    );
+    assert!(patched.contains("version: 5,"));
+    assert!(patched.contains("// Minimum execution time: 1_000_000 picoseconds."));
+    assert!(patched.contains("// Standard Error: 0\n"));
+
+    let diff = unified_diff("weights.rs", WEIGHTS_RS, &patched);
+    assert!(diff.starts_with("--- a/weights.rs\n+++ b/weights.rs\n"));
+    assert!(diff.contains("-            version: 4,\n+            version: 5,\n"));
+    assert!(diff.contains(
+        "-            .saturating_add(Weight::from_parts(5_534, 0).saturating_mul(r.into()))\n\
+         +        // Minimum execution time: 1_000_000 picoseconds.\n"
+    ));
+    assert!(diff.ends_with(
+        "+            .saturating_add(Weight::from_parts(10_000, 0).saturating_mul(r.into()))\n     }\n }\n"
+    ));
+    assert_eq!(diff.matches("@@ -").count(), 2);
+}
+
+#[test]
+fn test_patch_native_function_costs_csv() {
+    let mut calibration = WeightsCalibration::new();
+    for calls in 1..5 {
+        calibration.add_native_function_sample(
+            "0d906318c6318c61e603c64c6318c6318cf7be913d63aafbc6318c6318c6",
+            "AuthZone_create_proof_of_all",
+            sample(calls, 2_000_000 + 1_500_000 * calls),
+        );
+    }
+    let report = calibration.fit();
+    let patched = report.patch_native_function_costs_csv(NATIVE_FUNCTION_COSTS_CSV);
+
+    assert_eq!(
+        patched,
+        "0d906318c6318c61e603c64c6318c6318cf7be913d63aafbc6318c6318c6,AuthZone_create_proof_of_all,1500000
+0d906318c6318c61e603c64c6318c6318cf7be913d63aafbc6318c6318c6,AuthZone_create_proof_of_amount,1448421
+"
+    );
+    assert!(report
+        .to_markdown(WEIGHTS_RS, FEE_TABLE_RS, NATIVE_FUNCTION_COSTS_CSV)
+        .contains("| 1_497_344 | 1_500_000 | +0.2% |"));
+}
+
+#[test]
+fn test_patch_fee_table_rs() {
+    let mut calibration = WeightsCalibration::new();
+    for x in 1..5 {
+        // 8_000 cost units per signature
+        calibration
+            .add_fee_table_entry_sample("verify_tx_signatures_cost", sample(x, 34 * 8_000 * x));
+        // 1 cost unit per 2_500 WASM execution units
+        calibration
+            .add_fee_table_entry_sample("run_wasm_code_cost", sample(x * 250_000, 34 * 100 * x));
+        calibration.add_fee_table_entry_sample("after_invoke_cost", sample(x, 34 * 10 * x));
+    }
+    let report = calibration.fit();
+    let patched = report.patch_fee_table_rs(FEE_TABLE_RS);
+
+    assert_eq!(
+        find_fee_table_constant(&patched, "verify_tx_signatures_cost"),
+        Some(FeeTableConstant::Multiplier(8_000))
+    );
+    assert_eq!(
+        find_fee_table_constant(&patched, "run_wasm_code_cost"),
+        Some(FeeTableConstant::Divisor(2_500))
+    );
+    assert!(patched.contains("        mul(cast(n), 8_000)\n"));
+    assert!(patched.contains("        wasm_execution_units / 2_500\n"));
+    // Comments and entries without a constant are untouched
+    assert!(patched.contains("= 7,000 cost units\n"));
+    assert!(patched.contains("        Self::data_processing_cost(input_size)\n"));
+    assert_eq!(
+        unified_diff("fee_table.rs", FEE_TABLE_RS, &patched)
+            .matches("@@ -")
+            .count(),
+        2
+    );
+
+    let markdown = report.to_markdown(WEIGHTS_RS, FEE_TABLE_RS, NATIVE_FUNCTION_COSTS_CSV);
+    assert!(markdown.contains("| × 7_000 | × 8_000 |"));
+    assert!(markdown.contains("| ÷ 3_000 | ÷ 2_500 |"));
+    assert!(markdown.contains("| - | manual |"));
+}
@@ -0,0 +1,382 @@
+use super::{StateTreeTraverser, StateTreeVisitor};
+use radix_engine::system::attached_modules::metadata::{
+    MetadataCollection, MetadataEntryEntryPayload,
+};
+use radix_engine::system::system_db_reader::SystemDatabaseReader;
+use radix_engine::types::*;
+use radix_engine_interface::api::{AttachedModuleId, ModuleId};
+use radix_engine_interface::blueprints::package::PartitionDescription;
+use radix_engine_interface::blueprints::resource::{
+    LiquidFungibleResource, LiquidNonFungibleVault,
+};
+use radix_engine_interface::types::ObjectType;
+use radix_engine_store_interface::db_key_mapper::{DatabaseKeyMapper, SpreadPrefixKeyMapper};
+use radix_engine_store_interface::interface::{
+    DbSortKey, HistoricalSubstateDatabase, StateVersionUnavailableError, StateVersionView,
+    SubstateDatabase,
+};
+use sbor::rust::prelude::*;
+
+/// The max depth (below the substates of a component) of its owned nodes which are searched for
+/// vaults.
+pub const HOLDINGS_TRAVERSAL_MAX_DEPTH: u32 = 100;
+
+/// A resource held by a global component, in the vaults it owns - either directly, or through
+/// its owned child objects (e.g. the vaults of an account).
+///
+/// When read from a [`HoldingsPage`], the balance and vaults only cover the vaults found on that
+/// page, which may be a part of the resource's holding (see [`HoldingsPage`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceHolding {
+    pub resource_address: ResourceAddress,
+    pub balance: HoldingBalance,
+    /// The vaults holding the resource.
+    pub vaults: Vec<NodeId>,
+    /// The metadata of the resource.
+    pub metadata: IndexMap<String, MetadataValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HoldingBalance {
+    Fungible(Decimal),
+    NonFungible(BTreeSet<NonFungibleLocalId>),
+}
+
+impl HoldingBalance {
+    pub fn amount(&self) -> Decimal {
+        match self {
+            HoldingBalance::Fungible(amount) => *amount,
+            HoldingBalance::NonFungible(ids) => ids.len().into(),
+        }
+    }
+}
+
+/// A page of the holdings of a component.
+///
+/// The holdings are in the order of resource addresses within the page only, as the pages follow
+/// the order of the substates owning the vaults. A resource held in vaults owned by several
+/// substates can thus be listed on several pages, each time with the part of its balance held on
+/// that page, so the balances of a resource must be summed over all pages for its total.
+///
+/// See [`HoldingsQuery::get_holdings_page`] for how the holdings are split into pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoldingsPage {
+    pub holdings: Vec<ResourceHolding>,
+    /// The position to request the next page from, if there are more holdings.
+    pub next_page_start: Option<HoldingsPageStart>,
+}
+
+/// The position of the substate of a component from which a page of its holdings is read.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HoldingsPageStart {
+    partition_number: PartitionNumber,
+    sort_key: DbSortKey,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HoldingsQueryError {
+    ComponentNotFound(GlobalAddress),
+    StateVersionUnavailable(StateVersionUnavailableError),
+    ZeroPageLimit,
+}
+
+/// Answers questions about the resources held by global components.
+///
+/// The database is taken by value, so that both references to databases and views of their past
+/// states (see [`Self::at_state_version`]) can be queried.
+pub struct HoldingsQuery<S: SubstateDatabase> {
+    substate_db: S,
+}
+
+impl<S: SubstateDatabase> HoldingsQuery<S> {
+    pub fn new(substate_db: S) -> Self {
+        Self { substate_db }
+    }
+
+    /// Returns all resources held by the given component, in the order of resource addresses.
+    pub fn get_holdings(
+        &self,
+        component_address: GlobalAddress,
+    ) -> Result<Vec<ResourceHolding>, HoldingsQueryError> {
+        self.get_holdings_page(component_address, None, usize::MAX)
+            .map(|page| page.holdings)
+    }
+
+    /// Returns a page of the resources held by the given component, starting from the given
+    /// position (or from the first page if `None`). The `limit` must not be zero.
+    ///
+    /// The pages follow the order in which the substates of the component are stored, and only
+    /// the nodes owned by the substates of a page are read. A page ends at the first substate
+    /// after which at least `limit` resources are found, so it can exceed the limit if a single
+    /// substate owns vaults of several resources. Conversely, a resource held in vaults owned by
+    /// several substates (which is never the case for accounts) can be listed on several pages,
+    /// each time with the vaults of that page.
+    pub fn get_holdings_page(
+        &self,
+        component_address: GlobalAddress,
+        page_start: Option<HoldingsPageStart>,
+        limit: usize,
+    ) -> Result<HoldingsPage, HoldingsQueryError> {
+        if limit == 0 {
+            return Err(HoldingsQueryError::ZeroPageLimit);
+        }
+
+        let mut collector = HeldResourceCollector::default();
+        let mut next_page_start = None;
+        for (position, owned_nodes) in self.iter_owning_substates(component_address, page_start)? {
+            if collector.held_resources.len() >= limit {
+                next_page_start = Some(position);
+                break;
+            }
+            self.traverse_owned_nodes(&mut collector, owned_nodes);
+        }
+
+        let holdings = collector
+            .held_resources
+            .into_iter()
+            .map(|(resource_address, held_resource)| ResourceHolding {
+                resource_address,
+                balance: held_resource.balance,
+                vaults: held_resource.vaults,
+                metadata: get_entity_metadata(&self.substate_db, resource_address.as_node_id()),
+            })
+            .collect();
+        Ok(HoldingsPage {
+            holdings,
+            next_page_start,
+        })
+    }
+
+    /// Returns the balance of the given resource held by the given component (zero if none).
+    pub fn get_balance(
+        &self,
+        component_address: GlobalAddress,
+        resource_address: ResourceAddress,
+    ) -> Result<Decimal, HoldingsQueryError> {
+        Ok(self
+            .collect_held_resources(component_address)?
+            .get(&resource_address)
+            .map(|held_resource| held_resource.balance.amount())
+            .unwrap_or_default())
+    }
+
+    /// Returns the vaults holding the given resource, owned by the given component.
+    pub fn get_vaults(
+        &self,
+        component_address: GlobalAddress,
+        resource_address: ResourceAddress,
+    ) -> Result<Vec<NodeId>, HoldingsQueryError> {
+        Ok(self
+            .collect_held_resources(component_address)?
+            .remove(&resource_address)
+            .map(|held_resource| held_resource.vaults)
+            .unwrap_or_default())
+    }
+
+    fn collect_held_resources(
+        &self,
+        component_address: GlobalAddress,
+    ) -> Result<BTreeMap<ResourceAddress, HeldResource>, HoldingsQueryError> {
+        let mut collector = HeldResourceCollector::default();
+        for (_, owned_nodes) in self.iter_owning_substates(component_address, None)? {
+            self.traverse_owned_nodes(&mut collector, owned_nodes);
+        }
+        Ok(collector.held_resources)
+    }
+
+    fn traverse_owned_nodes(
+        &self,
+        collector: &mut HeldResourceCollector,
+        owned_nodes: Vec<NodeId>,
+    ) {
+        let mut traverser =
+            StateTreeTraverser::new(&self.substate_db, collector, HOLDINGS_TRAVERSAL_MAX_DEPTH);
+        for node_id in owned_nodes {
+            traverser.traverse_subtree(None, node_id);
+        }
+    }
+
+    /// Iterates over the substates of the given component which own nodes (i.e. of its state
+    /// and its royalty vault), in the order of their partitions and sort keys, starting from the
+    /// given position.
+    fn iter_owning_substates(
+        &self,
+        component_address: GlobalAddress,
+        page_start: Option<HoldingsPageStart>,
+    ) -> Result<impl Iterator<Item = (HoldingsPageStart, Vec<NodeId>)> + '_, HoldingsQueryError>
+    {
+        let reader = SystemDatabaseReader::new(&self.substate_db);
+        let object_info = reader
+            .get_object_info(component_address)
+            .map_err(|_| HoldingsQueryError::ComponentNotFound(component_address))?;
+        let state = reader
+            .get_blueprint_definition(&object_info.blueprint_info.blueprint_id)
+            .expect("Broken database")
+            .interface
+            .state;
+
+        let mut partition_numbers: Vec<PartitionNumber> = state
+            .fields
+            .iter()
+            .map(|(partition_description, _)| partition_description)
+            .chain(
+                state
+                    .collections
+                    .iter()
+                    .map(|(partition_description, _)| partition_description),
+            )
+            .map(|partition_description| match partition_description {
+                PartitionDescription::Logical(offset) => {
+                    MAIN_BASE_PARTITION.at_offset(*offset).unwrap()
+                }
+                PartitionDescription::Physical(partition_number) => *partition_number,
+            })
+            .collect();
+        if let ObjectType::Global { modules } = &object_info.object_type {
+            if modules.contains_key(&AttachedModuleId::Royalty) {
+                partition_numbers.push(ROYALTY_FIELDS_PARTITION);
+            }
+        }
+        partition_numbers.sort();
+        partition_numbers.dedup();
+
+        let node_id = *component_address.as_node_id();
+        let substate_db = &self.substate_db;
+        let start_partition_number = page_start
+            .as_ref()
+            .map(|page_start| page_start.partition_number);
+        Ok(partition_numbers
+            .into_iter()
+            .filter(move |partition_number| {
+                start_partition_number.map_or(true, |start_partition_number| {
+                    *partition_number >= start_partition_number
+                })
+            })
+            .flat_map(move |partition_number| {
+                let from_sort_key = match &page_start {
+                    Some(page_start) if page_start.partition_number == partition_number => {
+                        page_start.sort_key.clone()
+                    }
+                    _ => DbSortKey(vec![]),
+                };
+                let partition_key =
+                    SpreadPrefixKeyMapper::to_db_partition_key(&node_id, partition_number);
+                substate_db
+                    .list_entries_from(&partition_key, &from_sort_key)
+                    .filter_map(move |(sort_key, value)| {
+                        let (_, owned_nodes, _) =
+                            IndexedScryptoValue::from_vec(value).unwrap().unpack();
+                        (!owned_nodes.is_empty()).then(|| {
+                            (
+                                HoldingsPageStart {
+                                    partition_number,
+                                    sort_key,
+                                },
+                                owned_nodes,
+                            )
+                        })
+                    })
+            }))
+    }
+}
+
+impl<'s, S: HistoricalSubstateDatabase + ?Sized> HoldingsQuery<StateVersionView<'s, S>> {
+    /// Creates a query of the state of the database as of the given state version.
+    pub fn at_state_version(
+        substate_db: &'s S,
+        state_version: u64,
+    ) -> Result<Self, HoldingsQueryError> {
+        StateVersionView::new(substate_db, state_version)
+            .map(Self::new)
+            .map_err(HoldingsQueryError::StateVersionUnavailable)
+    }
+}
+
+/// Reads all metadata entries of the given entity.
+pub fn get_entity_metadata<S: SubstateDatabase>(
+    substate_db: &S,
+    entity_node_id: &NodeId,
+) -> IndexMap<String, MetadataValue> {
+    let reader = SystemDatabaseReader::new(substate_db);
+    reader
+        .collection_iter(
+            entity_node_id,
+            ModuleId::Metadata,
+            MetadataCollection::EntryKeyValue.collection_index(),
+        )
+        .unwrap()
+        .map(|(key, value)| {
+            let map_key = key.into_map();
+            let key = scrypto_decode::<String>(&map_key).unwrap();
+            let value = scrypto_decode::<MetadataEntryEntryPayload>(&value).unwrap();
+            (key, value.into_latest())
+        })
+        .collect()
+}
+
+struct HeldResource {
+    balance: HoldingBalance,
+    vaults: Vec<NodeId>,
+}
+
+#[derive(Default)]
+struct HeldResourceCollector {
+    held_resources: BTreeMap<ResourceAddress, HeldResource>,
+}
+
+impl StateTreeVisitor for HeldResourceCollector {
+    fn visit_fungible_vault(
+        &mut self,
+        vault_id: NodeId,
+        address: &ResourceAddress,
+        resource: &LiquidFungibleResource,
+    ) {
+        let held_resource = self
+            .held_resources
+            .entry(*address)
+            .or_insert_with(|| HeldResource {
+                balance: HoldingBalance::Fungible(Decimal::ZERO),
+                vaults: Vec::new(),
+            });
+        held_resource.vaults.push(vault_id);
+        if let HoldingBalance::Fungible(amount) = &mut held_resource.balance {
+            // NOTE: Decimal arithmetic operation safe unwrap.
+            //       Resources have a mint limit below the Decimal max
+            *amount = amount
+                .checked_add(resource.amount())
+                .expect("Resource overflow despite mint limit");
+        }
+    }
+
+    fn visit_non_fungible_vault(
+        &mut self,
+        vault_id: NodeId,
+        address: &ResourceAddress,
+        _resource: &LiquidNonFungibleVault,
+    ) {
+        self.held_resources
+            .entry(*address)
+            .or_insert_with(|| HeldResource {
+                balance: HoldingBalance::NonFungible(BTreeSet::new()),
+                vaults: Vec::new(),
+            })
+            .vaults
+            .push(vault_id);
+    }
+
+    fn visit_non_fungible(
+        &mut self,
+        _vault_id: NodeId,
+        address: &ResourceAddress,
+        id: &NonFungibleLocalId,
+    ) {
+        // The vault itself is always visited before its non-fungibles
+        if let Some(HeldResource {
+            balance: HoldingBalance::NonFungible(ids),
+            ..
+        }) = self.held_resources.get_mut(address)
+        {
+            ids.insert(id.clone());
+        }
+    }
+}
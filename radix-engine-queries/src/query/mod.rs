@@ -1,7 +1,9 @@
 mod accounter;
+mod holdings;
 mod traverse;
 mod vault_finder;
 
 pub use accounter::*;
+pub use holdings::*;
 pub use traverse::*;
 pub use vault_finder::*;
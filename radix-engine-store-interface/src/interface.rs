@@ -3,6 +3,7 @@ use utils::prelude::index_map_new;
 use utils::prelude::vec;
 use utils::rust::boxed::Box;
 use utils::rust::collections::IndexMap;
+use utils::rust::ops::RangeInclusive;
 use utils::rust::vec::Vec;
 
 pub type DbNodeKey = Vec<u8>;
@@ -146,6 +147,48 @@ pub trait SubstateDatabase {
         &self,
         partition_key: &DbPartitionKey,
     ) -> Box<dyn Iterator<Item = PartitionEntry> + '_>;
+
+    /// Iterates over the entries of the given partition, starting from the given [`DbSortKey`]
+    /// (inclusive), in a lexicographical order (ascending) of the [`DbSortKey`]s.
+    ///
+    /// The default implementation skips over the preceding entries - databases which support
+    /// seeking should override it.
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: &DbSortKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        let from_sort_key = from_sort_key.clone();
+        Box::new(
+            self.list_entries(partition_key)
+                .skip_while(move |(sort_key, _)| *sort_key < from_sort_key),
+        )
+    }
+}
+
+impl<S: SubstateDatabase + ?Sized> SubstateDatabase for &S {
+    fn get_substate(
+        &self,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        (**self).get_substate(partition_key, sort_key)
+    }
+
+    fn list_entries(
+        &self,
+        partition_key: &DbPartitionKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        (**self).list_entries(partition_key)
+    }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: &DbSortKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        (**self).list_entries_from(partition_key, from_sort_key)
+    }
 }
 
 /// A write interface between Track and a database vendor.
@@ -159,3 +202,91 @@ pub trait ListableSubstateDatabase {
     /// Iterates over all partition keys, in an arbitrary order.
     fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_>;
 }
+
+impl<S: ListableSubstateDatabase + ?Sized> ListableSubstateDatabase for &S {
+    fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_> {
+        (**self).list_partition_keys()
+    }
+}
+
+/// A read interface to the past states of a database, identified by their state versions (i.e.
+/// the numbers of commits which led to them).
+pub trait HistoricalSubstateDatabase {
+    /// Returns the range of state versions which can be read (always including the current one).
+    fn available_state_versions(&self) -> RangeInclusive<u64>;
+
+    /// Reads a substate value as of the given state version, or [`Option::None`] if missing.
+    ///
+    /// Panics if the state version is not available.
+    fn get_substate_at(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue>;
+
+    /// Iterates over all entries of the given partition as of the given state version, in a
+    /// lexicographical order (ascending) of the [`DbSortKey`]s.
+    ///
+    /// Panics if the state version is not available.
+    fn list_entries_at(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_>;
+}
+
+/// A read-only [`SubstateDatabase`] view of a past state of a [`HistoricalSubstateDatabase`],
+/// which allows to use it with any reader (e.g. the `SystemDatabaseReader`).
+pub struct StateVersionView<'a, S: HistoricalSubstateDatabase + ?Sized> {
+    substate_db: &'a S,
+    state_version: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateVersionUnavailableError {
+    pub state_version: u64,
+    pub available_state_versions: RangeInclusive<u64>,
+}
+
+impl<'a, S: HistoricalSubstateDatabase + ?Sized> StateVersionView<'a, S> {
+    pub fn new(
+        substate_db: &'a S,
+        state_version: u64,
+    ) -> Result<Self, StateVersionUnavailableError> {
+        let available_state_versions = substate_db.available_state_versions();
+        if !available_state_versions.contains(&state_version) {
+            return Err(StateVersionUnavailableError {
+                state_version,
+                available_state_versions,
+            });
+        }
+        Ok(Self {
+            substate_db,
+            state_version,
+        })
+    }
+
+    pub fn state_version(&self) -> u64 {
+        self.state_version
+    }
+}
+
+impl<'a, S: HistoricalSubstateDatabase + ?Sized> SubstateDatabase for StateVersionView<'a, S> {
+    fn get_substate(
+        &self,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        self.substate_db
+            .get_substate_at(self.state_version, partition_key, sort_key)
+    }
+
+    fn list_entries(
+        &self,
+        partition_key: &DbPartitionKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.substate_db
+            .list_entries_at(self.state_version, partition_key)
+    }
+}
@@ -46,7 +46,25 @@ pub fn put_at_next_version<S: TreeStore>(
     node_root_version: Option<Version>,
     database_updates: &DatabaseUpdates,
 ) -> Hash {
-    let next_version = node_root_version.unwrap_or(0) + 1;
+    put_at_version(
+        node_tier_store,
+        node_root_version,
+        node_root_version.unwrap_or(0) + 1,
+        database_updates,
+    )
+}
+
+/// Inserts a new set of nodes at the given `next_version`, which may skip versions after
+/// `node_root_version` (see [`put_at_next_version`] for the details).
+///
+/// # Panics
+/// Panics if a root node for `node_root_version` does not exist.
+pub fn put_at_version<S: TreeStore>(
+    node_tier_store: &mut S,
+    node_root_version: Option<Version>,
+    next_version: Version,
+    database_updates: &DatabaseUpdates,
+) -> Hash {
     let node_hash_changes = database_updates
         .node_updates
         .iter()
@@ -61,6 +61,15 @@ impl<D: SubstateDatabase> SubstateDatabase for HashTreeUpdatingDatabase<D> {
     ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
         self.underlying.list_entries(partition_key)
     }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: &DbSortKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.underlying
+            .list_entries_from(partition_key, from_sort_key)
+    }
 }
 
 impl<D: ListableSubstateDatabase> ListableSubstateDatabase for HashTreeUpdatingDatabase<D> {
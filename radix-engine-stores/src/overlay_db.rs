@@ -0,0 +1,483 @@
+use radix_engine_store_interface::interface::*;
+use sbor::rust::cmp::Ordering;
+use sbor::rust::iter;
+use sbor::rust::iter::Peekable;
+use sbor::rust::prelude::*;
+
+/// A copy-on-write database, which keeps all the committed changes in an in-memory layer on top of
+/// a base database, so that the base database itself is never changed.
+///
+/// The accumulated changes can be extracted as [`DatabaseUpdates`] and committed to the base (or
+/// any other) database later. The base may be a reference to a database, or another overlay.
+#[derive(Debug, Clone)]
+pub struct OverlaySubstateDatabase<S> {
+    base: S,
+    overlay: BTreeMap<DbPartitionKey, PartitionOverlay>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PartitionOverlay {
+    /// Whether all the substates of the partition in the base database are deleted.
+    reset: bool,
+    /// The changed substates, where `None` stands for a deleted substate.
+    substates: BTreeMap<DbSortKey, Option<DbSubstateValue>>,
+}
+
+impl<S> OverlaySubstateDatabase<S> {
+    pub fn new(base: S) -> Self {
+        Self {
+            base,
+            overlay: BTreeMap::new(),
+        }
+    }
+
+    pub fn base(&self) -> &S {
+        &self.base
+    }
+
+    /// Returns the changes committed to this database since its creation, as updates to be
+    /// committed to the base database.
+    pub fn database_updates(&self) -> DatabaseUpdates {
+        let mut database_updates = DatabaseUpdates::default();
+        for (partition_key, partition) in &self.overlay {
+            let partition_updates = if partition.reset {
+                PartitionDatabaseUpdates::Reset {
+                    new_substate_values: partition
+                        .substates
+                        .iter()
+                        .filter_map(|(sort_key, value)| {
+                            value
+                                .as_ref()
+                                .map(|value| (sort_key.clone(), value.clone()))
+                        })
+                        .collect(),
+                }
+            } else {
+                PartitionDatabaseUpdates::Delta {
+                    substate_updates: partition
+                        .substates
+                        .iter()
+                        .map(|(sort_key, value)| {
+                            let update = match value {
+                                Some(value) => DatabaseUpdate::Set(value.clone()),
+                                None => DatabaseUpdate::Delete,
+                            };
+                            (sort_key.clone(), update)
+                        })
+                        .collect(),
+                }
+            };
+            database_updates
+                .node_updates
+                .entry(partition_key.node_key.clone())
+                .or_default()
+                .partition_updates
+                .insert(partition_key.partition_num, partition_updates);
+        }
+        database_updates
+    }
+
+    /// Discards the overlay, returning the base database and the changes which were committed to
+    /// this database (see [`Self::database_updates`]).
+    pub fn into_parts(self) -> (S, DatabaseUpdates) {
+        let database_updates = self.database_updates();
+        (self.base, database_updates)
+    }
+}
+
+impl<S: SubstateDatabase> OverlaySubstateDatabase<S> {
+    fn merged_entries(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        let base_entries = || match from_sort_key {
+            Some(from_sort_key) => self.base.list_entries_from(partition_key, from_sort_key),
+            None => self.base.list_entries(partition_key),
+        };
+        let Some(partition) = self.overlay.get(partition_key) else {
+            return base_entries();
+        };
+        let base_entries: Box<dyn Iterator<Item = PartitionEntry> + '_> = if partition.reset {
+            Box::new(iter::empty())
+        } else {
+            base_entries()
+        };
+        let overlay_entries = match from_sort_key {
+            Some(from_sort_key) => partition.substates.range(from_sort_key.clone()..),
+            None => partition.substates.range::<DbSortKey, _>(..),
+        };
+        Box::new(MergedEntries::new(
+            base_entries,
+            overlay_entries.map(|(sort_key, value)| (sort_key.clone(), value.clone())),
+        ))
+    }
+}
+
+impl<S: SubstateDatabase> SubstateDatabase for OverlaySubstateDatabase<S> {
+    fn get_substate(
+        &self,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        if let Some(partition) = self.overlay.get(partition_key) {
+            if let Some(value) = partition.substates.get(sort_key) {
+                return value.clone();
+            }
+            if partition.reset {
+                return None;
+            }
+        }
+        self.base.get_substate(partition_key, sort_key)
+    }
+
+    fn list_entries(
+        &self,
+        partition_key: &DbPartitionKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.merged_entries(partition_key, None)
+    }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: &DbSortKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.merged_entries(partition_key, Some(from_sort_key))
+    }
+}
+
+impl<S> CommittableSubstateDatabase for OverlaySubstateDatabase<S> {
+    fn commit(&mut self, database_updates: &DatabaseUpdates) {
+        for (node_key, node_updates) in &database_updates.node_updates {
+            for (partition_num, partition_updates) in &node_updates.partition_updates {
+                let partition = self
+                    .overlay
+                    .entry(DbPartitionKey {
+                        node_key: node_key.clone(),
+                        partition_num: *partition_num,
+                    })
+                    .or_default();
+                match partition_updates {
+                    PartitionDatabaseUpdates::Delta { substate_updates } => {
+                        for (sort_key, update) in substate_updates {
+                            let value = match update {
+                                DatabaseUpdate::Set(value) => Some(value.clone()),
+                                DatabaseUpdate::Delete => None,
+                            };
+                            partition.substates.insert(sort_key.clone(), value);
+                        }
+                    }
+                    PartitionDatabaseUpdates::Reset {
+                        new_substate_values,
+                    } => {
+                        partition.reset = true;
+                        partition.substates = new_substate_values
+                            .iter()
+                            .map(|(sort_key, value)| (sort_key.clone(), Some(value.clone())))
+                            .collect();
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<S: SubstateDatabase + ListableSubstateDatabase> ListableSubstateDatabase
+    for OverlaySubstateDatabase<S>
+{
+    fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_> {
+        // The base partitions are listed first, unless all their substates were deleted
+        let base_partition_keys = self
+            .base
+            .list_partition_keys()
+            .filter(move |partition_key| {
+                !self.overlay.contains_key(partition_key)
+                    || self.list_entries(partition_key).next().is_some()
+            });
+        // Followed by the non-empty partitions which only exist in the overlay
+        let new_partition_keys = self
+            .overlay
+            .iter()
+            .filter(move |(partition_key, partition)| {
+                partition.substates.values().any(Option::is_some)
+                    && self.base.list_entries(partition_key).next().is_none()
+            })
+            .map(|(partition_key, _)| partition_key.clone());
+        Box::new(base_partition_keys.chain(new_partition_keys))
+    }
+}
+
+/// Merges the entries of a base partition with the changed entries on top of them (where `None`
+/// stands for a deleted substate), keeping the order of sort keys.
+pub struct MergedEntries<'a, C: Iterator<Item = (DbSortKey, Option<DbSubstateValue>)>> {
+    base_entries: Peekable<Box<dyn Iterator<Item = PartitionEntry> + 'a>>,
+    changed_entries: Peekable<C>,
+}
+
+impl<'a, C: Iterator<Item = (DbSortKey, Option<DbSubstateValue>)>> MergedEntries<'a, C> {
+    pub fn new(
+        base_entries: Box<dyn Iterator<Item = PartitionEntry> + 'a>,
+        changed_entries: C,
+    ) -> Self {
+        Self {
+            base_entries: base_entries.peekable(),
+            changed_entries: changed_entries.peekable(),
+        }
+    }
+}
+
+impl<'a, C: Iterator<Item = (DbSortKey, Option<DbSubstateValue>)>> Iterator
+    for MergedEntries<'a, C>
+{
+    type Item = PartitionEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let ordering = match (self.base_entries.peek(), self.changed_entries.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((sort_key, _)), Some((changed_sort_key, _))) => {
+                    sort_key.cmp(changed_sort_key)
+                }
+            };
+            if ordering == Ordering::Less {
+                return self.base_entries.next();
+            }
+            if ordering == Ordering::Equal {
+                // The base entry is shadowed by the changed one
+                self.base_entries.next();
+            }
+            let (sort_key, value) = self.changed_entries.next().unwrap();
+            if let Some(value) = value {
+                return Some((sort_key, value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database without any substates, to put the overlay under test on.
+    #[derive(Debug, Clone)]
+    struct EmptyDatabase;
+
+    impl SubstateDatabase for EmptyDatabase {
+        fn get_substate(
+            &self,
+            _partition_key: &DbPartitionKey,
+            _sort_key: &DbSortKey,
+        ) -> Option<DbSubstateValue> {
+            None
+        }
+
+        fn list_entries(
+            &self,
+            _partition_key: &DbPartitionKey,
+        ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+            Box::new(iter::empty())
+        }
+    }
+
+    impl ListableSubstateDatabase for EmptyDatabase {
+        fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_> {
+            Box::new(iter::empty())
+        }
+    }
+
+    fn partition_key(node: u8, partition_num: u8) -> DbPartitionKey {
+        DbPartitionKey {
+            node_key: vec![node; 3],
+            partition_num,
+        }
+    }
+
+    fn sort_key(key: u8) -> DbSortKey {
+        DbSortKey(vec![key])
+    }
+
+    fn updates(
+        partition_key: &DbPartitionKey,
+        partition_updates: PartitionDatabaseUpdates,
+    ) -> DatabaseUpdates {
+        let mut database_updates = DatabaseUpdates::default();
+        database_updates
+            .node_updates
+            .entry(partition_key.node_key.clone())
+            .or_default()
+            .partition_updates
+            .insert(partition_key.partition_num, partition_updates);
+        database_updates
+    }
+
+    fn delta(changes: &[(u8, Option<u8>)]) -> PartitionDatabaseUpdates {
+        PartitionDatabaseUpdates::Delta {
+            substate_updates: changes
+                .iter()
+                .map(|(key, value)| {
+                    let update = match value {
+                        Some(value) => DatabaseUpdate::Set(vec![*value]),
+                        None => DatabaseUpdate::Delete,
+                    };
+                    (sort_key(*key), update)
+                })
+                .collect(),
+        }
+    }
+
+    fn reset(values: &[(u8, u8)]) -> PartitionDatabaseUpdates {
+        PartitionDatabaseUpdates::Reset {
+            new_substate_values: values
+                .iter()
+                .map(|(key, value)| (sort_key(*key), vec![*value]))
+                .collect(),
+        }
+    }
+
+    fn entries<S: SubstateDatabase>(db: &S, partition_key: &DbPartitionKey) -> Vec<(u8, u8)> {
+        db.list_entries(partition_key)
+            .map(|(sort_key, value)| (sort_key.0[0], value[0]))
+            .collect()
+    }
+
+    fn base_database() -> OverlaySubstateDatabase<EmptyDatabase> {
+        let mut base = OverlaySubstateDatabase::new(EmptyDatabase);
+        base.commit(&updates(
+            &partition_key(1, 0),
+            delta(&[(1, Some(10)), (3, Some(30)), (5, Some(50))]),
+        ));
+        base.commit(&updates(&partition_key(2, 0), delta(&[(1, Some(11))])));
+        base
+    }
+
+    #[test]
+    fn reads_and_listings_merge_the_overlay_over_the_base() {
+        let base = base_database();
+        let mut overlay = OverlaySubstateDatabase::new(&base);
+        overlay.commit(&updates(
+            &partition_key(1, 0),
+            delta(&[(0, Some(0)), (3, None), (4, Some(40)), (5, Some(55))]),
+        ));
+
+        assert_eq!(
+            overlay.get_substate(&partition_key(1, 0), &sort_key(1)),
+            Some(vec![10])
+        );
+        assert_eq!(
+            overlay.get_substate(&partition_key(1, 0), &sort_key(3)),
+            None
+        );
+        assert_eq!(
+            overlay.get_substate(&partition_key(1, 0), &sort_key(5)),
+            Some(vec![55])
+        );
+        assert_eq!(
+            entries(&overlay, &partition_key(1, 0)),
+            vec![(0, 0), (1, 10), (4, 40), (5, 55)]
+        );
+        assert_eq!(
+            overlay
+                .list_entries_from(&partition_key(1, 0), &sort_key(2))
+                .map(|(sort_key, value)| (sort_key.0[0], value[0]))
+                .collect::<Vec<_>>(),
+            vec![(4, 40), (5, 55)]
+        );
+        assert_eq!(entries(&overlay, &partition_key(2, 0)), vec![(1, 11)]);
+        // The base database is untouched
+        assert_eq!(
+            entries(&base, &partition_key(1, 0)),
+            vec![(1, 10), (3, 30), (5, 50)]
+        );
+    }
+
+    #[test]
+    fn reset_hides_all_base_substates() {
+        let base = base_database();
+        let mut overlay = OverlaySubstateDatabase::new(&base);
+        overlay.commit(&updates(&partition_key(1, 0), reset(&[(2, 20)])));
+
+        assert_eq!(
+            overlay.get_substate(&partition_key(1, 0), &sort_key(1)),
+            None
+        );
+        assert_eq!(entries(&overlay, &partition_key(1, 0)), vec![(2, 20)]);
+
+        overlay.commit(&updates(&partition_key(1, 0), delta(&[(3, Some(33))])));
+        assert_eq!(
+            entries(&overlay, &partition_key(1, 0)),
+            vec![(2, 20), (3, 33)]
+        );
+    }
+
+    #[test]
+    fn nested_overlays_only_change_the_top_layer() {
+        let base = base_database();
+        let mut outer = OverlaySubstateDatabase::new(&base);
+        outer.commit(&updates(&partition_key(1, 0), delta(&[(1, None)])));
+
+        let mut inner = OverlaySubstateDatabase::new(&outer);
+        inner.commit(&updates(
+            &partition_key(1, 0),
+            delta(&[(1, Some(12)), (2, Some(22))]),
+        ));
+        assert_eq!(
+            entries(&inner, &partition_key(1, 0)),
+            vec![(1, 12), (2, 22), (3, 30), (5, 50)]
+        );
+
+        let (_, inner_updates) = inner.into_parts();
+        assert_eq!(
+            entries(&outer, &partition_key(1, 0)),
+            vec![(3, 30), (5, 50)]
+        );
+        outer.commit(&inner_updates);
+        assert_eq!(
+            entries(&outer, &partition_key(1, 0)),
+            vec![(1, 12), (2, 22), (3, 30), (5, 50)]
+        );
+    }
+
+    #[test]
+    fn committing_the_extracted_updates_to_the_base_reproduces_the_overlay() {
+        let base = base_database();
+        let mut overlay = OverlaySubstateDatabase::new(base.clone());
+        overlay.commit(&updates(
+            &partition_key(1, 0),
+            delta(&[(1, None), (2, Some(20))]),
+        ));
+        overlay.commit(&updates(&partition_key(2, 0), reset(&[(7, 70)])));
+        overlay.commit(&updates(&partition_key(3, 1), delta(&[(1, Some(13))])));
+
+        let (mut committed, database_updates) = overlay.clone().into_parts();
+        committed.commit(&database_updates);
+
+        let mut overlay_partition_keys = overlay.list_partition_keys().collect::<Vec<_>>();
+        let mut committed_partition_keys = committed.list_partition_keys().collect::<Vec<_>>();
+        overlay_partition_keys.sort();
+        committed_partition_keys.sort();
+        assert_eq!(overlay_partition_keys, committed_partition_keys);
+        for partition_key in committed_partition_keys {
+            assert_eq!(
+                entries(&overlay, &partition_key),
+                entries(&committed, &partition_key)
+            );
+        }
+    }
+
+    #[test]
+    fn emptied_partitions_are_not_listed() {
+        let base = base_database();
+        let mut overlay = OverlaySubstateDatabase::new(&base);
+        overlay.commit(&updates(&partition_key(2, 0), delta(&[(1, None)])));
+        overlay.commit(&updates(&partition_key(3, 0), delta(&[(1, None)])));
+        overlay.commit(&updates(&partition_key(4, 0), reset(&[])));
+
+        assert_eq!(
+            overlay.list_partition_keys().collect::<Vec<_>>(),
+            vec![partition_key(1, 0)]
+        );
+    }
+}
@@ -14,18 +14,32 @@ use rocksdb::{
 use sbor::prelude::*;
 use std::path::PathBuf;
 
+mod state_history;
 mod state_tree;
 use crate::rocks_db::{decode_from_rocksdb_bytes, encode_to_rocksdb_bytes};
+use state_history::StateHistoryMetadata;
+pub use state_history::StateHistoryRetention;
 use state_tree::*;
 
 const META_CF: &str = "meta";
 const SUBSTATES_CF: &str = "substates";
 const MERKLE_NODES_CF: &str = "merkle_nodes";
 const STALE_MERKLE_TREE_PARTS_CF: &str = "stale_merkle_tree_parts";
+const STATE_HISTORY_CF: &str = "state_history";
+const STATE_HISTORY_CHANGE_SETS_CF: &str = "state_history_change_sets";
+const ALL_CFS: [&str; 6] = [
+    META_CF,
+    SUBSTATES_CF,
+    MERKLE_NODES_CF,
+    STALE_MERKLE_TREE_PARTS_CF,
+    STATE_HISTORY_CF,
+    STATE_HISTORY_CHANGE_SETS_CF,
+];
 
 pub struct RocksDBWithMerkleTreeSubstateStore {
     db: DBWithThreadMode<SingleThreaded>,
     pruning_enabled: bool,
+    state_history: Option<StateHistoryMetadata>,
 }
 
 impl RocksDBWithMerkleTreeSubstateStore {
@@ -49,21 +63,35 @@ impl RocksDBWithMerkleTreeSubstateStore {
         let db = DB::open_cf_descriptors(
             options,
             root.as_path(),
-            [
-                META_CF,
-                SUBSTATES_CF,
-                MERKLE_NODES_CF,
-                STALE_MERKLE_TREE_PARTS_CF,
-            ]
-            .into_iter()
-            .map(|name| ColumnFamilyDescriptor::new(name, Options::default()))
-            .collect::<Vec<_>>(),
+            ALL_CFS
+                .into_iter()
+                .map(|name| ColumnFamilyDescriptor::new(name, Options::default()))
+                .collect::<Vec<_>>(),
         )
         .unwrap();
-        Self {
+        Self::new(db, pruning_enabled)
+    }
+
+    /// Opens an existing database in read-only mode, which is allowed even while another process
+    /// has it open for writing (the reads then see the state as of opening).
+    ///
+    /// Any commit to a read-only database panics.
+    pub fn read_only(root: PathBuf) -> Self {
+        // A database created before the state history was introduced lacks its column families
+        let cfs = DB::list_cf(&Options::default(), root.as_path()).unwrap();
+        let db =
+            DB::open_cf_for_read_only(&Options::default(), root.as_path(), cfs, false).unwrap();
+        Self::new(db, false)
+    }
+
+    fn new(db: DBWithThreadMode<SingleThreaded>, pruning_enabled: bool) -> Self {
+        let mut store = Self {
             db,
             pruning_enabled,
-        }
+            state_history: None,
+        };
+        store.state_history = store.read_state_history_metadata();
+        store
     }
 
     fn cf(&self, cf: &str) -> &ColumnFamily {
@@ -110,9 +138,17 @@ impl SubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
     fn list_entries(
         &self,
         partition_key: &DbPartitionKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.list_entries_from(partition_key, &DbSortKey(vec![]))
+    }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: &DbSortKey,
     ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
         let partition_key = partition_key.clone();
-        let start_key_bytes = encode_to_rocksdb_bytes(&partition_key, &DbSortKey(vec![]));
+        let start_key_bytes = encode_to_rocksdb_bytes(&partition_key, from_sort_key);
         let iter = self
             .db
             .iterator_cf(
@@ -133,6 +169,19 @@ impl SubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
 
 impl CommittableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
     fn commit(&mut self, database_updates: &DatabaseUpdates) {
+        let next_state_version = self.get_current_version() + 1;
+        self.commit_at_version(database_updates, next_state_version);
+    }
+}
+
+impl RocksDBWithMerkleTreeSubstateStore {
+    /// Commits the given updates as the given state version, skipping the state versions after
+    /// the current one (e.g. to import the state of a ledger at its state version).
+    ///
+    /// # Panics
+    /// Panics if the given state version is not after the current one, or if it skips state
+    /// versions while the state history is recorded (as it can't be read at skipped versions).
+    pub fn commit_at_version(&mut self, database_updates: &DatabaseUpdates, state_version: u64) {
         // read required info about current database state (here I fake it a bit)
         let metadata = self
             .db
@@ -144,11 +193,25 @@ impl CommittableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
                 current_state_root_hash: Hash([0u8; Hash::LENGTH]),
             });
         let parent_state_version = metadata.current_state_version;
-        let next_state_version = parent_state_version + 1;
+        let next_state_version = state_version;
+        assert!(
+            next_state_version > parent_state_version,
+            "Cannot commit state version {} after state version {}",
+            next_state_version,
+            parent_state_version
+        );
+        assert!(
+            next_state_version == parent_state_version + 1 || self.state_history.is_none(),
+            "Cannot skip state versions while the state history is recorded"
+        );
 
         // prepare a batch write (we use the same approach in the actual Node)
         let mut batch = WriteBatch::default();
 
+        // record and prune the state history (needs to read the substates before they are changed)
+        let new_state_history =
+            self.update_state_history(&mut batch, next_state_version, database_updates);
+
         // put regular substate changes
         for (node_key, node_updates) in &database_updates.node_updates {
             for (partition_num, partition_updates) in &node_updates.partition_updates {
@@ -196,8 +259,12 @@ impl CommittableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
         }
 
         // derive and put new JMT nodes (also record references to stale parts, for later amortized background GC [not implemented here!])
-        let (state_hash_tree_update, new_root_hash) =
-            compute_state_tree_update(self, parent_state_version, database_updates);
+        let (state_hash_tree_update, new_root_hash) = compute_state_tree_update(
+            self,
+            parent_state_version,
+            next_state_version,
+            database_updates,
+        );
         for (key, node) in state_hash_tree_update.new_nodes {
             batch.put_cf(
                 self.cf(MERKLE_NODES_CF),
@@ -227,6 +294,9 @@ impl CommittableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
 
         // flush the batch
         self.db.write(batch).unwrap();
+        if new_state_history.is_some() {
+            self.state_history = new_state_history;
+        }
 
         if self.pruning_enabled {
             for part in state_hash_tree_update.stale_tree_parts {
@@ -277,7 +347,7 @@ impl ListableSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
     fn list_partition_keys(&self) -> Box<dyn Iterator<Item = DbPartitionKey> + '_> {
         Box::new(
             self.db
-                .iterator(IteratorMode::Start)
+                .iterator_cf(self.cf(SUBSTATES_CF), IteratorMode::Start)
                 .map(|kv| {
                     let (iter_key_bytes, _) = kv.as_ref().unwrap();
                     let (iter_key, _) = decode_from_rocksdb_bytes(iter_key_bytes);
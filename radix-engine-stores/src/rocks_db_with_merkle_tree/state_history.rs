@@ -0,0 +1,454 @@
+use super::{
+    RocksDBWithMerkleTreeSubstateStore, META_CF, STATE_HISTORY_CF, STATE_HISTORY_CHANGE_SETS_CF,
+};
+use crate::overlay_db::MergedEntries;
+use crate::rocks_db::encode_to_rocksdb_bytes;
+use radix_engine_common::data::scrypto::{scrypto_decode, scrypto_encode};
+use radix_engine_derive::ScryptoSbor;
+use radix_engine_store_interface::interface::*;
+use rocksdb::{Direction, IteratorMode, WriteBatch};
+use sbor::prelude::*;
+use std::iter;
+use std::ops::RangeInclusive;
+use utils::copy_u8_array;
+
+// The state history consists of:
+// - entries keyed by a substate key and the state version at which the substate was changed (so
+//   that all entries of a substate are adjacent and ordered by version), holding the value set at
+//   that version (or `None` if deleted),
+// - change sets keyed by a state version, listing the substates changed at that version (so that
+//   their entries can be found when pruning).
+// A substate without any entries has not changed since the earliest available state version.
+// Before the first entry of a substate is recorded, its previous value (if any) is recorded at the
+// earliest available state version.
+
+const STATE_HISTORY_METADATA_KEY: &[u8] = b"state_history";
+
+/// Configures which part of the recorded state history is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ScryptoSbor)]
+pub enum StateHistoryRetention {
+    /// All past state versions (since enabling the history) are kept readable.
+    All,
+    /// Only the given number of state versions preceding the current one are kept readable, and
+    /// the history of the earlier ones is pruned.
+    LastVersions(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub(super) struct StateHistoryMetadata {
+    earliest_state_version: u64,
+    retention: StateHistoryRetention,
+}
+
+impl RocksDBWithMerkleTreeSubstateStore {
+    /// Enables recording the substate values of past state versions (starting from the current
+    /// one), so that they can be read through the [`HistoricalSubstateDatabase`] interface.
+    ///
+    /// The setting is persisted in the database, i.e. once enabled, the history keeps being
+    /// recorded on every later opening. Calling this again only changes the retention.
+    pub fn with_state_history(mut self, retention: StateHistoryRetention) -> Self {
+        let earliest_state_version = match &self.state_history {
+            Some(state_history) => state_history.earliest_state_version,
+            None => self.get_current_version(),
+        };
+        self.write_state_history_metadata(StateHistoryMetadata {
+            earliest_state_version,
+            retention,
+        });
+        self
+    }
+
+    pub(super) fn read_state_history_metadata(&self) -> Option<StateHistoryMetadata> {
+        self.db
+            .get_cf(self.cf(META_CF), STATE_HISTORY_METADATA_KEY)
+            .unwrap()
+            .map(|bytes| scrypto_decode(&bytes).unwrap())
+    }
+
+    fn write_state_history_metadata(&mut self, state_history: StateHistoryMetadata) {
+        self.db
+            .put_cf(
+                self.cf(META_CF),
+                STATE_HISTORY_METADATA_KEY,
+                scrypto_encode(&state_history).unwrap(),
+            )
+            .unwrap();
+        self.state_history = Some(state_history);
+    }
+
+    /// Adds the history entries and the change set of the given (not yet applied) commit to the
+    /// batch, along with the deletion of the history which is no longer retained after it, so that
+    /// the state history is updated atomically with the commit.
+    ///
+    /// Returns the new metadata (which is also added to the batch) if the earliest available state
+    /// version changes.
+    pub(super) fn update_state_history(
+        &self,
+        batch: &mut WriteBatch,
+        state_version: u64,
+        database_updates: &DatabaseUpdates,
+    ) -> Option<StateHistoryMetadata> {
+        let state_history = self.state_history.as_ref()?;
+        let new_earliest_state_version = match state_history.retention {
+            StateHistoryRetention::All => state_history.earliest_state_version,
+            StateHistoryRetention::LastVersions(retained_versions) => state_version
+                .saturating_sub(retained_versions)
+                .max(state_history.earliest_state_version),
+        };
+
+        let changes = self.list_state_changes(database_updates);
+        // The changes of the committed state version are only needed to read earlier ones
+        let is_recorded = new_earliest_state_version < state_version;
+        if is_recorded {
+            self.record_state_history(batch, state_history, state_version, &changes);
+        }
+
+        if new_earliest_state_version <= state_history.earliest_state_version {
+            return None;
+        }
+        let changed_substate_keys = changes.into_iter().map(|(substate_key, _)| substate_key);
+        self.prune_state_history(
+            batch,
+            state_history,
+            new_earliest_state_version,
+            state_version,
+            changed_substate_keys,
+            is_recorded,
+        );
+        let new_state_history = StateHistoryMetadata {
+            earliest_state_version: new_earliest_state_version,
+            ..state_history.clone()
+        };
+        batch.put_cf(
+            self.cf(META_CF),
+            STATE_HISTORY_METADATA_KEY,
+            scrypto_encode(&new_state_history).unwrap(),
+        );
+        Some(new_state_history)
+    }
+
+    /// Lists the substates changed by the given (not yet applied) commit, with their new values.
+    fn list_state_changes(
+        &self,
+        database_updates: &DatabaseUpdates,
+    ) -> Vec<(DbSubstateKey, Option<DbSubstateValue>)> {
+        let mut changes = Vec::new();
+        for (node_key, node_updates) in &database_updates.node_updates {
+            for (partition_num, partition_updates) in &node_updates.partition_updates {
+                let partition_key = DbPartitionKey {
+                    node_key: node_key.clone(),
+                    partition_num: *partition_num,
+                };
+                match partition_updates {
+                    PartitionDatabaseUpdates::Delta { substate_updates } => {
+                        for (sort_key, update) in substate_updates {
+                            let value = match update {
+                                DatabaseUpdate::Set(value) => Some(value.clone()),
+                                DatabaseUpdate::Delete => None,
+                            };
+                            changes.push(((partition_key.clone(), sort_key.clone()), value));
+                        }
+                    }
+                    PartitionDatabaseUpdates::Reset {
+                        new_substate_values,
+                    } => {
+                        for (sort_key, _) in self.list_entries(&partition_key) {
+                            if !new_substate_values.contains_key(&sort_key) {
+                                changes.push(((partition_key.clone(), sort_key), None));
+                            }
+                        }
+                        for (sort_key, value) in new_substate_values {
+                            changes.push((
+                                (partition_key.clone(), sort_key.clone()),
+                                Some(value.clone()),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    /// Adds the history entries and the change set of the given changes to the batch.
+    fn record_state_history(
+        &self,
+        batch: &mut WriteBatch,
+        state_history: &StateHistoryMetadata,
+        state_version: u64,
+        changes: &[(DbSubstateKey, Option<DbSubstateValue>)],
+    ) {
+        let history_cf = self.cf(STATE_HISTORY_CF);
+        for ((partition_key, sort_key), value) in changes {
+            let key_prefix = encode_history_key_prefix(partition_key, sort_key);
+            if !self.has_history_entries(&key_prefix) {
+                if let Some(previous_value) = self.get_substate(partition_key, sort_key) {
+                    batch.put_cf(
+                        history_cf,
+                        encode_history_key(&key_prefix, state_history.earliest_state_version),
+                        scrypto_encode(&Some(previous_value)).unwrap(),
+                    );
+                }
+            }
+            batch.put_cf(
+                history_cf,
+                encode_history_key(&key_prefix, state_version),
+                scrypto_encode(value).unwrap(),
+            );
+        }
+        let changed_substate_keys = changes
+            .iter()
+            .map(|(substate_key, _)| substate_key.clone())
+            .collect::<Vec<DbSubstateKey>>();
+        batch.put_cf(
+            self.cf(STATE_HISTORY_CHANGE_SETS_CF),
+            state_version.to_be_bytes(),
+            scrypto_encode(&changed_substate_keys).unwrap(),
+        );
+    }
+
+    /// Adds the deletion of the history of the state versions up to the new earliest one to the
+    /// batch, given the substates changed by the (not yet applied) commit of the state version.
+    fn prune_state_history(
+        &self,
+        batch: &mut WriteBatch,
+        state_history: &StateHistoryMetadata,
+        new_earliest_state_version: u64,
+        state_version: u64,
+        changed_substate_keys: impl Iterator<Item = DbSubstateKey>,
+        is_recorded: bool,
+    ) {
+        let changed_key_prefixes: BTreeSet<Vec<u8>> = changed_substate_keys
+            .map(|(partition_key, sort_key)| encode_history_key_prefix(&partition_key, &sort_key))
+            .collect();
+
+        // The change sets of the committed state versions which are no longer retained
+        let change_sets_cf = self.cf(STATE_HISTORY_CHANGE_SETS_CF);
+        let mut prunable_key_prefixes = BTreeSet::new();
+        for state_version in (state_history.earliest_state_version + 1)
+            ..=new_earliest_state_version.min(state_version - 1)
+        {
+            let Some(bytes) = self
+                .db
+                .get_cf(change_sets_cf, state_version.to_be_bytes())
+                .unwrap()
+            else {
+                continue;
+            };
+            let changed_substate_keys: Vec<DbSubstateKey> = scrypto_decode(&bytes).unwrap();
+            for (partition_key, sort_key) in changed_substate_keys {
+                prunable_key_prefixes.insert(encode_history_key_prefix(&partition_key, &sort_key));
+            }
+            batch.delete_cf(change_sets_cf, state_version.to_be_bytes());
+        }
+        // The committed state version itself, if it becomes the earliest one
+        if !is_recorded {
+            prunable_key_prefixes.extend(changed_key_prefixes.iter().cloned());
+        }
+
+        for key_prefix in prunable_key_prefixes {
+            // A recorded change is a later entry, which is only written by the batch
+            let has_recorded_change = is_recorded && changed_key_prefixes.contains(&key_prefix);
+            self.prune_substate_history(
+                batch,
+                &key_prefix,
+                new_earliest_state_version,
+                has_recorded_change,
+            );
+        }
+    }
+
+    /// Adds the deletion of the history entries of a substate which are not needed to read it at
+    /// the given earliest state version or later to the batch.
+    fn prune_substate_history(
+        &self,
+        batch: &mut WriteBatch,
+        key_prefix: &[u8],
+        earliest_state_version: u64,
+        has_recorded_change: bool,
+    ) {
+        let history_cf = self.cf(STATE_HISTORY_CF);
+        let mut prunable_entries = Vec::new();
+        let mut has_later_entries = has_recorded_change;
+        for (key, value) in self.iter_history_entries(key_prefix) {
+            let state_version = u64::from_be_bytes(copy_u8_array(&key[key_prefix.len()..]));
+            if state_version <= earliest_state_version {
+                prunable_entries.push((key, value));
+            } else {
+                has_later_entries = true;
+                break;
+            }
+        }
+        // The last entry still holds the value at the earliest state version - unless it was
+        // a deletion, or no later change happened (i.e. the current value is the same)
+        if let Some((_, value)) = prunable_entries.last() {
+            let value: Option<DbSubstateValue> = scrypto_decode(value).unwrap();
+            if value.is_some() && has_later_entries {
+                prunable_entries.pop();
+            }
+        }
+        for (key, _) in prunable_entries {
+            batch.delete_cf(history_cf, key);
+        }
+    }
+
+    fn iter_history_entries<'a>(
+        &'a self,
+        key_prefix: &'a [u8],
+    ) -> impl Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a {
+        self.db
+            .iterator_cf(
+                self.cf(STATE_HISTORY_CF),
+                IteratorMode::From(key_prefix, Direction::Forward),
+            )
+            .map(|kv| kv.unwrap())
+            .take_while(move |(key, _)| key.starts_with(key_prefix))
+    }
+
+    fn has_history_entries(&self, key_prefix: &[u8]) -> bool {
+        self.iter_history_entries(key_prefix).next().is_some()
+    }
+
+    fn assert_state_version_available(&self, state_version: u64) {
+        let available_state_versions = self.available_state_versions();
+        assert!(
+            available_state_versions.contains(&state_version),
+            "State version {} is not available (available: {:?})",
+            state_version,
+            available_state_versions
+        );
+    }
+}
+
+impl HistoricalSubstateDatabase for RocksDBWithMerkleTreeSubstateStore {
+    fn available_state_versions(&self) -> RangeInclusive<u64> {
+        let current_state_version = self.get_current_version();
+        match &self.state_history {
+            Some(state_history) => state_history.earliest_state_version..=current_state_version,
+            None => current_state_version..=current_state_version,
+        }
+    }
+
+    fn get_substate_at(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        self.assert_state_version_available(state_version);
+        if state_version == self.get_current_version() {
+            return self.get_substate(partition_key, sort_key);
+        }
+
+        let key_prefix = encode_history_key_prefix(partition_key, sort_key);
+        let latest_entry = self
+            .db
+            .iterator_cf(
+                self.cf(STATE_HISTORY_CF),
+                IteratorMode::From(
+                    &encode_history_key(&key_prefix, state_version),
+                    Direction::Reverse,
+                ),
+            )
+            .next()
+            .map(|kv| kv.unwrap())
+            .filter(|(key, _)| key.starts_with(&key_prefix));
+        match latest_entry {
+            Some((_, value)) => scrypto_decode(&value).unwrap(),
+            // The substate only appeared later
+            None if self.has_history_entries(&key_prefix) => None,
+            // The substate has not changed since
+            None => self.get_substate(partition_key, sort_key),
+        }
+    }
+
+    fn list_entries_at(
+        &self,
+        state_version: u64,
+        partition_key: &DbPartitionKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.assert_state_version_available(state_version);
+        if state_version == self.get_current_version() {
+            return self.list_entries(partition_key);
+        }
+
+        let partition_prefix = encode_to_rocksdb_bytes(partition_key, &DbSortKey(vec![]));
+        let partition_prefix_len = partition_prefix.len();
+        let mut history_entries = self
+            .db
+            .iterator_cf(
+                self.cf(STATE_HISTORY_CF),
+                IteratorMode::From(&partition_prefix, Direction::Forward),
+            )
+            .map(|kv| kv.unwrap())
+            .take_while(move |(key, _)| key.starts_with(&partition_prefix))
+            .map(move |(key, value)| {
+                let (sort_key, entry_state_version) =
+                    decode_history_key(partition_prefix_len, &key);
+                (sort_key, entry_state_version, value)
+            })
+            .peekable();
+        // The value of each substate with history entries, as of the state version
+        let historical_values = iter::from_fn(move || {
+            let (sort_key, entry_state_version, value) = history_entries.next()?;
+            let mut value_at_state_version =
+                Some(value).filter(|_| entry_state_version <= state_version);
+            while history_entries
+                .peek()
+                .map_or(false, |(next_sort_key, _, _)| *next_sort_key == sort_key)
+            {
+                let (_, entry_state_version, value) = history_entries.next().unwrap();
+                if entry_state_version <= state_version {
+                    value_at_state_version = Some(value);
+                }
+            }
+            let value_at_state_version = value_at_state_version
+                .and_then(|value| scrypto_decode::<Option<DbSubstateValue>>(&value).unwrap());
+            Some((sort_key, value_at_state_version))
+        });
+
+        Box::new(MergedEntries::new(
+            self.list_entries(partition_key),
+            historical_values,
+        ))
+    }
+}
+
+/// Encodes the common prefix of the history entry keys of a substate.
+///
+/// The zero bytes of the sort key are escaped and the sort key is terminated, so that the prefixes
+/// of different substates never overlap, while the order of the sort keys is preserved.
+fn encode_history_key_prefix(partition_key: &DbPartitionKey, sort_key: &DbSortKey) -> Vec<u8> {
+    let mut buffer = encode_to_rocksdb_bytes(partition_key, &DbSortKey(vec![]));
+    for byte in &sort_key.0 {
+        buffer.push(*byte);
+        if *byte == 0 {
+            buffer.push(0xFF);
+        }
+    }
+    buffer.extend([0, 0]);
+    buffer
+}
+
+fn encode_history_key(key_prefix: &[u8], state_version: u64) -> Vec<u8> {
+    [key_prefix, &state_version.to_be_bytes()].concat()
+}
+
+fn decode_history_key(partition_prefix_len: usize, key: &[u8]) -> (DbSortKey, u64) {
+    let mut sort_key = Vec::new();
+    let mut offset = partition_prefix_len;
+    loop {
+        let byte = key[offset];
+        if byte == 0 {
+            offset += 2;
+            if key[offset - 1] == 0 {
+                break;
+            }
+        } else {
+            offset += 1;
+        }
+        sort_key.push(byte);
+    }
+    let state_version = u64::from_be_bytes(copy_u8_array(&key[offset..]));
+    (DbSortKey(sort_key), state_version)
+}
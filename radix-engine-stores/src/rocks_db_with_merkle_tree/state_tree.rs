@@ -1,4 +1,4 @@
-use crate::hash_tree::put_at_next_version;
+use crate::hash_tree::put_at_version;
 use crate::hash_tree::tree_store::{
     NodeKey, ReadableTreeStore, StaleTreePart, TreeNode, WriteableTreeStore,
 };
@@ -57,12 +57,14 @@ impl StateHashTreeDiff {
 pub fn compute_state_tree_update<S: ReadableTreeStore>(
     store: &S,
     parent_state_version: u64,
+    next_state_version: u64,
     database_updates: &DatabaseUpdates,
 ) -> (StateHashTreeDiff, Hash) {
     let mut collector = CollectingTreeStore::new(store);
-    let root_hash = put_at_next_version(
+    let root_hash = put_at_version(
         &mut collector,
         Some(parent_state_version).filter(|v| *v > 0),
+        next_state_version,
         database_updates,
     );
     (collector.into_diff(), root_hash)
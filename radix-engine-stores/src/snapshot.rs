@@ -0,0 +1,479 @@
+//! A portable file format for the state of a ledger, for moving it between substate stores.
+//!
+//! A snapshot consists of the [`SNAPSHOT_MAGIC`] bytes, followed by a sequence of records, each
+//! prefixed by its SBOR-encoded length as a little-endian `u32`:
+//! * a [`VersionedLedgerSnapshotHeader`], with the state version and the state hash tree root of
+//!   the ledger,
+//! * for each partition, in the order listed by the database, a [`SnapshotRecord::Partition`]
+//!   record followed by [`SnapshotRecord::Substates`] records with its substates in chunks,
+//! * a single [`SnapshotRecord::End`] record, with the number of partitions and substates.
+//!
+//! Both the export and the import stream the substates, and check the state hash tree root (which
+//! only depends on the contents of the substates) against the one in the header. The import does
+//! so in a first pass over the snapshot, before committing anything.
+
+use crate::hash_tree::put_at_next_version;
+use crate::hash_tree::tree_store::{TypedInMemoryTreeStore, Version};
+#[cfg(feature = "rocksdb")]
+use crate::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+use radix_engine_common::crypto::Hash;
+use radix_engine_common::data::scrypto::{
+    scrypto_decode, scrypto_encode, ScryptoDecode, ScryptoEncode,
+};
+use radix_engine_store_interface::interface::*;
+use sbor::rust::mem;
+use sbor::rust::prelude::*;
+use sbor::*;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// The bytes which every snapshot starts with.
+pub const SNAPSHOT_MAGIC: [u8; 8] = *b"RDXSNAP\0";
+
+/// The maximum number of substates in a single [`SnapshotRecord::Substates`] record.
+const MAX_SUBSTATES_PER_RECORD: usize = 1000;
+/// The number of bytes of substate values after which a [`SnapshotRecord::Substates`] record is
+/// closed, even if it has fewer than [`MAX_SUBSTATES_PER_RECORD`] substates.
+const RECORD_SIZE_TARGET: usize = 1024 * 1024;
+/// The maximum length of a record accepted on read, which protects the reader from allocating
+/// arbitrary amounts of memory for a corrupted length prefix.
+const MAX_RECORD_LENGTH: u32 = 256 * 1024 * 1024;
+/// The number of substates committed to the database in a single batch by the import.
+const IMPORT_BATCH_SIZE: usize = 10_000;
+
+define_single_versioned! {
+    #[derive(Debug, Clone, PartialEq, Eq, Sbor)]
+    pub enum VersionedLedgerSnapshotHeader => LedgerSnapshotHeader = LedgerSnapshotHeaderV1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Sbor)]
+pub struct LedgerSnapshotHeaderV1 {
+    /// The state version of the ledger, as tracked by the store it was exported from.
+    pub state_version: u64,
+    /// The root of the state hash tree of all the substates of the snapshot.
+    pub state_root_hash: Hash,
+}
+
+/// A record of a snapshot, following its header.
+#[derive(Debug, Clone, PartialEq, Eq, Sbor)]
+pub enum SnapshotRecord {
+    /// Starts a partition, which all the following substates belong to.
+    Partition(DbPartitionKey),
+    /// A chunk of the substates of the current partition, in the order of their sort keys.
+    Substates(Vec<(DbSortKey, DbSubstateValue)>),
+    /// Ends the snapshot.
+    End {
+        partition_count: u64,
+        substate_count: u64,
+    },
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    IOError(io::Error),
+    EncodeError(EncodeError),
+    DecodeError(DecodeError),
+    InvalidMagic,
+    RecordTooLong(usize),
+    /// The snapshot ends right after its magic bytes.
+    MissingHeader,
+    /// The substates of a [`SnapshotRecord::Substates`] record do not belong to any partition.
+    SubstatesWithoutPartition,
+    /// The snapshot ends without an [`SnapshotRecord::End`] record, e.g. because it is truncated.
+    MissingEnd,
+    /// There are more bytes after the [`SnapshotRecord::End`] record.
+    TrailingData,
+    CountMismatch {
+        expected_partition_count: u64,
+        expected_substate_count: u64,
+        partition_count: u64,
+        substate_count: u64,
+    },
+    StateRootHashMismatch {
+        expected: Hash,
+        actual: Hash,
+    },
+    /// The state version of the header can't be reached by committing each non-empty batch of the
+    /// snapshot as a state version of its own.
+    UnreachableStateVersion {
+        state_version: u64,
+        batch_count: u64,
+    },
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        SnapshotError::IOError(error)
+    }
+}
+
+/// The counts of the contents of a snapshot which was exported, imported or verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    pub header: LedgerSnapshotHeader,
+    pub partition_count: u64,
+    pub substate_count: u64,
+}
+
+/// Computes the root of the state hash tree of all the substates of the given database, which is
+/// the same as the one of a [`crate::hash_tree_support::HashTreeUpdatingDatabase`] or a
+/// `RocksDBWithMerkleTreeSubstateStore` with the same substates.
+pub fn compute_state_root_hash<S: SubstateDatabase + ListableSubstateDatabase>(
+    substate_db: &S,
+) -> Hash {
+    let mut hasher = StateRootHasher::new();
+    let mut batch = ImportBatch::default();
+    for partition_key in substate_db.list_partition_keys() {
+        for (sort_key, value) in substate_db.list_entries(&partition_key) {
+            batch.add(&partition_key, sort_key, value);
+            if batch.substate_count >= IMPORT_BATCH_SIZE {
+                hasher.put(&batch.take());
+            }
+        }
+    }
+    hasher.put(&batch.take());
+    hasher.root_hash()
+}
+
+/// Writes a snapshot of all the substates of the given database, with the given header.
+///
+/// The state root hash of the header is computed while streaming, which requires it to be known
+/// upfront (see [`compute_state_root_hash`]): a snapshot whose substates do not match it is still
+/// written, but reported as a [`SnapshotError::StateRootHashMismatch`].
+pub fn export_snapshot<S, W>(
+    substate_db: &S,
+    header: LedgerSnapshotHeader,
+    writer: &mut W,
+) -> Result<SnapshotSummary, SnapshotError>
+where
+    S: SubstateDatabase + ListableSubstateDatabase,
+    W: Write,
+{
+    writer.write_all(&SNAPSHOT_MAGIC)?;
+    write_record(
+        writer,
+        &VersionedLedgerSnapshotHeader::new_latest(header.clone()),
+    )?;
+
+    let mut hasher = StateRootHasher::new();
+    let mut batch = ImportBatch::default();
+    let mut partition_count = 0u64;
+    let mut substate_count = 0u64;
+    for partition_key in substate_db.list_partition_keys() {
+        write_record(writer, &SnapshotRecord::Partition(partition_key.clone()))?;
+        partition_count += 1;
+
+        let mut chunk = Vec::new();
+        let mut chunk_size = 0;
+        for (sort_key, value) in substate_db.list_entries(&partition_key) {
+            chunk_size += value.len();
+            chunk.push((sort_key.clone(), value.clone()));
+            batch.add(&partition_key, sort_key, value);
+            substate_count += 1;
+            if chunk.len() >= MAX_SUBSTATES_PER_RECORD || chunk_size >= RECORD_SIZE_TARGET {
+                write_record(writer, &SnapshotRecord::Substates(mem::take(&mut chunk)))?;
+                chunk_size = 0;
+            }
+            if batch.substate_count >= IMPORT_BATCH_SIZE {
+                hasher.put(&batch.take());
+            }
+        }
+        if !chunk.is_empty() {
+            write_record(writer, &SnapshotRecord::Substates(chunk))?;
+        }
+    }
+    write_record(
+        writer,
+        &SnapshotRecord::End {
+            partition_count,
+            substate_count,
+        },
+    )?;
+    writer.flush()?;
+
+    hasher.put(&batch.take());
+    check_state_root_hash(&header, hasher.root_hash())?;
+    Ok(SnapshotSummary {
+        header,
+        partition_count,
+        substate_count,
+    })
+}
+
+/// Reads a snapshot and checks its integrity, i.e. that it is complete and that its substates
+/// match the state root hash of its header, without importing it anywhere.
+pub fn verify_snapshot<R: Read>(reader: &mut R) -> Result<SnapshotSummary, SnapshotError> {
+    read_snapshot(reader, |_| {})
+}
+
+/// Reads a snapshot and commits all of its substates to the given database, in batches.
+///
+/// The database is expected to be empty: partitions of the snapshot replace those of the database,
+/// but other partitions are left untouched. The snapshot is read twice: it is verified in full (as
+/// by [`verify_snapshot`]) before the reader is rewound and the first batch is committed, so a
+/// corrupted snapshot leaves the database untouched. Only a snapshot which changes between the two
+/// passes may still fail after some batches have been committed.
+///
+/// Note that stores which track a state version advance it once per committed batch, so it does
+/// not match the state version of the header, unlike their state root hash. Use
+/// [`import_snapshot_at_state_version`] to import into a `RocksDBWithMerkleTreeSubstateStore`.
+pub fn import_snapshot<R, S>(
+    reader: &mut R,
+    substate_db: &mut S,
+) -> Result<SnapshotSummary, SnapshotError>
+where
+    R: Read + Seek,
+    S: CommittableSubstateDatabase,
+{
+    let start = reader.stream_position()?;
+    read_snapshot(reader, |_| {})?;
+    reader.seek(SeekFrom::Start(start))?;
+    read_snapshot(reader, |database_updates| {
+        substate_db.commit(database_updates)
+    })
+}
+
+/// Reads a snapshot and commits all of its substates to the given store, as by [`import_snapshot`],
+/// so that the store ends at the state version of the header, which transactions following the
+/// snapshot can then be committed on top of.
+///
+/// Each non-empty batch is committed as a state version of its own, the last one being the state
+/// version of the header, and the state versions before the first batch are skipped. The store is
+/// expected to be empty and not to record its state history.
+#[cfg(feature = "rocksdb")]
+pub fn import_snapshot_at_state_version<R: Read + Seek>(
+    reader: &mut R,
+    substate_db: &mut RocksDBWithMerkleTreeSubstateStore,
+) -> Result<SnapshotSummary, SnapshotError> {
+    let start = reader.stream_position()?;
+    let mut batch_count = 0u64;
+    let summary = read_snapshot(reader, |database_updates| {
+        if !database_updates.node_updates.is_empty() {
+            batch_count += 1;
+        }
+    })?;
+    let state_version = summary.header.state_version;
+    if batch_count > state_version || (batch_count == 0 && state_version > 0) {
+        return Err(SnapshotError::UnreachableStateVersion {
+            state_version,
+            batch_count,
+        });
+    }
+
+    reader.seek(SeekFrom::Start(start))?;
+    let mut next_state_version = state_version + 1 - batch_count;
+    read_snapshot(reader, |database_updates| {
+        if !database_updates.node_updates.is_empty() {
+            substate_db.commit_at_version(database_updates, next_state_version);
+            next_state_version += 1;
+        }
+    })
+}
+
+fn read_snapshot<R: Read, F: FnMut(&DatabaseUpdates)>(
+    reader: &mut R,
+    mut on_batch: F,
+) -> Result<SnapshotSummary, SnapshotError> {
+    let mut magic = [0u8; SNAPSHOT_MAGIC.len()];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|_| SnapshotError::InvalidMagic)?;
+    if magic != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+    let header = read_record::<_, VersionedLedgerSnapshotHeader>(reader)?
+        .ok_or(SnapshotError::MissingHeader)?
+        .into_latest();
+
+    let mut hasher = StateRootHasher::new();
+    let mut batch = ImportBatch::default();
+    let mut partition_key = None;
+    let mut partition_count = 0u64;
+    let mut substate_count = 0u64;
+    loop {
+        match read_record::<_, SnapshotRecord>(reader)?.ok_or(SnapshotError::MissingEnd)? {
+            SnapshotRecord::Partition(key) => {
+                partition_key = Some(key);
+                partition_count += 1;
+            }
+            SnapshotRecord::Substates(substates) => {
+                let partition_key = partition_key
+                    .as_ref()
+                    .ok_or(SnapshotError::SubstatesWithoutPartition)?;
+                for (sort_key, value) in substates {
+                    batch.add(partition_key, sort_key, value);
+                    substate_count += 1;
+                }
+                if batch.substate_count >= IMPORT_BATCH_SIZE {
+                    let database_updates = batch.take();
+                    hasher.put(&database_updates);
+                    on_batch(&database_updates);
+                }
+            }
+            SnapshotRecord::End {
+                partition_count: expected_partition_count,
+                substate_count: expected_substate_count,
+            } => {
+                if (expected_partition_count, expected_substate_count)
+                    != (partition_count, substate_count)
+                {
+                    return Err(SnapshotError::CountMismatch {
+                        expected_partition_count,
+                        expected_substate_count,
+                        partition_count,
+                        substate_count,
+                    });
+                }
+                break;
+            }
+        }
+    }
+    if reader.read(&mut [0u8])? != 0 {
+        return Err(SnapshotError::TrailingData);
+    }
+
+    let database_updates = batch.take();
+    hasher.put(&database_updates);
+    on_batch(&database_updates);
+    check_state_root_hash(&header, hasher.root_hash())?;
+    Ok(SnapshotSummary {
+        header,
+        partition_count,
+        substate_count,
+    })
+}
+
+fn check_state_root_hash(header: &LedgerSnapshotHeader, actual: Hash) -> Result<(), SnapshotError> {
+    if header.state_root_hash != actual {
+        return Err(SnapshotError::StateRootHashMismatch {
+            expected: header.state_root_hash,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn write_record<W: Write, T: ScryptoEncode>(
+    writer: &mut W,
+    record: &T,
+) -> Result<(), SnapshotError> {
+    let bytes = scrypto_encode(record).map_err(SnapshotError::EncodeError)?;
+    if bytes.len() > MAX_RECORD_LENGTH as usize {
+        return Err(SnapshotError::RecordTooLong(bytes.len()));
+    }
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Reads the next record, or returns `None` if the reader is at its end.
+fn read_record<R: Read, T: ScryptoDecode>(reader: &mut R) -> Result<Option<T>, SnapshotError> {
+    let mut length_bytes = [0u8; 4];
+    let mut read = 0;
+    while read < length_bytes.len() {
+        match reader.read(&mut length_bytes[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(SnapshotError::MissingEnd),
+            Ok(n) => read += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    let length = u32::from_le_bytes(length_bytes);
+    if length > MAX_RECORD_LENGTH {
+        return Err(SnapshotError::RecordTooLong(length as usize));
+    }
+    let mut bytes = vec![0u8; length as usize];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::UnexpectedEof => SnapshotError::MissingEnd,
+            _ => SnapshotError::IOError(error),
+        })?;
+    scrypto_decode(&bytes)
+        .map(Some)
+        .map_err(SnapshotError::DecodeError)
+}
+
+/// A batch of substates of the snapshot, which are hashed (and committed) together.
+///
+/// A partition which is split across batches is reset by the first of them, and only extended by
+/// the following ones.
+#[derive(Default)]
+struct ImportBatch {
+    started_partitions: IndexSet<DbPartitionKey>,
+    partitions: IndexMap<DbPartitionKey, IndexMap<DbSortKey, DbSubstateValue>>,
+    substate_count: usize,
+}
+
+impl ImportBatch {
+    fn add(&mut self, partition_key: &DbPartitionKey, sort_key: DbSortKey, value: DbSubstateValue) {
+        self.partitions
+            .entry(partition_key.clone())
+            .or_default()
+            .insert(sort_key, value);
+        self.substate_count += 1;
+    }
+
+    fn take(&mut self) -> DatabaseUpdates {
+        let mut database_updates = DatabaseUpdates::default();
+        for (partition_key, substates) in mem::take(&mut self.partitions) {
+            let partition_updates = if self.started_partitions.insert(partition_key.clone()) {
+                PartitionDatabaseUpdates::Reset {
+                    new_substate_values: substates,
+                }
+            } else {
+                PartitionDatabaseUpdates::Delta {
+                    substate_updates: substates
+                        .into_iter()
+                        .map(|(sort_key, value)| (sort_key, DatabaseUpdate::Set(value)))
+                        .collect(),
+                }
+            };
+            database_updates
+                .node_updates
+                .entry(partition_key.node_key)
+                .or_default()
+                .partition_updates
+                .insert(partition_key.partition_num, partition_updates);
+        }
+        self.substate_count = 0;
+        database_updates
+    }
+}
+
+/// Builds an in-memory state hash tree from batches of substates.
+///
+/// Stale nodes are pruned after each batch, but all the nodes of the current tree are kept in
+/// memory until the hasher is dropped: its memory use grows linearly with the number of substates
+/// hashed (one leaf, plus its share of the internal nodes, per substate and per partition and
+/// node), i.e. roughly a few hundred bytes per substate. Exporting, verifying or importing the
+/// state of a full ledger hence needs memory in proportion to its size, on top of the database.
+struct StateRootHasher {
+    tree_store: TypedInMemoryTreeStore,
+    current_version: Option<Version>,
+    current_hash: Hash,
+}
+
+impl StateRootHasher {
+    fn new() -> Self {
+        Self {
+            tree_store: TypedInMemoryTreeStore::with_pruning(),
+            current_version: None,
+            // The root hash of a store without any commits
+            current_hash: Hash([0; Hash::LENGTH]),
+        }
+    }
+
+    fn put(&mut self, database_updates: &DatabaseUpdates) {
+        if database_updates.node_updates.is_empty() {
+            return;
+        }
+        self.current_hash =
+            put_at_next_version(&mut self.tree_store, self.current_version, database_updates);
+        self.current_version = Some(self.current_version.unwrap_or(0) + 1);
+    }
+
+    fn root_hash(&self) -> Hash {
+        self.current_hash
+    }
+}
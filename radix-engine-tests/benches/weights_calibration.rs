@@ -0,0 +1,531 @@
+//! Calibrates WASM instruction weights (`weights.rs`) and fee table entries (`fee_table.rs`,
+//! `native_function_base_costs.csv`) against CPU instructions measured with `cpu_ram_metrics`.
+//!
+//! Run with `./calibrate-weights.sh`. The report and proposed diffs are written to the directory
+//! given by `WEIGHTS_CALIBRATION_OUTPUT_DIR` (`target/weights-calibration` by default).
+
+use radix_engine::kernel::resources_tracker::ResourcesTracker;
+use radix_engine::system::system_modules::costing::{FeeTable, SystemLoanFeeReserve};
+use radix_engine::transaction::CostingParameters;
+use radix_engine::types::*;
+use radix_engine::vm::wasm::{
+    DefaultWasmEngine, WasmEngine, WasmInstance, WasmRuntime, WasmValidator,
+};
+use radix_engine::vm::wasm_runtime::NoOpWasmRuntime;
+use radix_engine_profiling::weights_calibration::*;
+use radix_engine_queries::typed_substate_layout::CodeHash;
+use sbor::rust::iter;
+use scrypto_unit::TestRunnerBuilder;
+use std::path::PathBuf;
+use transaction::prelude::*;
+use transaction::validation::{recover_secp256k1, verify_secp256k1};
+use wabt::wat2wasm;
+
+/// Number of measurements of each data point, the median of which is used for fitting.
+const ITERATIONS: usize = 11;
+
+/// Number of times a single instruction is repeated in the measured function body.
+const INSTRUCTION_REPETITIONS: [u64; 6] = [0, 1000, 2000, 3000, 4000, 5000];
+
+/// Number of times an instruction with a variable number of entries (eg. `br_table` targets)
+/// is repeated, so that a single entry is measurable.
+const PER_ENTRY_REPETITIONS: u64 = 100;
+
+const WEIGHTS_RS_PATH: &str = "radix-engine/src/vm/wasm/weights.rs";
+const FEE_TABLE_RS_PATH: &str = "radix-engine/src/system/system_modules/costing/fee_table.rs";
+const NATIVE_FUNCTION_COSTS_CSV_PATH: &str = "assets/native_function_base_costs.csv";
+
+/// Benchmarked instructions: `WeightInfo` function name and the WAT of a single repetition,
+/// including supporting instructions (same as in `cost_instr!` params count).
+const INSTRUCTION_BENCHMARKS: &[(&str, &str)] = &[
+    ("instr_i64const", "(drop (i64.const 42))"),
+    ("instr_i64load", "(drop (i64.load (i32.const 8)))"),
+    ("instr_i64store", "(i64.store (i32.const 8) (i64.const 42))"),
+    (
+        "instr_select",
+        "(drop (select (i64.const 1) (i64.const 2) (i32.const 1)))",
+    ),
+    ("instr_if", "(if (i32.const 1) (then (nop)))"),
+    ("instr_br", "(block (br 0))"),
+    ("instr_br_if", "(block (br_if 0 (i32.const 1)))"),
+    ("instr_br_table", "(block (br_table 0 (i32.const 0)))"),
+    ("instr_call", "(call $nop)"),
+    (
+        "instr_call_indirect",
+        "(call_indirect (type $nop_type) (i32.const 0))",
+    ),
+    ("instr_local_get", "(drop (local.get $l))"),
+    ("instr_local_set", "(local.set $l (i64.const 42))"),
+    ("instr_local_tee", "(drop (local.tee $l (i64.const 42)))"),
+    ("instr_global_get", "(drop (global.get $g))"),
+    ("instr_global_set", "(global.set $g (i64.const 42))"),
+    ("instr_memory_size", "(drop (memory.size))"),
+    ("instr_memory_grow", "(drop (memory.grow (i32.const 0)))"),
+    ("instr_i64clz", "(drop (i64.clz (i64.const 42)))"),
+    ("instr_i64ctz", "(drop (i64.ctz (i64.const 42)))"),
+    ("instr_i64popcnt", "(drop (i64.popcnt (i64.const 42)))"),
+    ("instr_i64eqz", "(drop (i64.eqz (i64.const 42)))"),
+    (
+        "instr_i64extendsi32",
+        "(drop (i64.extend_i32_s (i32.const 42)))",
+    ),
+    (
+        "instr_i64extendui32",
+        "(drop (i64.extend_i32_u (i32.const 42)))",
+    ),
+    ("instr_i32wrapi64", "(drop (i32.wrap_i64 (i64.const 42)))"),
+    (
+        "instr_i64eq",
+        "(drop (i64.eq (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64ne",
+        "(drop (i64.ne (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64lts",
+        "(drop (i64.lt_s (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64ltu",
+        "(drop (i64.lt_u (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64gts",
+        "(drop (i64.gt_s (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64gtu",
+        "(drop (i64.gt_u (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64les",
+        "(drop (i64.le_s (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64leu",
+        "(drop (i64.le_u (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64ges",
+        "(drop (i64.ge_s (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64geu",
+        "(drop (i64.ge_u (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64add",
+        "(drop (i64.add (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64sub",
+        "(drop (i64.sub (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64mul",
+        "(drop (i64.mul (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64divs",
+        "(drop (i64.div_s (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64divu",
+        "(drop (i64.div_u (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64rems",
+        "(drop (i64.rem_s (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64remu",
+        "(drop (i64.rem_u (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64and",
+        "(drop (i64.and (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64or",
+        "(drop (i64.or (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64xor",
+        "(drop (i64.xor (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64shl",
+        "(drop (i64.shl (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64shrs",
+        "(drop (i64.shr_s (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64shru",
+        "(drop (i64.shr_u (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64rotl",
+        "(drop (i64.rotl (i64.const 42) (i64.const 7)))",
+    ),
+    (
+        "instr_i64rotr",
+        "(drop (i64.rotr (i64.const 42) (i64.const 7)))",
+    ),
+];
+
+fn main() {
+    let output_dir = std::env::var("WEIGHTS_CALIBRATION_OUTPUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| workspace_path("target/weights-calibration"));
+
+    let mut calibration = WeightsCalibration::new();
+    calibrate_instructions(&mut calibration);
+    calibrate_fee_table_entries(&mut calibration);
+    calibrate_native_functions(&mut calibration);
+    let report = calibration.fit();
+
+    let weights_rs = std::fs::read_to_string(workspace_path(WEIGHTS_RS_PATH)).unwrap();
+    let fee_table_rs = std::fs::read_to_string(workspace_path(FEE_TABLE_RS_PATH)).unwrap();
+    let native_function_costs_csv =
+        std::fs::read_to_string(workspace_path(NATIVE_FUNCTION_COSTS_CSV_PATH)).unwrap();
+
+    std::fs::create_dir_all(&output_dir).unwrap();
+    std::fs::write(
+        output_dir.join("report.md"),
+        report.to_markdown(&weights_rs, &fee_table_rs, &native_function_costs_csv),
+    )
+    .unwrap();
+    std::fs::write(
+        output_dir.join("weights.rs.diff"),
+        unified_diff(
+            WEIGHTS_RS_PATH,
+            &weights_rs,
+            &report.patch_weights_rs(&weights_rs),
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        output_dir.join("fee_table.rs.diff"),
+        unified_diff(
+            FEE_TABLE_RS_PATH,
+            &fee_table_rs,
+            &report.patch_fee_table_rs(&fee_table_rs),
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        output_dir.join("native_function_base_costs.csv.diff"),
+        unified_diff(
+            NATIVE_FUNCTION_COSTS_CSV_PATH,
+            &native_function_costs_csv,
+            &report.patch_native_function_costs_csv(&native_function_costs_csv),
+        ),
+    )
+    .unwrap();
+
+    println!("Weights calibration written to: {}", output_dir.display());
+}
+
+fn workspace_path(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+fn measure<F: FnMut()>(mut f: F) -> u64 {
+    let mut tracker = ResourcesTracker::start_measurement();
+    f();
+    tracker.end_measurement().cpu_cycles
+}
+
+fn instrument(wat: &str) -> Vec<u8> {
+    let code = wat2wasm(wat).unwrap();
+    WasmValidator::default()
+        .validate(&code, iter::empty())
+        .unwrap()
+        .0
+}
+
+fn create_runtime(wasm_execution_units_consumed: &mut u64) -> Box<dyn WasmRuntime + '_> {
+    let fee_reserve = SystemLoanFeeReserve::new(
+        &CostingParameters::default(),
+        &TransactionCostingParameters {
+            free_credit_in_xrd: Decimal::try_from(PREVIEW_CREDIT_IN_XRD).unwrap(),
+            tip_percentage: DEFAULT_TIP_PERCENTAGE,
+        },
+        false,
+    );
+    Box::new(NoOpWasmRuntime::new(
+        fee_reserve,
+        wasm_execution_units_consumed,
+    ))
+}
+
+/// Returns a module exporting `Test_f`, which executes `body` and returns `()`.
+fn benchmark_module(body: &str, callee_locals: u32, functions: &str) -> String {
+    let callee_locals = if callee_locals > 0 {
+        format!("(local {})", "i64 ".repeat(callee_locals as usize))
+    } else {
+        String::new()
+    };
+    format!(
+        r#"
+(module
+  (type $nop_type (func))
+  (func $nop)
+  (func $with_locals {callee_locals})
+  (table 1 funcref)
+  (elem (i32.const 0) $nop)
+  (global $g (mut i64) (i64.const 0))
+  {functions}
+
+  (func $Test_f (param $0 i64) (result i64)
+    (local $l i64)
+    {body}
+
+    ;; Encode () in SBOR at address 0x0
+    (i32.store8 (i32.const 0) (i32.const 92))
+    (i32.store8 (i32.const 1) (i32.const 33))
+    (i32.store8 (i32.const 2) (i32.const 0))
+
+    ;; Return slice (ptr = 0, len = 3)
+    (i64.const 3)
+  )
+
+  (memory $0 1)
+  (export "memory" (memory $0))
+  (export "Test_f" (func $Test_f))
+)
+"#
+    )
+}
+
+/// Invokes `Test_f` of the module `ITERATIONS` times and returns measured CPU instructions
+/// and consumed WASM execution units of each invocation. Instantiation is not measured.
+fn measure_invocations(wasm_engine: &DefaultWasmEngine, wat: &str) -> Vec<(u64, u64)> {
+    let instrumented_code = instrument(wat);
+    let mut instance = wasm_engine.instantiate(CodeHash(hash(wat)), &instrumented_code);
+
+    (0..ITERATIONS)
+        .map(|_| {
+            let mut wasm_execution_units_consumed = 0;
+            let cpu_instructions = {
+                let mut runtime = create_runtime(&mut wasm_execution_units_consumed);
+                measure(|| {
+                    instance
+                        .invoke_export("Test_f", vec![Buffer(0)], &mut runtime)
+                        .unwrap();
+                })
+            };
+            (cpu_instructions, wasm_execution_units_consumed)
+        })
+        .collect()
+}
+
+fn calibrate_instructions(calibration: &mut WeightsCalibration) {
+    let wasm_engine = DefaultWasmEngine::default();
+
+    for (weight_fn, instruction) in INSTRUCTION_BENCHMARKS {
+        for repetitions in INSTRUCTION_REPETITIONS {
+            let wat = benchmark_module(&instruction.repeat(repetitions as usize), 0, "");
+            for (cpu_instructions, _) in measure_invocations(&wasm_engine, &wat) {
+                calibration.add_instruction_sample(
+                    weight_fn,
+                    CalibrationSample {
+                        repetitions,
+                        cpu_instructions,
+                    },
+                );
+            }
+        }
+    }
+
+    // Instructions costed per entry
+    for entries in (0..=MAX_NUMBER_OF_BR_TABLE_TARGETS as u64).step_by(32) {
+        let instruction = format!(
+            "(block (br_table {}0 (i32.const 0)))",
+            "0 ".repeat(entries as usize)
+        );
+        let wat = benchmark_module(&instruction.repeat(PER_ENTRY_REPETITIONS as usize), 0, "");
+        for (cpu_instructions, _) in measure_invocations(&wasm_engine, &wat) {
+            calibration.add_instruction_sample(
+                "instr_br_table_per_entry",
+                CalibrationSample {
+                    repetitions: entries * PER_ENTRY_REPETITIONS,
+                    cpu_instructions,
+                },
+            );
+        }
+    }
+    for locals in (0..=MAX_NUMBER_OF_FUNCTION_LOCALS as u64).step_by(32) {
+        let wat = benchmark_module(
+            &"(call $with_locals)".repeat(PER_ENTRY_REPETITIONS as usize),
+            locals as u32,
+            "",
+        );
+        for (cpu_instructions, _) in measure_invocations(&wasm_engine, &wat) {
+            calibration.add_instruction_sample(
+                "instr_call_per_local",
+                CalibrationSample {
+                    repetitions: locals * PER_ENTRY_REPETITIONS,
+                    cpu_instructions,
+                },
+            );
+        }
+    }
+}
+
+fn calibrate_fee_table_entries(calibration: &mut WeightsCalibration) {
+    let fee_table = FeeTable::new();
+
+    // Costed per WASM execution unit
+    let wasm_engine = DefaultWasmEngine::default();
+    for n in [1_000, 20_000, 40_000, 60_000, 80_000, 100_000] {
+        let wat = include_str!("../tests/wasm/loop.wat").replace("${n}", &n.to_string());
+        for (cpu_instructions, wasm_execution_units) in measure_invocations(&wasm_engine, &wat) {
+            calibration.add_fee_table_entry_sample(
+                "run_wasm_code_cost",
+                CalibrationSample {
+                    repetitions: wasm_execution_units,
+                    cpu_instructions,
+                },
+            );
+        }
+    }
+    calibration.set_fee_table_entry_current_cost(
+        "run_wasm_code_cost",
+        0f64,
+        fee_table.run_wasm_code_cost(&PACKAGE_PACKAGE, "", 3_000_000) as f64 / 3_000_000f64,
+    );
+
+    // Costed per byte of instrumented code
+    for functions in [1, 200, 400, 600, 800, 1_000] {
+        let functions = (0..functions)
+            .map(|i| {
+                format!(
+                    "(func $f{} (result i64) (i64.add (i64.const {}) (i64.const 1)))\n",
+                    i, i
+                )
+            })
+            .collect::<String>();
+        let instrumented_code = instrument(&benchmark_module("", 0, &functions));
+        for i in 0..ITERATIONS {
+            // A fresh engine and code hash for each measurement, to skip the module cache
+            let wasm_engine = DefaultWasmEngine::default();
+            let code_hash = CodeHash(hash(format!("{}-{}", instrumented_code.len(), i)));
+            let cpu_instructions = measure(|| {
+                wasm_engine.instantiate(code_hash, &instrumented_code);
+            });
+            calibration.add_fee_table_entry_sample(
+                "instantiate_wasm_code_cost",
+                CalibrationSample {
+                    repetitions: instrumented_code.len() as u64,
+                    cpu_instructions,
+                },
+            );
+        }
+    }
+    calibration.set_fee_table_entry_current_cost(
+        "instantiate_wasm_code_cost",
+        0f64,
+        fee_table.instantiate_wasm_code_cost(1_000_000) as f64 / 1_000_000f64,
+    );
+
+    // Costed per byte of invocation input and output
+    for elements in [0, 1_000, 2_000, 3_000, 4_000, 5_000] {
+        let payload = scrypto_encode(&vec![(7u32, "calibration".to_string()); elements]).unwrap();
+        for _ in 0..ITERATIONS {
+            let cpu_instructions = measure(|| {
+                scrypto_decode::<ScryptoValue>(&payload).unwrap();
+            });
+            calibration.add_fee_table_entry_sample(
+                "after_invoke_cost",
+                CalibrationSample {
+                    repetitions: payload.len() as u64,
+                    cpu_instructions,
+                },
+            );
+        }
+    }
+    calibration.set_fee_table_entry_current_cost(
+        "after_invoke_cost",
+        0f64,
+        fee_table.after_invoke_cost(1_000_000) as f64 / 1_000_000f64,
+    );
+
+    // Costed per signature
+    let message_hash = hash("calibration");
+    let signer = Secp256k1PrivateKey::from_u64(123123123123).unwrap();
+    let signature = signer.sign(&message_hash);
+    for signatures in [1, 4, 8, 12, 16] {
+        for _ in 0..ITERATIONS {
+            let cpu_instructions = measure(|| {
+                for _ in 0..signatures {
+                    let public_key = recover_secp256k1(&message_hash, &signature).unwrap();
+                    verify_secp256k1(&message_hash, &public_key, &signature);
+                }
+            });
+            calibration.add_fee_table_entry_sample(
+                "verify_tx_signatures_cost",
+                CalibrationSample {
+                    repetitions: signatures,
+                    cpu_instructions,
+                },
+            );
+        }
+    }
+    calibration.set_fee_table_entry_current_cost(
+        "verify_tx_signatures_cost",
+        0f64,
+        fee_table.verify_tx_signatures_cost(1) as f64,
+    );
+}
+
+fn calibrate_native_functions(calibration: &mut WeightsCalibration) {
+    let mut test_runner = TestRunnerBuilder::new().without_trace().build();
+    let (public_key, _, account) = test_runner.new_allocated_account();
+
+    // Native functions invoked by a manifest instruction, which is repeated in the transaction
+    let benchmarks: [(&str, fn(ManifestBuilder, usize) -> ManifestBuilder); 2] = [
+        ("AuthZone_create_proof_of_amount", |builder, i| {
+            builder.create_proof_from_auth_zone_of_amount(XRD, dec!(1), format!("proof{}", i))
+        }),
+        ("AuthZone_create_proof_of_all", |builder, i| {
+            builder.create_proof_from_auth_zone_of_all(XRD, format!("proof{}", i))
+        }),
+    ];
+
+    for (export_name, add_call) in benchmarks {
+        for calls in [1, 10, 20, 30, 40] {
+            let manifest = (0..calls)
+                .fold(
+                    ManifestBuilder::new()
+                        .lock_fee_from_faucet()
+                        .create_proof_from_account_of_amount(account, XRD, dec!(1)),
+                    add_call,
+                )
+                .drop_all_proofs()
+                .build();
+            for _ in 0..ITERATIONS {
+                let receipt = test_runner.execute_manifest(
+                    manifest.clone(),
+                    vec![NonFungibleGlobalId::from_public_key(&public_key)],
+                );
+                receipt.expect_commit_success();
+                let cpu_instructions = receipt.resources_usage.as_ref().map(|x| x.cpu_cycles);
+                calibration.add_native_function_sample(
+                    &RESOURCE_PACKAGE.to_hex(),
+                    export_name,
+                    CalibrationSample {
+                        repetitions: calls as u64,
+                        cpu_instructions: cpu_instructions
+                            .expect("Resources usage is available with cpu_ram_metrics feature"),
+                    },
+                );
+            }
+        }
+    }
+}
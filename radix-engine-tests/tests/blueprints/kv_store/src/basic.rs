@@ -63,6 +63,60 @@ mod basic {
         pub fn remove(&mut self, key: String) -> Option<String> {
             self.map.remove(&key)
         }
+
+        pub fn iterate_local() -> Global<Basic> {
+            let map = KeyValueStore::new();
+            map.insert("hello".to_owned(), "hello".to_owned());
+            map.insert("hello2".to_owned(), "hello2".to_owned());
+            map.insert("hello3".to_owned(), "hello3".to_owned());
+            map.remove(&"hello2".to_owned());
+
+            let mut keys = map.keys();
+            keys.sort();
+            assert_eq!(keys, vec!["hello".to_owned(), "hello3".to_owned()]);
+
+            Self { map }
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize()
+        }
+
+        pub fn keys(&self) -> Vec<String> {
+            self.map.keys()
+        }
+
+        pub fn iter(
+            &self,
+            limit: u32,
+            cursor: Option<Vec<u8>>,
+        ) -> (Vec<(String, String)>, Option<Vec<u8>>) {
+            let page = self.map.iter(limit, cursor);
+            (page.entries, page.next_cursor)
+        }
+
+        /// Returns the keys in the order of a paginated scan of the map while it is still
+        /// local, before the map is stored.
+        pub fn new_with_keys_in_pages(
+            keys: Vec<String>,
+            limit: u32,
+        ) -> (Global<Basic>, Vec<String>) {
+            let map = KeyValueStore::new();
+            for key in keys {
+                let value = format!("value_{}", key);
+                map.insert(key, value);
+            }
+            let scanned_keys = keys_in_pages(&map, limit);
+
+            let component = Self { map }
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize();
+            (component, scanned_keys)
+        }
+
+        pub fn keys_in_pages(&self, limit: u32) -> Vec<String> {
+            keys_in_pages(&self.map, limit)
+        }
     }
 }
 
@@ -101,3 +155,16 @@ mod kv_vault {
         }
     }
 }
+
+fn keys_in_pages(map: &KeyValueStore<String, String>, limit: u32) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = map.iter(limit, cursor);
+        keys.extend(page.entries.into_iter().map(|(key, _value)| key));
+        match page.next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => return keys,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod randomness_test {
+    struct RandomnessTest {
+        commitment: Option<RandomSeedCommitment>,
+    }
+
+    impl RandomnessTest {
+        pub fn new() -> Global<RandomnessTest> {
+            Self { commitment: None }
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize()
+        }
+
+        pub fn commit(&mut self) -> RandomSeedCommitment {
+            let commitment = Runtime::commit_random_seed();
+            self.commitment = Some(commitment);
+            commitment
+        }
+
+        pub fn reveal(&self) -> Hash {
+            Runtime::random_seed(self.commitment.expect("Nothing committed"))
+        }
+
+        pub fn commit_and_reveal(&mut self) -> Hash {
+            self.commit();
+            self.reveal()
+        }
+    }
+}
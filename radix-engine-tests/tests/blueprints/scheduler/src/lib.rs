@@ -0,0 +1,68 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod scheduler_test {
+    enable_method_auth! {
+        roles {
+            scheduler => updatable_by: [];
+        },
+        methods {
+            schedule => PUBLIC;
+            cancel => PUBLIC;
+            get_values => PUBLIC;
+            record => restrict_to: [scheduler];
+            fail => restrict_to: [scheduler];
+        }
+    }
+
+    struct SchedulerTest {
+        values: Vec<u32>,
+    }
+
+    impl SchedulerTest {
+        pub fn new() -> Global<SchedulerTest> {
+            Self { values: vec![] }
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .roles(roles! {
+                    scheduler => rule!(require(global_caller(SCHEDULER)));
+                })
+                .globalize()
+        }
+
+        pub fn schedule(
+            &self,
+            method_name: String,
+            value: u32,
+            trigger: ScheduledCallTrigger,
+            fee: Bucket,
+            cancel_rule: AccessRule,
+        ) -> ScheduledCallId {
+            Scheduler::schedule_call(
+                Runtime::global_address(),
+                &method_name,
+                scrypto_args!(value),
+                trigger,
+                fee,
+                cancel_rule,
+            )
+        }
+
+        pub fn cancel(&self, id: ScheduledCallId) -> Bucket {
+            Scheduler::cancel_call(id)
+        }
+
+        pub fn record(&mut self, value: u32) {
+            self.values.push(value);
+        }
+
+        pub fn fail(&mut self, value: u32) {
+            self.values.push(value);
+            panic!("Scheduled call failed");
+        }
+
+        pub fn get_values(&self) -> Vec<u32> {
+            self.values.clone()
+        }
+    }
+}
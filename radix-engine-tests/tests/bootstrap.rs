@@ -80,6 +80,11 @@ fn test_bootstrap_receipt_should_match_constants() {
         .new_component_addresses()
         .contains(&TRANSACTION_TRACKER));
 
+    assert!(system_bootstrap_receipt
+        .expect_commit_success()
+        .new_component_addresses()
+        .contains(&SCHEDULER));
+
     assert!(system_bootstrap_receipt
         .expect_commit_success()
         .new_component_addresses()
@@ -97,6 +102,11 @@ fn test_bootstrap_receipt_should_match_constants() {
         .check_db(&substate_db)
         .expect("Database should be consistent");
     println!("{:#?}", db_results);
+    assert!(
+        db_results.1.errors.is_empty(),
+        "Resource violations: {:?}",
+        db_results.1.errors
+    );
 
     let mut event_checker = SystemEventChecker::<ResourceEventChecker>::new();
     let mut events = Vec::new();
@@ -0,0 +1,221 @@
+use radix_engine::system::actor::Actor;
+use radix_engine::system::system_modules::debugger::*;
+use radix_engine::system::system_modules::execution_trace::BucketSnapshot;
+use radix_engine::types::*;
+use radix_engine::vm::NoExtension;
+use scrypto_unit::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use transaction::prelude::*;
+
+type Breaks = Rc<RefCell<Vec<(DebugEvent, DebugContext)>>>;
+
+struct RecordingDebugger {
+    breaks: Breaks,
+    action: DebuggerAction,
+}
+
+impl TransactionDebugger for RecordingDebugger {
+    fn on_break(&mut self, event: &DebugEvent, context: &DebugContext) -> DebuggerAction {
+        self.breaks
+            .borrow_mut()
+            .push((event.clone(), context.clone()));
+        self.action
+    }
+}
+
+fn run_with_breakpoints(
+    test_runner: &mut DefaultTestRunner,
+    manifest: TransactionManifestV1,
+    initial_proofs: Vec<NonFungibleGlobalId>,
+    breakpoints: Vec<Breakpoint>,
+    action: DebuggerAction,
+) -> (TransactionReceipt, Vec<(DebugEvent, DebugContext)>) {
+    let breaks = Breaks::default();
+    let config = breakpoints.into_iter().fold(
+        DebuggerConfig::new(Box::new(RecordingDebugger {
+            breaks: breaks.clone(),
+            action,
+        })),
+        |config, breakpoint| config.with_breakpoint(breakpoint),
+    );
+
+    let receipt = test_runner
+        .execute_manifest_with_system::<_, TestRunnerDebuggerSystem<'_, NoExtension>>(
+            manifest,
+            initial_proofs,
+            config,
+        );
+
+    let breaks = breaks.take();
+    (receipt, breaks)
+}
+
+fn faucet_breakpoint(ident: &str) -> Breakpoint {
+    Breakpoint::Invocation {
+        blueprint_id: BlueprintId::new(&FAUCET_PACKAGE, FAUCET_BLUEPRINT),
+        ident: Some(ident.to_string()),
+    }
+}
+
+fn invoked_ident(event: &DebugEvent) -> Option<&str> {
+    match event {
+        DebugEvent::Invocation(Actor::Method(actor)) => Some(&actor.ident),
+        DebugEvent::Invocation(Actor::Function(actor)) => Some(&actor.ident),
+        _ => None,
+    }
+}
+
+#[test]
+fn debugger_breaks_on_invocation_of_blueprint_method() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+
+    // Act
+    let (receipt, breaks) = run_with_breakpoints(
+        &mut test_runner,
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .build(),
+        vec![],
+        vec![faucet_breakpoint("free")],
+        DebuggerAction::Continue,
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    assert_eq!(breaks.len(), 1);
+    let (event, context) = &breaks[0];
+    assert_eq!(invoked_ident(event), Some("free"));
+    assert_eq!(
+        context.call_frames.last(),
+        match event {
+            DebugEvent::Invocation(actor) => Some(actor),
+            _ => None,
+        }
+    );
+    assert_eq!(context.call_frames.first(), Some(&Actor::Root));
+}
+
+#[test]
+fn debugger_steps_through_invocations_after_breakpoint() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+
+    // Act
+    let (receipt, breaks) = run_with_breakpoints(
+        &mut test_runner,
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .build(),
+        vec![],
+        vec![faucet_breakpoint("lock_fee")],
+        DebuggerAction::Step,
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    assert!(breaks.len() > 2);
+    assert_eq!(invoked_ident(&breaks[0].0), Some("lock_fee"));
+    assert!(breaks
+        .iter()
+        .any(|(event, _)| invoked_ident(event) == Some("free")));
+}
+
+#[test]
+fn debugger_shows_worktop_contents() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+
+    // Act
+    let (receipt, breaks) = run_with_breakpoints(
+        &mut test_runner,
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .get_free_xrd_from_faucet()
+            .build(),
+        vec![],
+        vec![faucet_breakpoint("free")],
+        DebuggerAction::Continue,
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    assert_eq!(breaks.len(), 2);
+    assert!(breaks[0].1.worktop.is_empty());
+    let worktop = &breaks[1].1.worktop;
+    assert_eq!(worktop.len(), 1);
+    assert!(matches!(
+        worktop[0].1,
+        BucketSnapshot::Fungible {
+            resource_address: XRD,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn debugger_breaks_on_substate_write_to_node() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _, sender) = test_runner.new_allocated_account();
+    let (_, _, account) = test_runner.new_allocated_account();
+    let resource_address = test_runner.create_fungible_resource(dec!(100), 18, sender);
+
+    // Act
+    let (receipt, breaks) = run_with_breakpoints(
+        &mut test_runner,
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(sender, resource_address, dec!(10))
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&public_key)],
+        vec![Breakpoint::SubstateWrite(*account.as_node_id())],
+        DebuggerAction::Continue,
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    assert!(!breaks.is_empty());
+    for (event, context) in &breaks {
+        assert!(matches!(
+            event,
+            DebugEvent::SubstateWrite { node_id, .. } if node_id == account.as_node_id()
+        ));
+        assert!(context.call_frames.iter().any(|actor| actor.blueprint_id()
+            == Some(BlueprintId::new(&ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT))));
+    }
+}
+
+#[test]
+fn debugger_breaks_on_emitted_event() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+
+    // Act
+    let (receipt, breaks) = run_with_breakpoints(
+        &mut test_runner,
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .build(),
+        vec![],
+        vec![Breakpoint::Event(Some("WithdrawEvent".to_string()))],
+        DebuggerAction::Continue,
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    assert!(!breaks.is_empty());
+    for (event, context) in &breaks {
+        assert!(matches!(
+            event,
+            DebugEvent::Event { type_identifier, .. } if type_identifier.1 == "WithdrawEvent"
+        ));
+        assert!(context.call_frames.len() > 1);
+    }
+}
@@ -210,6 +210,26 @@ fn create_proof_emits_correct_events() {
 // Scrypto
 //=========
 
+#[test]
+fn events_can_be_checked_against_rustlike_notation() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+
+    // Act
+    let manifest = ManifestBuilder::new().lock_fee(FAUCET, dec!(500)).build();
+    let receipt = test_runner.execute_manifest(manifest, vec![]);
+
+    // Assert
+    let (event_identifier, event_data) = &receipt.expect_commit_success().application_events[0];
+    assert_eq!(
+        &test_runner.parse_rustlike_event(
+            event_identifier,
+            r#"LockFeeEvent { amount: Decimal("500") }"#
+        ),
+        event_data
+    );
+}
+
 #[test]
 fn scrypto_cant_emit_unregistered_event() {
     // Arrange
@@ -0,0 +1,145 @@
+use radix_engine::types::*;
+use radix_engine_queries::query::*;
+use scrypto_unit::*;
+use transaction::prelude::*;
+
+#[test]
+fn holdings_list_fungible_balances_and_non_fungible_ids_with_metadata() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (_, _, account) = test_runner.new_allocated_account();
+    let fungible = test_runner.create_fungible_resource(dec!("100"), 18, account);
+    let non_fungible = test_runner.create_non_fungible_resource(account);
+
+    // Act
+    let holdings = HoldingsQuery::new(test_runner.substate_db())
+        .get_holdings(account.into())
+        .unwrap();
+
+    // Assert
+    let mut resource_addresses = vec![XRD, fungible, non_fungible];
+    resource_addresses.sort();
+    assert_eq!(
+        holdings
+            .iter()
+            .map(|holding| holding.resource_address)
+            .collect::<Vec<_>>(),
+        resource_addresses
+    );
+    let holding = |resource_address| {
+        holdings
+            .iter()
+            .find(|holding| holding.resource_address == resource_address)
+            .unwrap()
+    };
+    assert_eq!(
+        holding(XRD).balance,
+        HoldingBalance::Fungible(dec!("10000"))
+    );
+    assert_eq!(
+        holding(XRD).metadata.get("symbol"),
+        Some(&MetadataValue::String("XRD".to_string()))
+    );
+    assert_eq!(
+        holding(XRD).vaults,
+        test_runner.get_component_vaults(account, XRD)
+    );
+    assert_eq!(
+        holding(fungible).balance,
+        HoldingBalance::Fungible(dec!("100"))
+    );
+    assert_eq!(
+        holding(non_fungible).balance,
+        HoldingBalance::NonFungible(btreeset!(
+            NonFungibleLocalId::integer(1),
+            NonFungibleLocalId::integer(2),
+            NonFungibleLocalId::integer(3)
+        ))
+    );
+    assert_eq!(holding(non_fungible).balance.amount(), dec!("3"));
+}
+
+#[test]
+fn holdings_pages_cover_all_holdings() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (_, _, account) = test_runner.new_allocated_account();
+    for _ in 0..4 {
+        test_runner.create_fungible_resource(dec!("1"), 18, account);
+    }
+    let query = HoldingsQuery::new(test_runner.substate_db());
+
+    // Act
+    let mut paged_holdings = Vec::new();
+    let mut page_start = None;
+    loop {
+        let page = query
+            .get_holdings_page(account.into(), page_start, 2)
+            .unwrap();
+        // Accounts own a single vault per resource, so their pages never exceed the limit
+        assert!(page.holdings.len() <= 2);
+        paged_holdings.extend(page.holdings);
+        page_start = page.next_page_start;
+        if page_start.is_none() {
+            break;
+        }
+    }
+
+    // Assert
+    assert_eq!(paged_holdings.len(), 5);
+    paged_holdings.sort_by_key(|holding| holding.resource_address);
+    assert_eq!(paged_holdings, query.get_holdings(account.into()).unwrap());
+}
+
+#[test]
+fn holdings_page_with_zero_limit_is_an_error() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (_, _, account) = test_runner.new_allocated_account();
+
+    // Act
+    let result =
+        HoldingsQuery::new(test_runner.substate_db()).get_holdings_page(account.into(), None, 0);
+
+    // Assert
+    assert_eq!(result, Err(HoldingsQueryError::ZeroPageLimit));
+}
+
+#[test]
+fn holdings_of_missing_component_are_an_error() {
+    // Arrange
+    let test_runner = TestRunnerBuilder::new().build();
+    let component_address = ComponentAddress::virtual_account_from_public_key(
+        &Secp256k1PrivateKey::from_u64(1).unwrap().public_key(),
+    );
+
+    // Act
+    let result =
+        HoldingsQuery::new(test_runner.substate_db()).get_holdings(component_address.into());
+
+    // Assert
+    assert_eq!(
+        result,
+        Err(HoldingsQueryError::ComponentNotFound(
+            component_address.into()
+        ))
+    );
+}
+
+#[test]
+fn test_runner_reports_no_holdings_of_missing_component() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let component_address = ComponentAddress::virtual_account_from_public_key(
+        &Secp256k1PrivateKey::from_u64(1).unwrap().public_key(),
+    );
+
+    // Act & Assert
+    assert_eq!(
+        test_runner.get_component_balance(component_address, XRD),
+        Decimal::ZERO
+    );
+    assert!(test_runner
+        .get_component_vaults(component_address, XRD)
+        .is_empty());
+}
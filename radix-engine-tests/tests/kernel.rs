@@ -19,7 +19,7 @@ use radix_engine::track::Track;
 use radix_engine::types::*;
 use radix_engine_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
 use radix_engine_stores::memory_db::InMemorySubstateDatabase;
-use transaction::model::PreAllocatedAddress;
+use transaction::model::{PreAllocatedAddress, SubintentParams};
 
 struct TestCallFrameData;
 
@@ -63,6 +63,7 @@ impl KernelCallbackObject for TestCallbackObject {
         _pre_allocated_addresses: &Vec<PreAllocatedAddress>,
         _references: &IndexSet<Reference>,
         _blobs: &IndexMap<Hash, Vec<u8>>,
+        _subintent_params: &SubintentParams,
     ) -> Result<Vec<u8>, RuntimeError>
     where
         Y: KernelApi<Self>,
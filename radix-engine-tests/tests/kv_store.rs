@@ -1,11 +1,15 @@
 mod package_loader;
 
 use package_loader::PackageLoader;
-use radix_engine::errors::{CallFrameError, KernelError, RuntimeError};
+use radix_engine::errors::{
+    CallFrameError, KernelError, RuntimeError, SystemError, SystemModuleError,
+};
 use radix_engine::kernel::call_frame::{
     OpenSubstateError, ProcessSubstateError, TakeNodeError, WriteSubstateError,
 };
+use radix_engine::system::system_modules::limits::TransactionLimitsError;
 use radix_engine::types::*;
+use radix_engine_common::constants::MAX_KEY_VALUE_STORE_SCAN_LIMIT;
 use scrypto_unit::*;
 use transaction::prelude::*;
 
@@ -532,3 +536,216 @@ fn nested_kv_stores_works() {
     // Assert
     receipt.expect_commit_success();
 }
+
+fn create_basic_kv_store_with_entries(
+    test_runner: &mut DefaultTestRunner,
+    package_address: PackageAddress,
+    keys: &[&str],
+) -> ComponentAddress {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_function(package_address, "Basic", "new", manifest_args!())
+        .build();
+    let receipt = test_runner.execute_manifest(manifest, vec![]);
+    let component = receipt.expect_commit_success().new_component_addresses()[0];
+
+    let mut builder = ManifestBuilder::new().lock_fee_from_faucet();
+    for key in keys {
+        builder = builder.call_method(
+            component,
+            "insert",
+            manifest_args!(key.to_string(), format!("value_{}", key)),
+        );
+    }
+    let receipt = test_runner.execute_manifest(builder.build(), vec![]);
+    receipt.expect_commit_success();
+
+    component
+}
+
+#[test]
+fn keys_of_local_map_should_skip_removed_entries() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let package_address = test_runner.publish_package_simple(PackageLoader::get("kv_store"));
+
+    // Act
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_function(package_address, "Basic", "iterate_local", manifest_args!())
+        .build();
+    let receipt = test_runner.execute_manifest(manifest, vec![]);
+
+    // Assert
+    receipt.expect_commit_success();
+}
+
+#[test]
+fn keys_of_stored_map_should_skip_removed_entries() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let package_address = test_runner.publish_package_simple(PackageLoader::get("kv_store"));
+    let component = create_basic_kv_store_with_entries(
+        &mut test_runner,
+        package_address,
+        &["a", "b", "c", "d"],
+    );
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(component, "remove", manifest_args!("b".to_string()))
+        .build();
+    test_runner
+        .execute_manifest(manifest, vec![])
+        .expect_commit_success();
+
+    // Act
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(component, "keys", manifest_args!())
+        .build();
+    let receipt = test_runner.execute_manifest(manifest, vec![]);
+
+    // Assert
+    let mut keys: Vec<String> = receipt.expect_commit_success().output(1);
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec!["a".to_string(), "c".to_string(), "d".to_string()]
+    );
+}
+
+#[test]
+fn paginated_iteration_over_stored_map_should_return_every_entry_once() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let package_address = test_runner.publish_package_simple(PackageLoader::get("kv_store"));
+    let component = create_basic_kv_store_with_entries(
+        &mut test_runner,
+        package_address,
+        &["a", "b", "c", "d", "e"],
+    );
+
+    // Act
+    let mut entries = Vec::new();
+    let mut cursor: Option<Vec<u8>> = None;
+    let mut pages = 0;
+    loop {
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_method(component, "iter", manifest_args!(2u32, cursor.clone()))
+            .build();
+        let receipt = test_runner.execute_manifest(manifest, vec![]);
+        let (page, next_cursor): (Vec<(String, String)>, Option<Vec<u8>>) =
+            receipt.expect_commit_success().output(1);
+        assert!(page.len() <= 2);
+        entries.extend(page);
+        pages += 1;
+        match next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+    }
+
+    // Assert
+    assert_eq!(pages, 3);
+    entries.sort();
+    assert_eq!(
+        entries,
+        ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|key| (key.to_string(), format!("value_{}", key)))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn iteration_with_limit_above_max_should_fail() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let package_address = test_runner.publish_package_simple(PackageLoader::get("kv_store"));
+    let component = create_basic_kv_store_with_entries(&mut test_runner, package_address, &["a"]);
+
+    // Act
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            component,
+            "iter",
+            manifest_args!(MAX_KEY_VALUE_STORE_SCAN_LIMIT + 1, Option::<Vec<u8>>::None),
+        )
+        .build();
+    let receipt = test_runner.execute_manifest(manifest, vec![]);
+
+    // Assert
+    receipt.expect_specific_failure(|e| {
+        matches!(
+            e,
+            RuntimeError::SystemModuleError(SystemModuleError::TransactionLimitsError(
+                TransactionLimitsError::KeyValueStoreScanLimitExceeded { .. }
+            ))
+        )
+    });
+}
+
+#[test]
+fn iteration_with_zero_limit_should_fail() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let package_address = test_runner.publish_package_simple(PackageLoader::get("kv_store"));
+    let component = create_basic_kv_store_with_entries(&mut test_runner, package_address, &["a"]);
+
+    // Act
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            component,
+            "iter",
+            manifest_args!(0u32, Option::<Vec<u8>>::None),
+        )
+        .build();
+    let receipt = test_runner.execute_manifest(manifest, vec![]);
+
+    // Assert
+    receipt.expect_specific_failure(|e| {
+        matches!(
+            e,
+            RuntimeError::SystemError(SystemError::KeyValueStoreScanLimitIsZero)
+        )
+    });
+}
+
+#[test]
+fn paginated_iteration_should_have_the_same_order_before_and_after_the_map_is_stored() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let package_address = test_runner.publish_package_simple(PackageLoader::get("kv_store"));
+    let keys: Vec<String> = (0..10).map(|i| format!("key_{}", i)).collect();
+
+    // Act
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_function(
+            package_address,
+            "Basic",
+            "new_with_keys_in_pages",
+            manifest_args!(keys.clone(), 3u32),
+        )
+        .build();
+    let receipt = test_runner.execute_manifest(manifest, vec![]);
+    let result = receipt.expect_commit_success();
+    let component = result.new_component_addresses()[0];
+    let (_, keys_scanned_locally): (ComponentAddress, Vec<String>) = result.output(1);
+
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(component, "keys_in_pages", manifest_args!(3u32))
+        .build();
+    let receipt = test_runner.execute_manifest(manifest, vec![]);
+    let keys_scanned_from_store: Vec<String> = receipt.expect_commit_success().output(1);
+
+    // Assert
+    assert_eq!(keys_scanned_locally, keys_scanned_from_store);
+    let mut sorted_keys = keys_scanned_locally;
+    sorted_keys.sort();
+    assert_eq!(sorted_keys, keys);
+}
@@ -0,0 +1,167 @@
+use radix_engine::blueprints::resource::{FungibleVaultBalanceFieldPayload, FungibleVaultField};
+use radix_engine::system::bootstrap::Bootstrapper;
+use radix_engine::system::checkers::*;
+use radix_engine::system::system_db_reader::SystemDatabaseWriter;
+use radix_engine::types::*;
+use radix_engine::vm::wasm::DefaultWasmEngine;
+use radix_engine::vm::*;
+use radix_engine_interface::api::{FieldIndex, ModuleId};
+use radix_engine_interface::blueprints::resource::{
+    LiquidFungibleResource, FUNGIBLE_VAULT_BLUEPRINT,
+};
+use radix_engine_queries::query::HoldingsQuery;
+use radix_engine_store_interface::db_key_mapper::{DatabaseKeyMapper, SpreadPrefixKeyMapper};
+use radix_engine_store_interface::interface::{
+    CommittableSubstateDatabase, DatabaseUpdate, DatabaseUpdates, DbPartitionKey,
+};
+use radix_engine_stores::memory_db::InMemorySubstateDatabase;
+
+fn bootstrapped_database() -> InMemorySubstateDatabase {
+    let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
+    let native_vm = DefaultNativeVm::new();
+    let vm = Vm::new(&scrypto_vm, native_vm);
+    let mut substate_db = InMemorySubstateDatabase::standard();
+    let mut bootstrapper =
+        Bootstrapper::new(NetworkDefinition::simulator(), &mut substate_db, vm, true);
+    bootstrapper.bootstrap_test_default().unwrap();
+    substate_db
+}
+
+/// Collects the balances of all fungible vaults.
+#[derive(Default)]
+struct FungibleVaultBalanceCollector(BTreeMap<NodeId, Decimal>);
+
+impl ApplicationChecker for FungibleVaultBalanceCollector {
+    type ApplicationCheckerResults = BTreeMap<NodeId, Decimal>;
+
+    fn on_field(
+        &mut self,
+        info: BlueprintInfo,
+        node_id: NodeId,
+        module_id: ModuleId,
+        field_index: FieldIndex,
+        value: &Vec<u8>,
+    ) {
+        if info.blueprint_id == BlueprintId::new(&RESOURCE_PACKAGE, FUNGIBLE_VAULT_BLUEPRINT)
+            && module_id == ModuleId::Main
+            && field_index == FungibleVaultField::Balance.field_index()
+        {
+            let balance: FungibleVaultBalanceFieldPayload = scrypto_decode(value).unwrap();
+            self.0.insert(node_id, balance.into_latest().amount());
+        }
+    }
+
+    fn on_finish(&self) -> Self::ApplicationCheckerResults {
+        self.0.clone()
+    }
+}
+
+#[test]
+fn ledger_checker_finds_no_violations_on_bootstrapped_ledger() {
+    // Arrange
+    let substate_db = bootstrapped_database();
+    let mut kernel_progress_count = 0;
+    let mut system_progress_count = 0;
+
+    // Act
+    let report = LedgerDatabaseChecker::new().check_db(&substate_db, |progress| match progress {
+        LedgerCheckProgress::Kernel { .. } => kernel_progress_count += 1,
+        LedgerCheckProgress::System(_) => system_progress_count += 1,
+    });
+
+    // Assert
+    assert!(report.is_consistent(), "{:?}", report.violations);
+    let system_results = report.system_results.unwrap();
+    assert_eq!(kernel_progress_count, system_results.partition_count);
+    assert_eq!(system_progress_count, system_results.node_counts.node_count);
+    assert!(report.resource_results.unwrap().num_resources > 0);
+    assert_eq!(report.application_results, Some(()));
+}
+
+#[test]
+fn ledger_checker_runs_application_checker() {
+    // Arrange
+    let substate_db = bootstrapped_database();
+
+    // Act
+    let report =
+        LedgerDatabaseChecker::with_application_checker(FungibleVaultBalanceCollector::default())
+            .check_db(&substate_db, |_| {});
+
+    // Assert
+    assert!(report.is_consistent(), "{:?}", report.violations);
+    let fungible_vault_balances = report.application_results.unwrap();
+    let faucet_vaults = HoldingsQuery::new(&substate_db)
+        .get_vaults(FAUCET.into(), XRD)
+        .unwrap();
+    assert!(fungible_vault_balances.contains_key(&faucet_vaults[0]));
+    let vault_balances = report.resource_results.unwrap().vaults;
+    for (vault_id, balance) in fungible_vault_balances {
+        assert_eq!(vault_balances.get(&vault_id), Some(&balance));
+    }
+}
+
+#[test]
+fn ledger_checker_reports_negative_vault_balance() {
+    // Arrange
+    let mut substate_db = bootstrapped_database();
+    let vault_id = HoldingsQuery::new(&substate_db)
+        .get_vaults(FAUCET.into(), XRD)
+        .unwrap()[0];
+    SystemDatabaseWriter::new(&mut substate_db)
+        .write_typed_object_field(
+            &vault_id,
+            ModuleId::Main,
+            FungibleVaultField::Balance.field_index(),
+            FungibleVaultBalanceFieldPayload::from_content_source(LiquidFungibleResource::new(
+                dec!("-1"),
+            )),
+        )
+        .unwrap();
+
+    // Act
+    let report = LedgerDatabaseChecker::new().check_db(&substate_db, |_| {});
+
+    // Assert
+    assert!(!report.is_consistent());
+    assert!(report.violations.iter().any(|violation| matches!(
+        violation,
+        LedgerCheckViolation::Resource(ResourceDatabaseCheckerError::NegativeVaultBalance {
+            vault_id: negative_vault_id,
+            amount,
+        }) if *negative_vault_id == vault_id && *amount == dec!("-1")
+    )));
+}
+
+#[test]
+fn ledger_checker_reports_node_of_system_violation() {
+    // Arrange
+    let mut substate_db = bootstrapped_database();
+    let (node_key, partition_num, sort_key, update) = (
+        SpreadPrefixKeyMapper::to_db_node_key(PACKAGE_PACKAGE.as_node_id()),
+        SpreadPrefixKeyMapper::to_db_partition_num(
+            ROLE_ASSIGNMENT_BASE_PARTITION
+                .at_offset(ROLE_ASSIGNMENT_FIELDS_PARTITION_OFFSET)
+                .unwrap(),
+        ),
+        SpreadPrefixKeyMapper::to_db_sort_key(&SubstateKey::Field(0u8)),
+        DatabaseUpdate::Delete,
+    );
+    let remove_owner_update = DatabaseUpdates::from_delta_maps(
+        indexmap!(DbPartitionKey {node_key, partition_num} => indexmap!(sort_key => update)),
+    );
+    substate_db.commit(&remove_owner_update);
+
+    // Act
+    let report = LedgerDatabaseChecker::new().check_db(&substate_db, |_| {});
+
+    // Assert
+    assert!(report.system_results.is_none());
+    assert!(report.violations.iter().any(|violation| matches!(
+        violation,
+        LedgerCheckViolation::System {
+            node_id,
+            error: SystemDatabaseCheckError::NodeError(SystemNodeCheckError::MissingExpectedFields),
+        } if node_id == PACKAGE_PACKAGE.as_node_id()
+    )));
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "rocksdb")]
+
+use radix_engine::types::*;
+use radix_engine_stores::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+use scrypto_unit::*;
+use std::path::PathBuf;
+use transaction::prelude::*;
+
+fn create_ledger(name: &str) -> (PathBuf, ComponentAddress) {
+    let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    let mut test_runner = TestRunnerBuilder::new()
+        .with_custom_database(RocksDBWithMerkleTreeSubstateStore::clear(path.clone()))
+        .without_trace()
+        .build();
+    let (_, _, account) = test_runner.new_allocated_account();
+    (path, account)
+}
+
+#[test]
+fn forked_test_runner_sees_ledger_state() {
+    // Arrange
+    let (path, account) = create_ledger("forked_test_runner_sees_ledger_state");
+
+    // Act
+    let mut test_runner = TestRunnerBuilder::from_database_path(path.clone()).build();
+
+    // Assert
+    assert_eq!(
+        test_runner.get_component_balance(account, XRD),
+        dec!("10000")
+    );
+    assert!(test_runner.substate_db().base().get_current_version() > 0);
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn forked_test_runner_does_not_write_to_ledger() {
+    // Arrange
+    let (path, account) = create_ledger("forked_test_runner_does_not_write_to_ledger");
+    let mut test_runner = TestRunnerBuilder::from_database_path(path.clone()).build();
+    let ledger_state_version = test_runner.substate_db().base().get_current_version();
+
+    // Act
+    let receipt = test_runner.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![],
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    assert_eq!(
+        test_runner.get_component_balance(account, XRD),
+        dec!("20000")
+    );
+    drop(test_runner);
+
+    let mut test_runner = TestRunnerBuilder::from_database_path(path.clone()).build();
+    assert_eq!(
+        test_runner.get_component_balance(account, XRD),
+        dec!("10000")
+    );
+    assert_eq!(
+        test_runner.substate_db().base().get_current_version(),
+        ledger_state_version
+    );
+    std::fs::remove_dir_all(path).unwrap();
+}
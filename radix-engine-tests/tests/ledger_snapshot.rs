@@ -0,0 +1,275 @@
+use radix_engine::types::*;
+use radix_engine_queries::query::HoldingsQuery;
+use radix_engine_store_interface::interface::*;
+use radix_engine_stores::memory_db::InMemorySubstateDatabase;
+use radix_engine_stores::snapshot::*;
+use scrypto_unit::*;
+use std::io::Cursor;
+
+fn create_snapshot() -> (Vec<u8>, Hash, ComponentAddress, ResourceAddress) {
+    let mut test_runner = TestRunnerBuilder::new().with_state_hashing().build();
+    let (_, _, account) = test_runner.new_allocated_account();
+    let resource_address = test_runner.create_fungible_resource(dec!("100"), 18, account);
+    let substate_db = test_runner.substate_db();
+    let header = LedgerSnapshotHeader {
+        state_version: substate_db.get_current_version(),
+        state_root_hash: substate_db.get_current_root_hash(),
+    };
+
+    let mut snapshot = Vec::new();
+    export_snapshot(substate_db, header, &mut snapshot).unwrap();
+    (
+        snapshot,
+        test_runner.get_state_hash(),
+        account,
+        resource_address,
+    )
+}
+
+fn list_substates<S: SubstateDatabase + ListableSubstateDatabase>(
+    substate_db: &S,
+) -> BTreeMap<DbPartitionKey, Vec<PartitionEntry>> {
+    substate_db
+        .list_partition_keys()
+        .map(|partition_key| {
+            let entries = substate_db.list_entries(&partition_key).collect();
+            (partition_key, entries)
+        })
+        .collect()
+}
+
+#[test]
+fn state_root_hash_of_database_matches_state_hash_tree() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().with_state_hashing().build();
+    test_runner.new_allocated_account();
+
+    // Act
+    let state_root_hash = compute_state_root_hash(test_runner.substate_db());
+
+    // Assert
+    assert_eq!(state_root_hash, test_runner.get_state_hash());
+}
+
+#[test]
+fn imported_snapshot_has_the_same_state_as_the_exported_ledger() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().with_state_hashing().build();
+    let (_, _, account) = test_runner.new_allocated_account();
+    test_runner.create_fungible_resource(dec!("100"), 18, account);
+    let substate_db = test_runner.substate_db();
+    let header = LedgerSnapshotHeader {
+        state_version: substate_db.get_current_version(),
+        state_root_hash: substate_db.get_current_root_hash(),
+    };
+    let mut snapshot = Vec::new();
+    let export_summary = export_snapshot(substate_db, header.clone(), &mut snapshot).unwrap();
+
+    // Act
+    let mut imported_db = InMemorySubstateDatabase::standard();
+    let import_summary = import_snapshot(&mut Cursor::new(&snapshot), &mut imported_db).unwrap();
+
+    // Assert
+    assert_eq!(import_summary, export_summary);
+    assert_eq!(import_summary.header, header);
+    let substates = list_substates(test_runner.substate_db());
+    assert_eq!(import_summary.partition_count, substates.len() as u64);
+    assert_eq!(
+        import_summary.substate_count,
+        substates.values().map(|entries| entries.len() as u64).sum()
+    );
+    assert_eq!(list_substates(&imported_db), substates);
+    assert_eq!(
+        compute_state_root_hash(&imported_db),
+        header.state_root_hash
+    );
+}
+
+#[test]
+fn test_runner_can_continue_from_imported_snapshot() {
+    // Arrange
+    let (snapshot, _, account, resource_address) = create_snapshot();
+    let mut imported_db = InMemorySubstateDatabase::standard();
+    import_snapshot(&mut Cursor::new(&snapshot), &mut imported_db).unwrap();
+
+    // Act
+    let mut test_runner = TestRunnerBuilder::new()
+        .with_custom_database(imported_db)
+        .build();
+    let new_resource_address = test_runner.create_fungible_resource(dec!("50"), 18, account);
+
+    // Assert
+    let query = HoldingsQuery::new(test_runner.substate_db());
+    assert_eq!(
+        query.get_balance(account.into(), resource_address),
+        Ok(dec!("100"))
+    );
+    assert_eq!(
+        query.get_balance(account.into(), new_resource_address),
+        Ok(dec!("50"))
+    );
+}
+
+#[test]
+fn snapshot_with_wrong_state_root_hash_is_rejected() {
+    // Arrange
+    let (_, state_root_hash, _, _) = create_snapshot();
+    let test_runner = TestRunnerBuilder::new().build();
+    let header = LedgerSnapshotHeader {
+        state_version: 1,
+        state_root_hash,
+    };
+
+    // Act
+    let mut snapshot = Vec::new();
+    let export_result = export_snapshot(test_runner.substate_db(), header, &mut snapshot);
+    let verify_result = verify_snapshot(&mut snapshot.as_slice());
+
+    // Assert
+    assert!(matches!(
+        export_result,
+        Err(SnapshotError::StateRootHashMismatch { expected, .. }) if expected == state_root_hash
+    ));
+    assert!(matches!(
+        verify_result,
+        Err(SnapshotError::StateRootHashMismatch { expected, .. }) if expected == state_root_hash
+    ));
+}
+
+#[test]
+fn corrupted_snapshot_is_rejected() {
+    // Arrange
+    let (snapshot, _, _, _) = create_snapshot();
+    assert!(verify_snapshot(&mut snapshot.as_slice()).is_ok());
+    let mut truncated_snapshot = snapshot.clone();
+    truncated_snapshot.truncate(snapshot.len() / 2);
+    let mut extended_snapshot = snapshot.clone();
+    extended_snapshot.push(0);
+    let mut snapshot_with_wrong_magic = snapshot.clone();
+    snapshot_with_wrong_magic[0] ^= 1;
+
+    // Act & Assert
+    assert!(matches!(
+        verify_snapshot(&mut truncated_snapshot.as_slice()),
+        Err(SnapshotError::MissingEnd)
+    ));
+    assert!(matches!(
+        verify_snapshot(&mut extended_snapshot.as_slice()),
+        Err(SnapshotError::TrailingData)
+    ));
+    assert!(matches!(
+        verify_snapshot(&mut snapshot_with_wrong_magic.as_slice()),
+        Err(SnapshotError::InvalidMagic)
+    ));
+}
+
+#[test]
+fn snapshot_with_changed_substate_is_rejected() {
+    // Arrange
+    let (snapshot, state_root_hash, _, _) = create_snapshot();
+    let mut imported_db = InMemorySubstateDatabase::standard();
+    import_snapshot(&mut Cursor::new(&snapshot), &mut imported_db).unwrap();
+    let partition_key = imported_db.list_partition_keys().last().unwrap();
+    let (sort_key, mut value) = imported_db.list_entries(&partition_key).next().unwrap();
+    value.push(0);
+    imported_db.commit(&DatabaseUpdates::from_delta_maps(indexmap!(
+        partition_key => indexmap!(sort_key => DatabaseUpdate::Set(value))
+    )));
+    let header = LedgerSnapshotHeader {
+        state_version: 1,
+        state_root_hash,
+    };
+    let mut changed_snapshot = Vec::new();
+    let _ = export_snapshot(&imported_db, header, &mut changed_snapshot);
+
+    // Act
+    let mut target_db = InMemorySubstateDatabase::standard();
+    let result = import_snapshot(&mut Cursor::new(&changed_snapshot), &mut target_db);
+
+    // Assert
+    assert!(matches!(
+        result,
+        Err(SnapshotError::StateRootHashMismatch { expected, .. }) if expected == state_root_hash
+    ));
+    assert_eq!(target_db.list_partition_keys().next(), None);
+}
+
+#[cfg(feature = "rocksdb")]
+#[test]
+fn snapshot_can_be_imported_into_rocksdb_with_merkle_tree() {
+    use radix_engine_stores::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+
+    // Arrange
+    let (snapshot, state_root_hash, _, _) = create_snapshot();
+    let header = verify_snapshot(&mut snapshot.as_slice()).unwrap().header;
+    let path = std::env::temp_dir().join(format!(
+        "snapshot_can_be_imported_into_rocksdb_with_merkle_tree-{}",
+        std::process::id()
+    ));
+    let mut substate_db = RocksDBWithMerkleTreeSubstateStore::clear(path.clone());
+
+    // Act
+    import_snapshot_at_state_version(&mut Cursor::new(&snapshot), &mut substate_db).unwrap();
+
+    // Assert
+    assert_eq!(substate_db.get_current_root_hash(), state_root_hash);
+    assert_eq!(substate_db.get_current_version(), header.state_version);
+    drop(substate_db);
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[cfg(feature = "rocksdb")]
+#[test]
+fn transactions_following_a_snapshot_can_be_synced_onto_its_import() {
+    use radix_engine_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
+    use radix_engine_stores::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+    use transaction::builder::ManifestBuilder;
+
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().with_state_hashing().build();
+    let (_, _, account) = test_runner.new_allocated_account();
+    let substate_db = test_runner.substate_db();
+    let header = LedgerSnapshotHeader {
+        state_version: substate_db.get_current_version(),
+        state_root_hash: substate_db.get_current_root_hash(),
+    };
+    let mut snapshot = Vec::new();
+    export_snapshot(substate_db, header.clone(), &mut snapshot).unwrap();
+    let path = std::env::temp_dir().join(format!(
+        "transactions_following_a_snapshot_can_be_synced_onto_its_import-{}",
+        std::process::id()
+    ));
+    let mut imported_db = RocksDBWithMerkleTreeSubstateStore::clear(path.clone());
+    import_snapshot_at_state_version(&mut Cursor::new(&snapshot), &mut imported_db).unwrap();
+    let receipt = test_runner.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+        vec![],
+    );
+
+    // Act
+    // As `replay sync` does, from the state version of the imported database
+    assert_eq!(imported_db.get_current_version(), header.state_version);
+    imported_db.commit(
+        &receipt
+            .expect_commit_success()
+            .state_updates
+            .create_database_updates::<SpreadPrefixKeyMapper>(),
+    );
+
+    // Assert
+    let substate_db = test_runner.substate_db();
+    assert_eq!(
+        imported_db.get_current_version(),
+        substate_db.get_current_version()
+    );
+    assert_eq!(
+        imported_db.get_current_root_hash(),
+        substate_db.get_current_root_hash()
+    );
+    drop(imported_db);
+    std::fs::remove_dir_all(path).unwrap();
+}
@@ -151,6 +151,16 @@ impl KernelSubstateApi<SystemLockData> for MockKernel {
         panic1!()
     }
 
+    fn kernel_scan_substates_after<F: SubstateKeyContent + 'static>(
+        &mut self,
+        _: &NodeId,
+        _: PartitionNumber,
+        _: Option<&SubstateKey>,
+        _: u32,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, RuntimeError> {
+        panic1!()
+    }
+
     fn kernel_drain_substates<F: SubstateKeyContent + 'static>(
         &mut self,
         _: &NodeId,
@@ -1,7 +1,11 @@
+use radix_engine::blueprints::consensus_manager::ConsensusManagerField;
 use radix_engine::system::system_modules::costing::FeeTable;
 use radix_engine::transaction::CostingParameters;
 use radix_engine::transaction::ExecutionConfig;
+use radix_engine::transaction::{PreviewError, StateOverride, StateOverrideError};
 use radix_engine::types::*;
+use radix_engine_interface::api::ModuleId;
+use radix_engine_interface::blueprints::consensus_manager::*;
 use radix_engine_interface::rule;
 use scrypto_unit::*;
 use transaction::prelude::*;
@@ -143,6 +147,138 @@ fn test_assume_all_signature_proofs_flag_method_authorization() {
     result.unwrap().expect_commit_success();
 }
 
+#[test]
+fn test_preview_with_fungible_balance_override() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let network = NetworkDefinition::simulator();
+    let (_, _, account) = test_runner.new_allocated_account();
+    let (_, _, other_account) = test_runner.new_allocated_account();
+    let resource_address = test_runner.create_fungible_resource(dec!(100), 18, other_account);
+    let preview_flags = PreviewFlags {
+        use_free_credit: true,
+        assume_all_signature_proofs: true,
+        skip_epoch_check: false,
+    };
+    let manifest = ManifestBuilder::new()
+        .lock_fee(account, 500)
+        .withdraw_from_account(account, resource_address, 1000)
+        .try_deposit_entire_worktop_or_abort(other_account, None)
+        .build();
+    let (_, preview_intent) = prepare_matching_test_tx_and_preview_intent(
+        &mut test_runner,
+        &network,
+        manifest,
+        &preview_flags,
+    );
+    let state_overrides = vec![StateOverride::FungibleBalance {
+        account,
+        resource_address,
+        amount: dec!(1000),
+    }];
+
+    // Act
+    let receipt = test_runner
+        .preview_with_state_overrides(preview_intent.clone(), &network, state_overrides.clone())
+        .unwrap();
+
+    // Assert
+    receipt.receipt.expect_commit_success();
+    assert_eq!(receipt.state_overrides, state_overrides);
+    test_runner
+        .preview(preview_intent, &network)
+        .unwrap()
+        .expect_commit_failure();
+    assert_eq!(
+        test_runner.get_component_balance(account, resource_address),
+        dec!(0)
+    );
+}
+
+#[test]
+fn test_preview_with_current_time_override() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let network = NetworkDefinition::simulator();
+    let preview_flags = PreviewFlags {
+        use_free_credit: true,
+        assume_all_signature_proofs: false,
+        skip_epoch_check: false,
+    };
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            CONSENSUS_MANAGER,
+            CONSENSUS_MANAGER_GET_CURRENT_TIME_IDENT,
+            ConsensusManagerGetCurrentTimeInput {
+                precision: TimePrecision::Minute,
+            },
+        )
+        .build();
+    let (_, preview_intent) = prepare_matching_test_tx_and_preview_intent(
+        &mut test_runner,
+        &network,
+        manifest,
+        &preview_flags,
+    );
+
+    // Act
+    let receipt = test_runner
+        .preview_with_state_overrides(
+            preview_intent,
+            &network,
+            vec![StateOverride::CurrentTime {
+                proposer_timestamp_ms: 1_700_000_000_000,
+            }],
+        )
+        .unwrap();
+
+    // Assert
+    let current_time: Instant = receipt.receipt.expect_commit_success().output(1);
+    assert_eq!(current_time.seconds_since_unix_epoch, 1_699_999_980);
+}
+
+#[test]
+fn test_preview_with_object_field_override_of_wrong_type_is_rejected() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let network = NetworkDefinition::simulator();
+    let preview_flags = PreviewFlags {
+        use_free_credit: true,
+        assume_all_signature_proofs: false,
+        skip_epoch_check: false,
+    };
+    let manifest = ManifestBuilder::new().lock_fee_from_faucet().build();
+    let (_, preview_intent) = prepare_matching_test_tx_and_preview_intent(
+        &mut test_runner,
+        &network,
+        manifest,
+        &preview_flags,
+    );
+
+    // Act
+    let result = test_runner.preview_with_state_overrides(
+        preview_intent,
+        &network,
+        vec![StateOverride::ObjectField {
+            node_id: *CONSENSUS_MANAGER.as_node_id(),
+            module_id: ModuleId::Main,
+            field_index: ConsensusManagerField::ProposerMilliTimestamp.field_index(),
+            value: ScryptoValue::String {
+                value: "yesterday".to_string(),
+            },
+        }],
+    );
+
+    // Assert
+    assert!(matches!(
+        result,
+        Err(PreviewError::StateOverrideError(
+            StateOverrideError::InvalidFieldValue { .. }
+        ))
+    ));
+}
+
 fn prepare_matching_test_tx_and_preview_intent(
     test_runner: &mut DefaultTestRunner,
     network: &NetworkDefinition,
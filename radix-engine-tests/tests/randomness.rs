@@ -0,0 +1,394 @@
+mod package_loader;
+
+use package_loader::PackageLoader;
+use radix_engine::blueprints::consensus_manager::{
+    ConsensusManagerCollection, ConsensusManagerError, ConsensusManagerField,
+    ConsensusManagerRandomnessBeaconFieldPayload,
+};
+use radix_engine::blueprints::models::KeyValueEntryPayload;
+use radix_engine::blueprints::package::{
+    PackageBlueprintVersionDefinitionEntryPayload, PackageBlueprintVersionDefinitionEntrySubstate,
+};
+use radix_engine::errors::{ApplicationError, RuntimeError};
+use radix_engine::system::bootstrap::ProtocolUpdate;
+use radix_engine::system::system_substates::{
+    FieldSubstate, KeyValueEntrySubstate, KeyValueEntrySubstateV1,
+};
+use radix_engine::types::*;
+use radix_engine_interface::blueprints::consensus_manager::*;
+use radix_engine_interface::blueprints::package::{
+    BlueprintVersionKey, PACKAGE_BLUEPRINTS_PARTITION_OFFSET,
+};
+use radix_engine_store_interface::db_key_mapper::{
+    DatabaseKeyMapper, MappedSubstateDatabase, SpreadPrefixKeyMapper,
+};
+use radix_engine_store_interface::interface::*;
+use scrypto_unit::*;
+use transaction::prelude::*;
+
+fn setup() -> (DefaultTestRunner, ComponentAddress) {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let package_address = test_runner.publish_package_simple(PackageLoader::get("randomness"));
+    let receipt = test_runner.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_function(package_address, "RandomnessTest", "new", manifest_args!())
+            .build(),
+        vec![],
+    );
+    let component_address = receipt.expect_commit_success().new_component_addresses()[0];
+    (test_runner, component_address)
+}
+
+fn call(
+    test_runner: &mut DefaultTestRunner,
+    component_address: ComponentAddress,
+    method_name: &str,
+) -> TransactionReceipt {
+    test_runner.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_method(component_address, method_name, manifest_args!())
+            .build(),
+        vec![],
+    )
+}
+
+fn build_test_runner_with_validator() -> DefaultTestRunner {
+    TestRunnerBuilder::new()
+        .with_custom_genesis(CustomGenesis::default(
+            Epoch::of(1),
+            CustomGenesis::default_consensus_manager_config(),
+        ))
+        .build()
+}
+
+/// Executes a round change proposed by the first validator, which reveals the given secret and
+/// commits to the next one. The secrets are the hashes of the given strings.
+fn next_round_with_randomness(
+    test_runner: &mut DefaultTestRunner,
+    reveal: Option<&str>,
+    next_secret: &str,
+) -> TransactionReceipt {
+    let timestamp_ms = test_runner.get_current_proposer_timestamp_ms();
+    test_runner.execute_validator_transaction(vec![InstructionV1::CallMethod {
+        address: CONSENSUS_MANAGER.into(),
+        method_name: CONSENSUS_MANAGER_NEXT_ROUND_WITH_RANDOMNESS_IDENT.to_string(),
+        args: to_manifest_value_and_unwrap!(&ConsensusManagerNextRoundInput::successful(
+            Round::of(1),
+            0,
+            timestamp_ms,
+        )
+        .with_randomness(RandomnessContribution {
+            reveal: reveal.map(hash),
+            next_commitment: hash(hash(next_secret)),
+        })),
+    }])
+}
+
+/// Restores the consensus manager definition from before the randomness beacon was introduced
+/// (i.e. without the beacon field and the random seed and randomness commitment collections), and
+/// removes the beacon, as on a ledger bootstrapped before the beacon was introduced.
+fn restore_pre_randomness_beacon_ledger(test_runner: &mut DefaultTestRunner) {
+    let definition_partition_key = SpreadPrefixKeyMapper::to_db_partition_key(
+        CONSENSUS_MANAGER_PACKAGE.as_node_id(),
+        MAIN_BASE_PARTITION
+            .at_offset(PACKAGE_BLUEPRINTS_PARTITION_OFFSET)
+            .unwrap(),
+    );
+    let definition_key = SubstateKey::Map(
+        scrypto_encode(&BlueprintVersionKey::new_default(
+            CONSENSUS_MANAGER_BLUEPRINT,
+        ))
+        .unwrap(),
+    );
+    let KeyValueEntrySubstate::V1(KeyValueEntrySubstateV1 { value, lock_status }) = test_runner
+        .substate_db()
+        .get_mapped::<SpreadPrefixKeyMapper, PackageBlueprintVersionDefinitionEntrySubstate>(
+            CONSENSUS_MANAGER_PACKAGE.as_node_id(),
+            MAIN_BASE_PARTITION
+                .at_offset(PACKAGE_BLUEPRINTS_PARTITION_OFFSET)
+                .unwrap(),
+            &definition_key,
+        )
+        .unwrap();
+    let mut definition = value.unwrap().into_latest();
+    let state = &mut definition.interface.state;
+    state
+        .fields
+        .as_mut()
+        .unwrap()
+        .1
+        .truncate(ConsensusManagerField::RandomnessBeacon.field_index() as usize);
+    let collection_count = state.collections.len();
+    state
+        .collections
+        .truncate(ConsensusManagerCollection::RandomSeedKeyValue.collection_index() as usize);
+    state.num_logical_partitions -= (collection_count - state.collections.len()) as u8;
+    let definition_substate = KeyValueEntrySubstate::V1(KeyValueEntrySubstateV1 {
+        value: Some(PackageBlueprintVersionDefinitionEntryPayload::from_content_source(definition)),
+        lock_status,
+    });
+
+    let beacon_partition_key = SpreadPrefixKeyMapper::to_db_partition_key(
+        CONSENSUS_MANAGER.as_node_id(),
+        MAIN_BASE_PARTITION,
+    );
+    test_runner.substate_db_mut().commit(&DatabaseUpdates {
+        node_updates: indexmap!(
+            definition_partition_key.node_key => NodeDatabaseUpdates {
+                partition_updates: indexmap!(definition_partition_key.partition_num => PartitionDatabaseUpdates::Delta {
+                    substate_updates: indexmap!(
+                        SpreadPrefixKeyMapper::to_db_sort_key(&definition_key) => DatabaseUpdate::Set(scrypto_encode(&definition_substate).unwrap())
+                    ),
+                }),
+            },
+            beacon_partition_key.node_key => NodeDatabaseUpdates {
+                partition_updates: indexmap!(beacon_partition_key.partition_num => PartitionDatabaseUpdates::Delta {
+                    substate_updates: indexmap!(
+                        SpreadPrefixKeyMapper::to_db_sort_key(&ConsensusManagerField::RandomnessBeacon.into()) => DatabaseUpdate::Delete
+                    ),
+                }),
+            },
+        ),
+    });
+}
+
+fn is_invalid_randomness_reveal_error(e: &RuntimeError) -> bool {
+    matches!(
+        e,
+        RuntimeError::ApplicationError(ApplicationError::ConsensusManagerError(
+            ConsensusManagerError::InvalidRandomnessReveal { .. }
+        ))
+    )
+}
+
+fn is_random_seed_not_revealed_error(e: &RuntimeError) -> bool {
+    matches!(
+        e,
+        RuntimeError::ApplicationError(ApplicationError::ConsensusManagerError(
+            ConsensusManagerError::RandomSeedNotRevealed { .. }
+        ))
+    )
+}
+
+#[test]
+fn random_seed_cannot_be_revealed_in_round_of_commitment() {
+    // Arrange
+    let (mut test_runner, component_address) = setup();
+
+    // Act
+    let receipt = call(&mut test_runner, component_address, "commit_and_reveal");
+
+    // Assert
+    receipt.expect_specific_failure(is_random_seed_not_revealed_error);
+}
+
+#[test]
+fn random_seed_can_be_revealed_in_later_round() {
+    // Arrange
+    let (mut test_runner, component_address) = setup();
+    call(&mut test_runner, component_address, "commit").expect_commit_success();
+    call(&mut test_runner, component_address, "reveal")
+        .expect_specific_failure(is_random_seed_not_revealed_error);
+
+    // Act
+    test_runner.advance_to_round(Round::of(1));
+    let receipt = call(&mut test_runner, component_address, "reveal");
+
+    // Assert
+    let seed: Hash = receipt.expect_commit_success().output(1);
+    assert_eq!(seed, test_runner.get_randomness_beacon_state().seed);
+}
+
+#[test]
+fn revealed_random_seed_does_not_change_in_subsequent_rounds() {
+    // Arrange
+    let (mut test_runner, component_address) = setup();
+    call(&mut test_runner, component_address, "commit").expect_commit_success();
+    test_runner.advance_to_round(Round::of(1));
+    let seed: Hash = call(&mut test_runner, component_address, "reveal")
+        .expect_commit_success()
+        .output(1);
+
+    // Act
+    test_runner.advance_to_round(Round::of(1));
+    test_runner.advance_to_round(Round::of(1));
+    let receipt = call(&mut test_runner, component_address, "reveal");
+
+    // Assert
+    assert_eq!(receipt.expect_commit_success().output::<Hash>(1), seed);
+    assert_ne!(test_runner.get_randomness_beacon_state().seed, seed);
+}
+
+#[test]
+fn random_seed_of_test_runner_is_deterministic() {
+    // Arrange
+    let (mut test_runner1, _) = setup();
+    let (mut test_runner2, _) = setup();
+
+    // Act
+    test_runner1.advance_to_round(Round::of(1));
+    test_runner2.advance_to_round(Round::of(1));
+
+    // Assert
+    let beacon1 = test_runner1.get_randomness_beacon_state();
+    let beacon2 = test_runner2.get_randomness_beacon_state();
+    assert_eq!(beacon1, beacon2);
+    assert_eq!(beacon1.round_sequence, 1);
+}
+
+#[test]
+fn randomness_revealed_by_leader_is_mixed_into_seed() {
+    // Arrange
+    let mut test_runner1 = build_test_runner_with_validator();
+    let mut test_runner2 = build_test_runner_with_validator();
+    next_round_with_randomness(&mut test_runner1, None, "secret 1").expect_commit_success();
+    next_round_with_randomness(&mut test_runner2, None, "secret 2").expect_commit_success();
+    assert_eq!(
+        test_runner1.get_randomness_beacon_state(),
+        test_runner2.get_randomness_beacon_state()
+    );
+
+    // Act
+    next_round_with_randomness(&mut test_runner1, Some("secret 1"), "secret 3")
+        .expect_commit_success();
+    next_round_with_randomness(&mut test_runner2, Some("secret 2"), "secret 3")
+        .expect_commit_success();
+
+    // Assert
+    assert_ne!(
+        test_runner1.get_randomness_beacon_state().seed,
+        test_runner2.get_randomness_beacon_state().seed
+    );
+}
+
+#[test]
+fn leader_cannot_reveal_a_secret_other_than_the_committed_one() {
+    // Arrange
+    let mut test_runner = build_test_runner_with_validator();
+    next_round_with_randomness(&mut test_runner, None, "secret 1").expect_commit_success();
+
+    // Act
+    let receipt = next_round_with_randomness(&mut test_runner, Some("secret 2"), "secret 3");
+
+    // Assert
+    receipt.expect_specific_failure(is_invalid_randomness_reveal_error);
+}
+
+#[test]
+fn leader_cannot_withhold_the_committed_secret() {
+    // Arrange
+    let mut test_runner = build_test_runner_with_validator();
+    next_round_with_randomness(&mut test_runner, None, "secret 1").expect_commit_success();
+
+    // Act
+    let receipt = next_round_with_randomness(&mut test_runner, None, "secret 2");
+
+    // Assert
+    receipt.expect_specific_failure(is_invalid_randomness_reveal_error);
+}
+
+#[test]
+fn randomness_cannot_be_contributed_by_a_leader_outside_the_validator_set() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+
+    // Act
+    let receipt = next_round_with_randomness(&mut test_runner, None, "secret");
+
+    // Assert
+    receipt.expect_specific_failure(|e| {
+        matches!(
+            e,
+            RuntimeError::ApplicationError(ApplicationError::ConsensusManagerError(
+                ConsensusManagerError::UnknownRandomnessContributor { leader: 0 }
+            ))
+        )
+    });
+}
+
+#[test]
+fn round_change_succeeds_on_a_ledger_without_a_randomness_beacon() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    restore_pre_randomness_beacon_ledger(&mut test_runner);
+
+    // Act
+    let receipt = test_runner.advance_to_round(Round::of(1));
+
+    // Assert
+    receipt.expect_commit_success();
+    let randomness_beacon = test_runner
+        .substate_db()
+        .get_mapped::<SpreadPrefixKeyMapper, FieldSubstate<ConsensusManagerRandomnessBeaconFieldPayload>>(
+            CONSENSUS_MANAGER.as_node_id(),
+            MAIN_BASE_PARTITION,
+            &ConsensusManagerField::RandomnessBeacon.into(),
+        );
+    assert!(randomness_beacon.is_none());
+}
+
+#[test]
+fn randomness_beacon_protocol_update_initializes_the_beacon() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    restore_pre_randomness_beacon_ledger(&mut test_runner);
+
+    // Act
+    let receipts = test_runner.apply_protocol_update(ProtocolUpdate::RandomnessBeacon);
+
+    // Assert
+    assert_eq!(receipts.len(), 1);
+    receipts[0].expect_commit_success();
+    test_runner
+        .advance_to_round(Round::of(1))
+        .expect_commit_success();
+    assert_eq!(test_runner.get_randomness_beacon_state().round_sequence, 1);
+}
+
+#[test]
+fn randomness_beacon_protocol_update_keeps_an_existing_beacon() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    test_runner
+        .advance_to_round(Round::of(1))
+        .expect_commit_success();
+    let randomness_beacon = test_runner.get_randomness_beacon_state();
+
+    // Act
+    let receipts = test_runner.apply_protocol_update(ProtocolUpdate::RandomnessBeacon);
+
+    // Assert
+    receipts[0].expect_commit_success();
+    assert_eq!(test_runner.get_randomness_beacon_state(), randomness_beacon);
+}
+
+#[test]
+fn random_seed_can_be_obtained_from_manifest() {
+    // Arrange
+    let (mut test_runner, _) = setup();
+    let commitment = RandomSeedCommitment {
+        round_sequence: test_runner.get_randomness_beacon_state().round_sequence,
+    };
+    test_runner.advance_to_round(Round::of(1));
+
+    // Act
+    let receipt = test_runner.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_method(
+                CONSENSUS_MANAGER,
+                CONSENSUS_MANAGER_GET_RANDOM_SEED_IDENT,
+                ConsensusManagerGetRandomSeedInput { commitment },
+            )
+            .build(),
+        vec![],
+    );
+
+    // Assert
+    assert_eq!(
+        receipt.expect_commit_success().output::<Hash>(1),
+        test_runner.get_randomness_beacon_state().seed
+    );
+}
@@ -0,0 +1,627 @@
+mod package_loader;
+
+use package_loader::PackageLoader;
+use radix_engine::blueprints::consensus_manager::{
+    ScheduledCallCancelledEvent, ScheduledCallDueEvent, ScheduledCallExecutedEvent,
+    ScheduledCallExpiredEvent, ScheduledCallRegisteredEvent, SchedulerError,
+};
+use radix_engine::errors::{ApplicationError, RuntimeError, SystemError};
+use radix_engine::system::bootstrap::ProtocolUpdate;
+use radix_engine::types::*;
+use radix_engine_interface::api::node_modules::auth::AuthAddresses;
+use radix_engine_interface::blueprints::consensus_manager::*;
+use radix_engine_interface::blueprints::resource::FromPublicKey;
+use radix_engine_store_interface::db_key_mapper::{DatabaseKeyMapper, SpreadPrefixKeyMapper};
+use radix_engine_store_interface::interface::*;
+use scrypto_unit::*;
+use transaction::prelude::*;
+
+struct SchedulerTestSetup {
+    test_runner: DefaultTestRunner,
+    component_address: ComponentAddress,
+    public_key: Secp256k1PublicKey,
+    account: ComponentAddress,
+}
+
+impl SchedulerTestSetup {
+    fn new() -> Self {
+        Self::with_test_runner(TestRunnerBuilder::new().build())
+    }
+
+    fn with_test_runner(mut test_runner: DefaultTestRunner) -> Self {
+        let (public_key, _, account) = test_runner.new_allocated_account();
+        let package_address = test_runner.publish_package_simple(PackageLoader::get("scheduler"));
+        let receipt = test_runner.execute_manifest(
+            ManifestBuilder::new()
+                .lock_fee_from_faucet()
+                .call_function(package_address, "SchedulerTest", "new", manifest_args!())
+                .build(),
+            vec![],
+        );
+        let component_address = receipt.expect_commit_success().new_component_addresses()[0];
+
+        Self {
+            test_runner,
+            component_address,
+            public_key,
+            account,
+        }
+    }
+
+    fn schedule(
+        &mut self,
+        method_name: &str,
+        value: u32,
+        trigger: ScheduledCallTrigger,
+    ) -> ScheduledCallId {
+        let cancel_rule = rule!(require(NonFungibleGlobalId::from_public_key(
+            &self.public_key
+        )));
+        let receipt = self.test_runner.execute_manifest(
+            ManifestBuilder::new()
+                .lock_fee_from_faucet()
+                .get_free_xrd_from_faucet()
+                .take_from_worktop(XRD, dec!(10), "fee")
+                .call_method_with_name_lookup(self.component_address, "schedule", |lookup| {
+                    (
+                        method_name.to_string(),
+                        value,
+                        trigger,
+                        lookup.bucket("fee"),
+                        cancel_rule,
+                    )
+                })
+                .try_deposit_entire_worktop_or_abort(self.account, None)
+                .build(),
+            vec![],
+        );
+        let result = receipt.expect_commit_success();
+        let registered_events = self
+            .test_runner
+            .extract_events_of_type::<ScheduledCallRegisteredEvent>(result);
+        assert_eq!(registered_events.len(), 1);
+        assert_eq!(registered_events[0].fee, dec!(10));
+        result.output(3)
+    }
+
+    fn get_values(&mut self) -> Vec<u32> {
+        let receipt = self.test_runner.execute_manifest(
+            ManifestBuilder::new()
+                .lock_fee_from_faucet()
+                .call_method(self.component_address, "get_values", manifest_args!())
+                .build(),
+            vec![],
+        );
+        receipt.expect_commit_success().output(1)
+    }
+
+    /// Commits a round change, without executing the scheduled calls it makes due.
+    fn next_round_without_executing_due_calls(&mut self) -> TransactionReceipt {
+        let proposer_timestamp_ms = self.test_runner.get_current_proposer_timestamp_ms();
+        self.test_runner
+            .execute_validator_transaction(vec![InstructionV1::CallMethod {
+                address: CONSENSUS_MANAGER.into(),
+                method_name: CONSENSUS_MANAGER_NEXT_ROUND_IDENT.to_string(),
+                args: to_manifest_value_and_unwrap!(&ConsensusManagerNextRoundInput::successful(
+                    Round::of(1),
+                    0,
+                    proposer_timestamp_ms,
+                )),
+            }])
+    }
+
+    fn execute_call_as_protocol(&mut self, id: ScheduledCallId) -> TransactionReceipt {
+        self.test_runner
+            .execute_validator_transaction(vec![InstructionV1::CallMethod {
+                address: SCHEDULER.into(),
+                method_name: SCHEDULER_EXECUTE_CALL_IDENT.to_string(),
+                args: to_manifest_value_and_unwrap!(&SchedulerExecuteCallInput { id }),
+            }])
+    }
+
+    fn cancel_manifest(&self, id: ScheduledCallId) -> TransactionManifestV1 {
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_method(
+                SCHEDULER,
+                SCHEDULER_CANCEL_CALL_IDENT,
+                SchedulerCancelCallInput { id },
+            )
+            .try_deposit_entire_worktop_or_abort(self.account, None)
+            .build()
+    }
+}
+
+#[test]
+fn scheduled_call_is_executed_once_its_epoch_is_reached() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    let target_epoch = current_epoch.after(10).unwrap();
+    setup.schedule("record", 7, ScheduledCallTrigger::AtEpoch(target_epoch));
+
+    // Act & Assert
+    let receipt = setup.test_runner.advance_to_round(Round::of(1));
+    let due_events = setup
+        .test_runner
+        .extract_events_of_type::<ScheduledCallDueEvent>(receipt.expect_commit_success());
+    assert!(due_events.is_empty());
+    assert_eq!(setup.get_values(), Vec::<u32>::new());
+
+    setup
+        .test_runner
+        .set_current_epoch(target_epoch.previous().unwrap());
+    let receipt = setup.test_runner.advance_to_round(Round::of(1));
+    let due_events = setup
+        .test_runner
+        .extract_events_of_type::<ScheduledCallDueEvent>(receipt.expect_commit_success());
+    assert_eq!(due_events.len(), 1);
+    assert_eq!(setup.get_values(), vec![7]);
+}
+
+#[test]
+fn scheduled_call_is_executed_once_its_time_is_reached() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    setup.schedule("record", 3, ScheduledCallTrigger::AtTime(Instant::new(90)));
+
+    // Act & Assert
+    setup
+        .test_runner
+        .advance_to_round_at_timestamp(Round::of(1), 90_000)
+        .expect_commit_success();
+    assert_eq!(setup.get_values(), Vec::<u32>::new());
+
+    setup
+        .test_runner
+        .advance_to_round_at_timestamp(Round::of(1), 120_000)
+        .expect_commit_success();
+    assert_eq!(setup.get_values(), vec![3]);
+}
+
+#[test]
+fn remaining_fee_of_executed_call_is_released_on_next_round() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    setup.schedule(
+        "record",
+        1,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+    assert_eq!(
+        setup.test_runner.get_component_balance(SCHEDULER, XRD),
+        dec!(10)
+    );
+
+    // Act
+    setup.test_runner.advance_to_round(Round::of(1));
+    let scheduler_balance_after_execution = setup.test_runner.get_component_balance(SCHEDULER, XRD);
+    setup.test_runner.advance_to_round(Round::of(1));
+
+    // Assert
+    assert_eq!(setup.get_values(), vec![1]);
+    assert!(scheduler_balance_after_execution.is_positive());
+    assert!(scheduler_balance_after_execution < dec!(10));
+    assert_eq!(
+        setup.test_runner.get_component_balance(SCHEDULER, XRD),
+        Decimal::ZERO
+    );
+}
+
+#[test]
+fn cancelled_call_is_not_executed_and_its_fee_is_refunded() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    let id = setup.schedule(
+        "record",
+        5,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+    let account_balance_before = setup.test_runner.get_component_balance(setup.account, XRD);
+
+    // Act
+    let receipt = setup.test_runner.execute_manifest(
+        setup.cancel_manifest(id),
+        vec![NonFungibleGlobalId::from_public_key(&setup.public_key)],
+    );
+
+    // Assert
+    let result = receipt.expect_commit_success();
+    let cancelled_events = setup
+        .test_runner
+        .extract_events_of_type::<ScheduledCallCancelledEvent>(result);
+    assert_eq!(cancelled_events, vec![ScheduledCallCancelledEvent { id }]);
+    assert_eq!(
+        setup.test_runner.get_component_balance(setup.account, XRD),
+        account_balance_before + dec!(10)
+    );
+    assert_eq!(
+        setup.test_runner.get_component_balance(SCHEDULER, XRD),
+        Decimal::ZERO
+    );
+
+    setup.test_runner.advance_to_round(Round::of(1));
+    setup.test_runner.advance_to_round(Round::of(1));
+    assert_eq!(setup.get_values(), Vec::<u32>::new());
+}
+
+#[test]
+fn cannot_cancel_call_without_satisfying_its_cancel_rule() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    let id = setup.schedule(
+        "record",
+        5,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+
+    // Act
+    let receipt = setup
+        .test_runner
+        .execute_manifest(setup.cancel_manifest(id), vec![]);
+
+    // Assert
+    receipt.expect_specific_failure(|e| {
+        matches!(
+            e,
+            RuntimeError::SystemError(SystemError::AssertAccessRuleFailed)
+        )
+    });
+    setup.test_runner.advance_to_round(Round::of(1));
+    assert_eq!(setup.get_values(), vec![5]);
+}
+
+#[test]
+fn cannot_cancel_call_once_it_is_due() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    let id = setup.schedule(
+        "record",
+        5,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+    setup
+        .next_round_without_executing_due_calls()
+        .expect_commit_success();
+
+    // Act
+    let receipt = setup.test_runner.execute_manifest(
+        setup.cancel_manifest(id),
+        vec![NonFungibleGlobalId::from_public_key(&setup.public_key)],
+    );
+
+    // Assert
+    receipt.expect_specific_failure(|e| {
+        matches!(
+            e,
+            RuntimeError::ApplicationError(ApplicationError::SchedulerError(
+                SchedulerError::ScheduledCallNotCancellable(_)
+            ))
+        )
+    });
+}
+
+#[test]
+fn failed_call_does_not_affect_round_change_and_is_not_refunded() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    let id = setup.schedule(
+        "fail",
+        9,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+
+    // Act
+    setup
+        .test_runner
+        .advance_to_round(Round::of(1))
+        .expect_commit_success();
+    let receipt = setup.test_runner.advance_to_round(Round::of(1));
+
+    // Assert
+    let expired_events = setup
+        .test_runner
+        .extract_events_of_type::<ScheduledCallExpiredEvent>(receipt.expect_commit_success());
+    assert!(expired_events.is_empty());
+    assert_eq!(setup.get_values(), Vec::<u32>::new());
+    assert_eq!(
+        setup.test_runner.get_component_balance(SCHEDULER, XRD),
+        Decimal::ZERO
+    );
+    setup
+        .test_runner
+        .execute_manifest(
+            setup.cancel_manifest(id),
+            vec![NonFungibleGlobalId::from_public_key(&setup.public_key)],
+        )
+        .expect_specific_failure(|e| {
+            matches!(
+                e,
+                RuntimeError::ApplicationError(ApplicationError::SchedulerError(
+                    SchedulerError::ScheduledCallNotFound(_)
+                ))
+            )
+        });
+}
+
+#[test]
+fn failed_call_pays_its_fee_from_the_scheduler() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    let id = setup.schedule(
+        "fail",
+        9,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+    let receipt = setup.next_round_without_executing_due_calls();
+
+    // Act
+    let receipts = setup.test_runner.execute_due_scheduled_calls(&receipt);
+
+    // Assert
+    assert_eq!(receipts.len(), 1);
+    receipts[0].expect_commit_failure();
+    assert!(receipts[0].fee_summary.total_cost().is_positive());
+    assert!(setup.test_runner.get_component_balance(SCHEDULER, XRD) < dec!(10));
+    setup
+        .execute_call_as_protocol(id)
+        .expect_specific_failure(|e| {
+            matches!(
+                e,
+                RuntimeError::ApplicationError(ApplicationError::SchedulerError(
+                    SchedulerError::ScheduledCallNotFound(_)
+                ))
+            )
+        });
+}
+
+#[test]
+fn expired_call_keeps_its_fee_until_cancelled() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    let id = setup.schedule(
+        "record",
+        6,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+    setup
+        .next_round_without_executing_due_calls()
+        .expect_commit_success();
+
+    // Act
+    let receipt = setup.test_runner.advance_to_round(Round::of(1));
+
+    // Assert
+    let expired_events = setup
+        .test_runner
+        .extract_events_of_type::<ScheduledCallExpiredEvent>(receipt.expect_commit_success());
+    assert_eq!(expired_events, vec![ScheduledCallExpiredEvent { id }]);
+    assert_eq!(
+        setup.test_runner.get_component_balance(SCHEDULER, XRD),
+        dec!(10)
+    );
+    setup
+        .execute_call_as_protocol(id)
+        .expect_specific_failure(|e| {
+            matches!(
+                e,
+                RuntimeError::ApplicationError(ApplicationError::SchedulerError(
+                    SchedulerError::ScheduledCallNotDue(_)
+                ))
+            )
+        });
+
+    let account_balance_before = setup.test_runner.get_component_balance(setup.account, XRD);
+    let receipt = setup.test_runner.execute_manifest(
+        setup.cancel_manifest(id),
+        vec![NonFungibleGlobalId::from_public_key(&setup.public_key)],
+    );
+    receipt.expect_commit_success();
+    assert_eq!(
+        setup.test_runner.get_component_balance(setup.account, XRD),
+        account_balance_before + dec!(10)
+    );
+    assert_eq!(
+        setup.test_runner.get_component_balance(SCHEDULER, XRD),
+        Decimal::ZERO
+    );
+    assert_eq!(setup.get_values(), Vec::<u32>::new());
+}
+
+#[test]
+fn only_the_protocol_can_execute_due_calls() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    let id = setup.schedule(
+        "record",
+        5,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+    let round_receipt = setup.next_round_without_executing_due_calls();
+
+    // Act
+    let receipt = setup.test_runner.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_method(
+                SCHEDULER,
+                SCHEDULER_EXECUTE_CALL_IDENT,
+                SchedulerExecuteCallInput { id },
+            )
+            .build(),
+        vec![],
+    );
+
+    // Assert
+    receipt.expect_auth_failure();
+    let receipts = setup
+        .test_runner
+        .execute_due_scheduled_calls(&round_receipt);
+    assert_eq!(receipts.len(), 1);
+    let executed_events = setup
+        .test_runner
+        .extract_events_of_type::<ScheduledCallExecutedEvent>(receipts[0].expect_commit_success());
+    assert_eq!(executed_events, vec![ScheduledCallExecutedEvent { id }]);
+    assert_eq!(setup.get_values(), vec![5]);
+}
+
+#[test]
+fn round_change_succeeds_on_a_ledger_without_a_scheduler() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    remove_scheduler(&mut test_runner);
+
+    // Act
+    let receipt = test_runner.advance_to_round(Round::of(1));
+
+    // Assert
+    receipt.expect_commit_success();
+}
+
+#[test]
+fn scheduler_protocol_update_creates_the_scheduler() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    remove_scheduler(&mut test_runner);
+
+    // Act
+    let receipts = test_runner.apply_protocol_update(ProtocolUpdate::Scheduler);
+
+    // Assert
+    assert_eq!(receipts.len(), 2);
+    for receipt in receipts {
+        receipt.expect_commit_success();
+    }
+    let mut setup = SchedulerTestSetup::with_test_runner(test_runner);
+    let current_epoch = setup.test_runner.get_current_epoch();
+    setup.schedule(
+        "record",
+        3,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+    setup
+        .test_runner
+        .advance_to_round(Round::of(1))
+        .expect_commit_success();
+    assert_eq!(setup.get_values(), vec![3]);
+}
+
+#[test]
+fn scheduler_protocol_update_keeps_an_existing_scheduler() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let current_epoch = setup.test_runner.get_current_epoch();
+    setup.schedule(
+        "record",
+        4,
+        ScheduledCallTrigger::AtEpoch(current_epoch.next().unwrap()),
+    );
+
+    // Act
+    let receipts = setup
+        .test_runner
+        .apply_protocol_update(ProtocolUpdate::Scheduler);
+
+    // Assert
+    assert_eq!(receipts.len(), 1);
+    receipts[0].expect_commit_success();
+    setup
+        .test_runner
+        .advance_to_round(Round::of(1))
+        .expect_commit_success();
+    assert_eq!(setup.get_values(), vec![4]);
+}
+
+#[test]
+fn only_consensus_manager_can_make_calls_due() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+
+    // Act
+    let receipt = test_runner.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_method(
+                SCHEDULER,
+                SCHEDULER_PROCESS_DUE_CALLS_IDENT,
+                SchedulerProcessDueCallsInput {
+                    epoch: Epoch::of(u64::MAX),
+                    proposer_timestamp_ms: i64::MAX,
+                },
+            )
+            .build(),
+        vec![AuthAddresses::validator_role()],
+    );
+
+    // Assert
+    receipt.expect_auth_failure();
+}
+
+#[test]
+fn fee_must_be_paid_in_xrd() {
+    // Arrange
+    let mut setup = SchedulerTestSetup::new();
+    let resource_address =
+        setup
+            .test_runner
+            .create_fungible_resource(dec!(100), DIVISIBILITY_MAXIMUM, setup.account);
+
+    // Act
+    let receipt = setup.test_runner.execute_manifest(
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .withdraw_from_account(setup.account, resource_address, dec!(10))
+            .take_from_worktop(resource_address, dec!(10), "fee")
+            .call_method_with_name_lookup(setup.component_address, "schedule", |lookup| {
+                (
+                    "record".to_string(),
+                    1u32,
+                    ScheduledCallTrigger::AtEpoch(Epoch::of(100)),
+                    lookup.bucket("fee"),
+                    rule!(allow_all),
+                )
+            })
+            .build(),
+        vec![NonFungibleGlobalId::from_public_key(&setup.public_key)],
+    );
+
+    // Assert
+    receipt.expect_specific_failure(|e| {
+        matches!(
+            e,
+            RuntimeError::ApplicationError(ApplicationError::SchedulerError(
+                SchedulerError::InvalidFeeResource(_)
+            ))
+        )
+    });
+}
+
+/// Removes the scheduler from the ledger, as if it was bootstrapped before the scheduler was
+/// introduced.
+fn remove_scheduler(test_runner: &mut DefaultTestRunner) {
+    let node_key = SpreadPrefixKeyMapper::to_db_node_key(SCHEDULER.as_node_id());
+    let partition_updates = test_runner
+        .substate_db()
+        .list_partition_keys()
+        .filter(|partition_key| partition_key.node_key == node_key)
+        .map(|partition_key| {
+            (
+                partition_key.partition_num,
+                PartitionDatabaseUpdates::Reset {
+                    new_substate_values: index_map_new(),
+                },
+            )
+        })
+        .collect();
+    test_runner.substate_db_mut().commit(&DatabaseUpdates {
+        node_updates: indexmap!(node_key => NodeDatabaseUpdates { partition_updates }),
+    });
+}
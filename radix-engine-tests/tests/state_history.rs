@@ -0,0 +1,252 @@
+#![cfg(feature = "rocksdb")]
+
+use radix_engine::blueprints::resource::{FungibleVaultBalanceFieldPayload, FungibleVaultField};
+use radix_engine::system::system_db_reader::SystemDatabaseReader;
+use radix_engine::types::*;
+use radix_engine::vm::NoExtension;
+use radix_engine_interface::api::ModuleId;
+use radix_engine_queries::query::{HoldingsQuery, HoldingsQueryError};
+use radix_engine_store_interface::interface::*;
+use radix_engine_stores::rocks_db_with_merkle_tree::{
+    RocksDBWithMerkleTreeSubstateStore, StateHistoryRetention,
+};
+use scrypto_unit::*;
+use std::path::PathBuf;
+use transaction::prelude::*;
+
+type Snapshot = BTreeMap<DbPartitionKey, Vec<PartitionEntry>>;
+
+fn create_test_runner(
+    name: &str,
+    retention: StateHistoryRetention,
+) -> (
+    PathBuf,
+    TestRunner<NoExtension, RocksDBWithMerkleTreeSubstateStore>,
+) {
+    let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    let test_runner = TestRunnerBuilder::new()
+        .with_custom_database(
+            RocksDBWithMerkleTreeSubstateStore::clear(path.clone()).with_state_history(retention),
+        )
+        .without_trace()
+        .build();
+    (path, test_runner)
+}
+
+fn get_free_xrd(
+    test_runner: &mut TestRunner<NoExtension, RocksDBWithMerkleTreeSubstateStore>,
+    account: ComponentAddress,
+) {
+    test_runner
+        .execute_manifest(
+            ManifestBuilder::new()
+                .lock_fee_from_faucet()
+                .get_free_xrd_from_faucet()
+                .try_deposit_entire_worktop_or_abort(account, None)
+                .build(),
+            vec![],
+        )
+        .expect_commit_success();
+}
+
+fn take_snapshot(substate_db: &RocksDBWithMerkleTreeSubstateStore) -> Snapshot {
+    substate_db
+        .list_partition_keys()
+        .map(|partition_key| {
+            let entries = substate_db.list_entries(&partition_key).collect();
+            (partition_key, entries)
+        })
+        .collect()
+}
+
+fn assert_state_at(
+    substate_db: &RocksDBWithMerkleTreeSubstateStore,
+    state_version: u64,
+    snapshot: &Snapshot,
+) {
+    let view = StateVersionView::new(substate_db, state_version).unwrap();
+    for partition_key in substate_db
+        .list_partition_keys()
+        .chain(snapshot.keys().cloned())
+    {
+        let expected_entries = snapshot.get(&partition_key).cloned().unwrap_or_default();
+        assert_eq!(
+            view.list_entries(&partition_key).collect::<Vec<_>>(),
+            expected_entries
+        );
+        for (sort_key, value) in &expected_entries {
+            assert_eq!(
+                view.get_substate(&partition_key, sort_key).as_ref(),
+                Some(value)
+            );
+        }
+        for (sort_key, _) in substate_db.list_entries(&partition_key) {
+            if !expected_entries.iter().any(|(key, _)| *key == sort_key) {
+                assert_eq!(view.get_substate(&partition_key, &sort_key), None);
+            }
+        }
+    }
+}
+
+fn vault_balance_at(
+    substate_db: &RocksDBWithMerkleTreeSubstateStore,
+    state_version: u64,
+    vault_id: &NodeId,
+) -> Option<Decimal> {
+    let view = StateVersionView::new(substate_db, state_version).unwrap();
+    let reader = SystemDatabaseReader::new(&view);
+    reader
+        .read_typed_object_field::<FungibleVaultBalanceFieldPayload>(
+            vault_id,
+            ModuleId::Main,
+            FungibleVaultField::Balance.into(),
+        )
+        .ok()
+        .map(|balance| balance.into_latest().amount())
+}
+
+#[test]
+fn historical_vault_balances_can_be_read() {
+    // Arrange
+    let (path, mut test_runner) = create_test_runner(
+        "historical_vault_balances_can_be_read",
+        StateHistoryRetention::All,
+    );
+    let (_, _, account) = test_runner.new_allocated_account();
+    let vault_id = test_runner.get_component_vaults(account, XRD)[0];
+    let state_version = test_runner.substate_db().get_current_version();
+
+    // Act
+    get_free_xrd(&mut test_runner, account);
+
+    // Assert
+    let substate_db = test_runner.substate_db();
+    assert_eq!(
+        vault_balance_at(substate_db, state_version, &vault_id),
+        Some(dec!("10000"))
+    );
+    assert_eq!(
+        vault_balance_at(substate_db, substate_db.get_current_version(), &vault_id),
+        Some(dec!("20000"))
+    );
+    assert_eq!(vault_balance_at(substate_db, 0, &vault_id), None);
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn historical_holdings_can_be_queried() {
+    // Arrange
+    let (path, mut test_runner) = create_test_runner(
+        "historical_holdings_can_be_queried",
+        StateHistoryRetention::All,
+    );
+    let (_, _, account) = test_runner.new_allocated_account();
+    let state_version = test_runner.substate_db().get_current_version();
+
+    // Act
+    let resource_address = test_runner.create_fungible_resource(dec!("100"), 18, account);
+
+    // Assert
+    let substate_db = test_runner.substate_db();
+    let past_holdings = HoldingsQuery::at_state_version(substate_db, state_version)
+        .unwrap()
+        .get_holdings(account.into())
+        .unwrap();
+    assert_eq!(past_holdings.len(), 1);
+    assert_eq!(past_holdings[0].resource_address, XRD);
+    assert_eq!(
+        HoldingsQuery::at_state_version(substate_db, state_version)
+            .unwrap()
+            .get_balance(account.into(), resource_address),
+        Ok(Decimal::ZERO)
+    );
+    assert_eq!(
+        HoldingsQuery::new(substate_db).get_balance(account.into(), resource_address),
+        Ok(dec!("100"))
+    );
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn historical_state_matches_snapshots() {
+    // Arrange
+    let (path, mut test_runner) = create_test_runner(
+        "historical_state_matches_snapshots",
+        StateHistoryRetention::All,
+    );
+    let (_, _, account) = test_runner.new_allocated_account();
+    let mut snapshots = Vec::new();
+
+    // Act
+    for _ in 0..3 {
+        let substate_db = test_runner.substate_db();
+        snapshots.push((
+            substate_db.get_current_version(),
+            take_snapshot(substate_db),
+        ));
+        get_free_xrd(&mut test_runner, account);
+        test_runner.new_allocated_account();
+    }
+
+    // Assert
+    for (state_version, snapshot) in &snapshots {
+        assert_state_at(test_runner.substate_db(), *state_version, snapshot);
+    }
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn state_history_is_pruned_beyond_retention() {
+    // Arrange
+    let (path, mut test_runner) = create_test_runner(
+        "state_history_is_pruned_beyond_retention",
+        StateHistoryRetention::LastVersions(2),
+    );
+    let (_, _, account) = test_runner.new_allocated_account();
+    let state_version = test_runner.substate_db().get_current_version();
+    let snapshot = take_snapshot(test_runner.substate_db());
+
+    // Act
+    get_free_xrd(&mut test_runner, account);
+    get_free_xrd(&mut test_runner, account);
+
+    // Assert
+    let substate_db = test_runner.substate_db();
+    assert_eq!(
+        substate_db.available_state_versions(),
+        state_version..=(state_version + 2)
+    );
+    assert_state_at(substate_db, state_version, &snapshot);
+
+    get_free_xrd(&mut test_runner, account);
+    assert!(StateVersionView::new(test_runner.substate_db(), state_version).is_err());
+    assert!(matches!(
+        HoldingsQuery::at_state_version(test_runner.substate_db(), state_version),
+        Err(HoldingsQueryError::StateVersionUnavailable(_))
+    ));
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn state_history_can_retain_only_the_current_version() {
+    // Arrange
+    let (path, mut test_runner) = create_test_runner(
+        "state_history_can_retain_only_the_current_version",
+        StateHistoryRetention::LastVersions(0),
+    );
+    let (_, _, account) = test_runner.new_allocated_account();
+
+    // Act
+    get_free_xrd(&mut test_runner, account);
+    get_free_xrd(&mut test_runner, account);
+
+    // Assert
+    let substate_db = test_runner.substate_db();
+    let state_version = substate_db.get_current_version();
+    assert_eq!(
+        substate_db.available_state_versions(),
+        state_version..=state_version
+    );
+    assert_state_at(substate_db, state_version, &take_snapshot(substate_db));
+    std::fs::remove_dir_all(path).unwrap();
+}
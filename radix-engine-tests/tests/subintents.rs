@@ -0,0 +1,288 @@
+use radix_engine::blueprints::resource::WorktopError;
+use radix_engine::errors::{ApplicationError, RuntimeError, SystemModuleError};
+use radix_engine::system::bootstrap::ProtocolUpdate;
+use radix_engine::system::system_modules::auth::AuthError;
+use radix_engine::transaction::{CostingParameters, ExecutionConfig, TransactionReceipt};
+use radix_engine::types::*;
+use scrypto_unit::*;
+use transaction::prelude::*;
+use transaction::validation::*;
+
+#[test]
+fn subintent_can_yield_a_bucket_to_the_root_intent() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (_, root_key, root_account) = test_runner.new_allocated_account();
+    let (_, child_key, child_account) = test_runner.new_allocated_account();
+    let child = create_subintent(1, withdraw_and_yield_to_parent(child_account, dec!("10")));
+
+    // Act
+    let receipt = execute_transaction_v2(
+        &mut test_runner,
+        &root_key,
+        deposit_from_child(root_account),
+        vec![(child, child_key)],
+    );
+
+    // Assert
+    receipt.expect_commit_success();
+    assert_eq!(
+        test_runner.get_component_balance(root_account, XRD),
+        dec!("10010")
+    );
+    assert_eq!(
+        test_runner.get_component_balance(child_account, XRD),
+        dec!("9990")
+    );
+}
+
+#[test]
+fn failing_subintent_fails_the_transaction() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (_, root_key, root_account) = test_runner.new_allocated_account();
+    let (_, child_key, _) = test_runner.new_allocated_account();
+    let mut child_instructions = to_instructions_v2(
+        ManifestBuilder::new()
+            .assert_worktop_contains(XRD, dec!("1"))
+            .build(),
+    );
+    child_instructions.push(InstructionV2::YieldToParent {
+        args: manifest_args!().into(),
+    });
+    let child = create_subintent(1, child_instructions);
+
+    // Act
+    let receipt = execute_transaction_v2(
+        &mut test_runner,
+        &root_key,
+        deposit_from_child(root_account),
+        vec![(child, child_key)],
+    );
+
+    // Assert
+    receipt.expect_specific_failure(|e| {
+        matches!(
+            e,
+            RuntimeError::ApplicationError(ApplicationError::WorktopError(
+                WorktopError::AssertionFailed
+            ))
+        )
+    });
+    assert_eq!(
+        test_runner.get_component_balance(root_account, XRD),
+        dec!("10000")
+    );
+}
+
+#[test]
+fn subintent_can_not_use_the_signatures_of_the_root_intent() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (_, root_key, root_account) = test_runner.new_allocated_account();
+    let (_, child_key, _) = test_runner.new_allocated_account();
+    let child = create_subintent(1, withdraw_and_yield_to_parent(root_account, dec!("10")));
+
+    // Act
+    let receipt = execute_transaction_v2(
+        &mut test_runner,
+        &root_key,
+        deposit_from_child(root_account),
+        vec![(child, child_key)],
+    );
+
+    // Assert
+    receipt.expect_specific_failure(is_unauthorized);
+}
+
+#[test]
+fn root_intent_can_not_use_the_signatures_of_a_subintent() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (_, root_key, root_account) = test_runner.new_allocated_account();
+    let (_, child_key, child_account) = test_runner.new_allocated_account();
+    let mut child_instructions = to_instructions_v2(ManifestBuilder::new().build());
+    child_instructions.push(InstructionV2::YieldToParent {
+        args: manifest_args!().into(),
+    });
+    let child = create_subintent(1, child_instructions);
+    let mut root_instructions = deposit_from_child(root_account);
+    root_instructions.extend(to_instructions_v2(
+        ManifestBuilder::new()
+            .withdraw_from_account(child_account, XRD, dec!("10"))
+            .try_deposit_entire_worktop_or_abort(root_account, None)
+            .build(),
+    ));
+
+    // Act
+    let receipt = execute_transaction_v2(
+        &mut test_runner,
+        &root_key,
+        root_instructions,
+        vec![(child, child_key)],
+    );
+
+    // Assert
+    receipt.expect_specific_failure(is_unauthorized);
+}
+
+#[test]
+fn transaction_processor_v2_protocol_update_keeps_subintents_working() {
+    // Arrange
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (_, root_key, root_account) = test_runner.new_allocated_account();
+    let (_, child_key, child_account) = test_runner.new_allocated_account();
+    let child = create_subintent(1, withdraw_and_yield_to_parent(child_account, dec!("10")));
+
+    // Act
+    let protocol_update_receipts =
+        test_runner.apply_protocol_update(ProtocolUpdate::TransactionProcessorV2);
+    let receipt = execute_transaction_v2(
+        &mut test_runner,
+        &root_key,
+        deposit_from_child(root_account),
+        vec![(child, child_key)],
+    );
+
+    // Assert
+    for protocol_update_receipt in protocol_update_receipts {
+        protocol_update_receipt.expect_commit_success();
+    }
+    receipt.expect_commit_success();
+}
+
+fn is_unauthorized(e: &RuntimeError) -> bool {
+    matches!(
+        e,
+        RuntimeError::SystemModuleError(SystemModuleError::AuthError(AuthError::Unauthorized(..)))
+    )
+}
+
+fn to_instructions_v2(manifest: TransactionManifestV1) -> Vec<InstructionV2> {
+    manifest
+        .instructions
+        .into_iter()
+        .map(InstructionV2::from)
+        .collect()
+}
+
+/// The instructions of a root intent, which locks a fee, yields to its only child, and then
+/// deposits everything on the worktop.
+fn deposit_from_child(account: ComponentAddress) -> Vec<InstructionV2> {
+    let mut instructions =
+        to_instructions_v2(ManifestBuilder::new().lock_fee_from_faucet().build());
+    instructions.push(InstructionV2::YieldToChild {
+        child_index: 0,
+        args: manifest_args!().into(),
+    });
+    instructions.extend(to_instructions_v2(
+        ManifestBuilder::new()
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build(),
+    ));
+    instructions
+}
+
+/// The instructions of a subintent, which withdraws XRD and yields it to its parent.
+fn withdraw_and_yield_to_parent(account: ComponentAddress, amount: Decimal) -> Vec<InstructionV2> {
+    let mut instructions = to_instructions_v2(
+        ManifestBuilder::new()
+            .withdraw_from_account(account, XRD, amount)
+            .take_from_worktop(XRD, amount, "xrd")
+            .build(),
+    );
+    instructions.push(InstructionV2::YieldToParent {
+        args: manifest_args!(ManifestBucket(0)).into(),
+    });
+    instructions
+}
+
+fn create_intent_core(
+    intent_discriminator: u64,
+    children: Vec<SubintentHash>,
+    instructions: Vec<InstructionV2>,
+) -> IntentCoreV2 {
+    IntentCoreV2 {
+        header: IntentHeaderV2 {
+            network_id: NetworkDefinition::simulator().id,
+            start_epoch_inclusive: Epoch::zero(),
+            end_epoch_exclusive: Epoch::of(100),
+            intent_discriminator,
+        },
+        blobs: BlobsV1 { blobs: vec![] },
+        message: MessageV1::None,
+        children: ChildIntentsV2 {
+            children: children
+                .into_iter()
+                .map(|hash| ChildSubintent { hash })
+                .collect(),
+        },
+        instructions: InstructionsV2(instructions),
+    }
+}
+
+fn create_subintent(intent_discriminator: u64, instructions: Vec<InstructionV2>) -> SubintentV2 {
+    SubintentV2 {
+        intent_core: create_intent_core(intent_discriminator, vec![], instructions),
+    }
+}
+
+/// Executes a transaction whose root intent is signed and notarized by the given key, and whose
+/// root intent has the given subintents (signed by their own keys) as its children.
+fn execute_transaction_v2(
+    test_runner: &mut DefaultTestRunner,
+    root_key: &Secp256k1PrivateKey,
+    root_instructions: Vec<InstructionV2>,
+    subintents: Vec<(SubintentV2, Secp256k1PrivateKey)>,
+) -> TransactionReceipt {
+    let mut children = Vec::new();
+    let mut by_subintent = Vec::new();
+    for (subintent, key) in &subintents {
+        let hash = subintent.prepare().unwrap().subintent_hash();
+        children.push(hash);
+        by_subintent.push(IntentSignaturesV1 {
+            signatures: vec![IntentSignatureV1(key.sign_with_public_key(&hash))],
+        });
+    }
+    let transaction_intent = TransactionIntentV2 {
+        transaction_header: TransactionHeaderV2 {
+            notary_public_key: root_key.public_key().into(),
+            notary_is_signatory: false,
+            tip_percentage: 0,
+        },
+        root_intent_core: create_intent_core(0, children, root_instructions),
+        non_root_subintents: NonRootSubintentsV2(
+            subintents
+                .into_iter()
+                .map(|(subintent, _)| subintent)
+                .collect(),
+        ),
+    };
+    let intent_hash = transaction_intent.prepare().unwrap().intent_hash();
+    let signed_transaction_intent = SignedTransactionIntentV2 {
+        transaction_intent,
+        transaction_intent_signatures: IntentSignaturesV1 {
+            signatures: vec![IntentSignatureV1(
+                root_key.sign_with_public_key(&intent_hash),
+            )],
+        },
+        non_root_subintent_signatures: NonRootSubintentSignaturesV2 { by_subintent },
+    };
+    let signed_intent_hash = signed_transaction_intent
+        .prepare()
+        .unwrap()
+        .signed_intent_hash();
+    let transaction = NotarizedTransactionV2 {
+        signed_transaction_intent,
+        notary_signature: NotarySignatureV1(root_key.sign_without_public_key(&signed_intent_hash)),
+    };
+
+    let validated = NotarizedTransactionValidatorV2::new(ValidationConfig::simulator())
+        .validate(transaction.prepare().unwrap())
+        .unwrap();
+    test_runner.execute_transaction(
+        validated.get_executable(),
+        CostingParameters::default(),
+        ExecutionConfig::for_notarized_transaction(NetworkDefinition::simulator()),
+    )
+}
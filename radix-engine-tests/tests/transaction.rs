@@ -224,6 +224,7 @@ fn transaction_processor_produces_expected_error_for_undecodable_instructions()
             num_of_signature_validations: 0,
             auth_zone_params: Default::default(),
             costing_parameters: Default::default(),
+            subintent_params: Default::default(),
         },
     );
 
@@ -1,7 +1,11 @@
 #[cfg(not(feature = "alloc"))]
 mod multi_threaded_test {
     use radix_engine::system::bootstrap::Bootstrapper;
-    use radix_engine::transaction::{execute_and_commit_transaction, execute_transaction};
+    use radix_engine::transaction::{
+        execute_and_commit_in_parallel, execute_and_commit_transaction,
+        execute_and_commit_transactions_in_parallel, execute_transaction,
+        execute_transaction_recording_database_reads,
+    };
     use radix_engine::transaction::{CostingParameters, ExecutionConfig};
     use radix_engine::types::*;
     use radix_engine::vm::wasm::{DefaultWasmEngine, WasmValidatorConfigV1};
@@ -16,6 +20,8 @@ mod multi_threaded_test {
     extern crate crossbeam;
     use crossbeam::thread;
     use radix_engine::vm::{DefaultNativeVm, ScryptoVm, Vm};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
 
     // this test was inspired by radix_engine "Transfer" benchmark
     #[test]
@@ -129,4 +135,292 @@ mod multi_threaded_test {
         })
         .unwrap();
     }
+
+    #[test]
+    fn test_parallel_execution_matches_serial_execution() {
+        // Set up environment.
+        let scrypto_vm = ScryptoVm {
+            wasm_engine: DefaultWasmEngine::default(),
+            wasm_validator_config: WasmValidatorConfigV1::new(),
+        };
+        let native_vm = DefaultNativeVm::new();
+        let vm = Vm {
+            scrypto_vm: &scrypto_vm,
+            native_vm,
+        };
+        let mut substate_db = InMemorySubstateDatabase::standard();
+        Bootstrapper::new(
+            NetworkDefinition::simulator(),
+            &mut substate_db,
+            vm.clone(),
+            false,
+        )
+        .bootstrap_test_default()
+        .unwrap();
+
+        let private_key = Secp256k1PrivateKey::from_u64(1).unwrap();
+        let public_key = private_key.public_key();
+        let initial_proofs = btreeset![NonFungibleGlobalId::from_public_key(&public_key)];
+
+        // Create two accounts, the first one holding some XRD
+        let accounts = (0..2)
+            .map(|i| {
+                let manifest = ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .new_account_advanced(
+                        OwnerRole::Fixed(rule!(require(NonFungibleGlobalId::from_public_key(
+                            &public_key
+                        )))),
+                        None,
+                    )
+                    .build();
+                execute_and_commit_transaction(
+                    &mut substate_db,
+                    vm.clone(),
+                    &CostingParameters::default(),
+                    &ExecutionConfig::for_test_transaction(),
+                    &TestTransaction::new(manifest, hash(format!("Account creation: {i}")))
+                        .prepare()
+                        .unwrap()
+                        .get_executable(initial_proofs.clone()),
+                )
+                .expect_commit(true)
+                .new_component_addresses()[0]
+            })
+            .collect::<Vec<ComponentAddress>>();
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .try_deposit_entire_worktop_or_abort(accounts[0], None)
+            .build();
+        execute_and_commit_transaction(
+            &mut substate_db,
+            vm.clone(),
+            &CostingParameters::default(),
+            &ExecutionConfig::for_test_transaction(),
+            &TestTransaction::new(manifest, hash("Fill account"))
+                .prepare()
+                .unwrap()
+                .get_executable(initial_proofs.clone()),
+        )
+        .expect_commit(true);
+
+        // A mix of conflicting transfers (some of which fail on insufficient balance) and
+        // account creations
+        let transactions = (0..24)
+            .map(|i| {
+                let manifest = match i % 3 {
+                    0 => ManifestBuilder::new()
+                        .lock_fee_from_faucet()
+                        .new_account_advanced(OwnerRole::None, None)
+                        .build(),
+                    1 => ManifestBuilder::new()
+                        .lock_fee_from_faucet()
+                        .withdraw_from_account(accounts[0], XRD, dec!("1000"))
+                        .try_deposit_entire_worktop_or_abort(accounts[1], None)
+                        .build(),
+                    _ => ManifestBuilder::new()
+                        .lock_fee_from_faucet()
+                        .withdraw_from_account(accounts[1], XRD, dec!("1500"))
+                        .try_deposit_entire_worktop_or_abort(accounts[0], None)
+                        .build(),
+                };
+                TestTransaction::new(manifest, hash(format!("Batch: {i}")))
+                    .prepare()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let executables = transactions
+            .iter()
+            .map(|transaction| transaction.get_executable(initial_proofs.clone()))
+            .collect::<Vec<_>>();
+
+        let mut serial_substate_db = substate_db.clone();
+        let serial_receipts = executables
+            .iter()
+            .map(|executable| {
+                execute_and_commit_transaction(
+                    &mut serial_substate_db,
+                    vm.clone(),
+                    &CostingParameters::default(),
+                    &ExecutionConfig::for_test_transaction(),
+                    executable,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut parallel_substate_db = substate_db.clone();
+        let parallel_receipts = execute_and_commit_transactions_in_parallel(
+            &mut parallel_substate_db,
+            vm.clone(),
+            &CostingParameters::default(),
+            &ExecutionConfig::for_test_transaction(),
+            &executables,
+            4,
+        );
+
+        assert!(serial_receipts
+            .iter()
+            .any(|receipt| receipt.is_commit_failure()));
+        assert_eq!(parallel_receipts.len(), serial_receipts.len());
+        for (parallel_receipt, serial_receipt) in parallel_receipts.iter().zip(&serial_receipts) {
+            assert_eq!(
+                parallel_receipt
+                    .expect_commit_ignore_outcome()
+                    .state_updates,
+                serial_receipt.expect_commit_ignore_outcome().state_updates
+            );
+            assert_eq!(
+                parallel_receipt.is_commit_success(),
+                serial_receipt.is_commit_success()
+            );
+        }
+        assert_eq!(parallel_substate_db, serial_substate_db);
+    }
+
+    #[test]
+    fn test_non_conflicting_transactions_are_executed_concurrently() {
+        // Set up environment.
+        let scrypto_vm = ScryptoVm {
+            wasm_engine: DefaultWasmEngine::default(),
+            wasm_validator_config: WasmValidatorConfigV1::new(),
+        };
+        let native_vm = DefaultNativeVm::new();
+        let vm = Vm {
+            scrypto_vm: &scrypto_vm,
+            native_vm,
+        };
+        let mut substate_db = InMemorySubstateDatabase::standard();
+        Bootstrapper::new(
+            NetworkDefinition::simulator(),
+            &mut substate_db,
+            vm.clone(),
+            false,
+        )
+        .bootstrap_test_default()
+        .unwrap();
+
+        let private_key = Secp256k1PrivateKey::from_u64(1).unwrap();
+        let public_key = private_key.public_key();
+        let initial_proofs = btreeset![NonFungibleGlobalId::from_public_key(&public_key)];
+
+        // Create accounts holding some XRD
+        let accounts = (0..8)
+            .map(|i| {
+                let manifest = ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .new_account_advanced(
+                        OwnerRole::Fixed(rule!(require(NonFungibleGlobalId::from_public_key(
+                            &public_key
+                        )))),
+                        None,
+                    )
+                    .build();
+                let account = execute_and_commit_transaction(
+                    &mut substate_db,
+                    vm.clone(),
+                    &CostingParameters::default(),
+                    &ExecutionConfig::for_test_transaction(),
+                    &TestTransaction::new(manifest, hash(format!("Account creation: {i}")))
+                        .prepare()
+                        .unwrap()
+                        .get_executable(initial_proofs.clone()),
+                )
+                .expect_commit(true)
+                .new_component_addresses()[0];
+                let manifest = ManifestBuilder::new()
+                    .lock_fee_from_faucet()
+                    .get_free_xrd_from_faucet()
+                    .try_deposit_entire_worktop_or_abort(account, None)
+                    .build();
+                execute_and_commit_transaction(
+                    &mut substate_db,
+                    vm.clone(),
+                    &CostingParameters::default(),
+                    &ExecutionConfig::for_test_transaction(),
+                    &TestTransaction::new(manifest, hash(format!("Fill account: {i}")))
+                        .prepare()
+                        .unwrap()
+                        .get_executable(initial_proofs.clone()),
+                )
+                .expect_commit(true);
+                account
+            })
+            .collect::<Vec<ComponentAddress>>();
+
+        // Transfers between distinct pairs of accounts, each paying its own fee. They only share
+        // the validator rewards, which the fees are added to.
+        let transactions = (0..4)
+            .map(|i| {
+                let manifest = ManifestBuilder::new()
+                    .lock_fee(accounts[2 * i], dec!("50"))
+                    .withdraw_from_account(accounts[2 * i], XRD, dec!("100"))
+                    .try_deposit_entire_worktop_or_abort(accounts[2 * i + 1], None)
+                    .build();
+                TestTransaction::new(manifest, hash(format!("Batch: {i}")))
+                    .prepare()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let executables = transactions
+            .iter()
+            .map(|transaction| transaction.get_executable(initial_proofs.clone()))
+            .collect::<Vec<_>>();
+
+        let mut serial_substate_db = substate_db.clone();
+        let serial_receipts = executables
+            .iter()
+            .map(|executable| {
+                execute_and_commit_transaction(
+                    &mut serial_substate_db,
+                    vm.clone(),
+                    &CostingParameters::default(),
+                    &ExecutionConfig::for_test_transaction(),
+                    executable,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let executions = AtomicUsize::new(0);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let mut parallel_substate_db = substate_db.clone();
+        let parallel_receipts = execute_and_commit_in_parallel(
+            &mut parallel_substate_db,
+            &executables,
+            4,
+            |substate_db, executable| {
+                let execution_index = executions.fetch_add(1, Ordering::SeqCst);
+                let now_in_flight = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(now_in_flight, Ordering::SeqCst);
+                // Let the first two executions wait for each other, if they run concurrently
+                if execution_index < 2 {
+                    let deadline = Instant::now() + Duration::from_secs(10);
+                    while max_in_flight.load(Ordering::SeqCst) < 2 && Instant::now() < deadline {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                }
+                let result = execute_transaction_recording_database_reads(
+                    substate_db,
+                    vm.clone(),
+                    &CostingParameters::default(),
+                    &ExecutionConfig::for_test_transaction(),
+                    executable,
+                );
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                result
+            },
+        );
+
+        // No transaction has been re-executed
+        assert_eq!(executions.load(Ordering::SeqCst), executables.len());
+        assert!(max_in_flight.load(Ordering::SeqCst) >= 2);
+        for (parallel_receipt, serial_receipt) in parallel_receipts.iter().zip(&serial_receipts) {
+            assert_eq!(
+                parallel_receipt.expect_commit_success().state_updates,
+                serial_receipt.expect_commit_success().state_updates
+            );
+        }
+        assert_eq!(parallel_substate_db, serial_substate_db);
+    }
 }
@@ -1,9 +1,12 @@
 use super::{EpochChangeEvent, RoundChangeEvent, ValidatorCreator, ValidatorOwnerBadgeData};
 use crate::blueprints::consensus_manager::VALIDATOR_ROLE;
+use crate::blueprints::package::PackageBlueprintVersionDefinitionEntrySubstate;
 use crate::errors::ApplicationError;
 use crate::errors::RuntimeError;
 use crate::internal_prelude::*;
-use crate::kernel::kernel_api::KernelNodeApi;
+use crate::kernel::kernel_api::{KernelNodeApi, KernelSubstateApi};
+use crate::system::system_callback::SystemLockData;
+use crate::system::type_info::TypeInfoSubstate;
 use crate::types::*;
 use native_sdk::modules::metadata::Metadata;
 use native_sdk::modules::role_assignment::RoleAssignment;
@@ -20,7 +23,9 @@ use radix_engine_interface::api::{
     AttachedModuleId, ClientApi, CollectionIndex, FieldValue, ACTOR_STATE_SELF,
 };
 use radix_engine_interface::blueprints::consensus_manager::*;
-use radix_engine_interface::blueprints::package::BlueprintDefinitionInit;
+use radix_engine_interface::blueprints::package::{
+    BlueprintDefinitionInit, BlueprintVersionKey, PACKAGE_BLUEPRINTS_PARTITION_OFFSET,
+};
 use radix_engine_interface::blueprints::resource::*;
 use radix_engine_interface::{metadata_init, mint_roles, rule};
 
@@ -28,6 +33,10 @@ const MILLIS_IN_SECOND: i64 = 1000;
 const SECONDS_IN_MINUTE: i64 = 60;
 const MILLIS_IN_MINUTE: i64 = MILLIS_IN_SECOND * SECONDS_IN_MINUTE;
 
+/// The number of most recent round seeds retained by the randomness beacon, i.e. the number of
+/// rounds within which a [`RandomSeedCommitment`] has to be revealed.
+pub const RANDOM_SEED_HISTORY_LENGTH: u64 = 10_000;
+
 #[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
 pub struct ConsensusManagerConfigSubstate {
     pub config: ConsensusManagerConfig,
@@ -147,6 +156,26 @@ pub struct ProposerMinuteTimestampSubstate {
     pub epoch_minute: i32,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub struct RandomnessBeaconSubstate {
+    /// The number of rounds since the beacon was introduced, across all epochs.
+    pub round_sequence: u64,
+    /// The seed of the current round, which is a hash of the previous round's seed and of the
+    /// secret revealed by the round's leader (see [`RandomnessContribution`]).
+    pub seed: Hash,
+}
+
+impl RandomnessBeaconSubstate {
+    /// The state of the beacon before its first round, either at genesis or when it is introduced
+    /// to an existing ledger by a protocol update.
+    pub fn initial() -> Self {
+        Self {
+            round_sequence: 0,
+            seed: Hash([0; Hash::LENGTH]),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
 pub struct CurrentProposalStatisticSubstate {
     /// A proposal statistic of each validator from the current validator set, in the iteration
@@ -228,6 +257,20 @@ pub enum ConsensusManagerError {
         current: u32,
         max: u32,
     },
+    RandomSeedNotRevealed {
+        commitment: RandomSeedCommitment,
+        current_round_sequence: u64,
+    },
+    RandomSeedExpired {
+        commitment: RandomSeedCommitment,
+        current_round_sequence: u64,
+    },
+    UnknownRandomnessContributor {
+        leader: ValidatorIndex,
+    },
+    InvalidRandomnessReveal {
+        validator: ComponentAddress,
+    },
 }
 
 declare_native_blueprint_state! {
@@ -285,6 +328,13 @@ declare_native_blueprint_state! {
             },
             condition: Condition::Always,
         },
+        randomness_beacon: {
+            ident: RandomnessBeacon,
+            field_type: {
+                kind: StaticSingleVersioned,
+            },
+            condition: Condition::Always,
+        },
     },
     collections: {
         registered_validators_by_stake: SortedIndex {
@@ -302,6 +352,28 @@ declare_native_blueprint_state! {
             },
             allow_ownership: false,
         },
+        random_seeds: KeyValue {
+            entry_ident: RandomSeed,
+            key_type: {
+                kind: Static,
+                content_type: u64,
+            },
+            value_type: {
+                kind: StaticSingleVersioned,
+            },
+            allow_ownership: false,
+        },
+        randomness_commitments: KeyValue {
+            entry_ident: RandomnessCommitment,
+            key_type: {
+                kind: Static,
+                content_type: ComponentAddress,
+            },
+            value_type: {
+                kind: StaticSingleVersioned,
+            },
+            allow_ownership: false,
+        },
     }
 }
 
@@ -348,9 +420,14 @@ pub type ConsensusManagerCurrentValidatorSetV1 = CurrentValidatorSetSubstate;
 pub type ConsensusManagerCurrentProposalStatisticV1 = CurrentProposalStatisticSubstate;
 pub type ConsensusManagerProposerMinuteTimestampV1 = ProposerMinuteTimestampSubstate;
 pub type ConsensusManagerProposerMilliTimestampV1 = ProposerMilliTimestampSubstate;
+pub type ConsensusManagerRandomnessBeaconV1 = RandomnessBeaconSubstate;
 pub type ConsensusManagerRegisteredValidatorByStakeV1 = Validator;
+pub type ConsensusManagerRandomSeedV1 = Hash;
+pub type ConsensusManagerRandomnessCommitmentV1 = Hash;
 
 pub const CONSENSUS_MANAGER_REGISTERED_VALIDATORS_BY_STAKE_INDEX: CollectionIndex = 0u8;
+pub const CONSENSUS_MANAGER_RANDOM_SEEDS_INDEX: CollectionIndex = 1u8;
+pub const CONSENSUS_MANAGER_RANDOMNESS_COMMITMENTS_INDEX: CollectionIndex = 2u8;
 
 pub struct ConsensusManagerBlueprint;
 
@@ -435,6 +512,36 @@ impl ConsensusManagerBlueprint {
                 export: CONSENSUS_MANAGER_COMPARE_CURRENT_TIME_IDENT.to_string(),
             },
         );
+        functions.insert(
+            CONSENSUS_MANAGER_COMMIT_RANDOM_SEED_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: Some(ReceiverInfo::normal_ref()),
+                input: TypeRef::Static(
+                    aggregator
+                        .add_child_type_and_descendents::<ConsensusManagerCommitRandomSeedInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator
+                        .add_child_type_and_descendents::<ConsensusManagerCommitRandomSeedOutput>(),
+                ),
+                export: CONSENSUS_MANAGER_COMMIT_RANDOM_SEED_IDENT.to_string(),
+            },
+        );
+        functions.insert(
+            CONSENSUS_MANAGER_GET_RANDOM_SEED_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: Some(ReceiverInfo::normal_ref()),
+                input: TypeRef::Static(
+                    aggregator
+                        .add_child_type_and_descendents::<ConsensusManagerGetRandomSeedInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator
+                        .add_child_type_and_descendents::<ConsensusManagerGetRandomSeedOutput>(),
+                ),
+                export: CONSENSUS_MANAGER_GET_RANDOM_SEED_IDENT.to_string(),
+            },
+        );
         functions.insert(
             CONSENSUS_MANAGER_NEXT_ROUND_IDENT.to_string(),
             FunctionSchemaInit {
@@ -448,6 +555,21 @@ impl ConsensusManagerBlueprint {
                 export: CONSENSUS_MANAGER_NEXT_ROUND_IDENT.to_string(),
             },
         );
+        functions.insert(
+            CONSENSUS_MANAGER_NEXT_ROUND_WITH_RANDOMNESS_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: Some(ReceiverInfo::normal_ref_mut()),
+                input: TypeRef::Static(
+                    aggregator
+                        .add_child_type_and_descendents::<ConsensusManagerNextRoundWithRandomnessInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator
+                        .add_child_type_and_descendents::<ConsensusManagerNextRoundWithRandomnessOutput>(),
+                ),
+                export: CONSENSUS_MANAGER_NEXT_ROUND_WITH_RANDOMNESS_IDENT.to_string(),
+            },
+        );
         functions.insert(
             CONSENSUS_MANAGER_CREATE_VALIDATOR_IDENT.to_string(),
             FunctionSchemaInit {
@@ -506,10 +628,13 @@ impl ConsensusManagerBlueprint {
                     methods {
                         CONSENSUS_MANAGER_START_IDENT => []; // Genesis is able to call this by skipping auth
                         CONSENSUS_MANAGER_NEXT_ROUND_IDENT => [VALIDATOR_ROLE];
+                        CONSENSUS_MANAGER_NEXT_ROUND_WITH_RANDOMNESS_IDENT => [VALIDATOR_ROLE];
 
                         CONSENSUS_MANAGER_GET_CURRENT_EPOCH_IDENT => MethodAccessibility::Public;
                         CONSENSUS_MANAGER_GET_CURRENT_TIME_IDENT => MethodAccessibility::Public;
                         CONSENSUS_MANAGER_COMPARE_CURRENT_TIME_IDENT => MethodAccessibility::Public;
+                        CONSENSUS_MANAGER_COMMIT_RANDOM_SEED_IDENT => MethodAccessibility::Public;
+                        CONSENSUS_MANAGER_GET_RANDOM_SEED_IDENT => MethodAccessibility::Public;
                         CONSENSUS_MANAGER_CREATE_VALIDATOR_IDENT => MethodAccessibility::Public;
                     }
                 )),
@@ -603,6 +728,7 @@ impl ConsensusManagerBlueprint {
             let milli_timestamp = ProposerMilliTimestampSubstate {
                 epoch_milli: initial_time_milli,
             };
+            let randomness_beacon = RandomnessBeaconSubstate::initial();
 
             api.new_simple_object(
                 CONSENSUS_MANAGER_BLUEPRINT,
@@ -614,6 +740,7 @@ impl ConsensusManagerBlueprint {
                     ConsensusManagerField::CurrentProposalStatistic.field_index() => FieldValue::new(&ConsensusManagerCurrentProposalStatisticFieldPayload::from_content_source(current_proposal_statistic)),
                     ConsensusManagerField::ProposerMinuteTimestamp.field_index() => FieldValue::new(&ConsensusManagerProposerMinuteTimestampFieldPayload::from_content_source(minute_timestamp)),
                     ConsensusManagerField::ProposerMilliTimestamp.field_index() => FieldValue::new(&ConsensusManagerProposerMilliTimestampFieldPayload::from_content_source(milli_timestamp)),
+                    ConsensusManagerField::RandomnessBeacon.field_index() => FieldValue::new(&ConsensusManagerRandomnessBeaconFieldPayload::from_content_source(randomness_beacon)),
                 },
             )?
         };
@@ -793,6 +920,81 @@ impl ConsensusManagerBlueprint {
         }
     }
 
+    pub(crate) fn commit_random_seed<Y>(api: &mut Y) -> Result<RandomSeedCommitment, RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            ConsensusManagerField::RandomnessBeacon.into(),
+            LockFlags::read_only(),
+        )?;
+        let randomness_beacon = api
+            .field_read_typed::<ConsensusManagerRandomnessBeaconFieldPayload>(handle)?
+            .into_latest();
+        api.field_close(handle)?;
+
+        Ok(RandomSeedCommitment {
+            round_sequence: randomness_beacon.round_sequence,
+        })
+    }
+
+    pub(crate) fn get_random_seed<Y>(
+        commitment: RandomSeedCommitment,
+        api: &mut Y,
+    ) -> Result<Hash, RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            ConsensusManagerField::RandomnessBeacon.into(),
+            LockFlags::read_only(),
+        )?;
+        let randomness_beacon = api
+            .field_read_typed::<ConsensusManagerRandomnessBeaconFieldPayload>(handle)?
+            .into_latest();
+        api.field_close(handle)?;
+        let current_round_sequence = randomness_beacon.round_sequence;
+
+        // The committed seed is the one of the first round started after the commitment
+        let revealed_round_sequence = match commitment.round_sequence.checked_add(1) {
+            Some(sequence) if sequence <= current_round_sequence => sequence,
+            _ => {
+                return Err(RuntimeError::ApplicationError(
+                    ApplicationError::ConsensusManagerError(
+                        ConsensusManagerError::RandomSeedNotRevealed {
+                            commitment,
+                            current_round_sequence,
+                        },
+                    ),
+                ));
+            }
+        };
+        let expired_error = RuntimeError::ApplicationError(
+            ApplicationError::ConsensusManagerError(ConsensusManagerError::RandomSeedExpired {
+                commitment,
+                current_round_sequence,
+            }),
+        );
+        if current_round_sequence - revealed_round_sequence >= RANDOM_SEED_HISTORY_LENGTH {
+            return Err(expired_error);
+        }
+
+        let handle = api.actor_open_key_value_entry(
+            ACTOR_STATE_SELF,
+            ConsensusManagerCollection::RandomSeedKeyValue.collection_index(),
+            &scrypto_encode(&revealed_round_sequence).unwrap(),
+            LockFlags::read_only(),
+        )?;
+        let seed = api
+            .key_value_entry_get_typed::<ConsensusManagerRandomSeedEntryPayload>(handle)?
+            .map(|seed| seed.into_latest());
+        api.key_value_entry_close(handle)?;
+
+        seed.ok_or(expired_error)
+    }
+
     fn epoch_minute_to_instant(epoch_minute: i32) -> Instant {
         Instant::new(epoch_minute as i64 * SECONDS_IN_MINUTE)
     }
@@ -805,13 +1007,24 @@ impl ConsensusManagerBlueprint {
         round: Round,
         proposer_timestamp_milli: i64,
         proposal_history: LeaderProposalHistory,
+        randomness: Option<RandomnessContribution>,
         api: &mut Y,
     ) -> Result<(), RuntimeError>
     where
-        Y: ClientApi<RuntimeError>,
+        Y: KernelSubstateApi<SystemLockData> + ClientApi<RuntimeError>,
     {
         Self::check_non_decreasing_and_update_timestamps(proposer_timestamp_milli, api)?;
 
+        // Verified against the current validator set, i.e. before a possible epoch change
+        let revealed_secret = match randomness {
+            Some(randomness) => Self::accept_randomness_contribution(
+                proposal_history.current_leader,
+                randomness,
+                api,
+            )?,
+            None => None,
+        };
+
         let config_handle = api.actor_open_field(
             ACTOR_STATE_SELF,
             ConsensusManagerField::Configuration.into(),
@@ -875,6 +1088,7 @@ impl ConsensusManagerBlueprint {
             }
         }
         manager_substate.current_leader = Some(current_leader);
+        let epoch = manager_substate.epoch;
 
         api.field_write_typed(
             manager_handle,
@@ -882,9 +1096,263 @@ impl ConsensusManagerBlueprint {
         )?;
         api.field_close(manager_handle)?;
 
+        Self::advance_randomness_beacon(epoch, round, revealed_secret, api)?;
+        Self::process_due_scheduled_calls(epoch, proposer_timestamp_milli, api)?;
+
         Ok(())
     }
 
+    /// Checks that the leader reveals the secret it committed to in its previous contribution (if
+    /// any), and records its commitment to the secret of its next one. Returns the revealed secret.
+    fn accept_randomness_contribution<Y>(
+        leader: ValidatorIndex,
+        randomness: RandomnessContribution,
+        api: &mut Y,
+    ) -> Result<Option<Hash>, RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let validator_set_handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            ConsensusManagerField::CurrentValidatorSet.into(),
+            LockFlags::read_only(),
+        )?;
+        let validator_set = api
+            .field_read_typed::<ConsensusManagerCurrentValidatorSetFieldPayload>(
+                validator_set_handle,
+            )?
+            .into_latest();
+        api.field_close(validator_set_handle)?;
+        let validator = *validator_set
+            .validator_set
+            .get_by_index(leader)
+            .ok_or_else(|| {
+                RuntimeError::ApplicationError(ApplicationError::ConsensusManagerError(
+                    ConsensusManagerError::UnknownRandomnessContributor { leader },
+                ))
+            })?
+            .0;
+
+        let handle = api.actor_open_key_value_entry(
+            ACTOR_STATE_SELF,
+            ConsensusManagerCollection::RandomnessCommitmentKeyValue.collection_index(),
+            &scrypto_encode(&validator).unwrap(),
+            LockFlags::MUTABLE,
+        )?;
+        let commitment = api
+            .key_value_entry_get_typed::<ConsensusManagerRandomnessCommitmentEntryPayload>(handle)?
+            .map(|commitment| commitment.into_latest());
+        let is_valid_reveal = match (commitment, randomness.reveal) {
+            (Some(commitment), Some(reveal)) => hash(reveal) == commitment,
+            (None, None) => true,
+            _ => false,
+        };
+        if !is_valid_reveal {
+            return Err(RuntimeError::ApplicationError(
+                ApplicationError::ConsensusManagerError(
+                    ConsensusManagerError::InvalidRandomnessReveal { validator },
+                ),
+            ));
+        }
+        api.key_value_entry_set_typed(
+            handle,
+            &ConsensusManagerRandomnessCommitmentEntryPayload::from_content_source(
+                randomness.next_commitment,
+            ),
+        )?;
+        api.key_value_entry_close(handle)?;
+
+        Ok(randomness.reveal)
+    }
+
+    /// Derives the seed of the new round, records it in the history and prunes the seeds which
+    /// can no longer be revealed.
+    ///
+    /// Ledgers bootstrapped before the randomness beacon was introduced keep the previous
+    /// definition of the consensus manager until the [`ProtocolUpdate::RandomnessBeacon`] is
+    /// applied, and have no beacon to advance until then.
+    ///
+    /// [`ProtocolUpdate::RandomnessBeacon`]: crate::system::bootstrap::ProtocolUpdate::RandomnessBeacon
+    fn advance_randomness_beacon<Y>(
+        epoch: Epoch,
+        round: Round,
+        revealed_secret: Option<Hash>,
+        api: &mut Y,
+    ) -> Result<(), RuntimeError>
+    where
+        Y: KernelSubstateApi<SystemLockData> + ClientApi<RuntimeError>,
+    {
+        if !Self::randomness_beacon_exists(api)? {
+            return Ok(());
+        }
+
+        let handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            ConsensusManagerField::RandomnessBeacon.into(),
+            LockFlags::MUTABLE,
+        )?;
+        let previous = api
+            .field_read_typed::<ConsensusManagerRandomnessBeaconFieldPayload>(handle)?
+            .into_latest();
+        let randomness_beacon = RandomnessBeaconSubstate {
+            round_sequence: previous.round_sequence + 1,
+            seed: hash(scrypto_encode(&(previous.seed, revealed_secret, epoch, round)).unwrap()),
+        };
+
+        let entry_handle = api.actor_open_key_value_entry(
+            ACTOR_STATE_SELF,
+            ConsensusManagerCollection::RandomSeedKeyValue.collection_index(),
+            &scrypto_encode(&randomness_beacon.round_sequence).unwrap(),
+            LockFlags::MUTABLE,
+        )?;
+        api.key_value_entry_set_typed(
+            entry_handle,
+            &ConsensusManagerRandomSeedEntryPayload::from_content_source(randomness_beacon.seed),
+        )?;
+        api.key_value_entry_close(entry_handle)?;
+        if randomness_beacon.round_sequence > RANDOM_SEED_HISTORY_LENGTH {
+            api.actor_remove_key_value_entry(
+                ACTOR_STATE_SELF,
+                ConsensusManagerCollection::RandomSeedKeyValue.collection_index(),
+                &scrypto_encode(&(randomness_beacon.round_sequence - RANDOM_SEED_HISTORY_LENGTH))
+                    .unwrap(),
+            )?;
+        }
+
+        api.field_write_typed(
+            handle,
+            &ConsensusManagerRandomnessBeaconFieldPayload::from_content_source(randomness_beacon),
+        )?;
+        api.field_close(handle)?;
+
+        Ok(())
+    }
+
+    /// Makes the scheduled calls which have become due ready for execution, and adds the fees of
+    /// the calls which are settled to the validator rewards.
+    ///
+    /// Ledgers bootstrapped before the scheduler was introduced have no scheduler until the
+    /// [`ProtocolUpdate::Scheduler`] is applied, and nothing is processed until then.
+    ///
+    /// [`ProtocolUpdate::Scheduler`]: crate::system::bootstrap::ProtocolUpdate::Scheduler
+    fn process_due_scheduled_calls<Y>(
+        epoch: Epoch,
+        proposer_timestamp_milli: i64,
+        api: &mut Y,
+    ) -> Result<(), RuntimeError>
+    where
+        Y: KernelSubstateApi<SystemLockData> + ClientApi<RuntimeError>,
+    {
+        if !Self::scheduler_exists(api)? {
+            return Ok(());
+        }
+
+        let rtn = api.call_method(
+            SCHEDULER.as_node_id(),
+            SCHEDULER_PROCESS_DUE_CALLS_IDENT,
+            scrypto_encode(&SchedulerProcessDueCallsInput {
+                epoch,
+                proposer_timestamp_ms: proposer_timestamp_milli,
+            })
+            .unwrap(),
+        )?;
+        let fees: SchedulerProcessDueCallsOutput = scrypto_decode(&rtn).unwrap();
+        let Some(fees) = fees else {
+            return Ok(());
+        };
+
+        let rewards_handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            ConsensusManagerField::ValidatorRewards.into(),
+            LockFlags::read_only(),
+        )?;
+        let mut rewards_substate = api
+            .field_read_typed::<ConsensusManagerValidatorRewardsFieldPayload>(rewards_handle)?
+            .into_latest();
+        rewards_substate.rewards_vault.put(fees, api)?;
+        api.field_close(rewards_handle)?;
+
+        Ok(())
+    }
+
+    fn randomness_beacon_exists<Y>(api: &mut Y) -> Result<bool, RuntimeError>
+    where
+        Y: KernelSubstateApi<SystemLockData>,
+    {
+        let handle = api.kernel_open_substate_with_default(
+            CONSENSUS_MANAGER_PACKAGE.as_node_id(),
+            MAIN_BASE_PARTITION
+                .at_offset(PACKAGE_BLUEPRINTS_PARTITION_OFFSET)
+                .unwrap(),
+            &SubstateKey::Map(
+                scrypto_encode(&BlueprintVersionKey::new_default(
+                    CONSENSUS_MANAGER_BLUEPRINT,
+                ))
+                .unwrap(),
+            ),
+            LockFlags::read_only(),
+            Some(|| IndexedScryptoValue::from_typed(&KeyValueEntrySubstate::<()>::default())),
+            SystemLockData::default(),
+        )?;
+        let definition: PackageBlueprintVersionDefinitionEntrySubstate =
+            api.kernel_read_substate(handle)?.as_typed().unwrap();
+        api.kernel_close_substate(handle)?;
+        let is_defined = match definition.into_value() {
+            Some(definition) => {
+                let state = definition.into_latest().interface.state;
+                state
+                    .field(ConsensusManagerField::RandomnessBeacon.field_index())
+                    .is_some()
+                    && state
+                        .get_partition(
+                            ConsensusManagerCollection::RandomSeedKeyValue.collection_index(),
+                        )
+                        .is_some()
+            }
+            None => false,
+        };
+        if !is_defined {
+            return Ok(false);
+        }
+
+        // The unit default stands in for a beacon which was never initialized
+        let handle = api.kernel_open_substate_with_default(
+            CONSENSUS_MANAGER.as_node_id(),
+            MAIN_BASE_PARTITION,
+            &ConsensusManagerField::RandomnessBeacon.into(),
+            LockFlags::read_only(),
+            Some(|| IndexedScryptoValue::from_typed(&())),
+            SystemLockData::default(),
+        )?;
+        let exists = api
+            .kernel_read_substate(handle)?
+            .as_typed::<FieldSubstate<ConsensusManagerRandomnessBeaconFieldPayload>>()
+            .is_ok();
+        api.kernel_close_substate(handle)?;
+        Ok(exists)
+    }
+
+    fn scheduler_exists<Y>(api: &mut Y) -> Result<bool, RuntimeError>
+    where
+        Y: KernelSubstateApi<SystemLockData>,
+    {
+        // The unit default stands in for the type info of a scheduler which was never created
+        let handle = api.kernel_open_substate_with_default(
+            SCHEDULER.as_node_id(),
+            TYPE_INFO_FIELD_PARTITION,
+            &TypeInfoField::TypeInfo.into(),
+            LockFlags::read_only(),
+            Some(|| IndexedScryptoValue::from_typed(&())),
+            SystemLockData::default(),
+        )?;
+        let exists = api
+            .kernel_read_substate(handle)?
+            .as_typed::<TypeInfoSubstate>()
+            .is_ok();
+        api.kernel_close_substate(handle)?;
+        Ok(exists)
+    }
+
     fn get_validator_xrd_cost<Y>(api: &mut Y) -> Result<Option<Decimal>, RuntimeError>
     where
         Y: KernelNodeApi + ClientApi<RuntimeError>,
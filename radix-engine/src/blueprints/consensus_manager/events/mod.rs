@@ -1,5 +1,7 @@
 mod consensus_manager;
+mod scheduler;
 mod validator;
 
 pub use consensus_manager::*;
+pub use scheduler::*;
 pub use validator::*;
@@ -0,0 +1,42 @@
+use crate::types::*;
+use radix_engine_interface::blueprints::consensus_manager::*;
+
+#[derive(Debug, Clone, ScryptoSbor, ScryptoEvent, PartialEq, Eq)]
+pub struct ScheduledCallRegisteredEvent {
+    pub id: ScheduledCallId,
+    pub target: ComponentAddress,
+    pub method_name: String,
+    pub trigger: ScheduledCallTrigger,
+    pub fee: Decimal,
+}
+
+#[derive(Debug, Clone, ScryptoSbor, ScryptoEvent, PartialEq, Eq)]
+pub struct ScheduledCallCancelledEvent {
+    pub id: ScheduledCallId,
+}
+
+/// Emitted on the round change which made the call due. The protocol then executes the call in
+/// its own transaction, which follows the round change.
+#[derive(Debug, Clone, ScryptoSbor, ScryptoEvent, PartialEq, Eq)]
+pub struct ScheduledCallDueEvent {
+    pub id: ScheduledCallId,
+}
+
+#[derive(Debug, Clone, ScryptoSbor, ScryptoEvent, PartialEq, Eq)]
+pub struct ScheduledCallExecutedEvent {
+    pub id: ScheduledCallId,
+}
+
+/// Emitted when the transaction executing a due call was committed as a failure. The fee it paid
+/// is kept, and the rest of the call's fee goes to the validators.
+#[derive(Debug, Clone, ScryptoSbor, ScryptoEvent, PartialEq, Eq)]
+pub struct ScheduledCallFailedEvent {
+    pub id: ScheduledCallId,
+}
+
+/// Emitted when a due call was not attempted before the next round change, e.g. because the
+/// transaction executing it was rejected. Its fee can be reclaimed by cancelling the call.
+#[derive(Debug, Clone, ScryptoSbor, ScryptoEvent, PartialEq, Eq)]
+pub struct ScheduledCallExpiredEvent {
+    pub id: ScheduledCallId,
+}
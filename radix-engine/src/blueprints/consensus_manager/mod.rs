@@ -1,9 +1,11 @@
 mod consensus_manager;
 mod events;
 mod package;
+mod scheduler;
 mod validator;
 
 pub use consensus_manager::*;
 pub use events::*;
 pub use package::*;
+pub use scheduler::*;
 pub use validator::*;
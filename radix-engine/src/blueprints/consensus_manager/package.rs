@@ -1,6 +1,9 @@
-use crate::blueprints::consensus_manager::{ConsensusManagerBlueprint, ValidatorBlueprint};
+use crate::blueprints::consensus_manager::{
+    ConsensusManagerBlueprint, SchedulerBlueprint, ValidatorBlueprint, SCHEDULER_CREATE_EXPORT_NAME,
+};
 use crate::errors::{ApplicationError, RuntimeError};
-use crate::kernel::kernel_api::KernelNodeApi;
+use crate::kernel::kernel_api::{KernelNodeApi, KernelSubstateApi};
+use crate::system::system_callback::SystemLockData;
 use crate::types::*;
 use radix_engine_interface::api::ClientApi;
 use radix_engine_interface::blueprints::consensus_manager::*;
@@ -15,6 +18,7 @@ impl ConsensusManagerNativePackage {
         let blueprints = indexmap!(
             CONSENSUS_MANAGER_BLUEPRINT.to_string() => ConsensusManagerBlueprint::definition(),
             VALIDATOR_BLUEPRINT.to_string() => ValidatorBlueprint::definition(),
+            SCHEDULER_BLUEPRINT.to_string() => SchedulerBlueprint::definition(),
         );
 
         PackageDefinition { blueprints }
@@ -26,7 +30,7 @@ impl ConsensusManagerNativePackage {
         api: &mut Y,
     ) -> Result<IndexedScryptoValue, RuntimeError>
     where
-        Y: KernelNodeApi + ClientApi<RuntimeError>,
+        Y: KernelNodeApi + KernelSubstateApi<SystemLockData> + ClientApi<RuntimeError>,
     {
         match export_name {
             CONSENSUS_MANAGER_CREATE_IDENT => {
@@ -83,6 +87,23 @@ impl ConsensusManagerNativePackage {
 
                 Ok(IndexedScryptoValue::from_typed(&rtn))
             }
+            CONSENSUS_MANAGER_COMMIT_RANDOM_SEED_IDENT => {
+                let _input: ConsensusManagerCommitRandomSeedInput =
+                    input.as_typed().map_err(|e| {
+                        RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                    })?;
+                let rtn = ConsensusManagerBlueprint::commit_random_seed(api)?;
+
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
+            CONSENSUS_MANAGER_GET_RANDOM_SEED_IDENT => {
+                let input: ConsensusManagerGetRandomSeedInput = input.as_typed().map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                })?;
+                let rtn = ConsensusManagerBlueprint::get_random_seed(input.commitment, api)?;
+
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
             CONSENSUS_MANAGER_NEXT_ROUND_IDENT => {
                 let input: ConsensusManagerNextRoundInput = input.as_typed().map_err(|e| {
                     RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
@@ -91,6 +112,22 @@ impl ConsensusManagerNativePackage {
                     input.round,
                     input.proposer_timestamp_ms,
                     input.leader_proposal_history,
+                    None,
+                    api,
+                )?;
+
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
+            CONSENSUS_MANAGER_NEXT_ROUND_WITH_RANDOMNESS_IDENT => {
+                let input: ConsensusManagerNextRoundWithRandomnessInput =
+                    input.as_typed().map_err(|e| {
+                        RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                    })?;
+                let rtn = ConsensusManagerBlueprint::next_round(
+                    input.round,
+                    input.proposer_timestamp_ms,
+                    input.leader_proposal_history,
+                    Some(input.randomness),
                     api,
                 )?;
 
@@ -268,6 +305,60 @@ impl ConsensusManagerNativePackage {
                 let rtn = ValidatorBlueprint::apply_reward(input.xrd_bucket, input.epoch, api)?;
                 Ok(IndexedScryptoValue::from_typed(&rtn))
             }
+            SCHEDULER_CREATE_EXPORT_NAME => {
+                let input: SchedulerCreateInput = input.as_typed().map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                })?;
+                let rtn = SchedulerBlueprint::create(input.address_reservation, api)?;
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
+            SCHEDULER_SCHEDULE_CALL_IDENT => {
+                let input: SchedulerScheduleCallInput = input.as_typed().map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                })?;
+                let rtn = SchedulerBlueprint::schedule_call(
+                    input.target,
+                    input.method_name,
+                    input.args,
+                    input.trigger,
+                    input.fee,
+                    input.cancel_rule,
+                    api,
+                )?;
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
+            SCHEDULER_CANCEL_CALL_IDENT => {
+                let input: SchedulerCancelCallInput = input.as_typed().map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                })?;
+                let rtn = SchedulerBlueprint::cancel_call(input.id, api)?;
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
+            SCHEDULER_PROCESS_DUE_CALLS_IDENT => {
+                let input: SchedulerProcessDueCallsInput = input.as_typed().map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                })?;
+                let rtn = SchedulerBlueprint::process_due_calls(
+                    input.epoch,
+                    input.proposer_timestamp_ms,
+                    api,
+                )?;
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
+            SCHEDULER_EXECUTE_CALL_IDENT => {
+                let input: SchedulerExecuteCallInput = input.as_typed().map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                })?;
+                let rtn = SchedulerBlueprint::execute_call(input.id, api)?;
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
+            SCHEDULER_RECORD_FAILED_CALL_IDENT => {
+                let input: SchedulerRecordFailedCallInput = input.as_typed().map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                })?;
+                let rtn = SchedulerBlueprint::record_failed_call(input.id, api)?;
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
             _ => Err(RuntimeError::ApplicationError(
                 ApplicationError::ExportDoesNotExist(export_name.to_string()),
             )),
@@ -0,0 +1,797 @@
+use super::{
+    ScheduledCallCancelledEvent, ScheduledCallDueEvent, ScheduledCallExecutedEvent,
+    ScheduledCallExpiredEvent, ScheduledCallFailedEvent, ScheduledCallRegisteredEvent,
+};
+use crate::errors::ApplicationError;
+use crate::errors::RuntimeError;
+use crate::internal_prelude::*;
+use crate::types::*;
+use native_sdk::modules::metadata::Metadata;
+use native_sdk::modules::role_assignment::RoleAssignment;
+use native_sdk::resource::{NativeBucket, NativeFungibleVault, NativeVault};
+use native_sdk::runtime::Runtime;
+use radix_engine_interface::api::field_api::LockFlags;
+use radix_engine_interface::api::node_modules::auth::AuthAddresses;
+use radix_engine_interface::api::node_modules::auth::RoleDefinition;
+use radix_engine_interface::api::node_modules::auth::ToRoleEntry;
+use radix_engine_interface::api::object_api::ModuleId;
+use radix_engine_interface::api::{
+    AttachedModuleId, ClientApi, CollectionIndex, FieldValue, ACTOR_STATE_SELF,
+};
+use radix_engine_interface::blueprints::consensus_manager::*;
+use radix_engine_interface::blueprints::package::BlueprintDefinitionInit;
+use radix_engine_interface::schema::{
+    BlueprintCollectionSchema, BlueprintSchemaInit, BlueprintStateSchemaInit, FieldSchema,
+};
+use radix_engine_interface::{metadata_init, rule};
+
+pub const SCHEDULER_CREATE_EXPORT_NAME: &str = "scheduler_create";
+
+/// Only the consensus manager's round change may make calls due.
+pub const SCHEDULER_CONSENSUS_MANAGER_ROLE: &str = "consensus_manager";
+/// Only the protocol may execute due calls, and record the ones which failed.
+pub const SCHEDULER_PROTOCOL_ROLE: &str = "protocol";
+
+pub const MAX_DUE_SCHEDULED_CALLS_PER_ROUND: u32 = 16;
+pub const MAX_SCHEDULED_CALLS_PER_SLOT: u32 = 64;
+pub const MAX_PENDING_SCHEDULE_SLOTS: u32 = 4096;
+
+pub const SCHEDULER_STATE_FIELD_INDEX: u8 = 0u8;
+pub const SCHEDULER_CALLS_COLLECTION_INDEX: CollectionIndex = 0u8;
+pub const SCHEDULER_SLOTS_COLLECTION_INDEX: CollectionIndex = 1u8;
+
+const SECONDS_IN_MINUTE: i64 = 60;
+const MILLIS_IN_MINUTE: i64 = 1000 * SECONDS_IN_MINUTE;
+
+#[derive(Debug, Clone, Eq, PartialEq, ScryptoSbor)]
+pub enum SchedulerError {
+    InvalidFeeResource(ResourceAddress),
+    InvalidFeeAmount(Decimal),
+    InvalidArgs(DecodeError),
+    ArgsContainOwnedNodes,
+    ArgsContainNonGlobalReferences,
+    InvalidTrigger(ScheduledCallTrigger),
+    ScheduledCallNotFound(ScheduledCallId),
+    ScheduledCallNotCancellable(ScheduledCallId),
+    ScheduledCallNotDue(ScheduledCallId),
+    SlotFull { slot: ScheduleSlot, max: u32 },
+    TooManyPendingSlots { max: u32 },
+    UnexpectedDecimalComputationError,
+}
+
+impl From<SchedulerError> for RuntimeError {
+    fn from(error: SchedulerError) -> Self {
+        RuntimeError::ApplicationError(ApplicationError::SchedulerError(error))
+    }
+}
+
+/// A group of scheduled calls which become due together.
+///
+/// Time-triggered calls are grouped by proposer minute, rounding up, so that a call is never
+/// executed before its requested instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ScryptoSbor)]
+pub enum ScheduleSlot {
+    Epoch(Epoch),
+    Minute(i32),
+}
+
+impl ScheduleSlot {
+    pub fn for_trigger(trigger: ScheduledCallTrigger) -> Result<Self, SchedulerError> {
+        match trigger {
+            ScheduledCallTrigger::AtEpoch(epoch) => Ok(ScheduleSlot::Epoch(epoch)),
+            ScheduledCallTrigger::AtTime(instant) => {
+                let seconds = instant.seconds_since_unix_epoch;
+                let minute = seconds.div_euclid(SECONDS_IN_MINUTE)
+                    + if seconds.rem_euclid(SECONDS_IN_MINUTE) > 0 {
+                        1
+                    } else {
+                        0
+                    };
+                i32::try_from(minute)
+                    .map(ScheduleSlot::Minute)
+                    .map_err(|_| SchedulerError::InvalidTrigger(trigger))
+            }
+        }
+    }
+
+    pub fn is_due(&self, epoch: Epoch, proposer_timestamp_ms: i64) -> bool {
+        match self {
+            ScheduleSlot::Epoch(slot_epoch) => epoch >= *slot_epoch,
+            ScheduleSlot::Minute(slot_minute) => {
+                proposer_timestamp_ms.div_euclid(MILLIS_IN_MINUTE) >= *slot_minute as i64
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ScryptoSbor)]
+pub enum ScheduledCallStatus {
+    /// Waiting for its slot to become due; may still be cancelled.
+    Pending,
+    /// Made due by the latest round change; is executed by the protocol before the next round
+    /// change.
+    Due,
+    /// Was not attempted before the next round change; its fee may be reclaimed by cancelling it.
+    Expired,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub struct ScheduledCall {
+    pub target: ComponentAddress,
+    pub method_name: String,
+    pub args: Vec<u8>,
+    pub trigger: ScheduledCallTrigger,
+    pub fee: Decimal,
+    pub cancel_rule: AccessRule,
+    pub status: ScheduledCallStatus,
+}
+
+#[derive(Debug, ScryptoSbor)]
+pub enum SchedulerSubstate {
+    V1(SchedulerSubstateV1),
+}
+
+impl SchedulerSubstate {
+    pub fn into_v1(self) -> SchedulerSubstateV1 {
+        match self {
+            SchedulerSubstate::V1(scheduler) => scheduler,
+        }
+    }
+}
+
+#[derive(Debug, ScryptoSbor)]
+pub struct SchedulerSubstateV1 {
+    pub next_id: u64,
+    /// The sum of the fees of all calls which have not yet been executed, cancelled or recorded
+    /// as failed. Anything in the fee vault beyond this amount is handed to the validators.
+    pub outstanding_fees: Decimal,
+    pub fee_vault: Vault,
+    pub pending_slots: BTreeSet<ScheduleSlot>,
+    pub due_calls: Vec<ScheduledCallId>,
+
+    // parameters
+    pub max_due_calls_per_round: u32,
+    pub max_calls_per_slot: u32,
+    pub max_pending_slots: u32,
+}
+
+pub struct SchedulerBlueprint;
+
+impl SchedulerBlueprint {
+    pub fn definition() -> BlueprintDefinitionInit {
+        let mut aggregator = TypeAggregator::<ScryptoCustomTypeKind>::new();
+
+        let mut fields = Vec::new();
+        fields.push(FieldSchema::static_field(
+            aggregator.add_child_type_and_descendents::<SchedulerSubstate>(),
+        ));
+
+        let mut collections: Vec<BlueprintCollectionSchema<TypeRef<LocalTypeId>>> = vec![];
+        collections.push(BlueprintCollectionSchema::KeyValueStore(
+            BlueprintKeyValueSchema {
+                key: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<ScheduledCallId>(),
+                ),
+                value: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<ScheduledCall>(),
+                ),
+                allow_ownership: false,
+            },
+        ));
+        collections.push(BlueprintCollectionSchema::KeyValueStore(
+            BlueprintKeyValueSchema {
+                key: TypeRef::Static(aggregator.add_child_type_and_descendents::<ScheduleSlot>()),
+                value: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<Vec<ScheduledCallId>>(),
+                ),
+                allow_ownership: false,
+            },
+        ));
+
+        let mut functions = index_map_new();
+        functions.insert(
+            SCHEDULER_CREATE_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: None,
+                input: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerCreateInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerCreateOutput>(),
+                ),
+                export: SCHEDULER_CREATE_EXPORT_NAME.to_string(),
+            },
+        );
+        functions.insert(
+            SCHEDULER_SCHEDULE_CALL_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: Some(ReceiverInfo::normal_ref_mut()),
+                input: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerScheduleCallInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerScheduleCallOutput>(),
+                ),
+                export: SCHEDULER_SCHEDULE_CALL_IDENT.to_string(),
+            },
+        );
+        functions.insert(
+            SCHEDULER_CANCEL_CALL_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: Some(ReceiverInfo::normal_ref_mut()),
+                input: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerCancelCallInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerCancelCallOutput>(),
+                ),
+                export: SCHEDULER_CANCEL_CALL_IDENT.to_string(),
+            },
+        );
+        functions.insert(
+            SCHEDULER_PROCESS_DUE_CALLS_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: Some(ReceiverInfo::normal_ref_mut()),
+                input: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerProcessDueCallsInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerProcessDueCallsOutput>(),
+                ),
+                export: SCHEDULER_PROCESS_DUE_CALLS_IDENT.to_string(),
+            },
+        );
+        functions.insert(
+            SCHEDULER_RECORD_FAILED_CALL_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: Some(ReceiverInfo::normal_ref_mut()),
+                input: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerRecordFailedCallInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerRecordFailedCallOutput>(),
+                ),
+                export: SCHEDULER_RECORD_FAILED_CALL_IDENT.to_string(),
+            },
+        );
+        functions.insert(
+            SCHEDULER_EXECUTE_CALL_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: Some(ReceiverInfo::normal_ref_mut()),
+                input: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerExecuteCallInput>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<SchedulerExecuteCallOutput>(),
+                ),
+                export: SCHEDULER_EXECUTE_CALL_IDENT.to_string(),
+            },
+        );
+
+        let event_schema = event_schema! {
+            aggregator,
+            [
+                ScheduledCallRegisteredEvent,
+                ScheduledCallCancelledEvent,
+                ScheduledCallDueEvent,
+                ScheduledCallExecutedEvent,
+                ScheduledCallFailedEvent,
+                ScheduledCallExpiredEvent
+            ]
+        };
+
+        let schema = generate_full_schema(aggregator);
+
+        BlueprintDefinitionInit {
+            blueprint_type: BlueprintType::default(),
+            is_transient: false,
+            feature_set: indexset!(),
+            dependencies: indexset!(XRD.into(),),
+            schema: BlueprintSchemaInit {
+                generics: vec![],
+                schema,
+                state: BlueprintStateSchemaInit {
+                    fields,
+                    collections,
+                },
+                events: event_schema,
+                types: BlueprintTypeSchemaInit::default(),
+                functions: BlueprintFunctionsSchemaInit { functions },
+                hooks: BlueprintHooksInit::default(),
+            },
+
+            royalty_config: PackageRoyaltyConfig::default(),
+            auth_config: AuthConfig {
+                function_auth: FunctionAuth::AccessRules(indexmap!(
+                    SCHEDULER_CREATE_IDENT.to_string() => rule!(require(AuthAddresses::system_role())),
+                )),
+                method_auth: MethodAuthTemplate::StaticRoleDefinition(roles_template!(
+                    roles {
+                        SCHEDULER_CONSENSUS_MANAGER_ROLE;
+                        SCHEDULER_PROTOCOL_ROLE;
+                    },
+                    methods {
+                        SCHEDULER_PROCESS_DUE_CALLS_IDENT => [SCHEDULER_CONSENSUS_MANAGER_ROLE];
+                        // Only due calls may be executed, and each pays for itself, once
+                        SCHEDULER_EXECUTE_CALL_IDENT => [SCHEDULER_PROTOCOL_ROLE];
+                        SCHEDULER_RECORD_FAILED_CALL_IDENT => [SCHEDULER_PROTOCOL_ROLE];
+
+                        SCHEDULER_SCHEDULE_CALL_IDENT => MethodAccessibility::Public;
+                        // Cancellation is checked against the call's own cancel rule
+                        SCHEDULER_CANCEL_CALL_IDENT => MethodAccessibility::Public;
+                    }
+                )),
+            },
+        }
+    }
+
+    pub(crate) fn create<Y>(
+        address_reservation: GlobalAddressReservation,
+        api: &mut Y,
+    ) -> Result<ComponentAddress, RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let scheduler = SchedulerSubstate::V1(SchedulerSubstateV1 {
+            next_id: 0,
+            outstanding_fees: Decimal::ZERO,
+            fee_vault: Vault::create(XRD, api)?,
+            pending_slots: BTreeSet::new(),
+            due_calls: Vec::new(),
+            max_due_calls_per_round: MAX_DUE_SCHEDULED_CALLS_PER_ROUND,
+            max_calls_per_slot: MAX_SCHEDULED_CALLS_PER_SLOT,
+            max_pending_slots: MAX_PENDING_SCHEDULE_SLOTS,
+        });
+        let scheduler_id = api.new_simple_object(
+            SCHEDULER_BLUEPRINT,
+            indexmap!(SCHEDULER_STATE_FIELD_INDEX => FieldValue::new(&scheduler)),
+        )?;
+
+        let role_definitions = roles2! {
+            SCHEDULER_CONSENSUS_MANAGER_ROLE => rule!(require(global_caller(CONSENSUS_MANAGER)));
+            SCHEDULER_PROTOCOL_ROLE => rule!(require(AuthAddresses::validator_role()));
+        };
+        let roles = indexmap!(ModuleId::Main => role_definitions);
+        let role_assignment = RoleAssignment::create(OwnerRole::None, roles, api)?.0;
+        let metadata = Metadata::create_with_data(
+            metadata_init! {
+                "name" => "Scheduler".to_owned(), locked;
+                "description" => "A component that keeps track of method calls deferred until a given epoch or time, which are then executed by the protocol.".to_owned(), locked;
+            },
+            api,
+        )?;
+
+        let address = api.globalize(
+            scheduler_id,
+            indexmap!(
+                AttachedModuleId::RoleAssignment => role_assignment.0,
+                AttachedModuleId::Metadata => metadata.0,
+            ),
+            Some(address_reservation),
+        )?;
+
+        Ok(ComponentAddress::new_or_panic(address.into()))
+    }
+
+    pub(crate) fn schedule_call<Y>(
+        target: ComponentAddress,
+        method_name: String,
+        args: Vec<u8>,
+        trigger: ScheduledCallTrigger,
+        fee: Bucket,
+        cancel_rule: AccessRule,
+        api: &mut Y,
+    ) -> Result<ScheduledCallId, RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let fee_resource = fee.resource_address(api)?;
+        if fee_resource != XRD {
+            return Err(SchedulerError::InvalidFeeResource(fee_resource).into());
+        }
+        let fee_amount = fee.amount(api)?;
+        if !fee_amount.is_positive() {
+            return Err(SchedulerError::InvalidFeeAmount(fee_amount).into());
+        }
+
+        // The call is executed long after this transaction, so its arguments may only refer to
+        // nodes which are going to remain visible.
+        let indexed_args =
+            IndexedScryptoValue::from_slice(&args).map_err(SchedulerError::InvalidArgs)?;
+        if !indexed_args.owned_nodes().is_empty() {
+            return Err(SchedulerError::ArgsContainOwnedNodes.into());
+        }
+        if !indexed_args
+            .references()
+            .iter()
+            .all(|node_id| node_id.is_global())
+        {
+            return Err(SchedulerError::ArgsContainNonGlobalReferences.into());
+        }
+
+        let slot = ScheduleSlot::for_trigger(trigger)?;
+
+        let handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            SCHEDULER_STATE_FIELD_INDEX,
+            LockFlags::MUTABLE,
+        )?;
+        let mut scheduler = api.field_read_typed::<SchedulerSubstate>(handle)?.into_v1();
+
+        if !scheduler.pending_slots.contains(&slot)
+            && scheduler.pending_slots.len() >= scheduler.max_pending_slots as usize
+        {
+            return Err(SchedulerError::TooManyPendingSlots {
+                max: scheduler.max_pending_slots,
+            }
+            .into());
+        }
+
+        let id = ScheduledCallId(scheduler.next_id);
+        scheduler.next_id += 1;
+
+        let slot_handle = api.actor_open_key_value_entry(
+            ACTOR_STATE_SELF,
+            SCHEDULER_SLOTS_COLLECTION_INDEX,
+            &scrypto_encode(&slot).unwrap(),
+            LockFlags::MUTABLE,
+        )?;
+        let mut slot_calls = api
+            .key_value_entry_get_typed::<Vec<ScheduledCallId>>(slot_handle)?
+            .unwrap_or_default();
+        if slot_calls.len() >= scheduler.max_calls_per_slot as usize {
+            return Err(SchedulerError::SlotFull {
+                slot,
+                max: scheduler.max_calls_per_slot,
+            }
+            .into());
+        }
+        slot_calls.push(id);
+        api.key_value_entry_set_typed(slot_handle, &slot_calls)?;
+        api.key_value_entry_close(slot_handle)?;
+        scheduler.pending_slots.insert(slot);
+
+        let call_handle = api.actor_open_key_value_entry(
+            ACTOR_STATE_SELF,
+            SCHEDULER_CALLS_COLLECTION_INDEX,
+            &scrypto_encode(&id).unwrap(),
+            LockFlags::MUTABLE,
+        )?;
+        api.key_value_entry_set_typed(
+            call_handle,
+            &ScheduledCall {
+                target,
+                method_name: method_name.clone(),
+                args,
+                trigger,
+                fee: fee_amount,
+                cancel_rule,
+                status: ScheduledCallStatus::Pending,
+            },
+        )?;
+        api.key_value_entry_close(call_handle)?;
+
+        scheduler.fee_vault.put(fee, api)?;
+        scheduler.outstanding_fees = scheduler
+            .outstanding_fees
+            .checked_add(fee_amount)
+            .ok_or(SchedulerError::UnexpectedDecimalComputationError)?;
+        api.field_write_typed(handle, &SchedulerSubstate::V1(scheduler))?;
+        api.field_close(handle)?;
+
+        Runtime::emit_event(
+            api,
+            ScheduledCallRegisteredEvent {
+                id,
+                target,
+                method_name,
+                trigger,
+                fee: fee_amount,
+            },
+        )?;
+
+        Ok(id)
+    }
+
+    pub(crate) fn cancel_call<Y>(id: ScheduledCallId, api: &mut Y) -> Result<Bucket, RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let call = Self::get_call(id, api)?;
+        if call.status == ScheduledCallStatus::Due {
+            return Err(SchedulerError::ScheduledCallNotCancellable(id).into());
+        }
+        Runtime::assert_access_rule(call.cancel_rule, api)?;
+
+        let handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            SCHEDULER_STATE_FIELD_INDEX,
+            LockFlags::MUTABLE,
+        )?;
+        let mut scheduler = api.field_read_typed::<SchedulerSubstate>(handle)?.into_v1();
+
+        // An expired call has already left its slot
+        if call.status == ScheduledCallStatus::Pending {
+            let slot = ScheduleSlot::for_trigger(call.trigger)?;
+            let slot_handle = api.actor_open_key_value_entry(
+                ACTOR_STATE_SELF,
+                SCHEDULER_SLOTS_COLLECTION_INDEX,
+                &scrypto_encode(&slot).unwrap(),
+                LockFlags::MUTABLE,
+            )?;
+            let mut slot_calls = api
+                .key_value_entry_get_typed::<Vec<ScheduledCallId>>(slot_handle)?
+                .unwrap_or_default();
+            slot_calls.retain(|slot_call| *slot_call != id);
+            if slot_calls.is_empty() {
+                api.key_value_entry_remove(slot_handle)?;
+                scheduler.pending_slots.remove(&slot);
+            } else {
+                api.key_value_entry_set_typed(slot_handle, &slot_calls)?;
+            }
+            api.key_value_entry_close(slot_handle)?;
+        }
+
+        api.actor_remove_key_value_entry(
+            ACTOR_STATE_SELF,
+            SCHEDULER_CALLS_COLLECTION_INDEX,
+            &scrypto_encode(&id).unwrap(),
+        )?;
+
+        scheduler.outstanding_fees = scheduler
+            .outstanding_fees
+            .checked_sub(call.fee)
+            .ok_or(SchedulerError::UnexpectedDecimalComputationError)?;
+        let refund = scheduler.fee_vault.take(call.fee, api)?;
+        api.field_write_typed(handle, &SchedulerSubstate::V1(scheduler))?;
+        api.field_close(handle)?;
+
+        Runtime::emit_event(api, ScheduledCallCancelledEvent { id })?;
+
+        Ok(refund)
+    }
+
+    /// Invoked by the consensus manager on every round change. Expires the calls which were made
+    /// due by the previous round change but have not been attempted since, and then makes up to
+    /// `max_due_calls_per_round` pending calls due, in order of their slots.
+    ///
+    /// Returns the part of the fee vault which is no longer owed to any call (i.e. the unused
+    /// remainder of the fees of executed and failed calls). Expired calls keep their fees, until
+    /// they are cancelled.
+    pub(crate) fn process_due_calls<Y>(
+        epoch: Epoch,
+        proposer_timestamp_ms: i64,
+        api: &mut Y,
+    ) -> Result<Option<Bucket>, RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            SCHEDULER_STATE_FIELD_INDEX,
+            LockFlags::MUTABLE,
+        )?;
+        let mut scheduler = api.field_read_typed::<SchedulerSubstate>(handle)?.into_v1();
+        let mut updated = !scheduler.due_calls.is_empty();
+
+        for id in scheduler.due_calls.drain(..) {
+            let call_handle = api.actor_open_key_value_entry(
+                ACTOR_STATE_SELF,
+                SCHEDULER_CALLS_COLLECTION_INDEX,
+                &scrypto_encode(&id).unwrap(),
+                LockFlags::MUTABLE,
+            )?;
+            let expired_call = api.key_value_entry_get_typed::<ScheduledCall>(call_handle)?;
+            if let Some(mut expired_call) = expired_call {
+                expired_call.status = ScheduledCallStatus::Expired;
+                api.key_value_entry_set_typed(call_handle, &expired_call)?;
+                Runtime::emit_event(api, ScheduledCallExpiredEvent { id })?;
+            }
+            api.key_value_entry_close(call_handle)?;
+        }
+
+        let due_slots: Vec<ScheduleSlot> = scheduler
+            .pending_slots
+            .iter()
+            .filter(|slot| slot.is_due(epoch, proposer_timestamp_ms))
+            .cloned()
+            .collect();
+        let mut budget = scheduler.max_due_calls_per_round as usize;
+        for slot in due_slots {
+            if budget == 0 {
+                break;
+            }
+            updated = true;
+
+            let slot_handle = api.actor_open_key_value_entry(
+                ACTOR_STATE_SELF,
+                SCHEDULER_SLOTS_COLLECTION_INDEX,
+                &scrypto_encode(&slot).unwrap(),
+                LockFlags::MUTABLE,
+            )?;
+            let mut slot_calls = api
+                .key_value_entry_get_typed::<Vec<ScheduledCallId>>(slot_handle)?
+                .unwrap_or_default();
+            let now_due_count = budget.min(slot_calls.len());
+            let now_due: Vec<ScheduledCallId> = slot_calls.drain(..now_due_count).collect();
+            if slot_calls.is_empty() {
+                api.key_value_entry_remove(slot_handle)?;
+                scheduler.pending_slots.remove(&slot);
+            } else {
+                api.key_value_entry_set_typed(slot_handle, &slot_calls)?;
+            }
+            api.key_value_entry_close(slot_handle)?;
+
+            for id in now_due {
+                let call_handle = api.actor_open_key_value_entry(
+                    ACTOR_STATE_SELF,
+                    SCHEDULER_CALLS_COLLECTION_INDEX,
+                    &scrypto_encode(&id).unwrap(),
+                    LockFlags::MUTABLE,
+                )?;
+                let mut call = api
+                    .key_value_entry_get_typed::<ScheduledCall>(call_handle)?
+                    .ok_or(SchedulerError::ScheduledCallNotFound(id))?;
+                call.status = ScheduledCallStatus::Due;
+                api.key_value_entry_set_typed(call_handle, &call)?;
+                api.key_value_entry_close(call_handle)?;
+
+                scheduler.due_calls.push(id);
+                budget -= 1;
+                Runtime::emit_event(api, ScheduledCallDueEvent { id })?;
+            }
+        }
+
+        let surplus = scheduler
+            .fee_vault
+            .amount(api)?
+            .checked_sub(scheduler.outstanding_fees)
+            .ok_or(SchedulerError::UnexpectedDecimalComputationError)?;
+        let surplus = if surplus.is_positive() {
+            Some(scheduler.fee_vault.take(surplus, api)?)
+        } else {
+            None
+        };
+
+        // Most rounds have nothing to process, in which case the state is left untouched
+        if updated {
+            api.field_write_typed(handle, &SchedulerSubstate::V1(scheduler))?;
+        }
+        api.field_close(handle)?;
+
+        Ok(surplus)
+    }
+
+    /// Executes a call made due by the latest round change, locking its fee from the fee vault.
+    /// The protocol executes each due call in its own transaction, following the round change.
+    ///
+    /// If the call fails, the transaction is committed as a failure which still pays the fee, and
+    /// the protocol then records the call as failed (see [`Self::record_failed_call`]).
+    pub(crate) fn execute_call<Y>(id: ScheduledCallId, api: &mut Y) -> Result<(), RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let call = Self::get_call(id, api)?;
+        if call.status != ScheduledCallStatus::Due {
+            return Err(SchedulerError::ScheduledCallNotDue(id).into());
+        }
+        api.actor_remove_key_value_entry(
+            ACTOR_STATE_SELF,
+            SCHEDULER_CALLS_COLLECTION_INDEX,
+            &scrypto_encode(&id).unwrap(),
+        )?;
+
+        let handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            SCHEDULER_STATE_FIELD_INDEX,
+            LockFlags::MUTABLE,
+        )?;
+        let mut scheduler = api.field_read_typed::<SchedulerSubstate>(handle)?.into_v1();
+        scheduler.due_calls.retain(|due_call| *due_call != id);
+        scheduler.outstanding_fees = scheduler
+            .outstanding_fees
+            .checked_sub(call.fee)
+            .ok_or(SchedulerError::UnexpectedDecimalComputationError)?;
+        scheduler.fee_vault.lock_fee(api, call.fee)?;
+        api.field_write_typed(handle, &SchedulerSubstate::V1(scheduler))?;
+        api.field_close(handle)?;
+
+        // No state is locked during the call, so that the target may schedule follow-up calls.
+        api.call_method(call.target.as_node_id(), &call.method_name, call.args)?;
+
+        Runtime::emit_event(api, ScheduledCallExecutedEvent { id })?;
+
+        Ok(())
+    }
+
+    /// Removes a due call whose execution was committed as a failure. Its fee has been paid, so it
+    /// must not be refunded on expiry; the unused remainder goes to the validators instead.
+    pub(crate) fn record_failed_call<Y>(
+        id: ScheduledCallId,
+        api: &mut Y,
+    ) -> Result<(), RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let call = Self::get_call(id, api)?;
+        if call.status != ScheduledCallStatus::Due {
+            return Err(SchedulerError::ScheduledCallNotDue(id).into());
+        }
+        api.actor_remove_key_value_entry(
+            ACTOR_STATE_SELF,
+            SCHEDULER_CALLS_COLLECTION_INDEX,
+            &scrypto_encode(&id).unwrap(),
+        )?;
+
+        let handle = api.actor_open_field(
+            ACTOR_STATE_SELF,
+            SCHEDULER_STATE_FIELD_INDEX,
+            LockFlags::MUTABLE,
+        )?;
+        let mut scheduler = api.field_read_typed::<SchedulerSubstate>(handle)?.into_v1();
+        scheduler.due_calls.retain(|due_call| *due_call != id);
+        scheduler.outstanding_fees = scheduler
+            .outstanding_fees
+            .checked_sub(call.fee)
+            .ok_or(SchedulerError::UnexpectedDecimalComputationError)?;
+        api.field_write_typed(handle, &SchedulerSubstate::V1(scheduler))?;
+        api.field_close(handle)?;
+
+        Runtime::emit_event(api, ScheduledCallFailedEvent { id })?;
+
+        Ok(())
+    }
+
+    fn get_call<Y>(id: ScheduledCallId, api: &mut Y) -> Result<ScheduledCall, RuntimeError>
+    where
+        Y: ClientApi<RuntimeError>,
+    {
+        let call_handle = api.actor_open_key_value_entry(
+            ACTOR_STATE_SELF,
+            SCHEDULER_CALLS_COLLECTION_INDEX,
+            &scrypto_encode(&id).unwrap(),
+            LockFlags::read_only(),
+        )?;
+        let call = api.key_value_entry_get_typed::<ScheduledCall>(call_handle)?;
+        api.key_value_entry_close(call_handle)?;
+
+        call.ok_or_else(|| SchedulerError::ScheduledCallNotFound(id).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_triggers_are_rounded_up_to_the_next_minute() {
+        assert_eq!(
+            ScheduleSlot::for_trigger(ScheduledCallTrigger::AtTime(Instant::new(120))),
+            Ok(ScheduleSlot::Minute(2))
+        );
+        assert_eq!(
+            ScheduleSlot::for_trigger(ScheduledCallTrigger::AtTime(Instant::new(121))),
+            Ok(ScheduleSlot::Minute(3))
+        );
+        assert_eq!(
+            ScheduleSlot::for_trigger(ScheduledCallTrigger::AtTime(Instant::new(-59))),
+            Ok(ScheduleSlot::Minute(0))
+        );
+        assert!(
+            ScheduleSlot::for_trigger(ScheduledCallTrigger::AtTime(Instant::new(i64::MAX)))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn slots_are_due_once_reached() {
+        let epoch_slot = ScheduleSlot::Epoch(Epoch::of(5));
+        assert!(!epoch_slot.is_due(Epoch::of(4), 0));
+        assert!(epoch_slot.is_due(Epoch::of(5), 0));
+        assert!(epoch_slot.is_due(Epoch::of(6), 0));
+
+        let minute_slot = ScheduleSlot::Minute(3);
+        assert!(!minute_slot.is_due(Epoch::of(1), 3 * MILLIS_IN_MINUTE - 1));
+        assert!(minute_slot.is_due(Epoch::of(1), 3 * MILLIS_IN_MINUTE));
+    }
+}
@@ -13,9 +13,11 @@ use radix_engine_interface::schema::{
     BlueprintEventSchemaInit, BlueprintFunctionsSchemaInit, BlueprintSchemaInit,
     BlueprintStateSchemaInit, FunctionSchemaInit, TypeRef,
 };
+use transaction::model::SubintentParams;
 
 use super::TransactionProcessorBlueprint;
 use super::TransactionProcessorRunInput;
+use super::TransactionProcessorRunV2Input;
 
 pub struct TransactionProcessorNativePackage;
 
@@ -39,6 +41,19 @@ impl TransactionProcessorNativePackage {
                 export: TRANSACTION_PROCESSOR_RUN_IDENT.to_string(),
             },
         );
+        functions.insert(
+            TRANSACTION_PROCESSOR_RUN_V2_IDENT.to_string(),
+            FunctionSchemaInit {
+                receiver: None,
+                input: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<TransactionProcessorRunV2Input>(),
+                ),
+                output: TypeRef::Static(
+                    aggregator.add_child_type_and_descendents::<TransactionProcessorRunOutput>(),
+                ),
+                export: TRANSACTION_PROCESSOR_RUN_V2_IDENT.to_string(),
+            },
+        );
 
         let schema = generate_full_schema(aggregator);
         let blueprints = indexmap!(
@@ -94,6 +109,23 @@ impl TransactionProcessorNativePackage {
                     input.global_address_reservations,
                     input.references,
                     input.blobs,
+                    SubintentParams::default(),
+                    api,
+                )?;
+
+                Ok(IndexedScryptoValue::from_typed(&rtn))
+            }
+            TRANSACTION_PROCESSOR_RUN_V2_IDENT => {
+                let input: TransactionProcessorRunV2Input = input.as_typed().map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+                })?;
+
+                let rtn = TransactionProcessorBlueprint::run(
+                    input.manifest_encoded_instructions,
+                    input.global_address_reservations,
+                    input.references,
+                    input.blobs,
+                    input.subintent_params,
                     api,
                 )?;
 
@@ -1,3 +1,4 @@
+use crate::blueprints::resource::AuthZone;
 use crate::blueprints::resource::WorktopSubstate;
 use crate::errors::ApplicationError;
 use crate::errors::RuntimeError;
@@ -12,7 +13,7 @@ use native_sdk::resource::NativeFungibleBucket;
 use native_sdk::resource::NativeNonFungibleBucket;
 use native_sdk::resource::{NativeBucket, NativeProof, Worktop};
 use native_sdk::runtime::LocalAuthZone;
-use radix_engine_interface::api::{AttachedModuleId, ClientApi};
+use radix_engine_interface::api::{AttachedModuleId, ClientApi, ACTOR_REF_AUTH_ZONE};
 use radix_engine_interface::blueprints::package::BlueprintVersion;
 use radix_engine_interface::blueprints::resource::*;
 use radix_engine_interface::blueprints::transaction_processor::*;
@@ -39,6 +40,28 @@ pub struct TransactionProcessorRunInputEfficientEncodable<'a> {
     pub blobs: &'a IndexMap<Hash, Vec<u8>>,
 }
 
+/// The input of `run_v2`, which also runs the subintents of a transaction.
+/// This is a separate function, so that the schema of `run` stays the one which existing ledgers
+/// were bootstrapped with.
+#[derive(Debug, Eq, PartialEq, ScryptoSbor)]
+pub struct TransactionProcessorRunV2Input {
+    pub manifest_encoded_instructions: Vec<u8>,
+    pub global_address_reservations: Vec<GlobalAddressReservation>,
+    pub references: Vec<Reference>, // Required so that the kernel passes the references to the processor frame
+    pub blobs: IndexMap<Hash, Vec<u8>>,
+    pub subintent_params: SubintentParams,
+}
+
+// This needs to match the above, but is easily encodable to avoid cloning from the transaction payload to encode
+#[derive(Debug, Eq, PartialEq, ScryptoEncode)]
+pub struct TransactionProcessorRunV2InputEfficientEncodable<'a> {
+    pub manifest_encoded_instructions: &'a [u8],
+    pub global_address_reservations: Vec<GlobalAddressReservation>,
+    pub references: &'a IndexSet<Reference>,
+    pub blobs: &'a IndexMap<Hash, Vec<u8>>,
+    pub subintent_params: &'a SubintentParams,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
 pub enum TransactionProcessorError {
     BucketNotFound(u32),
@@ -53,6 +76,10 @@ pub enum TransactionProcessorError {
     AuthZoneIsEmpty,
     InvocationOutputDecodeError(DecodeError),
     ArgsEncodeError(EncodeError),
+    ChildIntentNotFound(u32),
+    ChildIntentAlreadyFinished(u32),
+    YieldToParentFromRootIntent,
+    SubintentNotFinished(Hash),
 }
 
 impl From<TransactionProcessorError> for RuntimeError {
@@ -98,12 +125,130 @@ impl TransactionProcessorBlueprint {
         global_address_reservations: Vec<GlobalAddressReservation>,
         _references: Vec<Reference>, // Required so that the kernel passes the references to the processor frame
         blobs: IndexMap<Hash, Vec<u8>>,
+        subintent_params: SubintentParams,
         api: &mut Y,
     ) -> Result<Vec<InstructionOutput>, RuntimeError>
     where
         Y: KernelNodeApi + KernelSubstateApi<L> + ClientApi<RuntimeError>,
     {
-        // Create a worktop
+        // The root intent is at index 0, and subintent `i` is at index `i + 1`
+        let mut intents = Vec::with_capacity(subintent_params.subintents.len() + 1);
+        intents.push(IntentProcessor {
+            subintent_hash: None,
+            instructions: Self::decode_instructions(&manifest_encoded_instructions)?.into_iter(),
+            worktop: Self::create_worktop(api)?,
+            processor: TransactionProcessor::new(blobs, global_address_reservations),
+            children: subintent_params
+                .root_children
+                .iter()
+                .map(|child| *child as usize + 1)
+                .collect(),
+            parent: None,
+            stashed_auth_zone: None,
+        });
+        for subintent in subintent_params.subintents {
+            intents.push(IntentProcessor {
+                subintent_hash: Some(subintent.subintent_hash),
+                instructions: Self::decode_instructions(&subintent.encoded_instructions)?
+                    .into_iter(),
+                worktop: Self::create_worktop(api)?,
+                processor: TransactionProcessor::new(subintent.blobs, Vec::new()),
+                children: subintent
+                    .children
+                    .iter()
+                    .map(|child| *child as usize + 1)
+                    .collect(),
+                parent: None,
+                // A subintent starts with only its own signature proofs in its auth zone
+                stashed_auth_zone: Some((Vec::new(), subintent.initial_proofs)),
+            });
+        }
+        for index in 0..intents.len() {
+            for child in intents[index].children.clone() {
+                if let Some(child) = intents.get_mut(child) {
+                    child.parent = Some(index);
+                }
+            }
+        }
+
+        // Run the intents as coroutines, starting at the root intent, and switching between
+        // intents on each yield. Only the outputs of the root intent are returned.
+        let mut outputs = Vec::new();
+        let mut current = 0usize;
+        let mut instruction_index = 0usize;
+        while let Some(inst) = intents[current].next_instruction() {
+            api.update_instruction_index(instruction_index)?;
+            instruction_index += 1;
+
+            let result = match inst {
+                InstructionV2::V1(inst) => {
+                    let intent = &mut intents[current];
+                    Self::execute_instruction_v1(
+                        inst,
+                        &mut intent.worktop,
+                        &mut intent.processor,
+                        api,
+                    )?
+                }
+                InstructionV2::YieldToChild { child_index, args } => {
+                    let child = *intents[current]
+                        .children
+                        .get(child_index as usize)
+                        .ok_or(TransactionProcessorError::ChildIntentNotFound(child_index))?;
+                    if intents[child].is_finished() {
+                        return Err(TransactionProcessorError::ChildIntentAlreadyFinished(
+                            child_index,
+                        )
+                        .into());
+                    }
+                    Self::yield_to(&mut intents, current, child, args, api)?;
+                    current = child;
+                    InstructionOutput::None
+                }
+                InstructionV2::YieldToParent { args } => {
+                    let parent = intents[current]
+                        .parent
+                        .ok_or(TransactionProcessorError::YieldToParentFromRootIntent)?;
+                    Self::yield_to(&mut intents, current, parent, args, api)?;
+                    current = parent;
+                    InstructionOutput::None
+                }
+            };
+            if current == 0 {
+                outputs.push(result);
+            }
+        }
+
+        for intent in intents {
+            if let Some(subintent_hash) = intent.subintent_hash {
+                if !intent.is_finished() {
+                    return Err(
+                        TransactionProcessorError::SubintentNotFinished(subintent_hash).into(),
+                    );
+                }
+            }
+            intent.worktop.drop(api)?;
+        }
+
+        Ok(outputs)
+    }
+
+    fn decode_instructions(
+        manifest_encoded_instructions: &[u8],
+    ) -> Result<Vec<InstructionV2>, RuntimeError> {
+        // Instructions V2 are a superset of instructions V1, so this also decodes V1 instructions
+        manifest_decode::<Vec<InstructionV2>>(manifest_encoded_instructions).map_err(|e| {
+            // This error should never occur if being called from root since this is constructed
+            // by the transaction executor. This error is more to protect against application
+            // space calling this function if/when possible
+            RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
+        })
+    }
+
+    fn create_worktop<Y>(api: &mut Y) -> Result<Worktop, RuntimeError>
+    where
+        Y: KernelNodeApi,
+    {
         let worktop_node_id = api.kernel_allocate_node_id(EntityType::InternalGenericComponent)?;
         api.kernel_create_node(
             worktop_node_id,
@@ -127,288 +272,373 @@ impl TransactionProcessorBlueprint {
         )?;
         api.kernel_pin_node(worktop_node_id)?;
 
-        let mut worktop = Worktop(Own(worktop_node_id));
-        let instructions = manifest_decode::<Vec<InstructionV1>>(&manifest_encoded_instructions)
-            .map_err(|e| {
-                // This error should never occur if being called from root since this is constructed
-                // by the transaction executor. This error is more to protect against application
-                // space calling this function if/when possible
-                RuntimeError::ApplicationError(ApplicationError::InputDecodeError(e))
-            })?;
-        let mut processor = TransactionProcessor::new(blobs, global_address_reservations);
-        let mut outputs = Vec::new();
-        for (index, inst) in instructions.into_iter().enumerate() {
-            api.update_instruction_index(index)?;
+        Ok(Worktop(Own(worktop_node_id)))
+    }
 
-            let result = match inst {
-                InstructionV1::TakeAllFromWorktop { resource_address } => {
-                    let bucket = worktop.take_all(resource_address, api)?;
-                    processor.create_manifest_bucket(bucket)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::TakeFromWorktop {
-                    amount,
-                    resource_address,
-                } => {
-                    let bucket = worktop.take(resource_address, amount, api)?;
-                    processor.create_manifest_bucket(bucket)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::TakeNonFungiblesFromWorktop {
-                    ids,
-                    resource_address,
-                } => {
-                    let bucket = worktop.take_non_fungibles(
-                        resource_address,
-                        ids.into_iter().collect(),
-                        api,
-                    )?;
-                    processor.create_manifest_bucket(bucket)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::ReturnToWorktop { bucket_id } => {
-                    let bucket = processor.take_bucket(&bucket_id)?;
-                    worktop.put(bucket, api)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::AssertWorktopContainsAny { resource_address } => {
-                    worktop.assert_contains(resource_address, api)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::AssertWorktopContains {
-                    amount,
-                    resource_address,
-                } => {
-                    worktop.assert_contains_amount(resource_address, amount, api)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::AssertWorktopContainsNonFungibles {
-                    ids,
-                    resource_address,
-                } => {
-                    worktop.assert_contains_non_fungibles(
-                        resource_address,
-                        ids.into_iter().collect(),
-                        api,
-                    )?;
-                    InstructionOutput::None
-                }
-                InstructionV1::PopFromAuthZone {} => {
-                    let proof = LocalAuthZone::pop(api)?.ok_or(RuntimeError::ApplicationError(
-                        ApplicationError::TransactionProcessorError(
-                            TransactionProcessorError::AuthZoneIsEmpty,
-                        ),
-                    ))?;
-                    processor.create_manifest_proof(proof)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::PushToAuthZone { proof_id } => {
-                    let proof = processor.take_proof(&proof_id)?;
-                    LocalAuthZone::push(proof, api)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::CreateProofFromAuthZoneOfAmount {
-                    amount,
+    /// Passes the args from one intent to another, and switches the auth zone over.
+    /// Buckets in the args are put onto the worktop of the target intent, and proofs
+    /// are pushed onto its auth zone.
+    fn yield_to<Y, L: Default>(
+        intents: &mut [IntentProcessor],
+        from: usize,
+        to: usize,
+        args: ManifestValue,
+        api: &mut Y,
+    ) -> Result<(), RuntimeError>
+    where
+        Y: KernelNodeApi + KernelSubstateApi<L> + ClientApi<RuntimeError>,
+    {
+        let scrypto_value = {
+            let intent = &mut intents[from];
+            let mut processor_with_api = TransactionProcessorWithApi {
+                worktop: &mut intent.worktop,
+                processor: &mut intent.processor,
+                api,
+            };
+            transform(args, &mut processor_with_api)?
+        };
+
+        let (proofs, virtual_non_fungibles) =
+            intents[to].stashed_auth_zone.take().unwrap_or_default();
+        intents[from].stashed_auth_zone =
+            Some(Self::swap_auth_zone(proofs, virtual_non_fungibles, api)?);
+
+        let value = IndexedScryptoValue::from_typed(&scrypto_value);
+        let intent = &mut intents[to];
+        intent
+            .processor
+            .handle_call_return_data(&value, &intent.worktop, api)?;
+
+        Ok(())
+    }
+
+    /// Replaces the proofs and virtual non-fungibles of the auth zone of the transaction
+    /// processor, returning the previous ones.
+    fn swap_auth_zone<Y, L: Default>(
+        proofs: Vec<Proof>,
+        virtual_non_fungibles: BTreeSet<NonFungibleGlobalId>,
+        api: &mut Y,
+    ) -> Result<(Vec<Proof>, BTreeSet<NonFungibleGlobalId>), RuntimeError>
+    where
+        Y: KernelSubstateApi<L> + ClientApi<RuntimeError>,
+    {
+        let auth_zone = api.actor_get_node_id(ACTOR_REF_AUTH_ZONE)?;
+        let handle = api.kernel_open_substate(
+            &auth_zone,
+            MAIN_BASE_PARTITION,
+            &AuthZoneField::AuthZone.into(),
+            LockFlags::MUTABLE,
+            L::default(),
+        )?;
+        let mut auth_zone = api
+            .kernel_read_substate(handle)?
+            .as_typed::<FieldSubstate<AuthZone>>()
+            .unwrap()
+            .into_payload();
+        let previous_proofs = core::mem::replace(&mut auth_zone.proofs, proofs);
+        let previous_virtual_non_fungibles =
+            core::mem::replace(&mut auth_zone.virtual_non_fungibles, virtual_non_fungibles);
+        api.kernel_write_substate(
+            handle,
+            IndexedScryptoValue::from_typed(&FieldSubstate::new_unlocked_field(auth_zone)),
+        )?;
+        api.kernel_close_substate(handle)?;
+
+        Ok((previous_proofs, previous_virtual_non_fungibles))
+    }
+
+    fn execute_instruction_v1<Y, L: Default>(
+        inst: InstructionV1,
+        worktop: &mut Worktop,
+        processor: &mut TransactionProcessor,
+        api: &mut Y,
+    ) -> Result<InstructionOutput, RuntimeError>
+    where
+        Y: KernelNodeApi + KernelSubstateApi<L> + ClientApi<RuntimeError>,
+    {
+        let result = match inst {
+            InstructionV1::TakeAllFromWorktop { resource_address } => {
+                let bucket = worktop.take_all(resource_address, api)?;
+                processor.create_manifest_bucket(bucket)?;
+                InstructionOutput::None
+            }
+            InstructionV1::TakeFromWorktop {
+                amount,
+                resource_address,
+            } => {
+                let bucket = worktop.take(resource_address, amount, api)?;
+                processor.create_manifest_bucket(bucket)?;
+                InstructionOutput::None
+            }
+            InstructionV1::TakeNonFungiblesFromWorktop {
+                ids,
+                resource_address,
+            } => {
+                let bucket =
+                    worktop.take_non_fungibles(resource_address, ids.into_iter().collect(), api)?;
+                processor.create_manifest_bucket(bucket)?;
+                InstructionOutput::None
+            }
+            InstructionV1::ReturnToWorktop { bucket_id } => {
+                let bucket = processor.take_bucket(&bucket_id)?;
+                worktop.put(bucket, api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::AssertWorktopContainsAny { resource_address } => {
+                worktop.assert_contains(resource_address, api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::AssertWorktopContains {
+                amount,
+                resource_address,
+            } => {
+                worktop.assert_contains_amount(resource_address, amount, api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::AssertWorktopContainsNonFungibles {
+                ids,
+                resource_address,
+            } => {
+                worktop.assert_contains_non_fungibles(
                     resource_address,
-                } => {
-                    let proof =
-                        LocalAuthZone::create_proof_of_amount(amount, resource_address, api)?;
-                    processor.create_manifest_proof(proof)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::CreateProofFromAuthZoneOfNonFungibles {
-                    ids,
+                    ids.into_iter().collect(),
+                    api,
+                )?;
+                InstructionOutput::None
+            }
+            InstructionV1::PopFromAuthZone {} => {
+                let proof = LocalAuthZone::pop(api)?.ok_or(RuntimeError::ApplicationError(
+                    ApplicationError::TransactionProcessorError(
+                        TransactionProcessorError::AuthZoneIsEmpty,
+                    ),
+                ))?;
+                processor.create_manifest_proof(proof)?;
+                InstructionOutput::None
+            }
+            InstructionV1::PushToAuthZone { proof_id } => {
+                let proof = processor.take_proof(&proof_id)?;
+                LocalAuthZone::push(proof, api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::CreateProofFromAuthZoneOfAmount {
+                amount,
+                resource_address,
+            } => {
+                let proof = LocalAuthZone::create_proof_of_amount(amount, resource_address, api)?;
+                processor.create_manifest_proof(proof)?;
+                InstructionOutput::None
+            }
+            InstructionV1::CreateProofFromAuthZoneOfNonFungibles {
+                ids,
+                resource_address,
+            } => {
+                let proof = LocalAuthZone::create_proof_of_non_fungibles(
+                    &ids.into_iter().collect(),
                     resource_address,
-                } => {
-                    let proof = LocalAuthZone::create_proof_of_non_fungibles(
-                        &ids.into_iter().collect(),
-                        resource_address,
-                        api,
-                    )?;
-                    processor.create_manifest_proof(proof)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::CreateProofFromAuthZoneOfAll { resource_address } => {
-                    let proof = LocalAuthZone::create_proof_of_all(resource_address, api)?;
-                    processor.create_manifest_proof(proof)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::CreateProofFromBucketOfAmount { bucket_id, amount } => {
-                    let bucket = processor.get_bucket(&bucket_id)?;
-                    let proof = bucket.create_proof_of_amount(amount, api)?;
-                    processor.create_manifest_proof(proof)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::CreateProofFromBucketOfNonFungibles { bucket_id, ids } => {
-                    let bucket = processor.get_bucket(&bucket_id)?;
-                    let proof =
-                        bucket.create_proof_of_non_fungibles(ids.into_iter().collect(), api)?;
-                    processor.create_manifest_proof(proof)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::CreateProofFromBucketOfAll { bucket_id } => {
-                    let bucket = processor.get_bucket(&bucket_id)?;
-                    let proof = bucket.create_proof_of_all(api)?;
-                    processor.create_manifest_proof(proof)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::DropAuthZoneProofs => {
-                    LocalAuthZone::drop_proofs(api)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::DropAuthZoneRegularProofs => {
-                    LocalAuthZone::drop_regular_proofs(api)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::DropAuthZoneSignatureProofs => {
-                    LocalAuthZone::drop_signature_proofs(api)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::BurnResource { bucket_id } => {
-                    let bucket = processor.take_bucket(&bucket_id)?;
-                    let rtn = bucket.burn(api)?;
+                    api,
+                )?;
+                processor.create_manifest_proof(proof)?;
+                InstructionOutput::None
+            }
+            InstructionV1::CreateProofFromAuthZoneOfAll { resource_address } => {
+                let proof = LocalAuthZone::create_proof_of_all(resource_address, api)?;
+                processor.create_manifest_proof(proof)?;
+                InstructionOutput::None
+            }
+            InstructionV1::CreateProofFromBucketOfAmount { bucket_id, amount } => {
+                let bucket = processor.get_bucket(&bucket_id)?;
+                let proof = bucket.create_proof_of_amount(amount, api)?;
+                processor.create_manifest_proof(proof)?;
+                InstructionOutput::None
+            }
+            InstructionV1::CreateProofFromBucketOfNonFungibles { bucket_id, ids } => {
+                let bucket = processor.get_bucket(&bucket_id)?;
+                let proof = bucket.create_proof_of_non_fungibles(ids.into_iter().collect(), api)?;
+                processor.create_manifest_proof(proof)?;
+                InstructionOutput::None
+            }
+            InstructionV1::CreateProofFromBucketOfAll { bucket_id } => {
+                let bucket = processor.get_bucket(&bucket_id)?;
+                let proof = bucket.create_proof_of_all(api)?;
+                processor.create_manifest_proof(proof)?;
+                InstructionOutput::None
+            }
+            InstructionV1::DropAuthZoneProofs => {
+                LocalAuthZone::drop_proofs(api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::DropAuthZoneRegularProofs => {
+                LocalAuthZone::drop_regular_proofs(api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::DropAuthZoneSignatureProofs => {
+                LocalAuthZone::drop_signature_proofs(api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::BurnResource { bucket_id } => {
+                let bucket = processor.take_bucket(&bucket_id)?;
+                let rtn = bucket.burn(api)?;
 
-                    let result = IndexedScryptoValue::from_typed(&rtn);
-                    processor.handle_call_return_data(&result, &worktop, api)?;
-                    InstructionOutput::CallReturn(result.into())
-                }
-                InstructionV1::CloneProof { proof_id } => {
-                    let proof = processor.get_proof(&proof_id)?;
-                    let proof = proof.clone(api)?;
-                    processor.create_manifest_proof(proof)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::DropProof { proof_id } => {
-                    let proof = processor.take_proof(&proof_id)?;
-                    proof.drop(api)?;
-                    InstructionOutput::None
-                }
-                InstructionV1::CallFunction {
-                    package_address,
-                    blueprint_name,
-                    function_name,
-                    args,
-                } => {
-                    let package_address = processor.resolve_package_address(package_address)?;
-                    handle_invocation(api, &mut processor, &mut worktop, args, |api, args| {
-                        api.call_function(
-                            package_address,
-                            &blueprint_name,
-                            &function_name,
-                            scrypto_encode(&args)
-                                .map_err(TransactionProcessorError::ArgsEncodeError)?,
-                        )
-                    })?
-                }
-                InstructionV1::CallMethod {
-                    address,
-                    method_name,
-                    args,
-                } => {
-                    let address = processor.resolve_global_address(address)?;
-                    handle_invocation(api, &mut processor, &mut worktop, args, |api, args| {
-                        api.call_method(
-                            address.as_node_id(),
-                            &method_name,
-                            scrypto_encode(&args)
-                                .map_err(TransactionProcessorError::ArgsEncodeError)?,
-                        )
-                    })?
-                }
-                InstructionV1::CallRoyaltyMethod {
-                    address,
-                    method_name,
-                    args,
-                } => {
-                    let address = processor.resolve_global_address(address)?;
-                    handle_invocation(api, &mut processor, &mut worktop, args, |api, args| {
-                        api.call_module_method(
-                            address.as_node_id(),
-                            AttachedModuleId::Royalty,
-                            &method_name,
-                            scrypto_encode(&args)
-                                .map_err(TransactionProcessorError::ArgsEncodeError)?,
-                        )
-                    })?
-                }
-                InstructionV1::CallMetadataMethod {
-                    address,
-                    method_name,
-                    args,
-                } => {
-                    let address = processor.resolve_global_address(address)?;
-                    handle_invocation(api, &mut processor, &mut worktop, args, |api, args| {
-                        api.call_module_method(
-                            address.as_node_id(),
-                            AttachedModuleId::Metadata,
-                            &method_name,
-                            scrypto_encode(&args)
-                                .map_err(TransactionProcessorError::ArgsEncodeError)?,
-                        )
-                    })?
-                }
-                InstructionV1::CallRoleAssignmentMethod {
-                    address,
-                    method_name,
-                    args,
-                } => {
-                    let address = processor.resolve_global_address(address)?;
-                    handle_invocation(api, &mut processor, &mut worktop, args, |api, args| {
-                        api.call_module_method(
-                            address.as_node_id(),
-                            AttachedModuleId::RoleAssignment,
-                            &method_name,
-                            scrypto_encode(&args)
-                                .map_err(TransactionProcessorError::ArgsEncodeError)?,
-                        )
-                    })?
-                }
-                InstructionV1::CallDirectVaultMethod {
-                    address,
-                    method_name,
-                    args,
-                } => handle_invocation(api, &mut processor, &mut worktop, args, |api, args| {
-                    api.call_direct_access_method(
+                let result = IndexedScryptoValue::from_typed(&rtn);
+                processor.handle_call_return_data(&result, worktop, api)?;
+                InstructionOutput::CallReturn(result.into())
+            }
+            InstructionV1::CloneProof { proof_id } => {
+                let proof = processor.get_proof(&proof_id)?;
+                let proof = proof.clone(api)?;
+                processor.create_manifest_proof(proof)?;
+                InstructionOutput::None
+            }
+            InstructionV1::DropProof { proof_id } => {
+                let proof = processor.take_proof(&proof_id)?;
+                proof.drop(api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::CallFunction {
+                package_address,
+                blueprint_name,
+                function_name,
+                args,
+            } => {
+                let package_address = processor.resolve_package_address(package_address)?;
+                handle_invocation(api, processor, worktop, args, |api, args| {
+                    api.call_function(
+                        package_address,
+                        &blueprint_name,
+                        &function_name,
+                        scrypto_encode(&args)
+                            .map_err(TransactionProcessorError::ArgsEncodeError)?,
+                    )
+                })?
+            }
+            InstructionV1::CallMethod {
+                address,
+                method_name,
+                args,
+            } => {
+                let address = processor.resolve_global_address(address)?;
+                handle_invocation(api, processor, worktop, args, |api, args| {
+                    api.call_method(
                         address.as_node_id(),
                         &method_name,
                         scrypto_encode(&args)
                             .map_err(TransactionProcessorError::ArgsEncodeError)?,
                     )
-                })?,
-                InstructionV1::DropNamedProofs => {
-                    for (_, real_id) in processor.proof_mapping.drain(..) {
-                        let proof = Proof(Own(real_id));
-                        proof.drop(api).map(|_| IndexedScryptoValue::unit())?;
-                    }
-                    InstructionOutput::None
+                })?
+            }
+            InstructionV1::CallRoyaltyMethod {
+                address,
+                method_name,
+                args,
+            } => {
+                let address = processor.resolve_global_address(address)?;
+                handle_invocation(api, processor, worktop, args, |api, args| {
+                    api.call_module_method(
+                        address.as_node_id(),
+                        AttachedModuleId::Royalty,
+                        &method_name,
+                        scrypto_encode(&args)
+                            .map_err(TransactionProcessorError::ArgsEncodeError)?,
+                    )
+                })?
+            }
+            InstructionV1::CallMetadataMethod {
+                address,
+                method_name,
+                args,
+            } => {
+                let address = processor.resolve_global_address(address)?;
+                handle_invocation(api, processor, worktop, args, |api, args| {
+                    api.call_module_method(
+                        address.as_node_id(),
+                        AttachedModuleId::Metadata,
+                        &method_name,
+                        scrypto_encode(&args)
+                            .map_err(TransactionProcessorError::ArgsEncodeError)?,
+                    )
+                })?
+            }
+            InstructionV1::CallRoleAssignmentMethod {
+                address,
+                method_name,
+                args,
+            } => {
+                let address = processor.resolve_global_address(address)?;
+                handle_invocation(api, processor, worktop, args, |api, args| {
+                    api.call_module_method(
+                        address.as_node_id(),
+                        AttachedModuleId::RoleAssignment,
+                        &method_name,
+                        scrypto_encode(&args)
+                            .map_err(TransactionProcessorError::ArgsEncodeError)?,
+                    )
+                })?
+            }
+            InstructionV1::CallDirectVaultMethod {
+                address,
+                method_name,
+                args,
+            } => handle_invocation(api, processor, worktop, args, |api, args| {
+                api.call_direct_access_method(
+                    address.as_node_id(),
+                    &method_name,
+                    scrypto_encode(&args).map_err(TransactionProcessorError::ArgsEncodeError)?,
+                )
+            })?,
+            InstructionV1::DropNamedProofs => {
+                for (_, real_id) in processor.proof_mapping.drain(..) {
+                    let proof = Proof(Own(real_id));
+                    proof.drop(api).map(|_| IndexedScryptoValue::unit())?;
                 }
-                InstructionV1::DropAllProofs => {
-                    for (_, real_id) in processor.proof_mapping.drain(..) {
-                        let proof = Proof(Own(real_id));
-                        proof.drop(api).map(|_| IndexedScryptoValue::unit())?;
-                    }
-                    LocalAuthZone::drop_proofs(api)?;
-                    InstructionOutput::None
+                InstructionOutput::None
+            }
+            InstructionV1::DropAllProofs => {
+                for (_, real_id) in processor.proof_mapping.drain(..) {
+                    let proof = Proof(Own(real_id));
+                    proof.drop(api).map(|_| IndexedScryptoValue::unit())?;
                 }
-                InstructionV1::AllocateGlobalAddress {
-                    package_address,
-                    blueprint_name,
-                } => {
-                    let (address_reservation, address) = api.allocate_global_address(
-                        BlueprintId::new(&package_address, blueprint_name),
-                    )?;
-                    processor.create_manifest_address_reservation(address_reservation)?;
-                    processor.create_manifest_address(address)?;
+                LocalAuthZone::drop_proofs(api)?;
+                InstructionOutput::None
+            }
+            InstructionV1::AllocateGlobalAddress {
+                package_address,
+                blueprint_name,
+            } => {
+                let (address_reservation, address) = api
+                    .allocate_global_address(BlueprintId::new(&package_address, blueprint_name))?;
+                processor.create_manifest_address_reservation(address_reservation)?;
+                processor.create_manifest_address(address)?;
+
+                InstructionOutput::None
+            }
+        };
 
-                    InstructionOutput::None
-                }
-            };
-            outputs.push(result);
-        }
+        Ok(result)
+    }
+}
+
+/// The execution state of a single intent, which is run as a coroutine.
+struct IntentProcessor {
+    /// `None` for the root intent
+    subintent_hash: Option<Hash>,
+    /// The instructions which are still to be executed
+    instructions: vec::IntoIter<InstructionV2>,
+    worktop: Worktop,
+    processor: TransactionProcessor,
+    /// The indices of the children in the list of all intents
+    children: Vec<usize>,
+    parent: Option<usize>,
+    /// The content of the auth zone, while another intent is running
+    stashed_auth_zone: Option<(Vec<Proof>, BTreeSet<NonFungibleGlobalId>)>,
+}
 
-        worktop.drop(api)?;
+impl IntentProcessor {
+    fn next_instruction(&mut self) -> Option<InstructionV2> {
+        self.instructions.next()
+    }
 
-        Ok(outputs)
+    fn is_finished(&self) -> bool {
+        self.instructions.len() == 0
     }
 }
 
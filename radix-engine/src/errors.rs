@@ -1,6 +1,6 @@
 use crate::blueprints::access_controller::AccessControllerError;
 use crate::blueprints::account::AccountError;
-use crate::blueprints::consensus_manager::{ConsensusManagerError, ValidatorError};
+use crate::blueprints::consensus_manager::{ConsensusManagerError, SchedulerError, ValidatorError};
 use crate::blueprints::package::PackageError;
 use crate::blueprints::pool::multi_resource_pool::MultiResourcePoolError;
 use crate::blueprints::pool::one_resource_pool::OneResourcePoolError;
@@ -56,6 +56,7 @@ pub enum RejectionReason {
     },
     IntentHashPreviouslyCommitted,
     IntentHashPreviouslyCancelled,
+    SubintentHashPreviouslyCommitted(Hash),
 }
 
 impl fmt::Display for RejectionReason {
@@ -209,6 +210,7 @@ pub enum SystemError {
     NotAnAddressReservation,
     NotAnObject,
     NotAKeyValueStore,
+    KeyValueStoreScanLimitIsZero,
     ModulesDontHaveOuterObjects,
     ActorNodeIdDoesNotExist,
     OuterObjectDoesNotExist,
@@ -439,6 +441,8 @@ pub enum ApplicationError {
 
     ValidatorError(ValidatorError),
 
+    SchedulerError(SchedulerError),
+
     FungibleResourceManagerError(FungibleResourceManagerError),
 
     NonFungibleResourceManagerError(NonFungibleResourceManagerError),
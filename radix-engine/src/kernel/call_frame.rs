@@ -1231,6 +1231,59 @@ impl<C, L: Clone> CallFrame<C, L> {
         Ok(keys)
     }
 
+    pub fn scan_substates_after<
+        'f,
+        K: SubstateKeyContent + 'static,
+        S: CommitableSubstateStore,
+        E,
+    >(
+        &mut self,
+        substate_io: &'f mut SubstateIO<S>,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        after: Option<&SubstateKey>,
+        limit: u32,
+        handler: &mut impl CallFrameIOAccessHandler<C, L, E>,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, CallbackError<CallFrameScanKeysError, E>>
+    {
+        // Check node visibility
+        let (_ref_origin, device) = self.get_node_ref(node_id).ok_or_else(|| {
+            CallbackError::Error(CallFrameScanKeysError::NodeNotVisible(node_id.clone()))
+        })?;
+
+        let mut adapter = CallFrameToIOAccessAdapter {
+            call_frame: self,
+            handler,
+            phantom: PhantomData::default(),
+        };
+
+        let substates = substate_io.scan_substates_after::<K, E>(
+            device,
+            node_id,
+            partition_num,
+            after,
+            limit,
+            &mut adapter,
+        )?;
+
+        for (key, substate) in &substates {
+            self.process_output_substate_key(key).map_err(|e| {
+                CallbackError::Error(CallFrameScanKeysError::ProcessSubstateKeyError(e))
+            })?;
+
+            // As with a substate which has been opened and closed again, any owned nodes are
+            // not made visible, but global references are
+            for reference in substate.references() {
+                if reference.is_global() {
+                    self.stable_references
+                        .insert(reference.clone(), StableReferenceType::Global);
+                }
+            }
+        }
+
+        Ok(substates)
+    }
+
     pub fn drain_substates<'f, K: SubstateKeyContent + 'static, S: CommitableSubstateStore, E>(
         &mut self,
         substate_io: &'f mut SubstateIO<S>,
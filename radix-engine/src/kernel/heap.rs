@@ -1,6 +1,8 @@
 use crate::track::interface::IOAccess;
 use crate::track::interface::{CallbackError, CanonicalSubstateKey, NodeSubstates};
 use crate::types::*;
+use radix_engine_store_interface::db_key_mapper::DatabaseKeyMapper;
+use radix_engine_store_interface::interface::DbSortKey;
 
 pub struct Heap {
     nodes: NonIterMap<NodeId, NodeSubstates>,
@@ -180,6 +182,40 @@ impl Heap {
         }
     }
 
+    /// Scans the substates of a node's partition whose keys come strictly after the given key,
+    /// in the order of their database sort keys, so that it matches the order of the node once
+    /// it has been persisted. On an non-existing node/partition, this will return an empty vector
+    pub fn scan_substates_after<M: DatabaseKeyMapper>(
+        &self,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        after: Option<&SubstateKey>,
+        count: u32,
+    ) -> Vec<(SubstateKey, IndexedScryptoValue)> {
+        let node_substates = self.nodes.get(node_id).and_then(|n| n.get(&partition_num));
+        if let Some(substates) = node_substates {
+            let after_sort_key = after.map(M::to_db_sort_key);
+            // The heap is keyed by substate key, so the substates have to be sorted here
+            let mut sorted_substates: Vec<(DbSortKey, &SubstateKey, &IndexedScryptoValue)> =
+                substates
+                    .iter()
+                    .map(|(key, value)| (M::to_db_sort_key(key), key, value))
+                    .filter(|(sort_key, _, _)| match &after_sort_key {
+                        Some(after_sort_key) => sort_key > after_sort_key,
+                        None => true,
+                    })
+                    .collect();
+            sorted_substates.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+            sorted_substates
+                .into_iter()
+                .take(count.try_into().unwrap())
+                .map(|(_sort_key, key, value)| (key.clone(), value.clone()))
+                .collect()
+        } else {
+            vec![]
+        }
+    }
+
     /// Drains the substates from a node's partition. On an non-existing node/partition, this
     /// will return an empty vector
     pub fn drain_substates<E, F: FnMut(&Heap, IOAccess) -> Result<(), E>>(
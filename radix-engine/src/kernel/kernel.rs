@@ -27,7 +27,7 @@ use radix_engine_interface::blueprints::resource::*;
 use radix_engine_store_interface::db_key_mapper::SubstateKeyContent;
 use resources_tracker_macro::trace_resources;
 use sbor::rust::mem;
-use transaction::prelude::PreAllocatedAddress;
+use transaction::prelude::{PreAllocatedAddress, SubintentParams};
 
 /// Organizes the radix engine stack to make a function entrypoint available for execution
 pub struct KernelBoot<'g, M: KernelCallbackObject, S: CommitableSubstateStore> {
@@ -63,6 +63,7 @@ impl<'g, M: KernelCallbackObject, S: CommitableSubstateStore> KernelBoot<'g, M,
         pre_allocated_addresses: &'a Vec<PreAllocatedAddress>,
         references: &'a IndexSet<Reference>,
         blobs: &'a IndexMap<Hash, Vec<u8>>,
+        subintent_params: &'a SubintentParams,
     ) -> Result<Vec<u8>, RuntimeError> {
         #[cfg(feature = "resource_tracker")]
         radix_engine_profiling::QEMU_PLUGIN_CALIBRATOR.with(|v| {
@@ -141,6 +142,7 @@ impl<'g, M: KernelCallbackObject, S: CommitableSubstateStore> KernelBoot<'g, M,
             pre_allocated_addresses,
             references,
             blobs,
+            subintent_params,
         )?;
 
         // Sanity check call frame
@@ -993,6 +995,45 @@ where
         Ok(keys)
     }
 
+    #[trace_resources]
+    fn kernel_scan_substates_after<K: SubstateKeyContent + 'static>(
+        &mut self,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        after: Option<&SubstateKey>,
+        limit: u32,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, RuntimeError> {
+        self.callback.on_scan_keys(ScanKeysEvent::Start)?;
+
+        let mut handler = KernelHandler {
+            callback: self.callback,
+            prev_frame: self.prev_frame_stack.last(),
+            on_io_access: |api, io_access| {
+                api.callback
+                    .on_scan_keys(ScanKeysEvent::IOAccess(&io_access))
+            },
+        };
+
+        let substates = self
+            .current_frame
+            .scan_substates_after::<K, _, _>(
+                &mut self.substate_io,
+                node_id,
+                partition_num,
+                after,
+                limit,
+                &mut handler,
+            )
+            .map_err(|e| match e {
+                CallbackError::Error(e) => RuntimeError::KernelError(KernelError::CallFrameError(
+                    CallFrameError::ScanSubstatesError(e),
+                )),
+                CallbackError::CallbackError(e) => e,
+            })?;
+
+        Ok(substates)
+    }
+
     #[trace_resources(log=limit)]
     fn kernel_drain_substates<K: SubstateKeyContent + 'static>(
         &mut self,
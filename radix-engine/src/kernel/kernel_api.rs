@@ -143,6 +143,16 @@ pub trait KernelSubstateApi<L> {
         count: u32,
     ) -> Result<Vec<SubstateKey>, RuntimeError>;
 
+    /// Scans the substates whose keys come strictly after the given key, in the order of their
+    /// database sort keys, so that a scan can be resumed from the last key it returned
+    fn kernel_scan_substates_after<K: SubstateKeyContent + 'static>(
+        &mut self,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        after: Option<&SubstateKey>,
+        count: u32,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, RuntimeError>;
+
     fn kernel_drain_substates<K: SubstateKeyContent + 'static>(
         &mut self,
         node_id: &NodeId,
@@ -6,7 +6,7 @@ use crate::kernel::substate_io::SubstateDevice;
 use crate::track::interface::{IOAccess, NodeSubstates};
 use crate::types::*;
 use radix_engine_interface::api::field_api::LockFlags;
-use transaction::prelude::PreAllocatedAddress;
+use transaction::prelude::{PreAllocatedAddress, SubintentParams};
 
 pub trait CallFrameReferences {
     fn root() -> Self;
@@ -140,6 +140,7 @@ pub trait KernelCallbackObject: Sized {
         pre_allocated_addresses: &Vec<PreAllocatedAddress>,
         references: &IndexSet<Reference>,
         blobs: &IndexMap<Hash, Vec<u8>>,
+        subintent_params: &SubintentParams,
     ) -> Result<Vec<u8>, RuntimeError>
     where
         Y: KernelApi<Self>;
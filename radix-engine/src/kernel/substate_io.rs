@@ -606,6 +606,40 @@ impl<'g, S: CommitableSubstateStore + 'g> SubstateIO<'g, S> {
         Ok(keys)
     }
 
+    pub fn scan_substates_after<K: SubstateKeyContent + 'static, E>(
+        &mut self,
+        device: SubstateDevice,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        after: Option<&SubstateKey>,
+        count: u32,
+        handler: &mut impl IOAccessHandler<E>,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, CallbackError<CallFrameScanKeysError, E>>
+    {
+        // Both devices order the substates by the store's sort keys, so that a scan returns the
+        // same order whether or not the node has been persisted in the meantime
+        let substates = match device {
+            SubstateDevice::Heap => self.heap.scan_substates_after::<S::DbKeyMapper>(
+                node_id,
+                partition_num,
+                after,
+                count,
+            ),
+            SubstateDevice::Store => self
+                .store
+                .scan_substates_after::<K, E, _>(
+                    node_id,
+                    partition_num,
+                    after,
+                    count,
+                    &mut |io_access| handler.on_io_access(&self.heap, io_access),
+                )
+                .map_err(|e| CallbackError::CallbackError(e))?,
+        };
+
+        Ok(substates)
+    }
+
     pub fn drain_substates<K: SubstateKeyContent + 'static, E>(
         &mut self,
         device: SubstateDevice,
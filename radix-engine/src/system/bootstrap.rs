@@ -1,6 +1,9 @@
 use crate::blueprints::access_controller::*;
 use crate::blueprints::account::{AccountNativePackage, AccountOwnerBadgeData};
-use crate::blueprints::consensus_manager::ConsensusManagerNativePackage;
+use crate::blueprints::consensus_manager::{
+    ConsensusManagerField, ConsensusManagerNativePackage,
+    ConsensusManagerRandomnessBeaconFieldPayload, RandomnessBeaconSubstate,
+};
 use crate::blueprints::identity::{IdentityNativePackage, IdentityOwnerBadgeData};
 use crate::blueprints::package::{
     create_bootstrap_package_partitions, PackageCollection, PackageNativePackage,
@@ -19,15 +22,16 @@ use crate::system::attached_modules::role_assignment::RoleAssignmentNativePackag
 use crate::system::attached_modules::royalty::RoyaltyNativePackage;
 use crate::system::system_callback_api::SystemCallbackObject;
 use crate::system::system_db_reader::SystemDatabaseReader;
+use crate::system::system_substates::FieldSubstate;
 use crate::system::type_info::TypeInfoSubstate;
 use crate::track::{
     BatchPartitionStateUpdate, LegacyStateUpdates, NodeStateUpdates, PartitionStateUpdates,
     StateUpdates,
 };
 use crate::transaction::{
-    execute_transaction, CommitResult, CostingParameters, ExecutionConfig, StateUpdateSummary,
-    SubstateSchemaMapper, SubstateSystemStructures, TransactionOutcome, TransactionReceipt,
-    TransactionResult,
+    execute_transaction, CommitResult, CostingParameters, ExecutionConfig, StagedSubstateDatabase,
+    StateUpdateSummary, SubstateSchemaMapper, SubstateSystemStructures, TransactionOutcome,
+    TransactionReceipt, TransactionResult,
 };
 use lazy_static::lazy_static;
 use radix_engine_common::crypto::Secp256k1PublicKey;
@@ -39,7 +43,8 @@ use radix_engine_interface::api::node_modules::metadata::{MetadataValue, Uncheck
 use radix_engine_interface::api::node_modules::ModuleConfig;
 use radix_engine_interface::blueprints::consensus_manager::{
     ConsensusManagerConfig, ConsensusManagerCreateManifestInput, EpochChangeCondition,
-    CONSENSUS_MANAGER_BLUEPRINT, CONSENSUS_MANAGER_CREATE_IDENT,
+    SchedulerCreateManifestInput, CONSENSUS_MANAGER_BLUEPRINT, CONSENSUS_MANAGER_CREATE_IDENT,
+    SCHEDULER_BLUEPRINT, SCHEDULER_CREATE_IDENT,
 };
 use radix_engine_interface::blueprints::package::*;
 use radix_engine_interface::blueprints::resource::*;
@@ -283,6 +288,20 @@ fn is_noop_partition_state_updates(opt_updates: &Option<PartitionStateUpdates>)
     }
 }
 
+/// An update of a ledger bootstrapped by an older version of the engine, which brings its native
+/// packages and system state up to date. Ledgers bootstrapped by this version of the engine are
+/// already up to date, and applying the updates to them changes nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolUpdate {
+    /// Adds `run_v2` to the transaction processor, which runs transactions with subintents.
+    TransactionProcessorV2,
+    /// Adds the scheduler blueprint to the consensus manager package, and creates the scheduler
+    /// component at its well-known address.
+    Scheduler,
+    /// Adds the randomness beacon to the consensus manager, starting from its initial state.
+    RandomnessBeacon,
+}
+
 pub struct Bootstrapper<'s, S, V>
 where
     S: SubstateDatabase + CommittableSubstateDatabase,
@@ -392,6 +411,49 @@ where
         }
     }
 
+    /// Applies the given protocol update to a bootstrapped database, returning the receipts of the
+    /// flashes and transactions which it consists of.
+    pub fn apply_protocol_update(
+        &mut self,
+        protocol_update: ProtocolUpdate,
+    ) -> Vec<TransactionReceipt> {
+        match protocol_update {
+            ProtocolUpdate::TransactionProcessorV2 => {
+                vec![self.commit_flash(create_native_package_update_flash(&[
+                    TRANSACTION_PROCESSOR_PACKAGE,
+                ]))]
+            }
+            ProtocolUpdate::Scheduler => {
+                let mut receipts = vec![
+                    self.commit_flash(create_consensus_manager_update_flash(self.substate_db))
+                ];
+                let scheduler_type_info = self
+                    .substate_db
+                    .get_mapped::<SpreadPrefixKeyMapper, TypeInfoSubstate>(
+                        SCHEDULER.as_node_id(),
+                        TYPE_INFO_FIELD_PARTITION,
+                        &TypeInfoField::TypeInfo.into(),
+                    );
+                if scheduler_type_info.is_none() {
+                    receipts.push(self.execute_scheduler_creation());
+                }
+                receipts
+            }
+            ProtocolUpdate::RandomnessBeacon => {
+                vec![self.commit_flash(create_consensus_manager_update_flash(self.substate_db))]
+            }
+        }
+    }
+
+    fn commit_flash(&mut self, flash_receipt: FlashReceipt) -> TransactionReceipt {
+        self.substate_db.commit(
+            &flash_receipt
+                .state_updates
+                .create_database_updates::<SpreadPrefixKeyMapper>(),
+        );
+        flash_receipt.into()
+    }
+
     fn execute_system_bootstrap(
         &mut self,
         genesis_epoch: Epoch,
@@ -431,6 +493,31 @@ where
         receipt
     }
 
+    fn execute_scheduler_creation(&mut self) -> TransactionReceipt {
+        let transaction = create_scheduler_creation_transaction();
+
+        let receipt = execute_transaction(
+            self.substate_db,
+            self.vm.clone(),
+            &CostingParameters::default(),
+            &ExecutionConfig::for_system_transaction(self.network_definition.clone())
+                .with_kernel_trace(self.trace),
+            &transaction
+                .prepare()
+                .expect("Expected scheduler creation transaction to be preparable")
+                .get_executable(btreeset![AuthAddresses::system_role()]),
+        );
+
+        let commit_result = receipt.expect_commit(true);
+        self.substate_db.commit(
+            &commit_result
+                .state_updates
+                .create_database_updates::<SpreadPrefixKeyMapper>(),
+        );
+
+        receipt
+    }
+
     fn ingest_genesis_data_chunk(
         &mut self,
         chunk: GenesisDataChunk,
@@ -601,7 +688,118 @@ pub fn create_system_bootstrap_flash(
 }
 
 pub fn create_substate_flash_for_genesis() -> FlashReceipt {
-    let substate_flash = create_system_bootstrap_flash();
+    create_flash_receipt(create_system_bootstrap_flash())
+}
+
+/// Creates the flash which brings the given flashed native packages of a ledger bootstrapped by an
+/// older version of the engine up to date with their current definitions.
+pub fn create_native_package_update_flash(package_addresses: &[PackageAddress]) -> FlashReceipt {
+    let mut substate_flash = create_system_bootstrap_flash();
+    substate_flash.retain(|(node_id, _), _| {
+        package_addresses
+            .iter()
+            .any(|package_address| package_address.as_node_id() == node_id)
+    });
+    let mut flash_receipt = create_flash_receipt(substate_flash);
+    // The packages already exist, and are only updated
+    flash_receipt.state_update_summary.new_packages.clear();
+    flash_receipt
+}
+
+/// Creates the flash which brings the blueprint definitions of the consensus manager package up to
+/// date, together with the state of the consensus manager which the current definitions expect.
+/// Unlike the flashed native packages, this package is published by the system bootstrap
+/// transaction, so only its main partitions are flashed, and its modules are kept.
+pub fn create_consensus_manager_update_flash<S: SubstateDatabase>(substate_db: &S) -> FlashReceipt {
+    let package_structure = PackageNativePackage::validate_and_build_package_structure(
+        ConsensusManagerNativePackage::definition(),
+        VmType::Native,
+        CONSENSUS_MANAGER_CODE_ID.to_be_bytes().to_vec(),
+        Default::default(),
+    )
+    .unwrap_or_else(|err| panic!("Invalid consensus manager package definition: {:?}", err));
+
+    let mut substate_flash = BTreeMap::new();
+    for (partition_num, partition_substates) in
+        create_bootstrap_package_partitions(package_structure, metadata_init!())
+    {
+        if partition_num != SCHEMAS_PARTITION && partition_num < MAIN_BASE_PARTITION {
+            continue;
+        }
+        let mut substates = BTreeMap::new();
+        for (key, value) in partition_substates {
+            substates.insert(key, value.into());
+        }
+        substate_flash.insert(
+            (CONSENSUS_MANAGER_PACKAGE.into_node_id(), partition_num),
+            substates,
+        );
+    }
+
+    // The random seeds and randomness commitments start empty, so only the beacon is initialized
+    let randomness_beacon_key: SubstateKey = ConsensusManagerField::RandomnessBeacon.into();
+    let randomness_beacon = substate_db
+        .get_mapped::<SpreadPrefixKeyMapper, FieldSubstate<ConsensusManagerRandomnessBeaconFieldPayload>>(
+            CONSENSUS_MANAGER.as_node_id(),
+            MAIN_BASE_PARTITION,
+            &randomness_beacon_key,
+        );
+    if randomness_beacon.is_none() {
+        let initial_beacon = FieldSubstate::new_unlocked_field(
+            ConsensusManagerRandomnessBeaconFieldPayload::from_content_source(
+                RandomnessBeaconSubstate::initial(),
+            ),
+        );
+        substate_flash.insert(
+            (CONSENSUS_MANAGER.into_node_id(), MAIN_BASE_PARTITION),
+            btreemap!(randomness_beacon_key => scrypto_encode(&initial_beacon).unwrap()),
+        );
+    }
+
+    let mut flash_receipt = create_flash_receipt_onto(substate_db, substate_flash);
+    // The package and the consensus manager already exist, and are only updated
+    flash_receipt.state_update_summary.new_packages.clear();
+    flash_receipt.state_update_summary.new_components.clear();
+    flash_receipt
+}
+
+fn create_flash_receipt(
+    substate_flash: BTreeMap<(NodeId, PartitionNumber), BTreeMap<SubstateKey, Vec<u8>>>,
+) -> FlashReceipt {
+    let (state_updates, state_update_summary) = create_flash_state_updates(substate_flash);
+    let flashed_db = FlashedSubstateDatabase {
+        flash_updates: state_updates.create_database_updates::<SpreadPrefixKeyMapper>(),
+    };
+    let substate_system_structures = map_substate_system_structures(&flashed_db, &state_updates);
+
+    FlashReceipt {
+        state_updates,
+        state_update_summary,
+        substate_system_structures,
+    }
+}
+
+/// Creates the receipt of a flash which also updates existing nodes, whose system structures are
+/// resolved from the given database with the flash applied on top.
+fn create_flash_receipt_onto<S: SubstateDatabase>(
+    substate_db: &S,
+    substate_flash: BTreeMap<(NodeId, PartitionNumber), BTreeMap<SubstateKey, Vec<u8>>>,
+) -> FlashReceipt {
+    let (state_updates, state_update_summary) = create_flash_state_updates(substate_flash);
+    let mut flashed_db = StagedSubstateDatabase::new(substate_db);
+    flashed_db.commit(&state_updates.create_database_updates::<SpreadPrefixKeyMapper>());
+    let substate_system_structures = map_substate_system_structures(&flashed_db, &state_updates);
+
+    FlashReceipt {
+        state_updates,
+        state_update_summary,
+        substate_system_structures,
+    }
+}
+
+fn create_flash_state_updates(
+    substate_flash: BTreeMap<(NodeId, PartitionNumber), BTreeMap<SubstateKey, Vec<u8>>>,
+) -> (StateUpdates, StateUpdateSummary) {
     let mut system_updates = index_map_new();
     let mut new_packages = index_set_new();
     let mut new_components = index_set_new();
@@ -633,25 +831,24 @@ pub fn create_substate_flash_for_genesis() -> FlashReceipt {
         partition_deletions: index_set_new(),
         system_updates,
     });
-    let flashed_db = FlashedSubstateDatabase {
-        flash_updates: state_updates.create_database_updates::<SpreadPrefixKeyMapper>(),
+    let state_update_summary = StateUpdateSummary {
+        new_packages,
+        new_components,
+        new_resources,
+        new_vaults,
+        vault_balance_changes: index_map_new(),
     };
-    let mut substate_schema_mapper =
-        SubstateSchemaMapper::new(SystemDatabaseReader::new(&flashed_db));
-    substate_schema_mapper.add_for_all_individually_updated(&state_updates);
-    let substate_system_structures = substate_schema_mapper.done();
+    (state_updates, state_update_summary)
+}
 
-    FlashReceipt {
-        state_updates,
-        state_update_summary: StateUpdateSummary {
-            new_packages,
-            new_components,
-            new_resources,
-            new_vaults,
-            vault_balance_changes: index_map_new(),
-        },
-        substate_system_structures,
-    }
+fn map_substate_system_structures<S: SubstateDatabase>(
+    flashed_db: &S,
+    state_updates: &StateUpdates,
+) -> SubstateSystemStructures {
+    let mut substate_schema_mapper =
+        SubstateSchemaMapper::new(SystemDatabaseReader::new(flashed_db));
+    substate_schema_mapper.add_for_all_individually_updated(state_updates);
+    substate_schema_mapper.done()
 }
 
 /// A [`SubstateDatabase`] implementation holding only the initial [`DatabaseUpdates`] from a system
@@ -1241,6 +1438,22 @@ pub fn create_system_bootstrap_transaction(
         });
     }
 
+    // Create Scheduler
+    {
+        pre_allocated_addresses.push((
+            BlueprintId::new(&CONSENSUS_MANAGER_PACKAGE, SCHEDULER_BLUEPRINT),
+            GlobalAddress::from(SCHEDULER),
+        ));
+        instructions.push(InstructionV1::CallFunction {
+            package_address: CONSENSUS_MANAGER_PACKAGE.into(),
+            blueprint_name: SCHEDULER_BLUEPRINT.to_string(),
+            function_name: SCHEDULER_CREATE_IDENT.to_string(),
+            args: to_manifest_value_and_unwrap!(&SchedulerCreateManifestInput {
+                address_reservation: id_allocator.new_address_reservation_id(),
+            }),
+        });
+    }
+
     // Create GenesisHelper
     {
         pre_allocated_addresses.push((
@@ -1418,6 +1631,30 @@ fn map_address_allocations_for_manifest(
     }
 }
 
+/// Creates the scheduler on a ledger bootstrapped before the scheduler was introduced, in the same
+/// way as the system bootstrap transaction does.
+pub fn create_scheduler_creation_transaction() -> SystemTransactionV1 {
+    let mut id_allocator = ManifestIdAllocator::new();
+
+    SystemTransactionV1 {
+        instructions: InstructionsV1(vec![InstructionV1::CallFunction {
+            package_address: CONSENSUS_MANAGER_PACKAGE.into(),
+            blueprint_name: SCHEDULER_BLUEPRINT.to_string(),
+            function_name: SCHEDULER_CREATE_IDENT.to_string(),
+            args: to_manifest_value_and_unwrap!(&SchedulerCreateManifestInput {
+                address_reservation: id_allocator.new_address_reservation_id(),
+            }),
+        }]),
+        pre_allocated_addresses: vec![(
+            BlueprintId::new(&CONSENSUS_MANAGER_PACKAGE, SCHEDULER_BLUEPRINT),
+            GlobalAddress::from(SCHEDULER),
+        )
+            .into()],
+        blobs: BlobsV1 { blobs: vec![] },
+        hash_for_execution: hash(format!("Scheduler Creation")),
+    }
+}
+
 pub fn create_genesis_wrap_up_transaction() -> SystemTransactionV1 {
     let mut instructions = Vec::new();
 
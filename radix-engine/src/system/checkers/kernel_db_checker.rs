@@ -33,9 +33,25 @@ impl KernelDatabaseChecker {
         &mut self,
         substate_db: &S,
     ) -> Result<(), KernelDatabaseCheckError> {
+        self.check_db_with_listener(substate_db, |_| {})
+    }
+
+    /// Same as [`Self::check_db`], but calls the given listener with the number of partitions
+    /// checked so far before checking each partition.
+    pub fn check_db_with_listener<S: SubstateDatabase + ListableSubstateDatabase, L>(
+        &mut self,
+        substate_db: &S,
+        mut listener: L,
+    ) -> Result<(), KernelDatabaseCheckError>
+    where
+        L: FnMut(usize),
+    {
         let mut internal_nodes = BTreeMap::new();
 
-        for db_partition_key in substate_db.list_partition_keys() {
+        for (checked_partition_count, db_partition_key) in
+            substate_db.list_partition_keys().enumerate()
+        {
+            listener(checked_partition_count);
             let (node_id, _) = SpreadPrefixKeyMapper::from_db_partition_key(&db_partition_key);
 
             let state = internal_nodes
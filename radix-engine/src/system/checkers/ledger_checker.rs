@@ -0,0 +1,267 @@
+use super::*;
+use crate::system::system_db_reader::SystemDatabaseReader;
+use radix_engine_interface::prelude::*;
+use radix_engine_store_interface::interface::{ListableSubstateDatabase, SubstateDatabase};
+
+/// The progress of a [`LedgerDatabaseChecker`], as reported to its listener.
+#[derive(Debug, Clone, Copy)]
+pub enum LedgerCheckProgress {
+    /// The kernel checker is going through the partitions of the database.
+    Kernel { partition_count: usize },
+    /// The system checker, and the application checkers with it, are going through the nodes of
+    /// the database.
+    System(SystemDatabaseCheckProgress),
+}
+
+/// A violation of the invariants of the ledger, found by one of the checkers.
+#[derive(Debug)]
+pub enum LedgerCheckViolation {
+    Kernel(KernelDatabaseCheckError),
+    System {
+        node_id: NodeId,
+        error: SystemDatabaseCheckError,
+    },
+    Resource(ResourceDatabaseCheckerError),
+    RoleAssignment(LocatedError<RoleAssignmentDatabaseCheckerError>),
+    PackageRoyalty(LocatedError<PackageRoyaltyDatabaseCheckerError>),
+    ComponentRoyalty(LocatedError<ComponentRoyaltyDatabaseCheckerError>),
+}
+
+#[derive(Debug)]
+pub struct LedgerCheckReport<R> {
+    /// The violations found by all of the checkers except for the user-provided one.
+    pub violations: Vec<LedgerCheckViolation>,
+    /// The results of the system checker, which are absent if it stopped at a violation.
+    pub system_results: Option<SystemDatabaseCheckerResults>,
+    /// The results of the resource checker, without its violations (which are moved to
+    /// [`Self::violations`]). Absent if the system checker stopped at a violation.
+    pub resource_results: Option<ResourceDatabaseCheckerResults>,
+    /// The results of the user-provided application checker. Absent if the system checker
+    /// stopped at a violation.
+    pub application_results: Option<R>,
+}
+
+impl<R> LedgerCheckReport<R> {
+    pub fn is_consistent(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Runs all of the database checkers over a ledger: the [`KernelDatabaseChecker`], and then the
+/// [`SystemDatabaseChecker`] with the application checkers of the native blueprints and an
+/// optional user-provided [`ApplicationChecker`].
+///
+/// The kernel and system checkers stop at their first violation, but a violation found by the
+/// kernel checker does not prevent the system checker from running, and the application checkers
+/// collect all of their violations.
+pub struct LedgerDatabaseChecker<A: ApplicationChecker> {
+    application_checker: A,
+}
+
+impl LedgerDatabaseChecker<()> {
+    pub fn new() -> Self {
+        Self::with_application_checker(())
+    }
+}
+
+impl Default for LedgerDatabaseChecker<()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: ApplicationChecker> LedgerDatabaseChecker<A> {
+    pub fn with_application_checker(application_checker: A) -> Self {
+        Self {
+            application_checker,
+        }
+    }
+
+    pub fn check_db<S: SubstateDatabase + ListableSubstateDatabase, L>(
+        self,
+        substate_db: &S,
+        mut listener: L,
+    ) -> LedgerCheckReport<A::ApplicationCheckerResults>
+    where
+        L: FnMut(LedgerCheckProgress),
+    {
+        let mut violations = Vec::new();
+
+        if let Err(error) = KernelDatabaseChecker::new()
+            .check_db_with_listener(substate_db, |partition_count| {
+                listener(LedgerCheckProgress::Kernel { partition_count })
+            })
+        {
+            violations.push(LedgerCheckViolation::Kernel(error));
+        }
+
+        let reader = SystemDatabaseReader::new(substate_db);
+        let mut checker = SystemDatabaseChecker::new(LedgerApplicationChecker {
+            resource: ResourceDatabaseChecker::default(),
+            role_assignment: RoleAssignmentDatabaseChecker::default(),
+            package_royalty: PackageRoyaltyDatabaseChecker::new(|blueprint_id, func_name| {
+                reader
+                    .get_blueprint_definition(blueprint_id)
+                    .map(|bp_def| bp_def.interface.functions.contains_key(func_name))
+                    .unwrap_or(false)
+            }),
+            component_royalty: ComponentRoyaltyDatabaseChecker::default(),
+            application_checker: self.application_checker,
+        });
+        let results = checker.check_db_with_listener(substate_db, |_, progress| {
+            listener(LedgerCheckProgress::System(*progress))
+        });
+
+        match results {
+            Ok((
+                system_results,
+                (
+                    mut resource_results,
+                    role_assignment_errors,
+                    package_royalty_errors,
+                    component_royalty_errors,
+                    application_results,
+                ),
+            )) => {
+                violations.extend(
+                    resource_results
+                        .errors
+                        .drain(..)
+                        .map(LedgerCheckViolation::Resource),
+                );
+                violations.extend(
+                    role_assignment_errors
+                        .into_iter()
+                        .map(LedgerCheckViolation::RoleAssignment),
+                );
+                violations.extend(
+                    package_royalty_errors
+                        .into_iter()
+                        .map(LedgerCheckViolation::PackageRoyalty),
+                );
+                violations.extend(
+                    component_royalty_errors
+                        .into_iter()
+                        .map(LedgerCheckViolation::ComponentRoyalty),
+                );
+                LedgerCheckReport {
+                    violations,
+                    system_results: Some(system_results),
+                    resource_results: Some(resource_results),
+                    application_results: Some(application_results),
+                }
+            }
+            Err((node_id, error)) => {
+                violations.push(LedgerCheckViolation::System { node_id, error });
+                LedgerCheckReport {
+                    violations,
+                    system_results: None,
+                    resource_results: None,
+                    application_results: None,
+                }
+            }
+        }
+    }
+}
+
+/// The application checkers of the native blueprints, followed by the user-provided one.
+struct LedgerApplicationChecker<F: Fn(&BlueprintId, &str) -> bool, A: ApplicationChecker> {
+    resource: ResourceDatabaseChecker,
+    role_assignment: RoleAssignmentDatabaseChecker,
+    package_royalty: PackageRoyaltyDatabaseChecker<F>,
+    component_royalty: ComponentRoyaltyDatabaseChecker,
+    application_checker: A,
+}
+
+impl<F: Fn(&BlueprintId, &str) -> bool, A: ApplicationChecker> ApplicationChecker
+    for LedgerApplicationChecker<F, A>
+{
+    type ApplicationCheckerResults = (
+        ResourceDatabaseCheckerResults,
+        Vec<LocatedError<RoleAssignmentDatabaseCheckerError>>,
+        Vec<LocatedError<PackageRoyaltyDatabaseCheckerError>>,
+        Vec<LocatedError<ComponentRoyaltyDatabaseCheckerError>>,
+        A::ApplicationCheckerResults,
+    );
+
+    fn on_field(
+        &mut self,
+        info: BlueprintInfo,
+        node_id: NodeId,
+        module_id: ModuleId,
+        field_index: FieldIndex,
+        value: &Vec<u8>,
+    ) {
+        self.resource
+            .on_field(info.clone(), node_id, module_id, field_index, value);
+        self.role_assignment
+            .on_field(info.clone(), node_id, module_id, field_index, value);
+        self.package_royalty
+            .on_field(info.clone(), node_id, module_id, field_index, value);
+        self.component_royalty
+            .on_field(info.clone(), node_id, module_id, field_index, value);
+        self.application_checker
+            .on_field(info, node_id, module_id, field_index, value);
+    }
+
+    fn on_collection_entry(
+        &mut self,
+        info: BlueprintInfo,
+        node_id: NodeId,
+        module_id: ModuleId,
+        collection_index: CollectionIndex,
+        key: &Vec<u8>,
+        value: &Vec<u8>,
+    ) {
+        self.resource.on_collection_entry(
+            info.clone(),
+            node_id,
+            module_id,
+            collection_index,
+            key,
+            value,
+        );
+        self.role_assignment.on_collection_entry(
+            info.clone(),
+            node_id,
+            module_id,
+            collection_index,
+            key,
+            value,
+        );
+        self.package_royalty.on_collection_entry(
+            info.clone(),
+            node_id,
+            module_id,
+            collection_index,
+            key,
+            value,
+        );
+        self.component_royalty.on_collection_entry(
+            info.clone(),
+            node_id,
+            module_id,
+            collection_index,
+            key,
+            value,
+        );
+        self.application_checker.on_collection_entry(
+            info,
+            node_id,
+            module_id,
+            collection_index,
+            key,
+            value,
+        );
+    }
+
+    fn on_finish(&self) -> Self::ApplicationCheckerResults {
+        (
+            self.resource.on_finish(),
+            self.role_assignment.on_finish(),
+            self.package_royalty.on_finish(),
+            self.component_royalty.on_finish(),
+            self.application_checker.on_finish(),
+        )
+    }
+}
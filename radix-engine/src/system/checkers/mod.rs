@@ -1,6 +1,7 @@
 pub mod component_royalty_db_checker;
 pub mod error;
 pub mod kernel_db_checker;
+pub mod ledger_checker;
 pub mod package_royalty_db_checker;
 pub mod resource_db_checker;
 pub mod resource_event_checker;
@@ -12,6 +13,7 @@ pub mod system_event_checker;
 pub use component_royalty_db_checker::*;
 pub use error::*;
 pub use kernel_db_checker::*;
+pub use ledger_checker::*;
 pub use package_royalty_db_checker::*;
 pub use resource_db_checker::*;
 pub use resource_event_checker::*;
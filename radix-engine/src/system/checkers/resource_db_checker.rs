@@ -35,6 +35,9 @@ pub struct ResourceDatabaseChecker {
     resources: BTreeMap<ResourceAddress, ResourceCounter>,
     non_fungible_vaults: BTreeMap<NodeId, ResourceCounter>,
     fungible_vaults: BTreeMap<NodeId, Decimal>,
+    validator_count: Option<usize>,
+    proposal_statistic_count: Option<usize>,
+    errors: Vec<ResourceDatabaseCheckerError>,
 }
 
 #[derive(Debug, Default)]
@@ -42,6 +45,35 @@ pub struct ResourceDatabaseCheckerResults {
     pub num_resources: usize,
     pub total_supply: BTreeMap<ResourceAddress, Decimal>,
     pub vaults: BTreeMap<NodeId, Decimal>,
+    /// The violations of the resource invariants encountered when going through the database.
+    pub errors: Vec<ResourceDatabaseCheckerError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceDatabaseCheckerError {
+    /// A fungible or non-fungible vault has a negative balance.
+    NegativeVaultBalance { vault_id: NodeId, amount: Decimal },
+    /// The amount of a non-fungible vault does not match the number of non-fungibles it contains.
+    NonFungibleVaultAmountMismatch {
+        vault_id: NodeId,
+        amount: Decimal,
+        tracked_amount: Decimal,
+    },
+    /// A non-fungible vault has non-fungibles but no balance field.
+    NonFungibleVaultWithoutAmount(NodeId),
+    /// The total supply of a resource does not match the sum of the balances of its vaults.
+    TotalSupplyMismatch {
+        resource_address: ResourceAddress,
+        total_supply: Decimal,
+        tracked_supply: Decimal,
+    },
+    /// The current validator set is not sorted by stake in descending order.
+    ValidatorSetNotSortedByStake,
+    /// The current proposal statistics do not have one entry per validator of the current set.
+    ValidatorCountMismatch {
+        validator_count: usize,
+        proposal_statistic_count: usize,
+    },
 }
 
 impl ApplicationChecker for ResourceDatabaseChecker {
@@ -85,7 +117,11 @@ impl ApplicationChecker for ResourceDatabaseChecker {
                         let amount = vault_balance.into_latest().amount();
 
                         if amount.is_negative() {
-                            panic!("Found Fungible Vault negative balance");
+                            self.errors
+                                .push(ResourceDatabaseCheckerError::NegativeVaultBalance {
+                                    vault_id: node_id,
+                                    amount,
+                                });
                         }
 
                         let tracker = self.resources.entry(address).or_default();
@@ -130,7 +166,11 @@ impl ApplicationChecker for ResourceDatabaseChecker {
                             self.non_fungible_vaults.entry(node_id).or_default();
 
                         if vault_balance.amount.is_negative() {
-                            panic!("Found Non Fungible Vault negative balance");
+                            self.errors
+                                .push(ResourceDatabaseCheckerError::NegativeVaultBalance {
+                                    vault_id: node_id,
+                                    amount: vault_balance.amount,
+                                });
                         }
 
                         non_fungible_vault_tracker.expected = Some(vault_balance.amount);
@@ -140,37 +180,36 @@ impl ApplicationChecker for ResourceDatabaseChecker {
             }
             CONSENSUS_MANAGER_BLUEPRINT => {
                 let field: ConsensusManagerField = field_index.try_into().unwrap();
-                let mut validator_count = 0;
-                let mut stats_count = 0;
                 match field {
                     ConsensusManagerField::CurrentValidatorSet => {
                         let validator_set: ConsensusManagerCurrentValidatorSetFieldPayload =
                             scrypto_decode(value).unwrap();
 
                         let mut prev = Decimal::MAX;
+                        let mut validator_count = 0;
                         for validator in validator_set
                             .into_latest()
                             .validator_set
                             .validators_by_stake_desc
                         {
                             if validator.1.stake > prev {
-                                panic!("Validator set are not in DESC order");
+                                self.errors.push(
+                                    ResourceDatabaseCheckerError::ValidatorSetNotSortedByStake,
+                                );
                             }
                             prev = validator.1.stake;
                             validator_count += 1;
                         }
+                        self.validator_count = Some(validator_count);
                     }
                     ConsensusManagerField::CurrentProposalStatistic => {
                         let stats: ConsensusManagerCurrentProposalStatisticFieldPayload =
                             scrypto_decode(value).unwrap();
-                        stats_count = stats.into_latest().validator_statistics.len();
+                        self.proposal_statistic_count =
+                            Some(stats.into_latest().validator_statistics.len());
                     }
                     _ => {}
                 }
-
-                if validator_count != stats_count {
-                    panic!("Validator count != proposal statistics count")
-                }
             }
             _ => {}
         }
@@ -208,16 +247,23 @@ impl ApplicationChecker for ResourceDatabaseChecker {
     }
 
     fn on_finish(&self) -> Self::ApplicationCheckerResults {
-        for (address, counter) in &self.non_fungible_vaults {
+        let mut errors = self.errors.clone();
+
+        for (vault_id, counter) in &self.non_fungible_vaults {
             if let Some(expected) = counter.expected {
                 if !expected.eq(&counter.tracking_supply) {
-                    panic!(
-                        "Vault amount mismatch: {:?} index: {:?} tracked_supply: {:?}",
-                        address, expected, counter.tracking_supply,
+                    errors.push(
+                        ResourceDatabaseCheckerError::NonFungibleVaultAmountMismatch {
+                            vault_id: *vault_id,
+                            amount: expected,
+                            tracked_amount: counter.tracking_supply,
+                        },
                     );
                 }
             } else {
-                panic!("Found non fungible vault with no amount index");
+                errors.push(ResourceDatabaseCheckerError::NonFungibleVaultWithoutAmount(
+                    *vault_id,
+                ));
             }
         }
 
@@ -233,20 +279,34 @@ impl ApplicationChecker for ResourceDatabaseChecker {
         for (address, tracker) in &self.resources {
             if let Some(total_supply) = tracker.expected {
                 if !total_supply.eq(&tracker.tracking_supply) {
-                    panic!(
-                        "Total Supply mismatch: {:?} total_supply: {:?} tracked_supply: {:?}",
-                        address, total_supply, tracker.tracking_supply,
-                    );
+                    errors.push(ResourceDatabaseCheckerError::TotalSupplyMismatch {
+                        resource_address: *address,
+                        total_supply,
+                        tracked_supply: tracker.tracking_supply,
+                    });
                 }
             }
 
             total_supply.insert(*address, tracker.tracking_supply);
         }
 
+        // Both are only present once the consensus manager has been found
+        if let (Some(validator_count), Some(proposal_statistic_count)) =
+            (self.validator_count, self.proposal_statistic_count)
+        {
+            if validator_count != proposal_statistic_count {
+                errors.push(ResourceDatabaseCheckerError::ValidatorCountMismatch {
+                    validator_count,
+                    proposal_statistic_count,
+                });
+            }
+        }
+
         ResourceDatabaseCheckerResults {
             num_resources: self.resources.len(),
             total_supply,
             vaults,
+            errors,
         }
     }
 }
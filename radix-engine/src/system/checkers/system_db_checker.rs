@@ -70,6 +70,14 @@ pub struct SystemDatabaseCheckerResults {
     pub substate_count: usize,
 }
 
+/// The progress of a [`SystemDatabaseChecker`], as reported to its listener.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemDatabaseCheckProgress {
+    pub node_count: usize,
+    pub partition_count: usize,
+    pub substate_count: usize,
+}
+
 #[derive(Debug)]
 pub struct SystemPartitionCheckResults {
     pub substate_count: usize,
@@ -207,6 +215,23 @@ impl<A: ApplicationChecker> SystemDatabaseChecker<A> {
         (SystemDatabaseCheckerResults, A::ApplicationCheckerResults),
         SystemDatabaseCheckError,
     > {
+        self.check_db_with_listener(substate_db, |_, _| {})
+            .map_err(|(_, error)| error)
+    }
+
+    /// Same as [`Self::check_db`], but calls the given listener with each node before checking
+    /// it, and returns the node which failed the check together with the error.
+    pub fn check_db_with_listener<S: SubstateDatabase + ListableSubstateDatabase, L>(
+        &mut self,
+        substate_db: &S,
+        mut listener: L,
+    ) -> Result<
+        (SystemDatabaseCheckerResults, A::ApplicationCheckerResults),
+        (NodeId, SystemDatabaseCheckError),
+    >
+    where
+        L: FnMut(&NodeId, &SystemDatabaseCheckProgress),
+    {
         let mut node_counts = NodeCounts::default();
         let mut partition_count = 0usize;
         let mut substate_count = 0usize;
@@ -229,14 +254,25 @@ impl<A: ApplicationChecker> SystemDatabaseChecker<A> {
             let node_checker_state = match new_node {
                 None => {
                     if let Some(last_node_checker_state) = &current_checker_node {
-                        last_node_checker_state
-                            .finish()
-                            .map_err(SystemDatabaseCheckError::NodeError)?;
+                        last_node_checker_state.finish().map_err(|e| {
+                            (
+                                last_node_checker_state.node_id,
+                                SystemDatabaseCheckError::NodeError(e),
+                            )
+                        })?;
                     }
 
+                    listener(
+                        &node_id,
+                        &SystemDatabaseCheckProgress {
+                            node_count: node_counts.node_count,
+                            partition_count,
+                            substate_count,
+                        },
+                    );
                     let new_node_check_state = self
                         .check_node(&reader, &node_id, &mut node_counts)
-                        .map_err(SystemDatabaseCheckError::NodeError)?;
+                        .map_err(|e| (node_id, SystemDatabaseCheckError::NodeError(e)))?;
                     current_checker_node = Some(new_node_check_state);
                     current_checker_node.as_mut().unwrap()
                 }
@@ -252,7 +288,10 @@ impl<A: ApplicationChecker> SystemDatabaseChecker<A> {
                         }
                         SystemNodeType::KeyValueStore {} => NodeInfo::KeyValueStore,
                     };
-                    SystemDatabaseCheckError::PartitionError(node_info, e)
+                    (
+                        node_id,
+                        SystemDatabaseCheckError::PartitionError(node_info, e),
+                    )
                 })?;
 
             substate_count += partition_results.substate_count;
@@ -260,9 +299,12 @@ impl<A: ApplicationChecker> SystemDatabaseChecker<A> {
         }
 
         if let Some(finished_node) = &current_checker_node {
-            finished_node
-                .finish()
-                .map_err(SystemDatabaseCheckError::NodeError)?;
+            finished_node.finish().map_err(|e| {
+                (
+                    finished_node.node_id,
+                    SystemDatabaseCheckError::NodeError(e),
+                )
+            })?;
         }
 
         node_counts.scrypto_global_component_count = self.scrypto_global_component_count;
@@ -34,7 +34,7 @@ use radix_engine_interface::api::key_value_entry_api::{
     ClientKeyValueEntryApi, KeyValueEntryHandle,
 };
 use radix_engine_interface::api::key_value_store_api::{
-    ClientKeyValueStoreApi, KeyValueStoreDataSchema,
+    ClientKeyValueStoreApi, KeyValueStoreDataSchema, KeyValueStoreEntriesPage,
 };
 use radix_engine_interface::api::object_api::ModuleId;
 use radix_engine_interface::api::*;
@@ -605,6 +605,8 @@ where
             flags: event_flags,
         };
 
+        SystemModuleMixer::on_emit_event(self.api, &event);
+
         // Adding the event to the event store
         self.api
             .kernel_get_system()
@@ -1155,8 +1157,8 @@ where
             }
         };
 
-        let value: ScryptoValue =
-            scrypto_decode(&buffer).expect("Should be valid due to payload check");
+        // The payload is copied straight into the substate, without decoding it into a ScryptoValue
+        let value = ScryptoLazyValue::from_valid_payload(&buffer, SCRYPTO_SBOR_V1_MAX_DEPTH);
 
         let substate = IndexedScryptoValue::from_typed(&FieldSubstate::new_unlocked_field(value));
 
@@ -1704,10 +1706,7 @@ where
             }
         }
 
-        let substate =
-            IndexedScryptoValue::from_slice(&buffer).expect("Should be valid due to payload check");
-
-        let value = substate.as_scrypto_value().clone();
+        let value = ScryptoLazyValue::from_valid_payload(&buffer, SCRYPTO_SBOR_V1_MAX_DEPTH);
         let kv_entry = KeyValueEntrySubstate::unlocked_entry(value);
         let indexed = IndexedScryptoValue::from_typed(&kv_entry);
 
@@ -1881,6 +1880,63 @@ where
         let handle = self.key_value_store_open_entry(node_id, key, LockFlags::MUTABLE)?;
         self.key_value_entry_remove_and_close_substate(handle)
     }
+
+    // Costing through kernel
+    #[trace_resources]
+    fn key_value_store_scan_entries(
+        &mut self,
+        node_id: &NodeId,
+        cursor: Option<Vec<u8>>,
+        limit: u32,
+    ) -> Result<KeyValueStoreEntriesPage, RuntimeError> {
+        // A page of zero entries could not tell an incomplete scan from a complete one
+        if limit == 0 {
+            return Err(RuntimeError::SystemError(
+                SystemError::KeyValueStoreScanLimitIsZero,
+            ));
+        }
+        self.api
+            .kernel_get_system()
+            .modules
+            .assert_can_scan_key_value_store(limit)?;
+
+        let type_info = TypeInfoBlueprint::get_type(&node_id, self.api)?;
+        match type_info {
+            TypeInfoSubstate::KeyValueStore(..) => {}
+            _ => return Err(RuntimeError::SystemError(SystemError::NotAKeyValueStore)),
+        }
+
+        let cursor = cursor.map(SubstateKey::Map);
+        let substates = self.api.kernel_scan_substates_after::<MapKey>(
+            node_id,
+            MAIN_BASE_PARTITION,
+            cursor.as_ref(),
+            limit,
+        )?;
+
+        // Entries which have been removed are still present as empty substates, so the
+        // cursor is derived from the scanned keys rather than the returned entries.
+        let next_cursor = if substates.len() == limit as usize {
+            substates
+                .last()
+                .map(|(key, _substate)| key.for_map().unwrap().clone())
+        } else {
+            None
+        };
+
+        let mut entries = Vec::new();
+        for (key, substate) in substates {
+            let entry: KeyValueEntrySubstate<ScryptoValue> = substate.as_typed().unwrap();
+            if let Some(value) = entry.into_value() {
+                entries.push((key.into_map(), scrypto_encode(&value).unwrap()));
+            }
+        }
+
+        Ok(KeyValueStoreEntriesPage {
+            entries,
+            next_cursor,
+        })
+    }
 }
 
 #[cfg_attr(
@@ -1927,7 +1983,7 @@ where
             &buffer,
         )?;
 
-        let value: ScryptoValue = scrypto_decode(&buffer).unwrap();
+        let value = ScryptoLazyValue::from_valid_payload(&buffer, SCRYPTO_SBOR_V1_MAX_DEPTH);
         let index_entry = IndexEntrySubstate::entry(value);
         let value = IndexedScryptoValue::from_typed(&index_entry);
 
@@ -2062,7 +2118,7 @@ where
             &buffer,
         )?;
 
-        let value: ScryptoValue = scrypto_decode(&buffer).unwrap();
+        let value = ScryptoLazyValue::from_valid_payload(&buffer, SCRYPTO_SBOR_V1_MAX_DEPTH);
         let sorted_entry = SortedIndexEntrySubstate::entry(value);
         let value = IndexedScryptoValue::from_typed(&sorted_entry);
 
@@ -3003,6 +3059,17 @@ where
             .kernel_scan_keys::<K>(node_id, partition_num, limit)
     }
 
+    fn kernel_scan_substates_after<K: SubstateKeyContent + 'static>(
+        &mut self,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        after: Option<&SubstateKey>,
+        limit: u32,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, RuntimeError> {
+        self.api
+            .kernel_scan_substates_after::<K>(node_id, partition_num, after, limit)
+    }
+
     fn kernel_drain_substates<K: SubstateKeyContent + 'static>(
         &mut self,
         node_id: &NodeId,
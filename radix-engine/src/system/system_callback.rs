@@ -3,7 +3,10 @@ use crate::blueprints::account::ACCOUNT_CREATE_VIRTUAL_ED25519_ID;
 use crate::blueprints::account::ACCOUNT_CREATE_VIRTUAL_SECP256K1_ID;
 use crate::blueprints::identity::IDENTITY_CREATE_VIRTUAL_ED25519_ID;
 use crate::blueprints::identity::IDENTITY_CREATE_VIRTUAL_SECP256K1_ID;
-use crate::blueprints::transaction_processor::TransactionProcessorRunInputEfficientEncodable;
+use crate::blueprints::transaction_processor::{
+    TransactionProcessorRunInputEfficientEncodable,
+    TransactionProcessorRunV2InputEfficientEncodable,
+};
 use crate::errors::*;
 use crate::kernel::call_frame::CallFrameMessage;
 use crate::kernel::kernel_api::{KernelApi, KernelInvocation};
@@ -32,6 +35,7 @@ use radix_engine_interface::blueprints::identity::IDENTITY_BLUEPRINT;
 use radix_engine_interface::blueprints::package::*;
 use radix_engine_interface::blueprints::transaction_processor::{
     TRANSACTION_PROCESSOR_BLUEPRINT, TRANSACTION_PROCESSOR_RUN_IDENT,
+    TRANSACTION_PROCESSOR_RUN_V2_IDENT,
 };
 use radix_engine_interface::hooks::OnDropInput;
 use radix_engine_interface::hooks::OnDropOutput;
@@ -40,7 +44,7 @@ use radix_engine_interface::hooks::OnMoveOutput;
 use radix_engine_interface::hooks::OnVirtualizeInput;
 use radix_engine_interface::hooks::OnVirtualizeOutput;
 use radix_engine_interface::schema::RefTypes;
-use transaction::model::PreAllocatedAddress;
+use transaction::model::{PreAllocatedAddress, SubintentParams};
 
 #[derive(Clone)]
 pub enum SystemLockData {
@@ -115,6 +119,7 @@ impl<C: SystemCallbackObject> KernelCallbackObject for SystemConfig<C> {
         pre_allocated_addresses: &Vec<PreAllocatedAddress>,
         references: &IndexSet<Reference>,
         blobs: &IndexMap<Hash, Vec<u8>>,
+        subintent_params: &SubintentParams,
     ) -> Result<Vec<u8>, RuntimeError>
     where
         Y: KernelApi<Self>,
@@ -134,18 +139,35 @@ impl<C: SystemCallbackObject> KernelCallbackObject for SystemConfig<C> {
         }
 
         // Call TX processor
-        let rtn = system.call_function(
-            TRANSACTION_PROCESSOR_PACKAGE,
-            TRANSACTION_PROCESSOR_BLUEPRINT,
-            TRANSACTION_PROCESSOR_RUN_IDENT,
-            scrypto_encode(&TransactionProcessorRunInputEfficientEncodable {
-                manifest_encoded_instructions,
-                global_address_reservations,
-                references,
-                blobs,
-            })
-            .unwrap(),
-        )?;
+        // Transactions without subintents are run by `run`, whose input is unchanged since genesis
+        let rtn = if subintent_params.subintents.is_empty() {
+            system.call_function(
+                TRANSACTION_PROCESSOR_PACKAGE,
+                TRANSACTION_PROCESSOR_BLUEPRINT,
+                TRANSACTION_PROCESSOR_RUN_IDENT,
+                scrypto_encode(&TransactionProcessorRunInputEfficientEncodable {
+                    manifest_encoded_instructions,
+                    global_address_reservations,
+                    references,
+                    blobs,
+                })
+                .unwrap(),
+            )?
+        } else {
+            system.call_function(
+                TRANSACTION_PROCESSOR_PACKAGE,
+                TRANSACTION_PROCESSOR_BLUEPRINT,
+                TRANSACTION_PROCESSOR_RUN_V2_IDENT,
+                scrypto_encode(&TransactionProcessorRunV2InputEfficientEncodable {
+                    manifest_encoded_instructions,
+                    global_address_reservations,
+                    references,
+                    blobs,
+                    subintent_params,
+                })
+                .unwrap(),
+            )?
+        };
 
         Ok(rtn)
     }
@@ -1179,4 +1179,59 @@ impl<'a, S: SubstateDatabase + CommittableSubstateDatabase> SystemDatabaseWriter
 
         Ok(())
     }
+
+    pub fn write_typed_object_collection_entry<K: ScryptoEncode, V: ScryptoEncode>(
+        &mut self,
+        node_id: &NodeId,
+        module_id: ModuleId,
+        collection_index: CollectionIndex,
+        key: &K,
+        value: V,
+    ) -> Result<(), SystemReaderError> {
+        let reader = SystemDatabaseReader::new(self.substate_db);
+        let blueprint_id = reader.get_blueprint_id(node_id, module_id)?;
+        let definition = reader.get_blueprint_definition(&blueprint_id)?;
+        let (partition_description, schema) = definition
+            .interface
+            .state
+            .collections
+            .get(collection_index as usize)
+            .ok_or_else(|| SystemReaderError::CollectionDoesNotExist)?;
+        let partition_number = match partition_description {
+            PartitionDescription::Logical(offset) => {
+                let base_partition = match module_id {
+                    ModuleId::Main => MAIN_BASE_PARTITION,
+                    ModuleId::Metadata => METADATA_BASE_PARTITION,
+                    ModuleId::Royalty => ROYALTY_BASE_PARTITION,
+                    ModuleId::RoleAssignment => ROLE_ASSIGNMENT_BASE_PARTITION,
+                };
+                base_partition.at_offset(*offset).unwrap()
+            }
+            PartitionDescription::Physical(partition_number) => *partition_number,
+        };
+
+        // Only key value collections are supported for now
+        if !matches!(schema, BlueprintCollectionSchema::KeyValueStore(..)) {
+            return Err(SystemReaderError::TargetNotSupported);
+        }
+
+        self.substate_db.put_mapped::<SpreadPrefixKeyMapper, _>(
+            node_id,
+            partition_number,
+            &SubstateKey::Map(scrypto_encode(key).unwrap()),
+            &KeyValueEntrySubstate::unlocked_entry(value),
+        );
+
+        Ok(())
+    }
+
+    /// Writes the type info of a node, which is how a new object is created in the database.
+    pub fn write_type_info(&mut self, node_id: &NodeId, type_info: TypeInfoSubstate) {
+        self.substate_db.put_mapped::<SpreadPrefixKeyMapper, _>(
+            node_id,
+            TYPE_INFO_FIELD_PARTITION,
+            &TypeInfoField::TypeInfo.into(),
+            &type_info,
+        );
+    }
 }
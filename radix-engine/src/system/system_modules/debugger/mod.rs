@@ -0,0 +1,5 @@
+mod module;
+mod wrapped_system;
+
+pub use module::*;
+pub use wrapped_system::*;
@@ -0,0 +1,394 @@
+use crate::errors::RuntimeError;
+use crate::kernel::kernel_api::{KernelApi, KernelInternalApi, KernelInvocation};
+use crate::kernel::kernel_callback_api::{
+    CloseSubstateEvent, CreateNodeEvent, DropNodeEvent, OpenSubstateEvent, WriteSubstateEvent,
+};
+use crate::system::actor::Actor;
+use crate::system::module::SystemModule;
+use crate::system::system_callback::SystemConfig;
+use crate::system::system_callback_api::SystemCallbackObject;
+use crate::system::system_modules::execution_trace::{BucketSnapshot, ProofSnapshot};
+use crate::system::system_modules::transaction_runtime::Event;
+use crate::system::type_info::TypeInfoSubstate;
+use crate::types::*;
+use radix_engine_interface::blueprints::resource::{AUTH_ZONE_BLUEPRINT, WORKTOP_BLUEPRINT};
+
+/// A condition on which the [`DebuggerModule`] pauses the transaction and hands control to
+/// the [`TransactionDebugger`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// The invocation of a function or method of a blueprint, or only of the one with the
+    /// given name.
+    Invocation {
+        blueprint_id: BlueprintId,
+        ident: Option<String>,
+    },
+    /// A write to any substate of a node.
+    SubstateWrite(NodeId),
+    /// The emission of any event, or only of the events with the given name.
+    Event(Option<String>),
+}
+
+/// What the transaction was about to do when it was paused.
+#[derive(Debug, Clone)]
+pub enum DebugEvent {
+    Invocation(Actor),
+    SubstateWrite {
+        node_id: NodeId,
+        partition_num: PartitionNumber,
+        substate_key: SubstateKey,
+        value: IndexedScryptoValue,
+    },
+    Event {
+        type_identifier: EventTypeIdentifier,
+        payload: Vec<u8>,
+    },
+}
+
+impl DebugEvent {
+    pub fn matches(&self, breakpoint: &Breakpoint) -> bool {
+        match (self, breakpoint) {
+            (
+                DebugEvent::Invocation(actor),
+                Breakpoint::Invocation {
+                    blueprint_id,
+                    ident,
+                },
+            ) => {
+                let actor_ident = match actor {
+                    Actor::Method(actor) => Some(&actor.ident),
+                    Actor::Function(actor) => Some(&actor.ident),
+                    Actor::BlueprintHook(..) | Actor::Root => None,
+                };
+                actor.blueprint_id().as_ref() == Some(blueprint_id)
+                    && ident
+                        .as_ref()
+                        .map_or(true, |ident| actor_ident == Some(ident))
+            }
+            (DebugEvent::SubstateWrite { node_id, .. }, Breakpoint::SubstateWrite(target)) => {
+                node_id == target
+            }
+            (
+                DebugEvent::Event {
+                    type_identifier, ..
+                },
+                Breakpoint::Event(name),
+            ) => name
+                .as_ref()
+                .map_or(true, |name| &type_identifier.1 == name),
+            _ => false,
+        }
+    }
+}
+
+/// The state of the transaction when it was paused.
+#[derive(Debug, Clone)]
+pub struct DebugContext {
+    /// The actors of the call frames, from the root frame to the current one.
+    pub call_frames: Vec<Actor>,
+    /// The nodes on the heap, with their blueprints if they are objects.
+    pub heap_nodes: Vec<(NodeId, Option<BlueprintId>)>,
+    /// The buckets on the worktop of the transaction processor.
+    pub worktop: Vec<(NodeId, BucketSnapshot)>,
+    /// The proofs in the auth zone of the current call frame.
+    pub auth_zone: Vec<(NodeId, ProofSnapshot)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebuggerAction {
+    /// Resumes the transaction until the next breakpoint.
+    Continue,
+    /// Resumes the transaction until the next invocation, substate write or event.
+    Step,
+}
+
+/// A hook which is handed control whenever the transaction reaches a [`Breakpoint`].
+pub trait TransactionDebugger {
+    fn on_break(&mut self, event: &DebugEvent, context: &DebugContext) -> DebuggerAction;
+}
+
+pub struct DebuggerConfig {
+    pub breakpoints: Vec<Breakpoint>,
+    /// Whether to pause at the first invocation, as if stepping from the start.
+    pub break_on_start: bool,
+    pub debugger: Box<dyn TransactionDebugger>,
+}
+
+impl DebuggerConfig {
+    pub fn new(debugger: Box<dyn TransactionDebugger>) -> Self {
+        Self {
+            breakpoints: Vec::new(),
+            break_on_start: false,
+            debugger,
+        }
+    }
+
+    pub fn with_breakpoint(mut self, breakpoint: Breakpoint) -> Self {
+        self.breakpoints.push(breakpoint);
+        self
+    }
+
+    pub fn with_break_on_start(mut self, enabled: bool) -> Self {
+        self.break_on_start = enabled;
+        self
+    }
+}
+
+/// Pauses the transaction at breakpoints, and keeps track of the state the debugger can
+/// inspect, which isn't otherwise available to system modules.
+#[derive(Default)]
+pub struct DebuggerModule {
+    debugger: Option<Box<dyn TransactionDebugger>>,
+    breakpoints: Vec<Breakpoint>,
+    stepping: bool,
+
+    call_frames: Vec<Actor>,
+    heap_nodes: IndexMap<NodeId, Option<BlueprintId>>,
+    /// The nodes owned by the worktops and auth zones on the heap.
+    containers: IndexMap<NodeId, Vec<NodeId>>,
+    open_substates: IndexMap<SubstateHandle, (NodeId, PartitionNumber, SubstateKey)>,
+    opening_substate: Option<(PartitionNumber, SubstateKey)>,
+}
+
+impl DebuggerModule {
+    pub fn new(config: DebuggerConfig) -> Self {
+        Self {
+            debugger: Some(config.debugger),
+            breakpoints: config.breakpoints,
+            stepping: config.break_on_start,
+            ..Default::default()
+        }
+    }
+
+    fn is_container(blueprint_id: &Option<BlueprintId>) -> bool {
+        match blueprint_id {
+            Some(blueprint_id) => {
+                blueprint_id.package_address == RESOURCE_PACKAGE
+                    && (blueprint_id.blueprint_name == WORKTOP_BLUEPRINT
+                        || blueprint_id.blueprint_name == AUTH_ZONE_BLUEPRINT)
+            }
+            None => false,
+        }
+    }
+
+    fn maybe_break<V: SystemCallbackObject, Y: KernelInternalApi<SystemConfig<V>>>(
+        api: &mut Y,
+        event: DebugEvent,
+    ) {
+        let debugger = &mut api.kernel_get_system().modules.debugger;
+        if debugger.debugger.is_none()
+            || !(debugger.stepping || debugger.breakpoints.iter().any(|b| event.matches(b)))
+        {
+            return;
+        }
+
+        let context = Self::read_context(api);
+        let debugger = &mut api.kernel_get_system().modules.debugger;
+        let action = debugger
+            .debugger
+            .as_mut()
+            .unwrap()
+            .on_break(&event, &context);
+        debugger.stepping = action == DebuggerAction::Step;
+    }
+
+    fn read_context<V: SystemCallbackObject, Y: KernelInternalApi<SystemConfig<V>>>(
+        api: &mut Y,
+    ) -> DebugContext {
+        let debugger = &api.kernel_get_system().modules.debugger;
+        let call_frames = debugger.call_frames.clone();
+        let heap_nodes: Vec<(NodeId, Option<BlueprintId>)> = debugger
+            .heap_nodes
+            .iter()
+            .map(|(node_id, blueprint_id)| (*node_id, blueprint_id.clone()))
+            .collect();
+        let worktop_contents = heap_nodes
+            .iter()
+            .find(|(_, blueprint_id)| {
+                blueprint_id.as_ref().map_or(false, |blueprint_id| {
+                    blueprint_id.package_address == RESOURCE_PACKAGE
+                        && blueprint_id.blueprint_name == WORKTOP_BLUEPRINT
+                })
+            })
+            .and_then(|(node_id, _)| debugger.containers.get(node_id))
+            .cloned()
+            .unwrap_or_default();
+        let auth_zone_contents = call_frames
+            .last()
+            .and_then(|actor| actor.self_auth_zone())
+            .and_then(|node_id| debugger.containers.get(&node_id))
+            .cloned()
+            .unwrap_or_default();
+
+        DebugContext {
+            call_frames,
+            heap_nodes,
+            worktop: worktop_contents
+                .into_iter()
+                .filter_map(|node_id| api.kernel_read_bucket(&node_id).map(|b| (node_id, b)))
+                .collect(),
+            auth_zone: auth_zone_contents
+                .into_iter()
+                .filter_map(|node_id| api.kernel_read_proof(&node_id).map(|p| (node_id, p)))
+                .collect(),
+        }
+    }
+
+    pub fn on_emit_event<V: SystemCallbackObject, Y: KernelApi<SystemConfig<V>>>(
+        api: &mut Y,
+        event: &Event,
+    ) {
+        Self::maybe_break(
+            api,
+            DebugEvent::Event {
+                type_identifier: event.type_identifier.clone(),
+                payload: event.payload.clone(),
+            },
+        );
+    }
+}
+
+impl<V: SystemCallbackObject> SystemModule<SystemConfig<V>> for DebuggerModule {
+    fn on_init<Y: KernelApi<SystemConfig<V>>>(api: &mut Y) -> Result<(), RuntimeError> {
+        api.kernel_get_system()
+            .modules
+            .debugger
+            .call_frames
+            .push(Actor::Root);
+        Ok(())
+    }
+
+    fn before_invoke<Y: KernelApi<SystemConfig<V>>>(
+        api: &mut Y,
+        invocation: &KernelInvocation<Actor>,
+    ) -> Result<(), RuntimeError> {
+        let actor = invocation.call_frame_data.clone();
+        api.kernel_get_system()
+            .modules
+            .debugger
+            .call_frames
+            .push(actor.clone());
+        Self::maybe_break(api, DebugEvent::Invocation(actor));
+        Ok(())
+    }
+
+    fn after_invoke<Y: KernelApi<SystemConfig<V>>>(
+        api: &mut Y,
+        _output: &IndexedScryptoValue,
+    ) -> Result<(), RuntimeError> {
+        api.kernel_get_system().modules.debugger.call_frames.pop();
+        Ok(())
+    }
+
+    fn on_create_node<Y: KernelInternalApi<SystemConfig<V>>>(
+        api: &mut Y,
+        event: &CreateNodeEvent,
+    ) -> Result<(), RuntimeError> {
+        if let CreateNodeEvent::Start(node_id, node_substates) = event {
+            if node_id.is_global() {
+                return Ok(());
+            }
+            let type_info_key: SubstateKey = TypeInfoField::TypeInfo.into();
+            let blueprint_id = node_substates
+                .get(&TYPE_INFO_FIELD_PARTITION)
+                .and_then(|partition| partition.get(&type_info_key))
+                .and_then(|value| value.as_typed::<TypeInfoSubstate>().ok())
+                .and_then(|type_info| match type_info {
+                    TypeInfoSubstate::Object(info) => Some(info.blueprint_info.blueprint_id),
+                    _ => None,
+                });
+
+            let debugger = &mut api.kernel_get_system().modules.debugger;
+            if Self::is_container(&blueprint_id) {
+                let contents = node_substates
+                    .values()
+                    .flat_map(|partition| partition.values())
+                    .flat_map(|value| value.owned_nodes().iter().cloned())
+                    .collect();
+                debugger.containers.insert(**node_id, contents);
+            }
+            debugger.heap_nodes.insert(**node_id, blueprint_id);
+        }
+        Ok(())
+    }
+
+    fn on_drop_node<Y: KernelInternalApi<SystemConfig<V>>>(
+        api: &mut Y,
+        event: &DropNodeEvent,
+    ) -> Result<(), RuntimeError> {
+        if let DropNodeEvent::End(node_id, _) = event {
+            let debugger = &mut api.kernel_get_system().modules.debugger;
+            debugger.heap_nodes.remove(*node_id);
+            debugger.containers.remove(*node_id);
+        }
+        Ok(())
+    }
+
+    fn on_open_substate<Y: KernelInternalApi<SystemConfig<V>>>(
+        api: &mut Y,
+        event: &OpenSubstateEvent,
+    ) -> Result<(), RuntimeError> {
+        let debugger = &mut api.kernel_get_system().modules.debugger;
+        match event {
+            OpenSubstateEvent::Start {
+                partition_num,
+                substate_key,
+                ..
+            } => {
+                debugger.opening_substate = Some((**partition_num, (*substate_key).clone()));
+            }
+            OpenSubstateEvent::End {
+                handle, node_id, ..
+            } => {
+                if let Some((partition_num, substate_key)) = debugger.opening_substate.take() {
+                    debugger
+                        .open_substates
+                        .insert(*handle, (**node_id, partition_num, substate_key));
+                }
+            }
+            OpenSubstateEvent::IOAccess(..) => {}
+        }
+        Ok(())
+    }
+
+    fn on_write_substate<Y: KernelInternalApi<SystemConfig<V>>>(
+        api: &mut Y,
+        event: &WriteSubstateEvent,
+    ) -> Result<(), RuntimeError> {
+        if let WriteSubstateEvent::Start { handle, value } = event {
+            let debugger = &mut api.kernel_get_system().modules.debugger;
+            let Some((node_id, partition_num, substate_key)) =
+                debugger.open_substates.get(handle).cloned()
+            else {
+                return Ok(());
+            };
+            if let Some(contents) = debugger.containers.get_mut(&node_id) {
+                *contents = value.owned_nodes().clone();
+            }
+
+            Self::maybe_break(
+                api,
+                DebugEvent::SubstateWrite {
+                    node_id,
+                    partition_num,
+                    substate_key,
+                    value: (*value).clone(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn on_close_substate<Y: KernelInternalApi<SystemConfig<V>>>(
+        api: &mut Y,
+        event: &CloseSubstateEvent,
+    ) -> Result<(), RuntimeError> {
+        let CloseSubstateEvent::Start(handle) = event;
+        api.kernel_get_system()
+            .modules
+            .debugger
+            .open_substates
+            .remove(handle);
+        Ok(())
+    }
+}
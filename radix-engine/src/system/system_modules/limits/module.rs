@@ -25,6 +25,7 @@ pub enum TransactionLimitsError {
     PanicMessageSizeTooLarge { actual: usize, max: usize },
     TooManyLogs,
     TooManyEvents,
+    KeyValueStoreScanLimitExceeded { actual: u32, max: u32 },
 }
 
 pub struct TransactionLimitsConfig {
@@ -38,6 +39,7 @@ pub struct TransactionLimitsConfig {
     pub max_panic_message_size: usize,
     pub max_number_of_logs: usize,
     pub max_number_of_events: usize,
+    pub max_key_value_store_scan_limit: u32,
 }
 
 /// Tracks and verifies transaction limits during transactino execution,
@@ -1,5 +1,6 @@
 pub mod auth;
 pub mod costing;
+pub mod debugger;
 pub mod execution_trace;
 pub mod kernel_trace;
 pub mod limits;
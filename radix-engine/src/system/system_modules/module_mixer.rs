@@ -20,6 +20,7 @@ use crate::system::system_modules::auth::AuthModule;
 use crate::system::system_modules::costing::CostingModule;
 use crate::system::system_modules::costing::FeeTable;
 use crate::system::system_modules::costing::SystemLoanFeeReserve;
+use crate::system::system_modules::debugger::{DebuggerConfig, DebuggerModule};
 use crate::system::system_modules::execution_trace::ExecutionTraceModule;
 use crate::system::system_modules::kernel_trace::KernelTraceModule;
 use crate::system::system_modules::limits::{LimitsModule, TransactionLimitsConfig};
@@ -48,6 +49,9 @@ bitflags! {
 
         // Execution trace, for preview only
         const EXECUTION_TRACE = 0x01 << 6;
+
+        // Breakpoints, for debugging only
+        const DEBUGGER = 0x01 << 7;
     }
 }
 
@@ -90,6 +94,7 @@ pub struct SystemModuleMixer {
     pub(crate) auth: AuthModule,
     pub(crate) transaction_runtime: TransactionRuntimeModule,
     pub(super) execution_trace: ExecutionTraceModule,
+    pub(super) debugger: DebuggerModule,
 }
 
 // Macro generates default modules dispatches call based on passed function name and arguments.
@@ -116,6 +121,9 @@ macro_rules! internal_call_dispatch {
             if modules.contains(EnabledModules::EXECUTION_TRACE) {
                 ExecutionTraceModule::[< $fn >]($($param, )*)?;
             }
+            if modules.contains(EnabledModules::DEBUGGER) {
+                DebuggerModule::[< $fn >]($($param, )*)?;
+            }
             Ok(())
         }
     }};
@@ -160,11 +168,13 @@ impl SystemModuleMixer {
                 max_invoke_payload_size: execution_config.max_invoke_input_size,
                 max_number_of_logs: execution_config.max_number_of_logs,
                 max_number_of_events: execution_config.max_number_of_events,
+                max_key_value_store_scan_limit: execution_config.max_key_value_store_scan_limit,
                 max_event_size: execution_config.max_event_size,
                 max_log_size: execution_config.max_log_size,
                 max_panic_message_size: execution_config.max_panic_message_size,
             }),
             execution_trace: ExecutionTraceModule::new(execution_config.max_execution_trace_depth),
+            debugger: DebuggerModule::default(),
             transaction_runtime: TransactionRuntimeModule {
                 network_definition,
                 tx_hash,
@@ -197,6 +207,11 @@ impl<V: SystemCallbackObject> SystemModule<SystemConfig<V>> for SystemModuleMixe
     fn on_init<Y: KernelApi<SystemConfig<V>>>(api: &mut Y) -> Result<(), RuntimeError> {
         let modules: EnabledModules = api.kernel_get_system().modules.enabled_modules;
 
+        // Enable debugger
+        if modules.contains(EnabledModules::DEBUGGER) {
+            DebuggerModule::on_init(api)?;
+        }
+
         // Enable execution trace
         if modules.contains(EnabledModules::EXECUTION_TRACE) {
             ExecutionTraceModule::on_init(api)?;
@@ -473,6 +488,27 @@ impl SystemModuleMixer {
         AuthModule::on_call_function_finish(api, auth_zone)
     }
 
+    pub fn on_emit_event<Y, V>(api: &mut Y, event: &Event)
+    where
+        Y: KernelApi<SystemConfig<V>>,
+        V: SystemCallbackObject,
+    {
+        if api
+            .kernel_get_system()
+            .modules
+            .enabled_modules
+            .contains(EnabledModules::DEBUGGER)
+        {
+            DebuggerModule::on_emit_event(api, event);
+        }
+    }
+
+    /// Pauses the transaction at the given breakpoints, to hand control to the debugger.
+    pub fn set_debugger(&mut self, config: DebuggerConfig) {
+        self.enabled_modules.insert(EnabledModules::DEBUGGER);
+        self.debugger = DebuggerModule::new(config);
+    }
+
     pub fn add_log(&mut self, level: Level, message: String) -> Result<(), RuntimeError> {
         if self.enabled_modules.contains(EnabledModules::LIMITS) {
             if self.transaction_runtime.logs.len() >= self.limits.config().max_number_of_logs {
@@ -516,6 +552,24 @@ impl SystemModuleMixer {
         Ok(())
     }
 
+    pub fn assert_can_scan_key_value_store(&mut self, limit: u32) -> Result<(), RuntimeError> {
+        if self.enabled_modules.contains(EnabledModules::LIMITS) {
+            let max = self.limits.config().max_key_value_store_scan_limit;
+            if limit > max {
+                return Err(RuntimeError::SystemModuleError(
+                    SystemModuleError::TransactionLimitsError(
+                        TransactionLimitsError::KeyValueStoreScanLimitExceeded {
+                            actual: limit,
+                            max,
+                        },
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn add_event_unchecked(&mut self, event: Event) -> Result<(), RuntimeError> {
         if self.enabled_modules.contains(EnabledModules::LIMITS) {
             if event.payload.len() > self.limits.config().max_event_size {
@@ -1,7 +1,7 @@
 use crate::errors::RuntimeError;
 use crate::types::*;
 use radix_engine_interface::types::*;
-use radix_engine_store_interface::db_key_mapper::SubstateKeyContent;
+use radix_engine_store_interface::db_key_mapper::{DatabaseKeyMapper, SubstateKeyContent};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CallbackError<E, C> {
@@ -41,6 +41,9 @@ pub enum TrackedSubstateInfo {
 ///
 /// The trait here is for formalizing the interface and intended user flow.
 pub trait CommitableSubstateStore {
+    /// Maps substate keys to the database's sort keys, which define the order of a scan
+    type DbKeyMapper: DatabaseKeyMapper;
+
     /// Marks a substate as transient, or a substate which was never and will never be persisted
     fn mark_as_transient(
         &mut self,
@@ -143,6 +146,27 @@ pub trait CommitableSubstateStore {
         on_io_access: &mut F,
     ) -> Result<Vec<SubstateKey>, E>;
 
+    /// Returns Substates of maximum count for a given partition, whose keys come strictly after
+    /// the given key. Substates are returned in the order of their `DbSortKey`s, so that a scan
+    /// can be resumed from the last key it returned.
+    ///
+    /// Clients must ensure that the SubstateKeyContent which the partition is
+    /// associated with is passed in. The returned SubstateKeys are guaranteed to be of
+    /// this type.
+    /// Otherwise, behavior is undefined.
+    fn scan_substates_after<
+        K: SubstateKeyContent + 'static,
+        E,
+        F: FnMut(IOAccess) -> Result<(), E>,
+    >(
+        &mut self,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        after: Option<&SubstateKey>,
+        count: u32,
+        on_io_access: &mut F,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, E>;
+
     /// Removes substates of maximum count for a given partition.
     ///
     /// Clients must ensure that the SubstateKeyContent which the partition is
@@ -7,14 +7,16 @@ use crate::track::utils::OverlayingResultIterator;
 use crate::types::*;
 use radix_engine_interface::types::*;
 use radix_engine_store_interface::db_key_mapper::SubstateKeyContent;
-use radix_engine_store_interface::interface::DbPartitionKey;
+use radix_engine_store_interface::interface::{DbPartitionKey, DbSubstateKey};
 use radix_engine_store_interface::{
     db_key_mapper::DatabaseKeyMapper,
     interface::{DbSortKey, PartitionEntry, SubstateDatabase},
 };
+use sbor::rust::cell::RefCell;
 use sbor::rust::collections::btree_map::Entry;
 use sbor::rust::iter::empty;
 use sbor::rust::mem;
+use sbor::rust::ops::Bound;
 
 use super::interface::{CanonicalPartition, CanonicalSubstateKey, StoreCommit, StoreCommitInfo};
 
@@ -23,6 +25,16 @@ pub enum TrackFinalizeError {
     TransientSubstateOwnsNode,
 }
 
+/// The database reads of a transaction, ie. the substates the `Track` reported as
+/// [`IOAccess::ReadFromDb`] and [`IOAccess::ReadFromDbNotFound`], plus the partitions which were
+/// listed (so that an insertion into a listed partition is detected as well).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatabaseReads {
+    /// The hash of the value of each substate read, or `None` if the substate didn't exist.
+    pub substates: IndexMap<DbSubstateKey, Option<Hash>>,
+    pub listed_partitions: IndexSet<DbPartitionKey>,
+}
+
 /// Transaction-wide states and side effects
 pub struct Track<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> {
     /// Substate database, use `get_substate_from_db` and `list_entries_from_db` for access
@@ -35,6 +47,9 @@ pub struct Track<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> {
 
     transient_substates: TransientSubstates,
 
+    /// The database reads are recorded into, if enabled
+    database_reads: Option<&'s RefCell<DatabaseReads>>,
+
     phantom_data: PhantomData<M>,
 }
 
@@ -46,21 +61,52 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
             tracked_nodes: index_map_new(),
             deleted_partitions: index_set_new(),
             transient_substates: TransientSubstates::new(),
+            database_reads: None,
             phantom_data: PhantomData::default(),
         }
     }
 
+    /// Records all the database reads into given [`DatabaseReads`].
+    pub fn record_database_reads(&mut self, database_reads: &'s RefCell<DatabaseReads>) {
+        self.database_reads = Some(database_reads);
+    }
+
+    /// Reads a substate like [`CommitableSubstateStore::read_substate`], without recording the
+    /// database read.
+    ///
+    /// This is meant for the substates which are only added to at the end of a transaction (ie.
+    /// the collected fees), so that the transaction does not depend on their previous value.
+    pub fn read_substate_unrecorded(
+        &mut self,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        substate_key: &SubstateKey,
+    ) -> Option<&IndexedScryptoValue> {
+        let database_reads = self.database_reads.take();
+        self.read_substate(node_id, partition_num, substate_key);
+        self.database_reads = database_reads;
+        self.read_substate(node_id, partition_num, substate_key)
+    }
+
     // TODO cleanup interface to avoid redundant information
     fn get_substate_from_db<E, F: FnMut(IOAccess) -> Result<(), E>>(
         substate_db: &'s S,
+        database_reads: Option<&'s RefCell<DatabaseReads>>,
         partition_key: &DbPartitionKey,
         sort_key: &DbSortKey,
         on_io_access: &mut F,
         canonical_substate_key: CanonicalSubstateKey,
     ) -> Result<Option<IndexedScryptoValue>, E> {
-        let result = substate_db
-            .get_substate(partition_key, sort_key)
-            .map(|e| IndexedScryptoValue::from_vec(e).expect("Failed to decode substate"));
+        let value = substate_db.get_substate(partition_key, sort_key);
+        if let Some(database_reads) = database_reads {
+            database_reads
+                .borrow_mut()
+                .substates
+                .entry((partition_key.clone(), sort_key.clone()))
+                .or_insert_with(|| value.as_ref().map(hash));
+        }
+        let result =
+            value.map(|e| IndexedScryptoValue::from_vec(e).expect("Failed to decode substate"));
         if let Some(x) = &result {
             on_io_access(IOAccess::ReadFromDb(canonical_substate_key, x.len()))?;
         } else {
@@ -77,7 +123,9 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
         K: SubstateKeyContent + 'static,
     >(
         substate_db: &'x S,
+        database_reads: Option<&RefCell<DatabaseReads>>,
         partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
         on_io_access: &'x mut F,
         canonical_partition: CanonicalPartition,
     ) -> Box<dyn Iterator<Item = Result<(DbSortKey, (SubstateKey, IndexedScryptoValue)), E>> + 'x>
@@ -135,8 +183,18 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
             }
         }
 
+        if let Some(database_reads) = database_reads {
+            database_reads
+                .borrow_mut()
+                .listed_partitions
+                .insert(partition_key.clone());
+        }
+
         Box::new(TracedIterator {
-            iterator: substate_db.list_entries(partition_key),
+            iterator: match from_sort_key {
+                Some(from_sort_key) => substate_db.list_entries_from(partition_key, from_sort_key),
+                None => substate_db.list_entries(partition_key),
+            },
             on_io_access,
             canonical_partition,
             errored_out: false,
@@ -268,6 +326,7 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
                     let db_partition_key = M::to_db_partition_key(node_id, partition_number);
                     let substate_value = Self::get_substate_from_db(
                         self.substate_db,
+                        self.database_reads,
                         &db_partition_key,
                         &M::to_db_sort_key(&substate_key),
                         on_io_access,
@@ -319,6 +378,8 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> Track<'s, S, M> {
 impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> CommitableSubstateStore
     for Track<'s, S, M>
 {
+    type DbKeyMapper = M;
+
     fn mark_as_transient(
         &mut self,
         node_id: NodeId,
@@ -587,7 +648,9 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> CommitableSubstate
         let db_partition_key = M::to_db_partition_key(node_id, partition_number);
         let mut tracked_iter = IterationCountedIter::new(Self::list_entries_from_db::<E, F, K>(
             self.substate_db,
+            self.database_reads,
             &db_partition_key,
+            None,
             on_io_access,
             CanonicalPartition {
                 node_id: *node_id,
@@ -622,6 +685,90 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> CommitableSubstate
         Ok(items)
     }
 
+    fn scan_substates_after<
+        K: SubstateKeyContent + 'static,
+        E,
+        F: FnMut(IOAccess) -> Result<(), E>,
+    >(
+        &mut self,
+        node_id: &NodeId,
+        partition_number: PartitionNumber,
+        after: Option<&SubstateKey>,
+        limit: u32,
+        on_io_access: &mut F,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, E> {
+        let limit: usize = limit.try_into().unwrap();
+        let after_sort_key = after.map(|substate_key| M::to_db_sort_key(substate_key));
+
+        let node_updates = self.tracked_nodes.get(node_id);
+        let is_new = node_updates
+            .map(|tracked_node| tracked_node.is_new)
+            .unwrap_or(false);
+        let tracked_partition =
+            node_updates.and_then(|n| n.tracked_partitions.get(&partition_number));
+
+        // initialize the "from db" iterator, starting from the cursor
+        let raw_db_entries: Box<
+            dyn Iterator<Item = Result<(DbSortKey, (SubstateKey, IndexedScryptoValue)), E>>,
+        > = if is_new {
+            Box::new(empty()) // optimization: avoid touching the database altogether
+        } else {
+            let partition_key = M::to_db_partition_key(node_id, partition_number);
+            Box::new(Self::list_entries_from_db::<E, F, K>(
+                self.substate_db,
+                self.database_reads,
+                &partition_key,
+                after_sort_key.as_ref(),
+                on_io_access,
+                CanonicalPartition {
+                    node_id: *node_id,
+                    partition_number,
+                },
+            ))
+        };
+        // the database listing is inclusive of the cursor, whereas the scan is exclusive
+        let db_read_entries = raw_db_entries.filter(|result| match (result, &after_sort_key) {
+            (Ok((db_sort_key, _)), Some(after_sort_key)) => db_sort_key != after_sort_key,
+            _ => true,
+        });
+
+        // initialize the "from track" iterator, starting after the cursor
+        let lower_bound = match &after_sort_key {
+            Some(after_sort_key) => Bound::Excluded(after_sort_key),
+            None => Bound::Unbounded,
+        };
+        let tracked_entry_changes = tracked_partition
+            .into_iter()
+            .flat_map(|tracked_partition| {
+                tracked_partition
+                    .substates
+                    .range((lower_bound, Bound::Unbounded))
+            })
+            .map(|(db_sort_key, tracked_substate)| {
+                // TODO: Check that substate is not write locked, before use outside of native blueprints
+                if let Some(value) = tracked_substate.substate_value.get() {
+                    (
+                        db_sort_key.clone(),
+                        Some((tracked_substate.substate_key.clone(), value.clone())),
+                    )
+                } else {
+                    (db_sort_key.clone(), None)
+                }
+            });
+
+        // Unlike `scan_keys`, tracked and database keys are merged into a single ordering by
+        // `DbSortKey`, so that a scan can be resumed from any key it returned
+        let mut items = Vec::new();
+        for result in
+            OverlayingResultIterator::new(db_read_entries, tracked_entry_changes).take(limit)
+        {
+            let (_db_sort_key, (substate_key, substate_value)) = result?;
+            items.push((substate_key, substate_value));
+        }
+
+        Ok(items)
+    }
+
     fn drain_substates<K: SubstateKeyContent + 'static, E, F: FnMut(IOAccess) -> Result<(), E>>(
         &mut self,
         node_id: &NodeId,
@@ -678,7 +825,9 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> CommitableSubstate
             let mut tracked_iter =
                 IterationCountedIter::new(Self::list_entries_from_db::<E, F, K>(
                     self.substate_db,
+                    self.database_reads,
                     &db_partition_key,
+                    None,
                     on_io_access,
                     CanonicalPartition {
                         node_id: *node_id,
@@ -779,7 +928,9 @@ impl<'s, S: SubstateDatabase, M: DatabaseKeyMapper + 'static> CommitableSubstate
             let partition_key = M::to_db_partition_key(node_id, partition_number);
             Box::new(Self::list_entries_from_db::<E, F, SortedKey>(
                 self.substate_db,
+                self.database_reads,
                 &partition_key,
+                None,
                 on_io_access,
                 CanonicalPartition {
                     node_id: *node_id,
@@ -1,11 +1,21 @@
+#[cfg(not(feature = "alloc"))]
+mod parallel_executor;
 mod preview_executor;
+mod scheduled_call_executor;
+mod staged_db;
+mod state_override;
 mod state_update_summary;
 mod system_structure;
 mod transaction_executor;
 mod transaction_receipt;
 mod transaction_reconciler;
 
+#[cfg(not(feature = "alloc"))]
+pub use parallel_executor::*;
 pub use preview_executor::*;
+pub use scheduled_call_executor::*;
+pub(crate) use staged_db::*;
+pub use state_override::*;
 pub use state_update_summary::*;
 pub use system_structure::*;
 pub use transaction_executor::*;
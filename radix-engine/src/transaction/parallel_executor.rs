@@ -0,0 +1,410 @@
+use crate::blueprints::consensus_manager::{
+    ConsensusManagerField, ConsensusManagerStateFieldPayload,
+    ConsensusManagerValidatorRewardsFieldPayload, ValidatorRewardsSubstate,
+};
+use crate::blueprints::models::FieldPayload;
+use crate::blueprints::resource::{
+    FungibleVaultBalanceFieldPayload, FungibleVaultBalanceFieldSubstate, FungibleVaultField,
+};
+use crate::system::system_callback_api::SystemCallbackObject;
+use crate::system::system_substates::FieldSubstate;
+use crate::track::{DatabaseReads, StateUpdates};
+use crate::transaction::*;
+use crate::types::*;
+use radix_engine_interface::blueprints::resource::LiquidFungibleResource;
+use radix_engine_store_interface::db_key_mapper::{
+    DatabaseKeyMapper, MappedSubstateDatabase, SpreadPrefixKeyMapper,
+};
+use radix_engine_store_interface::interface::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use transaction::model::Executable;
+
+/// A receipt of a transaction executed by [`execute_and_commit_in_parallel`].
+pub trait ParallelExecutionReceipt {
+    /// The state updates to commit, if any.
+    fn state_updates_mut(&mut self) -> Option<&mut StateUpdates>;
+
+    /// Where the fees of the transaction went, if any were distributed.
+    fn fee_destination(&self) -> Option<&FeeDestination>;
+}
+
+impl ParallelExecutionReceipt for TransactionReceipt {
+    fn state_updates_mut(&mut self) -> Option<&mut StateUpdates> {
+        match &mut self.result {
+            TransactionResult::Commit(commit) => Some(&mut commit.state_updates),
+            _ => None,
+        }
+    }
+
+    fn fee_destination(&self) -> Option<&FeeDestination> {
+        match &self.result {
+            TransactionResult::Commit(commit) => Some(&commit.fee_destination),
+            _ => None,
+        }
+    }
+}
+
+/// The substates written by the transactions committed so far.
+#[derive(Default)]
+struct DatabaseWrites {
+    substates: IndexSet<DbSubstateKey>,
+    partitions: IndexSet<DbPartitionKey>,
+    reset_partitions: IndexSet<DbPartitionKey>,
+}
+
+impl DatabaseWrites {
+    fn add(&mut self, database_updates: &DatabaseUpdates) {
+        for (partition_key, sort_key) in written_substates(database_updates) {
+            match sort_key {
+                Some(sort_key) => {
+                    self.substates.insert((partition_key.clone(), sort_key));
+                }
+                None => {
+                    self.reset_partitions.insert(partition_key.clone());
+                }
+            }
+            self.partitions.insert(partition_key);
+        }
+    }
+
+    fn contains(&self, substate_key: &DbSubstateKey) -> bool {
+        self.substates.contains(substate_key) || self.reset_partitions.contains(&substate_key.0)
+    }
+
+    /// Checks whether a transaction executed against the state before the batch has to be
+    /// re-executed against the current state.
+    ///
+    /// Reads are checked by value, so that a substate which was rewritten with the same value
+    /// (eg. the transaction tracker) isn't a conflict. A substate written without being read is a
+    /// conflict if it was written before, unless it is one of the fee accumulation substates
+    /// which are re-based instead.
+    fn conflicts_with<S: SubstateDatabase>(
+        &self,
+        substate_db: &S,
+        reads: &DatabaseReads,
+        database_updates: Option<&DatabaseUpdates>,
+        rebased_substates: Option<&FeeAccumulationSubstates>,
+    ) -> bool {
+        let changed_reads = reads.substates.iter().any(|(substate_key, value_hash)| {
+            self.contains(substate_key)
+                && substate_db
+                    .get_substate(&substate_key.0, &substate_key.1)
+                    .map(hash)
+                    != *value_hash
+        });
+        let changed_listings = reads
+            .listed_partitions
+            .iter()
+            .any(|partition_key| self.partitions.contains(partition_key));
+        let overwritten_writes = database_updates
+            .into_iter()
+            .flat_map(written_substates)
+            .filter_map(|(partition_key, sort_key)| sort_key.map(|x| (partition_key, x)))
+            .any(|substate_key| {
+                !reads.substates.contains_key(&substate_key)
+                    && !rebased_substates
+                        .map(|x| x.contains(&substate_key))
+                        .unwrap_or(false)
+                    && self.contains(&substate_key)
+            });
+        changed_reads || changed_listings || overwritten_writes
+    }
+}
+
+/// Lists the substates written by given updates, with `None` sort key for a partition reset.
+fn written_substates(
+    database_updates: &DatabaseUpdates,
+) -> impl Iterator<Item = (DbPartitionKey, Option<DbSortKey>)> + '_ {
+    database_updates
+        .node_updates
+        .iter()
+        .flat_map(|(node_key, node_updates)| {
+            node_updates.partition_updates.iter().flat_map(
+                move |(partition_num, partition_updates)| {
+                    let partition_key = DbPartitionKey {
+                        node_key: node_key.clone(),
+                        partition_num: *partition_num,
+                    };
+                    let sort_keys: Vec<Option<DbSortKey>> = match partition_updates {
+                        PartitionDatabaseUpdates::Delta { substate_updates } => {
+                            substate_updates.keys().cloned().map(Some).collect()
+                        }
+                        PartitionDatabaseUpdates::Reset { .. } => vec![None],
+                    };
+                    sort_keys
+                        .into_iter()
+                        .map(move |sort_key| (partition_key.clone(), sort_key))
+                },
+            )
+        })
+}
+
+/// The substates the fees are added to at the end of every transaction (ie. the validator
+/// rewards and their vault).
+///
+/// Each transaction only adds to them, so a transaction executed against the state before the
+/// batch is re-based onto the fees added by the preceding transactions of the batch, rather than
+/// re-executed.
+struct FeeAccumulationSubstates {
+    validator_rewards: DbSubstateKey,
+    rewards_vault_balance: DbSubstateKey,
+}
+
+impl FeeAccumulationSubstates {
+    fn read<S: SubstateDatabase>(substate_db: &S) -> Option<Self> {
+        let rewards = read_validator_rewards(substate_db)?;
+        Some(Self {
+            validator_rewards: db_substate_key(
+                CONSENSUS_MANAGER.as_node_id(),
+                &ConsensusManagerField::ValidatorRewards.into(),
+            ),
+            rewards_vault_balance: db_substate_key(
+                &rewards.rewards_vault.0 .0,
+                &FungibleVaultField::Balance.into(),
+            ),
+        })
+    }
+
+    fn contains(&self, substate_key: &DbSubstateKey) -> bool {
+        self.validator_rewards == *substate_key || self.rewards_vault_balance == *substate_key
+    }
+
+    /// Whether the transaction's writes to the substates can be re-based, ie. the transaction
+    /// didn't depend on their values other than by adding the fees to them.
+    fn can_rebase(&self, reads: &DatabaseReads) -> bool {
+        !reads.substates.contains_key(&self.validator_rewards)
+            && !reads.substates.contains_key(&self.rewards_vault_balance)
+    }
+
+    /// Adds the fees distributed by a transaction to the current values of the substates, in the
+    /// same way as `TransactionExecutor::finalize_fees`.
+    fn rebase<S: SubstateDatabase>(
+        &self,
+        substate_db: &S,
+        state_updates: &mut StateUpdates,
+        fee_destination: &FeeDestination,
+    ) {
+        let to_proposer = fee_destination.to_proposer;
+        let to_validator_set = fee_destination.to_validator_set;
+        if to_proposer.is_zero() && to_validator_set.is_zero() {
+            return;
+        }
+
+        let current_leader = substate_db
+            .get_mapped::<SpreadPrefixKeyMapper, FieldSubstate<ConsensusManagerStateFieldPayload>>(
+                CONSENSUS_MANAGER.as_node_id(),
+                MAIN_BASE_PARTITION,
+                &ConsensusManagerField::State.into(),
+            )
+            .unwrap()
+            .into_payload()
+            .into_latest()
+            .current_leader;
+        let mut rewards = read_validator_rewards(substate_db).unwrap();
+        if let Some(current_leader) = current_leader {
+            let entry = rewards.proposer_rewards.entry(current_leader).or_default();
+            *entry = entry.checked_add(to_proposer).unwrap()
+        }
+        let vault_node_id = rewards.rewards_vault.0 .0;
+        let mut vault_balance = substate_db
+            .get_mapped::<SpreadPrefixKeyMapper, FungibleVaultBalanceFieldSubstate>(
+                &vault_node_id,
+                MAIN_BASE_PARTITION,
+                &FungibleVaultField::Balance.into(),
+            )
+            .unwrap()
+            .into_payload()
+            .into_latest();
+        vault_balance.put(LiquidFungibleResource::new(
+            to_proposer.checked_add(to_validator_set).unwrap(),
+        ));
+
+        state_updates
+            .of_node(CONSENSUS_MANAGER.into_node_id())
+            .of_partition(MAIN_BASE_PARTITION)
+            .update_substates([(
+                ConsensusManagerField::ValidatorRewards.into(),
+                DatabaseUpdate::Set(
+                    scrypto_encode(&FieldSubstate::new_unlocked_field(
+                        ConsensusManagerValidatorRewardsFieldPayload::from_content_source(rewards),
+                    ))
+                    .unwrap(),
+                ),
+            )]);
+        state_updates
+            .of_node(vault_node_id)
+            .of_partition(MAIN_BASE_PARTITION)
+            .update_substates([(
+                FungibleVaultField::Balance.into(),
+                DatabaseUpdate::Set(
+                    scrypto_encode(
+                        &FungibleVaultBalanceFieldPayload::from_content_source(vault_balance)
+                            .into_unlocked_substate(),
+                    )
+                    .unwrap(),
+                ),
+            )]);
+    }
+}
+
+fn read_validator_rewards<S: SubstateDatabase>(
+    substate_db: &S,
+) -> Option<ValidatorRewardsSubstate> {
+    substate_db
+        .get_mapped::<SpreadPrefixKeyMapper, FieldSubstate<ConsensusManagerValidatorRewardsFieldPayload>>(
+            CONSENSUS_MANAGER.as_node_id(),
+            MAIN_BASE_PARTITION,
+            &ConsensusManagerField::ValidatorRewards.into(),
+        )
+        .map(|substate| substate.into_payload().into_latest())
+}
+
+fn db_substate_key(node_id: &NodeId, substate_key: &SubstateKey) -> DbSubstateKey {
+    (
+        SpreadPrefixKeyMapper::to_db_partition_key(node_id, MAIN_BASE_PARTITION),
+        SpreadPrefixKeyMapper::to_db_sort_key(substate_key),
+    )
+}
+
+/// Executes and commits a batch of transactions, using up to `num_threads` threads.
+///
+/// All transactions are first executed speculatively in parallel, against the state before the
+/// batch. They are then committed in order: a transaction which depends on a substate changed by
+/// a preceding transaction of the batch (see [`DatabaseReads`]) is re-executed against the
+/// up-to-date state, while the fees it added to the validator rewards are re-based onto the
+/// current rewards. As execution depends on the database reads only, the receipts and the
+/// committed [`DatabaseUpdates`] are the same as if the transactions were executed and committed
+/// one by one.
+///
+/// The `execute` function returns the receipt along with the database reads of the transaction,
+/// eg. by [`execute_transaction_recording_database_reads`].
+pub fn execute_and_commit_in_parallel<S, T, R, F>(
+    substate_db: &mut S,
+    transactions: &[T],
+    num_threads: usize,
+    execute: F,
+) -> Vec<R>
+where
+    S: SubstateDatabase + CommittableSubstateDatabase + Sync,
+    T: Sync,
+    R: ParallelExecutionReceipt + Send,
+    F: Fn(&S, &T) -> (R, DatabaseReads) + Sync,
+{
+    let commit = |substate_db: &mut S, receipt: &mut R| {
+        receipt.state_updates_mut().map(|state_updates| {
+            let database_updates = state_updates.create_database_updates::<SpreadPrefixKeyMapper>();
+            substate_db.commit(&database_updates);
+            database_updates
+        })
+    };
+
+    let num_threads = num_threads.min(transactions.len());
+    if num_threads <= 1 {
+        return transactions
+            .iter()
+            .map(|transaction| {
+                let (mut receipt, _) = execute(&*substate_db, transaction);
+                commit(substate_db, &mut receipt);
+                receipt
+            })
+            .collect();
+    }
+
+    let mut speculative_results: Vec<Option<(R, DatabaseReads)>> =
+        transactions.iter().map(|_| None).collect();
+    {
+        let snapshot: &S = substate_db;
+        let next_index = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..num_threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        loop {
+                            let index = next_index.fetch_add(1, Ordering::Relaxed);
+                            match transactions.get(index) {
+                                Some(transaction) => {
+                                    results.push((index, execute(snapshot, transaction)))
+                                }
+                                None => break results,
+                            }
+                        }
+                    })
+                })
+                .collect();
+            for handle in handles {
+                let results = handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                for (index, result) in results {
+                    speculative_results[index] = Some(result);
+                }
+            }
+        });
+    }
+
+    let fee_accumulation_substates = FeeAccumulationSubstates::read(&*substate_db);
+    let mut writes = DatabaseWrites::default();
+    let mut receipts = Vec::with_capacity(transactions.len());
+    for (transaction, speculative_result) in transactions.iter().zip(speculative_results) {
+        let (mut receipt, reads) = speculative_result.expect("All transactions have been executed");
+        let rebased_substates = fee_accumulation_substates
+            .as_ref()
+            .filter(|substates| substates.can_rebase(&reads));
+        let speculative_updates = receipt
+            .state_updates_mut()
+            .map(|state_updates| state_updates.create_database_updates::<SpreadPrefixKeyMapper>());
+        if writes.conflicts_with(
+            &*substate_db,
+            &reads,
+            speculative_updates.as_ref(),
+            rebased_substates,
+        ) {
+            receipt = execute(&*substate_db, transaction).0;
+        } else if let Some(rebased_substates) = rebased_substates {
+            let fee_destination = receipt.fee_destination().cloned();
+            if let (Some(state_updates), Some(fee_destination)) =
+                (receipt.state_updates_mut(), fee_destination)
+            {
+                rebased_substates.rebase(&*substate_db, state_updates, &fee_destination);
+            }
+        }
+        if let Some(database_updates) = commit(substate_db, &mut receipt) {
+            writes.add(&database_updates);
+        }
+        receipts.push(receipt);
+    }
+    receipts
+}
+
+/// Executes and commits a batch of transactions in parallel, with the same results as calling
+/// [`execute_and_commit_transaction`] for each of them in order.
+///
+/// See [`execute_and_commit_in_parallel`] for details.
+pub fn execute_and_commit_transactions_in_parallel<
+    S: SubstateDatabase + CommittableSubstateDatabase + Sync,
+    V: SystemCallbackObject + Clone + Sync,
+>(
+    substate_db: &mut S,
+    vm: V,
+    costing_parameters: &CostingParameters,
+    execution_config: &ExecutionConfig,
+    transactions: &[Executable],
+    num_threads: usize,
+) -> Vec<TransactionReceipt> {
+    execute_and_commit_in_parallel(
+        substate_db,
+        transactions,
+        num_threads,
+        |substate_db, transaction| {
+            execute_transaction_recording_database_reads(
+                substate_db,
+                vm.clone(),
+                costing_parameters,
+                execution_config,
+                transaction,
+            )
+        },
+    )
+}
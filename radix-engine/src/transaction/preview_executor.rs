@@ -11,6 +11,7 @@ use transaction::validation::ValidationConfig;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PreviewError {
     TransactionValidationError(TransactionValidationError),
+    StateOverrideError(StateOverrideError),
 }
 
 pub fn execute_preview<S: SubstateDatabase, V: SystemCallbackObject + Clone>(
@@ -36,3 +37,43 @@ pub fn execute_preview<S: SubstateDatabase, V: SystemCallbackObject + Clone>(
         &validated.get_executable(),
     ))
 }
+
+/// Previews a transaction as if the given changes had been made to the ledger state.
+///
+/// The overrides only live in memory for the duration of the preview, and are returned alongside
+/// the receipt so that its results can't be mistaken for those of a regular preview.
+pub fn execute_preview_with_state_overrides<
+    S: SubstateDatabase,
+    V: SystemCallbackObject + Clone,
+>(
+    substate_db: &S,
+    vm: V,
+    network: &NetworkDefinition,
+    preview_intent: PreviewIntentV1,
+    state_overrides: Vec<StateOverride>,
+    with_kernel_trace: bool,
+) -> Result<StateOverridePreviewReceipt, PreviewError> {
+    let validation_config = ValidationConfig::default(network.id);
+
+    let validator = NotarizedTransactionValidator::new(validation_config);
+
+    let validated = validator
+        .validate_preview_intent_v1(preview_intent)
+        .map_err(PreviewError::TransactionValidationError)?;
+
+    let mut overridden_db = StagedSubstateDatabase::new(substate_db);
+    apply_state_overrides(&mut overridden_db, &state_overrides)
+        .map_err(PreviewError::StateOverrideError)?;
+
+    let receipt = execute_transaction(
+        &overridden_db,
+        vm,
+        &CostingParameters::default(),
+        &ExecutionConfig::for_preview(network.clone()).with_kernel_trace(with_kernel_trace),
+        &validated.get_executable(),
+    );
+    Ok(StateOverridePreviewReceipt {
+        state_overrides,
+        receipt,
+    })
+}
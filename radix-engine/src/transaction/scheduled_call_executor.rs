@@ -0,0 +1,171 @@
+use crate::blueprints::consensus_manager::ScheduledCallDueEvent;
+use crate::internal_prelude::*;
+use crate::system::system_callback_api::SystemCallbackObject;
+use crate::transaction::{
+    execute_transaction, CommitResult, CostingParameters, ExecutionConfig, TransactionReceipt,
+    TransactionResult,
+};
+use radix_engine_interface::api::node_modules::auth::AuthAddresses;
+use radix_engine_interface::api::ModuleId;
+use radix_engine_interface::blueprints::consensus_manager::{
+    ScheduledCallId, SchedulerExecuteCallInput, SchedulerRecordFailedCallInput,
+    SCHEDULER_EXECUTE_CALL_IDENT, SCHEDULER_RECORD_FAILED_CALL_IDENT,
+};
+use radix_engine_interface::prelude::Emitter;
+use radix_engine_interface::traits::ScryptoEvent;
+use radix_engine_interface::types::EventTypeIdentifier;
+use radix_engine_store_interface::{
+    db_key_mapper::SpreadPrefixKeyMapper,
+    interface::{CommittableSubstateDatabase, SubstateDatabase},
+};
+use transaction::model::{
+    BlobsV1, InstructionV1, InstructionsV1, SystemTransactionV1, TransactionPayload,
+};
+
+/// Returns the scheduled calls made due by a committed round change, in the order in which the
+/// protocol executes them.
+pub fn due_scheduled_calls(round_change: &CommitResult) -> Vec<ScheduledCallId> {
+    if !round_change.outcome.is_success() {
+        return vec![];
+    }
+    round_change
+        .application_events
+        .iter()
+        .filter(|(EventTypeIdentifier(emitter, event_name), _)| {
+            *emitter == Emitter::Method(*SCHEDULER.as_node_id(), ModuleId::Main)
+                && event_name == ScheduledCallDueEvent::EVENT_NAME
+        })
+        .map(|(_, data)| {
+            scrypto_decode::<ScheduledCallDueEvent>(data)
+                .expect("Scheduler emitted an undecodable due event")
+                .id
+        })
+        .collect()
+}
+
+/// Creates the transaction which executes a due scheduled call, paid for by the call's fee.
+pub fn create_scheduled_call_transaction(id: ScheduledCallId) -> SystemTransactionV1 {
+    SystemTransactionV1 {
+        instructions: InstructionsV1(vec![InstructionV1::CallMethod {
+            address: SCHEDULER.into(),
+            method_name: SCHEDULER_EXECUTE_CALL_IDENT.to_string(),
+            args: to_manifest_value_and_unwrap!(&SchedulerExecuteCallInput { id }),
+        }]),
+        pre_allocated_addresses: vec![],
+        blobs: BlobsV1 { blobs: vec![] },
+        // Each call is made due and executed only once, so its id makes the hash unique
+        hash_for_execution: hash(format!("Scheduled Call: {}", id.0)),
+    }
+}
+
+/// Creates the transaction which records that the execution of a due scheduled call was committed
+/// as a failure.
+pub fn create_failed_scheduled_call_transaction(id: ScheduledCallId) -> SystemTransactionV1 {
+    SystemTransactionV1 {
+        instructions: InstructionsV1(vec![InstructionV1::CallMethod {
+            address: SCHEDULER.into(),
+            method_name: SCHEDULER_RECORD_FAILED_CALL_IDENT.to_string(),
+            args: to_manifest_value_and_unwrap!(&SchedulerRecordFailedCallInput { id }),
+        }]),
+        pre_allocated_addresses: vec![],
+        blobs: BlobsV1 { blobs: vec![] },
+        hash_for_execution: hash(format!("Failed Scheduled Call: {}", id.0)),
+    }
+}
+
+/// Executes the scheduled calls made due by a round change, as the protocol does right after
+/// committing the round change.
+///
+/// Each call is executed in its own transaction, with its own fee and cost budget, so that a call
+/// can neither fail the round change nor any other call. A call whose transaction is committed as
+/// a failure is then recorded as failed, while a call whose transaction is rejected is left to
+/// expire on the next round change, which keeps its fee refundable.
+pub struct ScheduledCallExecutor<'s, S, V>
+where
+    S: SubstateDatabase + CommittableSubstateDatabase,
+    V: SystemCallbackObject + Clone,
+{
+    network_definition: NetworkDefinition,
+    substate_db: &'s mut S,
+    vm: V,
+    trace: bool,
+}
+
+impl<'s, S, V> ScheduledCallExecutor<'s, S, V>
+where
+    S: SubstateDatabase + CommittableSubstateDatabase,
+    V: SystemCallbackObject + Clone,
+{
+    pub fn new(
+        network_definition: NetworkDefinition,
+        substate_db: &'s mut S,
+        vm: V,
+        trace: bool,
+    ) -> ScheduledCallExecutor<'s, S, V> {
+        ScheduledCallExecutor {
+            network_definition,
+            substate_db,
+            vm,
+            trace,
+        }
+    }
+
+    /// Executes and commits the calls made due by the given round change, returning the receipts
+    /// of their transactions.
+    pub fn execute_due_calls(&mut self, round_change: &CommitResult) -> Vec<TransactionReceipt> {
+        due_scheduled_calls(round_change)
+            .into_iter()
+            .map(|id| self.execute_call(id))
+            .collect()
+    }
+
+    /// Executes and commits a due call, recording it as failed if its transaction is committed as
+    /// a failure.
+    fn execute_call(&mut self, id: ScheduledCallId) -> TransactionReceipt {
+        let receipt = execute_transaction(
+            self.substate_db,
+            self.vm.clone(),
+            &CostingParameters::default(),
+            &ExecutionConfig::for_scheduled_call_transaction(self.network_definition.clone())
+                .with_kernel_trace(self.trace),
+            &create_scheduled_call_transaction(id)
+                .prepare()
+                .expect("Expected scheduled call transaction to be preparable")
+                .get_executable(btreeset![AuthAddresses::validator_role()]),
+        );
+
+        if let TransactionResult::Commit(commit) = &receipt.result {
+            self.commit(commit);
+            if !commit.outcome.is_success() {
+                self.record_failed_call(id);
+            }
+        }
+
+        receipt
+    }
+
+    fn record_failed_call(&mut self, id: ScheduledCallId) {
+        let receipt = execute_transaction(
+            self.substate_db,
+            self.vm.clone(),
+            &CostingParameters::default(),
+            &ExecutionConfig::for_system_transaction(self.network_definition.clone())
+                .with_kernel_trace(self.trace),
+            &create_failed_scheduled_call_transaction(id)
+                .prepare()
+                .expect("Expected failed scheduled call transaction to be preparable")
+                .get_executable(btreeset![AuthAddresses::validator_role()]),
+        );
+
+        let commit_result = receipt.expect_commit(true);
+        self.commit(commit_result);
+    }
+
+    fn commit(&mut self, commit_result: &CommitResult) {
+        self.substate_db.commit(
+            &commit_result
+                .state_updates
+                .create_database_updates::<SpreadPrefixKeyMapper>(),
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use radix_engine_store_interface::interface::*;
+use sbor::rust::prelude::*;
+
+/// A [`SubstateDatabase`] which stages committed changes in memory on top of a base database,
+/// leaving the base database itself untouched.
+///
+/// Only meant for short-lived changes, such as previews with state overrides: listing a partition
+/// with staged changes collects all its entries.
+pub(crate) struct StagedSubstateDatabase<'s, S> {
+    base: &'s S,
+    staged: BTreeMap<DbPartitionKey, StagedPartition>,
+}
+
+#[derive(Default)]
+struct StagedPartition {
+    /// Whether all the substates of the partition in the base database are deleted.
+    reset: bool,
+    /// The changed substates, where `None` stands for a deleted substate.
+    substates: BTreeMap<DbSortKey, Option<DbSubstateValue>>,
+}
+
+impl<'s, S: SubstateDatabase> StagedSubstateDatabase<'s, S> {
+    pub fn new(base: &'s S) -> Self {
+        Self {
+            base,
+            staged: BTreeMap::new(),
+        }
+    }
+
+    fn staged_entries(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: Option<&DbSortKey>,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        let base_entries = || match from_sort_key {
+            Some(from_sort_key) => self.base.list_entries_from(partition_key, from_sort_key),
+            None => self.base.list_entries(partition_key),
+        };
+        let Some(partition) = self.staged.get(partition_key) else {
+            return base_entries();
+        };
+        let mut entries: BTreeMap<DbSortKey, DbSubstateValue> = if partition.reset {
+            BTreeMap::new()
+        } else {
+            base_entries().collect()
+        };
+        for (sort_key, value) in &partition.substates {
+            if from_sort_key.map_or(false, |from_sort_key| sort_key < from_sort_key) {
+                continue;
+            }
+            match value {
+                Some(value) => entries.insert(sort_key.clone(), value.clone()),
+                None => entries.remove(sort_key),
+            };
+        }
+        Box::new(entries.into_iter())
+    }
+}
+
+impl<'s, S: SubstateDatabase> SubstateDatabase for StagedSubstateDatabase<'s, S> {
+    fn get_substate(
+        &self,
+        partition_key: &DbPartitionKey,
+        sort_key: &DbSortKey,
+    ) -> Option<DbSubstateValue> {
+        if let Some(partition) = self.staged.get(partition_key) {
+            if let Some(value) = partition.substates.get(sort_key) {
+                return value.clone();
+            }
+            if partition.reset {
+                return None;
+            }
+        }
+        self.base.get_substate(partition_key, sort_key)
+    }
+
+    fn list_entries(
+        &self,
+        partition_key: &DbPartitionKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.staged_entries(partition_key, None)
+    }
+
+    fn list_entries_from(
+        &self,
+        partition_key: &DbPartitionKey,
+        from_sort_key: &DbSortKey,
+    ) -> Box<dyn Iterator<Item = PartitionEntry> + '_> {
+        self.staged_entries(partition_key, Some(from_sort_key))
+    }
+}
+
+impl<'s, S> CommittableSubstateDatabase for StagedSubstateDatabase<'s, S> {
+    fn commit(&mut self, database_updates: &DatabaseUpdates) {
+        for (node_key, node_updates) in &database_updates.node_updates {
+            for (partition_num, partition_updates) in &node_updates.partition_updates {
+                let partition = self
+                    .staged
+                    .entry(DbPartitionKey {
+                        node_key: node_key.clone(),
+                        partition_num: *partition_num,
+                    })
+                    .or_default();
+                match partition_updates {
+                    PartitionDatabaseUpdates::Delta { substate_updates } => {
+                        for (sort_key, update) in substate_updates {
+                            let value = match update {
+                                DatabaseUpdate::Set(value) => Some(value.clone()),
+                                DatabaseUpdate::Delete => None,
+                            };
+                            partition.substates.insert(sort_key.clone(), value);
+                        }
+                    }
+                    PartitionDatabaseUpdates::Reset {
+                        new_substate_values,
+                    } => {
+                        partition.reset = true;
+                        partition.substates = new_substate_values
+                            .iter()
+                            .map(|(sort_key, value)| (sort_key.clone(), Some(value.clone())))
+                            .collect();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::blueprints::account::{AccountCollection, AccountResourceVaultEntryPayload};
+use crate::blueprints::consensus_manager::*;
+use crate::blueprints::resource::*;
+use crate::internal_prelude::*;
+use crate::system::system_db_reader::{
+    ObjectCollectionKey, SystemDatabaseReader, SystemDatabaseWriter, SystemReaderError,
+};
+use crate::system::type_info::TypeInfoSubstate;
+use radix_engine_interface::api::ModuleId;
+use radix_engine_interface::blueprints::package::{BlueprintPayloadIdentifier, BlueprintVersion};
+use radix_engine_interface::blueprints::resource::*;
+use radix_engine_interface::types::*;
+use radix_engine_store_interface::interface::*;
+use sbor::rust::prelude::*;
+
+/// A change of the ledger state which a preview should pretend has happened.
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub enum StateOverride {
+    /// Pretends that the account holds the given amount of a fungible resource, creating the
+    /// vault if the account has none. The total supply of the resource is left unchanged.
+    FungibleBalance {
+        account: ComponentAddress,
+        resource_address: ResourceAddress,
+        amount: Decimal,
+    },
+    /// Pretends that the clock of the consensus manager is at the given time.
+    CurrentTime { proposer_timestamp_ms: i64 },
+    /// Pretends that a field of an object holds the given value, which must match the schema
+    /// of the field.
+    ObjectField {
+        node_id: NodeId,
+        module_id: ModuleId,
+        field_index: FieldIndex,
+        value: ScryptoValue,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateOverrideError {
+    SystemReaderError(SystemReaderError),
+    NotAnAccount(ComponentAddress),
+    NotAFungibleResource(ResourceAddress),
+    InvalidAmount(Decimal),
+    InvalidTimestamp(i64),
+    InvalidFieldValue {
+        node_id: NodeId,
+        module_id: ModuleId,
+        field_index: FieldIndex,
+        error: String,
+    },
+}
+
+impl From<SystemReaderError> for StateOverrideError {
+    fn from(value: SystemReaderError) -> Self {
+        Self::SystemReaderError(value)
+    }
+}
+
+/// Applies the given overrides to the database, after checking each of them against the
+/// blueprint schemas.
+pub fn apply_state_overrides<S: SubstateDatabase + CommittableSubstateDatabase>(
+    substate_db: &mut S,
+    state_overrides: &[StateOverride],
+) -> Result<(), StateOverrideError> {
+    for state_override in state_overrides {
+        match state_override {
+            StateOverride::FungibleBalance {
+                account,
+                resource_address,
+                amount,
+            } => override_fungible_balance(substate_db, account, resource_address, *amount)?,
+            StateOverride::CurrentTime {
+                proposer_timestamp_ms,
+            } => override_current_time(substate_db, *proposer_timestamp_ms)?,
+            StateOverride::ObjectField {
+                node_id,
+                module_id,
+                field_index,
+                value,
+            } => override_object_field(substate_db, node_id, *module_id, *field_index, value)?,
+        }
+    }
+    Ok(())
+}
+
+fn override_fungible_balance<S: SubstateDatabase + CommittableSubstateDatabase>(
+    substate_db: &mut S,
+    account: &ComponentAddress,
+    resource_address: &ResourceAddress,
+    amount: Decimal,
+) -> Result<(), StateOverrideError> {
+    let reader = SystemDatabaseReader::new(substate_db);
+    if reader.get_blueprint_id(account.as_node_id(), ModuleId::Main)?
+        != BlueprintId::new(&ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT)
+    {
+        return Err(StateOverrideError::NotAnAccount(*account));
+    }
+    if !resource_address.is_fungible() {
+        return Err(StateOverrideError::NotAFungibleResource(*resource_address));
+    }
+    let divisibility = reader
+        .read_typed_object_field::<FungibleResourceManagerDivisibilityFieldPayload>(
+            resource_address.as_node_id(),
+            ModuleId::Main,
+            FungibleResourceManagerField::Divisibility.field_index(),
+        )?
+        .into_latest();
+    if amount.is_negative() || !check_fungible_amount(&amount, divisibility) {
+        return Err(StateOverrideError::InvalidAmount(amount));
+    }
+    let vault = reader
+        .read_object_collection_entry::<_, AccountResourceVaultEntryPayload>(
+            account.as_node_id(),
+            ModuleId::Main,
+            ObjectCollectionKey::KeyValue(
+                AccountCollection::ResourceVaultKeyValue.collection_index(),
+                resource_address,
+            ),
+        )?
+        .map(|vault| vault.into_latest());
+    let resource_features = reader
+        .get_object_info(*resource_address)?
+        .blueprint_info
+        .features;
+
+    let mut writer = SystemDatabaseWriter::new(substate_db);
+    let vault_id = match vault {
+        Some(vault) => vault.0 .0,
+        None => {
+            // The id only needs to be unique within the preview
+            let vault_id = NodeId::new(
+                EntityType::InternalFungibleVault as u8,
+                &hash(scrypto_encode(&(account, resource_address)).unwrap()).lower_bytes(),
+            );
+            let with_freeze_status = resource_features
+                .contains(FungibleResourceManagerFeature::VaultFreeze.feature_name());
+            writer.write_type_info(
+                &vault_id,
+                TypeInfoSubstate::Object(ObjectInfo {
+                    blueprint_info: BlueprintInfo {
+                        blueprint_id: BlueprintId::new(&RESOURCE_PACKAGE, FUNGIBLE_VAULT_BLUEPRINT),
+                        blueprint_version: BlueprintVersion::default(),
+                        outer_obj_info: OuterObjectInfo::Some {
+                            outer_object: (*resource_address).into(),
+                        },
+                        features: resource_features,
+                        generic_substitutions: vec![],
+                    },
+                    object_type: ObjectType::Owned,
+                }),
+            );
+            writer.write_typed_object_field(
+                &vault_id,
+                ModuleId::Main,
+                FungibleVaultField::LockedBalance.field_index(),
+                FungibleVaultLockedBalanceFieldPayload::from_content_source(
+                    LockedFungibleResource::default(),
+                ),
+            )?;
+            if with_freeze_status {
+                writer.write_typed_object_field(
+                    &vault_id,
+                    ModuleId::Main,
+                    FungibleVaultField::FreezeStatus.field_index(),
+                    FungibleVaultFreezeStatusFieldPayload::from_content_source(
+                        VaultFrozenFlag::default(),
+                    ),
+                )?;
+            }
+            writer.write_typed_object_collection_entry(
+                account.as_node_id(),
+                ModuleId::Main,
+                AccountCollection::ResourceVaultKeyValue.collection_index(),
+                resource_address,
+                AccountResourceVaultEntryPayload::from_content_source(Vault(Own(vault_id))),
+            )?;
+            vault_id
+        }
+    };
+
+    writer.write_typed_object_field(
+        &vault_id,
+        ModuleId::Main,
+        FungibleVaultField::Balance.field_index(),
+        FungibleVaultBalanceFieldPayload::from_content_source(LiquidFungibleResource::new(amount)),
+    )?;
+
+    Ok(())
+}
+
+fn override_current_time<S: SubstateDatabase + CommittableSubstateDatabase>(
+    substate_db: &mut S,
+    proposer_timestamp_ms: i64,
+) -> Result<(), StateOverrideError> {
+    let epoch_minute = i32::try_from(proposer_timestamp_ms.div_euclid(60 * 1000))
+        .map_err(|_| StateOverrideError::InvalidTimestamp(proposer_timestamp_ms))?;
+
+    let mut writer = SystemDatabaseWriter::new(substate_db);
+    writer.write_typed_object_field(
+        CONSENSUS_MANAGER.as_node_id(),
+        ModuleId::Main,
+        ConsensusManagerField::ProposerMilliTimestamp.field_index(),
+        ConsensusManagerProposerMilliTimestampFieldPayload::from_content_source(
+            ProposerMilliTimestampSubstate {
+                epoch_milli: proposer_timestamp_ms,
+            },
+        ),
+    )?;
+    writer.write_typed_object_field(
+        CONSENSUS_MANAGER.as_node_id(),
+        ModuleId::Main,
+        ConsensusManagerField::ProposerMinuteTimestamp.field_index(),
+        ConsensusManagerProposerMinuteTimestampFieldPayload::from_content_source(
+            ProposerMinuteTimestampSubstate { epoch_minute },
+        ),
+    )?;
+
+    Ok(())
+}
+
+fn override_object_field<S: SubstateDatabase + CommittableSubstateDatabase>(
+    substate_db: &mut S,
+    node_id: &NodeId,
+    module_id: ModuleId,
+    field_index: FieldIndex,
+    value: &ScryptoValue,
+) -> Result<(), StateOverrideError> {
+    let reader = SystemDatabaseReader::new(substate_db);
+    let target = reader.get_blueprint_type_target(node_id, module_id)?;
+    let schema = reader
+        .get_blueprint_payload_schema(&target, &BlueprintPayloadIdentifier::Field(field_index))?;
+    let payload = scrypto_encode(value).map_err(|e| StateOverrideError::InvalidFieldValue {
+        node_id: *node_id,
+        module_id,
+        field_index,
+        error: format!("{:?}", e),
+    })?;
+    reader
+        .validate_payload(&payload, &schema, BLUEPRINT_PAYLOAD_MAX_DEPTH)
+        .map_err(|e| StateOverrideError::InvalidFieldValue {
+            node_id: *node_id,
+            module_id,
+            field_index,
+            error: e.error_message(schema.schema.v1()),
+        })?;
+
+    SystemDatabaseWriter::new(substate_db).write_typed_object_field(
+        node_id,
+        module_id,
+        field_index,
+        value.clone(),
+    )?;
+
+    Ok(())
+}
@@ -9,6 +9,7 @@ use crate::blueprints::resource::{
 };
 use crate::blueprints::transaction_tracker::{
     TransactionStatus, TransactionStatusV1, TransactionTrackerSubstate,
+    TransactionTrackerSubstateV1,
 };
 use crate::errors::*;
 use crate::internal_prelude::KeyValueEntrySubstateV1;
@@ -25,7 +26,7 @@ use crate::system::system_modules::{EnabledModules, SystemModuleMixer};
 use crate::system::system_substates::KeyValueEntrySubstate;
 use crate::system::system_substates::{FieldSubstate, LockStatus};
 use crate::track::interface::CommitableSubstateStore;
-use crate::track::{to_state_updates, Track, TrackFinalizeError};
+use crate::track::{to_state_updates, DatabaseReads, Track, TrackFinalizeError};
 use crate::transaction::*;
 use crate::types::*;
 use radix_engine_common::constants::*;
@@ -33,6 +34,7 @@ use radix_engine_interface::api::ModuleId;
 use radix_engine_interface::blueprints::resource::LiquidFungibleResource;
 use radix_engine_interface::blueprints::transaction_processor::InstructionOutput;
 use radix_engine_store_interface::{db_key_mapper::SpreadPrefixKeyMapper, interface::*};
+use sbor::rust::cell::RefCell;
 use transaction::model::*;
 
 /// Protocol-defined costing parameters
@@ -98,6 +100,7 @@ pub struct ExecutionConfig {
     pub max_panic_message_size: usize,
     pub max_number_of_logs: usize,
     pub max_number_of_events: usize,
+    pub max_key_value_store_scan_limit: u32,
     pub max_per_function_royalty_in_xrd: Decimal,
 }
 
@@ -122,6 +125,7 @@ impl ExecutionConfig {
             max_panic_message_size: MAX_PANIC_MESSAGE_SIZE,
             max_number_of_logs: MAX_NUMBER_OF_LOGS,
             max_number_of_events: MAX_NUMBER_OF_EVENTS,
+            max_key_value_store_scan_limit: MAX_KEY_VALUE_STORE_SCAN_LIMIT,
             max_per_function_royalty_in_xrd: Decimal::try_from(MAX_PER_FUNCTION_ROYALTY_IN_XRD)
                 .unwrap(),
         }
@@ -144,6 +148,15 @@ impl ExecutionConfig {
         }
     }
 
+    /// For the transactions executing calls made due by the scheduler, which pay for
+    /// themselves out of the fee locked by the scheduler.
+    pub fn for_scheduled_call_transaction(network_definition: NetworkDefinition) -> Self {
+        Self {
+            enabled_modules: EnabledModules::for_notarized_transaction(),
+            ..Self::default(network_definition)
+        }
+    }
+
     pub fn for_notarized_transaction(network_definition: NetworkDefinition) -> Self {
         Self {
             enabled_modules: EnabledModules::for_notarized_transaction(),
@@ -229,6 +242,37 @@ where
         costing_parameters: &CostingParameters,
         execution_config: &ExecutionConfig,
         init: T::Init,
+    ) -> TransactionReceipt {
+        self.execute_internal::<T>(executable, costing_parameters, execution_config, init, None)
+    }
+
+    /// Executes a transaction, and returns the database reads it depends on along with the
+    /// receipt.
+    pub fn execute_recording_database_reads<T: WrappedSystem<V>>(
+        &mut self,
+        executable: &Executable,
+        costing_parameters: &CostingParameters,
+        execution_config: &ExecutionConfig,
+        init: T::Init,
+    ) -> (TransactionReceipt, DatabaseReads) {
+        let database_reads = RefCell::new(DatabaseReads::default());
+        let receipt = self.execute_internal::<T>(
+            executable,
+            costing_parameters,
+            execution_config,
+            init,
+            Some(&database_reads),
+        );
+        (receipt, database_reads.into_inner())
+    }
+
+    fn execute_internal<T: WrappedSystem<V>>(
+        &mut self,
+        executable: &Executable,
+        costing_parameters: &CostingParameters,
+        execution_config: &ExecutionConfig,
+        init: T::Init,
+        database_reads: Option<&RefCell<DatabaseReads>>,
     ) -> TransactionReceipt {
         let fee_reserve = SystemLoanFeeReserve::new(
             costing_parameters,
@@ -253,6 +297,9 @@ where
 
         // Create a track
         let mut track = Track::<_, SpreadPrefixKeyMapper>::new(self.substate_db);
+        if let Some(database_reads) = database_reads {
+            track.record_database_reads(database_reads);
+        }
 
         // Perform runtime validation.
         // TODO: the following assumptions can be removed with better interface.
@@ -272,6 +319,21 @@ where
                         range.end_epoch_exclusive,
                     )
                 })
+                .and_then(|_| {
+                    for subintent in &executable.subintent_params().subintents {
+                        Self::validate_intent_hash(
+                            &mut track,
+                            subintent.subintent_hash,
+                            subintent.expiry_epoch,
+                        )
+                        .map_err(|_| {
+                            RejectionReason::SubintentHashPreviouslyCommitted(
+                                subintent.subintent_hash,
+                            )
+                        })?;
+                    }
+                    Ok(())
+                })
             } else {
                 Ok(())
             }
@@ -369,6 +431,7 @@ where
                                 &mut track,
                                 next_epoch,
                                 executable.intent_hash(),
+                                &executable.subintent_params().subintents,
                                 is_success,
                             );
                         }
@@ -636,6 +699,7 @@ where
                 executable.pre_allocated_addresses(),
                 executable.references(),
                 executable.blobs(),
+                executable.subintent_params(),
             )
             .and_then(|x| {
                 let system = wrapped_system.system_mut();
@@ -899,8 +963,10 @@ where
             let current_leader = substate.into_payload().into_latest().current_leader;
 
             // Update validator rewards
+            // The rewards are only added to, so the transaction doesn't depend on their previous
+            // value, see `rebase_fee_distribution`
             let substate: FieldSubstate<ConsensusManagerValidatorRewardsFieldPayload> = track
-                .read_substate(
+                .read_substate_unrecorded(
                     CONSENSUS_MANAGER.as_node_id(),
                     MAIN_BASE_PARTITION,
                     &ConsensusManagerField::ValidatorRewards.into(),
@@ -934,7 +1000,7 @@ where
             // Put validator rewards into the vault
             let total_amount = to_proposer.checked_add(to_validator_set).unwrap();
             let mut vault_balance = track
-                .read_substate(
+                .read_substate_unrecorded(
                     &vault_node_id,
                     MAIN_BASE_PARTITION,
                     &FungibleVaultField::Balance.into(),
@@ -987,6 +1053,7 @@ where
         track: &mut Track<S, SpreadPrefixKeyMapper>,
         next_epoch: Epoch,
         intent_hash: &TransactionIntentHash,
+        subintents: &[ExecutableSubintent],
         is_success: bool,
     ) {
         // Read the intent hash store
@@ -1009,30 +1076,27 @@ where
             intent_hash,
         } = intent_hash
         {
-            if let Some(partition_number) =
-                transaction_tracker.partition_for_expiry_epoch(*expiry_epoch)
-            {
-                track
-                    .set_substate(
-                        TRANSACTION_TRACKER.into_node_id(),
-                        PartitionNumber(partition_number),
-                        SubstateKey::Map(scrypto_encode(intent_hash).unwrap()),
-                        IndexedScryptoValue::from_typed(&KeyValueEntrySubstate::V1(
-                            KeyValueEntrySubstateV1 {
-                                value: Some(if is_success {
-                                    TransactionStatus::V1(TransactionStatusV1::CommittedSuccess)
-                                } else {
-                                    TransactionStatus::V1(TransactionStatusV1::CommittedFailure)
-                                }),
-                                // TODO: maybe make it immutable, but how does this affect partition deletion?
-                                lock_status: LockStatus::Unlocked,
-                            },
-                        )),
-                        &mut |_| -> Result<(), ()> { Ok(()) },
-                    )
-                    .unwrap();
-            } else {
-                panic!("No partition for an expiry epoch")
+            Self::set_transaction_status(
+                track,
+                &transaction_tracker,
+                intent_hash,
+                *expiry_epoch,
+                is_success,
+            );
+        }
+
+        // Update the status of the subintent hashes.
+        // A subintent is only used up if the transaction succeeds, so that a failing transaction
+        // can't prevent a subintent from being included in a later transaction.
+        if is_success {
+            for subintent in subintents {
+                Self::set_transaction_status(
+                    track,
+                    &transaction_tracker,
+                    &subintent.subintent_hash,
+                    subintent.expiry_epoch,
+                    is_success,
+                );
             }
         }
 
@@ -1065,6 +1129,39 @@ where
             .unwrap();
     }
 
+    fn set_transaction_status(
+        track: &mut Track<S, SpreadPrefixKeyMapper>,
+        transaction_tracker: &TransactionTrackerSubstateV1,
+        intent_hash: &Hash,
+        expiry_epoch: Epoch,
+        is_success: bool,
+    ) {
+        if let Some(partition_number) = transaction_tracker.partition_for_expiry_epoch(expiry_epoch)
+        {
+            track
+                .set_substate(
+                    TRANSACTION_TRACKER.into_node_id(),
+                    PartitionNumber(partition_number),
+                    SubstateKey::Map(scrypto_encode(intent_hash).unwrap()),
+                    IndexedScryptoValue::from_typed(&KeyValueEntrySubstate::V1(
+                        KeyValueEntrySubstateV1 {
+                            value: Some(if is_success {
+                                TransactionStatus::V1(TransactionStatusV1::CommittedSuccess)
+                            } else {
+                                TransactionStatus::V1(TransactionStatusV1::CommittedFailure)
+                            }),
+                            // TODO: maybe make it immutable, but how does this affect partition deletion?
+                            lock_status: LockStatus::Unlocked,
+                        },
+                    )),
+                    &mut |_| -> Result<(), ()> { Ok(()) },
+                )
+                .unwrap();
+        } else {
+            panic!("No partition for an expiry epoch")
+        }
+    }
+
     #[cfg(not(feature = "alloc"))]
     fn print_executable(executable: &Executable) {
         println!("{:-^120}", "Executable");
@@ -1217,6 +1314,26 @@ pub fn execute_transaction<S: SubstateDatabase, V: SystemCallbackObject + Clone>
     )
 }
 
+/// Executes a transaction like [`execute_transaction`], and returns the database reads it depends
+/// on along with the receipt.
+pub fn execute_transaction_recording_database_reads<
+    S: SubstateDatabase,
+    V: SystemCallbackObject + Clone,
+>(
+    substate_db: &S,
+    vm: V,
+    costing_parameters: &CostingParameters,
+    execution_config: &ExecutionConfig,
+    transaction: &Executable,
+) -> (TransactionReceipt, DatabaseReads) {
+    TransactionExecutor::new(substate_db, vm).execute_recording_database_reads::<SystemConfig<V>>(
+        transaction,
+        costing_parameters,
+        execution_config,
+        (),
+    )
+}
+
 pub fn execute_transaction_with_system<
     S: SubstateDatabase,
     V: SystemCallbackObject + Clone,
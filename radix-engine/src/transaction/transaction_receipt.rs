@@ -8,6 +8,7 @@ use crate::track::BatchPartitionStateUpdate;
 use crate::track::NodeStateUpdates;
 use crate::track::PartitionStateUpdates;
 use crate::track::StateUpdates;
+use crate::transaction::StateOverride;
 use crate::transaction::SystemStructure;
 use colored::*;
 use radix_engine_interface::blueprints::transaction_processor::InstructionOutput;
@@ -42,6 +43,18 @@ pub struct TransactionReceiptV1 {
     pub resources_usage: Option<ResourcesUsage>,
 }
 
+/// The receipt of a transaction previewed with state overrides.
+///
+/// This is kept apart from [`TransactionReceipt`] so that the versioned receipt encoding stays
+/// unchanged, while still listing the overrides next to the results they produced.
+#[derive(Clone)]
+pub struct StateOverridePreviewReceipt {
+    /// The changes to the ledger state which were pretended for this transaction
+    pub state_overrides: Vec<StateOverride>,
+    /// The receipt of the transaction executed against the overridden state
+    pub receipt: TransactionReceipt,
+}
+
 #[derive(Default, Debug, Clone, ScryptoSbor)]
 pub struct TransactionFeeSummary {
     /// Total execution cost units consumed.
@@ -576,6 +589,16 @@ impl fmt::Debug for TransactionReceipt {
     }
 }
 
+impl fmt::Debug for StateOverridePreviewReceipt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.display(TransactionReceiptDisplayContext::default())
+        )
+    }
+}
+
 #[derive(Default)]
 pub struct TransactionReceiptDisplayContext<'a> {
     pub encoder: Option<&'a AddressBech32Encoder>,
@@ -655,6 +678,77 @@ impl<'a> TransactionReceiptDisplayContextBuilder<'a> {
     }
 }
 
+impl<'a> ContextualDisplay<TransactionReceiptDisplayContext<'a>> for StateOverridePreviewReceipt {
+    type Error = fmt::Error;
+
+    fn contextual_format<F: fmt::Write>(
+        &self,
+        f: &mut F,
+        context: &TransactionReceiptDisplayContext<'a>,
+    ) -> Result<(), Self::Error> {
+        let state_overrides = &self.state_overrides;
+        let scrypto_value_display_context = context.display_context();
+        let address_display_context = context.address_display_context();
+
+        write!(
+            f,
+            "{} {}",
+            "State Overrides (previewed state is not the ledger state):"
+                .bold()
+                .yellow(),
+            state_overrides.len()
+        )?;
+        for (i, state_override) in state_overrides.iter().enumerate() {
+            let description = match state_override {
+                StateOverride::FungibleBalance {
+                    account,
+                    resource_address,
+                    amount,
+                } => format!(
+                    "{} holds {} of {}",
+                    account.display(address_display_context),
+                    amount,
+                    resource_address.display(address_display_context),
+                ),
+                StateOverride::CurrentTime {
+                    proposer_timestamp_ms,
+                } => format!(
+                    "Current time is {} ms since Unix epoch",
+                    proposer_timestamp_ms
+                ),
+                StateOverride::ObjectField {
+                    node_id,
+                    module_id,
+                    field_index,
+                    value,
+                } => format!(
+                    "Field {} of {:?} module of {} is {}",
+                    field_index,
+                    module_id,
+                    node_id.display(address_display_context),
+                    IndexedScryptoValue::from_scrypto_value(value.clone()).to_string(
+                        ValueDisplayParameters::Schemaless {
+                            display_mode: DisplayMode::RustLike,
+                            print_mode: PrintMode::SingleLine,
+                            custom_context: scrypto_value_display_context,
+                            depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH
+                        }
+                    ),
+                ),
+            };
+            write!(
+                f,
+                "\n{} {}",
+                prefix!(i, state_overrides),
+                description.yellow()
+            )?;
+        }
+
+        writeln!(f)?;
+        self.receipt.contextual_format(f, context)
+    }
+}
+
 impl<'a> ContextualDisplay<TransactionReceiptDisplayContext<'a>> for TransactionReceipt {
     type Error = fmt::Error;
 
@@ -22,6 +22,19 @@ impl<W: WasmEngine + Default> Default for ScryptoVm<W> {
 }
 
 impl<W: WasmEngine> ScryptoVm<W> {
+    /// Persists the code instrumented when validating packages and, if the WASM engine supports
+    /// it, the modules prepared by the engine to the given cache, so that they can be reused
+    /// across process restarts.
+    ///
+    /// Note that package validation uses the cache process-wide (see
+    /// [`WasmModuleDiskCache::use_for_package_validation`]).
+    #[cfg(not(feature = "alloc"))]
+    pub fn with_module_disk_cache(mut self, module_disk_cache: WasmModuleDiskCache) -> Self {
+        module_disk_cache.use_for_package_validation();
+        self.wasm_engine.set_module_disk_cache(module_disk_cache);
+        self
+    }
+
     pub fn create_instance(
         &self,
         package_address: &PackageAddress,
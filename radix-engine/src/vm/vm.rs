@@ -5,6 +5,8 @@ use crate::system::system_callback::{SystemConfig, SystemLockData};
 use crate::system::system_callback_api::SystemCallbackObject;
 use crate::system::system_substates::KeyValueEntrySubstate;
 use crate::types::*;
+#[cfg(not(feature = "alloc"))]
+use crate::vm::wasm::WasmModuleDiskCache;
 use crate::vm::wasm::{WasmEngine, WasmValidator};
 use crate::vm::{NativeVm, NativeVmExtension, ScryptoVm};
 use radix_engine_interface::api::field_api::LockFlags;
@@ -178,14 +180,27 @@ impl VmPackageValidation {
             VmType::Native => Ok(None),
             VmType::ScryptoV1 => {
                 // Validate WASM
-                let instrumented_code = WasmValidator::default()
+                let wasm_validator = WasmValidator::default();
+                #[cfg(not(feature = "alloc"))]
+                let validation_result = match WasmModuleDiskCache::for_package_validation() {
+                    Some(disk_cache) => wasm_validator.validate_with_disk_cache(
+                        &code,
+                        &definition.blueprints,
+                        &disk_cache,
+                    ),
+                    None => wasm_validator
+                        .validate(&code, definition.blueprints.values())
+                        .map(|(instrumented_code, _)| instrumented_code),
+                };
+                #[cfg(feature = "alloc")]
+                let validation_result = wasm_validator
                     .validate(&code, definition.blueprints.values())
-                    .map_err(|e| {
-                        RuntimeError::ApplicationError(ApplicationError::PackageError(
-                            PackageError::InvalidWasm(e),
-                        ))
-                    })?
-                    .0;
+                    .map(|(instrumented_code, _)| instrumented_code);
+                let instrumented_code = validation_result.map_err(|e| {
+                    RuntimeError::ApplicationError(ApplicationError::PackageError(
+                        PackageError::InvalidWasm(e),
+                    ))
+                })?;
 
                 for BlueprintDefinitionInit {
                     is_transient,
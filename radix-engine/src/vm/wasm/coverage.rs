@@ -0,0 +1,660 @@
+use crate::errors::InvokeError;
+use crate::types::*;
+use crate::vm::wasm::*;
+use radix_engine_interface::blueprints::package::CodeHash;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use wasmparser::{BinaryReader, FunctionBody, ImportSectionReader, Operator, Parser, Payload};
+
+/// The prefix of the names under which the block counters of an instrumented module are exported.
+pub const COVERAGE_COUNTER_EXPORT_PREFIX: &str = "__coverage_counter_";
+
+const WASM_HEADER_LENGTH: usize = 8;
+
+const CUSTOM_SECTION_ID: u8 = 0;
+const IMPORT_SECTION_ID: u8 = 2;
+const GLOBAL_SECTION_ID: u8 = 6;
+const EXPORT_SECTION_ID: u8 = 7;
+const CODE_SECTION_ID: u8 = 10;
+
+/// The ids of the sections which have to follow the global section.
+const SECTION_IDS_AFTER_GLOBAL: [u8; 6] = [7, 8, 9, 12, 10, 11];
+/// The ids of the sections which have to follow the export section.
+const SECTION_IDS_AFTER_EXPORT: [u8; 5] = [8, 9, 12, 10, 11];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WasmCoverageError {
+    PrepareError(PrepareError),
+    DebugInfoError(String),
+}
+
+impl From<PrepareError> for WasmCoverageError {
+    fn from(error: PrepareError) -> Self {
+        Self::PrepareError(error)
+    }
+}
+
+impl From<gimli::Error> for WasmCoverageError {
+    fn from(error: gimli::Error) -> Self {
+        Self::DebugInfoError(error.to_string())
+    }
+}
+
+fn parser_error(error: wasmparser::BinaryReaderError) -> PrepareError {
+    PrepareError::WasmParserError(error.to_string())
+}
+
+struct Section<'a> {
+    id: u8,
+    payload: &'a [u8],
+    payload_offset: usize,
+}
+
+fn read_sections(code: &[u8]) -> Result<Vec<Section>, PrepareError> {
+    if code.len() < WASM_HEADER_LENGTH {
+        return Err(PrepareError::DeserializationError);
+    }
+    let mut reader = BinaryReader::new_with_offset(&code[WASM_HEADER_LENGTH..], WASM_HEADER_LENGTH);
+    let mut sections = Vec::new();
+    while !reader.eof() {
+        let id = reader.read_u8().map_err(parser_error)?;
+        let size = reader.read_var_u32().map_err(parser_error)? as usize;
+        let payload_offset = reader.original_position();
+        let payload = reader.read_bytes(size).map_err(parser_error)?;
+        sections.push(Section {
+            id,
+            payload,
+            payload_offset,
+        });
+    }
+    Ok(sections)
+}
+
+fn write_var_u32(buffer: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer.push(byte);
+            return;
+        }
+        buffer.push(byte | 0x80);
+    }
+}
+
+fn write_section(buffer: &mut Vec<u8>, id: u8, payload: &[u8]) {
+    buffer.push(id);
+    write_var_u32(buffer, payload.len() as u32);
+    buffer.extend_from_slice(payload);
+}
+
+/// Splits the payload of a section holding a vector into the item count and the encoded items.
+fn split_vector(payload: &[u8]) -> Result<(u32, &[u8]), PrepareError> {
+    let mut reader = BinaryReader::new(payload);
+    let count = reader.read_var_u32().map_err(parser_error)?;
+    Ok((count, &payload[reader.current_position()..]))
+}
+
+/// Writes `global.get $g; i64.const 1; i64.add; global.set $g`.
+fn write_counter_increment(buffer: &mut Vec<u8>, global_index: u32) {
+    buffer.push(0x23);
+    write_var_u32(buffer, global_index);
+    buffer.extend_from_slice(&[0x42, 0x01, 0x7c, 0x24]);
+    write_var_u32(buffer, global_index);
+}
+
+/// Inserts a counter increment at the start of each basic block of a function body, i.e. at its
+/// entry, at the start of the bodies of `loop`, `if` and `else`, and after each `end` and `br_if`
+/// (where the execution may continue after a branch).
+///
+/// The counted blocks are identified by the code section offsets of the original instructions
+/// they start at, which are appended to `block_offsets`.
+fn instrument_function_body(
+    body: &[u8],
+    body_offset: usize,
+    first_counter_global: u32,
+    block_offsets: &mut Vec<u32>,
+) -> Result<Vec<u8>, PrepareError> {
+    let mut reader = FunctionBody::new(body_offset, body)
+        .get_operators_reader()
+        .map_err(parser_error)?;
+
+    let mut insertion_offsets = Vec::new();
+    let mut depth = 0u32;
+    let mut starts_block = true;
+    while !reader.eof() {
+        let (operator, offset) = reader.read_with_offset().map_err(parser_error)?;
+        if starts_block {
+            insertion_offsets.push(offset);
+            starts_block = false;
+        }
+        match operator {
+            Operator::Block { .. } => depth += 1,
+            Operator::Loop { .. } | Operator::If { .. } => {
+                depth += 1;
+                starts_block = true;
+            }
+            Operator::Else | Operator::BrIf { .. } => starts_block = true,
+            Operator::End => {
+                // The last `end` closes the function body itself
+                if depth > 0 {
+                    depth -= 1;
+                    starts_block = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut instrumented_body = Vec::with_capacity(body.len() + insertion_offsets.len() * 8);
+    let mut copied = 0;
+    for offset in insertion_offsets {
+        let position = offset - body_offset;
+        instrumented_body.extend_from_slice(&body[copied..position]);
+        copied = position;
+        write_counter_increment(
+            &mut instrumented_body,
+            first_counter_global + block_offsets.len() as u32,
+        );
+        block_offsets.push(offset as u32);
+    }
+    instrumented_body.extend_from_slice(&body[copied..]);
+
+    Ok(instrumented_body)
+}
+
+/// Instruments all function bodies of a code section, with offsets relative to the start of the
+/// section payload (which is how DWARF addresses code in WASM modules).
+fn instrument_code_section(
+    payload: &[u8],
+    first_counter_global: u32,
+    block_offsets: &mut Vec<u32>,
+) -> Result<Vec<u8>, PrepareError> {
+    let mut reader = BinaryReader::new(payload);
+    let count = reader.read_var_u32().map_err(parser_error)?;
+
+    let mut instrumented_payload = Vec::with_capacity(payload.len() * 2);
+    write_var_u32(&mut instrumented_payload, count);
+    for _ in 0..count {
+        let size = reader.read_var_u32().map_err(parser_error)? as usize;
+        let body_offset = reader.current_position();
+        let body = reader.read_bytes(size).map_err(parser_error)?;
+        let instrumented_body =
+            instrument_function_body(body, body_offset, first_counter_global, block_offsets)?;
+        write_var_u32(&mut instrumented_payload, instrumented_body.len() as u32);
+        instrumented_payload.extend_from_slice(&instrumented_body);
+    }
+
+    Ok(instrumented_payload)
+}
+
+fn imported_global_count(section: &Section) -> Result<u32, PrepareError> {
+    let reader =
+        ImportSectionReader::new(section.payload, section.payload_offset).map_err(parser_error)?;
+    let mut count = 0;
+    for import in reader {
+        if let wasmparser::TypeRef::Global(_) = import.map_err(parser_error)?.ty {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Counts the executions of each basic block of the code, in mutable `i64` globals which are
+/// exported as [`COVERAGE_COUNTER_EXPORT_PREFIX`] followed by the index of the block.
+///
+/// Returns the instrumented code and the code section offsets of the counted blocks, by index.
+/// Only instructions are inserted, so the original code section offsets remain meaningful.
+pub fn inject_coverage_counters(code: &[u8]) -> Result<(Vec<u8>, Vec<u32>), PrepareError> {
+    let sections = read_sections(code)?;
+    let Some(code_section) = sections.iter().find(|s| s.id == CODE_SECTION_ID) else {
+        return Ok((code.to_vec(), Vec::new()));
+    };
+
+    let imported_globals = match sections.iter().find(|s| s.id == IMPORT_SECTION_ID) {
+        Some(section) => imported_global_count(section)?,
+        None => 0,
+    };
+    let (defined_global_count, defined_globals) =
+        match sections.iter().find(|s| s.id == GLOBAL_SECTION_ID) {
+            Some(section) => split_vector(section.payload)?,
+            None => (0, &[][..]),
+        };
+    let (export_count, exports) = match sections.iter().find(|s| s.id == EXPORT_SECTION_ID) {
+        Some(section) => split_vector(section.payload)?,
+        None => (0, &[][..]),
+    };
+    let first_counter_global = imported_globals + defined_global_count;
+
+    let mut block_offsets = Vec::new();
+    let code_payload = instrument_code_section(
+        code_section.payload,
+        first_counter_global,
+        &mut block_offsets,
+    )?;
+    let counter_count = block_offsets.len() as u32;
+
+    let mut global_payload = Vec::new();
+    write_var_u32(&mut global_payload, defined_global_count + counter_count);
+    global_payload.extend_from_slice(defined_globals);
+    for _ in 0..counter_count {
+        // (global (mut i64) (i64.const 0))
+        global_payload.extend_from_slice(&[0x7e, 0x01, 0x42, 0x00, 0x0b]);
+    }
+
+    let mut export_payload = Vec::new();
+    write_var_u32(&mut export_payload, export_count + counter_count);
+    export_payload.extend_from_slice(exports);
+    for i in 0..counter_count {
+        let name = format!("{}{}", COVERAGE_COUNTER_EXPORT_PREFIX, i);
+        write_var_u32(&mut export_payload, name.len() as u32);
+        export_payload.extend_from_slice(name.as_bytes());
+        export_payload.push(0x03);
+        write_var_u32(&mut export_payload, first_counter_global + i);
+    }
+
+    let mut instrumented_code = code[..WASM_HEADER_LENGTH].to_vec();
+    let mut globals_written = false;
+    let mut exports_written = false;
+    for section in &sections {
+        if !globals_written
+            && (section.id == GLOBAL_SECTION_ID || SECTION_IDS_AFTER_GLOBAL.contains(&section.id))
+        {
+            write_section(&mut instrumented_code, GLOBAL_SECTION_ID, &global_payload);
+            globals_written = true;
+        }
+        if !exports_written
+            && (section.id == EXPORT_SECTION_ID || SECTION_IDS_AFTER_EXPORT.contains(&section.id))
+        {
+            write_section(&mut instrumented_code, EXPORT_SECTION_ID, &export_payload);
+            exports_written = true;
+        }
+        match section.id {
+            GLOBAL_SECTION_ID | EXPORT_SECTION_ID => {}
+            CODE_SECTION_ID => {
+                write_section(&mut instrumented_code, CODE_SECTION_ID, &code_payload)
+            }
+            id => write_section(&mut instrumented_code, id, section.payload),
+        }
+    }
+
+    Ok((instrumented_code, block_offsets))
+}
+
+/// Removes the DWARF custom sections from the code. As no other section changes, code section
+/// offsets remain the same.
+pub fn strip_debug_info(code: &[u8]) -> Result<Vec<u8>, PrepareError> {
+    let mut stripped_code = code[..WASM_HEADER_LENGTH].to_vec();
+    for section in read_sections(code)? {
+        if section.id == CUSTOM_SECTION_ID {
+            let name = BinaryReader::new(section.payload)
+                .read_string()
+                .map_err(parser_error)?;
+            if name.starts_with(".debug_") {
+                continue;
+            }
+        }
+        write_section(&mut stripped_code, section.id, section.payload);
+    }
+    Ok(stripped_code)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineRow {
+    /// The code section offset at which the instructions of the line start.
+    offset: u64,
+    file: String,
+    line: u64,
+}
+
+/// Reads the line table from the DWARF debug info of the code, if it has any.
+fn read_line_rows(code: &[u8]) -> Result<Vec<LineRow>, WasmCoverageError> {
+    let mut debug_sections = index_map_new();
+    for payload in Parser::new(0).parse_all(code) {
+        if let Payload::CustomSection(reader) = payload.map_err(parser_error)? {
+            if reader.name().starts_with(".debug_") {
+                debug_sections.insert(reader.name().to_string(), reader.data());
+            }
+        }
+    }
+    if debug_sections.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let dwarf = gimli::Dwarf::load(|id| -> Result<_, gimli::Error> {
+        let data = debug_sections.get(id.name()).copied().unwrap_or(&[]);
+        Ok(gimli::EndianSlice::new(data, gimli::LittleEndian))
+    })?;
+
+    let mut line_rows = Vec::new();
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let Some(program) = unit.line_program.clone() else {
+            continue;
+        };
+        let mut rows = program.rows();
+        while let Some((header, row)) = rows.next_row()? {
+            if row.end_sequence() {
+                continue;
+            }
+            let (Some(file), Some(line)) = (row.file(header), row.line()) else {
+                continue;
+            };
+            let mut path = PathBuf::new();
+            if let Some(comp_dir) = &unit.comp_dir {
+                path.push(comp_dir.to_string_lossy().as_ref());
+            }
+            if let Some(directory) = file.directory(header) {
+                path.push(
+                    dwarf
+                        .attr_string(&unit, directory)?
+                        .to_string_lossy()
+                        .as_ref(),
+                );
+            }
+            path.push(
+                dwarf
+                    .attr_string(&unit, file.path_name())?
+                    .to_string_lossy()
+                    .as_ref(),
+            );
+            line_rows.push(LineRow {
+                offset: row.address(),
+                file: path.to_string_lossy().into_owned(),
+                line: line.get(),
+            });
+        }
+    }
+    Ok(line_rows)
+}
+
+struct CoveredCode {
+    instrumented_code: Vec<u8>,
+    /// The code section offset of each counted block, by counter index.
+    block_offsets: Vec<u32>,
+    hits: Vec<u64>,
+    line_rows: Vec<LineRow>,
+}
+
+impl CoveredCode {
+    /// Attributes the hits of each block to the source lines of its instructions.
+    fn add_line_hits(&self, line_hits: &mut IndexMap<String, BTreeMap<u64, u64>>) {
+        let mut blocks: Vec<(u64, u64)> = self
+            .block_offsets
+            .iter()
+            .zip(self.hits.iter())
+            .map(|(offset, hits)| (*offset as u64, *hits))
+            .collect();
+        blocks.sort();
+
+        for row in &self.line_rows {
+            // The block a row belongs to is the last one starting at or before it
+            let index = blocks.partition_point(|(offset, _)| *offset <= row.offset);
+            let hits = match index {
+                0 => 0,
+                index => blocks[index - 1].1,
+            };
+            let line = line_hits
+                .entry(row.file.clone())
+                .or_default()
+                .entry(row.line)
+                .or_default();
+            *line = (*line).max(hits);
+        }
+    }
+}
+
+/// Collects the execution counts of the basic blocks of registered Scrypto code, and reports
+/// them as the coverage of source lines, using the DWARF debug info of the code.
+///
+/// The collector is shared by all its clones, so that a single report can cover the transactions
+/// executed by multiple engines.
+#[derive(Clone, Default)]
+pub struct WasmCoverageCollector {
+    codes: Arc<Mutex<IndexMap<CodeHash, CoveredCode>>>,
+}
+
+impl WasmCoverageCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the code of a package, as built with debug info, to be counted by
+    /// [`CoverageWasmEngine`]s.
+    ///
+    /// Returns the code without its debug info, which is the code to be published (debug info
+    /// can easily exceed the substate size limit). Code built without debug info is still
+    /// counted, but it doesn't appear in the reports.
+    pub fn register_code(&self, code: &[u8]) -> Result<Vec<u8>, WasmCoverageError> {
+        let stripped_code = strip_debug_info(code)?;
+        let code_hash = CodeHash::from_hash(hash(&stripped_code));
+        if self.codes.lock().unwrap().contains_key(&code_hash) {
+            return Ok(stripped_code);
+        }
+
+        let (instrumented_code, block_offsets) =
+            WasmValidator::default().instrument_for_coverage(&stripped_code)?;
+        let line_rows = read_line_rows(code)?;
+        self.codes.lock().unwrap().insert(
+            code_hash,
+            CoveredCode {
+                instrumented_code,
+                hits: vec![0; block_offsets.len()],
+                block_offsets,
+                line_rows,
+            },
+        );
+        Ok(stripped_code)
+    }
+
+    fn with_instrumented_code<R, F: FnOnce(&[u8], usize) -> R>(
+        &self,
+        code_hash: &CodeHash,
+        f: F,
+    ) -> Option<R> {
+        let codes = self.codes.lock().unwrap();
+        codes
+            .get(code_hash)
+            .map(|code| f(&code.instrumented_code, code.block_offsets.len()))
+    }
+
+    fn record_hits(&self, code_hash: &CodeHash, hits: &[u64]) {
+        if let Some(code) = self.codes.lock().unwrap().get_mut(code_hash) {
+            for (total, hits) in code.hits.iter_mut().zip(hits) {
+                *total = total.saturating_add(*hits);
+            }
+        }
+    }
+
+    /// Renders the line coverage of all registered code as an `lcov` tracefile.
+    pub fn to_lcov(&self) -> String {
+        let mut line_hits = index_map_new();
+        for code in self.codes.lock().unwrap().values() {
+            code.add_line_hits(&mut line_hits);
+        }
+        line_hits.sort_keys();
+
+        let mut lcov = String::from("TN:\n");
+        for (file, lines) in line_hits {
+            lcov.push_str(&format!("SF:{}\n", file));
+            for (line, hits) in &lines {
+                lcov.push_str(&format!("DA:{},{}\n", line, hits));
+            }
+            lcov.push_str(&format!("LF:{}\n", lines.len()));
+            lcov.push_str(&format!(
+                "LH:{}\n",
+                lines.values().filter(|hits| **hits > 0).count()
+            ));
+            lcov.push_str("end_of_record\n");
+        }
+        lcov
+    }
+}
+
+/// A [`WasmEngine`] which runs the code registered with its [`WasmCoverageCollector`]
+/// instrumented with block counters, and reports the counts to the collector after each
+/// invocation. Any other code runs unchanged.
+///
+/// The counters consume execution cost units, so costing differs from regular execution.
+/// Prepared modules are never persisted to a disk cache, which is keyed by the code hash only
+/// and would mix up the instrumented and regular modules of the same code.
+pub struct CoverageWasmEngine<W: WasmEngine> {
+    engine: W,
+    collector: Option<WasmCoverageCollector>,
+}
+
+impl<W: WasmEngine> CoverageWasmEngine<W> {
+    pub fn new(engine: W, collector: Option<WasmCoverageCollector>) -> Self {
+        Self { engine, collector }
+    }
+}
+
+impl<W: WasmEngine + Default> Default for CoverageWasmEngine<W> {
+    fn default() -> Self {
+        Self::new(W::default(), None)
+    }
+}
+
+impl<W: WasmEngine> WasmEngine for CoverageWasmEngine<W> {
+    type WasmInstance = CoverageWasmInstance<W::WasmInstance>;
+
+    fn instantiate(&self, code_hash: CodeHash, instrumented_code: &[u8]) -> Self::WasmInstance {
+        if let Some(collector) = &self.collector {
+            let covered = collector.with_instrumented_code(&code_hash, |code, counter_count| {
+                (self.engine.instantiate(code_hash, code), counter_count)
+            });
+            if let Some((instance, counter_count)) = covered {
+                return CoverageWasmInstance {
+                    instance,
+                    counters: Some(CoverageCounters {
+                        collector: collector.clone(),
+                        code_hash,
+                        count: counter_count,
+                    }),
+                };
+            }
+        }
+
+        CoverageWasmInstance {
+            instance: self.engine.instantiate(code_hash, instrumented_code),
+            counters: None,
+        }
+    }
+}
+
+struct CoverageCounters {
+    collector: WasmCoverageCollector,
+    code_hash: CodeHash,
+    count: usize,
+}
+
+pub struct CoverageWasmInstance<I: WasmInstance> {
+    instance: I,
+    counters: Option<CoverageCounters>,
+}
+
+impl<I: WasmInstance> WasmInstance for CoverageWasmInstance<I> {
+    fn invoke_export<'r>(
+        &mut self,
+        func_name: &str,
+        args: Vec<Buffer>,
+        runtime: &mut Box<dyn WasmRuntime + 'r>,
+    ) -> Result<Vec<u8>, InvokeError<WasmRuntimeError>> {
+        let result = self.instance.invoke_export(func_name, args, runtime);
+
+        // The counters are read even if the invocation failed, to cover the failing paths too
+        if let Some(counters) = &self.counters {
+            let hits: Vec<u64> = (0..counters.count)
+                .map(|i| {
+                    let name = format!("{}{}", COVERAGE_COUNTER_EXPORT_PREFIX, i);
+                    self.instance.get_exported_global_i64(&name).unwrap_or(0) as u64
+                })
+                .collect();
+            counters.collector.record_hits(&counters.code_hash, &hits);
+        }
+
+        result
+    }
+
+    fn get_exported_global_i64(&mut self, name: &str) -> Option<i64> {
+        self.instance.get_exported_global_i64(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wabt::wat2wasm;
+
+    #[test]
+    fn test_inject_coverage_counters() {
+        let code = wat2wasm(
+            r#"
+            (module
+                (global $g (mut i32) (i32.const 0))
+                (func $f (param $0 i64) (result i64)
+                    (if (i64.eqz (local.get $0))
+                        (then (global.set $g (i32.const 1)))
+                        (else (global.set $g (i32.const 2)))
+                    )
+                    (local.get $0)
+                )
+                (memory $0 1)
+                (export "memory" (memory $0))
+                (export "f" (func $f))
+            )
+            "#,
+        )
+        .unwrap();
+
+        let (instrumented_code, block_offsets) = inject_coverage_counters(&code).unwrap();
+
+        // Function entry, `then`, `else` and the continuation after `if`
+        assert_eq!(block_offsets.len(), 4);
+        assert!(block_offsets.windows(2).all(|w| w[0] < w[1]));
+        WasmModule::init(&instrumented_code).unwrap();
+        let wat = wabt::wasm2wat(instrumented_code).unwrap();
+        assert!(wat.contains(r#"(export "__coverage_counter_3" (global 4))"#));
+        assert_eq!(wat.matches("i64.const 1").count(), 4);
+    }
+
+    #[test]
+    fn test_block_hits_are_attributed_to_lines() {
+        let code = CoveredCode {
+            instrumented_code: vec![],
+            block_offsets: vec![10, 20],
+            hits: vec![3, 0],
+            line_rows: vec![
+                LineRow {
+                    offset: 10,
+                    file: "lib.rs".to_string(),
+                    line: 1,
+                },
+                LineRow {
+                    offset: 15,
+                    file: "lib.rs".to_string(),
+                    line: 2,
+                },
+                LineRow {
+                    offset: 20,
+                    file: "lib.rs".to_string(),
+                    line: 3,
+                },
+                LineRow {
+                    offset: 25,
+                    file: "lib.rs".to_string(),
+                    line: 1,
+                },
+            ],
+        };
+
+        let mut line_hits = index_map_new();
+        code.add_line_hits(&mut line_hits);
+
+        assert_eq!(
+            line_hits.get("lib.rs").unwrap(),
+            &btreemap!(1 => 3, 2 => 3, 3 => 0)
+        );
+    }
+}
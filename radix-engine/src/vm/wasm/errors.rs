@@ -139,6 +139,8 @@ pub enum WasmRuntimeError {
 
     InvalidKeyValueStoreSchema(DecodeError),
 
+    InvalidKeyValueStoreCursor(DecodeError),
+
     /// Invalid component address
     InvalidLockFlags,
 
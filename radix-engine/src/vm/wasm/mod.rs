@@ -1,7 +1,12 @@
 mod constants;
+#[cfg(feature = "coverage")]
+mod coverage;
 mod errors;
+#[cfg(not(feature = "alloc"))]
+mod module_disk_cache;
 mod prepare;
 mod traits;
+mod wasm_analyzer;
 mod wasm_validator;
 mod wasm_validator_config;
 #[cfg(feature = "wasmer")]
@@ -13,9 +18,14 @@ mod weights;
 pub use self::wasmer::*;
 pub use self::wasmi::*;
 pub use constants::*;
+#[cfg(feature = "coverage")]
+pub use coverage::*;
 pub use errors::*;
+#[cfg(not(feature = "alloc"))]
+pub use module_disk_cache::*;
 pub use prepare::*;
 pub use traits::*;
+pub use wasm_analyzer::*;
 pub use wasm_validator::*;
 pub use wasm_validator_config::*;
 pub use weights::*;
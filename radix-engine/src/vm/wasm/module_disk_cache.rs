@@ -0,0 +1,285 @@
+use crate::types::*;
+use radix_engine_interface::blueprints::package::CodeHash;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Bump this whenever the layout of `WasmModuleDiskCacheEntry` changes.
+const DISK_CACHE_FORMAT_VERSION: u8 = 1;
+
+const DISK_CACHE_FILE_EXTENSION: &'static str = "module";
+
+/// The id under which the code instrumented by package validation is cached, in place of an
+/// engine id: instrumented code doesn't depend on the WASM engine, so it's cached for all of them.
+const INSTRUMENTED_CODE_ENTRY_ID: &'static str = "instrumented";
+
+/// The cache used by package validation, if any. It's process-wide, because packages are
+/// validated by the package blueprint, which has no access to the [`crate::vm::ScryptoVm`].
+static PACKAGE_VALIDATION_DISK_CACHE: RwLock<Option<WasmModuleDiskCache>> = RwLock::new(None);
+
+/// A cache of prepared WASM modules which is persisted to disk, so that modules don't need
+/// to be prepared again after a process restart.
+///
+/// Entries are keyed by the code hash of the package code and the id of the engine which
+/// prepared the module. Each entry also records the hash of the instrumented code it was
+/// prepared from (which changes with the metering configuration) and a checksum of the
+/// prepared module. Entries which fail any of these checks are discarded and re-prepared.
+///
+/// Besides the modules prepared by an engine, the cache also holds the code instrumented when
+/// validating packages (see [`Self::use_for_package_validation`]), which is reused by all engines.
+///
+/// The cache is an optimization only: any I/O error is treated as a cache miss.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmModuleDiskCache {
+    directory: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+struct WasmModuleDiskCacheEntry {
+    format_version: u8,
+    engine_id: String,
+    instrumented_code_hash: Hash,
+    module_hash: Hash,
+    module: Vec<u8>,
+}
+
+impl WasmModuleDiskCache {
+    /// Opens the cache in the given directory, creating the directory if it doesn't exist.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> io::Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        Ok(Self { directory })
+    }
+
+    pub fn directory(&self) -> &PathBuf {
+        &self.directory
+    }
+
+    /// Returns the prepared module for the given code, if it's in the cache and intact.
+    pub fn load(
+        &self,
+        engine_id: &str,
+        code_hash: &CodeHash,
+        instrumented_code: &[u8],
+    ) -> Option<Vec<u8>> {
+        let path = self.entry_path(engine_id, code_hash);
+        let bytes = fs::read(&path).ok()?;
+
+        let entry = scrypto_decode::<WasmModuleDiskCacheEntry>(&bytes)
+            .ok()
+            .filter(|entry| {
+                entry.format_version == DISK_CACHE_FORMAT_VERSION
+                    && entry.engine_id == engine_id
+                    && entry.instrumented_code_hash == hash(instrumented_code)
+                    && entry.module_hash == hash(&entry.module)
+            });
+        match entry {
+            Some(entry) => Some(entry.module),
+            None => {
+                // The entry is corrupted or stale, so remove it to have it re-prepared
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Stores a prepared module in the cache, replacing any existing entry for the code.
+    pub fn store(
+        &self,
+        engine_id: &str,
+        code_hash: &CodeHash,
+        instrumented_code: &[u8],
+        module: Vec<u8>,
+    ) {
+        let entry = WasmModuleDiskCacheEntry {
+            format_version: DISK_CACHE_FORMAT_VERSION,
+            engine_id: engine_id.to_string(),
+            instrumented_code_hash: hash(instrumented_code),
+            module_hash: hash(&module),
+            module,
+        };
+        let bytes = match scrypto_encode(&entry) {
+            Ok(bytes) => bytes,
+            Err(_) => return,
+        };
+
+        // Write to a temporary file first and then rename it, so that a concurrent reader
+        // never observes a partially written entry.
+        let file_name = Self::entry_file_name(engine_id, code_hash);
+        let path = self.directory.join(&file_name);
+        let temp_path = self
+            .directory
+            .join(format!("{}.{}.tmp", file_name, std::process::id()));
+        if fs::write(&temp_path, bytes).is_err() || fs::rename(&temp_path, &path).is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+    }
+
+    /// Makes the validation of packages in this process reuse and persist the code it instruments
+    /// through this cache, replacing any cache which was used before.
+    pub fn use_for_package_validation(&self) {
+        *PACKAGE_VALIDATION_DISK_CACHE.write().unwrap() = Some(self.clone());
+    }
+
+    /// Returns the cache used by package validation, if any.
+    pub fn for_package_validation() -> Option<Self> {
+        PACKAGE_VALIDATION_DISK_CACHE.read().unwrap().clone()
+    }
+
+    /// Returns the code instrumented from the code with the given hash, if it's in the cache and
+    /// was instrumented from the same validation input (see [`Self::store_instrumented_code`]).
+    pub fn load_instrumented_code(
+        &self,
+        code_hash: &CodeHash,
+        validation_input: &[u8],
+    ) -> Option<Vec<u8>> {
+        self.load(INSTRUMENTED_CODE_ENTRY_ID, code_hash, validation_input)
+    }
+
+    /// Stores the code instrumented from the code with the given hash, where the validation input
+    /// covers everything else which the validation depends on (such as its configuration).
+    pub fn store_instrumented_code(
+        &self,
+        code_hash: &CodeHash,
+        validation_input: &[u8],
+        instrumented_code: Vec<u8>,
+    ) {
+        self.store(
+            INSTRUMENTED_CODE_ENTRY_ID,
+            code_hash,
+            validation_input,
+            instrumented_code,
+        )
+    }
+
+    /// Removes all entries from the cache.
+    pub fn clear(&self) -> io::Result<()> {
+        for dir_entry in fs::read_dir(&self.directory)? {
+            let path = dir_entry?.path();
+            if path.extension().and_then(|x| x.to_str()) == Some(DISK_CACHE_FILE_EXTENSION) {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn entry_path(&self, engine_id: &str, code_hash: &CodeHash) -> PathBuf {
+        self.directory
+            .join(Self::entry_file_name(engine_id, code_hash))
+    }
+
+    // Engine ids contain dots (e.g. "wasmer-2.2.1-singlepass"), so the extension is appended to
+    // the file name rather than set with `Path::with_extension`, which would truncate the name.
+    fn entry_file_name(engine_id: &str, code_hash: &CodeHash) -> String {
+        format!(
+            "{}_{}.{}",
+            engine_id, code_hash.0, DISK_CACHE_FILE_EXTENSION
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_cache(name: &str) -> WasmModuleDiskCache {
+        let directory = std::env::temp_dir().join(format!(
+            "radix-engine-wasm-module-disk-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        WasmModuleDiskCache::new(directory).unwrap()
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let cache = create_cache("store-and-load");
+        let code_hash = CodeHash(hash("code"));
+
+        assert_eq!(cache.load("engine", &code_hash, b"code"), None);
+        cache.store("engine", &code_hash, b"code", vec![1, 2, 3]);
+        assert_eq!(
+            cache.load("engine", &code_hash, b"code"),
+            Some(vec![1, 2, 3])
+        );
+
+        // Different engine
+        assert_eq!(cache.load("other_engine", &code_hash, b"code"), None);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.load("engine", &code_hash, b"code"), None);
+    }
+
+    #[test]
+    fn test_entries_of_different_code_are_kept_apart() {
+        let cache = create_cache("different-code");
+        let engine_id = "wasmer-2.2.1-singlepass";
+        let code_hash_1 = CodeHash(hash("code 1"));
+        let code_hash_2 = CodeHash(hash("code 2"));
+
+        cache.store(engine_id, &code_hash_1, b"code 1", vec![1]);
+        cache.store(engine_id, &code_hash_2, b"code 2", vec![2]);
+
+        assert_ne!(
+            cache.entry_path(engine_id, &code_hash_1),
+            cache.entry_path(engine_id, &code_hash_2)
+        );
+        assert_eq!(
+            cache.load(engine_id, &code_hash_1, b"code 1"),
+            Some(vec![1])
+        );
+        assert_eq!(
+            cache.load(engine_id, &code_hash_2, b"code 2"),
+            Some(vec![2])
+        );
+    }
+
+    #[test]
+    fn test_instrumented_code_is_kept_apart_from_modules() {
+        let cache = create_cache("instrumented-code");
+        let code_hash = CodeHash(hash("code"));
+
+        cache.store("engine", &code_hash, b"code", vec![1]);
+        cache.store_instrumented_code(&code_hash, b"input", vec![2]);
+
+        assert_eq!(cache.load("engine", &code_hash, b"code"), Some(vec![1]));
+        assert_eq!(
+            cache.load_instrumented_code(&code_hash, b"input"),
+            Some(vec![2])
+        );
+        // Validated with different blueprints or configuration
+        assert_eq!(
+            cache.load_instrumented_code(&code_hash, b"other input"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_stale_entry_is_discarded() {
+        let cache = create_cache("stale-entry");
+        let code_hash = CodeHash(hash("code"));
+
+        cache.store("engine", &code_hash, b"code", vec![1, 2, 3]);
+        // Instrumented with a different metering configuration
+        assert_eq!(cache.load("engine", &code_hash, b"other code"), None);
+        assert_eq!(cache.load("engine", &code_hash, b"code"), None);
+    }
+
+    #[test]
+    fn test_corrupted_entry_is_discarded() {
+        let cache = create_cache("corrupted-entry");
+        let code_hash = CodeHash(hash("code"));
+
+        cache.store("engine", &code_hash, b"code", vec![1, 2, 3]);
+        let path = cache.entry_path("engine", &code_hash);
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+
+        assert_eq!(cache.load("engine", &code_hash, b"code"), None);
+        assert!(!path.exists());
+    }
+}
@@ -16,6 +16,44 @@ pub struct WasmModule {
     module: ModuleInfo,
 }
 
+/// A violation of the rules enforced by [`WasmModule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmViolation {
+    pub error: PrepareError,
+    /// The index of the function the violation was found in, if applicable.
+    /// Imported functions are included in the function index space.
+    pub function_index: Option<u32>,
+}
+
+impl WasmViolation {
+    pub fn new(error: PrepareError) -> Self {
+        Self {
+            error,
+            function_index: None,
+        }
+    }
+
+    pub fn in_function(error: PrepareError, function_index: u32) -> Self {
+        Self {
+            error,
+            function_index: Some(function_index),
+        }
+    }
+}
+
+/// Runs a check which records all violations it finds, and may stop on the first one
+/// which prevents further checking (eg. a malformed section).
+fn collect_violations<F>(check: F) -> Vec<WasmViolation>
+where
+    F: FnOnce(&mut Vec<WasmViolation>) -> Result<(), PrepareError>,
+{
+    let mut violations = Vec::new();
+    if let Err(error) = check(&mut violations) {
+        violations.push(WasmViolation::new(error));
+    }
+    violations
+}
+
 impl WasmModule {
     pub fn init(code: &[u8]) -> Result<Self, PrepareError> {
         // deserialize
@@ -52,14 +90,30 @@ impl WasmModule {
     }
 
     pub fn enforce_no_start_function(self) -> Result<Self, PrepareError> {
-        if self.module.start_function.is_some() {
-            Err(PrepareError::StartFunctionNotAllowed)
-        } else {
-            Ok(self)
+        let violations = self.start_function_violations();
+        self.ok_or_first_violation(violations)
+    }
+
+    pub fn start_function_violations(&self) -> Vec<WasmViolation> {
+        match self.module.start_function {
+            Some(function_index) => vec![WasmViolation::in_function(
+                PrepareError::StartFunctionNotAllowed,
+                function_index,
+            )],
+            None => vec![],
         }
     }
 
     pub fn enforce_import_limit(self) -> Result<Self, PrepareError> {
+        let violations = self.import_violations();
+        self.ok_or_first_violation(violations)
+    }
+
+    pub fn import_violations(&self) -> Vec<WasmViolation> {
+        collect_violations(|violations| self.check_imports(violations))
+    }
+
+    fn check_imports(&self, violations: &mut Vec<WasmViolation>) -> Result<(), PrepareError> {
         // Only allow `env::radix_engine` import
         for entry in self
             .module
@@ -80,9 +134,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     OBJECT_CALL_FUNCTION_NAME => {
@@ -103,9 +158,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     OBJECT_CALL_MODULE_FUNCTION_NAME => {
@@ -127,9 +183,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     OBJECT_CALL_DIRECT_FUNCTION_NAME => {
@@ -150,9 +207,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     BLUEPRINT_CALL_FUNCTION_NAME => {
@@ -175,9 +233,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     KEY_VALUE_STORE_OPEN_ENTRY_FUNCTION_NAME => {
@@ -197,9 +256,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     KEY_VALUE_ENTRY_READ_FUNCTION_NAME => {
@@ -213,9 +273,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     KEY_VALUE_ENTRY_WRITE_FUNCTION_NAME => {
@@ -229,9 +290,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     KEY_VALUE_ENTRY_REMOVE_FUNCTION_NAME => {
@@ -245,9 +307,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     KEY_VALUE_ENTRY_CLOSE_FUNCTION_NAME => {
@@ -261,9 +324,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     KEY_VALUE_STORE_REMOVE_ENTRY_FUNCTION_NAME => {
@@ -277,9 +341,33 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
+                        }
+                    }
+                    KEY_VALUE_STORE_SCAN_ENTRIES_FUNCTION_NAME => {
+                        if let TypeRef::Func(type_index) = entry.ty {
+                            if Self::function_type_matches(
+                                &self.module,
+                                type_index,
+                                vec![
+                                    ValType::I32,
+                                    ValType::I32,
+                                    ValType::I32,
+                                    ValType::I32,
+                                    ValType::I32,
+                                ],
+                                vec![ValType::I64],
+                            ) {
+                                continue;
+                            }
+
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
+                                InvalidImport::InvalidFunctionType(entry.name.to_string()),
+                            )));
+                            continue;
                         }
                     }
                     ACTOR_OPEN_FIELD_FUNCTION_NAME => {
@@ -293,9 +381,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     FIELD_ENTRY_READ_FUNCTION_NAME => {
@@ -309,9 +398,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     FIELD_ENTRY_WRITE_FUNCTION_NAME => {
@@ -325,9 +415,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     FIELD_ENTRY_CLOSE_FUNCTION_NAME => {
@@ -341,9 +432,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     ACTOR_GET_OBJECT_ID_FUNCTION_NAME => {
@@ -357,9 +449,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     ACTOR_GET_PACKAGE_ADDRESS_FUNCTION_NAME => {
@@ -373,9 +466,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     ACTOR_GET_BLUEPRINT_NAME_FUNCTION_NAME => {
@@ -389,9 +483,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
 
@@ -406,9 +501,10 @@ impl WasmModule {
                                 continue;
                             }
 
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
 
@@ -422,9 +518,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     COSTING_GET_EXECUTION_COST_UNIT_PRICE_FUNCTION_NAME => {
@@ -437,9 +534,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     COSTING_GET_FINALIZATION_COST_UNIT_LIMIT_FUNCTION_NAME => {
@@ -452,9 +550,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     COSTING_GET_FINALIZATION_COST_UNIT_PRICE_FUNCTION_NAME => {
@@ -467,9 +566,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     COSTING_GET_USD_PRICE_FUNCTION_NAME => {
@@ -482,9 +582,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     COSTING_GET_TIP_PERCENTAGE_FUNCTION_NAME => {
@@ -497,9 +598,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     COSTING_GET_FEE_BALANCE_FUNCTION_NAME => {
@@ -512,9 +614,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
 
@@ -528,9 +631,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     ADDRESS_GET_RESERVATION_ADDRESS_FUNCTION_NAME => {
@@ -543,9 +647,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     OBJECT_GLOBALIZE_FUNCTION_NAME => {
@@ -565,9 +670,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     KEY_VALUE_STORE_NEW_FUNCTION_NAME => {
@@ -580,9 +686,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     OBJECT_INSTANCE_OF_FUNCTION_NAME => {
@@ -602,9 +709,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     OBJECT_GET_BLUEPRINT_ID_FUNCTION_NAME => {
@@ -617,9 +725,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     OBJECT_GET_OUTER_OBJECT_FUNCTION_NAME => {
@@ -632,9 +741,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     ACTOR_EMIT_EVENT_FUNCTION_NAME => {
@@ -653,9 +763,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     SYS_LOG_FUNCTION_NAME => {
@@ -668,9 +779,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     SYS_BECH32_ENCODE_ADDRESS_FUNCTION_NAME => {
@@ -683,9 +795,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     SYS_PANIC_FUNCTION_NAME => {
@@ -698,9 +811,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     SYS_GET_TRANSACTION_HASH_FUNCTION_NAME => {
@@ -713,9 +827,10 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     SYS_GENERATE_RUID_FUNCTION_NAME => {
@@ -728,135 +843,177 @@ impl WasmModule {
                             ) {
                                 continue;
                             }
-                            return Err(PrepareError::InvalidImport(
+                            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                                 InvalidImport::InvalidFunctionType(entry.name.to_string()),
-                            ));
+                            )));
+                            continue;
                         }
                     }
                     _ => {}
                 };
             }
 
-            return Err(PrepareError::InvalidImport(
+            violations.push(WasmViolation::new(PrepareError::InvalidImport(
                 InvalidImport::ImportNotAllowed(entry.name.to_string()),
-            ));
+            )));
         }
 
-        Ok(self)
+        Ok(())
     }
 
     pub fn enforce_memory_limit_and_inject_max(
         mut self,
         max_memory_size_in_pages: u32,
     ) -> Result<Self, PrepareError> {
-        // Check if memory section exists
+        let violations = self.memory_limit_violations(max_memory_size_in_pages);
+        self = self.ok_or_first_violation(violations)?;
+
+        // Inject the max memory size, if not defined
         let memory_section = self
             .module
             .memory_section()
             .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
-            .ok_or(PrepareError::InvalidMemory(
-                InvalidMemory::MissingMemorySection,
-            ))?;
-
-        // Check if there is only one memory definition
-        let mut memory = match memory_section.len() {
-            0 => Err(PrepareError::InvalidMemory(
-                InvalidMemory::NoMemoryDefinition,
-            )),
-            1 => Ok(memory_section[0]),
-            _ => Err(PrepareError::InvalidMemory(
-                InvalidMemory::TooManyMemoryDefinition,
-            )),
-        }?;
-
-        // Check the memory limits
-        if memory.initial > max_memory_size_in_pages.into() {
-            return Err(PrepareError::InvalidMemory(
-                InvalidMemory::MemorySizeLimitExceeded,
-            ));
+            .unwrap_or(vec![]);
+        if let Some(mut memory) = memory_section.first().cloned() {
+            if memory.maximum.is_none() {
+                memory.maximum = Some(max_memory_size_in_pages.into());
+                self.module
+                    .modify_memory_type(0, memory)
+                    .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?;
+            }
         }
-        if let Some(max) = memory.maximum {
-            if max > max_memory_size_in_pages.into() {
-                return Err(PrepareError::InvalidMemory(
+
+        Ok(self)
+    }
+
+    pub fn memory_limit_violations(&self, max_memory_size_in_pages: u32) -> Vec<WasmViolation> {
+        collect_violations(|violations| {
+            // Check if memory section exists
+            let memory_section = self
+                .module
+                .memory_section()
+                .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
+                .ok_or(PrepareError::InvalidMemory(
+                    InvalidMemory::MissingMemorySection,
+                ))?;
+
+            // Check if there is only one memory definition
+            let memory = match memory_section.len() {
+                0 => Err(PrepareError::InvalidMemory(
+                    InvalidMemory::NoMemoryDefinition,
+                )),
+                1 => Ok(memory_section[0]),
+                _ => Err(PrepareError::InvalidMemory(
+                    InvalidMemory::TooManyMemoryDefinition,
+                )),
+            }?;
+
+            // Check the memory limits
+            if memory.initial > max_memory_size_in_pages.into() {
+                violations.push(WasmViolation::new(PrepareError::InvalidMemory(
                     InvalidMemory::MemorySizeLimitExceeded,
-                ));
+                )));
+            } else if let Some(max) = memory.maximum {
+                if max > max_memory_size_in_pages.into() {
+                    violations.push(WasmViolation::new(PrepareError::InvalidMemory(
+                        InvalidMemory::MemorySizeLimitExceeded,
+                    )));
+                }
             }
-        } else {
-            memory.maximum = Some(max_memory_size_in_pages.into());
-            self.module
-                .modify_memory_type(0, memory)
-                .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?;
-        }
 
-        // Check if the memory is exported
-        if !self
-            .module
-            .export_section()
-            .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
-            .unwrap_or(vec![])
-            .iter()
-            .any(|e| e.kind == ExternalKind::Memory && e.name == EXPORT_MEMORY)
-        {
-            return Err(PrepareError::InvalidMemory(
-                InvalidMemory::MemoryNotExported,
-            ));
-        }
+            // Check if the memory is exported
+            if !self
+                .module
+                .export_section()
+                .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
+                .unwrap_or(vec![])
+                .iter()
+                .any(|e| e.kind == ExternalKind::Memory && e.name == EXPORT_MEMORY)
+            {
+                violations.push(WasmViolation::new(PrepareError::InvalidMemory(
+                    InvalidMemory::MemoryNotExported,
+                )));
+            }
 
-        Ok(self)
+            Ok(())
+        })
     }
 
     pub fn enforce_table_limit(self, max_initial_table_size: u32) -> Result<Self, PrepareError> {
-        let section = self
-            .module
-            .table_section()
-            .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?;
+        let violations = self.table_limit_violations(max_initial_table_size);
+        self.ok_or_first_violation(violations)
+    }
 
-        if let Some(section) = section {
-            if section.len() > 1 {
-                // Sanity check MVP rule
-                return Err(PrepareError::InvalidTable(InvalidTable::MoreThanOneTable));
-            }
+    pub fn table_limit_violations(&self, max_initial_table_size: u32) -> Vec<WasmViolation> {
+        collect_violations(|violations| {
+            let section = self
+                .module
+                .table_section()
+                .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?;
 
-            if let Some(table) = section.get(0) {
-                if table.ty.initial > max_initial_table_size {
-                    return Err(PrepareError::InvalidTable(
-                        InvalidTable::InitialTableSizeLimitExceeded,
-                    ));
+            if let Some(section) = section {
+                if section.len() > 1 {
+                    // Sanity check MVP rule
+                    return Err(PrepareError::InvalidTable(InvalidTable::MoreThanOneTable));
+                }
+
+                if let Some(table) = section.get(0) {
+                    if table.ty.initial > max_initial_table_size {
+                        violations.push(WasmViolation::new(PrepareError::InvalidTable(
+                            InvalidTable::InitialTableSizeLimitExceeded,
+                        )));
+                    }
                 }
             }
-        }
 
-        Ok(self)
+            Ok(())
+        })
     }
 
     pub fn enforce_br_table_limit(
         self,
         max_number_of_br_table_targets: u32,
     ) -> Result<Self, PrepareError> {
-        for fb in self
-            .module
-            .code_section()
-            .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
-            .unwrap_or(vec![])
-        {
-            let reader = fb
-                .get_operators_reader()
-                .map_err(|err| PrepareError::WasmParserError(err.to_string()))?;
-
-            for op in reader {
-                let inst = op.map_err(|err| PrepareError::WasmParserError(err.to_string()))?;
+        let violations = self.br_table_limit_violations(max_number_of_br_table_targets);
+        self.ok_or_first_violation(violations)
+    }
 
-                if let Operator::BrTable {
-                    targets: table_data,
-                } = inst
-                {
-                    if table_data.len() > max_number_of_br_table_targets {
-                        return Err(PrepareError::TooManyTargetsInBrTable);
+    pub fn br_table_limit_violations(
+        &self,
+        max_number_of_br_table_targets: u32,
+    ) -> Vec<WasmViolation> {
+        collect_violations(|violations| {
+            let num_imported_functions = self.num_imported_functions();
+            for (i, fb) in self
+                .module
+                .code_section()
+                .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
+                .unwrap_or(vec![])
+                .into_iter()
+                .enumerate()
+            {
+                let reader = fb
+                    .get_operators_reader()
+                    .map_err(|err| PrepareError::WasmParserError(err.to_string()))?;
+
+                for op in reader {
+                    let inst = op.map_err(|err| PrepareError::WasmParserError(err.to_string()))?;
+
+                    if let Operator::BrTable {
+                        targets: table_data,
+                    } = inst
+                    {
+                        if table_data.len() > max_number_of_br_table_targets {
+                            violations.push(WasmViolation::in_function(
+                                PrepareError::TooManyTargetsInBrTable,
+                                num_imported_functions + i as u32,
+                            ));
+                        }
                     }
                 }
             }
-        }
-        Ok(self)
+            Ok(())
+        })
     }
 
     pub fn enforce_function_limit(
@@ -865,91 +1022,135 @@ impl WasmModule {
         max_number_of_function_params: u32,
         max_number_of_function_locals: u32,
     ) -> Result<Self, PrepareError> {
-        if self.module.num_local_functions() > max_number_of_functions {
-            return Err(PrepareError::TooManyFunctions);
-        }
+        let violations = self.function_limit_violations(
+            max_number_of_functions,
+            max_number_of_function_params,
+            max_number_of_function_locals,
+        );
+        self.ok_or_first_violation(violations)
+    }
 
-        for func_idx in 0..self.module.num_local_functions() {
-            if let wasmparser::Type::Func(ty) = self
+    pub fn function_limit_violations(
+        &self,
+        max_number_of_functions: u32,
+        max_number_of_function_params: u32,
+        max_number_of_function_locals: u32,
+    ) -> Vec<WasmViolation> {
+        collect_violations(|violations| {
+            if self.module.num_local_functions() > max_number_of_functions {
+                violations.push(WasmViolation::new(PrepareError::TooManyFunctions));
+            }
+
+            for func_idx in 0..self.module.num_local_functions() {
+                if let wasmparser::Type::Func(ty) = self
+                    .module
+                    .get_type_by_func_idx(func_idx)
+                    .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
+                {
+                    if ty.params().len() > max_number_of_function_params as usize {
+                        violations.push(WasmViolation::in_function(
+                            PrepareError::TooManyFunctionParams,
+                            func_idx,
+                        ));
+                    }
+                }
+            }
+
+            let num_imported_functions = self.num_imported_functions();
+            for (i, func_body) in self
                 .module
-                .get_type_by_func_idx(func_idx)
+                .code_section()
                 .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
+                .unwrap_or(vec![])
+                .into_iter()
+                .enumerate()
             {
-                if ty.params().len() > max_number_of_function_params as usize {
-                    return Err(PrepareError::TooManyFunctionParams);
+                let local_reader = func_body
+                    .get_locals_reader()
+                    .map_err(|err| PrepareError::WasmParserError(err.to_string()))?;
+                let mut locals_count = 0;
+
+                // According to the documentation local_reader.get_count() would do the job here
+                // see: https://docs.rs/wasmparser/latest/wasmparser/struct.LocalsReader.html#method.get_count
+                // But the description is misleading, get_count() returns the number of different types of
+                // locals (or number of LocalReader iterator items).
+                // To get the number of locals we need to iterate over LocalReader, which
+                // returns following tuple for each item:
+                //  ( u32, ValType) - where u32 is the number of locals of ValType
+                for local in local_reader.into_iter() {
+                    // Number of locals of some type
+                    let (count, _ty) =
+                        local.map_err(|err| PrepareError::WasmParserError(err.to_string()))?;
+                    locals_count = locals_count
+                        .checked_add(&count)
+                        .ok_or(PrepareError::Overflow)?;
                 }
-            }
-        }
-
-        for func_body in self
-            .module
-            .code_section()
-            .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
-            .unwrap_or(vec![])
-        {
-            let local_reader = func_body
-                .get_locals_reader()
-                .map_err(|err| PrepareError::WasmParserError(err.to_string()))?;
-            let mut locals_count = 0;
-
-            // According to the documentation local_reader.get_count() would do the job here
-            // see: https://docs.rs/wasmparser/latest/wasmparser/struct.LocalsReader.html#method.get_count
-            // But the description is misleading, get_count() returns the number of different types of
-            // locals (or number of LocalReader iterator items).
-            // To get the number of locals we need to iterate over LocalReader, which
-            // returns following tuple for each item:
-            //  ( u32, ValType) - where u32 is the number of locals of ValType
-            for local in local_reader.into_iter() {
-                // Number of locals of some type
-                let (count, _ty) =
-                    local.map_err(|err| PrepareError::WasmParserError(err.to_string()))?;
-                locals_count = locals_count
-                    .checked_add(&count)
-                    .ok_or(PrepareError::Overflow)?;
-            }
 
-            if locals_count > max_number_of_function_locals {
-                return Err(PrepareError::TooManyFunctionLocals {
-                    max: max_number_of_function_locals,
-                    actual: locals_count,
-                });
+                if locals_count > max_number_of_function_locals {
+                    violations.push(WasmViolation::in_function(
+                        PrepareError::TooManyFunctionLocals {
+                            max: max_number_of_function_locals,
+                            actual: locals_count,
+                        },
+                        num_imported_functions + i as u32,
+                    ));
+                }
             }
-        }
 
-        Ok(self)
+            Ok(())
+        })
     }
 
     pub fn enforce_export_names(self) -> Result<Self, PrepareError> {
-        // Any exported name should follow Rust Identifier specification
-        for name in &self.module.export_names {
-            syn::parse_str::<Ident>(name)
-                .map_err(|_| PrepareError::InvalidExportName(name.to_string()))?;
-        }
+        let violations = self.export_name_violations();
+        self.ok_or_first_violation(violations)
+    }
 
-        Ok(self)
+    pub fn export_name_violations(&self) -> Vec<WasmViolation> {
+        // Any exported name should follow Rust Identifier specification
+        self.module
+            .export_names
+            .iter()
+            .filter(|name| syn::parse_str::<Ident>(name).is_err())
+            .map(|name| WasmViolation::new(PrepareError::InvalidExportName(name.to_string())))
+            .collect()
     }
 
     pub fn enforce_global_limit(self, max_number_of_globals: u32) -> Result<Self, PrepareError> {
+        let violations = self.global_limit_violations(max_number_of_globals);
+        self.ok_or_first_violation(violations)
+    }
+
+    pub fn global_limit_violations(&self, max_number_of_globals: u32) -> Vec<WasmViolation> {
         if self.module.num_local_globals() > max_number_of_globals {
-            return Err(PrepareError::TooManyGlobals {
+            vec![WasmViolation::new(PrepareError::TooManyGlobals {
                 max: max_number_of_globals,
                 current: self.module.num_local_globals(),
-            });
+            })]
+        } else {
+            vec![]
         }
-
-        Ok(self)
     }
 
     pub fn enforce_export_constraints<'a, I: Iterator<Item = &'a BlueprintDefinitionInit>>(
         self,
         blueprints: I,
     ) -> Result<Self, PrepareError> {
-        let exports = self
-            .module
-            .export_section()
-            .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?;
+        let violations = self.export_constraint_violations(blueprints);
+        self.ok_or_first_violation(violations)
+    }
+
+    pub fn export_constraint_violations<'a, I: Iterator<Item = &'a BlueprintDefinitionInit>>(
+        &self,
+        blueprints: I,
+    ) -> Vec<WasmViolation> {
+        collect_violations(|violations| {
+            let exports = self
+                .module
+                .export_section()
+                .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
+                .ok_or(PrepareError::NoExportSection)?;
 
-        if let Some(exports) = exports {
             for blueprint_def_init in blueprints {
                 for export_name in blueprint_def_init.schema.exports() {
                     if !exports.iter().any(|x| {
@@ -966,17 +1167,15 @@ impl WasmModule {
                             }
                         }
                     }) {
-                        return Err(PrepareError::MissingExport {
+                        violations.push(WasmViolation::new(PrepareError::MissingExport {
                             export_name: export_name.to_string(),
-                        });
+                        }));
                     }
                 }
             }
 
-            Ok(self)
-        } else {
-            Err(PrepareError::NoExportSection)
-        }
+            Ok(())
+        })
     }
 
     pub fn inject_instruction_metering<R: Rules>(
@@ -1005,6 +1204,17 @@ impl WasmModule {
         Ok(self)
     }
 
+    /// Injects a counter into each basic block of the module, see [`inject_coverage_counters`].
+    ///
+    /// Returns the instrumented module and the code section offsets of the counted blocks.
+    #[cfg(feature = "coverage")]
+    pub fn inject_coverage_counters(self) -> Result<(Self, Vec<u32>), PrepareError> {
+        let (code, block_offsets) = inject_coverage_counters(&self.module.bytes())?;
+        let module =
+            ModuleInfo::new(&code).map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?;
+        Ok((Self { module }, block_offsets))
+    }
+
     pub fn ensure_instantiatable(self) -> Result<Self, PrepareError> {
         // During instantiation time, the following procedures are applied:
 
@@ -1060,6 +1270,50 @@ impl WasmModule {
         Ok((code, function_exports))
     }
 
+    pub fn num_imported_functions(&self) -> u32 {
+        (self.module.function_map.len() as u32).saturating_sub(self.module.num_local_functions())
+    }
+
+    /// Returns the sizes (in bytes) of the bodies of local functions, by function index.
+    pub fn function_body_sizes(&self) -> Result<Vec<(u32, usize)>, PrepareError> {
+        let num_imported_functions = self.num_imported_functions();
+        Ok(self
+            .module
+            .code_section()
+            .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
+            .unwrap_or(vec![])
+            .into_iter()
+            .enumerate()
+            .map(|(i, func_body)| {
+                let range = func_body.range();
+                (num_imported_functions + i as u32, range.end - range.start)
+            })
+            .collect())
+    }
+
+    /// Returns the names of exported functions, by function index.
+    pub fn exported_function_names(&self) -> Result<IndexMap<u32, String>, PrepareError> {
+        let mut names = index_map_new();
+        for export in self
+            .module
+            .export_section()
+            .map_err(|err| PrepareError::ModuleInfoError(err.to_string()))?
+            .unwrap_or(vec![])
+        {
+            if let ExternalKind::Func = export.kind {
+                names.insert(export.index, export.name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    fn ok_or_first_violation(self, violations: Vec<WasmViolation>) -> Result<Self, PrepareError> {
+        match violations.into_iter().next() {
+            Some(violation) => Err(violation.error),
+            None => Ok(self),
+        }
+    }
+
     fn function_matches(
         module: &ModuleInfo,
         func_index: usize,
@@ -1252,6 +1506,7 @@ mod tests {
             KEY_VALUE_ENTRY_REMOVE_FUNCTION_NAME,
             KEY_VALUE_ENTRY_CLOSE_FUNCTION_NAME,
             KEY_VALUE_STORE_REMOVE_ENTRY_FUNCTION_NAME,
+            KEY_VALUE_STORE_SCAN_ENTRIES_FUNCTION_NAME,
             ACTOR_OPEN_FIELD_FUNCTION_NAME,
             FIELD_ENTRY_READ_FUNCTION_NAME,
             FIELD_ENTRY_WRITE_FUNCTION_NAME,
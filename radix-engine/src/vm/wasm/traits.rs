@@ -1,6 +1,8 @@
 use crate::errors::InvokeError;
 use crate::types::*;
 use crate::vm::wasm::errors::*;
+#[cfg(not(feature = "alloc"))]
+use crate::vm::wasm::module_disk_cache::WasmModuleDiskCache;
 use radix_engine_interface::api::actor_api::EventFlags;
 use radix_engine_interface::api::ActorRefHandle;
 use radix_engine_interface::blueprints::package::CodeHash;
@@ -105,6 +107,13 @@ pub trait WasmRuntime {
         key: Vec<u8>,
     ) -> Result<Buffer, InvokeError<WasmRuntimeError>>;
 
+    fn key_value_store_scan_entries(
+        &mut self,
+        node_id: Vec<u8>,
+        cursor: Vec<u8>,
+        limit: u32,
+    ) -> Result<Buffer, InvokeError<WasmRuntimeError>>;
+
     fn instance_of(
         &mut self,
         object_id: Vec<u8>,
@@ -217,6 +226,9 @@ pub trait WasmInstance {
         args: Vec<Buffer>,
         runtime: &mut Box<dyn WasmRuntime + 'r>,
     ) -> Result<Vec<u8>, InvokeError<WasmRuntimeError>>;
+
+    /// Reads the current value of an exported `i64` global, if there is one with the name.
+    fn get_exported_global_i64(&mut self, name: &str) -> Option<i64>;
 }
 
 /// A Scrypto WASM engine validates, instruments and runs Scrypto modules.
@@ -227,4 +239,19 @@ pub trait WasmEngine {
     ///
     /// The code must have been validated and instrumented!
     fn instantiate(&self, code_hash: CodeHash, instrumented_code: &[u8]) -> Self::WasmInstance;
+
+    /// Whether the engine can persist its prepared modules to a [`WasmModuleDiskCache`].
+    ///
+    /// Only Wasmer can serialize its prepared modules, so Wasmi (the default engine) can't. The
+    /// instrumented code is persisted for all engines (see [`crate::vm::ScryptoVm`]).
+    #[cfg(not(feature = "alloc"))]
+    fn supports_module_disk_cache(&self) -> bool {
+        false
+    }
+
+    /// Sets a cache which persists prepared modules to disk, to speed up cold starts.
+    ///
+    /// Engines which don't support it (see [`Self::supports_module_disk_cache`]) ignore the cache.
+    #[cfg(not(feature = "alloc"))]
+    fn set_module_disk_cache(&mut self, _module_disk_cache: WasmModuleDiskCache) {}
 }
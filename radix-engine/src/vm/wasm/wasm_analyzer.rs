@@ -0,0 +1,265 @@
+use crate::system::system_modules::costing::FeeTable;
+use crate::types::*;
+use crate::vm::wasm::*;
+use radix_engine_interface::blueprints::package::BlueprintDefinitionInit;
+use wasmparser::{Name, NameSectionReader, Parser, Payload};
+
+/// The number of the largest functions reported as size hotspots.
+pub const MAX_NUMBER_OF_SIZE_HOTSPOTS: usize = 10;
+
+/// A report of all violations of the validation rules found in a WASM code,
+/// produced by [`WasmValidator::analyze`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmValidationReport {
+    /// The size of the code, in bytes.
+    pub code_size: usize,
+    /// The size of the instrumented code, in bytes.
+    /// Only available if the code passes validation.
+    pub instrumented_code_size: Option<usize>,
+    pub violations: Vec<WasmFunctionViolation>,
+    /// The largest functions of the code, sorted by size in descending order.
+    pub size_hotspots: Vec<WasmFunctionSize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmFunctionViolation {
+    pub violation: WasmViolation,
+    /// The name of the function the violation was found in, if known.
+    pub function_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmFunctionSize {
+    pub function_index: u32,
+    pub function_name: Option<String>,
+    /// The size of the function body, in bytes.
+    pub size: usize,
+}
+
+impl WasmValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns the estimated cost units of instantiating the code.
+    ///
+    /// The cost is charged on the instrumented code, so the estimation is exact only if the
+    /// code passes validation. Otherwise, it's based on the size of the original code.
+    pub fn instantiation_cost(&self) -> u32 {
+        FeeTable::new()
+            .instantiate_wasm_code_cost(self.instrumented_code_size.unwrap_or(self.code_size))
+    }
+}
+
+impl WasmValidator {
+    /// Runs all the checks of [`Self::validate`], reporting all violations found instead of
+    /// failing on the first one, along with instantiation cost and size hotspots.
+    ///
+    /// Returns an error only if the code can't be parsed.
+    pub fn analyze<'a, I: Iterator<Item = &'a BlueprintDefinitionInit> + Clone>(
+        &self,
+        code: &[u8],
+        blueprints: I,
+    ) -> Result<WasmValidationReport, PrepareError> {
+        let module = WasmModule::init(code)?;
+
+        let mut violations = Vec::new();
+        violations.extend(module.start_function_violations());
+        violations.extend(module.import_violations());
+        violations.extend(module.export_name_violations());
+        violations.extend(module.memory_limit_violations(self.max_memory_size_in_pages));
+        violations.extend(module.table_limit_violations(self.max_initial_table_size));
+        violations.extend(module.br_table_limit_violations(self.max_number_of_br_table_targets));
+        violations.extend(module.function_limit_violations(
+            self.max_number_of_functions,
+            self.max_number_of_function_params,
+            self.max_number_of_function_locals,
+        ));
+        violations.extend(module.global_limit_violations(self.max_number_of_globals));
+        violations.extend(module.export_constraint_violations(blueprints.clone()));
+
+        // Instrumentation and compilation are only attempted if all the checks above pass,
+        // as they do during validation.
+        let instrumented_code_size = if violations.is_empty() {
+            match self.validate(code, blueprints) {
+                Ok((instrumented_code, _)) => Some(instrumented_code.len()),
+                Err(error) => {
+                    violations.push(WasmViolation::new(error));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let function_names = function_names(&module, code);
+        let function_name = |function_index: u32| function_names.get(&function_index).cloned();
+
+        let violations = violations
+            .into_iter()
+            .map(|violation| WasmFunctionViolation {
+                function_name: violation.function_index.and_then(function_name),
+                violation,
+            })
+            .collect();
+
+        let mut function_sizes = module.function_body_sizes().unwrap_or_default();
+        function_sizes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let size_hotspots = function_sizes
+            .into_iter()
+            .take(MAX_NUMBER_OF_SIZE_HOTSPOTS)
+            .map(|(function_index, size)| WasmFunctionSize {
+                function_index,
+                function_name: function_name(function_index),
+                size,
+            })
+            .collect();
+
+        Ok(WasmValidationReport {
+            code_size: code.len(),
+            instrumented_code_size,
+            violations,
+            size_hotspots,
+        })
+    }
+}
+
+/// Resolves function names from the `name` custom section, if present, falling back to
+/// export names (the `name` section is usually stripped from release builds).
+fn function_names(module: &WasmModule, code: &[u8]) -> IndexMap<u32, String> {
+    let mut names = index_map_new();
+    for payload in Parser::new(0).parse_all(code) {
+        if let Ok(Payload::CustomSection(reader)) = payload {
+            if reader.name() != "name" {
+                continue;
+            }
+            for name in NameSectionReader::new(reader.data(), reader.data_offset()) {
+                if let Ok(Name::Function(name_map)) = name {
+                    for naming in name_map.into_iter().flatten() {
+                        names.insert(naming.index, naming.name.to_string());
+                    }
+                }
+            }
+        }
+    }
+    for (function_index, name) in module.exported_function_names().unwrap_or_default() {
+        names.entry(function_index).or_insert(name);
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radix_engine_interface::blueprints::package::PackageDefinition;
+    use wabt::wat2wasm;
+
+    #[test]
+    fn test_analyze_reports_all_violations() {
+        let code = wat2wasm(
+            r#"
+            (module
+                (func $Test_f (param $0 i64) (result i64)
+                    (local i32 i32 i32)
+                    (block (br_table 0 0 0 0 (i32.const 0)))
+                    (i64.const 0)
+                )
+                (func $helper)
+                (memory $0 100)
+                (export "memory" (memory $0))
+                (export "Test_f" (func $Test_f))
+                (export "helper" (func $helper))
+                (start $helper)
+            )
+            "#,
+        )
+        .unwrap();
+        let validator = WasmValidator {
+            max_memory_size_in_pages: 64,
+            max_number_of_br_table_targets: 2,
+            max_number_of_function_locals: 2,
+            ..Default::default()
+        };
+
+        let report = validator
+            .analyze(
+                &code,
+                PackageDefinition::new_single_function_test_definition("Test", "f")
+                    .blueprints
+                    .values(),
+            )
+            .unwrap();
+
+        assert!(!report.is_valid());
+        assert_eq!(report.instrumented_code_size, None);
+        assert_eq!(
+            report
+                .violations
+                .iter()
+                .map(|x| (
+                    x.violation.error.clone(),
+                    x.violation.function_index,
+                    x.function_name.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    PrepareError::StartFunctionNotAllowed,
+                    Some(1),
+                    Some("helper".to_string())
+                ),
+                (
+                    PrepareError::InvalidMemory(InvalidMemory::MemorySizeLimitExceeded),
+                    None,
+                    None
+                ),
+                (
+                    PrepareError::TooManyTargetsInBrTable,
+                    Some(0),
+                    Some("Test_f".to_string())
+                ),
+                (
+                    PrepareError::TooManyFunctionLocals { max: 2, actual: 3 },
+                    Some(0),
+                    Some("Test_f".to_string())
+                ),
+            ]
+        );
+        assert_eq!(report.size_hotspots.len(), 2);
+        assert_eq!(report.size_hotspots[0].function_index, 0);
+    }
+
+    #[test]
+    fn test_analyze_valid_code() {
+        let code = wat2wasm(
+            r#"
+            (module
+                (func $Test_f (param $0 i64) (result i64)
+                    (i64.const 0)
+                )
+                (memory $0 1)
+                (export "memory" (memory $0))
+                (export "Test_f" (func $Test_f))
+            )
+            "#,
+        )
+        .unwrap();
+
+        let report = WasmValidator::default()
+            .analyze(
+                &code,
+                PackageDefinition::new_single_function_test_definition("Test", "f")
+                    .blueprints
+                    .values(),
+            )
+            .unwrap();
+
+        assert!(report.is_valid());
+        let instrumented_code_size = report.instrumented_code_size.unwrap();
+        assert!(instrumented_code_size > code.len());
+        assert_eq!(
+            report.instantiation_cost(),
+            FeeTable::new().instantiate_wasm_code_cost(instrumented_code_size)
+        );
+    }
+}
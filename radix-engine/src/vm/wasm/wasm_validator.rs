@@ -1,6 +1,8 @@
 use crate::types::*;
 use crate::vm::wasm::*;
 use radix_engine_interface::blueprints::package::BlueprintDefinitionInit;
+#[cfg(not(feature = "alloc"))]
+use radix_engine_interface::blueprints::package::CodeHash;
 
 pub struct WasmValidator {
     pub max_memory_size_in_pages: u32,
@@ -54,6 +56,66 @@ impl WasmValidator {
             .ensure_compilable()?
             .to_bytes()
     }
+
+    /// Validates the code like [`Self::validate`], returning the instrumented code, which is
+    /// reused from the given cache when the same code was validated against the same blueprints
+    /// and configuration before, and persisted to it otherwise.
+    #[cfg(not(feature = "alloc"))]
+    pub fn validate_with_disk_cache(
+        &self,
+        code: &[u8],
+        blueprints: &IndexMap<String, BlueprintDefinitionInit>,
+        disk_cache: &WasmModuleDiskCache,
+    ) -> Result<Vec<u8>, PrepareError> {
+        // The instrumented code only depends on the code and the configuration, but whether the
+        // code is valid also depends on the blueprints, so a valid entry must match all of them.
+        let code_hash = CodeHash(hash(code));
+        let validation_input = scrypto_encode(&(
+            [
+                self.max_memory_size_in_pages,
+                self.max_initial_table_size,
+                self.max_number_of_br_table_targets,
+                self.max_number_of_functions,
+                self.max_number_of_function_params,
+                self.max_number_of_function_locals,
+                self.max_number_of_globals,
+            ],
+            format!("{:?}", self.instrumenter_config),
+            blueprints,
+        ))
+        .expect("Blueprint definitions are encodable");
+
+        if let Some(instrumented_code) =
+            disk_cache.load_instrumented_code(&code_hash, &validation_input)
+        {
+            return Ok(instrumented_code);
+        }
+        let (instrumented_code, _) = self.validate(code, blueprints.values())?;
+        disk_cache.store_instrumented_code(
+            &code_hash,
+            &validation_input,
+            instrumented_code.clone(),
+        );
+        Ok(instrumented_code)
+    }
+
+    /// Instruments already validated code for coverage reporting, with the block counters
+    /// injected before the metering, so that they don't count the metering code.
+    ///
+    /// Returns the instrumented code and the code section offsets of the counted blocks.
+    #[cfg(feature = "coverage")]
+    pub fn instrument_for_coverage(
+        &self,
+        code: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u32>), PrepareError> {
+        let (module, block_offsets) = WasmModule::init(code)?.inject_coverage_counters()?;
+        let (instrumented_code, _) = module
+            .enforce_memory_limit_and_inject_max(self.max_memory_size_in_pages)?
+            .inject_instruction_metering(&self.instrumenter_config)?
+            .inject_stack_metering(self.instrumenter_config.max_stack_size())?
+            .to_bytes()?;
+        Ok((instrumented_code, block_offsets))
+    }
 }
 
 #[cfg(test)]
@@ -2,6 +2,8 @@ use crate::errors::InvokeError;
 use crate::types::*;
 use crate::vm::wasm::constants::*;
 use crate::vm::wasm::errors::*;
+#[cfg(not(feature = "alloc"))]
+use crate::vm::wasm::module_disk_cache::WasmModuleDiskCache;
 use crate::vm::wasm::traits::*;
 use radix_engine_interface::api::actor_api::EventFlags;
 use radix_engine_interface::blueprints::package::CodeHash;
@@ -83,8 +85,15 @@ pub struct WasmerEngine {
     modules_cache: moka::sync::Cache<CodeHash, Arc<WasmerModule>>,
     #[cfg(feature = "radix_engine_fuzzing")]
     modules_cache: usize,
+    #[cfg(not(feature = "alloc"))]
+    module_disk_cache: Option<WasmModuleDiskCache>,
 }
 
+/// Identifies the compiler of the modules persisted to the disk cache, as compiled modules
+/// can only be loaded by the same version of Wasmer and the same compiler.
+#[cfg(not(feature = "alloc"))]
+const WASMER_MODULE_DISK_CACHE_ENGINE_ID: &'static str = "wasmer-2.2.1-singlepass";
+
 pub fn read_memory(instance: &Instance, ptr: u32, len: u32) -> Result<Vec<u8>, WasmRuntimeError> {
     let ptr = ptr as usize;
     let len = len as usize;
@@ -417,6 +426,25 @@ impl WasmerModule {
                 .map(|buffer| buffer.0)
         }
 
+        pub fn key_value_store_scan_entries(
+            env: &WasmerInstanceEnv,
+            node_id_ptr: u32,
+            node_id_len: u32,
+            cursor_ptr: u32,
+            cursor_len: u32,
+            limit: u32,
+        ) -> Result<u64, InvokeError<WasmRuntimeError>> {
+            let (instance, runtime) = grab_runtime!(env);
+
+            runtime
+                .key_value_store_scan_entries(
+                    read_memory(&instance, node_id_ptr, node_id_len)?,
+                    read_memory(&instance, cursor_ptr, cursor_len)?,
+                    limit,
+                )
+                .map(|buffer| buffer.0)
+        }
+
         pub fn key_value_entry_read(
             env: &WasmerInstanceEnv,
             handle: u32,
@@ -751,6 +779,7 @@ impl WasmerModule {
                 KEY_VALUE_STORE_NEW_FUNCTION_NAME => Function::new_native_with_env(self.module.store(), env.clone(), key_value_store_new),
                 KEY_VALUE_STORE_OPEN_ENTRY_FUNCTION_NAME => Function::new_native_with_env(self.module.store(), env.clone(), key_value_store_open_entry),
                 KEY_VALUE_STORE_REMOVE_ENTRY_FUNCTION_NAME => Function::new_native_with_env(self.module.store(), env.clone(), key_value_store_remove_entry),
+                KEY_VALUE_STORE_SCAN_ENTRIES_FUNCTION_NAME => Function::new_native_with_env(self.module.store(), env.clone(), key_value_store_scan_entries),
                 KEY_VALUE_ENTRY_READ_FUNCTION_NAME => Function::new_native_with_env(self.module.store(), env.clone(), key_value_entry_read),
                 KEY_VALUE_ENTRY_WRITE_FUNCTION_NAME => Function::new_native_with_env(self.module.store(), env.clone(), key_value_entry_write),
                 KEY_VALUE_ENTRY_REMOVE_FUNCTION_NAME => Function::new_native_with_env(self.module.store(), env.clone(), key_value_entry_remove),
@@ -843,6 +872,14 @@ impl WasmInstance for WasmerInstance {
             Err(InvokeError::SelfError(WasmRuntimeError::InvalidWasmPointer))
         }
     }
+
+    fn get_exported_global_i64(&mut self, name: &str) -> Option<i64> {
+        self.instance
+            .exports
+            .get_global(name)
+            .ok()
+            .and_then(|global| global.get().i64())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -882,8 +919,46 @@ impl WasmerEngine {
         Self {
             store: Store::new(&Universal::new(compiler).engine()),
             modules_cache,
+            #[cfg(not(feature = "alloc"))]
+            module_disk_cache: None,
         }
     }
+
+    #[allow(unused_variables)]
+    fn compile_module(&self, code_hash: CodeHash, instrumented_code: &[u8]) -> Module {
+        #[cfg(not(feature = "alloc"))]
+        if let Some(disk_cache) = &self.module_disk_cache {
+            if let Some(serialized) = disk_cache.load(
+                WASMER_MODULE_DISK_CACHE_ENGINE_ID,
+                &code_hash,
+                instrumented_code,
+            ) {
+                // SAFETY: the entry was written by `Module::serialize` with the same engine id,
+                // and its integrity has been checked by the disk cache. The cache directory must
+                // only be writable by trusted parties.
+                if let Ok(module) = unsafe { Module::deserialize(&self.store, &serialized[..]) } {
+                    return module;
+                }
+            }
+        }
+
+        let module =
+            Module::new(&self.store, instrumented_code).expect("Failed to parse WASM module");
+
+        #[cfg(not(feature = "alloc"))]
+        if let Some(disk_cache) = &self.module_disk_cache {
+            if let Ok(serialized) = module.serialize() {
+                disk_cache.store(
+                    WASMER_MODULE_DISK_CACHE_ENGINE_ID,
+                    &code_hash,
+                    instrumented_code,
+                    serialized,
+                );
+            }
+        }
+
+        module
+    }
 }
 
 impl WasmEngine for WasmerEngine {
@@ -905,8 +980,7 @@ impl WasmEngine for WasmerEngine {
         }
 
         let new_module = Arc::new(WasmerModule {
-            module: Module::new(&self.store, instrumented_code)
-                .expect("Failed to parse WASM module"),
+            module: self.compile_module(code_hash, instrumented_code),
             code_size_bytes: instrumented_code.len(),
         });
 
@@ -922,4 +996,14 @@ impl WasmEngine for WasmerEngine {
 
         new_module.instantiate()
     }
+
+    #[cfg(not(feature = "alloc"))]
+    fn supports_module_disk_cache(&self) -> bool {
+        true
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn set_module_disk_cache(&mut self, module_disk_cache: WasmModuleDiskCache) {
+        self.module_disk_cache = Some(module_disk_cache);
+    }
 }
@@ -502,6 +502,23 @@ fn key_value_store_remove(
         .map(|buffer| buffer.0)
 }
 
+fn key_value_store_scan(
+    mut caller: Caller<'_, HostState>,
+    node_id_ptr: u32,
+    node_id_len: u32,
+    cursor_ptr: u32,
+    cursor_len: u32,
+    limit: u32,
+) -> Result<u64, InvokeError<WasmRuntimeError>> {
+    let (memory, runtime) = grab_runtime!(caller);
+    let node_id = read_memory(caller.as_context_mut(), memory, node_id_ptr, node_id_len)?;
+    let cursor = read_memory(caller.as_context_mut(), memory, cursor_ptr, cursor_len)?;
+
+    runtime
+        .key_value_store_scan_entries(node_id, cursor, limit)
+        .map(|buffer| buffer.0)
+}
+
 fn lock_field(
     caller: Caller<'_, HostState>,
     object_handle: u32,
@@ -1092,6 +1109,27 @@ impl WasmiModule {
             },
         );
 
+        let host_key_value_store_scan = Func::wrap(
+            store.as_context_mut(),
+            |caller: Caller<'_, HostState>,
+             node_id_ptr: u32,
+             node_id_len: u32,
+             cursor_ptr: u32,
+             cursor_len: u32,
+             limit: u32|
+             -> Result<u64, Trap> {
+                key_value_store_scan(
+                    caller,
+                    node_id_ptr,
+                    node_id_len,
+                    cursor_ptr,
+                    cursor_len,
+                    limit,
+                )
+                .map_err(|e| e.into())
+            },
+        );
+
         let host_lock_field = Func::wrap(
             store.as_context_mut(),
             |caller: Caller<'_, HostState>,
@@ -1335,6 +1373,11 @@ impl WasmiModule {
             KEY_VALUE_STORE_REMOVE_ENTRY_FUNCTION_NAME,
             host_key_value_store_remove
         );
+        linker_define!(
+            linker,
+            KEY_VALUE_STORE_SCAN_ENTRIES_FUNCTION_NAME,
+            host_key_value_store_scan
+        );
 
         linker_define!(linker, FIELD_ENTRY_READ_FUNCTION_NAME, host_field_lock_read);
         linker_define!(
@@ -1553,6 +1596,13 @@ impl WasmInstance for WasmiInstance {
             _ => Err(InvokeError::SelfError(WasmRuntimeError::InvalidWasmPointer)),
         }
     }
+
+    fn get_exported_global_i64(&mut self, name: &str) -> Option<i64> {
+        self.instance
+            .get_export(self.store.as_context(), name)
+            .and_then(Extern::into_global)
+            .and_then(|global| i64::try_from(global.get(self.store.as_context())).ok())
+    }
 }
 
 #[derive(Debug, Clone)]
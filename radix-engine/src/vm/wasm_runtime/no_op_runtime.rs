@@ -161,6 +161,15 @@ impl<'a> WasmRuntime for NoOpWasmRuntime<'a> {
         Err(InvokeError::SelfError(WasmRuntimeError::NotImplemented))
     }
 
+    fn key_value_store_scan_entries(
+        &mut self,
+        node_id: Vec<u8>,
+        cursor: Vec<u8>,
+        limit: u32,
+    ) -> Result<Buffer, InvokeError<WasmRuntimeError>> {
+        Err(InvokeError::SelfError(WasmRuntimeError::NotImplemented))
+    }
+
     fn actor_open_field(
         &mut self,
         object_handle: u32,
@@ -309,6 +309,25 @@ where
         self.allocate_buffer(rtn)
     }
 
+    fn key_value_store_scan_entries(
+        &mut self,
+        node_id: Vec<u8>,
+        cursor: Vec<u8>,
+        limit: u32,
+    ) -> Result<Buffer, InvokeError<WasmRuntimeError>> {
+        let node_id = NodeId(
+            TryInto::<[u8; NodeId::LENGTH]>::try_into(node_id.as_ref())
+                .map_err(|_| WasmRuntimeError::InvalidNodeId)?,
+        );
+        let cursor = scrypto_decode::<Option<Vec<u8>>>(&cursor)
+            .map_err(WasmRuntimeError::InvalidKeyValueStoreCursor)?;
+        let page = self
+            .api
+            .key_value_store_scan_entries(&node_id, cursor, limit)?;
+        let buffer = scrypto_encode(&page).expect("Failed to encode key value store page");
+        self.allocate_buffer(buffer)
+    }
+
     fn actor_open_field(
         &mut self,
         object_handle: u32,
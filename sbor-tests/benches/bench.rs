@@ -1,13 +1,48 @@
 #[macro_use]
 extern crate bencher;
-use bencher::Bencher;
-use sbor::{basic_decode, basic_encode};
+use bencher::{black_box, Bencher};
+use sbor::{basic_decode, basic_encode, BasicLazyValue, BasicValue, BASIC_SBOR_V1_MAX_DEPTH};
 use sbor_tests::adapter;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 mod data;
 
 const REPEAT: usize = 1000;
 
+/// Counts allocations, so that the decoding approaches can be compared on allocations as well
+/// as on time.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Prints the number of allocations (including reallocations) made by one iteration.
+fn report_allocations<T>(name: &str, f: impl Fn() -> T) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!("{}: {} allocations per iteration", name, allocations);
+}
+
 fn encode_simple_json(b: &mut Bencher) {
     let t = data::get_simple_dataset(REPEAT);
     b.iter(|| adapter::json_encode(&t));
@@ -41,6 +76,42 @@ fn decode_simple_sbor(b: &mut Bencher) {
     b.iter(|| basic_decode::<data::SimpleStruct>(&bytes));
 }
 
+fn read_fields_from_sbor_value(bytes: &[u8]) -> (BasicValue, BasicValue) {
+    let value = basic_decode::<BasicValue>(bytes).unwrap();
+    match value {
+        BasicValue::Tuple { fields } => (fields[1].clone(), fields[2].clone()),
+        _ => unreachable!(),
+    }
+}
+
+fn read_fields_from_sbor_lazy_value(bytes: &[u8]) -> (usize, usize) {
+    // Validates the whole payload, but only decodes the string and bytes fields, as slices
+    let value = BasicLazyValue::from_payload(bytes, BASIC_SBOR_V1_MAX_DEPTH).unwrap();
+    let mut fields = value.children();
+    let _number = fields.next();
+    let string = fields.next().and_then(|v| v.as_str()).unwrap();
+    let bytes = fields.next().and_then(|v| v.as_bytes()).unwrap();
+    (string.len(), bytes.len())
+}
+
+fn decode_simple_sbor_value(b: &mut Bencher) {
+    let t = data::get_simple_dataset(REPEAT);
+    let bytes = basic_encode(&t).unwrap();
+    report_allocations("decode_simple_sbor_value", || {
+        read_fields_from_sbor_value(&bytes)
+    });
+    b.iter(|| read_fields_from_sbor_value(&bytes));
+}
+
+fn decode_simple_sbor_lazy_value(b: &mut Bencher) {
+    let t = data::get_simple_dataset(REPEAT);
+    let bytes = basic_encode(&t).unwrap();
+    report_allocations("decode_simple_sbor_lazy_value", || {
+        read_fields_from_sbor_lazy_value(&bytes)
+    });
+    b.iter(|| read_fields_from_sbor_lazy_value(&bytes));
+}
+
 benchmark_group!(
     encode_simple,
     encode_simple_json,
@@ -53,4 +124,9 @@ benchmark_group!(
     decode_simple_bincode,
     decode_simple_sbor,
 );
-benchmark_main!(encode_simple, decode_simple);
+benchmark_group!(
+    decode_simple_fields,
+    decode_simple_sbor_value,
+    decode_simple_sbor_lazy_value,
+);
+benchmark_main!(encode_simple, decode_simple, decode_simple_fields);
@@ -28,6 +28,7 @@ impl CustomValue<NoCustomValueKind> for NoCustomValue {
 pub type BasicEncoder<'a> = VecEncoder<'a, NoCustomValueKind>;
 pub type BasicDecoder<'a> = VecDecoder<'a, NoCustomValueKind>;
 pub type BasicTraverser<'a> = VecTraverser<'a, NoCustomTraversal>;
+pub type BasicLazyValue<'a> = LazyValue<'a, NoCustomExtension>;
 pub type BasicValue = Value<NoCustomValueKind, NoCustomValue>;
 pub type BasicValueKind = ValueKind<NoCustomValueKind>;
 
@@ -291,6 +292,28 @@ impl FormattableCustomExtension for NoCustomExtension {
     }
 }
 
+impl ParseableCustomExtension for NoCustomExtension {
+    type CustomValue = NoCustomValue;
+    type CustomParseContext<'a> = ();
+
+    fn parse_custom_value_content(
+        _: &Self::CustomParseContext<'_>,
+        custom_value_kind: Self::CustomValueKind,
+        _: &str,
+    ) -> Result<Self::CustomValue, String> {
+        match custom_value_kind {}
+    }
+}
+
+impl ExportableCustomExtension for NoCustomExtension {
+    fn custom_string_format(
+        _: &Schema<Self::CustomSchema>,
+        _: LocalTypeId,
+    ) -> Option<CustomStringFormat> {
+        unreachable!("No custom type kinds exist")
+    }
+}
+
 impl ValidatableCustomExtension<()> for NoCustomExtension {
     fn apply_validation_for_custom_value<'de>(
         _: &Schema<Self::CustomSchema>,
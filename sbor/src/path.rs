@@ -1,5 +1,6 @@
 use crate::rust::vec;
 use crate::rust::vec::Vec;
+use crate::traversal::LazyValue;
 use crate::value::Value;
 use crate::CustomValueKind;
 use crate::*;
@@ -57,6 +58,14 @@ impl SborPath {
         let rel_path = ValueRetriever(&self.0);
         rel_path.get_from_mut(value)
     }
+
+    pub fn get_from_lazy_value<'de, E: CustomExtension>(
+        &self,
+        value: LazyValue<'de, E>,
+    ) -> Option<LazyValue<'de, E>> {
+        let rel_path = ValueRetriever(&self.0);
+        rel_path.get_from_lazy(value)
+    }
 }
 
 /// Helper structure which helps in retrieving a value given a root value and sbor path
@@ -110,6 +119,28 @@ impl<'a> ValueRetriever<'a> {
         }
     }
 
+    fn get_from_lazy<'de, E: CustomExtension>(
+        self,
+        value: LazyValue<'de, E>,
+    ) -> Option<LazyValue<'de, E>> {
+        let Some((index, next_path)) = self.advance() else {
+            return Some(value);
+        };
+
+        match value.value_kind() {
+            // Map children are flattened into key, value pairs
+            ValueKind::Map => {
+                let (key_or_value, next_path) = next_path.advance()?;
+                if key_or_value > 1 {
+                    return None;
+                }
+                let child = value.child(index.checked_mul(2)?.checked_add(key_or_value)?)?;
+                next_path.get_from_lazy(child)
+            }
+            _ => next_path.get_from_lazy(value.child(index)?),
+        }
+    }
+
     fn get_from_mut<X: CustomValueKind, Y: CustomValue<X>>(
         self,
         value: &'a mut Value<X, Y>,
@@ -1,7 +1,9 @@
 mod contextual_display;
 mod nested_string;
+mod rustlike_parser;
 mod rustlike_string;
 
 pub use contextual_display::*;
 pub use nested_string::*;
+pub use rustlike_parser::*;
 pub use rustlike_string::*;
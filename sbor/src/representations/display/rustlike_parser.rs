@@ -0,0 +1,1329 @@
+use crate::representations::*;
+use crate::rust::prelude::*;
+use crate::rust::str::FromStr;
+use crate::traversal::*;
+use crate::*;
+
+/// The parameters for parsing text in the [`DisplayMode::RustLike`] format back into a value.
+///
+/// With a schema, the type and field names are checked against the schema, integer suffixes
+/// can be omitted and enum variants can be given by name. Without a schema, only the untyped
+/// subset of the format is accepted (as output by [`ValueDisplayParameters::Schemaless`]) -
+/// in particular, empty arrays and maps are rejected, as their element kinds can't be known.
+pub enum RustLikeParseParameters<'s, 'a, E: ParseableCustomExtension> {
+    Schemaless {
+        custom_context: E::CustomParseContext<'a>,
+        depth_limit: usize,
+    },
+    Annotated {
+        custom_context: E::CustomParseContext<'a>,
+        schema: &'s Schema<E::CustomSchema>,
+        type_id: LocalTypeId,
+        depth_limit: usize,
+    },
+}
+
+impl<'s, 'a, E: ParseableCustomExtension> RustLikeParseParameters<'s, 'a, E> {
+    fn get_context_params(&self) -> (RustLikeParseContext<'s, 'a, E>, LocalTypeId, usize) {
+        match self {
+            Self::Schemaless {
+                custom_context,
+                depth_limit,
+            } => (
+                RustLikeParseContext {
+                    schema: E::CustomSchema::empty_schema(),
+                    custom_context: *custom_context,
+                },
+                LocalTypeId::any(),
+                *depth_limit,
+            ),
+            Self::Annotated {
+                custom_context,
+                schema,
+                type_id,
+                depth_limit,
+            } => (
+                RustLikeParseContext {
+                    schema: *schema,
+                    custom_context: *custom_context,
+                },
+                *type_id,
+                *depth_limit,
+            ),
+        }
+    }
+}
+
+/// An error from parsing the RustLike format. Positions are byte offsets into the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RustLikeParseError {
+    UnexpectedEndOfInput,
+    UnexpectedCharacter {
+        position: usize,
+        character: char,
+    },
+    UnexpectedToken {
+        position: usize,
+        expected: &'static str,
+        actual: String,
+    },
+    UnterminatedString {
+        position: usize,
+    },
+    TrailingInput {
+        position: usize,
+    },
+    InvalidInteger {
+        position: usize,
+        integer: String,
+    },
+    MissingIntegerSuffix {
+        position: usize,
+    },
+    InvalidHex {
+        position: usize,
+    },
+    InvalidCustomValue {
+        position: usize,
+        value_kind: String,
+        error: String,
+    },
+    UnknownTypeName {
+        position: usize,
+        name: String,
+    },
+    MismatchingTypeName {
+        position: usize,
+        expected: String,
+        actual: String,
+    },
+    MismatchingValueKind {
+        position: usize,
+        expected: String,
+        actual: String,
+    },
+    MismatchingFieldName {
+        position: usize,
+        expected: String,
+        actual: String,
+    },
+    MismatchingFieldCount {
+        position: usize,
+        expected: usize,
+        actual: usize,
+    },
+    UnknownEnumVariant {
+        position: usize,
+        variant: String,
+    },
+    UnresolvableEmptyCollection {
+        position: usize,
+    },
+    TypeIdNotFound(LocalTypeId),
+    DepthLimitExceeded,
+    EncodeError(EncodeError),
+    SchemaMismatch(String),
+}
+
+impl fmt::Display for RustLikeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Parses a value from text in the [`DisplayMode::RustLike`] format, in either print mode.
+///
+/// If a schema is provided, the value is checked against the given type.
+pub fn parse_rustlike_value<E: ParseableCustomExtension>(
+    text: &str,
+    parameters: &RustLikeParseParameters<'_, '_, E>,
+) -> Result<ParsedValue<E>, RustLikeParseError> {
+    parse_rustlike_value_and_payload(text, parameters).map(|(value, _)| value)
+}
+
+/// Parses a value as [`parse_rustlike_value`] does, and encodes it into a payload.
+pub fn parse_rustlike_payload<E: ParseableCustomExtension>(
+    text: &str,
+    parameters: &RustLikeParseParameters<'_, '_, E>,
+) -> Result<Vec<u8>, RustLikeParseError> {
+    parse_rustlike_value_and_payload(text, parameters).map(|(_, payload)| payload)
+}
+
+fn parse_rustlike_value_and_payload<E: ParseableCustomExtension>(
+    text: &str,
+    parameters: &RustLikeParseParameters<'_, '_, E>,
+) -> Result<(ParsedValue<E>, Vec<u8>), RustLikeParseError> {
+    let (context, type_id, depth_limit) = parameters.get_context_params();
+
+    let mut parser = SyntaxParser {
+        tokens: tokenize(text)?,
+        index: 0,
+        depth_limit,
+    };
+    let node = parser.parse_node(1)?;
+    if let Some((position, _)) = parser.tokens.get(parser.index) {
+        return Err(RustLikeParseError::TrailingInput {
+            position: *position,
+        });
+    }
+    let value = context.convert(&node, type_id)?;
+
+    let mut payload = Vec::with_capacity(512);
+    VecEncoder::<E::CustomValueKind>::new(&mut payload, depth_limit)
+        .encode_payload(&value, E::PAYLOAD_PREFIX)
+        .map_err(RustLikeParseError::EncodeError)?;
+
+    // The names in the text have been checked, but the schema's validations haven't
+    let mut traverser =
+        traverse_payload_with_types::<E>(&payload, context.schema, type_id, depth_limit);
+    traverser
+        .consume_value_tree()
+        .and_then(|_| traverser.consume_end_event())
+        .map_err(RustLikeParseError::SchemaMismatch)?;
+
+    Ok((value, payload))
+}
+
+//==============
+// Tokenizing
+//==============
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'t> {
+    Identifier(&'t str),
+    /// An integer, including its type suffix (if any), eg `-3i32`
+    Integer(&'t str),
+    /// The content of a string, which isn't escaped by the printer
+    String(&'t str),
+    DoubleColon,
+    FatArrow,
+    Colon,
+    Comma,
+    OpenParenthesis,
+    CloseParenthesis,
+    OpenBracket,
+    CloseBracket,
+    OpenBrace,
+    CloseBrace,
+}
+
+impl<'t> Token<'t> {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Token::Identifier(_) => "identifier",
+            Token::Integer(_) => "integer",
+            Token::String(_) => "string",
+            Token::DoubleColon => "::",
+            Token::FatArrow => "=>",
+            Token::Colon => ":",
+            Token::Comma => ",",
+            Token::OpenParenthesis => "(",
+            Token::CloseParenthesis => ")",
+            Token::OpenBracket => "[",
+            Token::CloseBracket => "]",
+            Token::OpenBrace => "{",
+            Token::CloseBrace => "}",
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Token::Identifier(text) | Token::Integer(text) => text.to_string(),
+            Token::String(text) => format!("\"{}\"", text),
+            _ => self.symbol().to_string(),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token<'_>)>, RustLikeParseError> {
+    let bytes = text.as_bytes();
+    let is_word_byte = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_';
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        let start = position;
+        let token = match bytes[position] {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                position += 1;
+                continue;
+            }
+            b'(' => Token::OpenParenthesis,
+            b')' => Token::CloseParenthesis,
+            b'[' => Token::OpenBracket,
+            b']' => Token::CloseBracket,
+            b'{' => Token::OpenBrace,
+            b'}' => Token::CloseBrace,
+            b',' => Token::Comma,
+            b':' if bytes.get(position + 1) == Some(&b':') => {
+                position += 1;
+                Token::DoubleColon
+            }
+            b':' => Token::Colon,
+            b'=' if bytes.get(position + 1) == Some(&b'>') => {
+                position += 1;
+                Token::FatArrow
+            }
+            b'"' => {
+                let length = text[start + 1..]
+                    .find('"')
+                    .ok_or(RustLikeParseError::UnterminatedString { position: start })?;
+                position += length + 1;
+                Token::String(&text[start + 1..start + 1 + length])
+            }
+            b'-' | b'0'..=b'9' => {
+                while bytes
+                    .get(position + 1)
+                    .map_or(false, |byte| is_word_byte(*byte))
+                {
+                    position += 1;
+                }
+                Token::Integer(&text[start..position + 1])
+            }
+            byte if is_word_byte(byte) => {
+                while bytes
+                    .get(position + 1)
+                    .map_or(false, |byte| is_word_byte(*byte))
+                {
+                    position += 1;
+                }
+                Token::Identifier(&text[start..position + 1])
+            }
+            _ => {
+                return Err(RustLikeParseError::UnexpectedCharacter {
+                    position,
+                    character: text[start..].chars().next().unwrap(),
+                })
+            }
+        };
+        position += 1;
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+//==============
+// Syntax tree
+//==============
+
+/// A value as written, before it's resolved against the schema.
+struct Node<'t> {
+    position: usize,
+    kind: NodeKind<'t>,
+}
+
+enum NodeKind<'t> {
+    Bool(bool),
+    Integer(&'t str),
+    String(&'t str),
+    /// `hex("...")`
+    Hex(&'t str),
+    /// `[a, b]`
+    Array(Vec<Node<'t>>),
+    /// `{ k => v }`
+    Map(Vec<(Node<'t>, Node<'t>)>),
+    /// `Name`, `Name(a, b)` or `Name { f: a }` - this covers tuples, named wrappers and custom values
+    Named {
+        name: &'t str,
+        body: Body<'t>,
+    },
+    /// `Name::Variant`, `Name::[1](a, b)`, `Name::Variant { f: a }` etc
+    EnumVariant {
+        enum_name: &'t str,
+        variant: Variant<'t>,
+        body: Body<'t>,
+    },
+}
+
+enum Variant<'t> {
+    Discriminator(u8),
+    Name(&'t str),
+}
+
+enum Body<'t> {
+    None,
+    Unnamed(Vec<Node<'t>>),
+    Named(Vec<(&'t str, Node<'t>)>),
+}
+
+struct SyntaxParser<'t> {
+    tokens: Vec<(usize, Token<'t>)>,
+    index: usize,
+    depth_limit: usize,
+}
+
+impl<'t> SyntaxParser<'t> {
+    fn peek(&self) -> Option<Token<'t>> {
+        self.tokens.get(self.index).map(|(_, token)| *token)
+    }
+
+    fn next(&mut self) -> Result<(usize, Token<'t>), RustLikeParseError> {
+        let next = self
+            .tokens
+            .get(self.index)
+            .copied()
+            .ok_or(RustLikeParseError::UnexpectedEndOfInput)?;
+        self.index += 1;
+        Ok(next)
+    }
+
+    fn eat(&mut self, token: Token<'t>) -> bool {
+        if self.peek() == Some(token) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: Token<'t>) -> Result<(), RustLikeParseError> {
+        let (position, token) = self.next()?;
+        if token != expected {
+            return Err(unexpected_token(position, expected.symbol(), token));
+        }
+        Ok(())
+    }
+
+    fn parse_node(&mut self, depth: usize) -> Result<Node<'t>, RustLikeParseError> {
+        if depth > self.depth_limit {
+            return Err(RustLikeParseError::DepthLimitExceeded);
+        }
+        let (position, token) = self.next()?;
+        let kind = match token {
+            Token::Integer(integer) => NodeKind::Integer(integer),
+            Token::String(string) => NodeKind::String(string),
+            Token::OpenBracket => NodeKind::Array(self.parse_list(
+                Token::CloseBracket,
+                depth,
+                |parser, depth| parser.parse_node(depth),
+            )?),
+            Token::OpenBrace => {
+                NodeKind::Map(self.parse_list(Token::CloseBrace, depth, |parser, depth| {
+                    let key = parser.parse_node(depth)?;
+                    parser.expect(Token::FatArrow)?;
+                    let value = parser.parse_node(depth)?;
+                    Ok((key, value))
+                })?)
+            }
+            Token::Identifier("true") => NodeKind::Bool(true),
+            Token::Identifier("false") => NodeKind::Bool(false),
+            Token::Identifier("hex") if self.peek() == Some(Token::OpenParenthesis) => {
+                self.next()?;
+                let (string_position, token) = self.next()?;
+                let Token::String(hex) = token else {
+                    return Err(unexpected_token(string_position, "hex string", token));
+                };
+                self.expect(Token::CloseParenthesis)?;
+                NodeKind::Hex(hex)
+            }
+            Token::Identifier(name) if self.eat(Token::DoubleColon) => {
+                let variant = match self.next()? {
+                    (_, Token::OpenBracket) => {
+                        let (discriminator_position, token) = self.next()?;
+                        let Token::Integer(discriminator) = token else {
+                            return Err(unexpected_token(
+                                discriminator_position,
+                                "discriminator",
+                                token,
+                            ));
+                        };
+                        let discriminator = u8::from_str(discriminator).map_err(|_| {
+                            RustLikeParseError::InvalidInteger {
+                                position: discriminator_position,
+                                integer: discriminator.to_string(),
+                            }
+                        })?;
+                        self.expect(Token::CloseBracket)?;
+                        Variant::Discriminator(discriminator)
+                    }
+                    (_, Token::Identifier(variant_name)) => Variant::Name(variant_name),
+                    (position, token) => {
+                        return Err(unexpected_token(position, "variant", token));
+                    }
+                };
+                NodeKind::EnumVariant {
+                    enum_name: name,
+                    variant,
+                    body: self.parse_body(depth)?,
+                }
+            }
+            Token::Identifier(name) => NodeKind::Named {
+                name,
+                body: self.parse_body(depth)?,
+            },
+            _ => return Err(unexpected_token(position, "value", token)),
+        };
+        Ok(Node { position, kind })
+    }
+
+    fn parse_body(&mut self, depth: usize) -> Result<Body<'t>, RustLikeParseError> {
+        if self.eat(Token::OpenParenthesis) {
+            let fields = self.parse_list(Token::CloseParenthesis, depth, |parser, depth| {
+                parser.parse_node(depth)
+            })?;
+            Ok(Body::Unnamed(fields))
+        } else if self.eat(Token::OpenBrace) {
+            let fields = self.parse_list(Token::CloseBrace, depth, |parser, depth| {
+                let (position, token) = parser.next()?;
+                let Token::Identifier(field_name) = token else {
+                    return Err(unexpected_token(position, "field name", token));
+                };
+                parser.expect(Token::Colon)?;
+                Ok((field_name, parser.parse_node(depth)?))
+            })?;
+            Ok(Body::Named(fields))
+        } else {
+            Ok(Body::None)
+        }
+    }
+
+    /// Parses comma separated items up to the closing token, allowing a trailing comma as in the
+    /// multi-line print mode.
+    fn parse_list<T>(
+        &mut self,
+        closing: Token<'t>,
+        parent_depth: usize,
+        mut parse_item: impl FnMut(&mut Self, usize) -> Result<T, RustLikeParseError>,
+    ) -> Result<Vec<T>, RustLikeParseError> {
+        let mut items = Vec::new();
+        loop {
+            if self.eat(closing) {
+                return Ok(items);
+            }
+            items.push(parse_item(self, parent_depth + 1)?);
+            if !self.eat(Token::Comma) {
+                self.expect(closing)?;
+                return Ok(items);
+            }
+        }
+    }
+}
+
+fn unexpected_token(position: usize, expected: &'static str, actual: Token) -> RustLikeParseError {
+    RustLikeParseError::UnexpectedToken {
+        position,
+        expected,
+        actual: actual.describe(),
+    }
+}
+
+//==============
+// Resolving
+//==============
+
+struct RustLikeParseContext<'s, 'a, E: ParseableCustomExtension> {
+    schema: &'s Schema<E::CustomSchema>,
+    custom_context: E::CustomParseContext<'a>,
+}
+
+impl<'s, 'a, E: ParseableCustomExtension> RustLikeParseContext<'s, 'a, E> {
+    fn convert(
+        &self,
+        node: &Node<'_>,
+        type_id: LocalTypeId,
+    ) -> Result<ParsedValue<E>, RustLikeParseError> {
+        let type_kind = self
+            .schema
+            .resolve_type_kind(type_id)
+            .ok_or(RustLikeParseError::TypeIdNotFound(type_id))?;
+        let type_name = self.schema.resolve_type_name_from_metadata(type_id);
+        match type_kind {
+            TypeKind::Any => self.convert_untyped(node),
+            TypeKind::Tuple { field_types } => {
+                let NodeKind::Named { name, body } = &node.kind else {
+                    return Err(mismatching_value_kind(node, "Tuple"));
+                };
+                let tuple_data = self
+                    .schema
+                    .resolve_matching_tuple_metadata(type_id, field_types.len());
+                check_type_name(node.position, name, tuple_data.name, "Tuple")?;
+                let field_nodes = resolve_fields(
+                    node.position,
+                    body,
+                    tuple_data.field_names,
+                    field_types.len(),
+                )?;
+                Ok(Value::Tuple {
+                    fields: self.convert_fields(&field_nodes, field_types)?,
+                })
+            }
+            TypeKind::Enum { variants } => {
+                let NodeKind::EnumVariant {
+                    enum_name,
+                    variant,
+                    body,
+                } = &node.kind
+                else {
+                    return Err(mismatching_value_kind(node, "Enum"));
+                };
+                check_type_name(node.position, enum_name, type_name, "Enum")?;
+                let discriminator = match variant {
+                    Variant::Discriminator(discriminator) => *discriminator,
+                    Variant::Name(variant_name) => variants
+                        .iter()
+                        .find(|(discriminator, field_types)| {
+                            self.schema
+                                .resolve_matching_enum_metadata(
+                                    type_id,
+                                    **discriminator,
+                                    field_types.len(),
+                                )
+                                .variant_name
+                                == Some(*variant_name)
+                        })
+                        .map(|(discriminator, _)| *discriminator)
+                        .ok_or_else(|| RustLikeParseError::UnknownEnumVariant {
+                            position: node.position,
+                            variant: variant_name.to_string(),
+                        })?,
+                };
+                let field_types = variants.get(&discriminator).ok_or_else(|| {
+                    RustLikeParseError::UnknownEnumVariant {
+                        position: node.position,
+                        variant: discriminator.to_string(),
+                    }
+                })?;
+                let variant_data = self.schema.resolve_matching_enum_metadata(
+                    type_id,
+                    discriminator,
+                    field_types.len(),
+                );
+                let field_nodes = resolve_fields(
+                    node.position,
+                    body,
+                    variant_data.field_names,
+                    field_types.len(),
+                )?;
+                Ok(Value::Enum {
+                    discriminator,
+                    fields: self.convert_fields(&field_nodes, field_types)?,
+                })
+            }
+            TypeKind::Array { element_type } => {
+                let node = unwrap_type_name(node, type_name);
+                let element_value_kind = self.value_kind_of(*element_type)?;
+                match &node.kind {
+                    NodeKind::Hex(hex) => {
+                        let value = parse_hex(node.position, hex)?;
+                        match element_value_kind {
+                            Some(element_value_kind) if element_value_kind != ValueKind::U8 => {
+                                Err(RustLikeParseError::MismatchingValueKind {
+                                    position: node.position,
+                                    expected: element_value_kind.to_string(),
+                                    actual: ValueKind::<E::CustomValueKind>::U8.to_string(),
+                                })
+                            }
+                            _ => Ok(value),
+                        }
+                    }
+                    NodeKind::Array(element_nodes) => {
+                        let elements = element_nodes
+                            .iter()
+                            .map(|element_node| self.convert(element_node, *element_type))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        let element_value_kind = resolve_collection_value_kind(
+                            node.position,
+                            element_value_kind,
+                            element_nodes.iter().zip(elements.iter()),
+                        )?;
+                        Ok(Value::Array {
+                            element_value_kind,
+                            elements,
+                        })
+                    }
+                    _ => Err(mismatching_value_kind(node, "Array")),
+                }
+            }
+            TypeKind::Map {
+                key_type,
+                value_type,
+            } => {
+                let node = unwrap_type_name(node, type_name);
+                let NodeKind::Map(entry_nodes) = &node.kind else {
+                    return Err(mismatching_value_kind(node, "Map"));
+                };
+                let entries = entry_nodes
+                    .iter()
+                    .map(|(key_node, value_node)| {
+                        Ok((
+                            self.convert(key_node, *key_type)?,
+                            self.convert(value_node, *value_type)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, RustLikeParseError>>()?;
+                let key_value_kind = resolve_collection_value_kind(
+                    node.position,
+                    self.value_kind_of(*key_type)?,
+                    entry_nodes
+                        .iter()
+                        .map(|(node, _)| node)
+                        .zip(entries.iter().map(|(key, _)| key)),
+                )?;
+                let value_value_kind = resolve_collection_value_kind(
+                    node.position,
+                    self.value_kind_of(*value_type)?,
+                    entry_nodes
+                        .iter()
+                        .map(|(_, node)| node)
+                        .zip(entries.iter().map(|(_, value)| value)),
+                )?;
+                Ok(Value::Map {
+                    key_value_kind,
+                    value_value_kind,
+                    entries,
+                })
+            }
+            _ => {
+                let node = unwrap_type_name(node, type_name);
+                let value_kind = value_kind_for_type_kind::<E>(self.schema, type_kind);
+                let value = match &node.kind {
+                    NodeKind::Integer(integer) => {
+                        parse_integer(node.position, integer, value_kind)?
+                    }
+                    _ => self.convert_untyped(node)?,
+                };
+                match value_kind {
+                    Some(value_kind) if value_kind != value.get_value_kind() => {
+                        Err(RustLikeParseError::MismatchingValueKind {
+                            position: node.position,
+                            expected: value_kind.to_string(),
+                            actual: value.get_value_kind().to_string(),
+                        })
+                    }
+                    _ => Ok(value),
+                }
+            }
+        }
+    }
+
+    fn convert_fields(
+        &self,
+        field_nodes: &[&Node<'_>],
+        field_types: &[LocalTypeId],
+    ) -> Result<Vec<ParsedValue<E>>, RustLikeParseError> {
+        field_nodes
+            .iter()
+            .zip(field_types)
+            .map(|(field_node, field_type)| self.convert(field_node, *field_type))
+            .collect()
+    }
+
+    fn convert_untyped(&self, node: &Node<'_>) -> Result<ParsedValue<E>, RustLikeParseError> {
+        let value = match &node.kind {
+            NodeKind::Bool(value) => Value::Bool { value: *value },
+            NodeKind::Integer(integer) => parse_integer(node.position, integer, None)?,
+            NodeKind::String(value) => Value::String {
+                value: value.to_string(),
+            },
+            NodeKind::Hex(hex) => parse_hex(node.position, hex)?,
+            NodeKind::Array(element_nodes) => {
+                let elements = element_nodes
+                    .iter()
+                    .map(|element_node| self.convert_untyped(element_node))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let element_value_kind = resolve_collection_value_kind(
+                    node.position,
+                    None,
+                    element_nodes.iter().zip(elements.iter()),
+                )?;
+                Value::Array {
+                    element_value_kind,
+                    elements,
+                }
+            }
+            NodeKind::Map(entry_nodes) => {
+                let entries = entry_nodes
+                    .iter()
+                    .map(|(key_node, value_node)| {
+                        Ok((
+                            self.convert_untyped(key_node)?,
+                            self.convert_untyped(value_node)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, RustLikeParseError>>()?;
+                let key_value_kind = resolve_collection_value_kind(
+                    node.position,
+                    None,
+                    entry_nodes
+                        .iter()
+                        .map(|(node, _)| node)
+                        .zip(entries.iter().map(|(key, _)| key)),
+                )?;
+                let value_value_kind = resolve_collection_value_kind(
+                    node.position,
+                    None,
+                    entry_nodes
+                        .iter()
+                        .map(|(_, node)| node)
+                        .zip(entries.iter().map(|(_, value)| value)),
+                )?;
+                Value::Map {
+                    key_value_kind,
+                    value_value_kind,
+                    entries,
+                }
+            }
+            NodeKind::Named { name, body } => match (*name, body) {
+                ("Unit", Body::None) => Value::Tuple { fields: vec![] },
+                ("Tuple", Body::Unnamed(field_nodes)) => Value::Tuple {
+                    fields: self.convert_untyped_fields(field_nodes.iter())?,
+                },
+                ("Struct", Body::Named(field_nodes)) => Value::Tuple {
+                    fields: self
+                        .convert_untyped_fields(field_nodes.iter().map(|(_, node)| node))?,
+                },
+                (name, Body::Unnamed(argument_nodes)) => {
+                    match (
+                        custom_value_kind_from_name::<E>(name),
+                        argument_nodes.as_slice(),
+                    ) {
+                        (
+                            Some(custom_value_kind),
+                            [Node {
+                                kind: NodeKind::String(content),
+                                ..
+                            }],
+                        ) => {
+                            let value = E::parse_custom_value_content(
+                                &self.custom_context,
+                                custom_value_kind,
+                                content,
+                            )
+                            .map_err(|error| {
+                                RustLikeParseError::InvalidCustomValue {
+                                    position: node.position,
+                                    value_kind: name.to_string(),
+                                    error,
+                                }
+                            })?;
+                            Value::Custom { value }
+                        }
+                        _ => return Err(unknown_type_name(node, name)),
+                    }
+                }
+                (name, _) => return Err(unknown_type_name(node, name)),
+            },
+            NodeKind::EnumVariant { variant, body, .. } => {
+                let discriminator = match variant {
+                    Variant::Discriminator(discriminator) => *discriminator,
+                    // Variant names can only be resolved with a schema
+                    Variant::Name(variant_name) => {
+                        return Err(RustLikeParseError::UnknownEnumVariant {
+                            position: node.position,
+                            variant: variant_name.to_string(),
+                        });
+                    }
+                };
+                let fields = match body {
+                    Body::None => vec![],
+                    Body::Unnamed(field_nodes) => {
+                        self.convert_untyped_fields(field_nodes.iter())?
+                    }
+                    Body::Named(field_nodes) => {
+                        self.convert_untyped_fields(field_nodes.iter().map(|(_, node)| node))?
+                    }
+                };
+                Value::Enum {
+                    discriminator,
+                    fields,
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    fn convert_untyped_fields<'n, 't: 'n>(
+        &self,
+        field_nodes: impl Iterator<Item = &'n Node<'t>>,
+    ) -> Result<Vec<ParsedValue<E>>, RustLikeParseError> {
+        field_nodes
+            .map(|field_node| self.convert_untyped(field_node))
+            .collect()
+    }
+
+    /// The value kind of the type, if it's fixed by the schema.
+    fn value_kind_of(
+        &self,
+        type_id: LocalTypeId,
+    ) -> Result<Option<ValueKind<E::CustomValueKind>>, RustLikeParseError> {
+        let type_kind = self
+            .schema
+            .resolve_type_kind(type_id)
+            .ok_or(RustLikeParseError::TypeIdNotFound(type_id))?;
+        Ok(value_kind_for_type_kind::<E>(self.schema, type_kind))
+    }
+}
+
+/// Types with a name, other than tuples and enums, are printed wrapped as `TypeName(value)`.
+fn unwrap_type_name<'n, 't>(node: &'n Node<'t>, type_name: Option<&str>) -> &'n Node<'t> {
+    match (&node.kind, type_name) {
+        (
+            NodeKind::Named {
+                name,
+                body: Body::Unnamed(argument_nodes),
+            },
+            Some(type_name),
+        ) if *name == type_name && argument_nodes.len() == 1 => &argument_nodes[0],
+        _ => node,
+    }
+}
+
+/// Checks the type name against the schema, also allowing the name used for unnamed types.
+fn check_type_name(
+    position: usize,
+    name: &str,
+    type_name: Option<&str>,
+    unnamed_type_name: &str,
+) -> Result<(), RustLikeParseError> {
+    let is_unnamed_name = match unnamed_type_name {
+        "Tuple" => matches!(name, "Unit" | "Tuple" | "Struct"),
+        _ => name == unnamed_type_name,
+    };
+    if Some(name) == type_name || is_unnamed_name {
+        return Ok(());
+    }
+    Err(RustLikeParseError::MismatchingTypeName {
+        position,
+        expected: type_name.unwrap_or(unnamed_type_name).to_string(),
+        actual: name.to_string(),
+    })
+}
+
+/// Checks any field names against the schema, and that the field count matches.
+fn resolve_fields<'n, 't>(
+    position: usize,
+    body: &'n Body<'t>,
+    field_names: Option<&[Cow<'static, str>]>,
+    field_count: usize,
+) -> Result<Vec<&'n Node<'t>>, RustLikeParseError> {
+    let field_nodes: Vec<_> = match body {
+        Body::None => vec![],
+        Body::Unnamed(field_nodes) => field_nodes.iter().collect(),
+        Body::Named(field_nodes) => {
+            if let Some(field_names) = field_names {
+                for ((field_name, field_node), expected) in field_nodes.iter().zip(field_names) {
+                    if *field_name != expected.as_ref() {
+                        return Err(RustLikeParseError::MismatchingFieldName {
+                            position: field_node.position,
+                            expected: expected.to_string(),
+                            actual: field_name.to_string(),
+                        });
+                    }
+                }
+            }
+            field_nodes
+                .iter()
+                .map(|(_, field_node)| field_node)
+                .collect()
+        }
+    };
+    if field_nodes.len() != field_count {
+        return Err(RustLikeParseError::MismatchingFieldCount {
+            position,
+            expected: field_count,
+            actual: field_nodes.len(),
+        });
+    }
+    Ok(field_nodes)
+}
+
+/// Resolves the value kind of the children of an array or map, from the schema or else the
+/// children themselves, checking they all match.
+fn resolve_collection_value_kind<'n, 't: 'n, 'v, X: CustomValueKind, Y: CustomValue<X> + 'v>(
+    position: usize,
+    schema_value_kind: Option<ValueKind<X>>,
+    children: impl Iterator<Item = (&'n Node<'t>, &'v Value<X, Y>)>,
+) -> Result<ValueKind<X>, RustLikeParseError> {
+    let mut value_kind = schema_value_kind;
+    for (node, value) in children {
+        let child_value_kind = value.get_value_kind();
+        match value_kind {
+            Some(value_kind) if value_kind != child_value_kind => {
+                return Err(RustLikeParseError::MismatchingValueKind {
+                    position: node.position,
+                    expected: value_kind.to_string(),
+                    actual: child_value_kind.to_string(),
+                });
+            }
+            _ => value_kind = Some(child_value_kind),
+        }
+    }
+    value_kind.ok_or(RustLikeParseError::UnresolvableEmptyCollection { position })
+}
+
+fn custom_value_kind_from_name<E: CustomExtension>(name: &str) -> Option<E::CustomValueKind> {
+    (CUSTOM_VALUE_KIND_START..=u8::MAX)
+        .filter_map(E::CustomValueKind::from_u8)
+        .find(|custom_value_kind| {
+            ValueKind::<E::CustomValueKind>::Custom(*custom_value_kind).to_string() == name
+        })
+}
+
+fn parse_integer<X: CustomValueKind, Y: CustomValue<X>>(
+    position: usize,
+    integer: &str,
+    value_kind: Option<ValueKind<X>>,
+) -> Result<Value<X, Y>, RustLikeParseError> {
+    let invalid_integer = || RustLikeParseError::InvalidInteger {
+        position,
+        integer: integer.to_string(),
+    };
+    let (number, suffix) = integer.split_at(
+        integer
+            .find(|character: char| character.is_ascii_alphabetic())
+            .unwrap_or(integer.len()),
+    );
+    let suffix_value_kind = match suffix {
+        "" => None,
+        "i8" => Some(ValueKind::I8),
+        "i16" => Some(ValueKind::I16),
+        "i32" => Some(ValueKind::I32),
+        "i64" => Some(ValueKind::I64),
+        "i128" => Some(ValueKind::I128),
+        "u8" => Some(ValueKind::U8),
+        "u16" => Some(ValueKind::U16),
+        "u32" => Some(ValueKind::U32),
+        "u64" => Some(ValueKind::U64),
+        "u128" => Some(ValueKind::U128),
+        _ => return Err(invalid_integer()),
+    };
+    let value_kind = match (suffix_value_kind, value_kind) {
+        (Some(suffix_value_kind), Some(value_kind)) if suffix_value_kind != value_kind => {
+            return Err(RustLikeParseError::MismatchingValueKind {
+                position,
+                expected: value_kind.to_string(),
+                actual: suffix_value_kind.to_string(),
+            });
+        }
+        (Some(value_kind), _) | (None, Some(value_kind)) => value_kind,
+        (None, None) => return Err(RustLikeParseError::MissingIntegerSuffix { position }),
+    };
+    let value = match value_kind {
+        ValueKind::I8 => Value::I8 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::I16 => Value::I16 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::I32 => Value::I32 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::I64 => Value::I64 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::I128 => Value::I128 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::U8 => Value::U8 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::U16 => Value::U16 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::U32 => Value::U32 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::U64 => Value::U64 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        ValueKind::U128 => Value::U128 {
+            value: number.parse().map_err(|_| invalid_integer())?,
+        },
+        _ => {
+            return Err(RustLikeParseError::MismatchingValueKind {
+                position,
+                expected: value_kind.to_string(),
+                actual: "Integer".to_string(),
+            });
+        }
+    };
+    Ok(value)
+}
+
+fn parse_hex<X: CustomValueKind, Y: CustomValue<X>>(
+    position: usize,
+    hex: &str,
+) -> Result<Value<X, Y>, RustLikeParseError> {
+    let bytes = hex::decode(hex).map_err(|_| RustLikeParseError::InvalidHex { position })?;
+    Ok(Value::Array {
+        element_value_kind: ValueKind::U8,
+        elements: bytes.into_iter().map(|value| Value::U8 { value }).collect(),
+    })
+}
+
+fn mismatching_value_kind(node: &Node<'_>, expected: &str) -> RustLikeParseError {
+    RustLikeParseError::MismatchingValueKind {
+        position: node.position,
+        expected: expected.to_string(),
+        actual: match &node.kind {
+            NodeKind::Bool(_) => "Bool",
+            NodeKind::Integer(_) => "Integer",
+            NodeKind::String(_) => "String",
+            NodeKind::Hex(_) | NodeKind::Array(_) => "Array",
+            NodeKind::Map(_) => "Map",
+            NodeKind::Named { .. } => "Tuple",
+            NodeKind::EnumVariant { .. } => "Enum",
+        }
+        .to_string(),
+    }
+}
+
+fn unknown_type_name(node: &Node<'_>, name: &str) -> RustLikeParseError {
+    RustLikeParseError::UnknownTypeName {
+        position: node.position,
+        name: name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::*;
+
+    #[derive(Sbor, PartialEq, Eq, Debug)]
+    enum TestEnum {
+        UnitVariant,
+        SingleFieldVariant { field: u8 },
+        TupleVariant(i32, String),
+    }
+
+    #[derive(Sbor, PartialEq, Eq, Debug)]
+    struct MyUnitStruct;
+
+    #[derive(Sbor, PartialEq, Eq, Debug)]
+    struct MyFieldStruct {
+        field1: u64,
+        field2: Vec<String>,
+    }
+
+    #[derive(Sbor, PartialEq, Eq, Debug)]
+    struct MyComplexTupleStruct(
+        Vec<u16>,
+        Vec<u16>,
+        Vec<u8>,
+        BTreeMap<String, MyUnitStruct>,
+        BTreeMap<u8, TestEnum>,
+        TestEnum,
+        TestEnum,
+        TestEnum,
+        MyFieldStruct,
+        (bool, i128, Option<u32>),
+    );
+
+    fn test_value() -> MyComplexTupleStruct {
+        MyComplexTupleStruct(
+            vec![1, 2, 3],
+            vec![],
+            vec![1, 2, 3],
+            btreemap! {
+                "hello".to_string() => MyUnitStruct,
+                "world".to_string() => MyUnitStruct,
+            },
+            btreemap! {},
+            TestEnum::UnitVariant,
+            TestEnum::SingleFieldVariant { field: 1 },
+            TestEnum::TupleVariant(-3, "x".to_string()),
+            MyFieldStruct {
+                field1: 21,
+                field2: vec!["hello".to_string(), "world!".to_string()],
+            },
+            (true, i128::MIN, Some(7)),
+        )
+    }
+
+    fn format_value(
+        payload: &[u8],
+        display_context: ValueDisplayParameters<NoCustomExtension>,
+    ) -> String {
+        BasicRawPayload::new_from_valid_slice_with_checks(payload)
+            .unwrap()
+            .to_string(display_context)
+    }
+
+    #[test]
+    fn annotated_display_round_trips() {
+        let (type_id, schema) =
+            generate_full_schema_from_single_type::<MyComplexTupleStruct, NoCustomSchema>();
+        let payload = basic_encode(&test_value()).unwrap();
+
+        for print_mode in [
+            PrintMode::SingleLine,
+            PrintMode::MultiLine {
+                indent_size: 4,
+                base_indent: 8,
+                first_line_indent: 0,
+            },
+        ] {
+            let text = format_value(
+                &payload,
+                ValueDisplayParameters::Annotated {
+                    display_mode: DisplayMode::RustLike,
+                    print_mode,
+                    schema: schema.v1(),
+                    custom_context: Default::default(),
+                    type_id,
+                    depth_limit: 64,
+                },
+            );
+            let parsed = parse_rustlike_payload(
+                &text,
+                &RustLikeParseParameters::<NoCustomExtension>::Annotated {
+                    custom_context: (),
+                    schema: schema.v1(),
+                    type_id,
+                    depth_limit: 64,
+                },
+            )
+            .unwrap();
+            assert_eq!(parsed, payload, "{}", text);
+        }
+    }
+
+    #[test]
+    fn schemaless_display_round_trips() {
+        // Empty collections can't be parsed without a schema
+        let value = (
+            vec![1u16, 2, 3],
+            vec![1u8, 2, 3],
+            btreemap! { "hello".to_string() => (MyUnitStruct, -5i8) },
+            TestEnum::TupleVariant(-3, "x".to_string()),
+            (true, u128::MAX, Some(7u32)),
+        );
+        let payload = basic_encode(&value).unwrap();
+        let text = format_value(
+            &payload,
+            ValueDisplayParameters::Schemaless {
+                display_mode: DisplayMode::RustLike,
+                print_mode: PrintMode::SingleLine,
+                custom_context: Default::default(),
+                depth_limit: 64,
+            },
+        );
+        let parsed = parse_rustlike_payload(
+            &text,
+            &RustLikeParseParameters::<NoCustomExtension>::Schemaless {
+                custom_context: (),
+                depth_limit: 64,
+            },
+        )
+        .unwrap();
+        assert_eq!(parsed, payload, "{}", text);
+    }
+
+    #[test]
+    fn hand_written_fixtures_can_omit_suffixes_and_use_discriminators() {
+        let (type_id, schema) =
+            generate_full_schema_from_single_type::<MyFieldStruct, NoCustomSchema>();
+        let value: BasicValue = parse_rustlike_value(
+            r#"MyFieldStruct { field1: 5, field2: ["a", "b",], }"#,
+            &RustLikeParseParameters::<NoCustomExtension>::Annotated {
+                custom_context: (),
+                schema: schema.v1(),
+                type_id,
+                depth_limit: 64,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            basic_encode(&value).unwrap(),
+            basic_encode(&MyFieldStruct {
+                field1: 5,
+                field2: vec!["a".to_string(), "b".to_string()],
+            })
+            .unwrap()
+        );
+
+        let (type_id, schema) = generate_full_schema_from_single_type::<TestEnum, NoCustomSchema>();
+        let payload = parse_rustlike_payload(
+            "TestEnum::[1] { field: 3 }",
+            &RustLikeParseParameters::<NoCustomExtension>::Annotated {
+                custom_context: (),
+                schema: schema.v1(),
+                type_id,
+                depth_limit: 64,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            payload,
+            basic_encode(&TestEnum::SingleFieldVariant { field: 3 }).unwrap()
+        );
+    }
+
+    #[test]
+    fn mismatches_with_the_schema_are_rejected() {
+        let (type_id, schema) =
+            generate_full_schema_from_single_type::<MyFieldStruct, NoCustomSchema>();
+        let parse = |text: &str| {
+            parse_rustlike_payload(
+                text,
+                &RustLikeParseParameters::<NoCustomExtension>::Annotated {
+                    custom_context: (),
+                    schema: schema.v1(),
+                    type_id,
+                    depth_limit: 64,
+                },
+            )
+        };
+        assert!(matches!(
+            parse(r#"OtherStruct { field1: 5u64, field2: [] }"#),
+            Err(RustLikeParseError::MismatchingTypeName { .. })
+        ));
+        assert!(matches!(
+            parse(r#"MyFieldStruct { field3: 5u64, field2: [] }"#),
+            Err(RustLikeParseError::MismatchingFieldName { .. })
+        ));
+        assert!(matches!(
+            parse(r#"MyFieldStruct { field1: 5u32, field2: [] }"#),
+            Err(RustLikeParseError::MismatchingValueKind { .. })
+        ));
+        assert!(matches!(
+            parse(r#"MyFieldStruct { field1: 5u64 }"#),
+            Err(RustLikeParseError::MismatchingFieldCount { .. })
+        ));
+        assert!(matches!(
+            parse(r#"MyFieldStruct { field1: 5u64, field2: [] } 1u8"#),
+            Err(RustLikeParseError::TrailingInput { .. })
+        ));
+        assert!(matches!(
+            parse(r#"MyFieldStruct { field1: 5u64, field2: ["#),
+            Err(RustLikeParseError::UnexpectedEndOfInput)
+        ));
+    }
+
+    #[test]
+    fn schemaless_parsing_rejects_what_needs_a_schema() {
+        let parse = |text: &str| {
+            parse_rustlike_payload(
+                text,
+                &RustLikeParseParameters::<NoCustomExtension>::Schemaless {
+                    custom_context: (),
+                    depth_limit: 64,
+                },
+            )
+        };
+        assert!(matches!(
+            parse("5"),
+            Err(RustLikeParseError::MissingIntegerSuffix { .. })
+        ));
+        assert!(matches!(
+            parse("[]"),
+            Err(RustLikeParseError::UnresolvableEmptyCollection { .. })
+        ));
+        assert!(matches!(
+            parse("MyUnitStruct"),
+            Err(RustLikeParseError::UnknownTypeName { .. })
+        ));
+        assert!(matches!(
+            parse("Enum::UnitVariant"),
+            Err(RustLikeParseError::UnknownEnumVariant { .. })
+        ));
+        assert!(matches!(
+            parse("[1u8, 2u16]"),
+            Err(RustLikeParseError::MismatchingValueKind { .. })
+        ));
+        assert!(matches!(
+            parse(&format!("{}1u8{}", "[".repeat(70), "]".repeat(70))),
+            Err(RustLikeParseError::DepthLimitExceeded)
+        ));
+    }
+}
@@ -1,9 +1,11 @@
 mod display;
+mod schema_export;
 #[cfg(feature = "serde")]
 pub mod serde_serialization;
 mod traits;
 
 pub use display::*;
+pub use schema_export::*;
 #[cfg(feature = "serde")]
 pub use serde_serialization::*;
 pub use traits::*;
@@ -0,0 +1,414 @@
+use super::*;
+use crate::rust::prelude::*;
+use crate::*;
+
+/// Exports a JSON Schema (draft 2020-12) document for the Programmatic JSON representation
+/// of the given root types.
+///
+/// Each root, and each schema-local type it references, becomes an entry under `$defs`.
+pub fn export_json_schema<E: ExportableCustomExtension>(
+    schema: &Schema<E::CustomSchema>,
+    roots: &[(String, LocalTypeId)],
+) -> Result<String, SchemaExportError> {
+    let ExportedShapes { roots, definitions } = export_shapes::<E>(schema, roots)?;
+
+    let mut defs = vec![];
+    for (name, shape) in &roots {
+        defs.push((name.clone(), shape_ref(shape)));
+    }
+    for (name, shape) in &definitions {
+        defs.push((name.clone(), value_shape(shape)));
+    }
+    let document = Json::Object(vec![
+        (
+            "$schema".to_string(),
+            Json::string("https://json-schema.org/draft/2020-12/schema"),
+        ),
+        ("$defs".to_string(), Json::Object(defs)),
+    ]);
+
+    let mut output = String::new();
+    document.write(&mut output, 0);
+    output.push('\n');
+    Ok(output)
+}
+
+fn shape_ref(shape: &ShapeRef) -> Json {
+    match shape {
+        ShapeRef::Definition(name) => {
+            Json::object(vec![("$ref", Json::string(&format!("#/$defs/{}", name)))])
+        }
+        ShapeRef::Inline(shape) => value_shape(shape),
+    }
+}
+
+fn value_shape(shape: &ValueShape) -> Json {
+    match shape {
+        ValueShape::Any => Json::object(vec![]),
+        ValueShape::Terminal {
+            kind,
+            type_name,
+            value,
+        } => {
+            let value = match value {
+                TerminalShape::Boolean => Json::object(vec![("type", Json::string("boolean"))]),
+                TerminalShape::Integer { signed: true } => integer_string("^-?[0-9]+$"),
+                TerminalShape::Integer { signed: false } => integer_string("^[0-9]+$"),
+                TerminalShape::String { format: None } => {
+                    Json::object(vec![("type", Json::string("string"))])
+                }
+                TerminalShape::String {
+                    format: Some(format),
+                } => Json::object(vec![
+                    ("type", Json::string("string")),
+                    ("format", Json::string(&format.format)),
+                    ("pattern", Json::string(&format.pattern)),
+                ]),
+            };
+            value_object(kind, type_name, vec![("value", value, true)])
+        }
+        ValueShape::Tuple { type_name, fields } => value_object(
+            "Tuple",
+            type_name,
+            vec![("fields", field_shapes(fields), true)],
+        ),
+        ValueShape::Enum {
+            type_name,
+            variants,
+        } => {
+            let variants = variants
+                .iter()
+                .map(|variant| {
+                    let mut properties = vec![
+                        ("variant_id", constant(&variant.id.to_string()), true),
+                        ("fields", field_shapes(&variant.fields), true),
+                    ];
+                    if let Some(name) = &variant.name {
+                        properties.push(("variant_name", constant(name), false));
+                    }
+                    value_object("Enum", type_name, properties)
+                })
+                .collect();
+            Json::object(vec![("oneOf", Json::Array(variants))])
+        }
+        ValueShape::Bytes { type_name, length } => {
+            let mut hex = vec![
+                ("type", Json::string("string")),
+                ("pattern", Json::string("^([0-9a-f]{2})*$")),
+            ];
+            if let Some(min) = length.min {
+                hex.push(("minLength", Json::Number(2 * min as u64)));
+            }
+            if let Some(max) = length.max {
+                hex.push(("maxLength", Json::Number(2 * max as u64)));
+            }
+            value_object(
+                "Bytes",
+                type_name,
+                vec![
+                    ("element_kind", constant("U8"), true),
+                    ("hex", Json::object(hex), true),
+                ],
+            )
+        }
+        ValueShape::Array {
+            type_name,
+            element_kind,
+            element_name,
+            element,
+            length,
+        } => {
+            let mut properties = vec![
+                ("element_kind", optional_constant(element_kind), true),
+                (
+                    "elements",
+                    array_of(shape_ref(element), length.min, length.max),
+                    true,
+                ),
+            ];
+            if let Some(element_name) = element_name {
+                properties.push(("element_name", constant(element_name), false));
+            }
+            value_object("Array", type_name, properties)
+        }
+        ValueShape::Map {
+            type_name,
+            key_kind,
+            key_type_name,
+            value_kind,
+            value_type_name,
+            key,
+            value,
+            length,
+        } => {
+            let entry = Json::object(vec![
+                ("type", Json::string("object")),
+                (
+                    "properties",
+                    Json::object(vec![("key", shape_ref(key)), ("value", shape_ref(value))]),
+                ),
+                (
+                    "required",
+                    Json::Array(vec![Json::string("key"), Json::string("value")]),
+                ),
+            ]);
+            let mut properties = vec![
+                ("key_kind", optional_constant(key_kind), true),
+                ("value_kind", optional_constant(value_kind), true),
+                ("entries", array_of(entry, length.min, length.max), true),
+            ];
+            if let Some(key_type_name) = key_type_name {
+                properties.push(("key_type_name", constant(key_type_name), false));
+            }
+            if let Some(value_type_name) = value_type_name {
+                properties.push(("value_type_name", constant(value_type_name), false));
+            }
+            value_object("Map", type_name, properties)
+        }
+    }
+}
+
+/// Every value is an object with a `kind`, an optional `type_name` and, when it is a named
+/// field, a `field_name`.
+fn value_object(
+    kind: &str,
+    type_name: &Option<String>,
+    properties: Vec<(&str, Json, bool)>,
+) -> Json {
+    let mut all_properties = vec![("kind".to_string(), constant(kind))];
+    let mut required = vec![Json::string("kind")];
+    if let Some(type_name) = type_name {
+        all_properties.push(("type_name".to_string(), constant(type_name)));
+    }
+    all_properties.push((
+        "field_name".to_string(),
+        Json::object(vec![("type", Json::string("string"))]),
+    ));
+    for (name, schema, is_required) in properties {
+        all_properties.push((name.to_string(), schema));
+        if is_required {
+            required.push(Json::string(name));
+        }
+    }
+    Json::object(vec![
+        ("type", Json::string("object")),
+        ("properties", Json::Object(all_properties)),
+        ("required", Json::Array(required)),
+    ])
+}
+
+fn field_shapes(fields: &[FieldShape]) -> Json {
+    let items = fields
+        .iter()
+        .map(|field| match &field.name {
+            Some(name) => Json::object(vec![(
+                "allOf",
+                Json::Array(vec![
+                    shape_ref(&field.shape),
+                    Json::object(vec![
+                        (
+                            "properties",
+                            Json::object(vec![("field_name", constant(name))]),
+                        ),
+                        ("required", Json::Array(vec![Json::string("field_name")])),
+                    ]),
+                ]),
+            )]),
+            None => shape_ref(&field.shape),
+        })
+        .collect();
+    Json::object(vec![
+        ("type", Json::string("array")),
+        ("prefixItems", Json::Array(items)),
+        ("minItems", Json::Number(fields.len() as u64)),
+        ("maxItems", Json::Number(fields.len() as u64)),
+    ])
+}
+
+fn array_of(items: Json, min: Option<u32>, max: Option<u32>) -> Json {
+    let mut properties = vec![("type", Json::string("array")), ("items", items)];
+    if let Some(min) = min {
+        properties.push(("minItems", Json::Number(min as u64)));
+    }
+    if let Some(max) = max {
+        properties.push(("maxItems", Json::Number(max as u64)));
+    }
+    Json::object(properties)
+}
+
+fn integer_string(pattern: &str) -> Json {
+    Json::object(vec![
+        ("type", Json::string("string")),
+        ("pattern", Json::string(pattern)),
+    ])
+}
+
+fn constant(value: &str) -> Json {
+    Json::object(vec![("const", Json::string(value))])
+}
+
+fn optional_constant<S: AsRef<str>>(value: &Option<S>) -> Json {
+    match value {
+        Some(value) => constant(value.as_ref()),
+        None => Json::object(vec![("type", Json::string("string"))]),
+    }
+}
+
+/// A minimal JSON model, as `sbor` doesn't depend on `serde_json`.
+enum Json {
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn string(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+
+    fn object(properties: Vec<(&str, Json)>) -> Self {
+        Json::Object(
+            properties
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    fn write(&self, output: &mut String, indent: usize) {
+        match self {
+            Json::Number(value) => output.push_str(&value.to_string()),
+            Json::String(value) => write_json_string(output, value),
+            Json::Array(items) if items.is_empty() => output.push_str("[]"),
+            Json::Array(items) => {
+                output.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        output.push(',');
+                    }
+                    write_newline(output, indent + 1);
+                    item.write(output, indent + 1);
+                }
+                write_newline(output, indent);
+                output.push(']');
+            }
+            Json::Object(properties) if properties.is_empty() => output.push_str("{}"),
+            Json::Object(properties) => {
+                output.push('{');
+                for (index, (key, value)) in properties.iter().enumerate() {
+                    if index > 0 {
+                        output.push(',');
+                    }
+                    write_newline(output, indent + 1);
+                    write_json_string(output, key);
+                    output.push_str(": ");
+                    value.write(output, indent + 1);
+                }
+                write_newline(output, indent);
+                output.push('}');
+            }
+        }
+    }
+}
+
+fn write_newline(output: &mut String, indent: usize) {
+    output.push('\n');
+    for _ in 0..indent {
+        output.push_str("  ");
+    }
+}
+
+fn write_json_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_str, json, Value as JsonValue};
+
+    #[derive(Sbor)]
+    struct Account {
+        owner: String,
+        balances: Vec<u64>,
+        status: Status,
+        children: Vec<Account>,
+    }
+
+    #[derive(Sbor)]
+    enum Status {
+        Active,
+        Frozen { since: i32 },
+    }
+
+    fn export<T: Describe<NoCustomTypeKind>>() -> JsonValue {
+        let (type_id, schema) = generate_full_schema_from_single_type::<T, NoCustomSchema>();
+        let json =
+            export_json_schema::<NoCustomExtension>(schema.v1(), &[("Root".to_string(), type_id)])
+                .unwrap();
+        from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn exports_named_definitions_for_schema_local_types() {
+        let document = export::<Account>();
+        let defs = &document["$defs"];
+        assert_eq!(defs["Root"], json!({ "$ref": "#/$defs/Account" }));
+
+        let account = &defs["Account"];
+        assert_eq!(account["properties"]["kind"], json!({ "const": "Tuple" }));
+        assert_eq!(
+            account["properties"]["type_name"],
+            json!({ "const": "Account" })
+        );
+        let fields = &account["properties"]["fields"];
+        assert_eq!(fields["minItems"], json!(4));
+        assert_eq!(
+            fields["prefixItems"][0]["allOf"][1]["properties"]["field_name"],
+            json!({ "const": "owner" })
+        );
+        // The recursive field refers back to the definition, via the unnamed `Vec<Account>` type
+        let children_ref = fields["prefixItems"][3]["allOf"][0]["$ref"]
+            .as_str()
+            .unwrap();
+        let children = &defs[children_ref.strip_prefix("#/$defs/").unwrap()];
+        assert_eq!(children["properties"]["kind"], json!({ "const": "Array" }));
+        assert_eq!(
+            children["properties"]["elements"]["items"],
+            json!({ "$ref": "#/$defs/Account" })
+        );
+    }
+
+    #[test]
+    fn exports_enum_variants_and_integers_as_strings() {
+        let document = export::<Status>();
+        let variants = document["$defs"]["Status"]["oneOf"].as_array().unwrap();
+        assert_eq!(variants.len(), 2);
+        assert_eq!(
+            variants[1]["properties"]["variant_id"],
+            json!({ "const": "1" })
+        );
+        assert_eq!(
+            variants[1]["properties"]["variant_name"],
+            json!({ "const": "Frozen" })
+        );
+        assert_eq!(
+            variants[1]["properties"]["fields"]["prefixItems"][0]["allOf"][0]["properties"]
+                ["value"],
+            json!({ "type": "string", "pattern": "^-?[0-9]+$" })
+        );
+    }
+}
@@ -0,0 +1,9 @@
+mod json_schema;
+mod shape;
+mod typescript;
+
+pub use json_schema::*;
+pub use shape::{CustomStringFormat, ExportableCustomExtension, SchemaExportError};
+pub use typescript::*;
+
+use shape::*;
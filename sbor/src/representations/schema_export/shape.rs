@@ -0,0 +1,298 @@
+use crate::rust::prelude::*;
+use crate::*;
+
+/// The shape of the programmatic JSON representation of a type, shared by the exporters.
+pub(super) enum ValueShape {
+    Any,
+    Terminal {
+        kind: String,
+        type_name: Option<String>,
+        value: TerminalShape,
+    },
+    Tuple {
+        type_name: Option<String>,
+        fields: Vec<FieldShape>,
+    },
+    Enum {
+        type_name: Option<String>,
+        variants: Vec<VariantShape>,
+    },
+    Bytes {
+        type_name: Option<String>,
+        length: LengthValidation,
+    },
+    Array {
+        type_name: Option<String>,
+        element_kind: Option<String>,
+        element_name: Option<String>,
+        element: ShapeRef,
+        length: LengthValidation,
+    },
+    Map {
+        type_name: Option<String>,
+        key_kind: Option<String>,
+        key_type_name: Option<String>,
+        value_kind: Option<String>,
+        value_type_name: Option<String>,
+        key: ShapeRef,
+        value: ShapeRef,
+        length: LengthValidation,
+    },
+}
+
+/// Integers are represented as strings, so that 64 and 128 bit values survive JSON parsing.
+pub(super) enum TerminalShape {
+    Boolean,
+    Integer { signed: bool },
+    String { format: Option<CustomStringFormat> },
+}
+
+pub(super) enum ShapeRef {
+    Definition(String),
+    Inline(Box<ValueShape>),
+}
+
+pub(super) struct FieldShape {
+    pub name: Option<String>,
+    pub shape: ShapeRef,
+}
+
+pub(super) struct VariantShape {
+    pub id: u8,
+    pub name: Option<String>,
+    pub fields: Vec<FieldShape>,
+}
+
+pub(super) struct ExportedShapes {
+    pub roots: Vec<(String, ShapeRef)>,
+    pub definitions: Vec<(String, ValueShape)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaExportError {
+    TypeIdNotFound(LocalTypeId),
+}
+
+/// The constraints on the string form of a custom value, eg a bech32 address or a decimal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomStringFormat {
+    /// The JSON Schema `format`, which also brands the string type in TypeScript.
+    pub format: String,
+    /// A regular expression which matches every string form of the value.
+    pub pattern: String,
+}
+
+pub trait ExportableCustomExtension: CustomExtension {
+    /// Describes the string form of a value of the given custom type kind.
+    ///
+    /// This is only called for custom type kinds, which the exporters represent as strings.
+    fn custom_string_format(
+        schema: &Schema<Self::CustomSchema>,
+        type_id: LocalTypeId,
+    ) -> Option<CustomStringFormat>;
+}
+
+/// Schema-local types become named definitions, so that recursive types can be expressed.
+/// Well-known types are inlined where they are used.
+pub(super) fn export_shapes<E: ExportableCustomExtension>(
+    schema: &Schema<E::CustomSchema>,
+    roots: &[(String, LocalTypeId)],
+) -> Result<ExportedShapes, SchemaExportError> {
+    let mut builder = ShapeBuilder::<E> {
+        schema,
+        used_names: index_set_new(),
+        local_names: index_map_new(),
+    };
+
+    let root_names: Vec<String> = roots
+        .iter()
+        .map(|(name, _)| builder.unique_name(name))
+        .collect();
+    let mut exported_roots = Vec::with_capacity(roots.len());
+    for (name, (_, type_id)) in root_names.into_iter().zip(roots) {
+        exported_roots.push((name, builder.reference(*type_id)?));
+    }
+
+    let mut definitions = vec![];
+    // Definitions are discovered while building earlier definitions, so the loop can't borrow
+    // `local_names` for its whole duration.
+    while let Some((index, name)) = builder
+        .local_names
+        .get_index(definitions.len())
+        .map(|(index, name)| (*index, name.clone()))
+    {
+        let shape = builder.shape(LocalTypeId::SchemaLocalIndex(index))?;
+        definitions.push((name, shape));
+    }
+
+    Ok(ExportedShapes {
+        roots: exported_roots,
+        definitions,
+    })
+}
+
+struct ShapeBuilder<'s, E: CustomExtension> {
+    schema: &'s Schema<E::CustomSchema>,
+    used_names: IndexSet<String>,
+    local_names: IndexMap<usize, String>,
+}
+
+impl<'s, E: ExportableCustomExtension> ShapeBuilder<'s, E> {
+    /// Returns an identifier which is valid in both JSON pointers and TypeScript.
+    fn unique_name(&mut self, name: &str) -> String {
+        let mut sanitized: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if !sanitized.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            sanitized = format!("Type{}", sanitized);
+        }
+        let mut unique = sanitized.clone();
+        let mut suffix = 2;
+        while self.used_names.contains(&unique) {
+            unique = format!("{}_{}", sanitized, suffix);
+            suffix += 1;
+        }
+        self.used_names.insert(unique.clone());
+        unique
+    }
+
+    fn reference(&mut self, type_id: LocalTypeId) -> Result<ShapeRef, SchemaExportError> {
+        match type_id {
+            LocalTypeId::SchemaLocalIndex(index) => {
+                if let Some(name) = self.local_names.get(&index) {
+                    return Ok(ShapeRef::Definition(name.clone()));
+                }
+                let name = match self.schema.resolve_type_name_from_metadata(type_id) {
+                    Some(type_name) => self.unique_name(type_name),
+                    None => self.unique_name(&format!("Type{}", index)),
+                };
+                self.local_names.insert(index, name.clone());
+                Ok(ShapeRef::Definition(name))
+            }
+            LocalTypeId::WellKnown(_) => Ok(ShapeRef::Inline(Box::new(self.shape(type_id)?))),
+        }
+    }
+
+    fn value_kind_name(&self, type_id: LocalTypeId) -> Option<String> {
+        let type_kind = self.schema.resolve_type_kind(type_id)?;
+        value_kind_for_type_kind::<E>(self.schema, type_kind).map(|kind| kind.to_string())
+    }
+
+    fn type_name(&self, type_id: LocalTypeId) -> Option<String> {
+        self.schema
+            .resolve_type_name_from_metadata(type_id)
+            .map(|name| name.to_string())
+    }
+
+    fn fields(
+        &mut self,
+        field_types: &[LocalTypeId],
+        field_names: Option<&[Cow<'static, str>]>,
+    ) -> Result<Vec<FieldShape>, SchemaExportError> {
+        field_types
+            .iter()
+            .enumerate()
+            .map(|(index, field_type)| {
+                Ok(FieldShape {
+                    name: field_names.map(|names| names[index].to_string()),
+                    shape: self.reference(*field_type)?,
+                })
+            })
+            .collect()
+    }
+
+    fn shape(&mut self, type_id: LocalTypeId) -> Result<ValueShape, SchemaExportError> {
+        let schema = self.schema;
+        let type_kind = schema
+            .resolve_type_kind(type_id)
+            .ok_or(SchemaExportError::TypeIdNotFound(type_id))?;
+        let type_name = self.type_name(type_id);
+        let length = match schema.resolve_type_validation(type_id) {
+            Some(TypeValidation::Array(length) | TypeValidation::Map(length)) => *length,
+            _ => LengthValidation::none(),
+        };
+        let terminal_value = match type_kind {
+            TypeKind::Bool => Some(TerminalShape::Boolean),
+            TypeKind::I8 | TypeKind::I16 | TypeKind::I32 | TypeKind::I64 | TypeKind::I128 => {
+                Some(TerminalShape::Integer { signed: true })
+            }
+            TypeKind::U8 | TypeKind::U16 | TypeKind::U32 | TypeKind::U64 | TypeKind::U128 => {
+                Some(TerminalShape::Integer { signed: false })
+            }
+            TypeKind::String => Some(TerminalShape::String { format: None }),
+            // Custom values are represented by their string form, eg a bech32 address or a decimal
+            TypeKind::Custom(_) => Some(TerminalShape::String {
+                format: E::custom_string_format(schema, type_id),
+            }),
+            _ => None,
+        };
+        if let Some(value) = terminal_value {
+            return Ok(ValueShape::Terminal {
+                kind: self.value_kind_name(type_id).unwrap_or_default(),
+                type_name,
+                value,
+            });
+        }
+
+        let shape = match type_kind {
+            TypeKind::Any => ValueShape::Any,
+            TypeKind::Array { element_type } => {
+                if let Some(TypeKind::U8) = schema.resolve_type_kind(*element_type) {
+                    ValueShape::Bytes { type_name, length }
+                } else {
+                    ValueShape::Array {
+                        type_name,
+                        element_kind: self.value_kind_name(*element_type),
+                        element_name: self.type_name(*element_type),
+                        element: self.reference(*element_type)?,
+                        length,
+                    }
+                }
+            }
+            TypeKind::Tuple { field_types } => {
+                let field_names = schema
+                    .resolve_matching_tuple_metadata(type_id, field_types.len())
+                    .field_names;
+                ValueShape::Tuple {
+                    type_name,
+                    fields: self.fields(field_types, field_names)?,
+                }
+            }
+            TypeKind::Enum { variants } => {
+                let mut variant_shapes = Vec::with_capacity(variants.len());
+                for (variant_id, field_types) in variants {
+                    let variant_data = schema.resolve_matching_enum_metadata(
+                        type_id,
+                        *variant_id,
+                        field_types.len(),
+                    );
+                    variant_shapes.push(VariantShape {
+                        id: *variant_id,
+                        name: variant_data.variant_name.map(|name| name.to_string()),
+                        fields: self.fields(field_types, variant_data.field_names)?,
+                    });
+                }
+                ValueShape::Enum {
+                    type_name,
+                    variants: variant_shapes,
+                }
+            }
+            TypeKind::Map {
+                key_type,
+                value_type,
+            } => ValueShape::Map {
+                type_name,
+                key_kind: self.value_kind_name(*key_type),
+                key_type_name: self.type_name(*key_type),
+                value_kind: self.value_kind_name(*value_type),
+                value_type_name: self.type_name(*value_type),
+                key: self.reference(*key_type)?,
+                value: self.reference(*value_type)?,
+                length,
+            },
+            _ => unreachable!("Terminal type kinds are handled above"),
+        };
+        Ok(shape)
+    }
+}
@@ -0,0 +1,243 @@
+use super::*;
+use crate::rust::prelude::*;
+use crate::*;
+
+/// Exports TypeScript type definitions (a `.d.ts` file) for the Programmatic JSON representation
+/// of the given root types.
+///
+/// Each root, and each schema-local type it references, becomes an exported type alias.
+pub fn export_typescript<E: ExportableCustomExtension>(
+    schema: &Schema<E::CustomSchema>,
+    roots: &[(String, LocalTypeId)],
+) -> Result<String, SchemaExportError> {
+    let ExportedShapes { roots, definitions } = export_shapes::<E>(schema, roots)?;
+
+    let mut output = String::new();
+    output.push_str("// Types of the programmatic JSON representation of SBOR values.\n\n");
+    output.push_str(
+        "export type ProgrammaticValue = {\n  kind: string;\n  type_name?: string;\n  field_name?: string;\n  [property: string]: unknown;\n};\n",
+    );
+    for (name, shape) in &roots {
+        output.push_str(&format!(
+            "\nexport type {} = {};\n",
+            name,
+            shape_ref(shape, 0)
+        ));
+    }
+    for (name, shape) in &definitions {
+        output.push_str(&format!(
+            "\nexport type {} = {};\n",
+            name,
+            value_shape(shape, 0)
+        ));
+    }
+    Ok(output)
+}
+
+fn shape_ref(shape: &ShapeRef, indent: usize) -> String {
+    match shape {
+        ShapeRef::Definition(name) => name.clone(),
+        ShapeRef::Inline(shape) => value_shape(shape, indent),
+    }
+}
+
+fn value_shape(shape: &ValueShape, indent: usize) -> String {
+    match shape {
+        ValueShape::Any => "ProgrammaticValue".to_string(),
+        ValueShape::Terminal {
+            kind,
+            type_name,
+            value,
+        } => {
+            let value = match value {
+                TerminalShape::Boolean => "boolean".to_string(),
+                TerminalShape::Integer { .. } | TerminalShape::String { format: None } => {
+                    "string".to_string()
+                }
+                // Branded, so that eg a decimal can't be passed where an address is expected
+                TerminalShape::String {
+                    format: Some(format),
+                } => format!(
+                    "string & {{ readonly __format: {} }}",
+                    literal(&format.format)
+                ),
+            };
+            value_object(kind, type_name, vec![("value", value)], indent)
+        }
+        ValueShape::Tuple { type_name, fields } => value_object(
+            "Tuple",
+            type_name,
+            vec![("fields", field_shapes(fields, indent + 1))],
+            indent,
+        ),
+        ValueShape::Enum {
+            type_name,
+            variants,
+        } => {
+            let variants: Vec<String> = variants
+                .iter()
+                .map(|variant| {
+                    let mut properties = vec![("variant_id", literal(&variant.id.to_string()))];
+                    if let Some(name) = &variant.name {
+                        properties.push(("variant_name?", literal(name)));
+                    }
+                    properties.push(("fields", field_shapes(&variant.fields, indent + 1)));
+                    value_object("Enum", type_name, properties, indent)
+                })
+                .collect();
+            // Unions are parenthesized, so that they can be intersected with a field name
+            match variants.len() {
+                0 => "never".to_string(),
+                1 => variants.join(""),
+                _ => format!("({})", variants.join(" | ")),
+            }
+        }
+        ValueShape::Bytes { type_name, .. } => value_object(
+            "Bytes",
+            type_name,
+            vec![
+                ("element_kind", literal("U8")),
+                ("hex", "string".to_string()),
+            ],
+            indent,
+        ),
+        ValueShape::Array {
+            type_name,
+            element_kind,
+            element_name,
+            element,
+            ..
+        } => {
+            let mut properties = vec![("element_kind", optional_literal(element_kind))];
+            if let Some(element_name) = element_name {
+                properties.push(("element_name?", literal(element_name)));
+            }
+            properties.push((
+                "elements",
+                format!("Array<{}>", shape_ref(element, indent + 1)),
+            ));
+            value_object("Array", type_name, properties, indent)
+        }
+        ValueShape::Map {
+            type_name,
+            key_kind,
+            key_type_name,
+            value_kind,
+            value_type_name,
+            key,
+            value,
+            ..
+        } => {
+            let mut properties = vec![("key_kind", optional_literal(key_kind))];
+            if let Some(key_type_name) = key_type_name {
+                properties.push(("key_type_name?", literal(key_type_name)));
+            }
+            properties.push(("value_kind", optional_literal(value_kind)));
+            if let Some(value_type_name) = value_type_name {
+                properties.push(("value_type_name?", literal(value_type_name)));
+            }
+            let entry = format!(
+                "{{ key: {}; value: {} }}",
+                shape_ref(key, indent + 1),
+                shape_ref(value, indent + 1)
+            );
+            properties.push(("entries", format!("Array<{}>", entry)));
+            value_object("Map", type_name, properties, indent)
+        }
+    }
+}
+
+/// Every value is an object with a `kind`, an optional `type_name` and, when it is a named
+/// field, a `field_name`.
+fn value_object(
+    kind: &str,
+    type_name: &Option<String>,
+    properties: Vec<(&str, String)>,
+    indent: usize,
+) -> String {
+    let mut all_properties = vec![("kind", literal(kind))];
+    if let Some(type_name) = type_name {
+        all_properties.push(("type_name?", literal(type_name)));
+    }
+    all_properties.push(("field_name?", "string".to_string()));
+    all_properties.extend(properties);
+
+    let mut output = "{\n".to_string();
+    for (name, type_expression) in all_properties {
+        output.push_str(&format!(
+            "{}{}: {};\n",
+            "  ".repeat(indent + 1),
+            name,
+            type_expression
+        ));
+    }
+    output.push_str(&"  ".repeat(indent));
+    output.push('}');
+    output
+}
+
+fn field_shapes(fields: &[FieldShape], indent: usize) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| match &field.name {
+            Some(name) => format!(
+                "{} & {{ field_name: {} }}",
+                shape_ref(&field.shape, indent),
+                literal(name)
+            ),
+            None => shape_ref(&field.shape, indent),
+        })
+        .collect();
+    format!("[{}]", fields.join(", "))
+}
+
+fn literal(value: &str) -> String {
+    let mut output = "\"".to_string();
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+fn optional_literal(value: &Option<String>) -> String {
+    match value {
+        Some(value) => literal(value),
+        None => "string".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Sbor)]
+    struct Order {
+        id: u64,
+        side: Side,
+        tags: Vec<String>,
+    }
+
+    #[derive(Sbor)]
+    enum Side {
+        Buy,
+        Sell,
+    }
+
+    #[test]
+    fn exports_type_aliases() {
+        let (type_id, schema) = generate_full_schema_from_single_type::<Order, NoCustomSchema>();
+        let typescript =
+            export_typescript::<NoCustomExtension>(schema.v1(), &[("Root".to_string(), type_id)])
+                .unwrap();
+
+        assert!(typescript.contains("export type Root = Order;\n"));
+        assert!(typescript.contains("  fields: [{\n"));
+        assert!(typescript.contains("} & { field_name: \"id\" }, Side & { field_name: \"side\" }"));
+        assert!(typescript.contains("export type Side = ({\n  kind: \"Enum\";\n  type_name?: \"Side\";\n  field_name?: string;\n  variant_id: \"0\";\n  variant_name?: \"Buy\";\n  fields: [];\n} | {"));
+    }
+}
@@ -20,15 +20,23 @@
 //!     // which ensures discriminators are printed first where possible, which can make deserialization more
 //!     // efficient in some cases.
 //!     let json = serde_json::to_string(&serializable).unwrap();
+//!
+//!     // The Programmatic and Natural modes can be deserialized back into a payload.
+//!     let payload_bytes = deserialize_payload(
+//!         &mut serde_json::Deserializer::from_str(&json),
+//!         // Provide some DeserializationParameters
+//!     ).unwrap();
 //! ```
 
 // Imports and Exports
 mod contextual_serialize;
+mod serde_deserializer;
 mod serde_serializer;
 mod traits;
 mod value_map_aggregator;
 
 pub use contextual_serialize::*;
+pub use serde_deserializer::*;
 pub use serde_serializer::*;
 pub use traits::*;
 pub use value_map_aggregator::*;
@@ -0,0 +1,1062 @@
+use super::*;
+use crate::rust::fmt;
+use crate::rust::prelude::*;
+use crate::rust::str::FromStr;
+use crate::traversal::*;
+use crate::*;
+use serde::de::{self, Deserialize, Deserializer, Error as _, MapAccess, SeqAccess, Visitor};
+
+/// The inverse of [`SerializationParameters`].
+///
+/// Only the [`SerializationMode::Programmatic`] and [`SerializationMode::Natural`] modes can be
+/// deserialized. As the Natural mode drops the value kinds, it can only be deserialized with a
+/// schema.
+pub enum DeserializationParameters<'s, 'a, E: DeserializableCustomExtension> {
+    Schemaless {
+        mode: SerializationMode,
+        custom_context: E::CustomParseContext<'a>,
+        depth_limit: usize,
+    },
+    WithSchema {
+        mode: SerializationMode,
+        custom_context: E::CustomParseContext<'a>,
+        schema: &'s Schema<E::CustomSchema>,
+        type_id: LocalTypeId,
+        depth_limit: usize,
+    },
+}
+
+impl<'s, 'a, E: DeserializableCustomExtension> DeserializationParameters<'s, 'a, E> {
+    fn get_context_params(&self) -> (DeserializationContext<'s, 'a, E>, LocalTypeId) {
+        match self {
+            DeserializationParameters::Schemaless {
+                mode,
+                custom_context,
+                depth_limit,
+            } => (
+                DeserializationContext {
+                    schema: E::CustomSchema::empty_schema(),
+                    mode: *mode,
+                    custom_context: *custom_context,
+                    depth_limit: *depth_limit,
+                },
+                LocalTypeId::any(),
+            ),
+            DeserializationParameters::WithSchema {
+                mode,
+                custom_context,
+                schema,
+                type_id,
+                depth_limit,
+            } => (
+                DeserializationContext {
+                    schema: *schema,
+                    mode: *mode,
+                    custom_context: *custom_context,
+                    depth_limit: *depth_limit,
+                },
+                *type_id,
+            ),
+        }
+    }
+}
+
+pub type DeserializedValue<E> = ParsedValue<E>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeserializationError {
+    UnsupportedMode(SerializationMode),
+    UnexpectedJsonType {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    MissingField(String),
+    UnknownValueKind(String),
+    InvalidInteger(String),
+    InvalidHex(String),
+    InvalidCustomValue {
+        value_kind: String,
+        error: String,
+    },
+    MismatchingChildValueKind {
+        expected: String,
+        actual: String,
+    },
+    MismatchingFieldCount {
+        expected: usize,
+        actual: usize,
+    },
+    UnknownEnumVariant(String),
+    TypeIdNotFound(LocalTypeId),
+    NoValueKindForType(LocalTypeId),
+    DepthLimitExceeded,
+    EncodeError(EncodeError),
+    /// The value doesn't match the type in the schema
+    SchemaMismatch(String),
+}
+
+impl fmt::Display for DeserializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Deserializes a value from any self-describing serde format (eg JSON), as output by the
+/// serializer in the given mode.
+///
+/// If a schema is provided, the value is checked against the given type.
+pub fn deserialize_value<'de, D: Deserializer<'de>, E: DeserializableCustomExtension>(
+    deserializer: D,
+    parameters: &DeserializationParameters<'_, '_, E>,
+) -> Result<DeserializedValue<E>, D::Error> {
+    let serde_value = SerdeValue::deserialize(deserializer)?;
+    deserialize_value_and_payload(&serde_value, parameters)
+        .map(|(value, _)| value)
+        .map_err(D::Error::custom)
+}
+
+/// Deserializes a value as [`deserialize_value`] does, and encodes it into a payload.
+pub fn deserialize_payload<'de, D: Deserializer<'de>, E: DeserializableCustomExtension>(
+    deserializer: D,
+    parameters: &DeserializationParameters<'_, '_, E>,
+) -> Result<Vec<u8>, D::Error> {
+    let serde_value = SerdeValue::deserialize(deserializer)?;
+    deserialize_value_and_payload(&serde_value, parameters)
+        .map(|(_, payload)| payload)
+        .map_err(D::Error::custom)
+}
+
+fn deserialize_value_and_payload<E: DeserializableCustomExtension>(
+    serde_value: &SerdeValue,
+    parameters: &DeserializationParameters<'_, '_, E>,
+) -> Result<(DeserializedValue<E>, Vec<u8>), DeserializationError> {
+    let (context, type_id) = parameters.get_context_params();
+    let value = match context.mode {
+        SerializationMode::Programmatic => context.programmatic_value(serde_value, 1)?,
+        SerializationMode::Natural => context.natural_value(serde_value, type_id, 1)?,
+        SerializationMode::Model => {
+            return Err(DeserializationError::UnsupportedMode(context.mode));
+        }
+    };
+
+    let mut payload = Vec::with_capacity(512);
+    VecEncoder::<E::CustomValueKind>::new(&mut payload, context.depth_limit)
+        .encode_payload(&value, E::PAYLOAD_PREFIX)
+        .map_err(DeserializationError::EncodeError)?;
+
+    // In Programmatic mode, the value is built from the kinds in the input, so it still needs
+    // to be checked against the schema type
+    let mut traverser =
+        traverse_payload_with_types::<E>(&payload, context.schema, type_id, context.depth_limit);
+    traverser
+        .consume_value_tree()
+        .and_then(|_| traverser.consume_end_event())
+        .map_err(DeserializationError::SchemaMismatch)?;
+
+    Ok((value, payload))
+}
+
+struct DeserializationContext<'s, 'a, E: DeserializableCustomExtension> {
+    schema: &'s Schema<E::CustomSchema>,
+    mode: SerializationMode,
+    custom_context: E::CustomParseContext<'a>,
+    depth_limit: usize,
+}
+
+impl<'s, 'a, E: DeserializableCustomExtension> DeserializationContext<'s, 'a, E> {
+    fn check_depth(&self, depth: usize) -> Result<(), DeserializationError> {
+        if depth > self.depth_limit {
+            return Err(DeserializationError::DepthLimitExceeded);
+        }
+        Ok(())
+    }
+
+    fn custom_value(
+        &self,
+        custom_value_kind: E::CustomValueKind,
+        value: &str,
+    ) -> Result<DeserializedValue<E>, DeserializationError> {
+        let value = E::parse_custom_value_content(&self.custom_context, custom_value_kind, value)
+            .map_err(|error| DeserializationError::InvalidCustomValue {
+            value_kind: ValueKind::Custom(custom_value_kind).to_string(),
+            error,
+        })?;
+        Ok(Value::Custom { value })
+    }
+
+    fn programmatic_value(
+        &self,
+        serde_value: &SerdeValue,
+        depth: usize,
+    ) -> Result<DeserializedValue<E>, DeserializationError> {
+        self.check_depth(depth)?;
+        let object = serde_value.as_object()?;
+        let kind = object.get("kind")?.as_str()?;
+        // U8 arrays are output with their own kind name
+        if kind == "Bytes" {
+            return Ok(Value::Array {
+                element_value_kind: ValueKind::U8,
+                elements: bytes_from_hex(object.get("hex")?.as_str()?)?,
+            });
+        }
+        let value = match parse_value_kind::<E::CustomValueKind>(kind)? {
+            ValueKind::Bool => Value::Bool {
+                value: object.get("value")?.as_bool()?,
+            },
+            ValueKind::I8 => Value::I8 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::I16 => Value::I16 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::I32 => Value::I32 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::I64 => Value::I64 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::I128 => Value::I128 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::U8 => Value::U8 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::U16 => Value::U16 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::U32 => Value::U32 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::U64 => Value::U64 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::U128 => Value::U128 {
+                value: object.get("value")?.as_integer()?,
+            },
+            ValueKind::String => Value::String {
+                value: object.get("value")?.as_str()?.to_string(),
+            },
+            ValueKind::Tuple => Value::Tuple {
+                fields: self.programmatic_values(object.get("fields")?, depth)?,
+            },
+            ValueKind::Enum => Value::Enum {
+                discriminator: object.get("variant_id")?.as_integer()?,
+                fields: self.programmatic_values(object.get("fields")?, depth)?,
+            },
+            ValueKind::Array => {
+                let element_value_kind = parse_value_kind(object.get("element_kind")?.as_str()?)?;
+                let elements = match (element_value_kind, object.find("hex")) {
+                    (ValueKind::U8, Some(hex)) => bytes_from_hex(hex.as_str()?)?,
+                    _ => self.programmatic_values(object.get("elements")?, depth)?,
+                };
+                for element in &elements {
+                    check_child_value_kind(element_value_kind, element)?;
+                }
+                Value::Array {
+                    element_value_kind,
+                    elements,
+                }
+            }
+            ValueKind::Map => {
+                let key_value_kind = parse_value_kind(object.get("key_kind")?.as_str()?)?;
+                let value_value_kind = parse_value_kind(object.get("value_kind")?.as_str()?)?;
+                let entries = object
+                    .get("entries")?
+                    .as_array()?
+                    .iter()
+                    .map(|entry| {
+                        let entry = entry.as_object()?;
+                        let key = self.programmatic_value(entry.get("key")?, depth + 1)?;
+                        let value = self.programmatic_value(entry.get("value")?, depth + 1)?;
+                        check_child_value_kind(key_value_kind, &key)?;
+                        check_child_value_kind(value_value_kind, &value)?;
+                        Ok((key, value))
+                    })
+                    .collect::<Result<_, DeserializationError>>()?;
+                Value::Map {
+                    key_value_kind,
+                    value_value_kind,
+                    entries,
+                }
+            }
+            ValueKind::Custom(custom_value_kind) => {
+                self.custom_value(custom_value_kind, object.get("value")?.as_str()?)?
+            }
+        };
+        Ok(value)
+    }
+
+    fn programmatic_values(
+        &self,
+        serde_value: &SerdeValue,
+        depth: usize,
+    ) -> Result<Vec<DeserializedValue<E>>, DeserializationError> {
+        serde_value
+            .as_array()?
+            .iter()
+            .map(|child| self.programmatic_value(child, depth + 1))
+            .collect()
+    }
+
+    fn natural_value(
+        &self,
+        serde_value: &SerdeValue,
+        type_id: LocalTypeId,
+        depth: usize,
+    ) -> Result<DeserializedValue<E>, DeserializationError> {
+        self.check_depth(depth)?;
+        let type_kind = self
+            .schema
+            .resolve_type_kind(type_id)
+            .ok_or(DeserializationError::TypeIdNotFound(type_id))?;
+        let value = match type_kind {
+            // The value kind is not included in the Natural mode, so it can't be deserialized
+            // without a concrete type
+            TypeKind::Any => return Err(DeserializationError::NoValueKindForType(type_id)),
+            TypeKind::Bool => Value::Bool {
+                value: serde_value.as_bool()?,
+            },
+            TypeKind::I8 => Value::I8 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::I16 => Value::I16 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::I32 => Value::I32 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::I64 => Value::I64 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::I128 => Value::I128 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::U8 => Value::U8 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::U16 => Value::U16 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::U32 => Value::U32 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::U64 => Value::U64 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::U128 => Value::U128 {
+                value: serde_value.as_integer()?,
+            },
+            TypeKind::String => Value::String {
+                value: serde_value.as_str()?.to_string(),
+            },
+            TypeKind::Array { element_type } => {
+                let element_value_kind = self.value_kind_of(*element_type)?;
+                let elements = match element_value_kind {
+                    ValueKind::U8 => {
+                        bytes_from_hex(serde_value.as_object()?.get("hex")?.as_str()?)?
+                    }
+                    _ => serde_value
+                        .as_array()?
+                        .iter()
+                        .map(|element| self.natural_value(element, *element_type, depth + 1))
+                        .collect::<Result<_, _>>()?,
+                };
+                Value::Array {
+                    element_value_kind,
+                    elements,
+                }
+            }
+            TypeKind::Tuple { field_types } => {
+                let tuple_metadata = self
+                    .schema
+                    .resolve_matching_tuple_metadata(type_id, field_types.len());
+                Value::Tuple {
+                    fields: self.natural_fields(
+                        serde_value,
+                        field_types,
+                        tuple_metadata.field_names,
+                        depth,
+                    )?,
+                }
+            }
+            TypeKind::Enum { variants } => {
+                let object = serde_value.as_object()?;
+                let discriminator = match object.find("variant_id") {
+                    Some(variant_id) => variant_id.as_integer()?,
+                    None => {
+                        let variant_name = object.get("variant_name")?.as_str()?;
+                        variants
+                            .iter()
+                            .find(|(variant_id, field_types)| {
+                                self.schema
+                                    .resolve_matching_enum_metadata(
+                                        type_id,
+                                        **variant_id,
+                                        field_types.len(),
+                                    )
+                                    .variant_name
+                                    == Some(variant_name)
+                            })
+                            .map(|(variant_id, _)| *variant_id)
+                            .ok_or_else(|| {
+                                DeserializationError::UnknownEnumVariant(variant_name.to_string())
+                            })?
+                    }
+                };
+                let field_types = variants.get(&discriminator).ok_or_else(|| {
+                    DeserializationError::UnknownEnumVariant(discriminator.to_string())
+                })?;
+                let enum_metadata = self.schema.resolve_matching_enum_metadata(
+                    type_id,
+                    discriminator,
+                    field_types.len(),
+                );
+                Value::Enum {
+                    discriminator,
+                    fields: self.natural_fields(
+                        object.get("fields")?,
+                        field_types,
+                        enum_metadata.field_names,
+                        depth,
+                    )?,
+                }
+            }
+            TypeKind::Map {
+                key_type,
+                value_type,
+            } => {
+                let key_value_kind = self.value_kind_of(*key_type)?;
+                let value_value_kind = self.value_kind_of(*value_type)?;
+                let entries = match (key_value_kind, serde_value) {
+                    // Maps with string keys are output as JSON objects
+                    (ValueKind::String, SerdeValue::Map(entries)) => entries
+                        .iter()
+                        .map(|(key, value)| {
+                            Ok((
+                                Value::String { value: key.clone() },
+                                self.natural_value(value, *value_type, depth + 1)?,
+                            ))
+                        })
+                        .collect::<Result<_, DeserializationError>>()?,
+                    _ => serde_value
+                        .as_array()?
+                        .iter()
+                        .map(|entry| {
+                            let entry = entry.as_object()?;
+                            Ok((
+                                self.natural_value(entry.get("key")?, *key_type, depth + 1)?,
+                                self.natural_value(entry.get("value")?, *value_type, depth + 1)?,
+                            ))
+                        })
+                        .collect::<Result<_, DeserializationError>>()?,
+                };
+                Value::Map {
+                    key_value_kind,
+                    value_value_kind,
+                    entries,
+                }
+            }
+            TypeKind::Custom(_) => match serde_value {
+                // Some custom values are tagged with their kind
+                SerdeValue::Map(_) => {
+                    let object = serde_value.as_object()?;
+                    let kind = object.get("kind")?.as_str()?;
+                    let ValueKind::Custom(custom_value_kind) =
+                        parse_value_kind::<E::CustomValueKind>(kind)?
+                    else {
+                        return Err(DeserializationError::UnknownValueKind(kind.to_string()));
+                    };
+                    self.custom_value(custom_value_kind, object.get("value")?.as_str()?)?
+                }
+                _ => match self.value_kind_of(type_id)? {
+                    ValueKind::Custom(custom_value_kind) => {
+                        self.custom_value(custom_value_kind, serde_value.as_str()?)?
+                    }
+                    _ => return Err(DeserializationError::NoValueKindForType(type_id)),
+                },
+            },
+        };
+        Ok(value)
+    }
+
+    fn natural_fields(
+        &self,
+        serde_value: &SerdeValue,
+        field_types: &[LocalTypeId],
+        field_names: Option<&[Cow<'static, str>]>,
+        depth: usize,
+    ) -> Result<Vec<DeserializedValue<E>>, DeserializationError> {
+        match (serde_value, field_names) {
+            // Structs are output as JSON objects
+            (SerdeValue::Map(_), Some(field_names)) if field_names.len() == field_types.len() => {
+                let object = serde_value.as_object()?;
+                field_names
+                    .iter()
+                    .zip(field_types)
+                    .map(|(field_name, field_type)| {
+                        self.natural_value(object.get(field_name)?, *field_type, depth + 1)
+                    })
+                    .collect()
+            }
+            _ => {
+                let fields = serde_value.as_array()?;
+                if fields.len() != field_types.len() {
+                    return Err(DeserializationError::MismatchingFieldCount {
+                        expected: field_types.len(),
+                        actual: fields.len(),
+                    });
+                }
+                fields
+                    .iter()
+                    .zip(field_types)
+                    .map(|(field, field_type)| self.natural_value(field, *field_type, depth + 1))
+                    .collect()
+            }
+        }
+    }
+
+    /// Resolves the value kind of values of the given type, as the Natural mode doesn't include it.
+    fn value_kind_of(
+        &self,
+        type_id: LocalTypeId,
+    ) -> Result<ValueKind<E::CustomValueKind>, DeserializationError> {
+        let type_kind = self
+            .schema
+            .resolve_type_kind(type_id)
+            .ok_or(DeserializationError::TypeIdNotFound(type_id))?;
+        value_kind_for_type_kind::<E>(self.schema, type_kind)
+            .ok_or(DeserializationError::NoValueKindForType(type_id))
+    }
+}
+
+fn parse_value_kind<X: CustomValueKind>(name: &str) -> Result<ValueKind<X>, DeserializationError> {
+    (0..=u8::MAX)
+        .filter_map(ValueKind::<X>::from_u8)
+        .find(|value_kind| value_kind.to_string() == name)
+        .ok_or_else(|| DeserializationError::UnknownValueKind(name.to_string()))
+}
+
+fn check_child_value_kind<X: CustomValueKind, Y: CustomValue<X>>(
+    expected: ValueKind<X>,
+    child: &Value<X, Y>,
+) -> Result<(), DeserializationError> {
+    let actual = child.get_value_kind();
+    if actual != expected {
+        return Err(DeserializationError::MismatchingChildValueKind {
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn bytes_from_hex<X: CustomValueKind, Y: CustomValue<X>>(
+    hex: &str,
+) -> Result<Vec<Value<X, Y>>, DeserializationError> {
+    let bytes = hex::decode(hex).map_err(|_| DeserializationError::InvalidHex(hex.to_string()))?;
+    Ok(bytes.into_iter().map(|value| Value::U8 { value }).collect())
+}
+
+/// A self-describing value of the serde data model, which the output of the serializer is
+/// first deserialized into, before being interpreted with the schema.
+///
+/// Floats aren't output in any mode, so only their presence is recorded.
+#[derive(Debug, Clone)]
+enum SerdeValue {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    UnsignedInteger(u128),
+    Float,
+    String(String),
+    Sequence(Vec<SerdeValue>),
+    Map(Vec<(String, SerdeValue)>),
+}
+
+struct SerdeObject<'v>(&'v [(String, SerdeValue)]);
+
+impl<'v> SerdeObject<'v> {
+    fn find(&self, field_name: &str) -> Option<&'v SerdeValue> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == field_name)
+            .map(|(_, value)| value)
+    }
+
+    fn get(&self, field_name: &str) -> Result<&'v SerdeValue, DeserializationError> {
+        self.find(field_name)
+            .ok_or_else(|| DeserializationError::MissingField(field_name.to_string()))
+    }
+}
+
+impl SerdeValue {
+    fn json_type(&self) -> &'static str {
+        match self {
+            SerdeValue::Null => "null",
+            SerdeValue::Bool(_) => "boolean",
+            SerdeValue::Integer(_) | SerdeValue::UnsignedInteger(_) | SerdeValue::Float => "number",
+            SerdeValue::String(_) => "string",
+            SerdeValue::Sequence(_) => "array",
+            SerdeValue::Map(_) => "object",
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> DeserializationError {
+        DeserializationError::UnexpectedJsonType {
+            expected,
+            actual: self.json_type(),
+        }
+    }
+
+    fn as_bool(&self) -> Result<bool, DeserializationError> {
+        match self {
+            SerdeValue::Bool(value) => Ok(*value),
+            _ => Err(self.unexpected("boolean")),
+        }
+    }
+
+    fn as_str(&self) -> Result<&str, DeserializationError> {
+        match self {
+            SerdeValue::String(value) => Ok(value),
+            _ => Err(self.unexpected("string")),
+        }
+    }
+
+    fn as_array(&self) -> Result<&[SerdeValue], DeserializationError> {
+        match self {
+            SerdeValue::Sequence(values) => Ok(values),
+            _ => Err(self.unexpected("array")),
+        }
+    }
+
+    fn as_object(&self) -> Result<SerdeObject<'_>, DeserializationError> {
+        match self {
+            SerdeValue::Map(entries) => Ok(SerdeObject(entries)),
+            _ => Err(self.unexpected("object")),
+        }
+    }
+
+    /// Integers are accepted both as numbers and as strings, as the serializer outputs either
+    /// depending on the mode and the integer size.
+    fn as_integer<T: FromStr + TryFrom<i128> + TryFrom<u128>>(
+        &self,
+    ) -> Result<T, DeserializationError> {
+        let (integer, display) = match self {
+            SerdeValue::String(value) => (value.parse().ok(), value.clone()),
+            SerdeValue::Integer(value) => (
+                <T as TryFrom<i128>>::try_from(*value).ok(),
+                value.to_string(),
+            ),
+            SerdeValue::UnsignedInteger(value) => (
+                <T as TryFrom<u128>>::try_from(*value).ok(),
+                value.to_string(),
+            ),
+            _ => return Err(self.unexpected("integer")),
+        };
+        integer.ok_or(DeserializationError::InvalidInteger(display))
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(SerdeValueVisitor)
+    }
+}
+
+struct SerdeValueVisitor;
+
+impl<'de> Visitor<'de> for SerdeValueVisitor {
+    type Value = SerdeValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any self-describing value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(SerdeValue::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(SerdeValue::Integer(value.into()))
+    }
+
+    fn visit_i128<E: de::Error>(self, value: i128) -> Result<Self::Value, E> {
+        Ok(SerdeValue::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(SerdeValue::UnsignedInteger(value.into()))
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<Self::Value, E> {
+        Ok(SerdeValue::UnsignedInteger(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(SerdeValue::Float)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(SerdeValue::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Self::Value, E> {
+        Ok(SerdeValue::String(value))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(SerdeValue::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(SerdeValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        SerdeValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(SerdeValue::Sequence(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry::<String, SerdeValue>()? {
+            entries.push(entry);
+        }
+        Ok(SerdeValue::Map(entries))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")] // Ensures that VS Code runs this module with the features serde tag!
+mod tests {
+    use super::*;
+    use serde_json::to_string;
+
+    fn round_trip<T: BasicEncode + BasicDescribe>(value: &T, mode: SerializationMode) -> Vec<u8> {
+        let (type_id, schema) = generate_full_schema_from_single_type::<T, NoCustomSchema>();
+        let payload = basic_encode(value).unwrap();
+        let json = to_string(
+            &BasicRawPayload::new_from_valid_slice_with_checks(&payload)
+                .unwrap()
+                .serializable(SerializationParameters::WithSchema {
+                    mode,
+                    custom_context: (),
+                    schema: schema.v1(),
+                    type_id,
+                    depth_limit: 64,
+                }),
+        )
+        .unwrap();
+        let deserialized = deserialize_payload(
+            &mut serde_json::Deserializer::from_str(&json),
+            &DeserializationParameters::<NoCustomExtension>::WithSchema {
+                mode,
+                custom_context: (),
+                schema: schema.v1(),
+                type_id,
+                depth_limit: 64,
+            },
+        )
+        .unwrap();
+        assert_eq!(deserialized, payload, "Mismatching round trip of {}", json);
+        deserialized
+    }
+
+    #[derive(BasicSbor)]
+    enum TestEnum {
+        UnitVariant,
+        SingleFieldVariant { field: u8 },
+        TupleVariant(i64, String),
+    }
+
+    #[derive(BasicSbor)]
+    struct TestStruct {
+        bytes: Vec<u8>,
+        numbers: Vec<u128>,
+        string_map: BTreeMap<String, TestEnum>,
+        enum_map: IndexMap<u16, (bool, i8)>,
+        enums: Vec<TestEnum>,
+        value: BasicValue,
+    }
+
+    fn test_struct() -> TestStruct {
+        TestStruct {
+            bytes: vec![1, 2, 255],
+            numbers: vec![0, u128::MAX],
+            string_map: btreemap! {
+                "a".to_string() => TestEnum::UnitVariant,
+                "b".to_string() => TestEnum::SingleFieldVariant { field: 7 },
+            },
+            enum_map: indexmap! {
+                3 => (true, -5),
+                1 => (false, i8::MIN),
+            },
+            enums: vec![
+                TestEnum::TupleVariant(i64::MIN, "hello".to_string()),
+                TestEnum::UnitVariant,
+            ],
+            value: Value::Tuple {
+                fields: vec![
+                    Value::U32 { value: u32::MAX },
+                    Value::Array {
+                        element_value_kind: ValueKind::U8,
+                        elements: vec![],
+                    },
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_programmatic_round_trip() {
+        round_trip(&test_struct(), SerializationMode::Programmatic);
+    }
+
+    #[test]
+    fn test_programmatic_round_trip_without_schema() {
+        let payload = basic_encode(&test_struct()).unwrap();
+        let json = to_string(
+            &BasicRawPayload::new_from_valid_slice_with_checks(&payload)
+                .unwrap()
+                .serializable(SerializationParameters::Schemaless {
+                    mode: SerializationMode::Programmatic,
+                    custom_context: (),
+                    depth_limit: 64,
+                }),
+        )
+        .unwrap();
+        let value = deserialize_value(
+            &mut serde_json::Deserializer::from_str(&json),
+            &DeserializationParameters::<NoCustomExtension>::Schemaless {
+                mode: SerializationMode::Programmatic,
+                custom_context: (),
+                depth_limit: 64,
+            },
+        )
+        .unwrap();
+        assert_eq!(basic_encode(&value).unwrap(), payload);
+    }
+
+    /// A deterministic generator of arbitrary basic values, covering all value kinds.
+    struct ValueGenerator(u64);
+
+    impl ValueGenerator {
+        fn next(&mut self) -> u64 {
+            // xorshift64
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: u64) -> usize {
+            (self.next() % bound) as usize
+        }
+
+        fn string(&mut self) -> String {
+            // Includes characters which need escaping in JSON
+            const CHARS: &[char] = &['a', 'Z', '0', ' ', '"', '\\', '\n', '\u{0}', 'é', '🦀'];
+            let length = self.below(8);
+            (0..length)
+                .map(|_| CHARS[self.below(CHARS.len() as u64)])
+                .collect()
+        }
+
+        fn value_kind(&mut self, depth: usize) -> BasicValueKind {
+            let value_kinds: &[BasicValueKind] = &[
+                ValueKind::Bool,
+                ValueKind::I8,
+                ValueKind::I16,
+                ValueKind::I32,
+                ValueKind::I64,
+                ValueKind::I128,
+                ValueKind::U8,
+                ValueKind::U16,
+                ValueKind::U32,
+                ValueKind::U64,
+                ValueKind::U128,
+                ValueKind::String,
+                ValueKind::Tuple,
+                ValueKind::Enum,
+                ValueKind::Array,
+                ValueKind::Map,
+            ];
+            // Containers are only generated up to a few levels deep
+            let count = if depth < 3 {
+                value_kinds.len()
+            } else {
+                value_kinds.len() - 4
+            };
+            value_kinds[self.below(count as u64)]
+        }
+
+        fn value(&mut self, value_kind: BasicValueKind, depth: usize) -> BasicValue {
+            match value_kind {
+                ValueKind::Bool => Value::Bool {
+                    value: self.next() % 2 == 0,
+                },
+                ValueKind::I8 => Value::I8 {
+                    value: self.next() as i8,
+                },
+                ValueKind::I16 => Value::I16 {
+                    value: self.next() as i16,
+                },
+                ValueKind::I32 => Value::I32 {
+                    value: self.next() as i32,
+                },
+                ValueKind::I64 => Value::I64 {
+                    value: self.next() as i64,
+                },
+                ValueKind::I128 => Value::I128 {
+                    value: (((self.next() as u128) << 64) | self.next() as u128) as i128,
+                },
+                ValueKind::U8 => Value::U8 {
+                    value: self.next() as u8,
+                },
+                ValueKind::U16 => Value::U16 {
+                    value: self.next() as u16,
+                },
+                ValueKind::U32 => Value::U32 {
+                    value: self.next() as u32,
+                },
+                ValueKind::U64 => Value::U64 { value: self.next() },
+                ValueKind::U128 => Value::U128 {
+                    value: ((self.next() as u128) << 64) | self.next() as u128,
+                },
+                ValueKind::String => Value::String {
+                    value: self.string(),
+                },
+                ValueKind::Tuple => Value::Tuple {
+                    fields: self.values(depth),
+                },
+                ValueKind::Enum => Value::Enum {
+                    discriminator: self.next() as u8,
+                    fields: self.values(depth),
+                },
+                ValueKind::Array => {
+                    let element_value_kind = self.value_kind(depth + 1);
+                    let length = self.below(4);
+                    Value::Array {
+                        element_value_kind,
+                        elements: (0..length)
+                            .map(|_| self.value(element_value_kind, depth + 1))
+                            .collect(),
+                    }
+                }
+                ValueKind::Map => {
+                    let key_value_kind = self.value_kind(depth + 1);
+                    let value_value_kind = self.value_kind(depth + 1);
+                    let length = self.below(4);
+                    Value::Map {
+                        key_value_kind,
+                        value_value_kind,
+                        entries: (0..length)
+                            .map(|_| {
+                                (
+                                    self.value(key_value_kind, depth + 1),
+                                    self.value(value_value_kind, depth + 1),
+                                )
+                            })
+                            .collect(),
+                    }
+                }
+                ValueKind::Custom(custom_value_kind) => match custom_value_kind {},
+            }
+        }
+
+        fn values(&mut self, depth: usize) -> Vec<BasicValue> {
+            let length = self.below(4);
+            (0..length)
+                .map(|_| {
+                    let value_kind = self.value_kind(depth + 1);
+                    self.value(value_kind, depth + 1)
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_programmatic_round_trip_of_generated_values() {
+        let mut generator = ValueGenerator(0x2545f4914f6cdd1d);
+        for _ in 0..500 {
+            let value_kind = generator.value_kind(0);
+            let value = generator.value(value_kind, 0);
+            let payload = basic_encode(&value).unwrap();
+
+            let json = to_string(
+                &BasicRawPayload::new_from_valid_slice_with_checks(&payload)
+                    .unwrap()
+                    .serializable(SerializationParameters::Schemaless {
+                        mode: SerializationMode::Programmatic,
+                        custom_context: (),
+                        depth_limit: 64,
+                    }),
+            )
+            .unwrap();
+            let deserialized = deserialize_value(
+                &mut serde_json::Deserializer::from_str(&json),
+                &DeserializationParameters::<NoCustomExtension>::Schemaless {
+                    mode: SerializationMode::Programmatic,
+                    custom_context: (),
+                    depth_limit: 64,
+                },
+            )
+            .unwrap();
+            assert_eq!(deserialized, value, "Mismatching round trip of {}", json);
+
+            let deserialized = round_trip(&value, SerializationMode::Programmatic);
+            assert_eq!(basic_decode::<BasicValue>(&deserialized).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_natural_round_trip() {
+        // The untyped `BasicValue` field can't be deserialized from the Natural mode
+        let value = (
+            vec![1u8, 2, 255],
+            vec![0u128, u128::MAX],
+            btreemap! { "a".to_string() => TestEnum::UnitVariant },
+            indexmap! { 3u16 => (true, -5i8) },
+            vec![TestEnum::TupleVariant(i64::MIN, "hello".to_string())],
+        );
+        round_trip(&value, SerializationMode::Natural);
+    }
+
+    #[test]
+    fn test_value_not_matching_schema_is_rejected() {
+        let (type_id, schema) = generate_full_schema_from_single_type::<u8, NoCustomSchema>();
+        let result = deserialize_payload(
+            &mut serde_json::Deserializer::from_str(r#"{ "kind": "U16", "value": "1" }"#),
+            &DeserializationParameters::<NoCustomExtension>::WithSchema {
+                mode: SerializationMode::Programmatic,
+                custom_context: (),
+                schema: schema.v1(),
+                type_id,
+                depth_limit: 64,
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_depth_limit_is_enforced() {
+        let result = deserialize_value(
+            &mut serde_json::Deserializer::from_str(
+                r#"{ "kind": "Tuple", "fields": [{ "kind": "Tuple", "fields": [] }] }"#,
+            ),
+            &DeserializationParameters::<NoCustomExtension>::Schemaless {
+                mode: SerializationMode::Programmatic,
+                custom_context: (),
+                depth_limit: 1,
+            },
+        );
+        assert!(result.is_err());
+    }
+}
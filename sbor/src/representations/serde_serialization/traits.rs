@@ -17,3 +17,13 @@ pub trait SerializableCustomExtension: FormattableCustomExtension + Copy {
         value: <Self::CustomTraversal as CustomTraversal>::CustomTerminalValueRef<'de>,
     ) -> CustomTypeSerialization<'a, 't, 'de, 's1, 's2, Self>;
 }
+
+pub trait DeserializableCustomExtension:
+    SerializableCustomExtension + ParseableCustomExtension
+{
+}
+
+impl<E: SerializableCustomExtension + ParseableCustomExtension> DeserializableCustomExtension
+    for E
+{
+}
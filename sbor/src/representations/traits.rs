@@ -20,3 +20,21 @@ pub trait FormattableCustomExtension: CustomExtension + Copy {
         value: &<Self::CustomTraversal as CustomTraversal>::CustomTerminalValueRef<'de>,
     ) -> Result<(), fmt::Error>;
 }
+
+pub type ParsedValue<E> =
+    Value<<E as CustomExtension>::CustomValueKind, <E as ParseableCustomExtension>::CustomValue>;
+
+pub trait ParseableCustomExtension: CustomExtension {
+    type CustomValue: CustomValue<Self::CustomValueKind>
+        + for<'b> Encode<Self::CustomValueKind, VecEncoder<'b, Self::CustomValueKind>>;
+
+    type CustomParseContext<'a>: Copy;
+
+    /// Parses a custom value from its string content, which is the inverse of `display_string_content`
+    /// (without the quotes), and is shared with the serde representations.
+    fn parse_custom_value_content(
+        context: &Self::CustomParseContext<'_>,
+        custom_value_kind: Self::CustomValueKind,
+        content: &str,
+    ) -> Result<Self::CustomValue, String>;
+}
@@ -0,0 +1,730 @@
+use crate::rust::prelude::*;
+use crate::*;
+
+/// How a change between two versions of a type affects payloads encoded against the old version.
+///
+/// The levels are ordered, so the compatibility of a set of changes is the maximum of its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SchemaCompatibility {
+    /// Exactly the same payloads are valid under both versions (eg a field was renamed).
+    Compatible,
+    /// Every payload valid under the old version is valid under the new version, but not
+    /// vice versa (eg an enum variant was added, or a validation was loosened).
+    Additive,
+    /// Some payloads valid under the old version are invalid under the new version.
+    Breaking,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChangeKind {
+    /// The compared item exists in the old version only.
+    Removed,
+    /// The compared item exists in the new version only.
+    Added,
+    /// A type id could not be resolved in its schema.
+    TypeNotFound,
+    /// The type kind was replaced by `Any`.
+    TypeKindGeneralized {
+        old: String,
+    },
+    TypeKindChanged {
+        old: String,
+        new: String,
+    },
+    FieldCountChanged {
+        old: usize,
+        new: usize,
+    },
+    EnumVariantAdded {
+        discriminator: u8,
+    },
+    EnumVariantRemoved {
+        discriminator: u8,
+    },
+    ValidationLoosened,
+    ValidationTightened,
+    CustomValidationChanged,
+    TypeNameChanged {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    FieldNamesChanged {
+        old: Option<Vec<String>>,
+        new: Option<Vec<String>>,
+    },
+    EnumVariantNameChanged {
+        discriminator: u8,
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+impl SchemaChangeKind {
+    /// Note that renames are classed as compatible, as they don't affect the encoded payload,
+    /// although they do affect any representation which includes names (eg annotated JSON).
+    pub fn compatibility(&self) -> SchemaCompatibility {
+        match self {
+            SchemaChangeKind::TypeNameChanged { .. }
+            | SchemaChangeKind::FieldNamesChanged { .. }
+            | SchemaChangeKind::EnumVariantNameChanged { .. } => SchemaCompatibility::Compatible,
+            SchemaChangeKind::Added
+            | SchemaChangeKind::TypeKindGeneralized { .. }
+            | SchemaChangeKind::EnumVariantAdded { .. }
+            | SchemaChangeKind::ValidationLoosened => SchemaCompatibility::Additive,
+            SchemaChangeKind::Removed
+            | SchemaChangeKind::TypeNotFound
+            | SchemaChangeKind::TypeKindChanged { .. }
+            | SchemaChangeKind::FieldCountChanged { .. }
+            | SchemaChangeKind::EnumVariantRemoved { .. }
+            | SchemaChangeKind::ValidationTightened
+            | SchemaChangeKind::CustomValidationChanged => SchemaCompatibility::Breaking,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChange {
+    /// The location of the change, eg `MyStruct.field::Variant.0[]`
+    pub path: String,
+    pub kind: SchemaChangeKind,
+}
+
+impl SchemaChange {
+    pub fn compatibility(&self) -> SchemaCompatibility {
+        self.kind.compatibility()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchemaComparison {
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaComparison {
+    pub fn compatibility(&self) -> SchemaCompatibility {
+        self.changes
+            .iter()
+            .map(|change| change.compatibility())
+            .max()
+            .unwrap_or(SchemaCompatibility::Compatible)
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility() == SchemaCompatibility::Breaking
+    }
+}
+
+/// Compares the type `old_type_id` in `old_schema` with the type `new_type_id` in `new_schema`.
+pub fn compare_schemas<S: CustomSchema>(
+    old_schema: &Schema<S>,
+    old_type_id: LocalTypeId,
+    new_schema: &Schema<S>,
+    new_type_id: LocalTypeId,
+) -> SchemaComparison {
+    let mut comparer = SchemaComparer::new(old_schema, new_schema);
+    comparer.compare_root("", old_type_id, new_type_id);
+    comparer.finish()
+}
+
+/// Accumulates the changes between multiple pairs of root types, eg all the state fields of a
+/// blueprint, into a single [`SchemaComparison`].
+pub struct SchemaComparer<'s, S: CustomSchema> {
+    old_schema: &'s Schema<S>,
+    new_schema: &'s Schema<S>,
+    /// The pairs of types currently being compared, to avoid walking recursive types forever.
+    stack: Vec<(LocalTypeId, LocalTypeId)>,
+    changes: Vec<SchemaChange>,
+}
+
+impl<'s, S: CustomSchema> SchemaComparer<'s, S> {
+    pub fn new(old_schema: &'s Schema<S>, new_schema: &'s Schema<S>) -> Self {
+        Self {
+            old_schema,
+            new_schema,
+            stack: vec![],
+            changes: vec![],
+        }
+    }
+
+    pub fn compare_root(&mut self, path: &str, old_type_id: LocalTypeId, new_type_id: LocalTypeId) {
+        self.compare_type(path.to_string(), old_type_id, new_type_id);
+    }
+
+    /// Records a change which can't be detected from the schemas themselves, eg a removed root.
+    pub fn record(&mut self, path: &str, kind: SchemaChangeKind) {
+        self.push(path.to_string(), kind);
+    }
+
+    pub fn finish(self) -> SchemaComparison {
+        SchemaComparison {
+            changes: self.changes,
+        }
+    }
+
+    fn push(&mut self, path: String, kind: SchemaChangeKind) {
+        self.changes.push(SchemaChange { path, kind });
+    }
+
+    fn compare_type(&mut self, path: String, old_type_id: LocalTypeId, new_type_id: LocalTypeId) {
+        if self.stack.contains(&(old_type_id, new_type_id)) {
+            return;
+        }
+        self.stack.push((old_type_id, new_type_id));
+        self.compare_type_kinds(path, old_type_id, new_type_id);
+        self.stack.pop();
+    }
+
+    fn compare_type_kinds(
+        &mut self,
+        path: String,
+        old_type_id: LocalTypeId,
+        new_type_id: LocalTypeId,
+    ) {
+        let (old_schema, new_schema) = (self.old_schema, self.new_schema);
+        let (Some(old_kind), Some(new_kind)) = (
+            old_schema.resolve_type_kind(old_type_id),
+            new_schema.resolve_type_kind(new_type_id),
+        ) else {
+            self.push(path, SchemaChangeKind::TypeNotFound);
+            return;
+        };
+
+        let old_name = old_schema.resolve_type_name_from_metadata(old_type_id);
+        let new_name = new_schema.resolve_type_name_from_metadata(new_type_id);
+        if old_name != new_name {
+            self.push(
+                path.clone(),
+                SchemaChangeKind::TypeNameChanged {
+                    old: old_name.map(|name| name.to_string()),
+                    new: new_name.map(|name| name.to_string()),
+                },
+            );
+        }
+
+        let kinds_match = match (old_kind, new_kind) {
+            (TypeKind::Any, TypeKind::Any) => true,
+            (old_kind, TypeKind::Any) => {
+                self.push(
+                    path,
+                    SchemaChangeKind::TypeKindGeneralized {
+                        old: type_kind_name(old_kind),
+                    },
+                );
+                return;
+            }
+            (
+                TypeKind::Array {
+                    element_type: old_element_type,
+                },
+                TypeKind::Array {
+                    element_type: new_element_type,
+                },
+            ) => {
+                self.compare_type(format!("{}[]", path), *old_element_type, *new_element_type);
+                true
+            }
+            (
+                TypeKind::Tuple {
+                    field_types: old_field_types,
+                },
+                TypeKind::Tuple {
+                    field_types: new_field_types,
+                },
+            ) => {
+                let old_field_names = old_schema
+                    .resolve_type_metadata(old_type_id)
+                    .and_then(|metadata| metadata.get_field_names());
+                let new_field_names = new_schema
+                    .resolve_type_metadata(new_type_id)
+                    .and_then(|metadata| metadata.get_field_names());
+                self.compare_fields(
+                    &path,
+                    (old_field_types, old_field_names),
+                    (new_field_types, new_field_names),
+                );
+                true
+            }
+            (
+                TypeKind::Enum {
+                    variants: old_variants,
+                },
+                TypeKind::Enum {
+                    variants: new_variants,
+                },
+            ) => {
+                let old_metadata = old_schema.resolve_type_metadata(old_type_id);
+                let new_metadata = new_schema.resolve_type_metadata(new_type_id);
+                for (discriminator, old_field_types) in old_variants {
+                    let old_variant = variant_metadata(old_metadata, *discriminator);
+                    let Some(new_field_types) = new_variants.get(discriminator) else {
+                        self.push(
+                            format!("{}{}", path, variant_path(*discriminator, old_variant)),
+                            SchemaChangeKind::EnumVariantRemoved {
+                                discriminator: *discriminator,
+                            },
+                        );
+                        continue;
+                    };
+                    let new_variant = variant_metadata(new_metadata, *discriminator);
+                    let variant_path =
+                        format!("{}{}", path, variant_path(*discriminator, new_variant));
+                    let old_variant_name = old_variant.and_then(|metadata| metadata.get_name());
+                    let new_variant_name = new_variant.and_then(|metadata| metadata.get_name());
+                    if old_variant_name != new_variant_name {
+                        self.push(
+                            variant_path.clone(),
+                            SchemaChangeKind::EnumVariantNameChanged {
+                                discriminator: *discriminator,
+                                old: old_variant_name.map(|name| name.to_string()),
+                                new: new_variant_name.map(|name| name.to_string()),
+                            },
+                        );
+                    }
+                    self.compare_fields(
+                        &variant_path,
+                        (
+                            old_field_types,
+                            old_variant.and_then(|metadata| metadata.get_field_names()),
+                        ),
+                        (
+                            new_field_types,
+                            new_variant.and_then(|metadata| metadata.get_field_names()),
+                        ),
+                    );
+                }
+                for discriminator in new_variants.keys() {
+                    if !old_variants.contains_key(discriminator) {
+                        self.push(
+                            format!(
+                                "{}{}",
+                                path,
+                                variant_path(
+                                    *discriminator,
+                                    variant_metadata(new_metadata, *discriminator)
+                                )
+                            ),
+                            SchemaChangeKind::EnumVariantAdded {
+                                discriminator: *discriminator,
+                            },
+                        );
+                    }
+                }
+                true
+            }
+            (
+                TypeKind::Map {
+                    key_type: old_key_type,
+                    value_type: old_value_type,
+                },
+                TypeKind::Map {
+                    key_type: new_key_type,
+                    value_type: new_value_type,
+                },
+            ) => {
+                self.compare_type(format!("{}{{key}}", path), *old_key_type, *new_key_type);
+                self.compare_type(
+                    format!("{}{{value}}", path),
+                    *old_value_type,
+                    *new_value_type,
+                );
+                true
+            }
+            (TypeKind::Custom(old_custom_kind), TypeKind::Custom(new_custom_kind)) => {
+                old_custom_kind == new_custom_kind
+            }
+            (old_kind, new_kind) => type_kind_name(old_kind) == type_kind_name(new_kind),
+        };
+
+        if !kinds_match {
+            self.push(
+                path,
+                SchemaChangeKind::TypeKindChanged {
+                    old: type_kind_name(old_kind),
+                    new: type_kind_name(new_kind),
+                },
+            );
+            return;
+        }
+
+        self.compare_validations(path, old_type_id, new_type_id);
+    }
+
+    fn compare_fields(
+        &mut self,
+        path: &str,
+        (old_field_types, old_field_names): (&[LocalTypeId], Option<&[Cow<'static, str>]>),
+        (new_field_types, new_field_names): (&[LocalTypeId], Option<&[Cow<'static, str>]>),
+    ) {
+        if old_field_types.len() != new_field_types.len() {
+            self.push(
+                path.to_string(),
+                SchemaChangeKind::FieldCountChanged {
+                    old: old_field_types.len(),
+                    new: new_field_types.len(),
+                },
+            );
+            return;
+        }
+        if old_field_names != new_field_names {
+            let to_strings =
+                |names: &[Cow<'static, str>]| names.iter().map(|name| name.to_string()).collect();
+            self.push(
+                path.to_string(),
+                SchemaChangeKind::FieldNamesChanged {
+                    old: old_field_names.map(to_strings),
+                    new: new_field_names.map(to_strings),
+                },
+            );
+        }
+        for (index, (old_field_type, new_field_type)) in
+            old_field_types.iter().zip(new_field_types).enumerate()
+        {
+            let field_path = match new_field_names.and_then(|names| names.get(index)) {
+                Some(name) => format!("{}.{}", path, name),
+                None => format!("{}.{}", path, index),
+            };
+            self.compare_type(field_path, *old_field_type, *new_field_type);
+        }
+    }
+
+    fn compare_validations(
+        &mut self,
+        path: String,
+        old_type_id: LocalTypeId,
+        new_type_id: LocalTypeId,
+    ) {
+        let (old_schema, new_schema) = (self.old_schema, self.new_schema);
+        let none = TypeValidation::None;
+        let old_validation = old_schema
+            .resolve_type_validation(old_type_id)
+            .unwrap_or(&none);
+        let new_validation = new_schema
+            .resolve_type_validation(new_type_id)
+            .unwrap_or(&none);
+
+        let change = match (old_validation, new_validation) {
+            (TypeValidation::Custom(old), TypeValidation::Custom(new)) => {
+                (old != new).then_some(SchemaChangeKind::CustomValidationChanged)
+            }
+            (TypeValidation::None, TypeValidation::Custom(_)) => {
+                Some(SchemaChangeKind::ValidationTightened)
+            }
+            (TypeValidation::Custom(_), TypeValidation::None) => {
+                Some(SchemaChangeKind::ValidationLoosened)
+            }
+            (old, new) => match (validation_bounds(old), validation_bounds(new)) {
+                (Some(old_bounds), Some(new_bounds)) => compare_bounds(old_bounds, new_bounds),
+                _ => Some(SchemaChangeKind::CustomValidationChanged),
+            },
+        };
+        if let Some(change) = change {
+            self.push(path, change);
+        }
+    }
+}
+
+fn type_kind_name<C: CustomTypeKind<LocalTypeId>>(type_kind: &TypeKind<C, LocalTypeId>) -> String {
+    match type_kind {
+        TypeKind::Any => "Any".to_string(),
+        TypeKind::Bool => "Bool".to_string(),
+        TypeKind::I8 => "I8".to_string(),
+        TypeKind::I16 => "I16".to_string(),
+        TypeKind::I32 => "I32".to_string(),
+        TypeKind::I64 => "I64".to_string(),
+        TypeKind::I128 => "I128".to_string(),
+        TypeKind::U8 => "U8".to_string(),
+        TypeKind::U16 => "U16".to_string(),
+        TypeKind::U32 => "U32".to_string(),
+        TypeKind::U64 => "U64".to_string(),
+        TypeKind::U128 => "U128".to_string(),
+        TypeKind::String => "String".to_string(),
+        TypeKind::Array { .. } => "Array".to_string(),
+        TypeKind::Tuple { .. } => "Tuple".to_string(),
+        TypeKind::Enum { .. } => "Enum".to_string(),
+        TypeKind::Map { .. } => "Map".to_string(),
+        TypeKind::Custom(custom_type_kind) => format!("{:?}", custom_type_kind),
+    }
+}
+
+fn variant_metadata(metadata: Option<&TypeMetadata>, discriminator: u8) -> Option<&TypeMetadata> {
+    match metadata.and_then(|metadata| metadata.child_names.as_ref()) {
+        Some(ChildNames::EnumVariants(variants)) => variants.get(&discriminator),
+        _ => None,
+    }
+}
+
+fn variant_path(discriminator: u8, metadata: Option<&TypeMetadata>) -> String {
+    match metadata.and_then(|metadata| metadata.get_name()) {
+        Some(name) => format!("::{}", name),
+        None => format!("::[{}]", discriminator),
+    }
+}
+
+/// A numeric or length bound. Both sides of a comparison always have the same variant, as the
+/// validations are only compared when the type kinds match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Bound {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+type Bounds = (Option<Bound>, Option<Bound>);
+
+fn validation_bounds<V: CustomTypeValidation>(validation: &TypeValidation<V>) -> Option<Bounds> {
+    fn signed<T: Into<i128> + Copy>(validation: &NumericValidation<T>) -> Bounds {
+        (
+            validation.min.map(|min| Bound::Signed(min.into())),
+            validation.max.map(|max| Bound::Signed(max.into())),
+        )
+    }
+    fn unsigned<T: Into<u128> + Copy>(validation: &NumericValidation<T>) -> Bounds {
+        (
+            validation.min.map(|min| Bound::Unsigned(min.into())),
+            validation.max.map(|max| Bound::Unsigned(max.into())),
+        )
+    }
+    let bounds = match validation {
+        TypeValidation::None => (None, None),
+        TypeValidation::I8(validation) => signed(validation),
+        TypeValidation::I16(validation) => signed(validation),
+        TypeValidation::I32(validation) => signed(validation),
+        TypeValidation::I64(validation) => signed(validation),
+        TypeValidation::I128(validation) => signed(validation),
+        TypeValidation::U8(validation) => unsigned(validation),
+        TypeValidation::U16(validation) => unsigned(validation),
+        TypeValidation::U32(validation) => unsigned(validation),
+        TypeValidation::U64(validation) => unsigned(validation),
+        TypeValidation::U128(validation) => unsigned(validation),
+        TypeValidation::String(validation)
+        | TypeValidation::Array(validation)
+        | TypeValidation::Map(validation) => (
+            validation.min.map(|min| Bound::Unsigned(min.into())),
+            validation.max.map(|max| Bound::Unsigned(max.into())),
+        ),
+        TypeValidation::Custom(_) => return None,
+    };
+    Some(bounds)
+}
+
+fn compare_bounds(
+    (old_min, old_max): Bounds,
+    (new_min, new_max): Bounds,
+) -> Option<SchemaChangeKind> {
+    if old_min == new_min && old_max == new_max {
+        return None;
+    }
+    let min_contained = match (old_min, new_min) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(old_min), Some(new_min)) => new_min <= old_min,
+    };
+    let max_contained = match (old_max, new_max) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(old_max), Some(new_max)) => new_max >= old_max,
+    };
+    if min_contained && max_contained {
+        Some(SchemaChangeKind::ValidationLoosened)
+    } else {
+        Some(SchemaChangeKind::ValidationTightened)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_well_known_types::*;
+
+    fn create_schema(type_data: Vec<TypeData<NoCustomTypeKind, LocalTypeId>>) -> BasicSchema {
+        let mut schema = BasicSchema::empty();
+        for type_data in type_data {
+            schema.type_kinds.push(type_data.kind);
+            schema.type_metadata.push(type_data.metadata);
+            schema.type_validations.push(type_data.validation);
+        }
+        schema
+    }
+
+    fn compare(old: &BasicSchema, new: &BasicSchema) -> SchemaComparison {
+        compare_schemas(
+            old,
+            LocalTypeId::SchemaLocalIndex(0),
+            new,
+            LocalTypeId::SchemaLocalIndex(0),
+        )
+    }
+
+    fn change(path: &str, kind: SchemaChangeKind) -> SchemaChange {
+        SchemaChange {
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    fn account(fields: Vec<(&'static str, LocalTypeId)>) -> BasicSchema {
+        create_schema(vec![TypeData::struct_with_named_fields("Account", fields)])
+    }
+
+    #[test]
+    pub fn identical_schemas_are_compatible() {
+        let schema = account(vec![("owner", STRING_TYPE.into()), ("age", U8_TYPE.into())]);
+        let comparison = compare(&schema, &schema);
+        assert_eq!(comparison.changes, vec![]);
+        assert_eq!(comparison.compatibility(), SchemaCompatibility::Compatible);
+    }
+
+    #[test]
+    pub fn renamed_field_is_compatible() {
+        let old = account(vec![("owner", STRING_TYPE.into())]);
+        let new = account(vec![("holder", STRING_TYPE.into())]);
+        let comparison = compare(&old, &new);
+        assert_eq!(
+            comparison.changes,
+            vec![change(
+                "",
+                SchemaChangeKind::FieldNamesChanged {
+                    old: Some(vec!["owner".to_string()]),
+                    new: Some(vec!["holder".to_string()]),
+                }
+            )]
+        );
+        assert_eq!(comparison.compatibility(), SchemaCompatibility::Compatible);
+    }
+
+    #[test]
+    pub fn changed_field_kind_is_breaking() {
+        let old = account(vec![("owner", STRING_TYPE.into()), ("age", U8_TYPE.into())]);
+        let new = account(vec![
+            ("owner", STRING_TYPE.into()),
+            ("age", U32_TYPE.into()),
+        ]);
+        let comparison = compare(&old, &new);
+        assert_eq!(
+            comparison.changes,
+            vec![change(
+                ".age",
+                SchemaChangeKind::TypeKindChanged {
+                    old: "U8".to_string(),
+                    new: "U32".to_string(),
+                }
+            )]
+        );
+        assert!(comparison.is_breaking());
+    }
+
+    #[test]
+    pub fn added_field_is_breaking() {
+        let old = account(vec![("owner", STRING_TYPE.into())]);
+        let new = account(vec![("owner", STRING_TYPE.into()), ("age", U8_TYPE.into())]);
+        let comparison = compare(&old, &new);
+        assert_eq!(
+            comparison.changes,
+            vec![change(
+                "",
+                SchemaChangeKind::FieldCountChanged { old: 1, new: 2 }
+            )]
+        );
+        assert!(comparison.is_breaking());
+    }
+
+    #[test]
+    pub fn field_generalized_to_any_is_additive() {
+        let old = account(vec![("owner", STRING_TYPE.into())]);
+        let new = account(vec![("owner", ANY_TYPE.into())]);
+        let comparison = compare(&old, &new);
+        assert_eq!(comparison.compatibility(), SchemaCompatibility::Additive);
+    }
+
+    #[test]
+    pub fn enum_variant_changes_are_classified() {
+        let status = |variants: IndexMap<u8, TypeData<NoCustomTypeKind, LocalTypeId>>| {
+            create_schema(vec![TypeData::enum_variants("Status", variants)])
+        };
+        let old = status(indexmap![
+            0 => TypeData::struct_with_unit_fields("Active"),
+            1 => TypeData::struct_with_unit_fields("Frozen"),
+        ]);
+        let added = status(indexmap![
+            0 => TypeData::struct_with_unit_fields("Active"),
+            1 => TypeData::struct_with_unit_fields("Frozen"),
+            2 => TypeData::struct_with_unit_fields("Closed"),
+        ]);
+        let removed = status(indexmap![
+            0 => TypeData::struct_with_unit_fields("Active"),
+        ]);
+
+        let comparison = compare(&old, &added);
+        assert_eq!(
+            comparison.changes,
+            vec![change(
+                "::Closed",
+                SchemaChangeKind::EnumVariantAdded { discriminator: 2 }
+            )]
+        );
+        assert_eq!(comparison.compatibility(), SchemaCompatibility::Additive);
+
+        let comparison = compare(&old, &removed);
+        assert_eq!(
+            comparison.changes,
+            vec![change(
+                "::Frozen",
+                SchemaChangeKind::EnumVariantRemoved { discriminator: 1 }
+            )]
+        );
+        assert!(comparison.is_breaking());
+    }
+
+    #[test]
+    pub fn validation_changes_are_classified() {
+        let names = |min: Option<u32>, max: Option<u32>| {
+            create_schema(vec![TypeData::unnamed(TypeKind::Array {
+                element_type: STRING_TYPE.into(),
+            })
+            .with_validation(TypeValidation::Array(LengthValidation { min, max }))])
+        };
+        let old = names(Some(1), Some(10));
+
+        let comparison = compare(&old, &names(None, Some(20)));
+        assert_eq!(
+            comparison.changes,
+            vec![change("", SchemaChangeKind::ValidationLoosened)]
+        );
+        assert_eq!(comparison.compatibility(), SchemaCompatibility::Additive);
+
+        let comparison = compare(&old, &names(Some(1), Some(5)));
+        assert_eq!(
+            comparison.changes,
+            vec![change("", SchemaChangeKind::ValidationTightened)]
+        );
+        assert!(comparison.is_breaking());
+    }
+
+    #[test]
+    pub fn recursive_types_terminate() {
+        let tree = |leaf: LocalTypeId| {
+            create_schema(vec![
+                TypeData::struct_with_named_fields(
+                    "Tree",
+                    vec![
+                        ("leaf", leaf),
+                        ("children", LocalTypeId::SchemaLocalIndex(1)),
+                    ],
+                ),
+                TypeData::unnamed(TypeKind::Array {
+                    element_type: LocalTypeId::SchemaLocalIndex(0),
+                }),
+            ])
+        };
+        let comparison = compare(&tree(U8_TYPE.into()), &tree(U32_TYPE.into()));
+        assert_eq!(
+            comparison.changes,
+            vec![change(
+                ".leaf",
+                SchemaChangeKind::TypeKindChanged {
+                    old: "U8".to_string(),
+                    new: "U32".to_string(),
+                }
+            )]
+        );
+    }
+}
@@ -549,3 +549,39 @@ fn value_kind_matches_type_kind<E: CustomExtension>(
         }
     }
 }
+
+/// Gives the value kind that values of the given type kind are encoded with, if it's determined by
+/// the type (ie it's not `Any`).
+pub fn value_kind_for_type_kind<E: CustomExtension>(
+    schema: &Schema<E::CustomSchema>,
+    type_kind: &SchemaTypeKind<E::CustomSchema>,
+) -> Option<ValueKind<E::CustomValueKind>> {
+    let value_kind = match type_kind {
+        TypeKind::Any => return None,
+        TypeKind::Bool => ValueKind::Bool,
+        TypeKind::I8 => ValueKind::I8,
+        TypeKind::I16 => ValueKind::I16,
+        TypeKind::I32 => ValueKind::I32,
+        TypeKind::I64 => ValueKind::I64,
+        TypeKind::I128 => ValueKind::I128,
+        TypeKind::U8 => ValueKind::U8,
+        TypeKind::U16 => ValueKind::U16,
+        TypeKind::U32 => ValueKind::U32,
+        TypeKind::U64 => ValueKind::U64,
+        TypeKind::U128 => ValueKind::U128,
+        TypeKind::String => ValueKind::String,
+        TypeKind::Array { .. } => ValueKind::Array,
+        TypeKind::Tuple { .. } => ValueKind::Tuple,
+        TypeKind::Enum { .. } => ValueKind::Enum,
+        TypeKind::Map { .. } => ValueKind::Map,
+        TypeKind::Custom(_) => {
+            return (CUSTOM_VALUE_KIND_START..=u8::MAX)
+                .filter_map(E::CustomValueKind::from_u8)
+                .find(|custom_value_kind| {
+                    E::custom_value_kind_matches_type_kind(schema, *custom_value_kind, type_kind)
+                })
+                .map(ValueKind::Custom)
+        }
+    };
+    Some(value_kind)
+}
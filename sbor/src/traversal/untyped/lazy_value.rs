@@ -0,0 +1,374 @@
+use super::*;
+use crate::decoder::BorrowingDecoder;
+use crate::rust::prelude::*;
+use crate::value_kind::*;
+use crate::*;
+
+/// A borrowed view over an encoded SBOR value, which decodes on demand.
+///
+/// Unlike decoding into a `Value`, navigating a `LazyValue` doesn't allocate: children are
+/// located by skipping over their siblings with the untyped traverser, and strings and byte
+/// arrays are returned as slices of the original payload.
+///
+/// The value is validated when it is created with `from_payload` or `from_value_body`, so
+/// navigation afterwards can't fail on a decode error.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LazyValue<'de, E: CustomExtension> {
+    value_kind: ValueKind<E::CustomValueKind>,
+    value_body: &'de [u8],
+    /// The number of containers above this value
+    depth: usize,
+    depth_limit: usize,
+}
+
+// Manually implemented, as the derives would require `E: Copy`
+impl<'de, E: CustomExtension> Clone for LazyValue<'de, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'de, E: CustomExtension> Copy for LazyValue<'de, E> {}
+
+impl<'de, E: CustomExtension> LazyValue<'de, E> {
+    /// The bytes should be a full payload, including the prefix byte (eg 0x5b for basic SBOR).
+    ///
+    /// The whole value is validated up front - by a single traversal, which doesn't allocate.
+    pub fn from_payload(payload: &'de [u8], depth_limit: usize) -> Result<Self, DecodeError> {
+        let mut decoder = VecDecoder::<E::CustomValueKind>::new(payload, depth_limit);
+        decoder.read_and_check_payload_prefix(E::PAYLOAD_PREFIX)?;
+        let value_kind = decoder.read_value_kind()?;
+        Self::from_value_body(value_kind, &payload[2..], depth_limit)
+    }
+
+    /// The bytes should include the value body, not the value kind or the prefix byte.
+    pub fn from_value_body(
+        value_kind: ValueKind<E::CustomValueKind>,
+        value_body: &'de [u8],
+        depth_limit: usize,
+    ) -> Result<Self, DecodeError> {
+        let length =
+            calculate_value_tree_body_byte_length::<E>(value_body, value_kind, 0, depth_limit)?;
+        if length != value_body.len() {
+            return Err(DecodeError::ExtraTrailingBytes(value_body.len() - length));
+        }
+        Ok(Self {
+            value_kind,
+            value_body,
+            depth: 0,
+            depth_limit,
+        })
+    }
+
+    /// The bytes should be a full payload, including the prefix byte (eg 0x5b for basic SBOR).
+    ///
+    /// It is the caller's responsibility to ensure that a valid SBOR payload for extension E is
+    /// passed, eg because it has already been validated against a schema.
+    ///
+    /// This constructor does not check the prefix byte, and panics if the root value kind is invalid.
+    pub fn from_valid_payload(payload: &'de [u8], depth_limit: usize) -> Self {
+        Self {
+            value_kind: ValueKind::<E::CustomValueKind>::from_u8(payload[1]).unwrap(),
+            value_body: &payload[2..],
+            depth: 0,
+            depth_limit,
+        }
+    }
+
+    pub fn value_kind(&self) -> ValueKind<E::CustomValueKind> {
+        self.value_kind
+    }
+
+    pub fn value_body_bytes(&self) -> &'de [u8] {
+        self.value_body
+    }
+
+    pub fn as_raw_value(&self) -> RawValue<'de, E> {
+        RawValue::new_from_valid_value_body_slice(self.value_kind, self.value_body)
+    }
+
+    /// Returns the value if it's a terminal value, ie not a container.
+    pub fn as_terminal_value(&self) -> Option<TerminalValueRef<'de, E::CustomTraversal>> {
+        let mut traverser = VecTraverser::<E::CustomTraversal>::new(
+            self.value_body,
+            self.depth_limit.saturating_sub(self.depth),
+            ExpectedStart::ValueBody(self.value_kind),
+            false,
+        );
+        match traverser.next_event().event {
+            TraversalEvent::TerminalValue(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, borrowed from the payload, if this is a `String`.
+    pub fn as_str(&self) -> Option<&'de str> {
+        match self.as_terminal_value()? {
+            TerminalValueRef::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes, borrowed from the payload, if this is an `Array` of `U8`.
+    pub fn as_bytes(&self) -> Option<&'de [u8]> {
+        if self.value_kind != ValueKind::Array {
+            return None;
+        }
+        let mut decoder = VecDecoder::<E::CustomValueKind>::new(self.value_body, self.depth_limit);
+        if decoder.read_value_kind().ok()? != ValueKind::U8 {
+            return None;
+        }
+        let length = decoder.read_size().ok()?;
+        decoder.read_slice_from_payload(length).ok()
+    }
+
+    /// Returns the variant discriminator, if this is an `Enum`.
+    pub fn enum_variant_id(&self) -> Option<u8> {
+        match self.value_kind {
+            ValueKind::Enum => self.value_body.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// Returns the children of a container value, in encoded order.
+    /// Map entries are flattened into their key, followed by their value.
+    ///
+    /// Terminal values have no children.
+    pub fn children(&self) -> LazyValueChildren<'de, E> {
+        let mut decoder = VecDecoder::new(self.value_body, self.depth_limit);
+        let header = read_container_header::<E>(&mut decoder, self.value_kind)
+            .ok()
+            .flatten();
+        LazyValueChildren {
+            decoder,
+            child_count: header.map(|header| header.get_child_count()).unwrap_or(0),
+            header,
+            next_index: 0,
+            child_depth: self.depth + 1,
+            depth_limit: self.depth_limit,
+        }
+    }
+
+    /// Returns the child at the given index, as per `children`.
+    pub fn child(&self, index: usize) -> Option<Self> {
+        self.children().nth(index)
+    }
+
+    pub fn get(&self, path: &SborPath) -> Option<Self> {
+        path.get_from_lazy_value(*self)
+    }
+
+    /// Decodes this value (only) into the given type.
+    pub fn decode<T: Decode<E::CustomValueKind, VecDecoder<'de, E::CustomValueKind>>>(
+        &self,
+    ) -> Result<T, DecodeError> {
+        let mut decoder =
+            VecDecoder::new(self.value_body, self.depth_limit.saturating_sub(self.depth));
+        let value = T::decode_body_with_value_kind(&mut decoder, self.value_kind)?;
+        decoder.check_end()?;
+        Ok(value)
+    }
+}
+
+/// Like `RawValue`, a `LazyValue` is encoded by copying its bytes verbatim.
+impl<'de, Ext: CustomExtension, E: Encoder<Ext::CustomValueKind>> Encode<Ext::CustomValueKind, E>
+    for LazyValue<'de, Ext>
+{
+    fn encode_value_kind(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        encoder.write_value_kind(self.value_kind)
+    }
+
+    fn encode_body(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        encoder.write_slice(self.value_body)
+    }
+}
+
+/// An iterator over the children of a `LazyValue`.
+pub struct LazyValueChildren<'de, E: CustomExtension> {
+    decoder: VecDecoder<'de, E::CustomValueKind>,
+    header: Option<ContainerHeader<E::CustomTraversal>>,
+    next_index: usize,
+    child_count: usize,
+    child_depth: usize,
+    depth_limit: usize,
+}
+
+impl<'de, E: CustomExtension> Iterator for LazyValueChildren<'de, E> {
+    type Item = LazyValue<'de, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index >= self.child_count {
+            return None;
+        }
+        let value_kind = match self.header?.get_implicit_child_value_kind(self.next_index) {
+            Some(value_kind) => value_kind,
+            None => self.decoder.read_value_kind().ok()?,
+        };
+        let length = calculate_value_tree_body_byte_length::<E>(
+            self.decoder.peek_remaining(),
+            value_kind,
+            self.child_depth,
+            self.depth_limit,
+        )
+        .ok()?;
+        let value_body = self.decoder.read_slice_from_payload(length).ok()?;
+        self.next_index += 1;
+        Some(LazyValue {
+            value_kind,
+            value_body,
+            depth: self.child_depth,
+            depth_limit: self.depth_limit,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.child_count - self.next_index;
+        (remaining, Some(remaining))
+    }
+}
+
+fn read_container_header<E: CustomExtension>(
+    decoder: &mut VecDecoder<E::CustomValueKind>,
+    value_kind: ValueKind<E::CustomValueKind>,
+) -> Result<Option<ContainerHeader<E::CustomTraversal>>, DecodeError> {
+    let header = match value_kind {
+        ValueKind::Tuple => ContainerHeader::Tuple(TupleHeader {
+            length: decoder.read_size()?,
+        }),
+        ValueKind::Enum => ContainerHeader::EnumVariant(EnumVariantHeader {
+            variant: decoder.read_discriminator()?,
+            length: decoder.read_size()?,
+        }),
+        ValueKind::Array => ContainerHeader::Array(ArrayHeader {
+            element_value_kind: decoder.read_value_kind()?,
+            length: decoder.read_size()?,
+        }),
+        ValueKind::Map => ContainerHeader::Map(MapHeader {
+            key_value_kind: decoder.read_value_kind()?,
+            value_value_kind: decoder.read_value_kind()?,
+            length: decoder.read_size()?,
+        }),
+        _ => return Ok(None),
+    };
+    Ok(Some(header))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Sbor, Debug, PartialEq, Eq)]
+    struct Blob {
+        name: String,
+        data: Vec<u8>,
+        tags: IndexMap<u16, String>,
+        kind: BlobKind,
+    }
+
+    #[derive(Sbor, Debug, PartialEq, Eq)]
+    enum BlobKind {
+        Inline,
+        Chunked { chunks: Vec<u32> },
+    }
+
+    fn example_payload() -> Vec<u8> {
+        let mut tags = index_map_new();
+        tags.insert(1u16, "large".to_string());
+        tags.insert(2u16, "binary".to_string());
+        basic_encode(&Blob {
+            name: "example".to_string(),
+            data: vec![7u8; 1024],
+            tags,
+            kind: BlobKind::Chunked {
+                chunks: vec![512, 512],
+            },
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn can_navigate_by_sbor_path() {
+        let payload = example_payload();
+        let value = BasicLazyValue::from_payload(&payload, BASIC_SBOR_V1_MAX_DEPTH).unwrap();
+
+        assert_eq!(value.value_kind(), ValueKind::Tuple);
+        assert_eq!(value.children().count(), 4);
+        let tag_value = value.get(&SborPath::new(vec![2, 1, 1])).unwrap();
+        assert_eq!(tag_value.as_str(), Some("binary"));
+        let tag_key = value.get(&SborPath::new(vec![2, 0, 0])).unwrap();
+        assert_eq!(tag_key.as_terminal_value(), Some(TerminalValueRef::U16(1)));
+        let kind = value.child(3).unwrap();
+        assert_eq!(kind.enum_variant_id(), Some(1));
+        assert_eq!(
+            value
+                .get(&SborPath::new(vec![3, 0, 1]))
+                .unwrap()
+                .decode::<u32>(),
+            Ok(512)
+        );
+
+        assert_eq!(value.get(&SborPath::new(vec![2, 0])), None);
+        assert_eq!(value.get(&SborPath::new(vec![2, 0, 2])), None);
+        assert_eq!(value.get(&SborPath::new(vec![4])), None);
+        assert_eq!(value.get(&SborPath::new(vec![0, 0])), None);
+    }
+
+    #[test]
+    fn strings_and_bytes_are_borrowed_from_the_payload() {
+        let payload = example_payload();
+        let value = BasicLazyValue::from_payload(&payload, BASIC_SBOR_V1_MAX_DEPTH).unwrap();
+
+        let name = value.child(0).unwrap().as_str().unwrap();
+        let data = value.child(1).unwrap().as_bytes().unwrap();
+        assert_eq!(name, "example");
+        assert_eq!(data, &[7u8; 1024][..]);
+        let payload_range = payload.as_ptr_range();
+        assert!(payload_range.contains(&name.as_ptr()));
+        assert!(payload_range.contains(&data.as_ptr()));
+
+        assert_eq!(value.child(0).unwrap().as_bytes(), None);
+        assert_eq!(value.child(1).unwrap().as_str(), None);
+    }
+
+    #[test]
+    fn can_decode_and_re_encode_sub_values() {
+        let payload = example_payload();
+        let value = BasicLazyValue::from_payload(&payload, BASIC_SBOR_V1_MAX_DEPTH).unwrap();
+
+        let kind = value.child(3).unwrap();
+        assert_eq!(
+            kind.decode::<BlobKind>(),
+            Ok(BlobKind::Chunked {
+                chunks: vec![512, 512]
+            })
+        );
+        assert_eq!(
+            basic_encode(&kind).unwrap(),
+            basic_encode(&BlobKind::Chunked {
+                chunks: vec![512, 512]
+            })
+            .unwrap()
+        );
+        assert_eq!(basic_encode(&value).unwrap(), payload);
+        assert_eq!(value.decode::<Blob>(), basic_decode::<Blob>(&payload));
+    }
+
+    #[test]
+    fn invalid_payloads_are_rejected_up_front() {
+        let mut payload = example_payload();
+        payload.push(0);
+        assert_eq!(
+            BasicLazyValue::from_payload(&payload, BASIC_SBOR_V1_MAX_DEPTH),
+            Err(DecodeError::ExtraTrailingBytes(1))
+        );
+        payload.truncate(payload.len() - 2);
+        assert!(matches!(
+            BasicLazyValue::from_payload(&payload, BASIC_SBOR_V1_MAX_DEPTH),
+            Err(DecodeError::BufferUnderflow { .. })
+        ));
+        assert!(matches!(
+            BasicLazyValue::from_payload(&example_payload(), 1),
+            Err(DecodeError::MaxDepthExceeded(_))
+        ));
+    }
+}
@@ -1,5 +1,7 @@
 mod events;
+mod lazy_value;
 mod traverser;
 
 pub use events::*;
+pub use lazy_value::*;
 pub use traverser::*;
@@ -7,6 +7,7 @@ compile_error!("Feature `std` and `alloc` can't be enabled at the same time.");
 
 use bitflags::bitflags;
 use radix_engine_common::prelude::*;
+use sbor::representations::{export_json_schema, export_typescript, SchemaExportError};
 
 #[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor, ManifestSbor)]
 pub struct KeyValueStoreGenericSubstitutions {
@@ -103,6 +104,54 @@ impl BlueprintSchemaInit {
             .chain(self.hooks.hooks.values().cloned())
             .collect()
     }
+
+    /// Returns the statically typed parts of the blueprint interface, named for export.
+    /// Generic types are skipped, as they're only known once instantiated.
+    pub fn exported_types(&self) -> Vec<(String, LocalTypeId)> {
+        let mut types = vec![];
+        let mut push = |name: String, type_ref: &TypeRef<LocalTypeId>| {
+            if let TypeRef::Static(type_id) = type_ref {
+                types.push((name, *type_id));
+            }
+        };
+        for (function_name, function) in &self.functions.functions {
+            push(format!("function_{}_input", function_name), &function.input);
+            push(
+                format!("function_{}_output", function_name),
+                &function.output,
+            );
+        }
+        for (event_name, event) in &self.events.event_schema {
+            push(format!("event_{}", event_name), event);
+        }
+        for (index, field) in self.state.fields.iter().enumerate() {
+            push(format!("state_field_{}", index), &field.field);
+        }
+        for (index, collection) in self.state.collections.iter().enumerate() {
+            let (BlueprintCollectionSchema::KeyValueStore(key_value_schema)
+            | BlueprintCollectionSchema::Index(key_value_schema)
+            | BlueprintCollectionSchema::SortedIndex(key_value_schema)) = collection;
+            push(
+                format!("state_collection_{}_key", index),
+                &key_value_schema.key,
+            );
+            push(
+                format!("state_collection_{}_value", index),
+                &key_value_schema.value,
+            );
+        }
+        types
+    }
+
+    /// Exports a JSON Schema document for the programmatic JSON of the blueprint interface.
+    pub fn export_json_schema(&self) -> Result<String, SchemaExportError> {
+        export_json_schema::<ScryptoCustomExtension>(self.schema.v1(), &self.exported_types())
+    }
+
+    /// Exports TypeScript definitions for the programmatic JSON of the blueprint interface.
+    pub fn export_typescript(&self) -> Result<String, SchemaExportError> {
+        export_typescript::<ScryptoCustomExtension>(self.schema.v1(), &self.exported_types())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ScryptoSbor, ManifestSbor)]
@@ -189,6 +189,12 @@ implement_client_api! {
             node_id: &NodeId,
             key: &Vec<u8>,
         ) -> Result<Vec<u8>, RuntimeError>,
+        key_value_store_scan_entries: (
+            &mut self,
+            node_id: &NodeId,
+            cursor: Option<Vec<u8>>,
+            limit: u32,
+        ) -> Result<KeyValueStoreEntriesPage, RuntimeError>,
     },
     ClientObjectApi: {
         new_object: (
@@ -2,7 +2,7 @@ use crate::prelude::*;
 
 /// Defines the set of Nodes that all test [`CallFrame`]s have visibility to when they're first
 /// created. This contains all of the well-known addresses of nodes.
-pub(super) const GLOBAL_VISIBLE_NODES: [NodeId; 28] = [
+pub(super) const GLOBAL_VISIBLE_NODES: [NodeId; 29] = [
     XRD.into_node_id(),
     SECP256K1_SIGNATURE_VIRTUAL_BADGE.into_node_id(),
     ED25519_SIGNATURE_VIRTUAL_BADGE.into_node_id(),
@@ -31,4 +31,5 @@ pub(super) const GLOBAL_VISIBLE_NODES: [NodeId; 28] = [
     GENESIS_HELPER.into_node_id(),
     FAUCET.into_node_id(),
     TRANSACTION_TRACKER.into_node_id(),
+    SCHEDULER.into_node_id(),
 ];
@@ -1,3 +1,4 @@
+use crate::TestRunnerWasmEngine;
 use radix_engine::errors::{RuntimeError, SystemModuleError};
 use radix_engine::kernel::call_frame::{CallFrameMessage, NodeVisibility};
 use radix_engine::kernel::kernel_api::{
@@ -16,13 +17,12 @@ use radix_engine::system::system_modules::execution_trace::{BucketSnapshot, Proo
 use radix_engine::track::NodeSubstates;
 use radix_engine::transaction::WrappedSystem;
 use radix_engine::types::*;
-use radix_engine::vm::wasm::DefaultWasmEngine;
 use radix_engine::vm::Vm;
 use radix_engine_store_interface::db_key_mapper::SubstateKeyContent;
-use transaction::prelude::PreAllocatedAddress;
+use transaction::prelude::{PreAllocatedAddress, SubintentParams};
 
 pub type InjectSystemCostingError<'a, E> =
-    InjectCostingError<SystemConfig<Vm<'a, DefaultWasmEngine, E>>>;
+    InjectCostingError<SystemConfig<Vm<'a, TestRunnerWasmEngine, E>>>;
 
 pub struct InjectCostingError<K: KernelCallbackObject> {
     fail_after: Rc<RefCell<u64>>,
@@ -112,6 +112,7 @@ impl<'a, K: KernelCallbackObject + 'a> KernelCallbackObject for InjectCostingErr
         pre_allocated_addresses: &Vec<PreAllocatedAddress>,
         references: &IndexSet<Reference>,
         blobs: &IndexMap<Hash, Vec<u8>>,
+        subintent_params: &SubintentParams,
     ) -> Result<Vec<u8>, RuntimeError>
     where
         Y: KernelApi<Self>,
@@ -123,6 +124,7 @@ impl<'a, K: KernelCallbackObject + 'a> KernelCallbackObject for InjectCostingErr
             pre_allocated_addresses,
             references,
             blobs,
+            subintent_params,
         )
     }
 
@@ -492,6 +494,17 @@ impl<'a, M: KernelCallbackObject, Y: KernelApi<InjectCostingError<M>>>
             .kernel_scan_keys::<K>(node_id, partition_num, count)
     }
 
+    fn kernel_scan_substates_after<K: SubstateKeyContent + 'static>(
+        &mut self,
+        node_id: &NodeId,
+        partition_num: PartitionNumber,
+        after: Option<&SubstateKey>,
+        count: u32,
+    ) -> Result<Vec<(SubstateKey, IndexedScryptoValue)>, RuntimeError> {
+        self.api
+            .kernel_scan_substates_after::<K>(node_id, partition_num, after, count)
+    }
+
     fn kernel_drain_substates<K: SubstateKeyContent + 'static>(
         &mut self,
         node_id: &NodeId,
@@ -13,15 +13,20 @@ use radix_engine::system::system_callback::SystemConfig;
 use radix_engine::system::system_db_reader::{
     ObjectCollectionKey, SystemDatabaseReader, SystemDatabaseWriter,
 };
+use radix_engine::system::system_modules::debugger::DebuggerSystem;
 use radix_engine::system::system_substates::FieldSubstate;
 use radix_engine::system::type_info::TypeInfoSubstate;
 use radix_engine::transaction::{
-    execute_preview, execute_transaction_with_system, BalanceChange, CommitResult,
-    CostingParameters, ExecutionConfig, PreviewError, TransactionReceipt, TransactionResult,
+    execute_and_commit_transactions_in_parallel, execute_preview,
+    execute_preview_with_state_overrides, execute_transaction_with_system, BalanceChange,
+    CommitResult, CostingParameters, ExecutionConfig, PreviewError, ScheduledCallExecutor,
+    StateOverride, StateOverridePreviewReceipt, TransactionReceipt, TransactionResult,
     WrappedSystem,
 };
 use radix_engine::types::*;
 use radix_engine::utils::*;
+#[cfg(feature = "coverage")]
+use radix_engine::vm::wasm::{CoverageWasmEngine, WasmCoverageCollector};
 use radix_engine::vm::wasm::{DefaultWasmEngine, WasmValidatorConfigV1};
 use radix_engine::vm::{NativeVm, NativeVmExtension, NoExtension, ScryptoVm, Vm};
 use radix_engine_interface::api::node_modules::auth::ToRoleEntry;
@@ -45,7 +50,9 @@ use radix_engine_interface::math::Decimal;
 use radix_engine_interface::network::NetworkDefinition;
 use radix_engine_interface::time::Instant;
 use radix_engine_interface::{dec, freeze_roles, rule};
-use radix_engine_queries::query::{ResourceAccounter, StateTreeTraverser, VaultFinder};
+use radix_engine_queries::query::{
+    HoldingsQuery, HoldingsQueryError, ResourceAccounter, StateTreeTraverser, VaultFinder,
+};
 use radix_engine_queries::typed_native_events::to_typed_native_event;
 use radix_engine_queries::typed_substate_layout::*;
 use radix_engine_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
@@ -55,6 +62,11 @@ use radix_engine_store_interface::interface::{
 };
 use radix_engine_stores::hash_tree_support::HashTreeUpdatingDatabase;
 use radix_engine_stores::memory_db::InMemorySubstateDatabase;
+#[cfg(feature = "rocksdb")]
+use radix_engine_stores::overlay_db::OverlaySubstateDatabase;
+#[cfg(feature = "rocksdb")]
+use radix_engine_stores::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+use sbor::representations::{parse_rustlike_payload, RustLikeParseParameters};
 use scrypto::prelude::*;
 use transaction::prelude::*;
 use transaction::signing::secp256k1::Secp256k1PrivateKey;
@@ -62,6 +74,39 @@ use transaction::validation::{
     NotarizedTransactionValidator, TransactionValidator, ValidationConfig,
 };
 
+/// The environment variable which enables the coverage reporting of the Scrypto packages
+/// compiled by the test runner, set to the directory to write the `lcov` reports to.
+#[cfg(feature = "coverage")]
+pub const SCRYPTO_COVERAGE_DIR_ENV_VAR: &str = "SCRYPTO_COVERAGE_DIR";
+
+#[cfg(feature = "coverage")]
+lazy_static::lazy_static! {
+    static ref COVERAGE_COLLECTOR: Option<WasmCoverageCollector> =
+        std::env::var_os(SCRYPTO_COVERAGE_DIR_ENV_VAR).map(|_| WasmCoverageCollector::new());
+}
+
+#[cfg(feature = "coverage")]
+static COVERAGE_REPORT_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// The WASM engine used by the test runner.
+#[cfg(feature = "coverage")]
+pub type TestRunnerWasmEngine = CoverageWasmEngine<DefaultWasmEngine>;
+#[cfg(not(feature = "coverage"))]
+pub type TestRunnerWasmEngine = DefaultWasmEngine;
+
+fn new_scrypto_vm() -> ScryptoVm<TestRunnerWasmEngine> {
+    #[cfg(feature = "coverage")]
+    let wasm_engine =
+        CoverageWasmEngine::new(DefaultWasmEngine::default(), COVERAGE_COLLECTOR.clone());
+    #[cfg(not(feature = "coverage"))]
+    let wasm_engine = DefaultWasmEngine::default();
+
+    ScryptoVm {
+        wasm_engine,
+        wasm_validator_config: WasmValidatorConfigV1::new(),
+    }
+}
+
 pub struct Compile;
 
 impl Compile {
@@ -79,6 +124,17 @@ impl Compile {
         package_dir: P,
         env_vars: sbor::rust::collections::BTreeMap<String, String>,
     ) -> (Vec<u8>, PackageDefinition) {
+        // Debug info is required to map the covered code back to the source lines
+        #[cfg(feature = "coverage")]
+        let env_vars = {
+            let mut env_vars = env_vars;
+            if COVERAGE_COLLECTOR.is_some() {
+                env_vars.insert("CARGO_PROFILE_RELEASE_DEBUG".to_owned(), "1".to_owned());
+                env_vars.insert("CARGO_PROFILE_RELEASE_STRIP".to_owned(), "false".to_owned());
+            }
+            env_vars
+        };
+
         // Build
         let status = Command::new("cargo")
             .envs(env_vars)
@@ -135,6 +191,16 @@ impl Compile {
                 &path, err
             )
         });
+        #[cfg(feature = "coverage")]
+        let code = match COVERAGE_COLLECTOR.as_ref() {
+            Some(collector) => collector.register_code(&code).unwrap_or_else(|err| {
+                panic!(
+                    "Failed to instrument WASM from path {:?} for coverage - {:?}",
+                    &path, err
+                )
+            }),
+            None => code,
+        };
         let definition = extract_definition(&code).unwrap();
 
         (code, definition)
@@ -299,6 +365,11 @@ impl<T: SubstateDatabase + CommittableSubstateDatabase + ListableSubstateDatabas
 
 pub type DefaultTestRunner = TestRunner<NoExtension, InMemorySubstateDatabase>;
 
+/// The system to pass to [`TestRunner::execute_manifest_with_system`] to run a transaction
+/// with breakpoints, given a `DebuggerConfig`.
+pub type TestRunnerDebuggerSystem<'a, E> =
+    DebuggerSystem<SystemConfig<Vm<'a, TestRunnerWasmEngine, E>>>;
+
 pub struct TestRunnerBuilder<E, D> {
     custom_genesis: Option<CustomGenesis>,
     custom_extension: E,
@@ -319,6 +390,31 @@ impl TestRunnerBuilder<NoExtension, InMemorySubstateDatabase> {
     }
 }
 
+/// A ledger database (e.g. one synced by `replay sync`) opened in read-only mode, with all the
+/// writes kept in an in-memory overlay, so that the ledger database itself is never changed.
+#[cfg(feature = "rocksdb")]
+pub type ForkedLedgerDatabase = OverlaySubstateDatabase<RocksDBWithMerkleTreeSubstateStore>;
+
+#[cfg(feature = "rocksdb")]
+impl TestRunnerBuilder<NoExtension, ForkedLedgerDatabase> {
+    /// Forks the state of a ledger database, such as one synced with real network transactions by
+    /// `replay sync`. The ledger database is opened in read-only mode, and all the writes of the
+    /// test runner are kept in memory.
+    ///
+    /// Note that the faucet of a real network may hold no XRD to lock fees from.
+    pub fn from_database_path(path: PathBuf) -> Self {
+        TestRunnerBuilder {
+            custom_genesis: None,
+            custom_extension: NoExtension,
+            custom_database: OverlaySubstateDatabase::new(
+                RocksDBWithMerkleTreeSubstateStore::read_only(path),
+            ),
+            trace: true,
+            skip_receipt_check: false,
+        }
+    }
+}
+
 impl<E: NativeVmExtension, D: TestDatabase> TestRunnerBuilder<E, D> {
     pub fn without_trace(mut self) -> Self {
         self.trace = false;
@@ -380,7 +476,7 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunnerBuilder<E, D> {
         //----------------------------------------------------------------
 
         TestRunner {
-            scrypto_vm: ScryptoVm::default(),
+            scrypto_vm: new_scrypto_vm(),
             native_vm: NativeVm::new_with_extension(self.custom_extension),
             database: snapshot.database,
             next_private_key: snapshot.next_private_key,
@@ -402,10 +498,7 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunnerBuilder<E, D> {
         let trace = false;
         //----------------------------------------------------------------
 
-        let scrypto_vm = ScryptoVm {
-            wasm_engine: DefaultWasmEngine::default(),
-            wasm_validator_config: WasmValidatorConfigV1::new(),
-        };
+        let scrypto_vm = new_scrypto_vm();
         let native_vm = NativeVm::new_with_extension(self.custom_extension);
         let vm = Vm::new(&scrypto_vm, native_vm.clone());
         let mut substate_db = self.custom_database;
@@ -415,22 +508,57 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunnerBuilder<E, D> {
             vm,
             bootstrap_trace,
         );
-        let GenesisReceipts {
+        let has_custom_genesis = self.custom_genesis.is_some();
+        let genesis_receipts = match self.custom_genesis {
+            Some(custom_genesis) => bootstrapper.bootstrap_with_genesis_data(
+                custom_genesis.genesis_data_chunks,
+                custom_genesis.genesis_epoch,
+                custom_genesis.initial_config,
+                custom_genesis.initial_time_ms,
+                custom_genesis.initial_current_leader,
+                custom_genesis.faucet_supply,
+            ),
+            None => bootstrapper.bootstrap_test_default(),
+        };
+
+        // Note that 0 is not a valid private key
+        let next_private_key = 100;
+
+        // Starting from non-zero considering that bootstrap might have used a few.
+        let next_transaction_nonce = 100;
+
+        let Some(GenesisReceipts {
             system_bootstrap_receipt,
             data_ingestion_receipts,
             wrap_up_receipt,
-        } = match self.custom_genesis {
-            Some(custom_genesis) => bootstrapper
-                .bootstrap_with_genesis_data(
-                    custom_genesis.genesis_data_chunks,
-                    custom_genesis.genesis_epoch,
-                    custom_genesis.initial_config,
-                    custom_genesis.initial_time_ms,
-                    custom_genesis.initial_current_leader,
-                    custom_genesis.faucet_supply,
+        }) = genesis_receipts
+        else {
+            // The database has been bootstrapped before, e.g. it is a fork of a ledger
+            assert!(
+                !has_custom_genesis,
+                "A custom genesis can't be applied to a bootstrapped database"
+            );
+            let validator_set = SystemDatabaseReader::new(&substate_db)
+                .read_typed_object_field::<ConsensusManagerCurrentValidatorSetFieldPayload>(
+                    CONSENSUS_MANAGER.as_node_id(),
+                    ModuleId::Main,
+                    ConsensusManagerField::CurrentValidatorSet.field_index(),
                 )
-                .unwrap(),
-            None => bootstrapper.bootstrap_test_default().unwrap(),
+                .unwrap()
+                .into_latest()
+                .validator_set;
+            let runner = TestRunner {
+                scrypto_vm,
+                native_vm,
+                database: substate_db,
+                next_private_key,
+                next_transaction_nonce,
+                trace,
+                collected_events: Vec::new(),
+                xrd_free_credits_used: false,
+                skip_receipt_check: self.skip_receipt_check,
+            };
+            return (runner, validator_set);
         };
 
         let mut events = Vec::new();
@@ -451,12 +579,6 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunnerBuilder<E, D> {
                 .clone(),
         );
 
-        // Note that 0 is not a valid private key
-        let next_private_key = 100;
-
-        // Starting from non-zero considering that bootstrap might have used a few.
-        let next_transaction_nonce = 100;
-
         let runner = TestRunner {
             scrypto_vm,
             native_vm,
@@ -482,7 +604,7 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunnerBuilder<E, D> {
 }
 
 pub struct TestRunner<E: NativeVmExtension, D: TestDatabase> {
-    scrypto_vm: ScryptoVm<DefaultWasmEngine>,
+    scrypto_vm: ScryptoVm<TestRunnerWasmEngine>,
     native_vm: NativeVm<E>,
     database: D,
     next_private_key: u64,
@@ -493,13 +615,34 @@ pub struct TestRunner<E: NativeVmExtension, D: TestDatabase> {
     skip_receipt_check: bool,
 }
 
-#[cfg(feature = "post_run_db_check")]
+#[cfg(any(feature = "post_run_db_check", feature = "coverage"))]
 impl<E: NativeVmExtension, D: TestDatabase> Drop for TestRunner<E, D> {
     fn drop(&mut self) {
-        self.check_database()
+        #[cfg(feature = "post_run_db_check")]
+        self.check_database();
+        #[cfg(feature = "coverage")]
+        write_coverage_report();
     }
 }
 
+/// Writes the coverage collected so far by this process, replacing its previous report.
+#[cfg(feature = "coverage")]
+fn write_coverage_report() {
+    let (Some(collector), Some(dir)) = (
+        COVERAGE_COLLECTOR.as_ref(),
+        std::env::var_os(SCRYPTO_COVERAGE_DIR_ENV_VAR),
+    ) else {
+        return;
+    };
+    let _guard = COVERAGE_REPORT_LOCK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let mut path = PathBuf::from(dir);
+    fs::create_dir_all(&path).expect("Failed to create the coverage directory");
+    path.push(format!("{}.info", std::process::id()));
+    fs::write(&path, collector.to_lcov()).expect("Failed to write the coverage report");
+}
+
 #[derive(Clone)]
 pub struct TestRunnerSnapshot {
     database: InMemorySubstateDatabase,
@@ -761,10 +904,14 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         component_address: ComponentAddress,
         resource_address: ResourceAddress,
     ) -> Vec<NodeId> {
-        SubtreeVaults::new(&self.database)
-            .get_all(component_address.as_node_id())
-            .remove(&resource_address)
-            .unwrap_or_else(|| Vec::new())
+        match HoldingsQuery::new(&self.database)
+            .get_vaults(component_address.into(), resource_address)
+        {
+            Ok(vaults) => vaults,
+            // e.g. a virtual account which hasn't been created yet
+            Err(HoldingsQueryError::ComponentNotFound(_)) => Vec::new(),
+            Err(error) => panic!("Failed to get the component vaults: {:?}", error),
+        }
     }
 
     pub fn get_component_balance(
@@ -772,14 +919,14 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         account_address: ComponentAddress,
         resource_address: ResourceAddress,
     ) -> Decimal {
-        let vaults = self.get_component_vaults(account_address, resource_address);
-        let mut sum = Decimal::ZERO;
-        for vault in vaults {
-            sum = sum
-                .checked_add(self.inspect_vault_balance(vault).unwrap())
-                .unwrap();
+        match HoldingsQuery::new(&self.database)
+            .get_balance(account_address.into(), resource_address)
+        {
+            Ok(balance) => balance,
+            // e.g. a virtual account which hasn't been created yet
+            Err(HoldingsQueryError::ComponentNotFound(_)) => Decimal::ZERO,
+            Err(error) => panic!("Failed to get the component balance: {:?}", error),
         }
-        sum
     }
 
     pub fn inspect_vault_balance(&mut self, vault_id: NodeId) -> Option<Decimal> {
@@ -1375,7 +1522,7 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
     pub fn execute_manifest_with_fee_from_faucet_with_system<
         'a,
         T,
-        R: WrappedSystem<Vm<'a, DefaultWasmEngine, E>>,
+        R: WrappedSystem<Vm<'a, TestRunnerWasmEngine, E>>,
     >(
         &'a mut self,
         mut manifest: TransactionManifestV1,
@@ -1447,7 +1594,7 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         )
     }
 
-    pub fn execute_manifest_with_system<'a, T, R: WrappedSystem<Vm<'a, DefaultWasmEngine, E>>>(
+    pub fn execute_manifest_with_system<'a, T, R: WrappedSystem<Vm<'a, TestRunnerWasmEngine, E>>>(
         &'a mut self,
         manifest: TransactionManifestV1,
         initial_proofs: T,
@@ -1514,7 +1661,7 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         costing_parameters: CostingParameters,
         execution_config: ExecutionConfig,
     ) -> TransactionReceipt {
-        self.execute_transaction_with_system::<SystemConfig<Vm<'_, DefaultWasmEngine, E>>>(
+        self.execute_transaction_with_system::<SystemConfig<Vm<'_, TestRunnerWasmEngine, E>>>(
             executable,
             costing_parameters,
             execution_config,
@@ -1522,7 +1669,10 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         )
     }
 
-    pub fn execute_transaction_with_system<'a, T: WrappedSystem<Vm<'a, DefaultWasmEngine, E>>>(
+    pub fn execute_transaction_with_system<
+        'a,
+        T: WrappedSystem<Vm<'a, TestRunnerWasmEngine, E>>,
+    >(
         &'a mut self,
         executable: Executable,
         costing_parameters: CostingParameters,
@@ -1568,6 +1718,93 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         transaction_receipt
     }
 
+    /// Executes a batch of manifests in parallel, with the same results as executing them
+    /// one by one with [`Self::execute_manifest`].
+    pub fn execute_manifests_in_parallel<T>(
+        &mut self,
+        manifests: Vec<(TransactionManifestV1, T)>,
+        num_threads: usize,
+    ) -> Vec<TransactionReceipt>
+    where
+        T: IntoIterator<Item = NonFungibleGlobalId>,
+        D: Sync,
+        E: Sync,
+    {
+        let transactions: Vec<_> = manifests
+            .into_iter()
+            .map(|(manifest, initial_proofs)| {
+                let nonce = self.next_transaction_nonce();
+                (
+                    TestTransaction::new_from_nonce(manifest, nonce)
+                        .prepare()
+                        .expect("expected transaction to be preparable"),
+                    initial_proofs.into_iter().collect(),
+                )
+            })
+            .collect();
+        let executables: Vec<_> = transactions
+            .iter()
+            .map(|(transaction, initial_proofs)| transaction.get_executable(initial_proofs.clone()))
+            .collect();
+        self.execute_transactions_in_parallel(
+            &executables,
+            CostingParameters::default(),
+            ExecutionConfig::for_test_transaction(),
+            num_threads,
+        )
+    }
+
+    /// Executes a batch of transactions in parallel, with the same results as executing them
+    /// one by one with [`Self::execute_transaction`].
+    pub fn execute_transactions_in_parallel(
+        &mut self,
+        executables: &[Executable],
+        costing_parameters: CostingParameters,
+        mut execution_config: ExecutionConfig,
+        num_threads: usize,
+    ) -> Vec<TransactionReceipt>
+    where
+        D: Sync,
+        E: Sync,
+    {
+        // Override the kernel trace config
+        execution_config = execution_config.with_kernel_trace(self.trace);
+
+        if executables.iter().any(|executable| {
+            executable
+                .costing_parameters()
+                .free_credit_in_xrd
+                .is_positive()
+        }) {
+            self.xrd_free_credits_used = true;
+        }
+
+        let vm = Vm {
+            scrypto_vm: &self.scrypto_vm,
+            native_vm: self.native_vm.clone(),
+        };
+
+        let transaction_receipts = execute_and_commit_transactions_in_parallel(
+            &mut self.database,
+            vm,
+            &costing_parameters,
+            &execution_config,
+            executables,
+            num_threads,
+        );
+        for transaction_receipt in &transaction_receipts {
+            if let TransactionResult::Commit(commit) = &transaction_receipt.result {
+                self.collected_events
+                    .push(commit.application_events.clone());
+
+                if !self.skip_receipt_check {
+                    assert_receipt_substate_changes_can_be_typed(commit);
+                }
+            }
+        }
+        transaction_receipts
+    }
+
     pub fn preview(
         &mut self,
         preview_intent: PreviewIntentV1,
@@ -1581,6 +1818,27 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         execute_preview(&self.database, vm, network, preview_intent, self.trace)
     }
 
+    pub fn preview_with_state_overrides(
+        &mut self,
+        preview_intent: PreviewIntentV1,
+        network: &NetworkDefinition,
+        state_overrides: Vec<StateOverride>,
+    ) -> Result<StateOverridePreviewReceipt, PreviewError> {
+        let vm = Vm {
+            scrypto_vm: &self.scrypto_vm,
+            native_vm: self.native_vm.clone(),
+        };
+
+        execute_preview_with_state_overrides(
+            &self.database,
+            vm,
+            network,
+            preview_intent,
+            state_overrides,
+            self.trace,
+        )
+    }
+
     pub fn preview_manifest(
         &mut self,
         manifest: TransactionManifestV1,
@@ -2286,16 +2544,37 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         )
     }
 
+    /// Applies a protocol update to the ledger, as a node does with a ledger bootstrapped by an
+    /// older version of the engine.
+    pub fn apply_protocol_update(
+        &mut self,
+        protocol_update: ProtocolUpdate,
+    ) -> Vec<TransactionReceipt> {
+        let vm = Vm::new(&self.scrypto_vm, self.native_vm.clone());
+        Bootstrapper::new(
+            NetworkDefinition::simulator(),
+            &mut self.database,
+            vm,
+            self.trace,
+        )
+        .apply_protocol_update(protocol_update)
+    }
+
     /// Executes a "start round number `round` at timestamp `timestamp_ms`" system transaction, as
     /// if it was proposed by the first validator from the validator set, after `round - 1` missed
     /// rounds by that validator.
+    ///
+    /// Any scheduled calls made due by the round change are then executed, each in its own
+    /// transaction, by the same [`ScheduledCallExecutor`] as the protocol uses.
+    ///
+    /// No randomness is contributed, so the randomness beacon advances deterministically.
     pub fn advance_to_round_at_timestamp(
         &mut self,
         round: Round,
         proposer_timestamp_ms: i64,
     ) -> TransactionReceipt {
         let expected_round_number = self.get_consensus_manager_state().round.number() + 1;
-        self.execute_system_transaction(
+        let receipt = self.execute_system_transaction(
             vec![InstructionV1::CallMethod {
                 address: CONSENSUS_MANAGER.into(),
                 method_name: CONSENSUS_MANAGER_NEXT_ROUND_IDENT.to_string(),
@@ -2312,7 +2591,35 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
                 }),
             }],
             btreeset![AuthAddresses::validator_role()],
+        );
+        self.execute_due_scheduled_calls(&receipt);
+        receipt
+    }
+
+    /// Executes all scheduled calls made due by the given round change, returning the receipts of
+    /// their transactions.
+    pub fn execute_due_scheduled_calls(
+        &mut self,
+        round_receipt: &TransactionReceipt,
+    ) -> Vec<TransactionReceipt> {
+        let TransactionResult::Commit(round_change) = &round_receipt.result else {
+            return vec![];
+        };
+        let vm = Vm::new(&self.scrypto_vm, self.native_vm.clone());
+        let receipts = ScheduledCallExecutor::new(
+            NetworkDefinition::simulator(),
+            &mut self.database,
+            vm,
+            self.trace,
         )
+        .execute_due_calls(round_change);
+        for receipt in &receipts {
+            if let TransactionResult::Commit(commit) = &receipt.result {
+                self.collected_events
+                    .push(commit.application_events.clone());
+            }
+        }
+        receipts
     }
 
     /// Performs an [`advance_to_round_at_timestamp()`] with an unchanged timestamp.
@@ -2348,6 +2655,18 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
             .into_latest()
     }
 
+    pub fn get_randomness_beacon_state(&mut self) -> RandomnessBeaconSubstate {
+        let reader = SystemDatabaseReader::new(self.substate_db());
+        reader
+            .read_typed_object_field::<ConsensusManagerRandomnessBeaconFieldPayload>(
+                CONSENSUS_MANAGER.as_node_id(),
+                ModuleId::Main,
+                ConsensusManagerField::RandomnessBeacon.field_index(),
+            )
+            .unwrap()
+            .into_latest()
+    }
+
     pub fn get_current_time(&mut self, precision: TimePrecision) -> Instant {
         let receipt = self.execute_system_transaction(
             vec![InstructionV1::CallMethod {
@@ -2427,6 +2746,27 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
             .unwrap()
     }
 
+    /// Parses an event written in the RustLike display format against the event's schema, giving
+    /// the payload to compare an emitted event against.
+    pub fn parse_rustlike_event(
+        &self,
+        event_type_identifier: &EventTypeIdentifier,
+        text: &str,
+    ) -> Vec<u8> {
+        let (local_type_id, schema) = self.event_schema(event_type_identifier);
+        let decoder = AddressBech32Decoder::new(&NetworkDefinition::simulator());
+        parse_rustlike_payload(
+            text,
+            &RustLikeParseParameters::<ScryptoCustomExtension>::Annotated {
+                custom_context: ScryptoValueParseContext::with_optional_bech32(Some(&decoder)),
+                schema: schema.v1(),
+                type_id: local_type_id,
+                depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+            },
+        )
+        .unwrap_or_else(|error| panic!("Failed to parse event {}: {}", text, error))
+    }
+
     pub fn is_event_name_equal<T: ScryptoDescribe>(
         &self,
         event_type_identifier: &EventTypeIdentifier,
@@ -2506,6 +2846,10 @@ impl<E: NativeVmExtension, D: TestDatabase> TestRunner<E, D> {
         };
         println!("{:#?}", db_results);
 
+        if !db_results.1 .0.errors.is_empty() {
+            panic!("Resource violations: {:?}", db_results.1 .0.errors);
+        }
+
         if !db_results.1 .1.is_empty() {
             panic!("Role assignment violations: {:?}", db_results.1 .1);
         }
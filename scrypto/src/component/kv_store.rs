@@ -9,10 +9,19 @@ use radix_engine_interface::data::scrypto::well_known_scrypto_custom_types::{
 use radix_engine_interface::data::scrypto::*;
 use radix_engine_interface::prelude::{
     LocalKeyValueStoreDataSchema, KV_STORE_DATA_SCHEMA_VARIANT_LOCAL,
+    MAX_KEY_VALUE_STORE_SCAN_LIMIT,
 };
 use sbor::rust::marker::PhantomData;
 use sbor::*;
 
+/// A page of entries read from a [`KeyValueStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyValueStorePage<K, V> {
+    pub entries: Vec<(K, V)>,
+    /// An opaque cursor to resume the iteration from, or `None` if there are no more entries.
+    pub next_cursor: Option<Vec<u8>>,
+}
+
 /// A scalable key-value map which loads entries on demand.
 pub struct KeyValueStore<
     K: ScryptoEncode + ScryptoDecode + ScryptoDescribe,
@@ -115,6 +124,50 @@ impl<
 
         scrypto_decode(&rtn).unwrap()
     }
+
+    /// Returns up to `limit` entries of this map, starting after the given cursor (or from
+    /// the start if `None`).
+    ///
+    /// Entries are returned in a deterministic but unspecified order, which is not related
+    /// to the ordering of the keys. Pass the `next_cursor` of a page in to read the next page.
+    /// A page may hold fewer than `limit` entries even if more entries follow, so iteration
+    /// is only complete once `next_cursor` is `None`.
+    ///
+    /// Every entry scanned is costed, and `limit` must be non-zero and is capped by the
+    /// transaction limits.
+    pub fn iter(&self, limit: u32, cursor: Option<Vec<u8>>) -> KeyValueStorePage<K, V> {
+        let page = ScryptoVmV1Api::kv_store_scan_entries(self.id.as_node_id(), cursor, limit);
+        KeyValueStorePage {
+            entries: page
+                .entries
+                .into_iter()
+                .map(|(key, value)| {
+                    (
+                        scrypto_decode(&key).unwrap(),
+                        scrypto_decode(&value).unwrap(),
+                    )
+                })
+                .collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+
+    /// Returns all keys of this map, in the same order as [`KeyValueStore::iter`].
+    ///
+    /// The cost of this grows with the size of the map, so it should only be used on maps
+    /// which are known to be small.
+    pub fn keys(&self) -> Vec<K> {
+        let mut keys = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = self.iter(MAX_KEY_VALUE_STORE_SCAN_LIMIT, cursor);
+            keys.extend(page.entries.into_iter().map(|(key, _value)| key));
+            match page.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => return keys,
+            }
+        }
+    }
 }
 
 //========
@@ -3,6 +3,7 @@ use radix_engine_common::math::Decimal;
 use radix_engine_common::types::GlobalAddressReservation;
 use radix_engine_interface::api::actor_api::EventFlags;
 use radix_engine_interface::api::key_value_entry_api::KeyValueEntryHandle;
+use radix_engine_interface::api::key_value_store_api::KeyValueStoreEntriesPage;
 use radix_engine_interface::api::{ActorRefHandle, FieldValue};
 use radix_engine_interface::api::{AttachedModuleId, FieldIndex, LockFlags};
 use radix_engine_interface::crypto::Hash;
@@ -194,6 +195,24 @@ impl ScryptoVmV1Api {
         removed
     }
 
+    pub fn kv_store_scan_entries(
+        node_id: &NodeId,
+        cursor: Option<Vec<u8>>,
+        limit: u32,
+    ) -> KeyValueStoreEntriesPage {
+        let cursor = scrypto_encode(&cursor).unwrap();
+        let bytes = copy_buffer(unsafe {
+            kv_store::kv_store_scan_entries(
+                node_id.as_ref().as_ptr(),
+                node_id.as_ref().len(),
+                cursor.as_ptr(),
+                cursor.len(),
+                limit,
+            )
+        });
+        scrypto_decode(&bytes).unwrap()
+    }
+
     pub fn actor_open_field(object_handle: u32, field: u8, flags: LockFlags) -> SubstateHandle {
         let handle =
             unsafe { actor::actor_open_field(object_handle, u32::from(field), flags.bits()) };
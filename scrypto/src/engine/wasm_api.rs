@@ -199,6 +199,16 @@ pub mod kv_store {
             key: *const u8,
             key_len: usize,
         ) -> Buffer;
+
+        /// Scans up to `limit` entries of a key value store, starting after the given
+        /// SBOR-encoded `Option` cursor
+        pub fn kv_store_scan_entries(
+            key_value_store_id_ptr: *const u8,
+            key_value_store_id_len: usize,
+            cursor: *const u8,
+            cursor_len: usize,
+            limit: u32,
+        ) -> Buffer;
     }
 }
 
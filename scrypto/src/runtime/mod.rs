@@ -3,6 +3,7 @@ mod data;
 mod local_auth_zone;
 mod logger;
 mod runtime;
+mod scheduler;
 
 pub use clock::*;
 pub use data::*;
@@ -10,3 +11,4 @@ pub use local_auth_zone::*;
 pub use logger::Logger;
 pub use radix_engine_interface::data::scrypto::model::*;
 pub use runtime::*;
+pub use scheduler::*;
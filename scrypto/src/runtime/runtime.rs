@@ -7,7 +7,10 @@ use radix_engine_common::types::GlobalAddressReservation;
 use radix_engine_interface::api::actor_api::EventFlags;
 use radix_engine_interface::api::*;
 use radix_engine_interface::blueprints::consensus_manager::{
-    ConsensusManagerGetCurrentEpochInput, CONSENSUS_MANAGER_GET_CURRENT_EPOCH_IDENT,
+    ConsensusManagerCommitRandomSeedInput, ConsensusManagerGetCurrentEpochInput,
+    ConsensusManagerGetRandomSeedInput, RandomSeedCommitment,
+    CONSENSUS_MANAGER_COMMIT_RANDOM_SEED_IDENT, CONSENSUS_MANAGER_GET_CURRENT_EPOCH_IDENT,
+    CONSENSUS_MANAGER_GET_RANDOM_SEED_IDENT,
 };
 use radix_engine_interface::blueprints::resource::{
     AccessRule, AuthZoneAssertAccessRuleInput, NonFungibleGlobalId,
@@ -138,6 +141,35 @@ impl Runtime {
         scrypto_decode(&rtn).unwrap()
     }
 
+    /// Commits to the random seed of the next round, which can be obtained by [`Self::random_seed`]
+    /// once that round has started.
+    ///
+    /// The returned commitment should be stored together with whatever it should randomize.
+    pub fn commit_random_seed() -> RandomSeedCommitment {
+        let rtn = ScryptoVmV1Api::object_call(
+            CONSENSUS_MANAGER.as_node_id(),
+            CONSENSUS_MANAGER_COMMIT_RANDOM_SEED_IDENT,
+            scrypto_encode(&ConsensusManagerCommitRandomSeedInput).unwrap(),
+        );
+
+        scrypto_decode(&rtn).unwrap()
+    }
+
+    /// Returns the random seed provided by the protocol's randomness beacon for the given
+    /// commitment.
+    ///
+    /// Panics if the committed seed is not revealed yet (i.e. it is still the round in which the
+    /// commitment was made), or if it is no longer retained by the beacon.
+    pub fn random_seed(commitment: RandomSeedCommitment) -> Hash {
+        let rtn = ScryptoVmV1Api::object_call(
+            CONSENSUS_MANAGER.as_node_id(),
+            CONSENSUS_MANAGER_GET_RANDOM_SEED_IDENT,
+            scrypto_encode(&ConsensusManagerGetRandomSeedInput { commitment }).unwrap(),
+        );
+
+        scrypto_decode(&rtn).unwrap()
+    }
+
     pub fn get_execution_cost_unit_limit() -> u32 {
         ScryptoVmV1Api::costing_get_execution_cost_unit_limit()
     }
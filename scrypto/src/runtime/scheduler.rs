@@ -0,0 +1,56 @@
+use radix_engine_interface::blueprints::consensus_manager::{
+    ScheduledCallId, ScheduledCallTrigger, SchedulerCancelCallInput, SchedulerScheduleCallInput,
+    SCHEDULER_CANCEL_CALL_IDENT, SCHEDULER_SCHEDULE_CALL_IDENT,
+};
+use radix_engine_interface::blueprints::resource::{AccessRule, Bucket};
+use radix_engine_interface::constants::SCHEDULER;
+use radix_engine_interface::data::scrypto::{scrypto_decode, scrypto_encode};
+use radix_engine_interface::types::ComponentAddress;
+use sbor::rust::prelude::*;
+use scrypto::engine::scrypto_env::ScryptoVmV1Api;
+
+/// The system scheduler, which executes deferred method calls on behalf of components.
+#[derive(Debug)]
+pub struct Scheduler {}
+
+impl Scheduler {
+    /// Schedules a call of `method_name` on `target`, with the given SBOR-encoded arguments
+    /// (e.g. produced by `scrypto_args!`), to be executed by the protocol once `trigger` is met.
+    ///
+    /// The execution is paid for out of the `fee` XRD bucket. The call can be cancelled by
+    /// anyone satisfying `cancel_rule`, until it becomes due, or once it has expired without
+    /// having been attempted.
+    pub fn schedule_call(
+        target: ComponentAddress,
+        method_name: &str,
+        args: Vec<u8>,
+        trigger: ScheduledCallTrigger,
+        fee: Bucket,
+        cancel_rule: AccessRule,
+    ) -> ScheduledCallId {
+        let rtn = ScryptoVmV1Api::object_call(
+            SCHEDULER.as_node_id(),
+            SCHEDULER_SCHEDULE_CALL_IDENT,
+            scrypto_encode(&SchedulerScheduleCallInput {
+                target,
+                method_name: method_name.to_string(),
+                args,
+                trigger,
+                fee,
+                cancel_rule,
+            })
+            .unwrap(),
+        );
+        scrypto_decode(&rtn).unwrap()
+    }
+
+    /// Cancels a pending or expired scheduled call, returning its fee.
+    pub fn cancel_call(id: ScheduledCallId) -> Bucket {
+        let rtn = ScryptoVmV1Api::object_call(
+            SCHEDULER.as_node_id(),
+            SCHEDULER_CANCEL_CALL_IDENT,
+            scrypto_encode(&SchedulerCancelCallInput { id }).unwrap(),
+        );
+        scrypto_decode(&rtn).unwrap()
+    }
+}
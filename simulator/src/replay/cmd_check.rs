@@ -0,0 +1,54 @@
+use super::Error;
+use crate::utils::check_ledger;
+use clap::Parser;
+use radix_engine::system::checkers::ApplicationChecker;
+use radix_engine::types::*;
+use radix_engine_interface::prelude::NetworkDefinition;
+use radix_engine_stores::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+use std::path::PathBuf;
+
+/// Check the invariants of the ledger state in a database
+#[derive(Parser, Debug)]
+pub struct LedgerCheck {
+    /// Path to a folder storing state
+    pub database_dir: PathBuf,
+
+    /// The network to use, [mainnet | stokenet]
+    #[clap(short, long)]
+    pub network: Option<String>,
+}
+
+impl LedgerCheck {
+    pub fn run(&self) -> Result<(), Error> {
+        self.run_with_application_checker(())
+    }
+
+    /// Runs the check with an additional, user-provided application checker, whose results are
+    /// printed along with the report.
+    pub fn run_with_application_checker<A: ApplicationChecker>(
+        &self,
+        application_checker: A,
+    ) -> Result<(), Error> {
+        let network = match &self.network {
+            Some(n) => NetworkDefinition::from_str(n).map_err(Error::ParseNetworkError)?,
+            None => NetworkDefinition::mainnet(),
+        };
+
+        let database = RocksDBWithMerkleTreeSubstateStore::read_only(self.database_dir.clone());
+        println!("State version: {}", database.get_current_version());
+        println!("State root hash: {}", database.get_current_root_hash());
+
+        let report = check_ledger(
+            &database,
+            application_checker,
+            &network,
+            &mut std::io::stdout(),
+        )
+        .map_err(Error::IOError)?;
+        if report.is_consistent() {
+            Ok(())
+        } else {
+            Err(Error::InconsistentLedger(report.violations.len()))
+        }
+    }
+}
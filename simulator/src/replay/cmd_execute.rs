@@ -1,12 +1,14 @@
-use super::ledger_transaction_execution::execute_ledger_transaction;
+use super::ledger_transaction_execution::{
+    execute_ledger_transaction, execute_ledger_transaction_recording_database_reads,
+    prepare_and_validate_ledger_transactions,
+};
 use super::txn_reader::TxnReader;
-use super::Error;
+use super::{create_scrypto_vm, Error};
 use clap::Parser;
 use flate2::read::GzDecoder;
 use flume;
+use radix_engine::transaction::execute_and_commit_in_parallel;
 use radix_engine::types::*;
-use radix_engine::vm::wasm::*;
-use radix_engine::vm::ScryptoVm;
 use radix_engine_interface::prelude::NetworkDefinition;
 use radix_engine_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
 use radix_engine_store_interface::interface::CommittableSubstateDatabase;
@@ -31,8 +33,18 @@ pub struct TxnExecute {
     /// The max version to execute
     #[clap(short, long)]
     pub max_version: Option<u64>,
+    /// Path to a folder for caching instrumented WASM code (and prepared WASM modules, with the Wasmer engine) across runs
+    #[clap(long)]
+    pub wasm_module_cache_dir: Option<PathBuf>,
+    /// The number of threads for executing batches of transactions in parallel.
+    /// The default is 1, i.e. transactions are executed one by one.
+    #[clap(long)]
+    pub threads: Option<usize>,
 }
 
+/// The max number of transactions per thread in a parallel batch.
+const MAX_BATCH_SIZE_PER_THREAD: usize = 16;
+
 impl TxnExecute {
     pub fn run(&self) -> Result<(), Error> {
         let network = match &self.network {
@@ -51,7 +63,9 @@ impl TxnExecute {
         let to_version = self.max_version.clone();
 
         let start = std::time::Instant::now();
-        let (tx, rx) = flume::bounded(10);
+        let threads = self.threads.unwrap_or(1);
+        let max_batch_size = threads.max(1) * MAX_BATCH_SIZE_PER_THREAD;
+        let (tx, rx) = flume::bounded(max_batch_size.max(10));
 
         // txn reader
         let mut txn_reader = if self.source.is_file() {
@@ -69,21 +83,54 @@ impl TxnExecute {
 
         // txn executor
         let mut database = RocksDBWithMerkleTreeSubstateStore::standard(self.database_dir.clone());
+        let scrypto_vm = create_scrypto_vm(self.wasm_module_cache_dir.as_ref())?;
         let txn_write_thread_handle = thread::spawn(move || {
-            let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
-            let iter = rx.iter();
-            for tx_payload in iter {
-                let state_updates =
-                    execute_ledger_transaction(&database, &scrypto_vm, &network, &tx_payload);
-                let database_updates =
-                    state_updates.create_database_updates::<SpreadPrefixKeyMapper>();
-                database.commit(&database_updates);
-
-                let new_state_root_hash = database.get_current_root_hash();
-                let new_version = database.get_current_version();
-
-                if new_version < 1000 || new_version % 1000 == 0 {
-                    print_progress(start.elapsed(), new_version, new_state_root_hash);
+            if threads <= 1 {
+                let iter = rx.iter();
+                for tx_payload in iter {
+                    let state_updates =
+                        execute_ledger_transaction(&database, &scrypto_vm, &network, &tx_payload);
+                    let database_updates =
+                        state_updates.create_database_updates::<SpreadPrefixKeyMapper>();
+                    database.commit(&database_updates);
+
+                    let new_state_root_hash = database.get_current_root_hash();
+                    let new_version = database.get_current_version();
+
+                    if new_version < 1000 || new_version % 1000 == 0 {
+                        print_progress(start.elapsed(), new_version, new_state_root_hash);
+                    }
+                }
+            } else {
+                // Execute all transactions available so far as a batch
+                while let Ok(tx_payload) = rx.recv() {
+                    let mut batch = vec![tx_payload];
+                    batch.extend(rx.try_iter().take(max_batch_size - 1));
+
+                    // The signatures of all user transactions of the batch are verified together
+                    let transactions = prepare_and_validate_ledger_transactions(&network, &batch);
+
+                    let prev_version = database.get_current_version();
+                    execute_and_commit_in_parallel(
+                        &mut database,
+                        &transactions,
+                        threads,
+                        |database, transaction| {
+                            execute_ledger_transaction_recording_database_reads(
+                                database,
+                                &scrypto_vm,
+                                &network,
+                                transaction,
+                            )
+                        },
+                    );
+
+                    let new_state_root_hash = database.get_current_root_hash();
+                    let new_version = database.get_current_version();
+
+                    if new_version < 1000 || new_version / 1000 > prev_version / 1000 {
+                        print_progress(start.elapsed(), new_version, new_state_root_hash);
+                    }
                 }
             }
 
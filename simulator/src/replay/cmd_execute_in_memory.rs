@@ -1,12 +1,10 @@
 use super::ledger_transaction_execution::execute_ledger_transaction;
 use super::txn_reader::TxnReader;
-use super::Error;
+use super::{create_scrypto_vm, Error};
 use clap::Parser;
 use flate2::read::GzDecoder;
 use flume;
 use radix_engine::types::*;
-use radix_engine::vm::wasm::*;
-use radix_engine::vm::ScryptoVm;
 use radix_engine_interface::prelude::NetworkDefinition;
 use radix_engine_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
 use radix_engine_store_interface::interface::CommittableSubstateDatabase;
@@ -34,6 +32,9 @@ pub struct TxnExecuteInMemory {
     /// State hash breakpoints, in format of comma separated `<version>:<hash>`
     #[clap(short, long)]
     pub breakpoints: Option<String>,
+    /// Path to a folder for caching instrumented WASM code (and prepared WASM modules, with the Wasmer engine) across runs
+    #[clap(long)]
+    pub wasm_module_cache_dir: Option<PathBuf>,
 }
 
 impl TxnExecuteInMemory {
@@ -81,8 +82,8 @@ impl TxnExecuteInMemory {
         // txn executor
         let substate_database = InMemorySubstateDatabase::standard();
         let mut database = HashTreeUpdatingDatabase::new(substate_database);
+        let scrypto_vm = create_scrypto_vm(self.wasm_module_cache_dir.as_ref())?;
         let txn_write_thread_handle = thread::spawn(move || {
-            let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
             let iter = rx.iter();
             for tx_payload in iter {
                 let state_updates =
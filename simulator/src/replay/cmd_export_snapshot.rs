@@ -0,0 +1,37 @@
+use super::Error;
+use clap::Parser;
+use radix_engine_stores::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+use radix_engine_stores::snapshot::{export_snapshot, LedgerSnapshotHeader};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+/// Export the ledger state in a database to a snapshot file
+#[derive(Parser, Debug)]
+pub struct SnapshotExport {
+    /// Path to a folder storing state
+    pub database_dir: PathBuf,
+    /// The path of the snapshot file to create
+    pub output: PathBuf,
+}
+
+impl SnapshotExport {
+    pub fn run(&self) -> Result<(), Error> {
+        let database = RocksDBWithMerkleTreeSubstateStore::read_only(self.database_dir.clone());
+        let header = LedgerSnapshotHeader {
+            state_version: database.get_current_version(),
+            state_root_hash: database.get_current_root_hash(),
+        };
+        println!("State version: {}", header.state_version);
+        println!("State root hash: {}", header.state_root_hash);
+
+        let file = File::create(&self.output).map_err(Error::IOError)?;
+        let summary = export_snapshot(&database, header, &mut BufWriter::new(file))
+            .map_err(Error::SnapshotError)?;
+        println!(
+            "Exported {} partitions and {} substates",
+            summary.partition_count, summary.substate_count
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use super::Error;
+use clap::Parser;
+use radix_engine_stores::rocks_db_with_merkle_tree::RocksDBWithMerkleTreeSubstateStore;
+use radix_engine_stores::snapshot::import_snapshot_at_state_version;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+/// Import the ledger state from a snapshot file into a new database
+#[derive(Parser, Debug)]
+pub struct SnapshotImport {
+    /// The path of the snapshot file
+    pub input: PathBuf,
+    /// Path to a folder storing state, which must not contain any yet
+    pub database_dir: PathBuf,
+}
+
+impl SnapshotImport {
+    pub fn run(&self) -> Result<(), Error> {
+        let file = File::open(&self.input).map_err(Error::IOError)?;
+        let mut database = RocksDBWithMerkleTreeSubstateStore::standard(self.database_dir.clone());
+        if database.get_current_version() > 0 {
+            return Err(Error::DatabaseNotEmpty);
+        }
+
+        let summary = import_snapshot_at_state_version(&mut BufReader::new(file), &mut database)
+            .map_err(Error::SnapshotError)?;
+        println!(
+            "Imported {} partitions and {} substates",
+            summary.partition_count, summary.substate_count
+        );
+        println!("State version: {}", database.get_current_version());
+        println!("State root hash: {}", database.get_current_root_hash());
+        Ok(())
+    }
+}
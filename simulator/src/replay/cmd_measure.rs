@@ -1,13 +1,11 @@
 use super::ledger_transaction_execution::*;
 use super::txn_reader::TxnReader;
-use super::Error;
+use super::{create_scrypto_vm, Error};
 use crate::replay::ledger_transaction::PreparedLedgerTransactionInner;
 use clap::Parser;
 use flate2::read::GzDecoder;
 use flume;
 use radix_engine::types::*;
-use radix_engine::vm::wasm::*;
-use radix_engine::vm::ScryptoVm;
 use radix_engine_interface::prelude::NetworkDefinition;
 use radix_engine_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
 use radix_engine_store_interface::interface::CommittableSubstateDatabase;
@@ -38,6 +36,9 @@ pub struct TxnMeasure {
     /// The max version to execute
     #[clap(short, long)]
     pub max_version: Option<u64>,
+    /// Path to a folder for caching instrumented WASM code (and prepared WASM modules, with the Wasmer engine) across runs
+    #[clap(long)]
+    pub wasm_module_cache_dir: Option<PathBuf>,
 }
 
 impl TxnMeasure {
@@ -91,8 +92,9 @@ impl TxnMeasure {
             .map_err(Error::IOError)?;
         }
 
+        let scrypto_vm = create_scrypto_vm(self.wasm_module_cache_dir.as_ref())?;
+
         let txn_write_thread_handle = thread::spawn(move || {
-            let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
             let iter = rx.iter();
             for tx_payload in iter {
                 let tx_start_time = std::time::Instant::now();
@@ -1,12 +1,10 @@
 use super::ledger_transaction::*;
 use super::ledger_transaction_execution::execute_ledger_transaction;
-use super::Error;
+use super::{create_scrypto_vm, Error};
 use clap::Parser;
 use flume;
 use flume::Sender;
 use radix_engine::types::*;
-use radix_engine::vm::wasm::*;
-use radix_engine::vm::ScryptoVm;
 use radix_engine_interface::prelude::NetworkDefinition;
 use radix_engine_store_interface::db_key_mapper::SpreadPrefixKeyMapper;
 use radix_engine_store_interface::interface::CommittableSubstateDatabase;
@@ -33,6 +31,9 @@ pub struct TxnSync {
     /// The max version to execute
     #[clap(short, long)]
     pub max_version: Option<u64>,
+    /// Path to a folder for caching instrumented WASM code (and prepared WASM modules, with the Wasmer engine) across runs
+    #[clap(long)]
+    pub wasm_module_cache_dir: Option<PathBuf>,
 }
 
 impl TxnSync {
@@ -62,8 +63,8 @@ impl TxnSync {
 
         // txn executor
         let mut database = RocksDBWithMerkleTreeSubstateStore::standard(self.database_dir.clone());
+        let scrypto_vm = create_scrypto_vm(self.wasm_module_cache_dir.as_ref())?;
         let txn_write_thread_handle = thread::spawn(move || {
-            let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
             let iter = rx.iter();
             for (tx_payload, expected_state_root_hash) in iter {
                 let state_updates =
@@ -0,0 +1,27 @@
+use super::Error;
+use clap::Parser;
+use radix_engine_stores::snapshot::verify_snapshot;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+/// Verify that a snapshot file is complete and matches its state root hash
+#[derive(Parser, Debug)]
+pub struct SnapshotVerify {
+    /// The path of the snapshot file
+    pub input: PathBuf,
+}
+
+impl SnapshotVerify {
+    pub fn run(&self) -> Result<(), Error> {
+        let file = File::open(&self.input).map_err(Error::IOError)?;
+        let summary = verify_snapshot(&mut BufReader::new(file)).map_err(Error::SnapshotError)?;
+        println!("State version: {}", summary.header.state_version);
+        println!("State root hash: {}", summary.header.state_root_hash);
+        println!(
+            "Verified {} partitions and {} substates",
+            summary.partition_count, summary.substate_count
+        );
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use radix_engine_interface::prelude::ParseNetworkError;
+use radix_engine_stores::snapshot::SnapshotError;
 
 #[derive(Debug)]
 pub enum Error {
@@ -8,4 +9,9 @@ pub enum Error {
     InvalidTransactionArchive,
     InvalidTransactionSource,
     InvalidBreakpoints(String),
+    /// The ledger checkers found the given number of violations.
+    InconsistentLedger(usize),
+    SnapshotError(SnapshotError),
+    /// A snapshot can only be imported into a database without any state.
+    DatabaseNotEmpty,
 }
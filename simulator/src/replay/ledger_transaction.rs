@@ -118,6 +118,7 @@ impl PreparedRoundUpdateTransactionV1 {
                     free_credit_in_xrd: Decimal::ZERO,
                 },
                 pre_allocated_addresses: vec![],
+                subintent_params: SubintentParams::default(),
             },
         )
     }
@@ -1,8 +1,9 @@
 use super::ledger_transaction::*;
 use radix_engine::system::bootstrap::*;
-use radix_engine::track::StateUpdates;
+use radix_engine::track::{DatabaseReads, StateUpdates};
 use radix_engine::transaction::{
-    execute_transaction, CostingParameters, ExecutionConfig, TransactionFeeSummary,
+    execute_transaction, execute_transaction_recording_database_reads, CostingParameters,
+    ExecutionConfig, FeeDestination, ParallelExecutionReceipt, TransactionFeeSummary,
     TransactionReceipt,
 };
 use radix_engine::types::*;
@@ -11,6 +12,7 @@ use radix_engine::vm::{DefaultNativeVm, ScryptoVm, Vm};
 use radix_engine_interface::prelude::node_modules::auth::AuthAddresses;
 use radix_engine_interface::prelude::NetworkDefinition;
 use radix_engine_store_interface::interface::SubstateDatabase;
+use transaction::model::Executable;
 use transaction::validation::{
     NotarizedTransactionValidator, TransactionValidator, ValidationConfig,
 };
@@ -38,6 +40,22 @@ impl LedgerTransactionReceipt {
     }
 }
 
+impl ParallelExecutionReceipt for LedgerTransactionReceipt {
+    fn state_updates_mut(&mut self) -> Option<&mut StateUpdates> {
+        match self {
+            LedgerTransactionReceipt::Flash(receipt) => Some(&mut receipt.state_updates),
+            LedgerTransactionReceipt::Standard(receipt) => receipt.state_updates_mut(),
+        }
+    }
+
+    fn fee_destination(&self) -> Option<&FeeDestination> {
+        match self {
+            LedgerTransactionReceipt::Flash(_) => None,
+            LedgerTransactionReceipt::Standard(receipt) => receipt.fee_destination(),
+        }
+    }
+}
+
 pub fn execute_ledger_transaction<S: SubstateDatabase>(
     database: &S,
     scrypto_vm: &ScryptoVm<DefaultWasmEngine>,
@@ -63,6 +81,102 @@ pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
     scrypto_vm: &ScryptoVm<DefaultWasmEngine>,
     network: &NetworkDefinition,
     prepared: &PreparedLedgerTransaction,
+) -> LedgerTransactionReceipt {
+    execute_prepared_ledger_transaction_with(network, prepared, |execution_config, executable| {
+        execute_transaction(
+            database,
+            Vm {
+                scrypto_vm,
+                native_vm: DefaultNativeVm::new(),
+            },
+            &CostingParameters::default(),
+            execution_config,
+            executable,
+        )
+    })
+}
+
+/// Prepares many ledger transactions, and validates all the user transactions among them at once
+/// (see [`NotarizedTransactionValidator::validate_batch`]).
+pub fn prepare_and_validate_ledger_transactions<T: AsRef<[u8]>>(
+    network: &NetworkDefinition,
+    tx_payloads: &[T],
+) -> Vec<ValidatedLedgerTransaction> {
+    let prepared: Vec<PreparedLedgerTransaction> = tx_payloads
+        .iter()
+        .map(|tx_payload| prepare_ledger_transaction(tx_payload.as_ref()))
+        .collect();
+
+    let user_transactions = prepared
+        .iter()
+        .filter_map(|prepared| match &prepared.inner {
+            PreparedLedgerTransactionInner::UserV1(tx) => Some(tx.as_ref().clone()),
+            _ => None,
+        })
+        .collect();
+    let mut validated_user_transactions =
+        NotarizedTransactionValidator::new(ValidationConfig::default(network.id))
+            .validate_batch(user_transactions)
+            .into_iter();
+
+    prepared
+        .into_iter()
+        .map(|prepared| ValidatedLedgerTransaction {
+            inner: match prepared.inner {
+                PreparedLedgerTransactionInner::Genesis(tx) => {
+                    ValidatedLedgerTransactionInner::Genesis(tx)
+                }
+                PreparedLedgerTransactionInner::UserV1(_) => {
+                    ValidatedLedgerTransactionInner::UserV1(Box::new(
+                        validated_user_transactions
+                            .next()
+                            .expect("There is a result for every user transaction")
+                            .expect("Transaction validation failure"),
+                    ))
+                }
+                PreparedLedgerTransactionInner::RoundUpdateV1(tx) => {
+                    ValidatedLedgerTransactionInner::RoundUpdateV1(tx)
+                }
+            },
+            summary: prepared.summary,
+        })
+        .collect()
+}
+
+/// Executes a validated ledger transaction like [`execute_ledger_transaction`], and returns the
+/// database reads it depends on along with the receipt.
+pub fn execute_ledger_transaction_recording_database_reads<S: SubstateDatabase>(
+    database: &S,
+    scrypto_vm: &ScryptoVm<DefaultWasmEngine>,
+    network: &NetworkDefinition,
+    validated: &ValidatedLedgerTransaction,
+) -> (LedgerTransactionReceipt, DatabaseReads) {
+    let mut database_reads = DatabaseReads::default();
+    let receipt = execute_validated_ledger_transaction_with(
+        network,
+        validated,
+        |execution_config, executable| {
+            let (receipt, reads) = execute_transaction_recording_database_reads(
+                database,
+                Vm {
+                    scrypto_vm,
+                    native_vm: DefaultNativeVm::new(),
+                },
+                &CostingParameters::default(),
+                execution_config,
+                executable,
+            );
+            database_reads = reads;
+            receipt
+        },
+    );
+    (receipt, database_reads)
+}
+
+fn execute_prepared_ledger_transaction_with(
+    network: &NetworkDefinition,
+    prepared: &PreparedLedgerTransaction,
+    execute: impl FnOnce(&ExecutionConfig, &Executable) -> TransactionReceipt,
 ) -> LedgerTransactionReceipt {
     match &prepared.inner {
         PreparedLedgerTransactionInner::Genesis(prepared_genesis_tx) => {
@@ -72,13 +186,7 @@ pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
                     LedgerTransactionReceipt::Flash(receipt)
                 }
                 PreparedGenesisTransaction::Transaction(tx) => {
-                    let receipt = execute_transaction(
-                        database,
-                        Vm {
-                            scrypto_vm,
-                            native_vm: DefaultNativeVm::new(),
-                        },
-                        &CostingParameters::default(),
+                    let receipt = execute(
                         &ExecutionConfig::for_genesis_transaction(network.clone()),
                         &tx.get_executable(btreeset!(AuthAddresses::system_role())),
                     );
@@ -87,13 +195,7 @@ pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
             }
         }
         PreparedLedgerTransactionInner::UserV1(tx) => {
-            let receipt = execute_transaction(
-                database,
-                Vm {
-                    scrypto_vm,
-                    native_vm: DefaultNativeVm::new(),
-                },
-                &CostingParameters::default(),
+            let receipt = execute(
                 &ExecutionConfig::for_notarized_transaction(network.clone()),
                 &NotarizedTransactionValidator::new(ValidationConfig::default(network.id))
                     .validate(tx.as_ref().clone())
@@ -103,13 +205,7 @@ pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
             LedgerTransactionReceipt::Standard(receipt)
         }
         PreparedLedgerTransactionInner::RoundUpdateV1(tx) => {
-            let receipt = execute_transaction(
-                database,
-                Vm {
-                    scrypto_vm,
-                    native_vm: DefaultNativeVm::new(),
-                },
-                &CostingParameters::default(),
+            let receipt = execute(
                 &ExecutionConfig::for_system_transaction(network.clone()),
                 &tx.get_executable(),
             );
@@ -117,3 +213,28 @@ pub fn execute_prepared_ledger_transaction<S: SubstateDatabase>(
         }
     }
 }
+
+fn execute_validated_ledger_transaction_with(
+    network: &NetworkDefinition,
+    validated: &ValidatedLedgerTransaction,
+    execute: impl FnOnce(&ExecutionConfig, &Executable) -> TransactionReceipt,
+) -> LedgerTransactionReceipt {
+    if validated.as_genesis_flash().is_some() {
+        let receipt = create_substate_flash_for_genesis();
+        return LedgerTransactionReceipt::Flash(receipt);
+    }
+
+    let execution_config = match &validated.inner {
+        ValidatedLedgerTransactionInner::Genesis(_) => {
+            ExecutionConfig::for_genesis_transaction(network.clone())
+        }
+        ValidatedLedgerTransactionInner::UserV1(_) => {
+            ExecutionConfig::for_notarized_transaction(network.clone())
+        }
+        ValidatedLedgerTransactionInner::RoundUpdateV1(_) => {
+            ExecutionConfig::for_system_transaction(network.clone())
+        }
+    };
+    let receipt = execute(&execution_config, &validated.get_executable());
+    LedgerTransactionReceipt::Standard(receipt)
+}
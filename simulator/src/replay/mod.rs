@@ -2,21 +2,32 @@ pub mod ledger_transaction;
 pub mod ledger_transaction_execution;
 pub mod txn_reader;
 
+mod cmd_check;
 mod cmd_execute;
 mod cmd_execute_in_memory;
+mod cmd_export_snapshot;
+mod cmd_import_snapshot;
 mod cmd_measure;
 mod cmd_prepare;
 mod cmd_sync;
+mod cmd_verify_snapshot;
 mod error;
 
+pub use cmd_check::*;
 pub use cmd_execute::*;
 pub use cmd_execute_in_memory::*;
+pub use cmd_export_snapshot::*;
+pub use cmd_import_snapshot::*;
 pub use cmd_measure::*;
 pub use cmd_prepare::*;
 pub use cmd_sync::*;
+pub use cmd_verify_snapshot::*;
 pub use error::*;
 
 use clap::{Parser, Subcommand};
+use radix_engine::vm::wasm::*;
+use radix_engine::vm::ScryptoVm;
+use std::path::PathBuf;
 
 /// Transaction replay toolkit
 #[derive(Parser, Debug)]
@@ -33,6 +44,10 @@ pub enum Command {
     ExecuteInMemory(TxnExecuteInMemory),
     Sync(TxnSync),
     Measure(TxnMeasure),
+    Check(LedgerCheck),
+    ExportSnapshot(SnapshotExport),
+    ImportSnapshot(SnapshotImport),
+    VerifySnapshot(SnapshotVerify),
 }
 
 pub fn run() -> Result<(), Error> {
@@ -44,5 +59,24 @@ pub fn run() -> Result<(), Error> {
         Command::ExecuteInMemory(cmd) => cmd.run(),
         Command::Sync(cmd) => cmd.sync(),
         Command::Measure(cmd) => cmd.run(),
+        Command::Check(cmd) => cmd.run(),
+        Command::ExportSnapshot(cmd) => cmd.run(),
+        Command::ImportSnapshot(cmd) => cmd.run(),
+        Command::VerifySnapshot(cmd) => cmd.run(),
+    }
+}
+
+/// Creates a Scrypto VM, which persists instrumented WASM code (and the prepared WASM modules, if
+/// the WASM engine supports it) to the given directory, if any.
+pub fn create_scrypto_vm(
+    wasm_module_cache_dir: Option<&PathBuf>,
+) -> Result<ScryptoVm<DefaultWasmEngine>, Error> {
+    let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
+    match wasm_module_cache_dir {
+        Some(directory) => {
+            let module_disk_cache = WasmModuleDiskCache::new(directory).map_err(Error::IOError)?;
+            Ok(scrypto_vm.with_module_disk_cache(module_disk_cache))
+        }
+        None => Ok(scrypto_vm),
     }
 }
@@ -0,0 +1,42 @@
+use crate::resim::*;
+use crate::utils::check_ledger;
+use clap::Parser;
+use radix_engine::system::checkers::ApplicationChecker;
+
+/// Check the invariants of the ledger state
+#[derive(Parser, Debug)]
+pub struct CheckLedger {}
+
+impl CheckLedger {
+    pub fn run<O: std::io::Write>(&self, out: &mut O) -> Result<(), Error> {
+        self.run_with_application_checker(out, ())
+    }
+
+    /// Runs the check with an additional, user-provided application checker, whose results are
+    /// printed along with the report.
+    pub fn run_with_application_checker<O: std::io::Write, A: ApplicationChecker>(
+        &self,
+        out: &mut O,
+        application_checker: A,
+    ) -> Result<(), Error> {
+        let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
+        let native_vm = DefaultNativeVm::new();
+        let vm = Vm::new(&scrypto_vm, native_vm);
+        let mut substate_db = RocksdbSubstateStore::standard(get_data_dir()?);
+        Bootstrapper::new(NetworkDefinition::simulator(), &mut substate_db, vm, false)
+            .bootstrap_test_default();
+
+        let report = check_ledger(
+            &substate_db,
+            application_checker,
+            &NetworkDefinition::simulator(),
+            out,
+        )
+        .map_err(Error::IOError)?;
+        if report.is_consistent() {
+            Ok(())
+        } else {
+            Err(Error::InconsistentLedger(report.violations.len()))
+        }
+    }
+}
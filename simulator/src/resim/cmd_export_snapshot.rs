@@ -0,0 +1,44 @@
+use crate::resim::*;
+use clap::Parser;
+use radix_engine_stores::snapshot::{
+    compute_state_root_hash, export_snapshot, LedgerSnapshotHeader,
+};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+/// Export the ledger state to a snapshot file
+#[derive(Parser, Debug)]
+pub struct ExportSnapshot {
+    /// The path of the snapshot file to create
+    pub output: PathBuf,
+}
+
+impl ExportSnapshot {
+    pub fn run<O: std::io::Write>(&self, out: &mut O) -> Result<(), Error> {
+        let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
+        let native_vm = DefaultNativeVm::new();
+        let vm = Vm::new(&scrypto_vm, native_vm);
+        let mut substate_db = RocksdbSubstateStore::standard(get_data_dir()?);
+        Bootstrapper::new(NetworkDefinition::simulator(), &mut substate_db, vm, false)
+            .bootstrap_test_default();
+
+        // The simulator ledger does not track state versions, so only the root hash is recorded
+        let header = LedgerSnapshotHeader {
+            state_version: 0,
+            state_root_hash: compute_state_root_hash(&substate_db),
+        };
+        let file = File::create(&self.output)
+            .map_err(|err| Error::IOErrorAtPath(err, self.output.clone()))?;
+        let summary = export_snapshot(&substate_db, header, &mut BufWriter::new(file))
+            .map_err(Error::SnapshotError)?;
+
+        writeln!(
+            out,
+            "Exported {} partitions and {} substates, with state root hash {}",
+            summary.partition_count, summary.substate_count, summary.header.state_root_hash
+        )
+        .map_err(Error::IOError)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use crate::resim::*;
+use clap::Parser;
+use radix_engine_store_interface::interface::ListableSubstateDatabase;
+use radix_engine_stores::snapshot::import_snapshot;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+/// Import the ledger state from a snapshot file, into a ledger which has been reset
+#[derive(Parser, Debug)]
+pub struct ImportSnapshot {
+    /// The path of the snapshot file
+    pub input: PathBuf,
+}
+
+impl ImportSnapshot {
+    pub fn run<O: std::io::Write>(&self, out: &mut O) -> Result<(), Error> {
+        let mut substate_db = RocksdbSubstateStore::standard(get_data_dir()?);
+        if substate_db.list_partition_keys().next().is_some() {
+            return Err(Error::LedgerNotEmpty);
+        }
+
+        let file =
+            File::open(&self.input).map_err(|err| Error::IOErrorAtPath(err, self.input.clone()))?;
+        let summary = import_snapshot(&mut BufReader::new(file), &mut substate_db)
+            .map_err(Error::SnapshotError)?;
+
+        writeln!(
+            out,
+            "Imported {} partitions and {} substates, with state root hash {}",
+            summary.partition_count, summary.substate_count, summary.header.state_root_hash
+        )
+        .map_err(Error::IOError)?;
+        Ok(())
+    }
+}
@@ -25,6 +25,19 @@ pub struct NewAccount {
     /// Turn on tracing
     #[clap(short, long)]
     pub trace: bool,
+
+    /// Derive the key of the account from a BIP39 mnemonic, along the same path as the Radix
+    /// wallet, instead of generating a random one
+    #[clap(long)]
+    pub mnemonic: Option<String>,
+
+    /// The BIP39 passphrase of the mnemonic
+    #[clap(long, requires = "mnemonic")]
+    pub passphrase: Option<String>,
+
+    /// The index of the account derived from the mnemonic, 0 by default
+    #[clap(long, requires = "mnemonic")]
+    pub index: Option<u32>,
 }
 
 #[derive(ScryptoSbor, ManifestSbor)]
@@ -32,6 +45,10 @@ struct EmptyStruct;
 
 impl NewAccount {
     pub fn run<O: std::io::Write>(&self, out: &mut O) -> Result<(), Error> {
+        if let Some(phrase) = &self.mnemonic {
+            return self.run_with_mnemonic(phrase, out);
+        }
+
         let secret = rand::thread_rng().gen::<[u8; 32]>();
         let private_key = Secp256k1PrivateKey::from_bytes(&secret).unwrap();
         let public_key = private_key.public_key();
@@ -148,4 +165,99 @@ impl NewAccount {
 
         Ok(())
     }
+
+    /// Creates the (virtual) account of an Ed25519 key derived from the mnemonic, by depositing
+    /// free XRD into it. As with the Radix wallet, the owner badge of the account is the signature
+    /// badge of its key.
+    fn run_with_mnemonic<O: std::io::Write>(&self, phrase: &str, out: &mut O) -> Result<(), Error> {
+        let network = match &self.network {
+            Some(n) => NetworkDefinition::from_str(n).map_err(Error::ParseNetworkError)?,
+            None => NetworkDefinition::simulator(),
+        };
+        let mnemonic = Mnemonic::from_phrase(phrase).map_err(Error::InvalidMnemonic)?;
+        let index = self.index.unwrap_or(0);
+        let private_key = mnemonic
+            .derive_account_private_key(self.passphrase.as_deref().unwrap_or(""), network.id, index)
+            .map_err(Error::InvalidDerivation)?;
+        let derivation_path =
+            DerivationPath::account(network.id, index).map_err(Error::InvalidDerivation)?;
+        let public_key = private_key.public_key();
+        let account = ComponentAddress::virtual_account_from_public_key(&public_key);
+        let owner_badge = NonFungibleGlobalId::from_public_key(&public_key);
+        let private_key = PrivateKey::Ed25519(private_key);
+
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .get_free_xrd_from_faucet()
+            .try_deposit_entire_worktop_or_refund(account, None)
+            .build();
+        let receipt = handle_manifest(
+            manifest,
+            &Some("".to_string()), // explicit empty signer public keys
+            &self.network,
+            &self.manifest,
+            self.trace,
+            false,
+            out,
+        )?;
+
+        let address_bech32_encoder = AddressBech32Encoder::new(&network);
+        if let Some(receipt) = receipt {
+            receipt
+                .expect_commit(true)
+                .outcome
+                .success_or_else(|err| TransactionFailed(err.clone()))?;
+            writeln!(out, "A new account has been created!").map_err(Error::IOError)?;
+        } else {
+            writeln!(out, "A manifest has been produced for the following account. To complete account creation, you will need to run the manifest!").map_err(Error::IOError)?;
+        }
+        writeln!(
+            out,
+            "Account component address: {}",
+            account.display(&address_bech32_encoder).to_string().green()
+        )
+        .map_err(Error::IOError)?;
+        writeln!(
+            out,
+            "Derivation path: {}",
+            derivation_path.to_string().green()
+        )
+        .map_err(Error::IOError)?;
+        writeln!(out, "Public key: {}", public_key.to_string().green()).map_err(Error::IOError)?;
+        writeln!(
+            out,
+            "Private key: {}",
+            private_key_to_string(&private_key).green()
+        )
+        .map_err(Error::IOError)?;
+        writeln!(
+            out,
+            "Owner badge: {}",
+            owner_badge
+                .to_canonical_string(&address_bech32_encoder)
+                .green()
+        )
+        .map_err(Error::IOError)?;
+
+        if self.manifest.is_none() {
+            let mut configs = get_configs()?;
+            if configs.default_account.is_none()
+                || configs.default_private_key.is_none()
+                || configs.default_owner_badge.is_none()
+            {
+                configs.default_account = Some(account);
+                configs.default_private_key = Some(private_key_to_string(&private_key));
+                configs.default_owner_badge = Some(owner_badge);
+                set_configs(&configs)?;
+
+                writeln!(
+                    out,
+                    "Set up as default account since you had none, you can change it using `set-default-account`."
+                )
+                .map_err(Error::IOError)?;
+            }
+        }
+
+        Ok(())
+    }
 }
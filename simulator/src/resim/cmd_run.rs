@@ -1,4 +1,5 @@
 use clap::Parser;
+use radix_engine::system::system_modules::debugger::{Breakpoint, DebuggerConfig};
 use radix_engine::utils::validate_call_arguments_to_native_components;
 use regex::{Captures, Regex};
 use std::env;
@@ -28,6 +29,22 @@ pub struct Run {
     /// Turn on tracing
     #[clap(short, long)]
     pub trace: bool,
+
+    /// Pause the transaction at the breakpoints, or at its start if no breakpoint is given
+    #[clap(long)]
+    pub debug: bool,
+
+    /// Break on the invocation of a blueprint, as `<package_address>:<blueprint_name>[:<function_or_method_name>]`
+    #[clap(long, multiple = true)]
+    pub break_on_invoke: Option<Vec<String>>,
+
+    /// Break on the writes to the substates of an entity, given by its address
+    #[clap(long, multiple = true)]
+    pub break_on_write: Option<Vec<String>>,
+
+    /// Break on the emission of an event with the given name
+    #[clap(long, multiple = true)]
+    pub break_on_event: Option<Vec<String>>,
 }
 
 impl Run {
@@ -62,15 +79,39 @@ impl Run {
         validate_call_arguments_to_native_components(&compiled_manifest.instructions)
             .map_err(Error::InstructionSchemaValidationError)?;
 
-        handle_manifest(
+        handle_manifest_with_debugger(
             compiled_manifest,
             &self.signing_keys,
             &self.network,
             &None,
             self.trace,
+            self.debugger_config()?,
             true,
             out,
         )
         .map(|_| ())
     }
+
+    fn debugger_config(&self) -> Result<Option<DebuggerConfig>, Error> {
+        let mut breakpoints = Vec::new();
+        for breakpoint in self.break_on_invoke.iter().flatten() {
+            breakpoints.push(parse_invocation_breakpoint(breakpoint)?);
+        }
+        for address in self.break_on_write.iter().flatten() {
+            breakpoints.push(parse_substate_write_breakpoint(address)?);
+        }
+        for event_name in self.break_on_event.iter().flatten() {
+            breakpoints.push(Breakpoint::Event(Some(event_name.clone())));
+        }
+        if !self.debug && breakpoints.is_empty() {
+            return Ok(None);
+        }
+
+        let break_on_start = breakpoints.is_empty();
+        let config = breakpoints.into_iter().fold(
+            DebuggerConfig::new(Box::new(ConsoleDebugger::new())),
+            |config, breakpoint| config.with_breakpoint(breakpoint),
+        );
+        Ok(Some(config.with_break_on_start(break_on_start)))
+    }
 }
@@ -27,7 +27,7 @@ impl SetDefaultAccount {
             }
         })?;
         configs.default_account = Some(self.component_address.0);
-        configs.default_private_key = Some(private_key.to_hex());
+        configs.default_private_key = Some(private_key_to_string(&private_key));
         configs.default_owner_badge = Some(self.owner_badge.clone().0);
         set_configs(&configs)?;
 
@@ -54,6 +54,21 @@ impl ShowConfigs {
             configs.nonce
         )
         .map_err(Error::IOError)?;
+        // Only some WASM engines can persist their prepared modules, but instrumented code is
+        // cached for all of them
+        let wasm_engine = DefaultWasmEngine::default();
+        writeln!(
+            out,
+            "{}: {}{}",
+            "WASM Module Cache".green().bold(),
+            get_wasm_module_cache_dir()?.display(),
+            if wasm_engine.supports_module_disk_cache() {
+                ""
+            } else {
+                " (instrumented code only, the WASM engine can't persist prepared modules)"
+            }
+        )
+        .map_err(Error::IOError)?;
         Ok(())
     }
 }
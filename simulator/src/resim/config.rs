@@ -30,6 +30,12 @@ pub fn get_data_dir() -> Result<PathBuf, Error> {
     Ok(path)
 }
 
+pub fn get_wasm_module_cache_dir() -> Result<PathBuf, Error> {
+    let mut path = get_data_dir()?;
+    path.push("wasm-module-cache");
+    Ok(path)
+}
+
 pub fn get_configs_path() -> Result<PathBuf, Error> {
     let mut path = get_data_dir()?;
     path.push("config");
@@ -56,11 +62,11 @@ pub fn get_default_account() -> Result<ComponentAddress, Error> {
         .ok_or(Error::NoDefaultAccount)
 }
 
-pub fn get_default_private_key() -> Result<Secp256k1PrivateKey, Error> {
+pub fn get_default_private_key() -> Result<PrivateKey, Error> {
     get_configs()?
         .default_private_key
-        .map(|v| Secp256k1PrivateKey::from_hex(&v).unwrap())
         .ok_or(Error::NoDefaultPrivateKey)
+        .and_then(|v| parse_private_key_from_str(&v))
 }
 
 pub fn get_default_owner_badge() -> Result<NonFungibleGlobalId, Error> {
@@ -0,0 +1,173 @@
+use radix_engine::system::actor::Actor;
+use radix_engine::system::system_modules::debugger::*;
+use radix_engine::types::*;
+use std::io::{self, BufRead, Write};
+use utils::ContextualDisplay;
+
+use crate::resim::*;
+
+/// Parses a breakpoint on the invocation of a blueprint, given as
+/// `<package_address>:<blueprint_name>[:<function_or_method_name>]`.
+pub fn parse_invocation_breakpoint(breakpoint: &str) -> Result<Breakpoint, Error> {
+    let mut parts = breakpoint.split(':');
+    let (Some(package_address), Some(blueprint_name)) = (parts.next(), parts.next()) else {
+        return Err(Error::InvalidBreakpoint(breakpoint.to_string()));
+    };
+    let package_address = SimulatorPackageAddress::from_str(package_address)
+        .map_err(|_| Error::InvalidBreakpoint(breakpoint.to_string()))?;
+    let ident = parts.next().map(|ident| ident.to_string());
+    if parts.next().is_some() {
+        return Err(Error::InvalidBreakpoint(breakpoint.to_string()));
+    }
+    Ok(Breakpoint::Invocation {
+        blueprint_id: BlueprintId::new(&package_address.0, blueprint_name),
+        ident,
+    })
+}
+
+/// Parses a breakpoint on the writes to the substates of a node, given by its address.
+pub fn parse_substate_write_breakpoint(address: &str) -> Result<Breakpoint, Error> {
+    AddressBech32Decoder::for_simulator()
+        .validate_and_decode(address)
+        .ok()
+        .and_then(|(_, data)| data.try_into().ok())
+        .map(|node_id| Breakpoint::SubstateWrite(NodeId(node_id)))
+        .ok_or_else(|| Error::InvalidBreakpoint(address.to_string()))
+}
+
+/// A debugger which prints where the transaction is paused, and reads commands to inspect it
+/// from the standard input.
+pub struct ConsoleDebugger {
+    encoder: AddressBech32Encoder,
+}
+
+impl ConsoleDebugger {
+    pub fn new() -> Self {
+        Self {
+            encoder: AddressBech32Encoder::for_simulator(),
+        }
+    }
+
+    fn display_node_id(&self, node_id: &NodeId) -> String {
+        self.encoder
+            .encode(node_id.as_ref())
+            .unwrap_or_else(|_| hex::encode(node_id.as_ref()))
+    }
+
+    fn display_actor(&self, actor: &Actor) -> String {
+        match actor {
+            Actor::Root => "<root>".to_string(),
+            Actor::Method(actor) => format!(
+                "{}::{} on {}",
+                actor.get_blueprint_id().blueprint_name,
+                actor.ident,
+                self.display_node_id(&actor.node_id)
+            ),
+            Actor::Function(actor) => format!(
+                "{}::{} in {}",
+                actor.blueprint_id.blueprint_name,
+                actor.ident,
+                actor.blueprint_id.package_address.display(&self.encoder)
+            ),
+            Actor::BlueprintHook(actor) => format!(
+                "{}::{:?} in {}",
+                actor.blueprint_id.blueprint_name,
+                actor.hook,
+                actor.blueprint_id.package_address.display(&self.encoder)
+            ),
+        }
+    }
+
+    fn display_event(&self, event: &DebugEvent) -> String {
+        match event {
+            DebugEvent::Invocation(actor) => format!("Invoking {}", self.display_actor(actor)),
+            DebugEvent::SubstateWrite {
+                node_id,
+                partition_num,
+                substate_key,
+                ..
+            } => format!(
+                "Writing substate {:?} of partition {} of {}",
+                substate_key,
+                partition_num.0,
+                self.display_node_id(node_id)
+            ),
+            DebugEvent::Event {
+                type_identifier, ..
+            } => format!(
+                "Emitting event {} from {:?}",
+                type_identifier.1, type_identifier.0
+            ),
+        }
+    }
+
+    fn print_context(&self, command: &str, context: &DebugContext) -> bool {
+        match command {
+            "bt" | "frames" => {
+                for (depth, actor) in context.call_frames.iter().enumerate().rev() {
+                    println!("  #{} {}", depth, self.display_actor(actor));
+                }
+            }
+            "heap" => {
+                for (node_id, blueprint_id) in &context.heap_nodes {
+                    match blueprint_id {
+                        Some(blueprint_id) => println!(
+                            "  {} ({})",
+                            self.display_node_id(node_id),
+                            blueprint_id.blueprint_name
+                        ),
+                        None => println!("  {}", self.display_node_id(node_id)),
+                    }
+                }
+            }
+            "worktop" => {
+                for (node_id, bucket) in &context.worktop {
+                    println!("  {}: {:?}", self.display_node_id(node_id), bucket);
+                }
+            }
+            "authzone" => {
+                for (node_id, proof) in &context.auth_zone {
+                    println!("  {}: {:?}", self.display_node_id(node_id), proof);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Default for ConsoleDebugger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionDebugger for ConsoleDebugger {
+    fn on_break(&mut self, event: &DebugEvent, context: &DebugContext) -> DebuggerAction {
+        println!("{}", self.display_event(event));
+
+        let stdin = io::stdin();
+        loop {
+            print!("(debug) ");
+            io::stdout().flush().ok();
+
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+                // The input is closed, so there is nobody left to debug
+                return DebuggerAction::Continue;
+            }
+            match line.trim() {
+                "c" | "continue" => return DebuggerAction::Continue,
+                "s" | "step" => return DebuggerAction::Step,
+                "" => {}
+                command => {
+                    if !self.print_context(command, context) {
+                        println!(
+                            "Commands: continue (c), step (s), frames (bt), heap, worktop, authzone"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
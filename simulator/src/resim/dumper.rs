@@ -6,7 +6,7 @@ use radix_engine::system::system_db_reader::SystemDatabaseReader;
 use radix_engine::types::*;
 use radix_engine_interface::api::ModuleId;
 use radix_engine_interface::network::NetworkDefinition;
-use radix_engine_queries::query::ResourceAccounter;
+use radix_engine_queries::query::{get_entity_metadata, HoldingBalance, HoldingsQuery};
 use radix_engine_queries::typed_substate_layout::*;
 use radix_engine_store_interface::{
     db_key_mapper::{MappedSubstateDatabase, SpreadPrefixKeyMapper},
@@ -53,7 +53,7 @@ pub fn dump_package<T: SubstateDatabase, O: std::io::Write>(
         substate.into_value().unwrap().into_latest().code.len()
     );
 
-    let metadata = get_entity_metadata(package_address.as_node_id(), substate_db);
+    let metadata = get_entity_metadata(substate_db, package_address.as_node_id());
     writeln!(output, "{}: {}", "Metadata".green().bold(), metadata.len());
     for (last, (key, value)) in metadata.iter().identify_last() {
         writeln!(output, "{} {}: {:?}", list_item_prefix(last), key, value);
@@ -72,22 +72,36 @@ pub fn dump_component<T: SubstateDatabase, O: std::io::Write>(
 
     let reader = SystemDatabaseReader::new(substate_db);
 
-    let (package_address, blueprint_name, resources) = {
+    let (package_address, blueprint_name, holdings) = {
         let object_info = reader
             .get_object_info(component_address)
             .map_err(|_| EntityDumpError::ComponentNotFound)?;
         let blueprint_id = object_info.blueprint_info.blueprint_id;
 
-        let mut accounter = ResourceAccounter::new(substate_db);
-        accounter.traverse(component_address.as_node_id().clone());
-        let resources = accounter.close();
+        let holdings = HoldingsQuery::new(substate_db)
+            .get_holdings(component_address.into())
+            .map_err(|_| EntityDumpError::ComponentNotFound)?;
 
         (
             blueprint_id.package_address,
             blueprint_id.blueprint_name,
-            resources,
+            holdings,
         )
     };
+    let fungibles = holdings
+        .iter()
+        .filter_map(|holding| match &holding.balance {
+            HoldingBalance::Fungible(amount) => Some((holding, amount)),
+            HoldingBalance::NonFungible(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let non_fungibles = holdings
+        .iter()
+        .filter_map(|holding| match &holding.balance {
+            HoldingBalance::Fungible(_) => None,
+            HoldingBalance::NonFungible(ids) => Some((holding, ids)),
+        })
+        .collect::<Vec<_>>();
 
     writeln!(
         output,
@@ -108,11 +122,10 @@ pub fn dump_component<T: SubstateDatabase, O: std::io::Write>(
         output,
         "{}: {}",
         "Owned Fungible Resources".green().bold(),
-        resources.balances.len()
+        fungibles.len()
     );
-    for (last, (resource_address, amount)) in resources.balances.iter().identify_last() {
-        let metadata = get_entity_metadata(resource_address.as_node_id(), substate_db);
-        let symbol = if let Some(MetadataValue::String(symbol)) = metadata.get("symbol") {
+    for (last, (holding, amount)) in fungibles.iter().identify_last() {
+        let symbol = if let Some(MetadataValue::String(symbol)) = holding.metadata.get("symbol") {
             symbol.as_str()
         } else {
             "?"
@@ -121,7 +134,7 @@ pub fn dump_component<T: SubstateDatabase, O: std::io::Write>(
             output,
             "{} {}: {} {}",
             list_item_prefix(last),
-            resource_address.display(&address_bech32_encoder),
+            holding.resource_address.display(&address_bech32_encoder),
             amount,
             symbol,
         );
@@ -131,11 +144,10 @@ pub fn dump_component<T: SubstateDatabase, O: std::io::Write>(
         output,
         "{}: {}",
         "Owned Non-fungibles Resources".green().bold(),
-        resources.non_fungibles.len()
+        non_fungibles.len()
     );
-    for (last, (resource_address, ids)) in resources.non_fungibles.iter().identify_last() {
-        let metadata = get_entity_metadata(resource_address.as_node_id(), substate_db);
-        let symbol = if let Some(MetadataValue::String(symbol)) = metadata.get("symbol") {
+    for (last, (holding, ids)) in non_fungibles.iter().identify_last() {
+        let symbol = if let Some(MetadataValue::String(symbol)) = holding.metadata.get("symbol") {
             symbol.as_str()
         } else {
             "?"
@@ -144,7 +156,7 @@ pub fn dump_component<T: SubstateDatabase, O: std::io::Write>(
             output,
             "{} {}: {} {}",
             list_item_prefix(last),
-            resource_address.display(&address_bech32_encoder),
+            holding.resource_address.display(&address_bech32_encoder),
             ids.len(),
             symbol,
         );
@@ -153,7 +165,7 @@ pub fn dump_component<T: SubstateDatabase, O: std::io::Write>(
         }
     }
 
-    let metadata = get_entity_metadata(component_address.as_node_id(), substate_db);
+    let metadata = get_entity_metadata(substate_db, component_address.as_node_id());
     writeln!(output, "{}: {}", "Metadata".green().bold(), metadata.len());
     for (last, (key, value)) in metadata.iter().identify_last() {
         writeln!(output, "{} {}: {:?}", list_item_prefix(last), key, value);
@@ -264,7 +276,7 @@ pub fn dump_resource_manager<T: SubstateDatabase, O: std::io::Write>(
         }
     }
 
-    let metadata = get_entity_metadata(resource_address.as_node_id(), substate_db);
+    let metadata = get_entity_metadata(substate_db, resource_address.as_node_id());
     writeln!(output, "{}: {}", "Metadata".green().bold(), metadata.len());
     for (last, (key, value)) in metadata.iter().identify_last() {
         writeln!(output, "{} {}: {:?}", list_item_prefix(last), key, value);
@@ -272,24 +284,3 @@ pub fn dump_resource_manager<T: SubstateDatabase, O: std::io::Write>(
 
     Ok(())
 }
-
-fn get_entity_metadata<T: SubstateDatabase>(
-    entity_node_id: &NodeId,
-    substate_db: &T,
-) -> IndexMap<String, MetadataValue> {
-    let reader = SystemDatabaseReader::new(substate_db);
-    reader
-        .collection_iter(
-            entity_node_id,
-            ModuleId::Metadata,
-            MetadataCollection::EntryKeyValue.collection_index(),
-        )
-        .unwrap()
-        .map(|(key, value)| {
-            let map_key = key.into_map();
-            let key = scrypto_decode::<String>(&map_key).unwrap();
-            let value = scrypto_decode::<MetadataEntryEntryPayload>(&value).unwrap();
-            (key, value.into_latest())
-        })
-        .collect()
-}
@@ -9,9 +9,11 @@ use radix_engine::vm::wasm::PrepareError;
 use radix_engine_interface::blueprints::resource::ParseNonFungibleGlobalIdError;
 use radix_engine_interface::network::ParseNetworkError;
 use radix_engine_interface::types::SchemaHash;
+use radix_engine_stores::snapshot::SnapshotError;
 use sbor::*;
 use transaction::errors::*;
 use transaction::model::PrepareError as TransactionPrepareError;
+use transaction::signing::hd::{DerivationError, MnemonicError};
 
 use crate::resim::EntityDumpError;
 use crate::utils::*;
@@ -67,6 +69,10 @@ pub enum Error {
 
     InvalidPrivateKey,
 
+    InvalidMnemonic(MnemonicError),
+
+    InvalidDerivation(DerivationError),
+
     /// e.g. if you accidentally pass in a public key in `set_default_account` command.
     GotPublicKeyExpectedPrivateKey,
 
@@ -82,5 +88,15 @@ pub enum Error {
 
     InvalidResourceSpecifier(String),
 
+    InvalidBreakpoint(String),
+
     RemoteGenericSubstitutionNotSupported,
+
+    /// The ledger checkers found the given number of violations.
+    InconsistentLedger(usize),
+
+    SnapshotError(SnapshotError),
+
+    /// A snapshot can only be imported into a ledger which has been reset.
+    LedgerNotEmpty,
 }
@@ -1,8 +1,11 @@
 mod addressing;
 mod cmd_call_function;
 mod cmd_call_method;
+mod cmd_check_ledger;
 mod cmd_export_package_definition;
+mod cmd_export_snapshot;
 mod cmd_generate_key_pair;
+mod cmd_import_snapshot;
 mod cmd_mint;
 mod cmd_new_account;
 mod cmd_new_badge_fixed;
@@ -21,14 +24,18 @@ mod cmd_show_configs;
 mod cmd_show_ledger;
 mod cmd_transfer;
 mod config;
+mod debugger;
 mod dumper;
 mod error;
 
 pub use addressing::*;
 pub use cmd_call_function::*;
 pub use cmd_call_method::*;
+pub use cmd_check_ledger::*;
 pub use cmd_export_package_definition::*;
+pub use cmd_export_snapshot::*;
 pub use cmd_generate_key_pair::*;
+pub use cmd_import_snapshot::*;
 pub use cmd_mint::*;
 pub use cmd_new_account::*;
 pub use cmd_new_badge_fixed::*;
@@ -47,6 +54,7 @@ pub use cmd_show_configs::*;
 pub use cmd_show_ledger::*;
 pub use cmd_transfer::*;
 pub use config::*;
+pub use debugger::*;
 pub use dumper::*;
 pub use error::*;
 
@@ -60,15 +68,19 @@ use radix_engine::blueprints::consensus_manager::{
 };
 use radix_engine::blueprints::models::FieldPayload;
 use radix_engine::system::bootstrap::Bootstrapper;
+use radix_engine::system::system_callback::SystemConfig;
 use radix_engine::system::system_db_reader::{
     ObjectCollectionKey, SystemDatabaseReader, SystemDatabaseWriter,
 };
+use radix_engine::system::system_modules::debugger::{DebuggerConfig, DebuggerSystem};
 use radix_engine::transaction::ExecutionConfig;
 use radix_engine::transaction::TransactionOutcome;
 use radix_engine::transaction::TransactionReceipt;
 use radix_engine::transaction::TransactionReceiptDisplayContextBuilder;
 use radix_engine::transaction::TransactionResult;
-use radix_engine::transaction::{execute_and_commit_transaction, CostingParameters};
+use radix_engine::transaction::{
+    execute_and_commit_transaction, execute_transaction_with_system, CostingParameters,
+};
 use radix_engine::types::*;
 use radix_engine::vm::wasm::*;
 use radix_engine::vm::{DefaultNativeVm, ScryptoVm, Vm};
@@ -90,7 +102,6 @@ use transaction::model::TestTransaction;
 use transaction::model::{BlobV1, BlobsV1, InstructionV1, InstructionsV1};
 use transaction::model::{SystemTransactionV1, TransactionPayload};
 use transaction::prelude::*;
-use transaction::signing::secp256k1::Secp256k1PrivateKey;
 use utils::ContextualDisplay;
 
 /// Build fast, reward everyone, and scale without friction
@@ -111,8 +122,11 @@ impl ResimCli {
 pub enum Command {
     CallFunction(CallFunction),
     CallMethod(CallMethod),
+    Check(CheckLedger),
     ExportPackageDefinition(ExportPackageDefinition),
+    ExportSnapshot(ExportSnapshot),
     GenerateKeyPair(GenerateKeyPair),
+    ImportSnapshot(ImportSnapshot),
     Mint(crate::resim::cmd_mint::Mint),
     NewAccount(NewAccount),
     NewSimpleBadge(NewSimpleBadge),
@@ -140,8 +154,11 @@ pub fn run() -> Result<(), Error> {
     match cli.command {
         Command::CallFunction(cmd) => cmd.run(&mut out),
         Command::CallMethod(cmd) => cmd.run(&mut out),
+        Command::Check(cmd) => cmd.run(&mut out),
         Command::ExportPackageDefinition(cmd) => cmd.run(&mut out),
+        Command::ExportSnapshot(cmd) => cmd.run(&mut out),
         Command::GenerateKeyPair(cmd) => cmd.run(&mut out),
+        Command::ImportSnapshot(cmd) => cmd.run(&mut out),
         Command::Mint(cmd) => cmd.run(&mut out),
         Command::NewAccount(cmd) => cmd.run(&mut out),
         Command::NewSimpleBadge(cmd) => cmd.run(&mut out).map(|_| ()),
@@ -162,6 +179,14 @@ pub fn run() -> Result<(), Error> {
     }
 }
 
+/// Creates a Scrypto VM, which persists instrumented WASM code (and the prepared WASM modules, if
+/// the WASM engine supports it) under the data directory.
+pub fn create_scrypto_vm() -> Result<ScryptoVm<DefaultWasmEngine>, Error> {
+    let module_disk_cache =
+        WasmModuleDiskCache::new(get_wasm_module_cache_dir()?).map_err(Error::IOError)?;
+    Ok(ScryptoVm::<DefaultWasmEngine>::default().with_module_disk_cache(module_disk_cache))
+}
+
 pub fn handle_system_transaction<O: std::io::Write>(
     instructions: Vec<InstructionV1>,
     blobs: Vec<Vec<u8>>,
@@ -170,7 +195,7 @@ pub fn handle_system_transaction<O: std::io::Write>(
     print_receipt: bool,
     out: &mut O,
 ) -> Result<TransactionReceipt, Error> {
-    let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
+    let scrypto_vm = create_scrypto_vm()?;
     let native_vm = DefaultNativeVm::new();
     let vm = Vm::new(&scrypto_vm, native_vm);
     let mut substate_db = RocksdbSubstateStore::standard(get_data_dir()?);
@@ -227,6 +252,30 @@ pub fn handle_manifest<O: std::io::Write>(
     trace: bool,
     print_receipt: bool,
     out: &mut O,
+) -> Result<Option<TransactionReceipt>, Error> {
+    handle_manifest_with_debugger(
+        manifest,
+        signing_keys,
+        network,
+        write_manifest,
+        trace,
+        None,
+        print_receipt,
+        out,
+    )
+}
+
+/// Same as [`handle_manifest`], but pauses the transaction at the breakpoints of the given
+/// debugger, if any.
+pub fn handle_manifest_with_debugger<O: std::io::Write>(
+    manifest: TransactionManifestV1,
+    signing_keys: &Option<String>,
+    network: &Option<String>,
+    write_manifest: &Option<PathBuf>,
+    trace: bool,
+    debugger: Option<DebuggerConfig>,
+    print_receipt: bool,
+    out: &mut O,
 ) -> Result<Option<TransactionReceipt>, Error> {
     let network = match network {
         Some(n) => NetworkDefinition::from_str(&n).map_err(Error::ParseNetworkError)?,
@@ -251,7 +300,7 @@ pub fn handle_manifest<O: std::io::Write>(
             Ok(None)
         }
         None => {
-            let scrypto_vm = ScryptoVm::<DefaultWasmEngine>::default();
+            let scrypto_vm = create_scrypto_vm()?;
             let native_vm = DefaultNativeVm::new();
             let vm = Vm::new(&scrypto_vm, native_vm);
             let mut substate_db = RocksdbSubstateStore::standard(get_data_dir()?);
@@ -271,16 +320,40 @@ pub fn handle_manifest<O: std::io::Write>(
             let nonce = get_nonce()?;
             let transaction = TestTransaction::new_from_nonce(manifest, nonce);
 
-            let receipt = execute_and_commit_transaction(
-                &mut substate_db,
-                vm,
-                &CostingParameters::default(),
-                &ExecutionConfig::for_test_transaction().with_kernel_trace(trace),
-                &transaction
-                    .prepare()
-                    .map_err(Error::TransactionPrepareError)?
-                    .get_executable(initial_proofs),
-            );
+            let prepared = transaction
+                .prepare()
+                .map_err(Error::TransactionPrepareError)?;
+            let executable = prepared.get_executable(initial_proofs);
+            let execution_config = ExecutionConfig::for_test_transaction().with_kernel_trace(trace);
+
+            let receipt = match debugger {
+                Some(debugger) => {
+                    let receipt =
+                        execute_transaction_with_system::<_, _, DebuggerSystem<SystemConfig<_>>>(
+                            &substate_db,
+                            vm,
+                            &CostingParameters::default(),
+                            &execution_config,
+                            &executable,
+                            debugger,
+                        );
+                    if let TransactionResult::Commit(commit) = &receipt.result {
+                        substate_db.commit(
+                            &commit
+                                .state_updates
+                                .create_database_updates::<SpreadPrefixKeyMapper>(),
+                        );
+                    }
+                    receipt
+                }
+                None => execute_and_commit_transaction(
+                    &mut substate_db,
+                    vm,
+                    &CostingParameters::default(),
+                    &execution_config,
+                    &executable,
+                ),
+            };
 
             if print_receipt {
                 let encoder = AddressBech32Encoder::for_simulator();
@@ -322,19 +395,46 @@ pub fn parse_private_key_from_bytes(slice: &[u8]) -> Result<Secp256k1PrivateKey,
     Secp256k1PrivateKey::from_bytes(slice).map_err(|_| Error::InvalidPrivateKey)
 }
 
-pub fn parse_private_key_from_str(key: &str) -> Result<Secp256k1PrivateKey, Error> {
-    hex::decode(key)
-        .map_err(|_| Error::InvalidPrivateKey)
-        .and_then(|bytes| parse_private_key_from_bytes(&bytes))
+/// The prefix of Ed25519 private keys, which distinguishes them from (unprefixed) Secp256k1 keys.
+pub const ED25519_PRIVATE_KEY_PREFIX: &str = "ed25519:";
+
+/// Parses a hex-encoded private key, which is an Ed25519 key if prefixed with `ed25519:` or a
+/// Secp256k1 key otherwise.
+pub fn parse_private_key_from_str(key: &str) -> Result<PrivateKey, Error> {
+    match key.strip_prefix(ED25519_PRIVATE_KEY_PREFIX) {
+        Some(key) => hex::decode(key)
+            .ok()
+            .and_then(|bytes| Ed25519PrivateKey::from_bytes(&bytes).ok())
+            .map(PrivateKey::Ed25519)
+            .ok_or(Error::InvalidPrivateKey),
+        None => hex::decode(key)
+            .map_err(|_| Error::InvalidPrivateKey)
+            .and_then(|bytes| parse_private_key_from_bytes(&bytes))
+            .map(PrivateKey::Secp256k1),
+    }
+}
+
+/// The inverse of `parse_private_key_from_str`.
+pub fn private_key_to_string(private_key: &PrivateKey) -> String {
+    match private_key {
+        PrivateKey::Secp256k1(key) => key.to_hex(),
+        PrivateKey::Ed25519(key) => {
+            format!(
+                "{}{}",
+                ED25519_PRIVATE_KEY_PREFIX,
+                hex::encode(key.to_bytes())
+            )
+        }
+    }
 }
 
-pub fn get_signing_keys(signing_keys: &Option<String>) -> Result<Vec<Secp256k1PrivateKey>, Error> {
+pub fn get_signing_keys(signing_keys: &Option<String>) -> Result<Vec<PrivateKey>, Error> {
     let private_keys = if let Some(keys) = signing_keys {
         keys.split(",")
             .map(str::trim)
             .filter(|s: &&str| !s.is_empty())
             .map(parse_private_key_from_str)
-            .collect::<Result<Vec<Secp256k1PrivateKey>, Error>>()?
+            .collect::<Result<Vec<PrivateKey>, Error>>()?
     } else {
         vec![get_default_private_key()?]
     };
@@ -567,6 +667,9 @@ mod tests {
             network: None,
             manifest: None,
             trace: false,
+            mnemonic: None,
+            passphrase: None,
+            index: None,
         };
         assert!(new_account.run(&mut out).is_ok());
         let cmd = Show { address: None };
@@ -617,6 +720,21 @@ mod tests {
 
         assert!(make_cmd(private_key.to_hex()).run(&mut out).is_ok());
         assert!(make_cmd(public_key.to_string()).run(&mut out).is_err());
+
+        let ed25519_private_key: PrivateKey = Ed25519PrivateKey::from_u64(1).unwrap().into();
+        let key_string = private_key_to_string(&ed25519_private_key);
+        assert!(key_string.starts_with(ED25519_PRIVATE_KEY_PREFIX));
+        assert_eq!(
+            parse_private_key_from_str(&key_string)
+                .unwrap()
+                .public_key(),
+            ed25519_private_key.public_key()
+        );
+        assert!(make_cmd(key_string).run(&mut out).is_ok());
+        assert_eq!(
+            get_default_private_key().unwrap().public_key(),
+            ed25519_private_key.public_key()
+        );
     }
 
     #[test]
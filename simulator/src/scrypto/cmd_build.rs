@@ -1,8 +1,13 @@
 use crate::scrypto::*;
 use crate::utils::*;
 use clap::Parser;
+use colored::*;
+use radix_engine::types::*;
+use radix_engine::vm::wasm::{WasmValidationReport, WasmValidator};
+use radix_engine_interface::blueprints::package::PackageDefinition;
 use radix_engine_interface::prelude::Level;
 use std::env::current_dir;
+use std::fs;
 use std::path::PathBuf;
 
 /// Build a Scrypto package
@@ -24,6 +29,16 @@ pub struct Build {
     /// The default is INFO.
     #[clap(long)]
     log_level: Option<Level>,
+
+    /// When passed, the built wasm is checked against all the validation rules applied when
+    /// publishing, and a report of all violations found is printed.
+    #[clap(long)]
+    analyze: bool,
+
+    /// The path to the package definition (`.rpd`) of a previous build. When passed, the blueprint
+    /// state and event schemas are compared against it, and the build fails on breaking changes.
+    #[clap(long)]
+    check_compatibility: Option<PathBuf>,
 }
 
 impl Build {
@@ -35,7 +50,230 @@ impl Build {
             self.disable_wasm_opt,
             self.log_level.unwrap_or(Level::default()),
         )
-        .map(|_| ())
         .map_err(Error::BuildError)
+        .and_then(|(wasm_path, definition_path)| {
+            if self.analyze {
+                analyze_package(wasm_path, definition_path.clone())?;
+            }
+            if let Some(previous_definition_path) = &self.check_compatibility {
+                check_compatibility(previous_definition_path.clone(), definition_path)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+fn analyze_package(wasm_path: PathBuf, definition_path: PathBuf) -> Result<(), Error> {
+    let code = fs::read(&wasm_path).map_err(|err| Error::IOErrorAtPath(err, wasm_path))?;
+    let definition = read_package_definition(definition_path)?;
+
+    let report = WasmValidator::default()
+        .analyze(&code, definition.blueprints.values())
+        .map_err(Error::InvalidPackage)?;
+    print_validation_report(&report);
+
+    match report.violations.first() {
+        Some(violation) => Err(Error::InvalidPackage(violation.violation.error.clone())),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn read_package_definition(path: PathBuf) -> Result<PackageDefinition, Error> {
+    manifest_decode(&fs::read(&path).map_err(|err| Error::IOErrorAtPath(err, path))?)
+        .map_err(Error::SborDecodeError)
+}
+
+fn check_compatibility(
+    previous_definition_path: PathBuf,
+    definition_path: PathBuf,
+) -> Result<(), Error> {
+    let old_definition = read_package_definition(previous_definition_path)?;
+    let new_definition = read_package_definition(definition_path)?;
+
+    let mut changes = vec![];
+    for (blueprint_name, old_blueprint) in &old_definition.blueprints {
+        match new_definition.blueprints.get(blueprint_name) {
+            Some(new_blueprint) => changes.extend(
+                compare_blueprint_schemas(
+                    blueprint_name,
+                    &old_blueprint.schema,
+                    &new_blueprint.schema,
+                )
+                .changes,
+            ),
+            None => changes.push(SchemaChange {
+                path: blueprint_name.clone(),
+                kind: SchemaChangeKind::Removed,
+            }),
+        }
+    }
+    for blueprint_name in new_definition.blueprints.keys() {
+        if !old_definition.blueprints.contains_key(blueprint_name) {
+            changes.push(SchemaChange {
+                path: blueprint_name.clone(),
+                kind: SchemaChangeKind::Added,
+            });
+        }
+    }
+
+    let comparison = SchemaComparison { changes };
+    print_schema_comparison(&comparison);
+    if comparison.is_breaking() {
+        Err(Error::BreakingSchemaChanges)
+    } else {
+        Ok(())
+    }
+}
+
+fn compare_blueprint_schemas(
+    blueprint_name: &str,
+    old: &BlueprintSchemaInit,
+    new: &BlueprintSchemaInit,
+) -> SchemaComparison {
+    let mut comparer = SchemaComparer::new(old.schema.v1(), new.schema.v1());
+
+    let fields = |schema: &BlueprintSchemaInit| {
+        schema
+            .state
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (format!("field_{}", index), field.field.clone()))
+            .collect::<IndexMap<_, _>>()
+    };
+    let collections = |schema: &BlueprintSchemaInit| {
+        let mut type_refs = index_map_new();
+        for (index, collection) in schema.state.collections.iter().enumerate() {
+            let (BlueprintCollectionSchema::KeyValueStore(key_value_schema)
+            | BlueprintCollectionSchema::Index(key_value_schema)
+            | BlueprintCollectionSchema::SortedIndex(key_value_schema)) = collection;
+            type_refs.insert(
+                format!("collection_{}{{key}}", index),
+                key_value_schema.key.clone(),
+            );
+            type_refs.insert(
+                format!("collection_{}{{value}}", index),
+                key_value_schema.value.clone(),
+            );
+        }
+        type_refs
+    };
+    let events = |schema: &BlueprintSchemaInit| {
+        schema
+            .events
+            .event_schema
+            .iter()
+            .map(|(name, type_ref)| (format!("events.{}", name), type_ref.clone()))
+            .collect::<IndexMap<_, _>>()
+    };
+
+    compare_type_refs(&mut comparer, blueprint_name, &fields(old), &fields(new));
+    compare_type_refs(
+        &mut comparer,
+        blueprint_name,
+        &collections(old),
+        &collections(new),
+    );
+    compare_type_refs(&mut comparer, blueprint_name, &events(old), &events(new));
+
+    comparer.finish()
+}
+
+fn compare_type_refs(
+    comparer: &mut SchemaComparer<ScryptoCustomSchema>,
+    blueprint_name: &str,
+    old: &IndexMap<String, TypeRef<LocalTypeId>>,
+    new: &IndexMap<String, TypeRef<LocalTypeId>>,
+) {
+    for (name, old_type_ref) in old {
+        let path = format!("{}.{}", blueprint_name, name);
+        match (old_type_ref, new.get(name)) {
+            (TypeRef::Static(old_type_id), Some(TypeRef::Static(new_type_id))) => {
+                comparer.compare_root(&path, *old_type_id, *new_type_id)
+            }
+            (TypeRef::Generic(old_index), Some(TypeRef::Generic(new_index)))
+                if old_index == new_index => {}
+            (old_type_ref, Some(new_type_ref)) => comparer.record(
+                &path,
+                SchemaChangeKind::TypeKindChanged {
+                    old: format!("{:?}", old_type_ref),
+                    new: format!("{:?}", new_type_ref),
+                },
+            ),
+            (_, None) => comparer.record(&path, SchemaChangeKind::Removed),
+        }
+    }
+    for name in new.keys() {
+        if !old.contains_key(name) {
+            comparer.record(
+                &format!("{}.{}", blueprint_name, name),
+                SchemaChangeKind::Added,
+            );
+        }
+    }
+}
+
+fn print_schema_comparison(comparison: &SchemaComparison) {
+    let colored = |compatibility: SchemaCompatibility| match compatibility {
+        SchemaCompatibility::Compatible => "compatible".green(),
+        SchemaCompatibility::Additive => "additive".yellow(),
+        SchemaCompatibility::Breaking => "breaking".red(),
+    };
+
+    println!("{}", "Schema compatibility report".bold());
+    for change in &comparison.changes {
+        println!(
+            "  - [{}] {}: {:?}",
+            colored(change.compatibility()),
+            change.path,
+            change.kind
+        );
+    }
+    println!("Result: {}", colored(comparison.compatibility()));
+}
+
+fn print_validation_report(report: &WasmValidationReport) {
+    let function = |index: u32, name: &Option<String>| match name {
+        Some(name) => format!("#{} ({})", index, name),
+        None => format!("#{}", index),
+    };
+
+    println!("{}", "WASM validation report".bold());
+    match report.instrumented_code_size {
+        Some(instrumented_code_size) => println!(
+            "Code size: {} bytes ({} bytes instrumented)",
+            report.code_size, instrumented_code_size
+        ),
+        None => println!("Code size: {} bytes", report.code_size),
+    }
+    println!(
+        "Estimated instantiation cost: {} cost units",
+        report.instantiation_cost()
+    );
+
+    if report.violations.is_empty() {
+        println!("Violations: {}", "none".green());
+    } else {
+        println!("Violations: {}", report.violations.len().to_string().red());
+        for violation in &report.violations {
+            match violation.violation.function_index {
+                Some(index) => println!(
+                    "  - {:?} in function {}",
+                    violation.violation.error,
+                    function(index, &violation.function_name)
+                ),
+                None => println!("  - {:?}", violation.violation.error),
+            }
+        }
+    }
+
+    println!("Largest functions:");
+    for hotspot in &report.size_hotspots {
+        println!(
+            "  - {}: {} bytes ({:.1}%)",
+            function(hotspot.function_index, &hotspot.function_name),
+            hotspot.size,
+            hotspot.size as f64 * 100.0 / report.code_size as f64
+        );
     }
 }
@@ -0,0 +1,50 @@
+use clap::Parser;
+use radix_engine::types::*;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::scrypto::*;
+
+/// Export the blueprint schemas of a package as JSON Schema or TypeScript definitions
+#[derive(Parser, Debug)]
+pub struct ExportSchema {
+    /// The package definition (`.rpd`) written by `scrypto build`
+    definition: PathBuf,
+
+    /// The output directory, to which one file is written per blueprint
+    output: PathBuf,
+
+    /// Export TypeScript definitions (`.d.ts`) instead of JSON Schema documents
+    #[clap(long)]
+    typescript: bool,
+}
+
+impl ExportSchema {
+    pub fn run(&self) -> Result<(), Error> {
+        let definition = read_package_definition(self.definition.clone())?;
+        fs::create_dir_all(&self.output)
+            .map_err(|err| Error::IOErrorAtPath(err, self.output.clone()))?;
+
+        for (blueprint_name, blueprint) in &definition.blueprints {
+            let (content, path) = if self.typescript {
+                (
+                    blueprint.schema.export_typescript(),
+                    self.output.join(format!("{}.d.ts", blueprint_name)),
+                )
+            } else {
+                (
+                    blueprint.schema.export_json_schema(),
+                    self.output.join(format!("{}.schema.json", blueprint_name)),
+                )
+            };
+            let content = content.map_err(Error::SchemaExportError)?;
+            fs::write(&path, content).map_err(|err| Error::IOErrorAtPath(err, path.clone()))?;
+            println!(
+                "Schema of blueprint {} exported to {}",
+                blueprint_name,
+                path.display()
+            );
+        }
+        Ok(())
+    }
+}
@@ -14,15 +14,24 @@ pub struct Test {
     /// The package directory
     #[clap(long)]
     path: Option<PathBuf>,
+
+    /// Reports the line coverage of the Scrypto code of the package, as an `lcov` tracefile
+    /// in the `coverage` directory of the target directory
+    #[clap(long)]
+    coverage: bool,
 }
 
 impl Test {
     pub fn run(&self) -> Result<(), Error> {
-        test_package(
+        let coverage_report = test_package(
             self.path.clone().unwrap_or(current_dir().unwrap()),
             self.arguments.clone(),
+            self.coverage,
         )
-        .map(|_| ())
-        .map_err(Error::TestError)
+        .map_err(Error::TestError)?;
+        if let Some(coverage_report) = coverage_report {
+            println!("Coverage report written to {}", coverage_report.display());
+        }
+        Ok(())
     }
 }
@@ -1,4 +1,9 @@
 use std::io;
+use std::path::PathBuf;
+
+use radix_engine::vm::wasm::PrepareError;
+use sbor::representations::SchemaExportError;
+use sbor::DecodeError;
 
 use crate::utils::*;
 
@@ -6,6 +11,10 @@ use crate::utils::*;
 pub enum Error {
     IOError(io::Error),
 
+    IOErrorAtPath(io::Error, PathBuf),
+
+    SborDecodeError(DecodeError),
+
     BuildError(BuildError),
 
     TestError(TestError),
@@ -13,4 +22,10 @@ pub enum Error {
     FormatError(FormatError),
 
     PackageAlreadyExists,
+
+    InvalidPackage(PrepareError),
+
+    BreakingSchemaChanges,
+
+    SchemaExportError(SchemaExportError),
 }
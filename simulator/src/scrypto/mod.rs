@@ -1,10 +1,12 @@
 mod cmd_build;
+mod cmd_export_schema;
 mod cmd_fmt;
 mod cmd_new_package;
 mod cmd_test;
 mod error;
 
 pub use cmd_build::*;
+pub use cmd_export_schema::*;
 pub use cmd_fmt::*;
 pub use cmd_new_package::*;
 pub use cmd_test::*;
@@ -23,6 +25,7 @@ pub struct ScryptoCli {
 #[derive(Subcommand, Debug)]
 pub enum Command {
     Build(Build),
+    ExportSchema(ExportSchema),
     Fmt(Fmt),
     NewPackage(NewPackage),
     Test(Test),
@@ -33,6 +36,7 @@ pub fn run() -> Result<(), Error> {
 
     match cli.command {
         Command::Build(cmd) => cmd.run(),
+        Command::ExportSchema(cmd) => cmd.run(),
         Command::Fmt(cmd) => cmd.run(),
         Command::NewPackage(cmd) => cmd.run(),
         Command::Test(cmd) => cmd.run(),
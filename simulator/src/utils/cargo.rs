@@ -216,8 +216,20 @@ pub fn build_package<P: AsRef<Path>>(
     Ok((wasm_path, definition_path))
 }
 
+/// The environment variable through which the test runner of `scrypto-unit` is told where
+/// to write its coverage reports.
+const SCRYPTO_COVERAGE_DIR_ENV_VAR: &str = "SCRYPTO_COVERAGE_DIR";
+
 /// Runs tests within a package.
-pub fn test_package<P: AsRef<Path>, I, S>(path: P, args: I) -> Result<(), TestError>
+///
+/// With `coverage`, the tests are run with the `scrypto-unit/coverage` feature, and the line
+/// coverage of the Scrypto code of the package is written as an `lcov` tracefile to
+/// `<target>/coverage/lcov.info`, whose path is returned.
+pub fn test_package<P: AsRef<Path>, I, S>(
+    path: P,
+    args: I,
+    coverage: bool,
+) -> Result<Option<PathBuf>, TestError>
 where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
@@ -227,11 +239,31 @@ where
     let mut cargo = path.as_ref().to_owned();
     cargo.push("Cargo.toml");
     if cargo.exists() {
-        let status = Command::new("cargo")
+        let mut command = Command::new("cargo");
+        command
             .arg("test")
             .arg("--release")
             .arg("--manifest-path")
-            .arg(cargo.to_str().unwrap())
+            .arg(cargo.to_str().unwrap());
+
+        let coverage_dir = if coverage {
+            let mut coverage_dir =
+                PathBuf::from(get_default_target_directory(&cargo).map_err(TestError::BuildError)?);
+            coverage_dir.push("coverage");
+            let raw_coverage_dir = coverage_dir.join("raw");
+            if raw_coverage_dir.exists() {
+                fs::remove_dir_all(&raw_coverage_dir).map_err(TestError::IOError)?;
+            }
+            command
+                .arg("--features")
+                .arg("scrypto-unit/coverage")
+                .env(SCRYPTO_COVERAGE_DIR_ENV_VAR, &raw_coverage_dir);
+            Some(coverage_dir)
+        } else {
+            None
+        };
+
+        let status = command
             .arg("--")
             .args(args)
             .status()
@@ -239,12 +271,71 @@ where
         if !status.success() {
             return Err(TestError::CargoFailure(status));
         }
-        Ok(())
+
+        match coverage_dir {
+            Some(coverage_dir) => {
+                let package_dir = fs::canonicalize(path.as_ref()).map_err(TestError::IOError)?;
+                merge_coverage_reports(&coverage_dir, &package_dir)
+                    .map(Some)
+                    .map_err(TestError::IOError)
+            }
+            None => Ok(None),
+        }
     } else {
         Err(TestError::NotCargoPackage)
     }
 }
 
+/// Merges the `lcov` reports written by the test processes, summing up the line hits, and keeps
+/// only the source files of the package (not those of its dependencies).
+fn merge_coverage_reports(coverage_dir: &Path, package_dir: &Path) -> Result<PathBuf, io::Error> {
+    let mut line_hits: BTreeMap<String, BTreeMap<u64, u64>> = BTreeMap::new();
+    let raw_coverage_dir = coverage_dir.join("raw");
+    if raw_coverage_dir.exists() {
+        for entry in fs::read_dir(&raw_coverage_dir)? {
+            let report = fs::read_to_string(entry?.path())?;
+            let mut file = None;
+            for line in report.lines() {
+                if let Some(source_file) = line.strip_prefix("SF:") {
+                    file = Path::new(source_file)
+                        .starts_with(package_dir)
+                        .then(|| source_file.to_string());
+                } else if let (Some(file), Some(data)) = (&file, line.strip_prefix("DA:")) {
+                    if let Some((line, hits)) = data.split_once(',') {
+                        if let (Ok(line), Ok(hits)) = (line.parse::<u64>(), hits.parse::<u64>()) {
+                            let total = line_hits
+                                .entry(file.clone())
+                                .or_default()
+                                .entry(line)
+                                .or_default();
+                            *total = total.saturating_add(hits);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut lcov = String::from("TN:\n");
+    for (file, lines) in &line_hits {
+        lcov.push_str(&format!("SF:{}\n", file));
+        for (line, hits) in lines {
+            lcov.push_str(&format!("DA:{},{}\n", line, hits));
+        }
+        lcov.push_str(&format!("LF:{}\n", lines.len()));
+        lcov.push_str(&format!(
+            "LH:{}\n",
+            lines.values().filter(|hits| **hits > 0).count()
+        ));
+        lcov.push_str("end_of_record\n");
+    }
+
+    fs::create_dir_all(coverage_dir)?;
+    let path = coverage_dir.join("lcov.info");
+    fs::write(&path, lcov)?;
+    Ok(path)
+}
+
 /// Format a package.
 pub fn fmt_package<P: AsRef<Path>>(path: P, check: bool, quiet: bool) -> Result<(), FormatError> {
     let mut cargo = path.as_ref().to_owned();
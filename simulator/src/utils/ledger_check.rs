@@ -0,0 +1,297 @@
+use colored::*;
+use radix_engine::system::checkers::*;
+use radix_engine::system::system_db_reader::SystemDatabaseReader;
+use radix_engine::types::*;
+use radix_engine_interface::api::ModuleId;
+use radix_engine_interface::blueprints::package::{BlueprintPayloadIdentifier, KeyOrValue};
+use radix_engine_store_interface::interface::{ListableSubstateDatabase, SubstateDatabase};
+use std::io;
+use std::time::{Duration, Instant};
+
+/// The number of partitions between two progress lines of the kernel check.
+const KERNEL_CHECK_PROGRESS_INTERVAL: usize = 100_000;
+/// The number of nodes between two progress lines of the system check.
+const SYSTEM_CHECK_PROGRESS_INTERVAL: usize = 10_000;
+
+/// Runs all ledger checkers, including the given application checker, over the given database.
+///
+/// The progress of the checkers is written to the output while they run, followed by a report of
+/// all violations found, with the addresses of the entities and the typed contents of the
+/// substates involved.
+pub fn check_ledger<S, A, O>(
+    substate_db: &S,
+    application_checker: A,
+    network: &NetworkDefinition,
+    out: &mut O,
+) -> Result<LedgerCheckReport<A::ApplicationCheckerResults>, io::Error>
+where
+    S: SubstateDatabase + ListableSubstateDatabase,
+    A: ApplicationChecker,
+    O: io::Write,
+{
+    let start = Instant::now();
+    let mut progress_result = Ok(());
+    let report = LedgerDatabaseChecker::with_application_checker(application_checker).check_db(
+        substate_db,
+        |progress| {
+            let line = match progress {
+                LedgerCheckProgress::Kernel { partition_count }
+                    if partition_count % KERNEL_CHECK_PROGRESS_INTERVAL == 0 =>
+                {
+                    format!("Kernel check: {} partitions", partition_count)
+                }
+                LedgerCheckProgress::System(progress)
+                    if progress.node_count % SYSTEM_CHECK_PROGRESS_INTERVAL == 0 =>
+                {
+                    format!(
+                        "System check: {} nodes, {} partitions, {} substates",
+                        progress.node_count, progress.partition_count, progress.substate_count
+                    )
+                }
+                _ => return,
+            };
+            if progress_result.is_ok() {
+                progress_result = writeln!(out, "{}, {}", line, format_duration(start.elapsed()));
+            }
+        },
+    );
+    progress_result?;
+
+    if let Some(results) = &report.system_results {
+        writeln!(
+            out,
+            "Checked {} nodes, {} partitions and {} substates in {}",
+            results.node_counts.node_count,
+            results.partition_count,
+            results.substate_count,
+            format_duration(start.elapsed())
+        )?;
+    }
+    if let Some(results) = &report.application_results {
+        writeln!(
+            out,
+            "{}: {:#?}",
+            "Application checker".green().bold(),
+            results
+        )?;
+    }
+
+    if report.is_consistent() {
+        writeln!(out, "{}", "No violations found".green().bold())?;
+        return Ok(report);
+    }
+
+    let address_bech32_encoder = AddressBech32Encoder::new(network);
+    let reader = SystemDatabaseReader::new(substate_db);
+    writeln!(
+        out,
+        "{}",
+        format!("Found {} violations:", report.violations.len())
+            .red()
+            .bold()
+    )?;
+    for (i, violation) in report.violations.iter().enumerate() {
+        writeln!(out, "{} {:?}", format!("[{}]", i + 1).red(), violation)?;
+        if let Some(node_id) = violation_node_id(violation) {
+            writeln!(
+                out,
+                "    {}: {}",
+                "Entity".bold(),
+                node_id.display(&address_bech32_encoder)
+            )?;
+        }
+        if let Some(location) = violation_location(violation) {
+            write_location(out, &reader, location, &address_bech32_encoder)?;
+        }
+    }
+
+    Ok(report)
+}
+
+fn violation_node_id(violation: &LedgerCheckViolation) -> Option<NodeId> {
+    match violation {
+        LedgerCheckViolation::Kernel(error) => match error {
+            KernelDatabaseCheckError::DecodeError(_) => None,
+            KernelDatabaseCheckError::MultipleOwnersOfNode(node_id)
+            | KernelDatabaseCheckError::NonGlobalReference(node_id)
+            | KernelDatabaseCheckError::NoOwnerForNonGlobalNode(node_id)
+            | KernelDatabaseCheckError::ZeroPartitionCount(node_id) => Some(*node_id),
+        },
+        LedgerCheckViolation::System { node_id, .. } => Some(*node_id),
+        LedgerCheckViolation::Resource(error) => match error {
+            ResourceDatabaseCheckerError::NegativeVaultBalance { vault_id, .. }
+            | ResourceDatabaseCheckerError::NonFungibleVaultAmountMismatch { vault_id, .. }
+            | ResourceDatabaseCheckerError::NonFungibleVaultWithoutAmount(vault_id) => {
+                Some(*vault_id)
+            }
+            ResourceDatabaseCheckerError::TotalSupplyMismatch {
+                resource_address, ..
+            } => Some(resource_address.into_node_id()),
+            ResourceDatabaseCheckerError::ValidatorSetNotSortedByStake
+            | ResourceDatabaseCheckerError::ValidatorCountMismatch { .. } => {
+                Some(CONSENSUS_MANAGER.into_node_id())
+            }
+        },
+        LedgerCheckViolation::RoleAssignment(_)
+        | LedgerCheckViolation::PackageRoyalty(_)
+        | LedgerCheckViolation::ComponentRoyalty(_) => None,
+    }
+}
+
+fn violation_location(violation: &LedgerCheckViolation) -> Option<&ErrorLocation> {
+    match violation {
+        LedgerCheckViolation::RoleAssignment(error) => Some(&error.location),
+        LedgerCheckViolation::PackageRoyalty(error) => Some(&error.location),
+        LedgerCheckViolation::ComponentRoyalty(error) => Some(&error.location),
+        LedgerCheckViolation::Kernel(_)
+        | LedgerCheckViolation::System { .. }
+        | LedgerCheckViolation::Resource(_) => None,
+    }
+}
+
+fn write_location<S: SubstateDatabase, O: io::Write>(
+    out: &mut O,
+    reader: &SystemDatabaseReader<S>,
+    location: &ErrorLocation,
+    address_bech32_encoder: &AddressBech32Encoder,
+) -> Result<(), io::Error> {
+    match location {
+        ErrorLocation::Field {
+            info,
+            node_id,
+            module_id,
+            field_index,
+            value,
+        } => {
+            writeln!(
+                out,
+                "    {}: {}",
+                "Entity".bold(),
+                node_id.display(address_bech32_encoder)
+            )?;
+            writeln!(
+                out,
+                "    {}: field {} of the {:?} module of {}",
+                "Substate".bold(),
+                field_index,
+                module_id,
+                info.blueprint_id.display(address_bech32_encoder)
+            )?;
+            let value = format_payload(
+                reader,
+                node_id,
+                *module_id,
+                &[BlueprintPayloadIdentifier::Field(*field_index)],
+                value,
+                address_bech32_encoder,
+            );
+            writeln!(out, "    {}: {}", "Value".bold(), value)
+        }
+        ErrorLocation::CollectionEntry {
+            info,
+            node_id,
+            module_id,
+            collection_index,
+            key,
+            value,
+        } => {
+            writeln!(
+                out,
+                "    {}: {}",
+                "Entity".bold(),
+                node_id.display(address_bech32_encoder)
+            )?;
+            writeln!(
+                out,
+                "    {}: entry of collection {} of the {:?} module of {}",
+                "Substate".bold(),
+                collection_index,
+                module_id,
+                info.blueprint_id.display(address_bech32_encoder)
+            )?;
+            for (name, payload, key_or_value) in [
+                ("Key", key, KeyOrValue::Key),
+                ("Value", value, KeyOrValue::Value),
+            ] {
+                let payload = format_payload(
+                    reader,
+                    node_id,
+                    *module_id,
+                    &[
+                        BlueprintPayloadIdentifier::KeyValueEntry(
+                            *collection_index,
+                            key_or_value.clone(),
+                        ),
+                        BlueprintPayloadIdentifier::IndexEntry(
+                            *collection_index,
+                            key_or_value.clone(),
+                        ),
+                        BlueprintPayloadIdentifier::SortedIndexEntry(
+                            *collection_index,
+                            key_or_value,
+                        ),
+                    ],
+                    payload,
+                    address_bech32_encoder,
+                );
+                writeln!(out, "    {}: {}", name.bold(), payload)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Formats the payload with the schema of the first of the given payload identifiers which exists
+/// in the blueprint of the given module, or without a schema if none of them does.
+fn format_payload<S: SubstateDatabase>(
+    reader: &SystemDatabaseReader<S>,
+    node_id: &NodeId,
+    module_id: ModuleId,
+    payload_identifiers: &[BlueprintPayloadIdentifier],
+    payload: &[u8],
+    address_bech32_encoder: &AddressBech32Encoder,
+) -> String {
+    let Some(payload) = ScryptoRawPayload::new_from_valid_slice_with_checks(payload) else {
+        return hex::encode(payload);
+    };
+    let custom_context =
+        ScryptoValueDisplayContext::with_optional_bech32(Some(address_bech32_encoder));
+    let print_mode = PrintMode::MultiLine {
+        indent_size: 2,
+        base_indent: 4,
+        first_line_indent: 0,
+    };
+    let schema = reader
+        .get_blueprint_type_target(node_id, module_id)
+        .ok()
+        .and_then(|type_target| {
+            payload_identifiers.iter().find_map(|payload_identifier| {
+                reader
+                    .get_blueprint_payload_schema(&type_target, payload_identifier)
+                    .ok()
+            })
+        });
+    match schema {
+        Some(schema) => payload.to_string(ValueDisplayParameters::Annotated {
+            display_mode: DisplayMode::RustLike,
+            print_mode,
+            custom_context,
+            schema: schema.schema.v1(),
+            type_id: schema.type_id,
+            depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+        }),
+        None => payload.to_string(ValueDisplayParameters::Schemaless {
+            display_mode: DisplayMode::RustLike,
+            print_mode,
+            custom_context,
+            depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+        }),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs() % 60;
+    let minutes = (duration.as_secs() / 60) % 60;
+    let hours = (duration.as_secs() / 60) / 60;
+    format!("{:0>2}:{:0>2}:{:0>2}", hours, minutes, seconds)
+}
@@ -12,6 +12,7 @@ $resim reset
 temp=`$resim new-account | awk '/Account component address:/ {print $NF}'`
 account=`echo $temp | cut -d " " -f1`
 account2=`$resim new-account | awk '/Account component address:/ {print $NF}'`
+account3=`$resim new-account --mnemonic "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about" --index 1 | awk '/Account component address:/ {print $NF}'`
 
 # Dump each entity in the ledger
 addresses=`$resim show-ledger | grep -e "─ " | awk '{print $2}'`
@@ -125,3 +126,12 @@ token_address=`$resim new-token-mutable $non_fungible_global_id | awk '/Resource
 
 # Test - mint and transfer (Mintable that requires a `NonFungibleGlobalId`)
 $resim mint 777 $token_address --proofs "$non_fungible_global_id"
+
+# Test - check ledger invariants
+$resim check
+
+# Test - export the ledger to a snapshot and import it again
+$resim export-snapshot target/ledger.snapshot
+$resim reset
+$resim import-snapshot target/ledger.snapshot
+$resim check
@@ -50,6 +50,7 @@ pub enum TransactionValidationError {
     IdValidationError(ManifestIdValidationError),
     CallDataValidationError(CallDataValidationError),
     InvalidMessage(InvalidMessageError),
+    SubintentStructureError(SubintentStructureError),
 }
 
 impl From<PrepareError> for TransactionValidationError {
@@ -64,6 +65,12 @@ impl From<EncodeError> for TransactionValidationError {
     }
 }
 
+impl From<SubintentStructureError> for TransactionValidationError {
+    fn from(value: SubintentStructureError) -> Self {
+        Self::SubintentStructureError(value)
+    }
+}
+
 impl From<InvalidMessageError> for TransactionValidationError {
     fn from(value: InvalidMessageError) -> Self {
         Self::InvalidMessage(value)
@@ -97,3 +104,23 @@ pub enum InvalidMessageError {
         curve_type: CurveType,
     },
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubintentStructureError {
+    DuplicateSubintent(SubintentHash),
+    ChildSubintentNotIncludedInTransaction(SubintentHash),
+    SubintentHasMultipleParents(SubintentHash),
+    SubintentIsNotReachableFromRoot(SubintentHash),
+    SubintentExceedsMaxDepth(SubintentHash),
+    MismatchingSubintentSignatureCount {
+        subintents: usize,
+        signature_groups: usize,
+    },
+    YieldToChildIndexOutOfBounds {
+        child_index: u32,
+        children: usize,
+    },
+    YieldToParentInRootIntent,
+    SubintentDoesNotEndWithYieldToParent(SubintentHash),
+    NoValidEpochRangeAcrossAllIntents,
+}
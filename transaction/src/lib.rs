@@ -20,6 +20,7 @@ pub mod prelude {
     pub use crate::builder::*;
     pub use crate::model::*;
     pub use crate::signing::ed25519::*;
+    pub use crate::signing::hd::*;
     pub use crate::signing::secp256k1::*;
     pub use crate::signing::{PrivateKey, Signer};
 }
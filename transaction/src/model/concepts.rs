@@ -27,6 +27,17 @@ pub trait HasNotarizedTransactionHash {
     fn notarized_transaction_hash(&self) -> NotarizedTransactionHash;
 }
 
+define_raw_transaction_payload!(RawSubintent);
+define_wrapped_hash!(
+    /// A hash of a subintent.
+    /// The engine guarantees each subintent hash can only be committed once.
+    SubintentHash
+);
+
+pub trait HasSubintentHash {
+    fn subintent_hash(&self) -> SubintentHash;
+}
+
 define_raw_transaction_payload!(RawSystemTransaction);
 define_wrapped_hash!(SystemTransactionHash);
 
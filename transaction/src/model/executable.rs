@@ -21,6 +21,30 @@ pub struct ExecutionContext {
     pub num_of_signature_validations: usize,
     pub auth_zone_params: AuthZoneParams,
     pub costing_parameters: TransactionCostingParameters,
+    pub subintent_params: SubintentParams,
+}
+
+/// The subintents of a transaction, and how they relate to each other.
+/// Transaction models without subintents use the default (empty) params.
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor, Default)]
+pub struct SubintentParams {
+    /// The indices in `subintents` of the children of the root intent, in the
+    /// order they are referenced by the root intent's yield instructions.
+    pub root_children: Vec<u32>,
+    pub subintents: Vec<ExecutableSubintent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
+pub struct ExecutableSubintent {
+    /// Should be checked with transaction tracker.
+    pub subintent_hash: Hash,
+    pub expiry_epoch: Epoch,
+    pub encoded_instructions: Vec<u8>,
+    pub blobs: IndexMap<Hash, Vec<u8>>,
+    /// The indices in `SubintentParams::subintents` of the children of this subintent
+    pub children: Vec<u32>,
+    /// The signature proofs which are available in the subintent's auth zone
+    pub initial_proofs: BTreeSet<NonFungibleGlobalId>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, ScryptoSbor)]
@@ -101,6 +125,11 @@ impl<'a> Executable<'a> {
         for resource in &context.auth_zone_params.virtual_resources {
             references.insert(resource.clone().into());
         }
+        for subintent in &context.subintent_params.subintents {
+            for proof in &subintent.initial_proofs {
+                references.insert(proof.resource_address().clone().into());
+            }
+        }
         for preallocated_address in &context.pre_allocated_addresses {
             references.insert(
                 preallocated_address
@@ -171,4 +200,8 @@ impl<'a> Executable<'a> {
     pub fn num_of_signature_validations(&self) -> usize {
         self.context.num_of_signature_validations
     }
+
+    pub fn subintent_params(&self) -> &SubintentParams {
+        &self.context.subintent_params
+    }
 }
@@ -50,6 +50,7 @@ macro_rules! impl_contextual_display {
 impl_contextual_display![
     IntentHash,
     SignedIntentHash,
+    SubintentHash,
     NotarizedTransactionHash,
     SystemTransactionHash
 ];
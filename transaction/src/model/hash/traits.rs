@@ -20,6 +20,12 @@ impl HashHasHrp for SignedIntentHash {
     }
 }
 
+impl HashHasHrp for SubintentHash {
+    fn hrp<'h>(hrp_set: &'h HrpSet) -> &'h str {
+        &hrp_set.subintent
+    }
+}
+
 impl HashHasHrp for NotarizedTransactionHash {
     fn hrp<'h>(hrp_set: &'h HrpSet) -> &'h str {
         &hrp_set.notarized_transaction
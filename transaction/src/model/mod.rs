@@ -3,6 +3,7 @@ mod executable;
 mod hash;
 mod preparation;
 mod v1;
+mod v2;
 mod versioned;
 
 pub use concepts::*;
@@ -10,6 +11,7 @@ pub use executable::*;
 pub use hash::*;
 pub use preparation::*;
 pub use v1::*;
+pub use v2::*;
 pub use versioned::*;
 
 #[cfg(test)]
@@ -229,7 +231,8 @@ Enum<3u8>(
                     costing_parameters: TransactionCostingParameters {
                         tip_percentage: 4,
                         free_credit_in_xrd: dec!(0)
-                    }
+                    },
+                    subintent_params: SubintentParams::default(),
                 }
             }
         );
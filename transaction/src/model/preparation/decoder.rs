@@ -5,6 +5,7 @@ use sbor::*;
 pub enum ValueType {
     Blob,
     Attachment,
+    Subintent,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -54,6 +55,12 @@ impl<'a> TransactionDecoder<'a> {
         Ok(())
     }
 
+    /// For use when the struct is inside an array, so its value kind is in the array header
+    pub fn read_inner_struct_header(&mut self, length: usize) -> Result<(), PrepareError> {
+        self.0.read_and_check_size(length)?;
+        Ok(())
+    }
+
     pub fn read_enum_header(&mut self) -> Result<(u8, usize), PrepareError> {
         self.0.read_and_check_value_kind(ValueKind::Enum)?;
         let discriminator = self.0.read_discriminator()?;
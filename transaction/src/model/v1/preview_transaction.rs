@@ -77,6 +77,7 @@ impl ValidatedPreviewIntent {
                 },
                 costing_parameters: fee_payment,
                 pre_allocated_addresses: vec![],
+                subintent_params: SubintentParams::default(),
             },
         )
     }
@@ -120,6 +120,7 @@ impl PreparedSystemTransactionV1 {
                     free_credit_in_xrd: Decimal::ZERO,
                 },
                 pre_allocated_addresses: self.pre_allocated_addresses.inner.clone(),
+                subintent_params: SubintentParams::default(),
             },
         )
     }
@@ -74,6 +74,7 @@ impl PreparedTestTransaction {
                     free_credit_in_xrd: Decimal::ZERO,
                 },
                 pre_allocated_addresses: vec![],
+                subintent_params: SubintentParams::default(),
             },
         )
     }
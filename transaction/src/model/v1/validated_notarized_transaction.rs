@@ -61,6 +61,7 @@ impl ValidatedNotarizedTransactionV1 {
                     free_credit_in_xrd,
                 },
                 pre_allocated_addresses: vec![],
+                subintent_params: SubintentParams::default(),
             },
         )
     }
@@ -0,0 +1,18 @@
+use crate::internal_prelude::*;
+
+/// A reference to a child subintent, by its hash.
+/// The subintent itself is included in the transaction's `NonRootSubintentsV2`.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+#[sbor(transparent)]
+pub struct ChildSubintent {
+    pub hash: SubintentHash,
+}
+
+/// The children of an intent, in the order used by `InstructionV2::YieldToChild`.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+#[sbor(transparent)]
+pub struct ChildIntentsV2 {
+    pub children: Vec<ChildSubintent>,
+}
+
+pub type PreparedChildIntentsV2 = SummarizedRawFullBody<ChildIntentsV2>;
@@ -0,0 +1,165 @@
+use crate::internal_prelude::*;
+use radix_engine_interface::data::manifest::ManifestValue;
+use sbor::{Decoder, Encoder};
+
+/*
+=================================================================================
+NOTE: The V2 instruction set is a strict superset of the V1 instruction set.
+=================================================================================
+Every V1 instruction is encoded identically as a V2 instruction, with the same
+discriminator and fields. This means that any encoded `Vec<InstructionV1>` is
+also a valid encoded `Vec<InstructionV2>`, which allows the transaction
+processor to decode all transaction versions as `InstructionV2`.
+
+The new instructions are for interacting with subintents:
+* `YIELD_TO_CHILD` passes control (and optionally some buckets) to the given
+  child subintent of the current intent, which then runs until it yields back.
+* `YIELD_TO_PARENT` passes control (and optionally some buckets) back to the
+  parent intent. Every subintent must end with a `YIELD_TO_PARENT`.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstructionV2 {
+    /// Any instruction from the V1 instruction set.
+    V1(InstructionV1),
+
+    /// Yields to the child subintent at the given index in the intent's children.
+    /// Any buckets in the args are put onto the child's worktop.
+    YieldToChild {
+        child_index: u32,
+        args: ManifestValue,
+    },
+
+    /// Yields back to the parent intent.
+    /// Any buckets in the args are put onto the parent's worktop.
+    YieldToParent { args: ManifestValue },
+}
+
+impl From<InstructionV1> for InstructionV2 {
+    fn from(value: InstructionV1) -> Self {
+        Self::V1(value)
+    }
+}
+
+impl Categorize<ManifestCustomValueKind> for InstructionV2 {
+    #[inline]
+    fn value_kind() -> ValueKind<ManifestCustomValueKind> {
+        ValueKind::Enum
+    }
+}
+
+impl<E: Encoder<ManifestCustomValueKind>> Encode<ManifestCustomValueKind, E> for InstructionV2 {
+    #[inline]
+    fn encode_value_kind(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        encoder.write_value_kind(Self::value_kind())
+    }
+
+    #[inline]
+    fn encode_body(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        match self {
+            Self::V1(instruction) => instruction.encode_body(encoder),
+            Self::YieldToChild { child_index, args } => {
+                encoder.write_discriminator(INSTRUCTION_YIELD_TO_CHILD_DISCRIMINATOR)?;
+                encoder.write_size(2)?;
+                encoder.encode(child_index)?;
+                encoder.encode(args)
+            }
+            Self::YieldToParent { args } => {
+                encoder.write_discriminator(INSTRUCTION_YIELD_TO_PARENT_DISCRIMINATOR)?;
+                encoder.write_size(1)?;
+                encoder.encode(args)
+            }
+        }
+    }
+}
+
+impl<D: Decoder<ManifestCustomValueKind>> Decode<ManifestCustomValueKind, D> for InstructionV2 {
+    fn decode_body_with_value_kind(
+        decoder: &mut D,
+        value_kind: ValueKind<ManifestCustomValueKind>,
+    ) -> Result<Self, DecodeError> {
+        decoder.check_preloaded_value_kind(value_kind, Self::value_kind())?;
+        // We peek the discriminator, so that V1 instructions can be decoded by the V1 decoder
+        match decoder.peek_byte()? {
+            INSTRUCTION_YIELD_TO_CHILD_DISCRIMINATOR => {
+                decoder.read_discriminator()?;
+                decoder.read_and_check_size(2)?;
+                Ok(Self::YieldToChild {
+                    child_index: decoder.decode()?,
+                    args: decoder.decode()?,
+                })
+            }
+            INSTRUCTION_YIELD_TO_PARENT_DISCRIMINATOR => {
+                decoder.read_discriminator()?;
+                decoder.read_and_check_size(1)?;
+                Ok(Self::YieldToParent {
+                    args: decoder.decode()?,
+                })
+            }
+            _ => Ok(Self::V1(InstructionV1::decode_body_with_value_kind(
+                decoder, value_kind,
+            )?)),
+        }
+    }
+}
+
+//===============================================================
+// INSTRUCTION DISCRIMINATORS:
+//
+// These continue from the V1 discriminators - see the note in
+// `model/v1/instruction.rs` about allocating new numbers.
+//===============================================================
+
+//==============
+// Subintents
+//==============
+pub const INSTRUCTION_YIELD_TO_PARENT_DISCRIMINATOR: u8 = 0x60;
+pub const INSTRUCTION_YIELD_TO_CHILD_DISCRIMINATOR: u8 = 0x61;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_instructions_decode_as_v2_instructions() {
+        let instructions = vec![
+            InstructionV1::DropAuthZoneProofs,
+            InstructionV1::TakeAllFromWorktop {
+                resource_address: XRD,
+            },
+            InstructionV1::ReturnToWorktop {
+                bucket_id: ManifestBucket(0),
+            },
+        ];
+        let encoded = manifest_encode(&instructions).unwrap();
+        let decoded = manifest_decode::<Vec<InstructionV2>>(&encoded).unwrap();
+        assert_eq!(
+            decoded,
+            instructions
+                .into_iter()
+                .map(InstructionV2::from)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(manifest_encode(&decoded).unwrap(), encoded);
+    }
+
+    #[test]
+    fn yield_instructions_round_trip() {
+        let instructions = vec![
+            InstructionV2::YieldToChild {
+                child_index: 1,
+                args: manifest_decode(&manifest_encode(&(ManifestBucket(3),)).unwrap()).unwrap(),
+            },
+            InstructionV2::YieldToParent {
+                args: manifest_decode(&manifest_encode(&()).unwrap()).unwrap(),
+            },
+        ];
+        let encoded = manifest_encode(&instructions).unwrap();
+        assert_eq!(
+            manifest_decode::<Vec<InstructionV2>>(&encoded).unwrap(),
+            instructions
+        );
+        // The new instructions are not valid V1 instructions
+        assert!(manifest_decode::<Vec<InstructionV1>>(&encoded).is_err());
+    }
+}
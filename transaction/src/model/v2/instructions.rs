@@ -0,0 +1,13 @@
+use super::*;
+use crate::internal_prelude::*;
+
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+#[sbor(transparent)]
+pub struct InstructionsV2(pub Vec<InstructionV2>);
+
+impl TransactionPartialEncode for InstructionsV2 {
+    type Prepared = PreparedInstructionsV2;
+}
+
+// We summarize all the transactions as a single unit (not transaction-by-transaction)
+pub type PreparedInstructionsV2 = SummarizedRawFullBodyWithReferences<InstructionsV2>;
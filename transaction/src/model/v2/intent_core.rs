@@ -0,0 +1,53 @@
+use super::*;
+use crate::internal_prelude::*;
+
+//=================================================================================
+// NOTE:
+// See versioned.rs for tests and a demonstration for the calculation of hashes etc
+//=================================================================================
+
+/// The content shared by transaction intents and subintents.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+pub struct IntentCoreV2 {
+    pub header: IntentHeaderV2,
+    pub blobs: BlobsV1,
+    pub message: MessageV1,
+    pub children: ChildIntentsV2,
+    pub instructions: InstructionsV2,
+}
+
+impl TransactionPartialEncode for IntentCoreV2 {
+    type Prepared = PreparedIntentCoreV2;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreparedIntentCoreV2 {
+    pub header: PreparedIntentHeaderV2,
+    pub blobs: PreparedBlobsV1,
+    pub message: PreparedMessageV1,
+    pub children: PreparedChildIntentsV2,
+    pub instructions: PreparedInstructionsV2,
+    pub summary: Summary,
+}
+
+impl HasSummary for PreparedIntentCoreV2 {
+    fn get_summary(&self) -> &Summary {
+        &self.summary
+    }
+}
+
+impl TransactionFullChildPreparable for PreparedIntentCoreV2 {
+    fn prepare_as_full_body_child(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // The intent core is always embedded as a child, so it's SBOR encoded as a struct
+        let ((header, blobs, message, children, instructions), summary) =
+            ConcatenatedDigest::prepare_from_sbor_tuple(decoder, HashAccumulator::new())?;
+        Ok(Self {
+            header,
+            blobs,
+            message,
+            children,
+            instructions,
+            summary,
+        })
+    }
+}
@@ -0,0 +1,16 @@
+use crate::internal_prelude::*;
+
+/// The header of an intent (either a transaction intent or a subintent).
+///
+/// Unlike `TransactionHeaderV1`, this doesn't include the notary or tip, which
+/// are properties of the whole transaction - see `TransactionHeaderV2`.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+pub struct IntentHeaderV2 {
+    pub network_id: u8,
+    pub start_epoch_inclusive: Epoch,
+    pub end_epoch_exclusive: Epoch,
+    /// Allows an intent author to create distinct intents with otherwise identical content
+    pub intent_discriminator: u64,
+}
+
+pub type PreparedIntentHeaderV2 = SummarizedRawFullBody<IntentHeaderV2>;
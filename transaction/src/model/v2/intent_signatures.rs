@@ -0,0 +1,12 @@
+use crate::internal_prelude::*;
+
+/// The signatures of each non-root subintent, in the same order as `NonRootSubintentsV2`.
+///
+/// Each `IntentSignaturesV1` contains signatures of the corresponding `SubintentHash`.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+#[sbor(transparent)]
+pub struct NonRootSubintentSignaturesV2 {
+    pub by_subintent: Vec<IntentSignaturesV1>,
+}
+
+pub type PreparedNonRootSubintentSignaturesV2 = SummarizedRawFullBody<NonRootSubintentSignaturesV2>;
@@ -0,0 +1,27 @@
+use super::*;
+
+mod child_intents;
+mod instruction;
+mod instructions;
+mod intent_core;
+mod intent_header;
+mod intent_signatures;
+mod notarized_transaction;
+mod signed_transaction_intent;
+mod subintent;
+mod transaction_header;
+mod transaction_intent;
+mod validated_notarized_transaction;
+
+pub use child_intents::*;
+pub use instruction::*;
+pub use instructions::*;
+pub use intent_core::*;
+pub use intent_header::*;
+pub use intent_signatures::*;
+pub use notarized_transaction::*;
+pub use signed_transaction_intent::*;
+pub use subintent::*;
+pub use transaction_header::*;
+pub use transaction_intent::*;
+pub use validated_notarized_transaction::*;
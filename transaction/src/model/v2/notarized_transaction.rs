@@ -0,0 +1,84 @@
+use super::*;
+use crate::internal_prelude::*;
+
+//=================================================================================
+// NOTE:
+// See versioned.rs for tests and a demonstration for the calculation of hashes etc
+//=================================================================================
+
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+pub struct NotarizedTransactionV2 {
+    pub signed_transaction_intent: SignedTransactionIntentV2,
+    pub notary_signature: NotarySignatureV1,
+}
+
+impl TransactionPayload for NotarizedTransactionV2 {
+    type Versioned = SborFixedEnumVariant<{ TransactionDiscriminator::V2Notarized as u8 }, Self>;
+    type Prepared = PreparedNotarizedTransactionV2;
+    type Raw = RawNotarizedTransaction;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreparedNotarizedTransactionV2 {
+    pub signed_transaction_intent: PreparedSignedTransactionIntentV2,
+    pub notary_signature: PreparedNotarySignatureV1,
+    pub summary: Summary,
+}
+
+impl HasSummary for PreparedNotarizedTransactionV2 {
+    fn get_summary(&self) -> &Summary {
+        &self.summary
+    }
+}
+
+impl TransactionFullChildPreparable for PreparedNotarizedTransactionV2 {
+    fn prepare_as_full_body_child(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // When embedded as an child, it's SBOR encoded as a struct
+        let ((signed_transaction_intent, notary_signature), summary) =
+            ConcatenatedDigest::prepare_from_transaction_child_struct(
+                decoder,
+                TransactionDiscriminator::V2Notarized,
+            )?;
+        Ok(Self {
+            signed_transaction_intent,
+            notary_signature,
+            summary,
+        })
+    }
+}
+
+impl TransactionPayloadPreparable for PreparedNotarizedTransactionV2 {
+    type Raw = RawNotarizedTransaction;
+
+    fn prepare_for_payload(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // When embedded as full payload, it's SBOR encoded as an enum
+        let ((signed_transaction_intent, notary_signature), summary) =
+            ConcatenatedDigest::prepare_from_transaction_payload_enum(
+                decoder,
+                TransactionDiscriminator::V2Notarized,
+            )?;
+        Ok(Self {
+            signed_transaction_intent,
+            notary_signature,
+            summary,
+        })
+    }
+}
+
+impl HasIntentHash for PreparedNotarizedTransactionV2 {
+    fn intent_hash(&self) -> IntentHash {
+        self.signed_transaction_intent.intent_hash()
+    }
+}
+
+impl HasSignedIntentHash for PreparedNotarizedTransactionV2 {
+    fn signed_intent_hash(&self) -> SignedIntentHash {
+        self.signed_transaction_intent.signed_intent_hash()
+    }
+}
+
+impl HasNotarizedTransactionHash for PreparedNotarizedTransactionV2 {
+    fn notarized_transaction_hash(&self) -> NotarizedTransactionHash {
+        NotarizedTransactionHash::from_hash(self.summary.hash)
+    }
+}
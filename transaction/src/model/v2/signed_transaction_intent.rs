@@ -0,0 +1,88 @@
+use super::*;
+use crate::internal_prelude::*;
+
+//=================================================================================
+// NOTE:
+// See versioned.rs for tests and a demonstration for the calculation of hashes etc
+//=================================================================================
+
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+pub struct SignedTransactionIntentV2 {
+    pub transaction_intent: TransactionIntentV2,
+    /// Signatures of the `IntentHash` of the transaction intent, for the root intent
+    pub transaction_intent_signatures: IntentSignaturesV1,
+    pub non_root_subintent_signatures: NonRootSubintentSignaturesV2,
+}
+
+impl TransactionPayload for SignedTransactionIntentV2 {
+    type Versioned =
+        SborFixedEnumVariant<{ TransactionDiscriminator::V2SignedTransactionIntent as u8 }, Self>;
+    type Prepared = PreparedSignedTransactionIntentV2;
+    type Raw = RawSignedIntent;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreparedSignedTransactionIntentV2 {
+    pub transaction_intent: PreparedTransactionIntentV2,
+    pub transaction_intent_signatures: PreparedIntentSignaturesV1,
+    pub non_root_subintent_signatures: PreparedNonRootSubintentSignaturesV2,
+    pub summary: Summary,
+}
+
+impl HasSummary for PreparedSignedTransactionIntentV2 {
+    fn get_summary(&self) -> &Summary {
+        &self.summary
+    }
+}
+
+impl TransactionFullChildPreparable for PreparedSignedTransactionIntentV2 {
+    fn prepare_as_full_body_child(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // When embedded as an child, it's SBOR encoded as a struct
+        let (
+            (transaction_intent, transaction_intent_signatures, non_root_subintent_signatures),
+            summary,
+        ) = ConcatenatedDigest::prepare_from_transaction_child_struct(
+            decoder,
+            TransactionDiscriminator::V2SignedTransactionIntent,
+        )?;
+        Ok(Self {
+            transaction_intent,
+            transaction_intent_signatures,
+            non_root_subintent_signatures,
+            summary,
+        })
+    }
+}
+
+impl TransactionPayloadPreparable for PreparedSignedTransactionIntentV2 {
+    type Raw = RawSignedIntent;
+
+    fn prepare_for_payload(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // When embedded as full payload, it's SBOR encoded as an enum
+        let (
+            (transaction_intent, transaction_intent_signatures, non_root_subintent_signatures),
+            summary,
+        ) = ConcatenatedDigest::prepare_from_transaction_payload_enum(
+            decoder,
+            TransactionDiscriminator::V2SignedTransactionIntent,
+        )?;
+        Ok(Self {
+            transaction_intent,
+            transaction_intent_signatures,
+            non_root_subintent_signatures,
+            summary,
+        })
+    }
+}
+
+impl HasIntentHash for PreparedSignedTransactionIntentV2 {
+    fn intent_hash(&self) -> IntentHash {
+        self.transaction_intent.intent_hash()
+    }
+}
+
+impl HasSignedIntentHash for PreparedSignedTransactionIntentV2 {
+    fn signed_intent_hash(&self) -> SignedIntentHash {
+        SignedIntentHash::from_hash(self.summary.hash)
+    }
+}
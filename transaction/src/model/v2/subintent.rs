@@ -0,0 +1,135 @@
+use super::*;
+use crate::internal_prelude::*;
+
+//=================================================================================
+// NOTE:
+// See versioned.rs for tests and a demonstration for the calculation of hashes etc
+//=================================================================================
+
+/// An intent which is a child of another intent in a transaction.
+///
+/// A subintent is created and signed separately by its own party, and can then be
+/// included into a transaction by any other party, whose intent lists its hash as
+/// one of its children.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+pub struct SubintentV2 {
+    pub intent_core: IntentCoreV2,
+}
+
+impl TransactionPayload for SubintentV2 {
+    type Versioned = SborFixedEnumVariant<{ TransactionDiscriminator::V2Subintent as u8 }, Self>;
+    type Prepared = PreparedSubintentV2;
+    type Raw = RawSubintent;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreparedSubintentV2 {
+    pub intent_core: PreparedIntentCoreV2,
+    pub summary: Summary,
+}
+
+impl HasSummary for PreparedSubintentV2 {
+    fn get_summary(&self) -> &Summary {
+        &self.summary
+    }
+}
+
+impl TransactionChildBodyPreparable for PreparedSubintentV2 {
+    fn prepare_as_inner_body_child(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // When embedded in NonRootSubintentsV2, it's SBOR encoded as a struct inside an array,
+        // so the value kind is in the array header. The hash must match the hash of the
+        // subintent when prepared as a full payload, so that signatures can be created
+        // against a standalone subintent.
+        decoder.track_stack_depth_increase()?;
+        decoder.read_inner_struct_header(1)?;
+
+        let intent_core = PreparedIntentCoreV2::prepare_as_full_body_child(decoder)?;
+
+        decoder.track_stack_depth_decrease()?;
+
+        let accumulator = HashAccumulator::new()
+            .update(&[
+                TRANSACTION_HASHABLE_PAYLOAD_PREFIX,
+                TransactionDiscriminator::V2Subintent as u8,
+            ])
+            .update(intent_core.get_summary().hash);
+
+        // See note in `ArrayPreparable` about why we assume 2 bytes for the struct header
+        let effective_length = 2usize
+            .checked_add(intent_core.get_summary().effective_length)
+            .ok_or(PrepareError::LengthOverflow)?;
+        let total_bytes_hashed = intent_core
+            .get_summary()
+            .total_bytes_hashed
+            .checked_add(accumulator.input_length())
+            .ok_or(PrepareError::LengthOverflow)?;
+
+        let summary = Summary {
+            effective_length,
+            total_bytes_hashed,
+            hash: accumulator.finalize(),
+        };
+        Ok(Self {
+            intent_core,
+            summary,
+        })
+    }
+
+    fn value_kind() -> ManifestValueKind {
+        ValueKind::Tuple
+    }
+}
+
+impl TransactionPayloadPreparable for PreparedSubintentV2 {
+    type Raw = RawSubintent;
+
+    fn prepare_for_payload(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // When embedded as full payload, it's SBOR encoded as an enum
+        let ((intent_core,), summary) = ConcatenatedDigest::prepare_from_transaction_payload_enum(
+            decoder,
+            TransactionDiscriminator::V2Subintent,
+        )?;
+        Ok(Self {
+            intent_core,
+            summary,
+        })
+    }
+}
+
+impl HasSubintentHash for PreparedSubintentV2 {
+    fn subintent_hash(&self) -> SubintentHash {
+        SubintentHash::from_hash(self.summary.hash)
+    }
+}
+
+/// The subintents of a transaction, other than the root transaction intent.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+#[sbor(transparent)]
+pub struct NonRootSubintentsV2(pub Vec<SubintentV2>);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreparedNonRootSubintentsV2 {
+    pub subintents: Vec<PreparedSubintentV2>,
+    pub summary: Summary,
+}
+
+impl HasSummary for PreparedNonRootSubintentsV2 {
+    fn get_summary(&self) -> &Summary {
+        &self.summary
+    }
+}
+
+impl TransactionFullChildPreparable for PreparedNonRootSubintentsV2 {
+    fn prepare_as_full_body_child(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        let (subintents, summary) =
+            ConcatenatedDigest::prepare_from_sbor_array::<
+                Vec<PreparedSubintentV2>,
+                MAX_NUMBER_OF_SUBINTENTS,
+            >(decoder, HashAccumulator::new(), ValueType::Subintent)?;
+
+        Ok(Self {
+            subintents,
+            summary,
+        })
+    }
+}
@@ -0,0 +1,11 @@
+use crate::internal_prelude::*;
+
+/// The parts of the header which apply to the whole transaction, rather than to an intent.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+pub struct TransactionHeaderV2 {
+    pub notary_public_key: PublicKey,
+    pub notary_is_signatory: bool,
+    pub tip_percentage: u16,
+}
+
+pub type PreparedTransactionHeaderV2 = SummarizedRawFullBody<TransactionHeaderV2>;
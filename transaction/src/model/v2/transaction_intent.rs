@@ -0,0 +1,80 @@
+use super::*;
+use crate::internal_prelude::*;
+
+//=================================================================================
+// NOTE:
+// See versioned.rs for tests and a demonstration for the calculation of hashes etc
+//=================================================================================
+
+/// The root intent of a V2 transaction, along with all of its (transitive) subintents.
+///
+/// The hash of this payload is the `IntentHash` (transaction id) of the transaction.
+#[derive(Debug, Clone, Eq, PartialEq, ManifestSbor)]
+pub struct TransactionIntentV2 {
+    pub transaction_header: TransactionHeaderV2,
+    pub root_intent_core: IntentCoreV2,
+    pub non_root_subintents: NonRootSubintentsV2,
+}
+
+impl TransactionPayload for TransactionIntentV2 {
+    type Versioned =
+        SborFixedEnumVariant<{ TransactionDiscriminator::V2TransactionIntent as u8 }, Self>;
+    type Prepared = PreparedTransactionIntentV2;
+    type Raw = RawIntent;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreparedTransactionIntentV2 {
+    pub transaction_header: PreparedTransactionHeaderV2,
+    pub root_intent_core: PreparedIntentCoreV2,
+    pub non_root_subintents: PreparedNonRootSubintentsV2,
+    pub summary: Summary,
+}
+
+impl HasSummary for PreparedTransactionIntentV2 {
+    fn get_summary(&self) -> &Summary {
+        &self.summary
+    }
+}
+
+impl TransactionFullChildPreparable for PreparedTransactionIntentV2 {
+    fn prepare_as_full_body_child(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // When embedded as an child, it's SBOR encoded as a struct
+        let ((transaction_header, root_intent_core, non_root_subintents), summary) =
+            ConcatenatedDigest::prepare_from_transaction_child_struct(
+                decoder,
+                TransactionDiscriminator::V2TransactionIntent,
+            )?;
+        Ok(Self {
+            transaction_header,
+            root_intent_core,
+            non_root_subintents,
+            summary,
+        })
+    }
+}
+
+impl TransactionPayloadPreparable for PreparedTransactionIntentV2 {
+    type Raw = RawIntent;
+
+    fn prepare_for_payload(decoder: &mut TransactionDecoder) -> Result<Self, PrepareError> {
+        // When embedded as full payload, it's SBOR encoded as an enum
+        let ((transaction_header, root_intent_core, non_root_subintents), summary) =
+            ConcatenatedDigest::prepare_from_transaction_payload_enum(
+                decoder,
+                TransactionDiscriminator::V2TransactionIntent,
+            )?;
+        Ok(Self {
+            transaction_header,
+            root_intent_core,
+            non_root_subintents,
+            summary,
+        })
+    }
+}
+
+impl HasIntentHash for PreparedTransactionIntentV2 {
+    fn intent_hash(&self) -> IntentHash {
+        IntentHash::from_hash(self.summary.hash)
+    }
+}
@@ -0,0 +1,107 @@
+use super::*;
+use crate::internal_prelude::*;
+use radix_engine_interface::api::node_modules::auth::AuthAddresses;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ValidatedNotarizedTransactionV2 {
+    pub prepared: PreparedNotarizedTransactionV2,
+    pub encoded_instructions: Vec<u8>,
+    pub signer_keys: Vec<PublicKey>,
+    /// The indices in `subintents` of the children of the root intent
+    pub root_children: Vec<u32>,
+    /// The validated non-root subintents, in the same order as in the transaction
+    pub subintents: Vec<ValidatedSubintentV2>,
+    /// The intersection of the epoch ranges of all intents in the transaction
+    pub epoch_range: EpochRange,
+    pub num_of_signature_validations: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ValidatedSubintentV2 {
+    pub encoded_instructions: Vec<u8>,
+    pub signer_keys: Vec<PublicKey>,
+    /// The indices in `ValidatedNotarizedTransactionV2::subintents` of the children of this subintent
+    pub children: Vec<u32>,
+}
+
+impl HasIntentHash for ValidatedNotarizedTransactionV2 {
+    fn intent_hash(&self) -> IntentHash {
+        self.prepared.intent_hash()
+    }
+}
+
+impl HasSignedIntentHash for ValidatedNotarizedTransactionV2 {
+    fn signed_intent_hash(&self) -> SignedIntentHash {
+        self.prepared.signed_intent_hash()
+    }
+}
+
+impl HasNotarizedTransactionHash for ValidatedNotarizedTransactionV2 {
+    fn notarized_transaction_hash(&self) -> NotarizedTransactionHash {
+        self.prepared.notarized_transaction_hash()
+    }
+}
+
+impl ValidatedNotarizedTransactionV2 {
+    pub fn get_executable_with_free_credit<'a>(
+        &'a self,
+        free_credit_in_xrd: Decimal,
+    ) -> Executable<'a> {
+        let transaction_intent = &self.prepared.signed_transaction_intent.transaction_intent;
+        let transaction_header = &transaction_intent.transaction_header.inner;
+        let root_intent_core = &transaction_intent.root_intent_core;
+        let intent_hash = transaction_intent.intent_hash();
+        let summary = &self.prepared.summary;
+
+        let mut references = root_intent_core.instructions.references.clone();
+        let mut subintents = Vec::with_capacity(self.subintents.len());
+        for (prepared, validated) in transaction_intent
+            .non_root_subintents
+            .subintents
+            .iter()
+            .zip(self.subintents.iter())
+        {
+            references.extend(prepared.intent_core.instructions.references.iter().cloned());
+            subintents.push(ExecutableSubintent {
+                subintent_hash: prepared.subintent_hash().into_hash(),
+                expiry_epoch: prepared.intent_core.header.inner.end_epoch_exclusive,
+                encoded_instructions: validated.encoded_instructions.clone(),
+                blobs: prepared.intent_core.blobs.blobs_by_hash.clone(),
+                children: validated.children.clone(),
+                initial_proofs: AuthAddresses::signer_set(&validated.signer_keys),
+            });
+        }
+
+        Executable::new(
+            &self.encoded_instructions,
+            &references,
+            &root_intent_core.blobs.blobs_by_hash,
+            ExecutionContext {
+                intent_hash: TransactionIntentHash::ToCheck {
+                    intent_hash: intent_hash.into_hash(),
+                    expiry_epoch: root_intent_core.header.inner.end_epoch_exclusive,
+                },
+                epoch_range: Some(self.epoch_range.clone()),
+                payload_size: summary.effective_length,
+                num_of_signature_validations: self.num_of_signature_validations,
+                auth_zone_params: AuthZoneParams {
+                    initial_proofs: AuthAddresses::signer_set(&self.signer_keys),
+                    virtual_resources: BTreeSet::new(),
+                },
+                costing_parameters: TransactionCostingParameters {
+                    tip_percentage: transaction_header.tip_percentage,
+                    free_credit_in_xrd,
+                },
+                pre_allocated_addresses: vec![],
+                subintent_params: SubintentParams {
+                    root_children: self.root_children.clone(),
+                    subintents,
+                },
+            },
+        )
+    }
+
+    pub fn get_executable<'a>(&'a self) -> Executable<'a> {
+        self.get_executable_with_free_credit(Decimal::ZERO)
+    }
+}
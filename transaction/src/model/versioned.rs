@@ -18,6 +18,10 @@ pub enum TransactionDiscriminator {
     V1RoundUpdate = V1_ROUND_UPDATE_TRANSACTION,
     V1Preview = V1_PREVIEW_TRANSACTION,
     V1Ledger = V1_LEDGER_TRANSACTION,
+    V2TransactionIntent = V2_TRANSACTION_INTENT,
+    V2SignedTransactionIntent = V2_SIGNED_TRANSACTION_INTENT,
+    V2Subintent = V2_SUBINTENT,
+    V2Notarized = V2_NOTARIZED_TRANSACTION,
 }
 
 const V1_INTENT: u8 = 1;
@@ -27,6 +31,10 @@ const V1_SYSTEM_TRANSACTION: u8 = 4;
 const V1_ROUND_UPDATE_TRANSACTION: u8 = 5;
 const V1_PREVIEW_TRANSACTION: u8 = 6;
 const V1_LEDGER_TRANSACTION: u8 = 7;
+const V2_TRANSACTION_INTENT: u8 = 10;
+const V2_SIGNED_TRANSACTION_INTENT: u8 = 11;
+const V2_SUBINTENT: u8 = 12;
+const V2_NOTARIZED_TRANSACTION: u8 = 13;
 
 // TODO - change this to use #[flatten] when REP-84 is out
 /// An enum of a variety of different transaction payload types
@@ -58,6 +66,25 @@ pub enum VersionedTransactionPayload {
         pre_allocated_addresses: Vec<PreAllocatedAddress>,
         hash_for_execution: Hash,
     },
+    #[sbor(discriminator(V2_TRANSACTION_INTENT))]
+    TransactionIntentV2 {
+        transaction_header: TransactionHeaderV2,
+        root_intent_core: IntentCoreV2,
+        non_root_subintents: NonRootSubintentsV2,
+    },
+    #[sbor(discriminator(V2_SIGNED_TRANSACTION_INTENT))]
+    SignedTransactionIntentV2 {
+        transaction_intent: TransactionIntentV2,
+        transaction_intent_signatures: IntentSignaturesV1,
+        non_root_subintent_signatures: NonRootSubintentSignaturesV2,
+    },
+    #[sbor(discriminator(V2_SUBINTENT))]
+    SubintentV2 { intent_core: IntentCoreV2 },
+    #[sbor(discriminator(V2_NOTARIZED_TRANSACTION))]
+    NotarizedTransactionV2 {
+        signed_transaction_intent: SignedTransactionIntentV2,
+        notary_signature: NotarySignatureV1,
+    },
 }
 
 #[cfg(test)]
@@ -302,6 +329,321 @@ mod tests {
         );
     }
 
+    /// This test demonstrates how the hashes and payloads are constructed in a valid V2 user transaction,
+    /// whose root intent has a single subintent as its child.
+    #[test]
+    pub fn v2_user_transaction_structure() {
+        let network = NetworkDefinition::simulator();
+
+        // Create key pairs
+        let sig_1_private_key = Secp256k1PrivateKey::from_u64(1).unwrap();
+        let sig_2_private_key = Ed25519PrivateKey::from_u64(2).unwrap();
+        let notary_private_key = Ed25519PrivateKey::from_u64(3).unwrap();
+
+        //===================
+        // SUBINTENT
+        //===================
+        let subintent_header_v2 = IntentHeaderV2 {
+            network_id: network.id,
+            start_epoch_inclusive: Epoch::of(1),
+            end_epoch_exclusive: Epoch::of(5),
+            intent_discriminator: 1,
+        };
+        let expected_subintent_header_hash =
+            hash_manifest_encoded_without_prefix_byte(&subintent_header_v2);
+
+        let blob1: Vec<u8> = vec![0, 1, 2, 3];
+        let expected_blobs_hash = hash(hash(&blob1).0.as_slice());
+        let blobs_v1 = BlobsV1 {
+            blobs: vec![BlobV1(blob1)],
+        };
+
+        let message_v1 = MessageV1::default();
+        let expected_attachments_hash = hash_manifest_encoded_without_prefix_byte(&message_v1);
+
+        let no_children_v2 = ChildIntentsV2 { children: vec![] };
+        let expected_no_children_hash = hash_manifest_encoded_without_prefix_byte(&no_children_v2);
+
+        let subintent_instructions = vec![InstructionV2::YieldToParent {
+            args: manifest_args!().into(),
+        }];
+        let expected_subintent_instructions_hash =
+            hash_manifest_encoded_without_prefix_byte(&subintent_instructions);
+
+        let subintent_core_v2 = IntentCoreV2 {
+            header: subintent_header_v2,
+            blobs: blobs_v1.clone(),
+            message: message_v1.clone(),
+            children: no_children_v2,
+            instructions: InstructionsV2(subintent_instructions),
+        };
+        let expected_subintent_core_hash = hash(
+            [
+                expected_subintent_header_hash.0.as_slice(),
+                expected_blobs_hash.0.as_slice(),
+                expected_attachments_hash.0.as_slice(),
+                expected_no_children_hash.0.as_slice(),
+                expected_subintent_instructions_hash.0.as_slice(),
+            ]
+            .concat(),
+        );
+
+        let subintent_v2 = SubintentV2 {
+            intent_core: subintent_core_v2.clone(),
+        };
+        let expected_subintent_hash = SubintentHash::from_hash(hash(
+            [
+                [
+                    TRANSACTION_HASHABLE_PAYLOAD_PREFIX,
+                    TransactionDiscriminator::V2Subintent as u8,
+                ]
+                .as_slice(),
+                expected_subintent_core_hash.0.as_slice(),
+            ]
+            .concat(),
+        ));
+
+        let subintent_payload_bytes = subintent_v2.to_payload_bytes().unwrap();
+        SubintentV2::from_payload_bytes(&subintent_payload_bytes)
+            .expect("Subintent can be decoded");
+        let subintent_as_versioned =
+            manifest_decode::<VersionedTransactionPayload>(&subintent_payload_bytes).unwrap();
+        assert_eq!(
+            subintent_as_versioned,
+            VersionedTransactionPayload::SubintentV2 {
+                intent_core: subintent_core_v2,
+            }
+        );
+
+        let prepared_subintent =
+            PreparedSubintentV2::prepare_from_payload(&subintent_payload_bytes).unwrap();
+        assert_eq!(expected_subintent_hash, prepared_subintent.subintent_hash());
+
+        let subintent_hash = expected_subintent_hash;
+
+        //===================
+        // TRANSACTION INTENT
+        //===================
+        let transaction_header_v2 = TransactionHeaderV2 {
+            notary_public_key: notary_private_key.public_key().into(),
+            notary_is_signatory: false,
+            tip_percentage: 0,
+        };
+        let expected_transaction_header_hash =
+            hash_manifest_encoded_without_prefix_byte(&transaction_header_v2);
+
+        let root_header_v2 = IntentHeaderV2 {
+            network_id: network.id,
+            start_epoch_inclusive: Epoch::of(1),
+            end_epoch_exclusive: Epoch::of(5),
+            intent_discriminator: 0,
+        };
+        let expected_root_header_hash = hash_manifest_encoded_without_prefix_byte(&root_header_v2);
+
+        let children_v2 = ChildIntentsV2 {
+            children: vec![ChildSubintent {
+                hash: subintent_hash,
+            }],
+        };
+        let expected_children_hash = hash_manifest_encoded_without_prefix_byte(&children_v2);
+
+        let root_instructions = vec![
+            InstructionV2::YieldToChild {
+                child_index: 0,
+                args: manifest_args!().into(),
+            },
+            InstructionV2::V1(InstructionV1::DropAuthZoneProofs),
+        ];
+        let expected_root_instructions_hash =
+            hash_manifest_encoded_without_prefix_byte(&root_instructions);
+
+        let root_intent_core_v2 = IntentCoreV2 {
+            header: root_header_v2,
+            blobs: blobs_v1,
+            message: message_v1,
+            children: children_v2,
+            instructions: InstructionsV2(root_instructions),
+        };
+        let expected_root_intent_core_hash = hash(
+            [
+                expected_root_header_hash.0.as_slice(),
+                expected_blobs_hash.0.as_slice(),
+                expected_attachments_hash.0.as_slice(),
+                expected_children_hash.0.as_slice(),
+                expected_root_instructions_hash.0.as_slice(),
+            ]
+            .concat(),
+        );
+
+        // The subintents are hashed as the concatenation of their subintent hashes
+        let non_root_subintents_v2 = NonRootSubintentsV2(vec![subintent_v2]);
+        let expected_non_root_subintents_hash = hash(subintent_hash.0.as_slice());
+
+        let transaction_intent_v2 = TransactionIntentV2 {
+            transaction_header: transaction_header_v2.clone(),
+            root_intent_core: root_intent_core_v2.clone(),
+            non_root_subintents: non_root_subintents_v2.clone(),
+        };
+        let expected_intent_hash = IntentHash::from_hash(hash(
+            [
+                [
+                    TRANSACTION_HASHABLE_PAYLOAD_PREFIX,
+                    TransactionDiscriminator::V2TransactionIntent as u8,
+                ]
+                .as_slice(),
+                expected_transaction_header_hash.0.as_slice(),
+                expected_root_intent_core_hash.0.as_slice(),
+                expected_non_root_subintents_hash.0.as_slice(),
+            ]
+            .concat(),
+        ));
+
+        let intent_payload_bytes = transaction_intent_v2.to_payload_bytes().unwrap();
+        TransactionIntentV2::from_payload_bytes(&intent_payload_bytes)
+            .expect("TransactionIntent can be decoded");
+        let intent_as_versioned =
+            manifest_decode::<VersionedTransactionPayload>(&intent_payload_bytes).unwrap();
+        assert_eq!(
+            intent_as_versioned,
+            VersionedTransactionPayload::TransactionIntentV2 {
+                transaction_header: transaction_header_v2,
+                root_intent_core: root_intent_core_v2,
+                non_root_subintents: non_root_subintents_v2,
+            }
+        );
+
+        let prepared_intent =
+            PreparedTransactionIntentV2::prepare_from_payload(&intent_payload_bytes).unwrap();
+        assert_eq!(expected_intent_hash, prepared_intent.intent_hash());
+        // A subintent embedded in a transaction has the same hash as the standalone subintent
+        assert_eq!(
+            subintent_hash,
+            prepared_intent.non_root_subintents.subintents[0].subintent_hash()
+        );
+
+        let intent_hash = expected_intent_hash;
+
+        //==========================
+        // SIGNED TRANSACTION INTENT
+        //==========================
+        let intent_signatures_v1 = IntentSignaturesV1 {
+            signatures: vec![IntentSignatureV1(
+                sig_1_private_key.sign_with_public_key(&intent_hash),
+            )],
+        };
+        let expected_intent_signatures_hash =
+            hash_manifest_encoded_without_prefix_byte(&intent_signatures_v1);
+
+        let non_root_subintent_signatures_v2 = NonRootSubintentSignaturesV2 {
+            by_subintent: vec![IntentSignaturesV1 {
+                signatures: vec![IntentSignatureV1(
+                    sig_2_private_key.sign_with_public_key(&subintent_hash),
+                )],
+            }],
+        };
+        let expected_non_root_subintent_signatures_hash =
+            hash_manifest_encoded_without_prefix_byte(&non_root_subintent_signatures_v2);
+
+        let signed_transaction_intent_v2 = SignedTransactionIntentV2 {
+            transaction_intent: transaction_intent_v2.clone(),
+            transaction_intent_signatures: intent_signatures_v1.clone(),
+            non_root_subintent_signatures: non_root_subintent_signatures_v2.clone(),
+        };
+        let expected_signed_intent_hash = SignedIntentHash::from_hash(hash(
+            [
+                [
+                    TRANSACTION_HASHABLE_PAYLOAD_PREFIX,
+                    TransactionDiscriminator::V2SignedTransactionIntent as u8,
+                ]
+                .as_slice(),
+                intent_hash.0.as_slice(),
+                expected_intent_signatures_hash.0.as_slice(),
+                expected_non_root_subintent_signatures_hash.0.as_slice(),
+            ]
+            .concat(),
+        ));
+
+        let signed_intent_payload_bytes = signed_transaction_intent_v2.to_payload_bytes().unwrap();
+        SignedTransactionIntentV2::from_payload_bytes(&signed_intent_payload_bytes)
+            .expect("SignedTransactionIntent can be decoded");
+        let signed_intent_as_versioned =
+            manifest_decode::<VersionedTransactionPayload>(&signed_intent_payload_bytes).unwrap();
+        assert_eq!(
+            signed_intent_as_versioned,
+            VersionedTransactionPayload::SignedTransactionIntentV2 {
+                transaction_intent: transaction_intent_v2,
+                transaction_intent_signatures: intent_signatures_v1,
+                non_root_subintent_signatures: non_root_subintent_signatures_v2,
+            }
+        );
+
+        let prepared_signed_intent =
+            PreparedSignedTransactionIntentV2::prepare_from_payload(&signed_intent_payload_bytes)
+                .unwrap();
+        assert_eq!(
+            expected_signed_intent_hash,
+            prepared_signed_intent.signed_intent_hash()
+        );
+        assert_eq!(intent_hash, prepared_signed_intent.intent_hash());
+
+        let signed_intent_hash = expected_signed_intent_hash;
+
+        //======================
+        // NOTARIZED TRANSACTION
+        //======================
+        let notary_signature = notary_private_key.sign(&signed_intent_hash);
+
+        let notary_signature_v1 = NotarySignatureV1(notary_signature.into());
+        let expected_notary_signature_v1_hash =
+            hash_manifest_encoded_without_prefix_byte(&notary_signature_v1);
+
+        let notarized_transaction_v2 = NotarizedTransactionV2 {
+            signed_transaction_intent: signed_transaction_intent_v2.clone(),
+            notary_signature: notary_signature_v1.clone(),
+        };
+        let expected_notarized_transaction_hash = NotarizedTransactionHash::from_hash(hash(
+            [
+                [
+                    TRANSACTION_HASHABLE_PAYLOAD_PREFIX,
+                    TransactionDiscriminator::V2Notarized as u8,
+                ]
+                .as_slice(),
+                signed_intent_hash.0.as_slice(),
+                expected_notary_signature_v1_hash.0.as_slice(),
+            ]
+            .concat(),
+        ));
+
+        let notarized_transaction_payload_bytes =
+            notarized_transaction_v2.to_payload_bytes().unwrap();
+        NotarizedTransactionV2::from_payload_bytes(&notarized_transaction_payload_bytes)
+            .expect("NotarizedTransaction can be decoded");
+        let notarized_transaction_as_versioned =
+            manifest_decode::<VersionedTransactionPayload>(&notarized_transaction_payload_bytes)
+                .unwrap();
+        assert_eq!(
+            notarized_transaction_as_versioned,
+            VersionedTransactionPayload::NotarizedTransactionV2 {
+                signed_transaction_intent: signed_transaction_intent_v2,
+                notary_signature: notary_signature_v1,
+            }
+        );
+
+        let prepared_notarized_transaction = PreparedNotarizedTransactionV2::prepare_from_payload(
+            &notarized_transaction_payload_bytes,
+        )
+        .unwrap();
+        assert_eq!(
+            expected_notarized_transaction_hash,
+            prepared_notarized_transaction.notarized_transaction_hash()
+        );
+        assert_eq!(
+            signed_intent_hash,
+            prepared_notarized_transaction.signed_intent_hash()
+        );
+        assert_eq!(intent_hash, prepared_notarized_transaction.intent_hash());
+    }
+
     /// This test demonstrates how the hashes and payloads are constructed in a valid system transaction.
     /// A system transaction can be embedded into the node's LedgerTransaction structure, eg as part of Genesis
     #[test]
@@ -0,0 +1,393 @@
+use crate::internal_prelude::*;
+use ::secp256k1::{Scalar, SecretKey};
+use hmac::{Hmac, Mac};
+use sbor::rust::fmt;
+use sbor::rust::str::FromStr;
+use sha2::Sha512;
+
+/// The offset of hardened child indices, which are written with a `H` (or `'`) suffix.
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerivationError {
+    InvalidPath(String),
+    /// Ed25519 keys can only be derived along hardened indices.
+    NonHardenedEd25519Index(u32),
+    /// Entity indices must be below 2^31, so that they can be hardened.
+    EntityIndexOutOfRange(u32),
+}
+
+/// A BIP32 derivation path, e.g. `m/44H/1022H/1H/525H/1460H/0H`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// The BIP44 purpose.
+    pub const PURPOSE: u32 = 44;
+    /// The SLIP-44 coin type of Radix.
+    pub const COIN_TYPE: u32 = 1022;
+    /// The entity kind of accounts (CAP-26).
+    pub const ACCOUNT_ENTITY_KIND: u32 = 525;
+    /// The entity kind of identities (CAP-26).
+    pub const IDENTITY_ENTITY_KIND: u32 = 618;
+    /// The key kind of transaction signing keys (CAP-26).
+    pub const TRANSACTION_SIGNING_KEY_KIND: u32 = 1460;
+
+    pub fn new(indices: Vec<u32>) -> Self {
+        Self(indices)
+    }
+
+    /// The path of the transaction signing key of an account, as used by the Radix wallet:
+    /// `m/44H/1022H/<network_id>H/525H/1460H/<index>H`.
+    pub fn account(network_id: u8, index: u32) -> Result<Self, DerivationError> {
+        Self::entity(network_id, Self::ACCOUNT_ENTITY_KIND, index)
+    }
+
+    /// The path of the transaction signing key of an identity, as used by the Radix wallet:
+    /// `m/44H/1022H/<network_id>H/618H/1460H/<index>H`.
+    pub fn identity(network_id: u8, index: u32) -> Result<Self, DerivationError> {
+        Self::entity(network_id, Self::IDENTITY_ENTITY_KIND, index)
+    }
+
+    fn entity(network_id: u8, entity_kind: u32, index: u32) -> Result<Self, DerivationError> {
+        if index >= HARDENED_OFFSET {
+            return Err(DerivationError::EntityIndexOutOfRange(index));
+        }
+        Ok(Self(
+            [
+                Self::PURPOSE,
+                Self::COIN_TYPE,
+                network_id as u32,
+                entity_kind,
+                Self::TRANSACTION_SIGNING_KEY_KIND,
+                index,
+            ]
+            .into_iter()
+            .map(|index| index | HARDENED_OFFSET)
+            .collect(),
+        ))
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DerivationError::InvalidPath(s.to_string());
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid());
+        }
+        parts
+            .map(|part| {
+                let (index, hardened) = match part.strip_suffix(['H', 'h', '\'']) {
+                    Some(index) => (index, true),
+                    None => (part, false),
+                };
+                let index = index.parse::<u32>().map_err(|_| invalid())?;
+                if index >= HARDENED_OFFSET {
+                    return Err(invalid());
+                }
+                Ok(if hardened {
+                    index | HARDENED_OFFSET
+                } else {
+                    index
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            if index & HARDENED_OFFSET != 0 {
+                write!(f, "/{}H", index & !HARDENED_OFFSET)?;
+            } else {
+                write!(f, "/{}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A private key along with its chain code, from which child keys are derived (SLIP-10).
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExtendedPrivateKey {
+    pub key: [u8; 32],
+    pub chain_code: [u8; 32],
+}
+
+/// The curves supported by SLIP-10 derivation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    Ed25519,
+    Secp256k1,
+}
+
+impl ExtendedPrivateKey {
+    pub fn master(curve: Curve, seed: &[u8]) -> Self {
+        let hmac_key: &[u8] = match curve {
+            Curve::Ed25519 => b"ed25519 seed",
+            Curve::Secp256k1 => b"Bitcoin seed",
+        };
+        let mut output = hmac_sha512(hmac_key, seed);
+        if curve == Curve::Secp256k1 {
+            // An invalid key is re-derived from the output itself
+            while SecretKey::from_slice(&output[..32]).is_err() {
+                output = hmac_sha512(hmac_key, &output);
+            }
+        }
+        Self::from_hmac_output(&output)
+    }
+
+    pub fn derive(
+        curve: Curve,
+        seed: &[u8],
+        path: &DerivationPath,
+    ) -> Result<Self, DerivationError> {
+        path.indices()
+            .iter()
+            .try_fold(Self::master(curve, seed), |key, index| {
+                key.derive_child(curve, *index)
+            })
+    }
+
+    pub fn derive_child(&self, curve: Curve, index: u32) -> Result<Self, DerivationError> {
+        let hardened = index & HARDENED_OFFSET != 0;
+        let mut data = Vec::with_capacity(37);
+        match (curve, hardened) {
+            (Curve::Ed25519, false) => return Err(DerivationError::NonHardenedEd25519Index(index)),
+            (_, true) => {
+                data.push(0u8);
+                data.extend_from_slice(&self.key);
+            }
+            (Curve::Secp256k1, false) => {
+                let private_key = Secp256k1PrivateKey::from_bytes(&self.key)
+                    .expect("Derived keys are always valid");
+                data.extend_from_slice(&private_key.public_key().0);
+            }
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let mut output = hmac_sha512(&self.chain_code, &data);
+        if curve == Curve::Secp256k1 {
+            // The child key is `parse256(IL) + k_par (mod n)`, and is re-derived from
+            // `0x01 || IR || ser32(index)` if that isn't a valid key
+            loop {
+                let parent =
+                    SecretKey::from_slice(&self.key).expect("Derived keys are always valid");
+                let child = SecretKey::from_slice(&output[..32])
+                    .ok()
+                    .and_then(|tweak| parent.add_tweak(&Scalar::from(tweak)).ok());
+                match child {
+                    Some(child) => {
+                        output[..32].copy_from_slice(&child.secret_bytes());
+                        break;
+                    }
+                    None => {
+                        let mut data = vec![1u8];
+                        data.extend_from_slice(&output[32..]);
+                        data.extend_from_slice(&index.to_be_bytes());
+                        output = hmac_sha512(&self.chain_code, &data);
+                    }
+                }
+            }
+        }
+        Ok(Self::from_hmac_output(&output))
+    }
+
+    fn from_hmac_output(output: &[u8; 64]) -> Self {
+        let mut key = [0u8; 32];
+        let mut chain_code = [0u8; 32];
+        key.copy_from_slice(&output[..32]);
+        chain_code.copy_from_slice(&output[32..]);
+        Self { key, chain_code }
+    }
+}
+
+/// Derives an Ed25519 private key from a BIP39 seed, using SLIP-10.
+pub fn derive_ed25519_private_key(
+    seed: &[u8],
+    path: &DerivationPath,
+) -> Result<Ed25519PrivateKey, DerivationError> {
+    let extended_key = ExtendedPrivateKey::derive(Curve::Ed25519, seed, path)?;
+    Ok(Ed25519PrivateKey::from_bytes(&extended_key.key).expect("Any 32 bytes are a valid key"))
+}
+
+/// Derives a Secp256k1 private key from a BIP39 seed, using SLIP-10 (which is BIP32 for this
+/// curve).
+pub fn derive_secp256k1_private_key(
+    seed: &[u8],
+    path: &DerivationPath,
+) -> Result<Secp256k1PrivateKey, DerivationError> {
+    let extended_key = ExtendedPrivateKey::derive(Curve::Secp256k1, seed, path)?;
+    Ok(Secp256k1PrivateKey::from_bytes(&extended_key.key).expect("Derived keys are always valid"))
+}
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut mac =
+        Hmac::<Sha512>::new_from_slice(key).expect("HMAC can be initialized with any key length");
+    mac.update(data);
+    let mut output = [0u8; 64];
+    output.copy_from_slice(&mac.finalize().into_bytes());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::hd::Mnemonic;
+
+    /// The seed of the first SLIP-10 test vector.
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    #[test]
+    fn test_slip10_ed25519_vectors() {
+        // (path, chain code, private key, public key)
+        let vectors = [
+            (
+                "m",
+                "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+                "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+            ),
+            (
+                "m/0H",
+                "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+            ),
+            (
+                "m/0H/1H/2H/2H/1000000000H",
+                "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+                "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+                "3c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+            ),
+        ];
+        let seed = hex::decode(SEED).unwrap();
+        for (path, chain_code, private_key, public_key) in vectors {
+            let path = DerivationPath::from_str(path).unwrap();
+            let extended_key = ExtendedPrivateKey::derive(Curve::Ed25519, &seed, &path).unwrap();
+            assert_eq!(hex::encode(extended_key.chain_code), chain_code);
+            assert_eq!(hex::encode(extended_key.key), private_key);
+
+            let private_key = derive_ed25519_private_key(&seed, &path).unwrap();
+            assert_eq!(private_key.public_key().to_string(), public_key);
+        }
+
+        assert_eq!(
+            derive_ed25519_private_key(&seed, &DerivationPath::from_str("m/0H/1").unwrap()).err(),
+            Some(DerivationError::NonHardenedEd25519Index(1))
+        );
+    }
+
+    #[test]
+    fn test_slip10_secp256k1_vectors() {
+        // (path, chain code, private key, public key)
+        let vectors = [
+            (
+                "m",
+                "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508",
+                "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35",
+                "0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2",
+            ),
+            (
+                "m/0H/1",
+                "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19",
+                "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368",
+                "03501e454bf00751f24b1b489aa925215d66af2234e3891c3b21a52bedb3cd711c",
+            ),
+            (
+                "m/0H/1/2H",
+                "04466b9cc8e161e966409ca52986c584f07e9dc81f735db683c3ff6ec7b1503f",
+                "cbce0d719ecf7431d88e6a89fa1483e02e35092af60c042b1df2ff59fa424dca",
+                "0357bfe1e341d01c69fe5654309956cbea516822fba8a601743a012a7896ee8dc2",
+            ),
+        ];
+        let seed = hex::decode(SEED).unwrap();
+        for (path, chain_code, private_key, public_key) in vectors {
+            let path = DerivationPath::from_str(path).unwrap();
+            let extended_key = ExtendedPrivateKey::derive(Curve::Secp256k1, &seed, &path).unwrap();
+            assert_eq!(hex::encode(extended_key.chain_code), chain_code);
+            assert_eq!(hex::encode(extended_key.key), private_key);
+
+            let private_key = derive_secp256k1_private_key(&seed, &path).unwrap();
+            assert_eq!(private_key.public_key().to_string(), public_key);
+        }
+    }
+
+    #[test]
+    fn test_derivation_path_parsing() {
+        let path = DerivationPath::account(1, 0).unwrap();
+        assert_eq!(path.to_string(), "m/44H/1022H/1H/525H/1460H/0H");
+        assert_eq!(
+            DerivationPath::from_str("m/44'/1022'/1'/525'/1460'/0'").unwrap(),
+            path
+        );
+        assert_eq!(
+            DerivationPath::identity(242, 3).unwrap().to_string(),
+            "m/44H/1022H/242H/618H/1460H/3H"
+        );
+        assert_eq!(
+            DerivationPath::account(1, HARDENED_OFFSET),
+            Err(DerivationError::EntityIndexOutOfRange(HARDENED_OFFSET))
+        );
+        assert_eq!(
+            DerivationPath::from_str("m/0/1H").unwrap().indices(),
+            &[0, 1 | HARDENED_OFFSET]
+        );
+        assert!(DerivationPath::from_str("44H/1022H").is_err());
+        assert!(DerivationPath::from_str("m/2147483648").is_err());
+        assert!(DerivationPath::from_str("m/abc").is_err());
+    }
+
+    #[test]
+    fn test_account_derivation_from_mnemonic() {
+        let mnemonic = Mnemonic::from_phrase(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let vectors = [
+            (
+                NetworkDefinition::mainnet(),
+                0,
+                "a5f40a306cd319049700e1dbbe8e446d17146926534e389ee9365f506549678d",
+                "account_rdx129xas2sw4fn5amyce4zun43amw73d6lu7htcxlvghd3egalnf2uv66",
+            ),
+            (
+                NetworkDefinition::mainnet(),
+                1,
+                "c1113a28db03caa752b45d3d0e91011d6adbc6e94d9b5bafccd7871729ade306",
+                "account_rdx128wc6u8qwqe75vywa02ghekzvks965prqxaug5rtumtad92m2f2267",
+            ),
+            (
+                NetworkDefinition::simulator(),
+                0,
+                "86ddeadfae5e4e5bf76ed7e10567dbf191ad3db185a60454f6fa8715ef47bdd9",
+                "account_sim1282xqkpupw60gfcvnvl0e9gk2el6uwr7jp79xc78vf6xp65ugzjp3x",
+            ),
+        ];
+        for (network, index, public_key, address) in vectors {
+            let private_key = mnemonic
+                .derive_account_private_key("", network.id, index)
+                .unwrap();
+            assert_eq!(private_key.public_key().to_string(), public_key);
+
+            let account =
+                ComponentAddress::virtual_account_from_public_key(&private_key.public_key());
+            assert_eq!(
+                AddressBech32Encoder::new(&network)
+                    .encode(account.as_node_id().as_ref())
+                    .unwrap(),
+                address
+            );
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use super::english::ENGLISH_WORDLIST;
+use super::*;
+use crate::internal_prelude::*;
+use hmac::Hmac;
+use sbor::rust::fmt;
+use sbor::rust::str::FromStr;
+use sha2::{Digest, Sha256, Sha512};
+use unicode_normalization::UnicodeNormalization;
+
+/// The number of PBKDF2 rounds of the seed derivation, as specified by BIP39.
+const SEED_ROUNDS: u32 = 2048;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// The entropy must be 128, 160, 192, 224 or 256 bits long.
+    InvalidEntropyLength(usize),
+    /// The phrase must have 12, 15, 18, 21 or 24 words.
+    InvalidWordCount(usize),
+    UnknownWord(String),
+    InvalidChecksum,
+}
+
+/// A BIP39 mnemonic, which encodes 128 to 256 bits of entropy (plus a checksum) as 12 to 24 words
+/// of the English wordlist.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Mnemonic {
+    entropy: Vec<u8>,
+}
+
+impl Mnemonic {
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, MnemonicError> {
+        if entropy.len() < 16 || entropy.len() > 32 || entropy.len() % 4 != 0 {
+            return Err(MnemonicError::InvalidEntropyLength(entropy.len()));
+        }
+        Ok(Self {
+            entropy: entropy.to_vec(),
+        })
+    }
+
+    pub fn from_phrase(phrase: &str) -> Result<Self, MnemonicError> {
+        let words: Vec<String> = phrase
+            .split_whitespace()
+            .map(|word| word.to_lowercase())
+            .collect();
+        if words.len() < 12 || words.len() > 24 || words.len() % 3 != 0 {
+            return Err(MnemonicError::InvalidWordCount(words.len()));
+        }
+
+        // Every word encodes 11 bits, and every 32 bits of entropy have a 1 bit checksum
+        let mut bits = Vec::with_capacity(words.len() * 11);
+        for word in &words {
+            let index = ENGLISH_WORDLIST
+                .binary_search(&word.as_str())
+                .map_err(|_| MnemonicError::UnknownWord(word.clone()))?;
+            bits.extend((0..11).rev().map(|i| (index >> i) & 1 == 1));
+        }
+        let checksum_length = bits.len() / 33;
+        let entropy: Vec<u8> = bits[..bits.len() - checksum_length]
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, bit| (acc << 1) | *bit as u8))
+            .collect();
+
+        let mnemonic = Self { entropy };
+        if mnemonic.checksum_bits() != bits[bits.len() - checksum_length..] {
+            return Err(MnemonicError::InvalidChecksum);
+        }
+        Ok(mnemonic)
+    }
+
+    pub fn entropy(&self) -> &[u8] {
+        &self.entropy
+    }
+
+    pub fn words(&self) -> Vec<&'static str> {
+        let mut bits: Vec<bool> = self
+            .entropy
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+        bits.extend(self.checksum_bits());
+        bits.chunks(11)
+            .map(|chunk| {
+                let index = chunk
+                    .iter()
+                    .fold(0usize, |acc, bit| (acc << 1) | *bit as usize);
+                ENGLISH_WORDLIST[index]
+            })
+            .collect()
+    }
+
+    pub fn phrase(&self) -> String {
+        self.words().join(" ")
+    }
+
+    /// Derives the BIP39 seed of the mnemonic, protected by the given passphrase (which is empty
+    /// unless the user chose one).
+    pub fn to_seed(&self, passphrase: &str) -> [u8; 64] {
+        let password: String = self.phrase().nfkd().collect();
+        let salt: String = format!("mnemonic{}", passphrase).nfkd().collect();
+        let mut seed = [0u8; 64];
+        pbkdf2::pbkdf2::<Hmac<Sha512>>(
+            password.as_bytes(),
+            salt.as_bytes(),
+            SEED_ROUNDS,
+            &mut seed,
+        )
+        .expect("HMAC can be initialized with any key length");
+        seed
+    }
+
+    /// Derives the transaction signing key of the account with the given index on the given
+    /// network, along the same path as the Radix wallet.
+    ///
+    /// The address of the account is then `ComponentAddress::virtual_account_from_public_key`.
+    pub fn derive_account_private_key(
+        &self,
+        passphrase: &str,
+        network_id: u8,
+        index: u32,
+    ) -> Result<Ed25519PrivateKey, DerivationError> {
+        derive_ed25519_private_key(
+            &self.to_seed(passphrase),
+            &DerivationPath::account(network_id, index)?,
+        )
+    }
+
+    /// The first `entropy_bits / 32` bits of the SHA-256 hash of the entropy.
+    fn checksum_bits(&self) -> Vec<bool> {
+        let hash = Sha256::digest(&self.entropy);
+        (0..self.entropy.len() / 4)
+            .map(|i| (hash[i / 8] >> (7 - i % 8)) & 1 == 1)
+            .collect()
+    }
+}
+
+/// The entropy is secret, so it is never printed.
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mnemonic(<redacted>)")
+    }
+}
+
+impl FromStr for Mnemonic {
+    type Err = MnemonicError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_phrase(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test vectors from the BIP39 reference implementation, all with the `TREZOR` passphrase.
+    const TEST_VECTORS: [(&str, &str, &str); 6] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        ),
+        (
+            "80808080808080808080808080808080",
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage above",
+            "d71de856f81a8acc65e6fc851a38d4d7ec216fd0796d0a6827a3ad6ed5511a30fa280f12eb2e47ed2ac03b5c462a0358d18d69fe4f985ec81778c1b370b652a8",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+            "ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+        ),
+        (
+            "9e885d952ad362caeb4efe34a8e91bd2",
+            "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic",
+            "274ddc525802f7c828d8ef7ddbcdc5304e87ac3535913611fbbfa986d0c9e5476c91689f9c8a54fd55bd38606aa6a8595ad213d4c9c9f9aca3fb217069a41028",
+        ),
+        (
+            "f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f",
+            "void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold",
+            "01f5bced59dec48e362f2c45b5de68b9fd6c92c6634f44d6d40aab69056506f0e35524a518034ddc1192e1dacd32c1ed3eaa3c3b131c88ed8e7e54c49a5d0998",
+        ),
+    ];
+
+    #[test]
+    fn test_bip39_vectors() {
+        for (entropy, phrase, seed) in TEST_VECTORS {
+            let mnemonic = Mnemonic::from_entropy(&hex::decode(entropy).unwrap()).unwrap();
+            assert_eq!(mnemonic.phrase(), phrase);
+            assert_eq!(hex::encode(mnemonic.to_seed("TREZOR")), seed);
+
+            let parsed = Mnemonic::from_phrase(phrase).unwrap();
+            assert_eq!(hex::encode(parsed.entropy()), entropy);
+            assert_eq!(format!("{:?}", parsed), "Mnemonic(<redacted>)");
+        }
+    }
+
+    #[test]
+    fn test_invalid_phrases() {
+        assert_eq!(
+            Mnemonic::from_phrase("abandon abandon abandon"),
+            Err(MnemonicError::InvalidWordCount(3))
+        );
+        assert_eq!(
+            Mnemonic::from_phrase(
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon"
+            ),
+            Err(MnemonicError::InvalidChecksum)
+        );
+        assert_eq!(
+            Mnemonic::from_phrase(
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon radix"
+            ),
+            Err(MnemonicError::UnknownWord("radix".to_string()))
+        );
+        assert_eq!(
+            Mnemonic::from_entropy(&[0u8; 15]),
+            Err(MnemonicError::InvalidEntropyLength(15))
+        );
+    }
+}
@@ -0,0 +1,6 @@
+mod derivation;
+mod english;
+mod mnemonic;
+
+pub use derivation::*;
+pub use mnemonic::*;
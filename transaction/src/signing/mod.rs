@@ -1,4 +1,5 @@
 pub mod ed25519;
+pub mod hd;
 pub mod secp256k1;
 mod signer;
 
@@ -2,8 +2,10 @@ mod id_allocator;
 mod id_validator;
 mod signature_validator;
 mod transaction_validator;
+mod transaction_validator_v2;
 
 pub use id_allocator::*;
 pub use id_validator::*;
 pub use signature_validator::*;
 pub use transaction_validator::*;
+pub use transaction_validator_v2::*;
@@ -73,3 +73,149 @@ pub fn verify_ed25519(
 
     false
 }
+
+/// The number of Ed25519 signatures which are verified together in a batch.
+#[cfg(feature = "parallel_validation")]
+const ED25519_BATCH_SIZE: usize = 64;
+
+/// Recovers the public keys of many signatures, returning the same results as `recover` on each
+/// of them.
+///
+/// With the `parallel_validation` feature, the (expensive) Secp256k1 recoveries run in parallel.
+pub fn recover_batch(requests: &[(Hash, &SignatureWithPublicKeyV1)]) -> Vec<Option<PublicKey>> {
+    #[cfg(feature = "parallel_validation")]
+    {
+        use rayon::prelude::*;
+        requests
+            .par_iter()
+            .map(|(signed_hash, signature)| recover(signed_hash, signature))
+            .collect()
+    }
+    #[cfg(not(feature = "parallel_validation"))]
+    {
+        requests
+            .iter()
+            .map(|(signed_hash, signature)| recover(signed_hash, signature))
+            .collect()
+    }
+}
+
+/// Verifies many signatures, returning whether each of them is valid.
+///
+/// This returns the same results as `verify` on each of the signatures. With the
+/// `parallel_validation` feature, Secp256k1 signatures are verified in parallel and Ed25519
+/// signatures are verified in parallel batches, with each failed batch verified again signature
+/// by signature. Only the Ed25519 signatures which pass `is_ed25519_batch_verifiable` are
+/// batched, the others are verified one by one with `verify_ed25519`.
+pub fn verify_batch(requests: &[(Hash, PublicKey, SignatureV1)]) -> Vec<bool> {
+    #[cfg(feature = "parallel_validation")]
+    {
+        use rayon::prelude::*;
+
+        let mut results: Vec<bool> = requests
+            .par_iter()
+            .map(
+                |(signed_hash, public_key, signature)| match (public_key, signature) {
+                    // These Ed25519 signatures are verified in batches below
+                    (PublicKey::Ed25519(pk), SignatureV1::Ed25519(sig))
+                        if is_ed25519_batch_verifiable(pk, sig) =>
+                    {
+                        false
+                    }
+                    _ => verify(signed_hash, public_key, signature),
+                },
+            )
+            .collect();
+
+        let ed25519_requests: Vec<(usize, &Hash, &Ed25519PublicKey, &Ed25519Signature)> = requests
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (signed_hash, public_key, signature))| {
+                match (public_key, signature) {
+                    (PublicKey::Ed25519(pk), SignatureV1::Ed25519(sig))
+                        if is_ed25519_batch_verifiable(pk, sig) =>
+                    {
+                        Some((index, signed_hash, pk, sig))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        let ed25519_results: Vec<(usize, bool)> = ed25519_requests
+            .par_chunks(ED25519_BATCH_SIZE)
+            .flat_map_iter(|chunk| {
+                let chunk_results: Vec<bool> = if verify_ed25519_batch(chunk) {
+                    vec![true; chunk.len()]
+                } else {
+                    chunk
+                        .iter()
+                        .map(|(_, signed_hash, pk, sig)| verify_ed25519(signed_hash, pk, sig))
+                        .collect()
+                };
+                chunk.iter().map(|(index, ..)| *index).zip(chunk_results)
+            })
+            .collect();
+        for (index, result) in ed25519_results {
+            results[index] = result;
+        }
+
+        results
+    }
+    #[cfg(not(feature = "parallel_validation"))]
+    {
+        requests
+            .iter()
+            .map(|(signed_hash, public_key, signature)| verify(signed_hash, public_key, signature))
+            .collect()
+    }
+}
+
+/// Whether verifying the Ed25519 signature in a batch returns the same result as
+/// `verify_ed25519`.
+///
+/// `verify_ed25519` rejects small-order public keys and `R`s, and checks the cofactorless
+/// equation `[s]B = R + [k]A` against the encoding of `R`. The batch checks the cofactored
+/// equation `[8]([s]B - R - [k]A) = 0` instead, which has the same solutions when both `A` and
+/// `R` are canonically encoded points of the prime-order subgroup, other than the identity.
+#[cfg(feature = "parallel_validation")]
+fn is_ed25519_batch_verifiable(
+    public_key: &Ed25519PublicKey,
+    signature: &Ed25519Signature,
+) -> bool {
+    use curve25519_dalek::edwards::CompressedEdwardsY;
+
+    let is_prime_order_point = |bytes: &[u8]| {
+        let compressed = CompressedEdwardsY::from_slice(bytes);
+        match compressed.decompress() {
+            Some(point) => {
+                point.compress() == compressed && !point.is_small_order() && point.is_torsion_free()
+            }
+            None => false,
+        }
+    };
+
+    is_prime_order_point(&public_key.0) && is_prime_order_point(&signature.0[..32])
+}
+
+/// Verifies a batch of Ed25519 signatures at once, returning whether all of them are valid.
+#[cfg(feature = "parallel_validation")]
+fn verify_ed25519_batch(requests: &[(usize, &Hash, &Ed25519PublicKey, &Ed25519Signature)]) -> bool {
+    let mut messages = Vec::with_capacity(requests.len());
+    let mut signatures = Vec::with_capacity(requests.len());
+    let mut public_keys = Vec::with_capacity(requests.len());
+    for (_, signed_hash, public_key, signature) in requests {
+        match (
+            ed25519_dalek::Signature::from_bytes(&signature.0),
+            ed25519_dalek::PublicKey::from_bytes(&public_key.0),
+        ) {
+            (Ok(sig), Ok(pk)) => {
+                messages.push(signed_hash.0.as_slice());
+                signatures.push(sig);
+                public_keys.push(pk);
+            }
+            _ => return false,
+        }
+    }
+
+    ed25519_dalek::verify_batch(&messages, &signatures, &public_keys).is_ok()
+}
@@ -138,6 +138,109 @@ impl NotarizedTransactionValidator {
         Self { config }
     }
 
+    /// Prepares and validates many notarized transactions at once, returning the same results as
+    /// `validate_from_payload_bytes` on each of them.
+    ///
+    /// With the `parallel_validation` feature, the transactions are prepared (decoded and hashed)
+    /// in parallel.
+    pub fn validate_batch_from_payload_bytes<T: AsRef<[u8]> + Sync>(
+        &self,
+        payloads: &[T],
+    ) -> Vec<Result<ValidatedNotarizedTransactionV1, TransactionValidationError>> {
+        #[cfg(feature = "parallel_validation")]
+        let prepared: Vec<_> = {
+            use rayon::prelude::*;
+            payloads
+                .par_iter()
+                .map(|payload| self.prepare_from_payload_bytes(payload.as_ref()))
+                .collect()
+        };
+        #[cfg(not(feature = "parallel_validation"))]
+        let prepared: Vec<_> = payloads
+            .iter()
+            .map(|payload| self.prepare_from_payload_bytes(payload.as_ref()))
+            .collect();
+
+        let mut transactions = Vec::new();
+        let prepare_errors: Vec<Option<TransactionValidationError>> = prepared
+            .into_iter()
+            .map(|result| match result {
+                Ok(transaction) => {
+                    transactions.push(transaction);
+                    None
+                }
+                Err(error) => Some(error),
+            })
+            .collect();
+        let mut validated = self.validate_batch(transactions).into_iter();
+
+        prepare_errors
+            .into_iter()
+            .map(|error| match error {
+                Some(error) => Err(error),
+                None => validated
+                    .next()
+                    .expect("There is a result for every prepared transaction"),
+            })
+            .collect()
+    }
+
+    /// Validates many prepared notarized transactions at once, returning the same results as
+    /// `validate` on each of them.
+    ///
+    /// The signatures of all transactions are recovered and verified together, see
+    /// `validate_signatures_batch_v1`.
+    pub fn validate_batch(
+        &self,
+        transactions: Vec<PreparedNotarizedTransactionV1>,
+    ) -> Vec<Result<ValidatedNotarizedTransactionV1, TransactionValidationError>> {
+        let checked: Vec<Result<_, TransactionValidationError>> = transactions
+            .into_iter()
+            .map(|transaction| {
+                self.validate_intent_v1(&transaction.signed_intent.intent)?;
+                let encoded_instructions =
+                    manifest_encode(&transaction.signed_intent.intent.instructions.inner.0)?;
+                Ok((transaction, encoded_instructions))
+            })
+            .collect();
+
+        let mut signature_results = self
+            .validate_signatures_batch_v1(
+                &checked
+                    .iter()
+                    .filter_map(|result| result.as_ref().ok())
+                    .map(|(transaction, _)| transaction)
+                    .collect::<Vec<_>>(),
+            )
+            .into_iter();
+
+        checked
+            .into_iter()
+            .map(|result| {
+                let (transaction, encoded_instructions) = result?;
+                let signer_keys = signature_results
+                    .next()
+                    .expect("There is a result for every checked transaction")
+                    .map_err(TransactionValidationError::SignatureValidationError)?;
+
+                let num_of_signature_validations = transaction
+                    .signed_intent
+                    .intent_signatures
+                    .inner
+                    .signatures
+                    .len()
+                    + 1;
+
+                Ok(ValidatedNotarizedTransactionV1 {
+                    prepared: transaction,
+                    encoded_instructions,
+                    signer_keys,
+                    num_of_signature_validations,
+                })
+            })
+            .collect()
+    }
+
     pub fn validate_preview_intent_v1(
         &self,
         preview_intent: PreviewIntentV1,
@@ -176,105 +279,114 @@ impl NotarizedTransactionValidator {
         // semantic analysis
         let mut id_validator = ManifestValidator::new();
         for inst in instructions {
-            match inst {
-                InstructionV1::TakeAllFromWorktop { .. } => {
-                    let _ = id_validator.new_bucket();
-                }
-                InstructionV1::TakeFromWorktop { .. } => {
-                    let _ = id_validator.new_bucket();
-                }
-                InstructionV1::TakeNonFungiblesFromWorktop { .. } => {
-                    let _ = id_validator.new_bucket();
-                }
-                InstructionV1::ReturnToWorktop { bucket_id } => {
-                    id_validator
-                        .drop_bucket(&bucket_id)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::AssertWorktopContainsAny { .. } => {}
-                InstructionV1::AssertWorktopContains { .. } => {}
-                InstructionV1::AssertWorktopContainsNonFungibles { .. } => {}
-                InstructionV1::PopFromAuthZone => {
-                    let _ = id_validator
-                        .new_proof(ProofKind::AuthZoneProof)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::PushToAuthZone { proof_id } => {
-                    id_validator
-                        .drop_proof(&proof_id)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::DropAuthZoneProofs => {}
-                InstructionV1::DropAuthZoneRegularProofs => {}
-                InstructionV1::DropAuthZoneSignatureProofs => {}
-                InstructionV1::CreateProofFromAuthZoneOfAmount { .. } => {
-                    let _ = id_validator
-                        .new_proof(ProofKind::AuthZoneProof)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::CreateProofFromAuthZoneOfNonFungibles { .. } => {
-                    let _ = id_validator
-                        .new_proof(ProofKind::AuthZoneProof)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::CreateProofFromAuthZoneOfAll { .. } => {
-                    let _ = id_validator
-                        .new_proof(ProofKind::AuthZoneProof)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::CreateProofFromBucketOfAmount { bucket_id, .. } => {
-                    let _ = id_validator
-                        .new_proof(ProofKind::BucketProof(bucket_id.clone()))
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::CreateProofFromBucketOfNonFungibles { bucket_id, .. } => {
-                    let _ = id_validator
-                        .new_proof(ProofKind::BucketProof(bucket_id.clone()))
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::CreateProofFromBucketOfAll { bucket_id, .. } => {
-                    let _ = id_validator
-                        .new_proof(ProofKind::BucketProof(bucket_id.clone()))
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::CloneProof { proof_id } => {
-                    let _ = id_validator
-                        .clone_proof(&proof_id)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::DropProof { proof_id } => {
-                    id_validator
-                        .drop_proof(&proof_id)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::DropNamedProofs => {
-                    id_validator
-                        .drop_all_named_proofs()
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::DropAllProofs => {
-                    id_validator
-                        .drop_all_named_proofs()
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::CallFunction { args, .. }
-                | InstructionV1::CallMethod { args, .. }
-                | InstructionV1::CallRoyaltyMethod { args, .. }
-                | InstructionV1::CallMetadataMethod { args, .. }
-                | InstructionV1::CallRoleAssignmentMethod { args, .. }
-                | InstructionV1::CallDirectVaultMethod { args, .. } => {
-                    Self::validate_call_args(&args, &mut id_validator)
-                        .map_err(TransactionValidationError::CallDataValidationError)?;
-                }
-                InstructionV1::BurnResource { bucket_id } => {
-                    id_validator
-                        .drop_bucket(&bucket_id)
-                        .map_err(TransactionValidationError::IdValidationError)?;
-                }
-                InstructionV1::AllocateGlobalAddress { .. } => {
-                    let _ = id_validator.new_address_reservation();
-                    id_validator.new_named_address();
-                }
+            Self::validate_instruction_v1(inst, &mut id_validator)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_instruction_v1(
+        inst: &InstructionV1,
+        id_validator: &mut ManifestValidator,
+    ) -> Result<(), TransactionValidationError> {
+        match inst {
+            InstructionV1::TakeAllFromWorktop { .. } => {
+                let _ = id_validator.new_bucket();
+            }
+            InstructionV1::TakeFromWorktop { .. } => {
+                let _ = id_validator.new_bucket();
+            }
+            InstructionV1::TakeNonFungiblesFromWorktop { .. } => {
+                let _ = id_validator.new_bucket();
+            }
+            InstructionV1::ReturnToWorktop { bucket_id } => {
+                id_validator
+                    .drop_bucket(&bucket_id)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::AssertWorktopContainsAny { .. } => {}
+            InstructionV1::AssertWorktopContains { .. } => {}
+            InstructionV1::AssertWorktopContainsNonFungibles { .. } => {}
+            InstructionV1::PopFromAuthZone => {
+                let _ = id_validator
+                    .new_proof(ProofKind::AuthZoneProof)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::PushToAuthZone { proof_id } => {
+                id_validator
+                    .drop_proof(&proof_id)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::DropAuthZoneProofs => {}
+            InstructionV1::DropAuthZoneRegularProofs => {}
+            InstructionV1::DropAuthZoneSignatureProofs => {}
+            InstructionV1::CreateProofFromAuthZoneOfAmount { .. } => {
+                let _ = id_validator
+                    .new_proof(ProofKind::AuthZoneProof)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::CreateProofFromAuthZoneOfNonFungibles { .. } => {
+                let _ = id_validator
+                    .new_proof(ProofKind::AuthZoneProof)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::CreateProofFromAuthZoneOfAll { .. } => {
+                let _ = id_validator
+                    .new_proof(ProofKind::AuthZoneProof)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::CreateProofFromBucketOfAmount { bucket_id, .. } => {
+                let _ = id_validator
+                    .new_proof(ProofKind::BucketProof(bucket_id.clone()))
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::CreateProofFromBucketOfNonFungibles { bucket_id, .. } => {
+                let _ = id_validator
+                    .new_proof(ProofKind::BucketProof(bucket_id.clone()))
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::CreateProofFromBucketOfAll { bucket_id, .. } => {
+                let _ = id_validator
+                    .new_proof(ProofKind::BucketProof(bucket_id.clone()))
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::CloneProof { proof_id } => {
+                let _ = id_validator
+                    .clone_proof(&proof_id)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::DropProof { proof_id } => {
+                id_validator
+                    .drop_proof(&proof_id)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::DropNamedProofs => {
+                id_validator
+                    .drop_all_named_proofs()
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::DropAllProofs => {
+                id_validator
+                    .drop_all_named_proofs()
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::CallFunction { args, .. }
+            | InstructionV1::CallMethod { args, .. }
+            | InstructionV1::CallRoyaltyMethod { args, .. }
+            | InstructionV1::CallMetadataMethod { args, .. }
+            | InstructionV1::CallRoleAssignmentMethod { args, .. }
+            | InstructionV1::CallDirectVaultMethod { args, .. } => {
+                Self::validate_call_args(&args, id_validator)
+                    .map_err(TransactionValidationError::CallDataValidationError)?;
+            }
+            InstructionV1::BurnResource { bucket_id } => {
+                id_validator
+                    .drop_bucket(&bucket_id)
+                    .map_err(TransactionValidationError::IdValidationError)?;
+            }
+            InstructionV1::AllocateGlobalAddress { .. } => {
+                let _ = id_validator.new_address_reservation();
+                id_validator.new_named_address();
             }
         }
 
@@ -363,6 +475,141 @@ impl NotarizedTransactionValidator {
         Ok(signers.into_iter().collect())
     }
 
+    /// Validates the signatures of many transactions at once, returning the same results as
+    /// `validate_signatures_v1` on each of them.
+    ///
+    /// All intent signers are recovered with a single `recover_batch`, and all intent and notary
+    /// signatures are verified with a single `verify_batch`.
+    pub fn validate_signatures_batch_v1(
+        &self,
+        transactions: &[&PreparedNotarizedTransactionV1],
+    ) -> Vec<Result<Vec<PublicKey>, SignatureValidationError>> {
+        // TODO: split into static validation part and runtime validation part to support more signatures
+        let has_valid_signature_count = |transaction: &PreparedNotarizedTransactionV1| {
+            transaction
+                .signed_intent
+                .intent_signatures
+                .inner
+                .signatures
+                .len()
+                <= MAX_NUMBER_OF_INTENT_SIGNATURES
+        };
+        let transactions_to_verify = || {
+            transactions
+                .iter()
+                .copied()
+                .filter(|transaction| has_valid_signature_count(*transaction))
+        };
+
+        // recover intent signers
+        let recover_requests: Vec<(Hash, &SignatureWithPublicKeyV1)> = transactions_to_verify()
+            .flat_map(|transaction| {
+                let intent_hash = transaction.intent_hash().into_hash();
+                transaction
+                    .signed_intent
+                    .intent_signatures
+                    .inner
+                    .signatures
+                    .iter()
+                    .map(move |intent_signature| (intent_hash, &intent_signature.0))
+            })
+            .collect();
+        let recovered = recover_batch(&recover_requests);
+
+        // verify intent signatures (of the recovered signers) and notary signatures
+        let mut verify_requests = Vec::new();
+        let mut recovered_signers = recovered.iter();
+        for transaction in transactions_to_verify() {
+            let intent_hash = transaction.intent_hash().into_hash();
+            for intent_signature in &transaction.signed_intent.intent_signatures.inner.signatures {
+                if let Some(public_key) = recovered_signers
+                    .next()
+                    .expect("There is a result for every intent signature")
+                {
+                    verify_requests.push((
+                        intent_hash,
+                        *public_key,
+                        intent_signature.0.signature(),
+                    ));
+                }
+            }
+            verify_requests.push((
+                transaction.signed_intent_hash().into_hash(),
+                transaction
+                    .signed_intent
+                    .intent
+                    .header
+                    .inner
+                    .notary_public_key,
+                transaction.notary_signature.inner.0.clone(),
+            ));
+        }
+        let verified = verify_batch(&verify_requests);
+
+        // check the results of every transaction in the same order as `validate_signatures_v1`
+        let mut recovered_signers = recovered.into_iter();
+        let mut verified_signatures = verified.into_iter();
+        transactions
+            .iter()
+            .map(|transaction| {
+                if !has_valid_signature_count(*transaction) {
+                    return Err(SignatureValidationError::TooManySignatures);
+                }
+
+                let intent_signers: Vec<Option<PublicKey>> = recovered_signers
+                    .by_ref()
+                    .take(
+                        transaction
+                            .signed_intent
+                            .intent_signatures
+                            .inner
+                            .signatures
+                            .len(),
+                    )
+                    .collect();
+                let intent_signatures_valid: Vec<bool> = verified_signatures
+                    .by_ref()
+                    .take(intent_signers.iter().flatten().count())
+                    .collect();
+                let notary_signature_valid = verified_signatures
+                    .next()
+                    .expect("There is a result for every notary signature");
+
+                // verify intent signature
+                let mut signers = index_set_new();
+                let mut intent_signatures_valid = intent_signatures_valid.into_iter();
+                for public_key in intent_signers {
+                    let public_key =
+                        public_key.ok_or(SignatureValidationError::InvalidIntentSignature)?;
+
+                    if !intent_signatures_valid
+                        .next()
+                        .expect("There is a result for every recovered signer")
+                    {
+                        return Err(SignatureValidationError::InvalidIntentSignature);
+                    }
+
+                    if !signers.insert(public_key) {
+                        return Err(SignatureValidationError::DuplicateSigner);
+                    }
+                }
+
+                let header = &transaction.signed_intent.intent.header.inner;
+
+                if header.notary_is_signatory {
+                    signers.insert(header.notary_public_key);
+                }
+
+                // verify notary signature
+                if !notary_signature_valid {
+                    return Err(SignatureValidationError::InvalidNotarySignature);
+                }
+
+                Ok(signers.into_iter().collect())
+            })
+            .collect()
+    }
+
     pub fn validate_call_args(
         value: &ManifestValue,
         id_validator: &mut ManifestValidator,
@@ -441,7 +688,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        builder::ManifestBuilder, builder::TransactionBuilder,
+        builder::ManifestBuilder, builder::TransactionBuilder, signing::ed25519::Ed25519PrivateKey,
         signing::secp256k1::Secp256k1PrivateKey,
     };
 
@@ -504,6 +751,144 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_batch_validation_matches_single_validation() {
+        let valid = create_transaction(Epoch::zero(), Epoch::of(100), 5, vec![1, 2], 3);
+        let mut invalid_notary_signature =
+            create_transaction(Epoch::zero(), Epoch::of(100), 5, vec![1], 3);
+        invalid_notary_signature.notary_signature =
+            create_transaction(Epoch::zero(), Epoch::of(100), 6, vec![1], 3).notary_signature;
+        let create_ed25519_signed_transaction = |nonce: u32| {
+            let sk_notary = Ed25519PrivateKey::from_u64(3).unwrap();
+            TransactionBuilder::new()
+                .header(TransactionHeaderV1 {
+                    network_id: NetworkDefinition::simulator().id,
+                    start_epoch_inclusive: Epoch::zero(),
+                    end_epoch_exclusive: Epoch::of(100),
+                    nonce,
+                    notary_public_key: sk_notary.public_key().into(),
+                    notary_is_signatory: true,
+                    tip_percentage: 5,
+                })
+                .manifest(ManifestBuilder::new().drop_auth_zone_proofs().build())
+                .sign(&Ed25519PrivateKey::from_u64(1).unwrap())
+                .sign(&Secp256k1PrivateKey::from_u64(2).unwrap())
+                .notarize(&sk_notary)
+                .build()
+        };
+        let ed25519_signed = create_ed25519_signed_transaction(5);
+        // The Ed25519 intent signature is over another intent
+        let mut invalid_intent_signature = create_ed25519_signed_transaction(5);
+        invalid_intent_signature.signed_intent.intent_signatures =
+            create_ed25519_signed_transaction(6)
+                .signed_intent
+                .intent_signatures;
+        let transactions = vec![
+            valid,
+            create_transaction(Epoch::zero(), Epoch::zero(), 5, vec![1], 2),
+            create_transaction(Epoch::zero(), Epoch::of(100), 5, (1..20).collect(), 2),
+            create_transaction(Epoch::zero(), Epoch::of(100), 5, vec![1, 1], 2),
+            invalid_notary_signature,
+            invalid_intent_signature,
+            ed25519_signed,
+        ];
+
+        let validator = NotarizedTransactionValidator::new(ValidationConfig::simulator());
+        let single_results: Vec<_> = transactions
+            .iter()
+            .map(|transaction| validator.validate(transaction.prepare().unwrap()))
+            .collect();
+        let batch_results = validator.validate_batch(
+            transactions
+                .iter()
+                .map(|transaction| transaction.prepare().unwrap())
+                .collect(),
+        );
+        assert_eq!(batch_results, single_results);
+        assert!(single_results[0].is_ok());
+        assert!(single_results[6].is_ok());
+        assert_eq!(
+            single_results[4],
+            Err(TransactionValidationError::SignatureValidationError(
+                SignatureValidationError::InvalidNotarySignature
+            ))
+        );
+        assert_eq!(
+            single_results[5],
+            Err(TransactionValidationError::SignatureValidationError(
+                SignatureValidationError::InvalidIntentSignature
+            ))
+        );
+
+        let mut payloads: Vec<Vec<u8>> = transactions
+            .iter()
+            .map(|transaction| transaction.to_payload_bytes().unwrap())
+            .collect();
+        payloads.insert(1, vec![0u8; 10]);
+        let payload_results = validator.validate_batch_from_payload_bytes(&payloads);
+        assert_eq!(
+            payload_results,
+            payloads
+                .iter()
+                .map(|payload| validator.validate_from_payload_bytes(payload))
+                .collect::<Vec<_>>()
+        );
+        assert!(payload_results[1].is_err());
+    }
+
+    /// Also run with the `parallel_validation` feature, where the Ed25519 signatures are verified
+    /// in batches.
+    #[test]
+    fn test_batch_verification_matches_strict_verification() {
+        let signed_hash = hash("message");
+        let mut requests: Vec<(Hash, PublicKey, SignatureV1)> = (1..10)
+            .map(|key| {
+                let private_key = Ed25519PrivateKey::from_u64(key).unwrap();
+                (
+                    signed_hash,
+                    private_key.public_key().into(),
+                    private_key.sign(&signed_hash).into(),
+                )
+            })
+            .collect();
+        // With the identity as both `A` and `R`, and a zero `s`, the (cofactored and cofactorless)
+        // verification equation holds for any message, but the strict verification rejects the
+        // small-order points
+        let mut identity = [0u8; Ed25519PublicKey::LENGTH];
+        identity[0] = 1;
+        let mut small_order_signature = [0u8; Ed25519Signature::LENGTH];
+        small_order_signature[..32].copy_from_slice(&identity);
+        requests.insert(
+            4,
+            (
+                signed_hash,
+                Ed25519PublicKey(identity).into(),
+                Ed25519Signature(small_order_signature).into(),
+            ),
+        );
+        // A signature of another message
+        requests[7].2 = Ed25519PrivateKey::from_u64(8)
+            .unwrap()
+            .sign(&hash("another message"))
+            .into();
+
+        let results = verify_batch(&requests);
+        assert_eq!(
+            results,
+            requests
+                .iter()
+                .map(|(signed_hash, public_key, signature)| verify(
+                    signed_hash,
+                    public_key,
+                    signature
+                ))
+                .collect::<Vec<_>>()
+        );
+        assert!(!results[4]);
+        assert!(!results[7]);
+        assert_eq!(results.iter().filter(|valid| **valid).count(), 8);
+    }
+
     #[test]
     fn test_valid_preview() {
         // Build the whole transaction but only really care about the intent
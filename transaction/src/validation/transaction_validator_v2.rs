@@ -0,0 +1,635 @@
+use crate::internal_prelude::*;
+use crate::validation::*;
+
+/// Validates notarized transactions with subintents.
+///
+/// Each intent (the root transaction intent and each non-root subintent) is validated as
+/// per the V1 rules for headers, messages and instructions, and then the intent tree is
+/// checked: every subintent must be the child of exactly one intent, and must be reachable
+/// from the root within `MAX_SUBINTENT_DEPTH`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NotarizedTransactionValidatorV2 {
+    config: ValidationConfig,
+}
+
+impl TransactionValidator<PreparedNotarizedTransactionV2> for NotarizedTransactionValidatorV2 {
+    type Validated = ValidatedNotarizedTransactionV2;
+
+    fn max_payload_length(&self) -> usize {
+        self.config.max_notarized_payload_size
+    }
+
+    fn validate(
+        &self,
+        transaction: PreparedNotarizedTransactionV2,
+    ) -> Result<Self::Validated, TransactionValidationError> {
+        let transaction_intent = &transaction.signed_transaction_intent.transaction_intent;
+
+        self.validate_transaction_header_v2(&transaction_intent.transaction_header.inner)
+            .map_err(TransactionValidationError::HeaderValidationError)?;
+
+        // Validate each intent in isolation
+        let mut epoch_range =
+            self.validate_intent_core_v2(&transaction_intent.root_intent_core, None)?;
+        for subintent in &transaction_intent.non_root_subintents.subintents {
+            let subintent_epoch_range =
+                self.validate_intent_core_v2(&subintent.intent_core, Some(subintent))?;
+            epoch_range = EpochRange {
+                start_epoch_inclusive: epoch_range
+                    .start_epoch_inclusive
+                    .max(subintent_epoch_range.start_epoch_inclusive),
+                end_epoch_exclusive: epoch_range
+                    .end_epoch_exclusive
+                    .min(subintent_epoch_range.end_epoch_exclusive),
+            };
+        }
+        if epoch_range.end_epoch_exclusive <= epoch_range.start_epoch_inclusive {
+            return Err(SubintentStructureError::NoValidEpochRangeAcrossAllIntents.into());
+        }
+
+        // Validate the intent tree
+        let (root_children, subintent_children) =
+            Self::validate_intent_tree_v2(transaction_intent)?;
+
+        let subintents_count = transaction_intent.non_root_subintents.subintents.len();
+        let signature_groups_count = transaction
+            .signed_transaction_intent
+            .non_root_subintent_signatures
+            .inner
+            .by_subintent
+            .len();
+        if subintents_count != signature_groups_count {
+            return Err(
+                SubintentStructureError::MismatchingSubintentSignatureCount {
+                    subintents: subintents_count,
+                    signature_groups: signature_groups_count,
+                }
+                .into(),
+            );
+        }
+
+        let (signer_keys, subintent_signer_keys) = self
+            .validate_signatures_v2(&transaction)
+            .map_err(TransactionValidationError::SignatureValidationError)?;
+
+        let num_of_signature_validations = signer_keys.len()
+            + subintent_signer_keys
+                .iter()
+                .map(|keys| keys.len())
+                .sum::<usize>()
+            + 1;
+
+        let encoded_instructions =
+            manifest_encode(&transaction_intent.root_intent_core.instructions.inner.0)?;
+        let mut subintents = Vec::with_capacity(subintent_children.len());
+        for ((subintent, children), signer_keys) in transaction_intent
+            .non_root_subintents
+            .subintents
+            .iter()
+            .zip(subintent_children.into_iter())
+            .zip(subintent_signer_keys.into_iter())
+        {
+            subintents.push(ValidatedSubintentV2 {
+                encoded_instructions: manifest_encode(&subintent.intent_core.instructions.inner.0)?,
+                signer_keys,
+                children,
+            });
+        }
+
+        Ok(ValidatedNotarizedTransactionV2 {
+            prepared: transaction,
+            encoded_instructions,
+            signer_keys,
+            root_children,
+            subintents,
+            epoch_range,
+            num_of_signature_validations,
+        })
+    }
+}
+
+impl NotarizedTransactionValidatorV2 {
+    pub fn new(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    fn v1_validator(&self) -> NotarizedTransactionValidator {
+        NotarizedTransactionValidator::new(self.config)
+    }
+
+    pub fn validate_transaction_header_v2(
+        &self,
+        header: &TransactionHeaderV2,
+    ) -> Result<(), HeaderValidationError> {
+        // tip percentage
+        if header.tip_percentage < self.config.min_tip_percentage
+            || header.tip_percentage > self.config.max_tip_percentage
+        {
+            return Err(HeaderValidationError::InvalidTipPercentage);
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_intent_header_v2(
+        &self,
+        header: &IntentHeaderV2,
+    ) -> Result<EpochRange, HeaderValidationError> {
+        // network
+        if header.network_id != self.config.network_id {
+            return Err(HeaderValidationError::InvalidNetwork);
+        }
+
+        // epoch
+        if header.end_epoch_exclusive <= header.start_epoch_inclusive {
+            return Err(HeaderValidationError::InvalidEpochRange);
+        }
+        let max_end_epoch = header
+            .start_epoch_inclusive
+            .after(self.config.max_epoch_range)
+            .ok_or(HeaderValidationError::InvalidEpochRange)?;
+        if header.end_epoch_exclusive > max_end_epoch {
+            return Err(HeaderValidationError::InvalidEpochRange);
+        }
+
+        Ok(EpochRange {
+            start_epoch_inclusive: header.start_epoch_inclusive,
+            end_epoch_exclusive: header.end_epoch_exclusive,
+        })
+    }
+
+    /// Validates an intent core, where `subintent` is `None` for the root intent.
+    /// Returns the epoch range of the intent.
+    pub fn validate_intent_core_v2(
+        &self,
+        intent_core: &PreparedIntentCoreV2,
+        subintent: Option<&PreparedSubintentV2>,
+    ) -> Result<EpochRange, TransactionValidationError> {
+        let epoch_range = self
+            .validate_intent_header_v2(&intent_core.header.inner)
+            .map_err(TransactionValidationError::HeaderValidationError)?;
+
+        self.v1_validator()
+            .validate_message_v1(&intent_core.message.inner)?;
+
+        let instructions = &intent_core.instructions.inner.0;
+        Self::validate_instructions_v2(
+            instructions,
+            intent_core.children.inner.children.len(),
+            subintent.is_none(),
+        )?;
+
+        if let Some(subintent) = subintent {
+            match instructions.last() {
+                Some(InstructionV2::YieldToParent { .. }) => {}
+                _ => {
+                    return Err(
+                        SubintentStructureError::SubintentDoesNotEndWithYieldToParent(
+                            subintent.subintent_hash(),
+                        )
+                        .into(),
+                    )
+                }
+            }
+        }
+
+        Ok(epoch_range)
+    }
+
+    pub fn validate_instructions_v2(
+        instructions: &[InstructionV2],
+        number_of_children: usize,
+        is_root: bool,
+    ) -> Result<(), TransactionValidationError> {
+        // semantic analysis
+        let mut id_validator = ManifestValidator::new();
+        for inst in instructions {
+            match inst {
+                InstructionV2::V1(inst) => {
+                    NotarizedTransactionValidator::validate_instruction_v1(
+                        inst,
+                        &mut id_validator,
+                    )?;
+                }
+                InstructionV2::YieldToChild { child_index, args } => {
+                    if *child_index as usize >= number_of_children {
+                        return Err(SubintentStructureError::YieldToChildIndexOutOfBounds {
+                            child_index: *child_index,
+                            children: number_of_children,
+                        }
+                        .into());
+                    }
+                    NotarizedTransactionValidator::validate_call_args(args, &mut id_validator)
+                        .map_err(TransactionValidationError::CallDataValidationError)?;
+                }
+                InstructionV2::YieldToParent { args } => {
+                    if is_root {
+                        return Err(SubintentStructureError::YieldToParentInRootIntent.into());
+                    }
+                    NotarizedTransactionValidator::validate_call_args(args, &mut id_validator)
+                        .map_err(TransactionValidationError::CallDataValidationError)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the subintents form a tree under the root intent, and resolves each
+    /// intent's children to indices into the non-root subintents.
+    ///
+    /// Returns the children of the root intent, and the children of each subintent.
+    pub fn validate_intent_tree_v2(
+        transaction_intent: &PreparedTransactionIntentV2,
+    ) -> Result<(Vec<u32>, Vec<Vec<u32>>), SubintentStructureError> {
+        let subintents = &transaction_intent.non_root_subintents.subintents;
+
+        let mut index_by_hash = index_map_with_capacity(subintents.len());
+        for (index, subintent) in subintents.iter().enumerate() {
+            let hash = subintent.subintent_hash();
+            if index_by_hash.insert(hash, index as u32).is_some() {
+                return Err(SubintentStructureError::DuplicateSubintent(hash));
+            }
+        }
+
+        let resolve_children = |intent_core: &PreparedIntentCoreV2,
+                                has_parent: &mut Vec<bool>|
+         -> Result<Vec<u32>, SubintentStructureError> {
+            let mut children = Vec::new();
+            for child in &intent_core.children.inner.children {
+                let index = *index_by_hash.get(&child.hash).ok_or(
+                    SubintentStructureError::ChildSubintentNotIncludedInTransaction(child.hash),
+                )?;
+                if has_parent[index as usize] {
+                    return Err(SubintentStructureError::SubintentHasMultipleParents(
+                        child.hash,
+                    ));
+                }
+                has_parent[index as usize] = true;
+                children.push(index);
+            }
+            Ok(children)
+        };
+
+        let mut has_parent = vec![false; subintents.len()];
+        let root_children =
+            resolve_children(&transaction_intent.root_intent_core, &mut has_parent)?;
+        let mut subintent_children = Vec::with_capacity(subintents.len());
+        for subintent in subintents {
+            subintent_children.push(resolve_children(&subintent.intent_core, &mut has_parent)?);
+        }
+
+        // As each subintent has at most one parent, walking down from the root visits each
+        // subintent at most once. Any subintent not visited is either orphaned or in a cycle.
+        let mut depth = vec![None; subintents.len()];
+        let mut current_layer = root_children.clone();
+        let mut current_depth = 1usize;
+        while !current_layer.is_empty() {
+            let mut next_layer = Vec::new();
+            for index in current_layer {
+                if current_depth > MAX_SUBINTENT_DEPTH {
+                    return Err(SubintentStructureError::SubintentExceedsMaxDepth(
+                        subintents[index as usize].subintent_hash(),
+                    ));
+                }
+                depth[index as usize] = Some(current_depth);
+                next_layer.extend(subintent_children[index as usize].iter().cloned());
+            }
+            current_layer = next_layer;
+            current_depth += 1;
+        }
+        for (index, depth) in depth.into_iter().enumerate() {
+            if depth.is_none() {
+                return Err(SubintentStructureError::SubintentIsNotReachableFromRoot(
+                    subintents[index].subintent_hash(),
+                ));
+            }
+        }
+
+        Ok((root_children, subintent_children))
+    }
+
+    /// Returns the signer keys of the root intent, and of each non-root subintent.
+    /// The number of signature groups is assumed to already match the number of subintents.
+    pub fn validate_signatures_v2(
+        &self,
+        transaction: &PreparedNotarizedTransactionV2,
+    ) -> Result<(Vec<PublicKey>, Vec<Vec<PublicKey>>), SignatureValidationError> {
+        let signed_intent = &transaction.signed_transaction_intent;
+        let transaction_intent = &signed_intent.transaction_intent;
+
+        // verify root intent signatures
+        let mut signers = Self::validate_intent_signatures_v2(
+            &transaction_intent.intent_hash().into_hash(),
+            &signed_intent.transaction_intent_signatures.inner,
+        )?;
+
+        // verify subintent signatures
+        let subintents = &transaction_intent.non_root_subintents.subintents;
+        let signature_groups = &signed_intent
+            .non_root_subintent_signatures
+            .inner
+            .by_subintent;
+        let mut subintent_signers = Vec::with_capacity(subintents.len());
+        for (subintent, signatures) in subintents.iter().zip(signature_groups.iter()) {
+            let signers = Self::validate_intent_signatures_v2(
+                &subintent.subintent_hash().into_hash(),
+                signatures,
+            )?;
+            subintent_signers.push(signers.into_iter().collect());
+        }
+
+        let header = &transaction_intent.transaction_header.inner;
+
+        if header.notary_is_signatory {
+            signers.insert(header.notary_public_key);
+        }
+
+        // verify notary signature
+        let signed_intent_hash = transaction.signed_intent_hash().into_hash();
+        if !verify(
+            &signed_intent_hash,
+            &header.notary_public_key,
+            &transaction.notary_signature.inner.0,
+        ) {
+            return Err(SignatureValidationError::InvalidNotarySignature);
+        }
+
+        Ok((signers.into_iter().collect(), subintent_signers))
+    }
+
+    fn validate_intent_signatures_v2(
+        hash: &Hash,
+        signatures: &IntentSignaturesV1,
+    ) -> Result<IndexSet<PublicKey>, SignatureValidationError> {
+        if signatures.signatures.len() > MAX_NUMBER_OF_INTENT_SIGNATURES {
+            return Err(SignatureValidationError::TooManySignatures);
+        }
+
+        let mut signers = index_set_new();
+        for intent_signature in &signatures.signatures {
+            let public_key = recover(hash, &intent_signature.0)
+                .ok_or(SignatureValidationError::InvalidIntentSignature)?;
+
+            if !verify(hash, &public_key, &intent_signature.0.signature()) {
+                return Err(SignatureValidationError::InvalidIntentSignature);
+            }
+
+            if !signers.insert(public_key) {
+                return Err(SignatureValidationError::DuplicateSigner);
+            }
+        }
+
+        Ok(signers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use radix_engine_interface::network::NetworkDefinition;
+
+    use super::*;
+    use crate::signing::secp256k1::Secp256k1PrivateKey;
+
+    fn create_intent_core(
+        children: Vec<SubintentHash>,
+        instructions: Vec<InstructionV2>,
+    ) -> IntentCoreV2 {
+        IntentCoreV2 {
+            header: IntentHeaderV2 {
+                network_id: NetworkDefinition::simulator().id,
+                start_epoch_inclusive: Epoch::zero(),
+                end_epoch_exclusive: Epoch::of(100),
+                intent_discriminator: 0,
+            },
+            blobs: BlobsV1 { blobs: vec![] },
+            message: MessageV1::None,
+            children: ChildIntentsV2 {
+                children: children
+                    .into_iter()
+                    .map(|hash| ChildSubintent { hash })
+                    .collect(),
+            },
+            instructions: InstructionsV2(instructions),
+        }
+    }
+
+    fn create_subintent(
+        discriminator: u64,
+        children: Vec<SubintentHash>,
+        instructions: Vec<InstructionV2>,
+    ) -> (SubintentV2, SubintentHash) {
+        let mut intent_core = create_intent_core(children, instructions);
+        intent_core.header.intent_discriminator = discriminator;
+        let subintent = SubintentV2 { intent_core };
+        let hash = subintent.prepare().unwrap().subintent_hash();
+        (subintent, hash)
+    }
+
+    fn yield_to_parent() -> InstructionV2 {
+        InstructionV2::YieldToParent {
+            args: manifest_args!().into(),
+        }
+    }
+
+    fn yield_to_child(child_index: u32) -> InstructionV2 {
+        InstructionV2::YieldToChild {
+            child_index,
+            args: manifest_args!().into(),
+        }
+    }
+
+    fn create_transaction(
+        root_intent_core: IntentCoreV2,
+        subintents: Vec<SubintentV2>,
+    ) -> NotarizedTransactionV2 {
+        let sk_notary = Secp256k1PrivateKey::from_u64(1).unwrap();
+        let sk_signer = Secp256k1PrivateKey::from_u64(2).unwrap();
+
+        let non_root_subintent_signatures = NonRootSubintentSignaturesV2 {
+            by_subintent: subintents
+                .iter()
+                .map(|subintent| {
+                    let hash = subintent.prepare().unwrap().subintent_hash();
+                    IntentSignaturesV1 {
+                        signatures: vec![IntentSignatureV1(sk_signer.sign_with_public_key(&hash))],
+                    }
+                })
+                .collect(),
+        };
+        let transaction_intent = TransactionIntentV2 {
+            transaction_header: TransactionHeaderV2 {
+                notary_public_key: sk_notary.public_key().into(),
+                notary_is_signatory: false,
+                tip_percentage: 5,
+            },
+            root_intent_core,
+            non_root_subintents: NonRootSubintentsV2(subintents),
+        };
+        let intent_hash = transaction_intent.prepare().unwrap().intent_hash();
+        let signed_transaction_intent = SignedTransactionIntentV2 {
+            transaction_intent,
+            transaction_intent_signatures: IntentSignaturesV1 {
+                signatures: vec![IntentSignatureV1(
+                    sk_signer.sign_with_public_key(&intent_hash),
+                )],
+            },
+            non_root_subintent_signatures,
+        };
+        let signed_intent_hash = signed_transaction_intent
+            .prepare()
+            .unwrap()
+            .signed_intent_hash();
+        NotarizedTransactionV2 {
+            signed_transaction_intent,
+            notary_signature: NotarySignatureV1(
+                sk_notary.sign_without_public_key(&signed_intent_hash),
+            ),
+        }
+    }
+
+    fn validate(
+        transaction: NotarizedTransactionV2,
+    ) -> Result<ValidatedNotarizedTransactionV2, TransactionValidationError> {
+        let validator = NotarizedTransactionValidatorV2::new(ValidationConfig::simulator());
+        validator.validate(transaction.prepare().unwrap())
+    }
+
+    #[test]
+    fn test_valid_subintent_tree() {
+        let (grandchild, grandchild_hash) = create_subintent(1, vec![], vec![yield_to_parent()]);
+        let (child, child_hash) = create_subintent(
+            2,
+            vec![grandchild_hash],
+            vec![yield_to_child(0), yield_to_parent()],
+        );
+        let transaction = create_transaction(
+            create_intent_core(vec![child_hash], vec![yield_to_child(0)]),
+            vec![grandchild, child],
+        );
+
+        let validated = validate(transaction).unwrap();
+        assert_eq!(validated.root_children, vec![1]);
+        assert_eq!(validated.subintents[0].children, Vec::<u32>::new());
+        assert_eq!(validated.subintents[1].children, vec![0]);
+        assert_eq!(validated.num_of_signature_validations, 4);
+    }
+
+    #[test]
+    fn test_invalid_yields() {
+        assert_eq!(
+            validate(create_transaction(
+                create_intent_core(vec![], vec![yield_to_parent()]),
+                vec![],
+            ))
+            .expect_err("Should be an error"),
+            TransactionValidationError::SubintentStructureError(
+                SubintentStructureError::YieldToParentInRootIntent
+            )
+        );
+
+        assert_eq!(
+            validate(create_transaction(
+                create_intent_core(vec![], vec![yield_to_child(0)]),
+                vec![],
+            ))
+            .expect_err("Should be an error"),
+            TransactionValidationError::SubintentStructureError(
+                SubintentStructureError::YieldToChildIndexOutOfBounds {
+                    child_index: 0,
+                    children: 0,
+                }
+            )
+        );
+
+        let (child, child_hash) = create_subintent(1, vec![], vec![]);
+        assert_eq!(
+            validate(create_transaction(
+                create_intent_core(vec![child_hash], vec![]),
+                vec![child],
+            ))
+            .expect_err("Should be an error"),
+            TransactionValidationError::SubintentStructureError(
+                SubintentStructureError::SubintentDoesNotEndWithYieldToParent(child_hash)
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_subintent_structure() {
+        let (child, child_hash) = create_subintent(1, vec![], vec![yield_to_parent()]);
+
+        // Not a child of any intent
+        assert_eq!(
+            validate(create_transaction(
+                create_intent_core(vec![], vec![]),
+                vec![child.clone()],
+            ))
+            .expect_err("Should be an error"),
+            TransactionValidationError::SubintentStructureError(
+                SubintentStructureError::SubintentIsNotReachableFromRoot(child_hash)
+            )
+        );
+
+        // Child not included in the transaction
+        assert_eq!(
+            validate(create_transaction(
+                create_intent_core(vec![child_hash], vec![]),
+                vec![],
+            ))
+            .expect_err("Should be an error"),
+            TransactionValidationError::SubintentStructureError(
+                SubintentStructureError::ChildSubintentNotIncludedInTransaction(child_hash)
+            )
+        );
+
+        // Included twice
+        assert_eq!(
+            validate(create_transaction(
+                create_intent_core(vec![child_hash], vec![]),
+                vec![child.clone(), child.clone()],
+            ))
+            .expect_err("Should be an error"),
+            TransactionValidationError::SubintentStructureError(
+                SubintentStructureError::DuplicateSubintent(child_hash)
+            )
+        );
+
+        // Multiple parents
+        let (other_child, other_child_hash) =
+            create_subintent(2, vec![child_hash], vec![yield_to_parent()]);
+        assert_eq!(
+            validate(create_transaction(
+                create_intent_core(vec![child_hash, other_child_hash], vec![]),
+                vec![child, other_child],
+            ))
+            .expect_err("Should be an error"),
+            TransactionValidationError::SubintentStructureError(
+                SubintentStructureError::SubintentHasMultipleParents(child_hash)
+            )
+        );
+    }
+
+    #[test]
+    fn test_subintent_max_depth() {
+        let mut subintents = vec![];
+        let mut children = vec![];
+        for i in 0..=MAX_SUBINTENT_DEPTH {
+            let (subintent, hash) = create_subintent(i as u64, children, vec![yield_to_parent()]);
+            subintents.push(subintent);
+            children = vec![hash];
+        }
+        let deepest_hash = subintents[0].prepare().unwrap().subintent_hash();
+
+        assert_eq!(
+            validate(create_transaction(
+                create_intent_core(children, vec![]),
+                subintents,
+            ))
+            .expect_err("Should be an error"),
+            TransactionValidationError::SubintentStructureError(
+                SubintentStructureError::SubintentExceedsMaxDepth(deepest_hash)
+            )
+        );
+    }
+}